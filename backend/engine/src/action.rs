@@ -0,0 +1,48 @@
+use crate::card::Card;
+use crate::combo_finder::ShedPosition;
+use serde::{Deserialize, Serialize};
+
+/// A play that changes the table. Bots and the simulator pick these; on the wire they
+/// travel as the matching `ClientMessage` variants, which serialize the same way.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum Action {
+    DrawFromDeck,
+    DrawFromDiscard,
+    Discard {
+        payload: DiscardPayload,
+    },
+    /// End the turn without drawing; only allowed once the deck is empty
+    PassTurn,
+    DropHand {
+        payload: DropHandPayload,
+    },
+    ShedCard {
+        payload: ShedCardPayload,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiscardPayload {
+    pub card_index: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DropHandPayload {
+    pub combinations: Vec<Vec<Card>>,
+}
+
+/// Shed a single card from hand onto an existing table combo.
+/// The position (left/right/trio-ext) is derived server-side by `can_shed()` unless given.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShedCardPayload {
+    /// Index into the current player's hand
+    pub hand_card_index: usize,
+    /// ID of the player whose bajada we are extending
+    pub target_player_id: String,
+    /// Index into that player's `dropped_combinations`
+    pub target_combo_idx: usize,
+    /// End of the escala to extend; the server picks one when omitted
+    #[serde(default)]
+    pub position: Option<ShedPosition>,
+}
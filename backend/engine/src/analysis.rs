@@ -0,0 +1,609 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+use crate::action::{Action, DiscardPayload, DropHandPayload, ShedCardPayload};
+use crate::card::Card;
+use crate::combo_finder::{HandMask, find_best_bajada, find_sheddable_cards};
+use crate::deck::Deck;
+use crate::game::GameState;
+use crate::rule_set::RuleSet;
+use crate::sim;
+use crate::transcript::RoundTranscript;
+
+/// Share of the cards left over after a bajada that are still held when the round ends;
+/// the rest get shed or discarded in the turns that follow.
+const LEFTOVER_KEPT: f64 = 0.5;
+
+/// A legal move and the penalty points it is expected to leave its player with when the
+/// round ends. Lower is better.
+#[derive(Debug, Clone)]
+pub struct ActionScore {
+    pub action: Action,
+    pub expected_points: f64,
+}
+
+/// Scores every legal move `player_id` has right now, best first. Empty when it is not
+/// their turn. Only uses what the player can see: the deck's order stays hidden.
+pub fn evaluate_actions(state: &GameState, player_id: &str) -> Vec<ActionScore> {
+    let Some(player) = state.players.iter().find(|p| p.id == player_id) else {
+        return Vec::new();
+    };
+    let view = View::new(state, player_id);
+
+    let mut scores: Vec<ActionScore> = legal_actions(state, player_id)
+        .into_iter()
+        .map(|action| {
+            let expected_points = match &action {
+                // The card drawn is unknown: average over everything still unseen
+                Action::DrawFromDeck => {
+                    let total: usize = view.unseen.values().sum();
+                    view.unseen
+                        .iter()
+                        .map(|(card, count)| {
+                            let mut hand = player.hand.clone();
+                            hand.push(*card);
+                            view.best_finish(&hand, player.has_dropped_hand) * *count as f64
+                        })
+                        .sum::<f64>()
+                        / total.max(1) as f64
+                }
+                action => {
+                    let mut after = state.clone();
+                    match sim::apply(&mut after, player_id, action.clone()) {
+                        Ok(Some(result)) => result
+                            .final_hands
+                            .iter()
+                            .find(|h| h.player_id == player_id)
+                            .map_or(0.0, |h| view.points(&h.cards)),
+                        Ok(None) => {
+                            let me = after.players.iter().find(|p| p.id == player_id);
+                            match me {
+                                Some(me) if me.has_drawn_this_turn => {
+                                    view.best_finish(&me.hand, me.has_dropped_hand)
+                                }
+                                Some(me) => view.expected_points(&me.hand, me.has_dropped_hand),
+                                None => 0.0,
+                            }
+                        }
+                        Err(_) => f64::MAX,
+                    }
+                }
+            };
+            ActionScore {
+                action,
+                expected_points,
+            }
+        })
+        .collect();
+    scores.sort_by(|a, b| a.expected_points.total_cmp(&b.expected_points));
+    scores
+}
+
+/// The moves the engine would accept from `player_id` now. Of all possible bajadas only
+/// the solver's best is offered.
+pub fn legal_actions(state: &GameState, player_id: &str) -> Vec<Action> {
+    let Some(player) = state.players.get(state.current_turn) else {
+        return Vec::new();
+    };
+    if player.id != player_id || state.is_game_over || state.is_waiting_for_next_round {
+        return Vec::new();
+    }
+
+    let mut candidates = Vec::new();
+    if !player.has_drawn_this_turn {
+        candidates.extend([
+            Action::DrawFromDeck,
+            Action::DrawFromDiscard,
+            Action::PassTurn,
+        ]);
+    } else {
+        if !player.has_dropped_hand {
+            let (trios, escalas) = state.current_round.get_requirements();
+            let min_escala = state.rules.min_escala_len(&state.current_round);
+            if let Some(melds) =
+                find_best_bajada(&player.hand, trios, escalas, min_escala, true, &state.rules)
+            {
+                let combinations = melds
+                    .iter()
+                    .map(|m| m.card_indices.iter().map(|&i| player.hand[i]).collect())
+                    .collect();
+                candidates.push(Action::DropHand {
+                    payload: DropHandPayload { combinations },
+                });
+            }
+        } else {
+            let bajadas: Vec<(&str, &Vec<Vec<Card>>)> = state
+                .players
+                .iter()
+                .filter(|p| p.has_dropped_hand)
+                .map(|p| (p.id.as_str(), &p.dropped_combinations))
+                .collect();
+            for shed in find_sheddable_cards(
+                &player.hand,
+                &bajadas,
+                &state.rules.jokers,
+                state.rules.twos,
+            ) {
+                candidates.push(Action::ShedCard {
+                    payload: ShedCardPayload {
+                        hand_card_index: shed.hand_index,
+                        target_player_id: shed.target_player_id,
+                        target_combo_idx: shed.target_combo_idx,
+                        position: Some(shed.position),
+                    },
+                });
+            }
+        }
+        candidates.extend((0..player.hand.len()).map(|card_index| Action::Discard {
+            payload: DiscardPayload { card_index },
+        }));
+    }
+
+    // Let the engine have the last word on what is allowed
+    candidates
+        .into_iter()
+        .filter(|action| sim::apply(&mut state.clone(), player_id, action.clone()).is_ok())
+        .collect()
+}
+
+/// The best a player could have done with a turn, had they seen every hand and the deck's
+/// order.
+#[derive(Debug, Clone)]
+pub struct OracleLine {
+    /// From the draw to the move that ended the turn
+    pub moves: Vec<Action>,
+    /// Penalty points held once the turn was over; `0` for going out
+    pub points_left: u32,
+}
+
+/// Searches every line `player_id` could play this turn, seeing the whole table and the
+/// deck's order, for the one that leaves the fewest penalty points in hand when the turn
+/// ends. For analysis only: it cheats, so no bot may use it. `None` when it is not their
+/// turn or the turn has already begun.
+pub fn oracle_line(state: &GameState, player_id: &str) -> Option<OracleLine> {
+    let player = state.players.get(state.current_turn)?;
+    if player.id != player_id || player.has_drawn_this_turn {
+        return None;
+    }
+    let mut best = None;
+    let mut visited = HashSet::new();
+    search_turn(state, player_id, &mut Vec::new(), &mut visited, &mut best);
+    best
+}
+
+fn search_turn(
+    state: &GameState,
+    player_id: &str,
+    line: &mut Vec<Action>,
+    visited: &mut HashSet<String>,
+    best: &mut Option<OracleLine>,
+) {
+    for action in legal_actions(state, player_id) {
+        let mut after = state.clone();
+        let Ok(result) = sim::apply(&mut after, player_id, action.clone()) else {
+            continue;
+        };
+        line.push(action);
+
+        let points_left = match result {
+            Some(result) => Some(
+                result
+                    .final_hands
+                    .iter()
+                    .find(|h| h.player_id == player_id)
+                    .map_or(0, |h| after.rules.hand_points(&h.cards)),
+            ),
+            None if after.players[after.current_turn].id != player_id => after
+                .players
+                .iter()
+                .find(|p| p.id == player_id)
+                .map(|p| after.rules.hand_points(&p.hand)),
+            None => None,
+        };
+        match points_left {
+            Some(points_left) => {
+                if best.as_ref().is_none_or(|b| points_left < b.points_left) {
+                    *best = Some(OracleLine {
+                        moves: line.clone(),
+                        points_left,
+                    });
+                }
+            }
+            // Still their turn: carry on from positions not already explored
+            None => {
+                if visited.insert(position_key(&after, player_id)) {
+                    search_turn(&after, player_id, line, visited, best);
+                }
+            }
+        }
+        line.pop();
+    }
+}
+
+/// Identifies a mid-turn position: the player's hand as a multiset, and the table.
+fn position_key(state: &GameState, player_id: &str) -> String {
+    let player = state.players.iter().find(|p| p.id == player_id);
+    let mut hand: Vec<String> = player
+        .map(|p| p.hand.iter().map(|c| c.to_string()).collect())
+        .unwrap_or_default();
+    hand.sort_unstable();
+    let table: Vec<_> = state
+        .players
+        .iter()
+        .map(|p| &p.dropped_combinations)
+        .collect();
+    format!("{:?}|{:?}", hand, table)
+}
+
+/// How one player's turns compared with the oracle's over a game.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlayReview {
+    pub player_id: String,
+    pub turns: u32,
+    /// Turns that ended with no more points in hand than the oracle's line
+    pub optimal_turns: u32,
+    /// Points held beyond the oracle's, summed over every turn
+    pub points_off: u32,
+}
+
+impl PlayReview {
+    /// Counts a turn that ended with `actual` points in hand, against the oracle's line
+    /// from the position the turn started in.
+    pub fn record_turn(&mut self, start: &GameState, actual: u32) {
+        let Some(oracle) = oracle_line(start, &self.player_id) else {
+            return;
+        };
+        self.turns += 1;
+        let off = actual.saturating_sub(oracle.points_left);
+        if off == 0 {
+            self.optimal_turns += 1;
+        }
+        self.points_off += off;
+    }
+}
+
+/// Highlights picked for a round's summary, at most
+pub const MAX_HIGHLIGHTS: usize = 2;
+
+/// A moment of a round worth showing again on the end-of-round screen.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Highlight {
+    /// Several cards shed one after another in a single turn
+    ShedChain {
+        player_id: String,
+        cards: Vec<Card>,
+        points: u32,
+    },
+    /// A bajada that took a lot of points out of the hand
+    Bajada {
+        player_id: String,
+        combinations: Vec<Vec<Card>>,
+        points: u32,
+    },
+}
+
+impl Highlight {
+    pub fn points(&self) -> u32 {
+        match self {
+            Highlight::ShedChain { points, .. } | Highlight::Bajada { points, .. } => *points,
+        }
+    }
+}
+
+/// The round's longest shed chain and its biggest bajada, the one worth more points
+/// first. A lone shed card is no chain.
+pub fn round_highlights(round: &RoundTranscript, rules: &RuleSet) -> Vec<Highlight> {
+    let points = |cards: &[Card]| cards.iter().map(|c| rules.card_points(c)).sum::<u32>();
+
+    let mut chains: Vec<(&str, Vec<Card>)> = Vec::new();
+    let mut chaining = false;
+    for m in &round.moves {
+        if m.action != "shed" {
+            chaining = false;
+            continue;
+        }
+        let Some(card) = m.card else { continue };
+        match chains.last_mut() {
+            Some((player_id, cards)) if chaining && *player_id == m.player_id => cards.push(card),
+            _ => chains.push((&m.player_id, vec![card])),
+        }
+        chaining = true;
+    }
+    let chain = chains
+        .into_iter()
+        .filter(|(_, cards)| cards.len() > 1)
+        .max_by_key(|(_, cards)| (cards.len(), points(cards)))
+        .map(|(player_id, cards)| Highlight::ShedChain {
+            player_id: player_id.to_string(),
+            points: points(&cards),
+            cards,
+        });
+
+    let bajada = round
+        .moves
+        .iter()
+        .filter(|m| m.action == "bajó")
+        .map(|m| Highlight::Bajada {
+            player_id: m.player_id.clone(),
+            points: points(&m.combinations.concat()),
+            combinations: m.combinations.clone(),
+        })
+        .max_by_key(Highlight::points);
+
+    let mut highlights: Vec<Highlight> = chain.into_iter().chain(bajada).collect();
+    highlights.sort_by_key(|h| std::cmp::Reverse(h.points()));
+    highlights.truncate(MAX_HIGHLIGHTS);
+    highlights
+}
+
+/// What one player knows of the table.
+struct View<'a> {
+    state: &'a GameState,
+    /// Cards not in the player's hand, on the table or visible in the discard pile
+    unseen: HashMap<Card, usize>,
+    /// Draws the player can still expect this round
+    draws_left: f64,
+}
+
+impl<'a> View<'a> {
+    fn new(state: &'a GameState, player_id: &str) -> Self {
+        let mut unseen: HashMap<Card, usize> = HashMap::new();
+        for card in Deck::with_jokers(state.rules.deck_jokers).cards() {
+            *unseen.entry(*card).or_default() += 1;
+        }
+        let seen = state
+            .players
+            .iter()
+            .filter(|p| p.id == player_id)
+            .flat_map(|p| &p.hand)
+            .chain(state.visible_discards())
+            .chain(
+                state
+                    .players
+                    .iter()
+                    .flat_map(|p| p.dropped_combinations.iter().flatten()),
+            );
+        for card in seen {
+            if let Some(count) = unseen.get_mut(card) {
+                *count = count.saturating_sub(1);
+            }
+        }
+        unseen.retain(|_, count| *count > 0);
+
+        let seats = state.players.len().max(1);
+        Self {
+            state,
+            unseen,
+            draws_left: state.deck.remaining().div_ceil(seats) as f64,
+        }
+    }
+
+    fn points(&self, hand: &[Card]) -> f64 {
+        hand.iter()
+            .map(|c| self.state.rules.card_points(c))
+            .sum::<u32>() as f64
+    }
+
+    /// Points left outside the round's best bajada, if `hand` can lay one down.
+    fn leftover_after_bajada(&self, hand: &[Card]) -> Option<Vec<Card>> {
+        let (trios, escalas) = self.state.current_round.get_requirements();
+        let rules = &self.state.rules;
+        let min_escala = rules.min_escala_len(&self.state.current_round);
+        let melds = find_best_bajada(hand, trios, escalas, min_escala, true, rules)?;
+        let used: HandMask = melds.iter().fold(0, |mask, m| mask | m.mask);
+        Some(
+            hand.iter()
+                .enumerate()
+                .filter(|(i, _)| (used >> i) & 1 == 0)
+                .map(|(_, c)| *c)
+                .collect(),
+        )
+    }
+
+    /// Expected end-of-round penalty of the hand the player holds between turns. A hand
+    /// that can't drop yet drops with the chance of drawing a card that completes it in the
+    /// draws left, and otherwise is held whole.
+    fn expected_points(&self, hand: &[Card], has_dropped: bool) -> f64 {
+        if has_dropped {
+            return self.points(hand) * LEFTOVER_KEPT;
+        }
+        if let Some(leftover) = self.leftover_after_bajada(hand) {
+            return self.points(&leftover) * LEFTOVER_KEPT;
+        }
+
+        let total: usize = self.unseen.values().sum();
+        let mut completing = 0;
+        let mut completed_points = 0.0;
+        for (card, count) in &self.unseen {
+            let mut drawn = hand.to_vec();
+            drawn.push(*card);
+            if let Some(leftover) = self.leftover_after_bajada(&drawn) {
+                completing += count;
+                completed_points += self.after_discard(&leftover) * *count as f64;
+            }
+        }
+        if completing == 0 || total == 0 {
+            return self.points(hand);
+        }
+        let per_draw = completing as f64 / total as f64;
+        let chance = 1.0 - (1.0 - per_draw).powf(self.draws_left);
+        let if_completed = completed_points / completing as f64 * LEFTOVER_KEPT;
+        chance * if_completed + (1.0 - chance) * self.points(hand)
+    }
+
+    /// Points of `cards` once the costliest is discarded.
+    fn after_discard(&self, cards: &[Card]) -> f64 {
+        let costliest = cards
+            .iter()
+            .map(|c| self.state.rules.card_points(c))
+            .max()
+            .unwrap_or(0);
+        self.points(cards) - costliest as f64
+    }
+
+    /// Expected penalty of a hand that has drawn and still has to discard, finishing the
+    /// turn as well as it can: dropping if possible, then letting go of the card that hurts
+    /// least. Looks no further than this turn.
+    fn best_finish(&self, hand: &[Card], has_dropped: bool) -> f64 {
+        if !has_dropped && let Some(leftover) = self.leftover_after_bajada(hand) {
+            return self.after_discard(&leftover) * LEFTOVER_KEPT;
+        }
+        let kept = if has_dropped { LEFTOVER_KEPT } else { 1.0 };
+        // Without a bajada the cheapest hand to keep is the one missing its costliest card
+        self.after_discard(hand) * kept
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::card::{Suit, Value};
+
+    fn std(suit: Suit, value: Value) -> Card {
+        Card::Standard { suit, value }
+    }
+
+    fn game_with_hand(hand: Vec<Card>, drawn: bool) -> GameState {
+        let mut game = GameState::new(vec!["p1".to_string(), "p2".to_string()]);
+        game.start_round();
+        game.current_turn = 0;
+        game.players[0].hand = hand;
+        game.players[0].has_drawn_this_turn = drawn;
+        game.players[0].turns_played = 1;
+        game
+    }
+
+    #[test]
+    fn only_the_player_on_turn_has_moves() {
+        let game = game_with_hand(vec![std(Suit::Hearts, Value::Two); 12], false);
+        let actions = legal_actions(&game, "p1");
+        assert!(matches!(
+            actions.as_slice(),
+            [Action::DrawFromDeck, Action::DrawFromDiscard]
+        ));
+        assert!(evaluate_actions(&game, "p2").is_empty());
+    }
+
+    #[test]
+    fn dropping_beats_holding_and_junk_goes_first() {
+        let hand = vec![
+            std(Suit::Hearts, Value::Five),
+            std(Suit::Clubs, Value::Five),
+            std(Suit::Spades, Value::Five),
+            std(Suit::Hearts, Value::Nine),
+            std(Suit::Clubs, Value::Nine),
+            std(Suit::Diamonds, Value::Nine),
+            std(Suit::Spades, Value::Ace),
+            std(Suit::Hearts, Value::Two),
+        ];
+        let game = game_with_hand(hand, true);
+        let scores = evaluate_actions(&game, "p1");
+        assert_eq!(scores.len(), 9, "A bajada and eight discards");
+        assert!(matches!(scores[0].action, Action::DropHand { .. }));
+
+        // Of the discards, breaking up a trio is the worst idea
+        let discard_of = |index: usize| {
+            scores
+                .iter()
+                .find(|s| {
+                    matches!(&s.action, Action::Discard { payload } if payload.card_index == index)
+                })
+                .unwrap()
+                .expected_points
+        };
+        assert!(discard_of(6) < discard_of(0));
+    }
+
+    #[test]
+    fn the_oracle_knows_the_next_card() {
+        let hand = vec![
+            std(Suit::Hearts, Value::Five),
+            std(Suit::Clubs, Value::Five),
+            std(Suit::Hearts, Value::Nine),
+            std(Suit::Clubs, Value::Nine),
+            std(Suit::Diamonds, Value::Nine),
+            std(Suit::Spades, Value::Ace),
+        ];
+        let mut game = game_with_hand(hand, false);
+        game.discard_pile = vec![std(Suit::Diamonds, Value::King)];
+        game.deck = Deck::stacked(&[std(Suit::Spades, Value::Five)]).unwrap();
+
+        // The deck's top card completes the second trio: draw it, drop, throw the ace
+        let line = oracle_line(&game, "p1").unwrap();
+        assert!(matches!(line.moves[0], Action::DrawFromDeck));
+        assert!(matches!(line.moves[1], Action::DropHand { .. }));
+        assert_eq!(line.points_left, 0);
+
+        let mut review = PlayReview {
+            player_id: "p1".to_string(),
+            ..PlayReview::default()
+        };
+        review.record_turn(&game, 20);
+        review.record_turn(&game, 0);
+        assert_eq!(
+            (review.turns, review.optimal_turns, review.points_off),
+            (2, 1, 20)
+        );
+    }
+
+    #[test]
+    fn highlights_the_longest_shed_chain_and_biggest_bajada() {
+        use crate::transcript::Move;
+
+        let step = |player_id: &str, action: &str, card: Option<Card>| Move {
+            player_id: player_id.to_string(),
+            action: action.to_string(),
+            card,
+            combinations: Vec::new(),
+        };
+        let king = std(Suit::Spades, Value::King);
+        let four = std(Suit::Hearts, Value::Four);
+        let trio = vec![std(Suit::Hearts, Value::Nine); 3];
+        let mut bajada = step("p1", "bajó", None);
+        bajada.combinations = vec![trio.clone(), trio];
+        let round = RoundTranscript {
+            round_index: 0,
+            round_name: String::new(),
+            deck_seed: None,
+            deal: Vec::new(),
+            first_discard: None,
+            moves: vec![
+                step("p1", "drew_from_deck", None),
+                bajada,
+                step("p1", "shed", Some(four)),
+                step("p2", "drew_from_deck", None),
+                step("p2", "shed", Some(king)),
+                step("p2", "shed", Some(king)),
+                step("p2", "shed", Some(four)),
+                step("p2", "discarded", Some(four)),
+                step("p1", "shed", Some(king)),
+            ],
+            scores: Vec::new(),
+            winner_id: None,
+        };
+        let rules = RuleSet::default();
+        let highlights = round_highlights(&round, &rules);
+        assert_eq!(highlights.len(), 2);
+
+        let chain_points = 2 * rules.card_points(&king) + rules.card_points(&four);
+        let bajada_points = 6 * rules.card_points(&std(Suit::Hearts, Value::Nine));
+        assert!(highlights.contains(&Highlight::ShedChain {
+            player_id: "p2".to_string(),
+            cards: vec![king, king, four],
+            points: chain_points,
+        }));
+        assert!(highlights.iter().any(|h| matches!(h,
+            Highlight::Bajada { player_id, points, .. } if player_id == "p1" && *points == bajada_points)));
+        assert!(highlights[0].points() >= highlights[1].points());
+
+        let quiet = RoundTranscript {
+            moves: vec![step("p1", "shed", Some(king))],
+            ..round
+        };
+        assert!(
+            round_highlights(&quiet, &rules).is_empty(),
+            "One card is no chain"
+        );
+    }
+}
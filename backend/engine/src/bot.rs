@@ -0,0 +1,952 @@
+use crate::action::{Action, DiscardPayload, DropHandPayload, ShedCardPayload};
+use crate::analysis;
+use crate::card::Card;
+use crate::combo_finder::find_best_bajada;
+use crate::game::{GameState, PlayerState, RoundType};
+use rand::RngExt;
+use rand::prelude::IndexedRandom;
+use rand::rng;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BotDifficulty {
+    Easy,
+    Medium,
+    Hard,
+}
+
+impl BotDifficulty {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BotDifficulty::Easy => "easy",
+            BotDifficulty::Medium => "medium",
+            BotDifficulty::Hard => "hard",
+        }
+    }
+}
+
+// ─── Turn Phase ───────────────────────────────────────────────────────────────
+
+/// Explicit state machine for a bot's turn.
+/// Replaces hand.len() branching which breaks when bajarse removes cards mid-turn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BotTurnPhase {
+    /// hand.len() == 12, no card drawn this turn yet.
+    NeedDraw,
+    /// hand.len() == 13, must either bajarse or discard.
+    AfterDraw,
+    /// has_dropped_hand == true and hand is not empty; must discard to end turn.
+    AfterBajada,
+}
+
+pub fn detect_phase(player: &PlayerState) -> BotTurnPhase {
+    if !player.has_drawn_this_turn {
+        BotTurnPhase::NeedDraw
+    } else if player.has_dropped_hand {
+        BotTurnPhase::AfterBajada
+    } else {
+        BotTurnPhase::AfterDraw
+    }
+}
+
+// ─── Public Entry Point ───────────────────────────────────────────────────────
+
+pub fn play_bot_turn(
+    game: &GameState,
+    player_id: &str,
+    difficulty: BotDifficulty,
+) -> Option<Action> {
+    let current_player_index = game.current_turn;
+    let player = game.players.get(current_player_index)?;
+
+    if player.id != player_id {
+        return None;
+    }
+
+    let phase = detect_phase(player);
+
+    match phase {
+        BotTurnPhase::NeedDraw => decide_draw(game, player, difficulty),
+        BotTurnPhase::AfterDraw => {
+            // Try bajarse first (not allowed on first turn of the round)
+            if player.turns_played > 0
+                && let Some(action) = try_bajarse(game, player, difficulty)
+            {
+                return Some(action);
+            }
+            Some(decide_discard(game, player, difficulty))
+        }
+        BotTurnPhase::AfterBajada => {
+            // Try to shed a card first.
+            // Since check_bot_turn handles one action at a time, shedding
+            // will trigger another turn iteration if successful.
+            if let Some(action) = try_shedding(game, player, difficulty) {
+                return Some(action);
+            }
+            // Must discard to end turn
+            Some(decide_discard(game, player, difficulty))
+        }
+    }
+}
+
+fn try_shedding(
+    game: &GameState,
+    player: &PlayerState,
+    _difficulty: BotDifficulty,
+) -> Option<Action> {
+    if !player.has_drawn_this_turn
+        || (player.dropped_hand_this_turn && !game.rules.shed_on_bajada_turn)
+    {
+        return None;
+    }
+
+    let mut all_bajadas: Vec<(&str, &Vec<Vec<Card>>)> = Vec::new();
+    for p in &game.players {
+        if p.has_dropped_hand {
+            all_bajadas.push((p.id.as_str(), &p.dropped_combinations));
+        }
+    }
+
+    let possible_sheds = crate::combo_finder::find_sheddable_cards(
+        &player.hand,
+        &all_bajadas,
+        &game.rules.jokers,
+        game.rules.twos,
+    );
+    if possible_sheds.is_empty() {
+        return None;
+    }
+
+    // Pick a shed based on point value to minimize penalty if stuck
+    let best_shed = possible_sheds
+        .into_iter()
+        .max_by_key(|s| {
+            let card = &player.hand[s.hand_index];
+            game.rules.card_points(card)
+        })
+        .unwrap();
+
+    Some(Action::ShedCard {
+        payload: ShedCardPayload {
+            hand_card_index: best_shed.hand_index,
+            target_player_id: best_shed.target_player_id,
+            target_combo_idx: best_shed.target_combo_idx,
+            position: Some(best_shed.position),
+        },
+    })
+}
+
+// ─── Draw Phase ───────────────────────────────────────────────────────────────
+
+fn decide_draw(
+    game: &GameState,
+    player: &PlayerState,
+    difficulty: BotDifficulty,
+) -> Option<Action> {
+    let can_take_discard = !game.discard_pile.is_empty() && !player.has_dropped_hand;
+
+    // Once the deck runs out the discard pile is the only source; pass if it is off-limits
+    if game.deck.remaining() == 0 {
+        return Some(if can_take_discard {
+            Action::DrawFromDiscard
+        } else {
+            Action::PassTurn
+        });
+    }
+
+    // Rule: "Si un jugador se baja no puede recoger desde el mazo de descarte"
+    if !can_take_discard {
+        return Some(Action::DrawFromDeck);
+    }
+
+    let top_discard = game.discard_pile.last().unwrap();
+
+    let should_draw_discard = match difficulty {
+        BotDifficulty::Easy => {
+            // 30% chance to draw from discard pile (random)
+            let mut rng = rng();
+            rng.random_bool(0.3)
+        }
+        BotDifficulty::Medium => {
+            // Draw from discard if card has meaningful synergy (helps a partial combo)
+            let weights = SynergyWeights::for_round(&game.current_round);
+            let score = card_synergy_score(&player.hand, top_discard, weights);
+            score >= 15
+        }
+        BotDifficulty::Hard => {
+            // Take the discard when it is expected to leave less in hand than a blind draw
+            let scores = analysis::evaluate_actions(game, &player.id);
+            matches!(
+                scores.first().map(|s| &s.action),
+                Some(Action::DrawFromDiscard)
+            )
+        }
+    };
+
+    if should_draw_discard {
+        Some(Action::DrawFromDiscard)
+    } else {
+        Some(Action::DrawFromDeck)
+    }
+}
+
+// ─── Bajarse Phase ────────────────────────────────────────────────────────────
+
+fn try_bajarse(
+    game: &GameState,
+    player: &PlayerState,
+    difficulty: BotDifficulty,
+) -> Option<Action> {
+    let (req_trios, req_escalas) = game.current_round.get_requirements();
+    let minimize_points = difficulty != BotDifficulty::Easy;
+
+    let melds = find_best_bajada(
+        &player.hand,
+        req_trios,
+        req_escalas,
+        game.rules.min_escala_len(&game.current_round),
+        minimize_points,
+        &game.rules,
+    )?;
+
+    // Hard bot: delay bajarse if we're close to going out completely (≤ 1 card remaining)
+    if difficulty == BotDifficulty::Hard {
+        let total_meld_cards: usize = melds.iter().map(|m| m.card_indices.len()).sum();
+        // hand has 13 cards; after bajarse we'd have 13 - total_meld_cards left to discard
+        let remaining_after = player.hand.len().saturating_sub(total_meld_cards);
+        // If only 1 card remains after bajada, it means we discard it immediately — great.
+        // If remaining > 4, consider delaying by checking if we can do even better next turn.
+        // For now: always bajarse when possible for Hard too (can refine timing later).
+        let _ = remaining_after; // explicitly acknowledge: no delay implemented yet
+    }
+
+    // Build combinations from meld candidates
+    let combinations: Vec<Vec<Card>> = melds
+        .iter()
+        .map(|m| m.card_indices.iter().map(|&i| player.hand[i]).collect())
+        .collect();
+
+    Some(Action::DropHand {
+        payload: DropHandPayload { combinations },
+    })
+}
+
+// ─── Discard Phase ────────────────────────────────────────────────────────────
+
+fn decide_discard(game: &GameState, player: &PlayerState, difficulty: BotDifficulty) -> Action {
+    if player.hand.is_empty() {
+        // Should never happen in normal game flow
+        return Action::Discard {
+            payload: DiscardPayload { card_index: 0 },
+        };
+    }
+
+    let best_index = match difficulty {
+        BotDifficulty::Easy => {
+            // Discard a random card
+            let mut rng = rng();
+            (0..player.hand.len())
+                .collect::<Vec<usize>>()
+                .choose(&mut rng)
+                .copied()
+                .unwrap_or(0)
+        }
+        BotDifficulty::Medium => {
+            // Discard the card with the lowest synergy score
+            find_lowest_synergy_index(&player.hand, SynergyWeights::for_round(&game.current_round))
+        }
+        BotDifficulty::Hard => {
+            // Discard using weighted composite: synergy + points + defensive penalty
+            find_best_discard_index_hard(game, player)
+        }
+    };
+
+    Action::Discard {
+        payload: DiscardPayload {
+            card_index: best_index,
+        },
+    }
+}
+
+/// Returns the index of the card with the lowest synergy score (Medium difficulty).
+fn find_lowest_synergy_index(hand: &[Card], weights: SynergyWeights) -> usize {
+    let mut best_index = 0;
+    let mut min_score = i64::MAX;
+
+    for (i, card) in hand.iter().enumerate() {
+        let mut hand_without = hand.to_vec();
+        hand_without.remove(i);
+        let synergy = card_synergy_score(&hand_without, card, weights) as i64;
+        if synergy < min_score {
+            min_score = synergy;
+            best_index = i;
+        }
+    }
+    best_index
+}
+
+/// Returns the best card index to discard for Hard difficulty.
+/// Considers synergy, point value, and defensive heuristic.
+fn find_best_discard_index_hard(game: &GameState, player: &PlayerState) -> usize {
+    let mut best_index = 0;
+    let mut lowest_score = f64::MAX;
+
+    for i in 0..player.hand.len() {
+        let total_score = discard_components(game, player, i).total();
+        if total_score < lowest_score {
+            lowest_score = total_score;
+            best_index = i;
+        }
+    }
+    best_index
+}
+
+/// The terms the Hard bot weighs when deciding whether to discard a card.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiscardComponents {
+    pub synergy: Synergy,
+    pub points: u32,
+    /// Penalty for cards an opponent could shed onto their bajada
+    pub defense: f64,
+    /// Visible discards the card would have needed
+    pub dead_outs: u32,
+}
+
+impl DiscardComponents {
+    /// Lower = better card to discard. Low synergy and high points are cheap to give up;
+    /// giving good cards to opponents is penalized; cards whose partners are visibly gone
+    /// in the discard pile are less worth keeping.
+    pub fn total(&self) -> f64 {
+        self.synergy.score as f64 - (self.points as f64 * 0.1) + self.defense
+            - (self.dead_outs as f64 * 3.0)
+    }
+}
+
+fn discard_components(game: &GameState, player: &PlayerState, index: usize) -> DiscardComponents {
+    let card = &player.hand[index];
+    let mut hand_without = player.hand.clone();
+    hand_without.remove(index);
+
+    DiscardComponents {
+        synergy: synergy(
+            &hand_without,
+            card,
+            SynergyWeights::for_round(&game.current_round),
+        ),
+        points: game.rules.card_points(card),
+        defense: defensive_penalty(card, game, &player.id),
+        dead_outs: dead_outs(card, game.visible_discards()),
+    }
+}
+
+// ─── Explanations ─────────────────────────────────────────────────────────────
+
+/// A short rationale for `action`, as the bot's heuristics see it, for teaching games.
+/// Call it on the state the action was chosen from; `None` for actions bots never take.
+pub fn explain_action(
+    game: &GameState,
+    player_id: &str,
+    difficulty: BotDifficulty,
+    action: &Action,
+) -> Option<String> {
+    let player = game.players.iter().find(|p| p.id == player_id)?;
+    let random = difficulty == BotDifficulty::Easy;
+    let top_discard = game.discard_pile.last();
+    let weights = SynergyWeights::for_round(&game.current_round);
+
+    let text = match action {
+        Action::PassTurn => {
+            "Passed: the deck is empty and the discard pile is off-limits".to_string()
+        }
+        Action::DrawFromDeck => match top_discard {
+            _ if player.has_dropped_hand => {
+                "Drew from the deck: after dropping, the discard pile is off-limits".to_string()
+            }
+            None => "Drew from the deck: the discard pile is empty".to_string(),
+            Some(top) if random => format!("Left {} on the pile, picking a pile at random", top),
+            Some(top) => match synergy(&player.hand, top, weights).describe() {
+                Some(reasons) => format!(
+                    "Left {} on the pile: it only {}, not worth taking",
+                    top, reasons
+                ),
+                None => format!("Left {} on the pile: it fits nothing in hand", top),
+            },
+        },
+        Action::DrawFromDiscard => {
+            let top = top_discard?;
+            match synergy(&player.hand, top, weights).describe() {
+                _ if game.deck.remaining() == 0 => {
+                    format!("Took {}: the deck is empty", top)
+                }
+                _ if random => format!("Took {}, picking a pile at random", top),
+                Some(reasons) => format!("Took {} because it {}", top, reasons),
+                None => format!("Took {}", top),
+            }
+        }
+        Action::DropHand { payload } => {
+            let melds: Vec<String> = payload
+                .combinations
+                .iter()
+                .map(|combo| join_cards(combo, " "))
+                .collect();
+            let dropped: usize = payload.combinations.iter().map(Vec::len).sum();
+            format!(
+                "Dropped {} for {}, leaving {} cards in hand",
+                melds.join(", "),
+                game.current_round.description(),
+                player.hand.len().saturating_sub(dropped)
+            )
+        }
+        Action::ShedCard { payload } => {
+            let card = player.hand.get(payload.hand_card_index)?;
+            let owner = if payload.target_player_id == player.id {
+                "its own".to_string()
+            } else {
+                format!("{}'s", payload.target_player_id)
+            };
+            format!(
+                "Shed {} onto {} combination, getting rid of {} points",
+                card,
+                owner,
+                game.rules.card_points(card)
+            )
+        }
+        Action::Discard { payload } => {
+            let card = player.hand.get(payload.card_index)?;
+            if random {
+                format!("Discarded {} at random", card)
+            } else {
+                explain_discard(game, player, payload.card_index, difficulty, card)
+            }
+        }
+    };
+    Some(text)
+}
+
+fn explain_discard(
+    game: &GameState,
+    player: &PlayerState,
+    index: usize,
+    difficulty: BotDifficulty,
+    card: &Card,
+) -> String {
+    let components = discard_components(game, player, index);
+    let weights = SynergyWeights::for_round(&game.current_round);
+    let mut text = match components.synergy.describe() {
+        Some(reasons) => format!(
+            "Discarded {}: it only {} (synergy {})",
+            card, reasons, components.synergy.score
+        ),
+        None => format!("Discarded {}: it fits nothing in hand", card),
+    };
+    if difficulty == BotDifficulty::Hard {
+        text.push_str(&format!(
+            "; {} points in hand, {} partners already discarded",
+            components.points, components.dead_outs
+        ));
+        if components.defense > 0.0 {
+            text.push_str(", though an opponent can shed it");
+        }
+    }
+
+    // The card most worth keeping, to show what the discard was weighed against
+    let kept = (0..player.hand.len())
+        .filter(|&i| i != index && !player.hand[i].is_joker())
+        .map(|i| {
+            let mut rest = player.hand.clone();
+            rest.remove(i);
+            (i, synergy(&rest, &player.hand[i], weights))
+        })
+        .max_by_key(|(_, s)| s.score);
+    if let Some((i, kept)) = kept
+        && let Some(reasons) = kept.describe()
+    {
+        text.push_str(&format!("; kept {} because it {}", player.hand[i], reasons));
+    }
+    text
+}
+
+// ─── Heuristics ───────────────────────────────────────────────────────────────
+
+/// Scores how useful `target` card is given the rest of `hand`.
+/// Higher score = more useful = less desirable to discard.
+fn card_synergy_score(hand: &[Card], target: &Card, weights: SynergyWeights) -> u32 {
+    synergy(hand, target, weights).score
+}
+
+/// What a partner card is worth to [`synergy`], by the melds the round asks for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SynergyWeights {
+    /// Same value, towards a trio
+    pub pair: u32,
+    /// Same suit, one step away
+    pub adjacent: u32,
+    /// Same suit, two steps away
+    pub gap: u32,
+}
+
+impl SynergyWeights {
+    /// Rounds of only escalas favour same-suit runs, rounds of only trios favour pairs,
+    /// and mixed rounds weigh both.
+    pub fn for_round(round: &RoundType) -> Self {
+        match round.get_requirements() {
+            (0, _) => Self {
+                pair: 3,
+                adjacent: 20,
+                gap: 10,
+            },
+            (_, 0) => Self {
+                pair: 20,
+                adjacent: 3,
+                gap: 1,
+            },
+            _ => Self::default(),
+        }
+    }
+}
+
+impl Default for SynergyWeights {
+    fn default() -> Self {
+        Self {
+            pair: 15,
+            adjacent: 10,
+            gap: 5,
+        }
+    }
+}
+
+/// A card's synergy score together with the cards that earned it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Synergy {
+    pub score: u32,
+    /// Same value: trio partners
+    pub pairs: Vec<Card>,
+    /// Same suit, at most two steps away: escala neighbours
+    pub neighbours: Vec<Card>,
+}
+
+fn synergy(hand: &[Card], target: &Card, weights: SynergyWeights) -> Synergy {
+    let mut found = Synergy::default();
+    match target {
+        Card::Joker => found.score = 100, // Always keep jokers
+        Card::Standard {
+            suit: target_suit,
+            value: target_value,
+        } => {
+            for c in hand {
+                if let Card::Standard { suit, value } = c {
+                    // Potential trio pair
+                    if value == target_value {
+                        found.score += weights.pair;
+                        found.pairs.push(*c);
+                    }
+                    // Potential escala adjacency (same suit, value within 2)
+                    if suit == target_suit {
+                        let diff = (*value as i32) - (*target_value as i32);
+                        if diff.abs() == 1 {
+                            found.score += weights.adjacent;
+                            found.neighbours.push(*c);
+                        } else if diff.abs() == 2 {
+                            found.score += weights.gap;
+                            found.neighbours.push(*c);
+                        }
+                    }
+                }
+            }
+        }
+    }
+    found.neighbours.sort_by_key(|c| match c {
+        Card::Standard { value, .. } => *value as u8,
+        Card::Joker => 0,
+    });
+    found
+}
+
+impl Synergy {
+    /// "pairs with 7♣ and extends 5♦-6♦", or `None` when the card fits nothing.
+    fn describe(&self) -> Option<String> {
+        let mut parts = Vec::new();
+        if !self.pairs.is_empty() {
+            parts.push(format!("pairs with {}", join_cards(&self.pairs, ", ")));
+        }
+        if !self.neighbours.is_empty() {
+            parts.push(format!("extends {}", join_cards(&self.neighbours, "-")));
+        }
+        (!parts.is_empty()).then(|| parts.join(" and "))
+    }
+}
+
+fn join_cards(cards: &[Card], separator: &str) -> String {
+    cards
+        .iter()
+        .map(|c| c.to_string())
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
+/// Counts visible discards that `card` would have needed: same-value cards for a trio and
+/// same-suit neighbours for an escala. Only as informative as the rule set's discard visibility.
+fn dead_outs(card: &Card, discards: &[Card]) -> u32 {
+    let Card::Standard {
+        suit: target_suit,
+        value: target_value,
+    } = card
+    else {
+        return 0;
+    };
+    discards
+        .iter()
+        .filter(|c| match c {
+            Card::Standard { suit, value } => {
+                value == target_value
+                    || (suit == target_suit
+                        && ((*value as i32) - (*target_value as i32)).abs() == 1)
+            }
+            Card::Joker => false,
+        })
+        .count() as u32
+}
+
+/// Penalty for discarding a card that would help an opponent extend their bajada.
+/// Used by Hard difficulty only.
+fn defensive_penalty(card: &Card, game: &GameState, my_id: &str) -> f64 {
+    let mut penalty = 0.0;
+
+    for player in &game.players {
+        if player.id == my_id || !player.has_dropped_hand {
+            continue;
+        }
+        for combo in &player.dropped_combinations {
+            if crate::combo_finder::can_shed(card, combo, &game.rules.jokers, game.rules.twos)
+                .is_some()
+            {
+                penalty += 10.0;
+            }
+        }
+    }
+    penalty
+}
+
+// ─── Tests ────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::card::{Card, Suit, Value};
+    use crate::game::{GameState, PlayerState};
+
+    fn std(suit: Suit, value: Value) -> Card {
+        Card::Standard { suit, value }
+    }
+
+    fn make_player(hand: Vec<Card>, has_dropped: bool, turns_played: u32) -> PlayerState {
+        PlayerState {
+            id: "bot_test".to_string(),
+            hand,
+            points: 0,
+            has_dropped_hand: has_dropped,
+            dropped_combinations: vec![],
+            turns_played,
+            cards_shed: 0,
+            points_shed: 0,
+            has_drawn_this_turn: false,
+            dropped_hand_this_turn: false,
+            is_ready_for_next_round: false,
+            penalty_cards: 0,
+            rounds_won: 0,
+            game_penalty_cards: 0,
+        }
+    }
+
+    #[test]
+    fn phase_detection_need_draw() {
+        let player = make_player(vec![std(Suit::Hearts, Value::Two); 12], false, 0);
+        assert_eq!(detect_phase(&player), BotTurnPhase::NeedDraw);
+    }
+
+    #[test]
+    fn phase_detection_after_draw() {
+        let mut player = make_player(vec![std(Suit::Hearts, Value::Two); 13], false, 1);
+        player.has_drawn_this_turn = true;
+        assert_eq!(detect_phase(&player), BotTurnPhase::AfterDraw);
+    }
+
+    #[test]
+    fn phase_detection_after_bajada() {
+        let mut player = make_player(
+            vec![std(Suit::Hearts, Value::Three); 5],
+            true, // has_dropped_hand
+            3,
+        );
+        player.has_drawn_this_turn = true;
+        assert_eq!(detect_phase(&player), BotTurnPhase::AfterBajada);
+    }
+
+    #[test]
+    fn easy_bot_produces_valid_discard_index() {
+        let hand: Vec<Card> = (2..=13).map(|_| std(Suit::Hearts, Value::Two)).collect();
+        let player = make_player(hand, false, 1);
+        let game = dummy_game_at_player(player);
+        let action = play_bot_turn(&game, "bot_test", BotDifficulty::Easy);
+        assert!(action.is_some());
+        match action.unwrap() {
+            Action::DrawFromDeck | Action::DrawFromDiscard => {}
+            other => panic!("Expected draw action, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn bot_passes_when_deck_empty_after_bajada() {
+        let player = make_player(vec![std(Suit::Hearts, Value::Two); 4], true, 3);
+        let mut game = dummy_game_at_player(player);
+        while game.deck.draw().is_some() {}
+
+        let action = play_bot_turn(&game, "bot_test", BotDifficulty::Hard);
+        assert!(matches!(action, Some(Action::PassTurn)));
+
+        // Not yet bajado: the discard pile is still fair game
+        game.players[0].has_dropped_hand = false;
+        let action = play_bot_turn(&game, "bot_test", BotDifficulty::Easy);
+        assert!(matches!(action, Some(Action::DrawFromDiscard)));
+    }
+
+    #[test]
+    fn medium_bot_bajarse_when_ready() {
+        // Build a hand with 2 valid trios + 6 junk cards (round 1 = 2 trios req)
+        let mut hand = vec![
+            std(Suit::Hearts, Value::Five),
+            std(Suit::Clubs, Value::Five),
+            std(Suit::Spades, Value::Five),
+            std(Suit::Hearts, Value::Nine),
+            std(Suit::Clubs, Value::Nine),
+            std(Suit::Diamonds, Value::Nine),
+        ];
+        // Add junk to reach 13 cards (simulate after draw)
+        hand.extend([
+            std(Suit::Hearts, Value::Two),
+            std(Suit::Clubs, Value::King),
+            std(Suit::Spades, Value::Ace),
+            std(Suit::Diamonds, Value::Jack),
+            std(Suit::Hearts, Value::Three),
+            std(Suit::Clubs, Value::Six),
+            std(Suit::Spades, Value::Queen), // 13th
+        ]);
+        let mut player = make_player(hand, false, 1); // turns_played > 0
+        player.has_drawn_this_turn = true;
+        let game = dummy_game_at_player(player);
+        let action = play_bot_turn(&game, "bot_test", BotDifficulty::Medium);
+        assert!(action.is_some());
+        match action.unwrap() {
+            Action::DropHand { payload } => {
+                assert_eq!(payload.combinations.len(), 2, "Should have 2 combinations");
+            }
+            other => panic!("Expected DropHand, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn bot_cannot_bajarse_on_first_turn() {
+        let mut hand = vec![
+            std(Suit::Hearts, Value::Five),
+            std(Suit::Clubs, Value::Five),
+            std(Suit::Spades, Value::Five),
+            std(Suit::Hearts, Value::Nine),
+            std(Suit::Clubs, Value::Nine),
+            std(Suit::Diamonds, Value::Nine),
+        ];
+        hand.extend([
+            std(Suit::Hearts, Value::Two),
+            std(Suit::Clubs, Value::King),
+            std(Suit::Spades, Value::Ace),
+            std(Suit::Diamonds, Value::Jack),
+            std(Suit::Hearts, Value::Three),
+            std(Suit::Clubs, Value::Six),
+            std(Suit::Spades, Value::Queen),
+        ]);
+        let mut player = make_player(hand, false, 0); // turns_played == 0 → first turn
+        player.has_drawn_this_turn = true;
+        let game = dummy_game_at_player(player);
+        let action = play_bot_turn(&game, "bot_test", BotDifficulty::Medium);
+        // Must Discard, NOT DropHand
+        assert!(action.is_some());
+        match action.unwrap() {
+            Action::Discard { .. } => {} // correct
+            Action::DropHand { .. } => panic!("Should not bajarse on first turn"),
+            other => panic!("Unexpected action {:?}", other),
+        }
+    }
+
+    #[test]
+    fn hard_bot_defensive_discard_avoids_extending_opponent_trio() {
+        // Opponent has a trio of Sevens on the table
+        let mut game = GameState::new(vec!["bot_test".to_string(), "opponent".to_string()]);
+        game.start_round();
+
+        // Manually set opponent as bajado with a trio of 7s
+        game.players[1].has_dropped_hand = true;
+        game.players[1].dropped_combinations = vec![vec![
+            std(Suit::Hearts, Value::Seven),
+            std(Suit::Clubs, Value::Seven),
+            std(Suit::Spades, Value::Seven),
+        ]];
+
+        // Bot hand: includes 7♦ (which would extend opponent's trio) + high-point junk
+        let hand = vec![
+            std(Suit::Diamonds, Value::Seven), // idx 0 — danger: extends opponent's trio
+            std(Suit::Clubs, Value::Ace),      // idx 1 — high points (20)
+            std(Suit::Hearts, Value::King),    // idx 2
+            std(Suit::Clubs, Value::Jack),     // idx 3
+            std(Suit::Spades, Value::Two),     // idx 4 — very low points + no synergy
+            std(Suit::Hearts, Value::Three),   // idx 5
+            std(Suit::Clubs, Value::Nine),     // idx 6
+            std(Suit::Diamonds, Value::Eight), // idx 7
+            std(Suit::Hearts, Value::Six),     // idx 8
+            std(Suit::Spades, Value::Queen),   // idx 9
+            std(Suit::Clubs, Value::Four),     // idx 10
+            std(Suit::Diamonds, Value::Ten),   // idx 11
+            std(Suit::Hearts, Value::Five),    // idx 12
+        ];
+        game.players[0].hand = hand;
+        game.players[0].turns_played = 2;
+        game.players[0].has_drawn_this_turn = true;
+        game.current_turn = 0;
+
+        let action = play_bot_turn(&game, "bot_test", BotDifficulty::Hard);
+        assert!(action.is_some());
+        // The bot should NOT discard index 0 (7♦ extends opponent's trio)
+        match action.unwrap() {
+            Action::Discard { payload } => {
+                assert_ne!(
+                    payload.card_index, 0,
+                    "Hard bot should avoid giving the 7♦ to opponent"
+                );
+            }
+            other => panic!("Unexpected action {:?}", other),
+        }
+    }
+
+    #[test]
+    fn bot_sheds_on_bajada_turn_only_when_allowed() {
+        let mut player = make_player(
+            vec![
+                std(Suit::Hearts, Value::Seven),
+                std(Suit::Clubs, Value::Two),
+            ],
+            true,
+            2,
+        );
+        player.has_drawn_this_turn = true;
+        player.dropped_hand_this_turn = true;
+        player.dropped_combinations = vec![vec![
+            std(Suit::Spades, Value::Seven),
+            std(Suit::Diamonds, Value::Seven),
+            std(Suit::Clubs, Value::Seven),
+        ]];
+        let mut game = dummy_game_at_player(player);
+
+        match play_bot_turn(&game, "bot_test", BotDifficulty::Medium) {
+            Some(Action::Discard { .. }) => {}
+            other => panic!("Expected Discard, got {:?}", other),
+        }
+
+        game.rules.shed_on_bajada_turn = true;
+        match play_bot_turn(&game, "bot_test", BotDifficulty::Medium) {
+            Some(Action::ShedCard { payload }) => {
+                assert_eq!(payload.hand_card_index, 0);
+                assert_eq!(payload.target_player_id, "bot_test");
+            }
+            other => panic!("Expected ShedCard, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn dead_outs_only_count_visible_discards() {
+        let mut game = GameState::new(vec!["bot_test".to_string(), "dummy_opponent".to_string()]);
+        game.start_round();
+        game.discard_pile = vec![
+            std(Suit::Clubs, Value::Nine),
+            std(Suit::Hearts, Value::Eight),
+            std(Suit::Spades, Value::King),
+        ];
+        let nine = std(Suit::Hearts, Value::Nine);
+
+        assert_eq!(dead_outs(&nine, game.visible_discards()), 0);
+        game.rules.discard_visibility = crate::rule_set::DiscardVisibility::All;
+        assert_eq!(dead_outs(&nine, game.visible_discards()), 2);
+    }
+
+    #[test]
+    fn synergy_reports_its_partners() {
+        let hand = vec![
+            std(Suit::Clubs, Value::Seven),
+            std(Suit::Diamonds, Value::Six),
+            std(Suit::Diamonds, Value::Five),
+            std(Suit::Spades, Value::King),
+        ];
+        let found = synergy(
+            &hand,
+            &std(Suit::Diamonds, Value::Seven),
+            SynergyWeights::default(),
+        );
+        assert_eq!(found.score, 15 + 10 + 5);
+        assert_eq!(found.pairs, vec![std(Suit::Clubs, Value::Seven)]);
+        assert_eq!(found.describe().unwrap(), "pairs with 7♣ and extends 5♦-6♦");
+    }
+
+    #[test]
+    fn synergy_weights_follow_the_round() {
+        let pair = [std(Suit::Clubs, Value::Seven)];
+        let neighbour = [std(Suit::Diamonds, Value::Six)];
+        let seven = std(Suit::Diamonds, Value::Seven);
+        let favours_pairs = |round: RoundType| {
+            let weights = SynergyWeights::for_round(&round);
+            card_synergy_score(&pair, &seven, weights)
+                > card_synergy_score(&neighbour, &seven, weights)
+        };
+
+        assert!(favours_pairs(RoundType::FourTrios));
+        assert!(!favours_pairs(RoundType::TwoEscalas));
+        assert!(!favours_pairs(RoundType::ThreeEscalas));
+        assert_eq!(
+            SynergyWeights::for_round(&RoundType::OneTrioOneEscala),
+            SynergyWeights::default()
+        );
+    }
+
+    #[test]
+    fn explains_what_a_discard_was_weighed_against() {
+        let hand = vec![
+            std(Suit::Diamonds, Value::Seven),
+            std(Suit::Clubs, Value::Seven),
+            std(Suit::Diamonds, Value::Six),
+            std(Suit::Diamonds, Value::Five),
+            std(Suit::Spades, Value::King),
+        ];
+        let mut player = make_player(hand, false, 2);
+        player.has_drawn_this_turn = true;
+        let game = dummy_game_at_player(player);
+
+        let action = play_bot_turn(&game, "bot_test", BotDifficulty::Medium).unwrap();
+        assert!(matches!(
+            action,
+            Action::Discard {
+                payload: DiscardPayload { card_index: 4 }
+            }
+        ));
+        let text = explain_action(&game, "bot_test", BotDifficulty::Medium, &action).unwrap();
+        assert_eq!(
+            text,
+            "Discarded K♠: it fits nothing in hand; \
+             kept 7♦ because it pairs with 7♣ and extends 5♦-6♦"
+        );
+    }
+
+    /// Creates a minimal GameState with `player` as the current player (index 0).
+    fn dummy_game_at_player(player: PlayerState) -> GameState {
+        let mut game = GameState::new(vec!["bot_test".to_string(), "dummy_opponent".to_string()]);
+        game.start_round();
+        game.players[0] = player;
+        game.current_turn = 0;
+        game
+    }
+}
@@ -1,6 +1,8 @@
 use serde::{Deserialize, Serialize};
 use std::fmt;
 
+use crate::points::PointsTable;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Suit {
     Hearts,
@@ -39,7 +41,7 @@ pub enum Value {
 
 impl Value {
     /// Values mapped to their points when left in hand
-    pub fn points(&self) -> u32 {
+    pub fn points(&self, table: &PointsTable) -> u32 {
         match self {
             Value::Two => 2,
             Value::Three => 3,
@@ -50,10 +52,8 @@ impl Value {
             Value::Eight => 8,
             Value::Nine => 9,
             Value::Ten => 10,
-            Value::Jack => 10,
-            Value::Queen => 10,
-            Value::King => 10,
-            Value::Ace => 20,
+            Value::Jack | Value::Queen | Value::King => table.face_cards,
+            Value::Ace => table.ace,
         }
     }
 }
@@ -85,13 +85,13 @@ pub enum Card {
 }
 
 impl Card {
-    pub fn points(&self) -> u32 {
+    pub fn points(&self, table: &PointsTable) -> u32 {
         match self {
-            Card::Standard { value, .. } => value.points(),
-            Card::Joker => 50,
+            Card::Standard { value, .. } => value.points(table),
+            Card::Joker => table.joker,
         }
     }
-    
+
     pub fn is_joker(&self) -> bool {
         matches!(self, Card::Joker)
     }
@@ -112,16 +112,27 @@ mod tests {
 
     #[test]
     fn test_card_points() {
-        let ace_spades = Card::Standard { suit: Suit::Spades, value: Value::Ace };
-        assert_eq!(ace_spades.points(), 20);
+        let table = PointsTable::default();
+
+        let ace_spades = Card::Standard {
+            suit: Suit::Spades,
+            value: Value::Ace,
+        };
+        assert_eq!(ace_spades.points(&table), 20);
+
+        let seven_hearts = Card::Standard {
+            suit: Suit::Hearts,
+            value: Value::Seven,
+        };
+        assert_eq!(seven_hearts.points(&table), 7);
 
-        let seven_hearts = Card::Standard { suit: Suit::Hearts, value: Value::Seven };
-        assert_eq!(seven_hearts.points(), 7);
-        
-        let jack_clubs = Card::Standard { suit: Suit::Clubs, value: Value::Jack };
-        assert_eq!(jack_clubs.points(), 10);
+        let jack_clubs = Card::Standard {
+            suit: Suit::Clubs,
+            value: Value::Jack,
+        };
+        assert_eq!(jack_clubs.points(&table), 10);
 
         let joker = Card::Joker;
-        assert_eq!(joker.points(), 50);
+        assert_eq!(joker.points(&table), 50);
     }
 }
@@ -1,4 +1,9 @@
-use crate::engine::card::{Card, Suit, Value};
+use crate::card::{Card, Suit, Value};
+use crate::rule_set::{JokerLimits, RuleSet, TwoBehavior};
+use crate::rules::{
+    ESCALA_CORTA_LEN, MIN_ESCALA_LEN, escala_wildcards, is_wildcard, trio_wildcards,
+};
+use serde::{Deserialize, Serialize};
 
 // ─── Core Types ───────────────────────────────────────────────────────────────
 
@@ -53,16 +58,21 @@ pub struct HandScore {
 ///
 /// Rules:
 /// - 3+ cards of the same value (suits may differ)
-/// - At most 1 Joker substituting any value
+/// - At most `max_jokers` Jokers substituting any value, and at least one standard card
+/// - Wild twos (see [`TwoBehavior::WildTwo`]) substitute like Jokers, except in a trío of twos
 /// - Each candidate is uniquely identified by its set of hand indices
-pub fn find_all_trio_candidates(hand: &[Card]) -> Vec<MeldCandidate> {
+pub fn find_all_trio_candidates(
+    hand: &[Card],
+    max_jokers: usize,
+    twos: TwoBehavior,
+) -> Vec<MeldCandidate> {
     let mut candidates = Vec::new();
 
-    // Collect joker indices
-    let joker_indices: Vec<usize> = hand
+    // Collect wildcard indices
+    let wildcard_indices: Vec<usize> = hand
         .iter()
         .enumerate()
-        .filter(|(_, c)| c.is_joker())
+        .filter(|(_, c)| is_wildcard(c, twos))
         .map(|(i, _)| i)
         .collect();
 
@@ -77,6 +87,12 @@ pub fn find_all_trio_candidates(hand: &[Card]) -> Vec<MeldCandidate> {
 
     for indices in by_value.values() {
         let n = indices.len();
+        // A two is a trío of twos' own card, not a stand-in
+        let joker_indices: Vec<usize> = wildcard_indices
+            .iter()
+            .copied()
+            .filter(|i| !indices.contains(i))
+            .collect();
 
         // Generate all subsets of exactly size 3 (trios must be exactly 3 cards at bajada time)
         for i in 0..n {
@@ -89,7 +105,7 @@ pub fn find_all_trio_candidates(hand: &[Card]) -> Vec<MeldCandidate> {
         }
 
         // Joker-enhanced trios: pick 2 standard cards + 1 joker
-        if n >= 2 && !joker_indices.is_empty() {
+        if max_jokers >= 1 && n >= 2 {
             for &joker_idx in &joker_indices {
                 for i in 0..n {
                     for j in (i + 1)..n {
@@ -99,6 +115,18 @@ pub fn find_all_trio_candidates(hand: &[Card]) -> Vec<MeldCandidate> {
                 }
             }
         }
+
+        // Variants allowing 2 jokers: 1 standard card + 2 jokers
+        if max_jokers >= 2 {
+            for (a, &joker_a) in joker_indices.iter().enumerate() {
+                for &joker_b in &joker_indices[a + 1..] {
+                    for &i in indices {
+                        let subset = vec![i, joker_a, joker_b];
+                        candidates.push(MeldCandidate::new(MeldType::Trio, subset));
+                    }
+                }
+            }
+        }
     }
 
     candidates
@@ -109,51 +137,57 @@ pub fn find_all_trio_candidates(hand: &[Card]) -> Vec<MeldCandidate> {
 /// Returns all valid escala meld candidates from the given hand.
 ///
 /// Rules:
-/// - 4+ cards of consecutive values in the **same suit**
-/// - At most 1 Joker filling exactly one gap
-/// - Ace = high only (value 14, after King). No K-A-2 wrap.
-pub fn find_all_escala_candidates(hand: &[Card]) -> Vec<MeldCandidate> {
+/// - `min_len`+ cards (4, or 3 for an escala corta) of consecutive values in the **same suit**
+/// - At most `max_jokers` Jokers, each filling one missing value inside the run
+/// - Wild twos fill gaps like Jokers, or sit in the run as themselves
+/// - Runs may wrap around the Ace (K-A-2)
+pub fn find_all_escala_candidates(
+    hand: &[Card],
+    max_jokers: usize,
+    min_len: usize,
+    twos: TwoBehavior,
+) -> Vec<MeldCandidate> {
     let mut candidates = Vec::new();
 
     let joker_indices: Vec<usize> = hand
         .iter()
         .enumerate()
-        .filter(|(_, c)| c.is_joker())
+        .filter(|(_, c)| is_wildcard(c, twos))
         .map(|(i, _)| i)
         .collect();
+    let max_jokers = max_jokers.min(joker_indices.len());
 
     // Group standard card indices by suit, sorted by value
     let suits = [Suit::Hearts, Suit::Diamonds, Suit::Clubs, Suit::Spades];
     for suit in suits {
         let mut suit_cards: Vec<(u8, usize)> = Vec::new();
         for (i, c) in hand.iter().enumerate() {
-            if let Card::Standard { suit: s, value } = c {
-                if *s == suit {
-                    let mut v = *value as u8;
-                    if v == 14 {
-                        v = 1;
-                    }
-                    suit_cards.push((v, i));
-                    suit_cards.push((v + 13, i)); // Duplicate for wrapping detection
+            if let Card::Standard { suit: s, value } = c
+                && *s == suit
+            {
+                let mut v = *value as u8;
+                if v == 14 {
+                    v = 1;
                 }
+                suit_cards.push((v, i));
+                suit_cards.push((v + 13, i)); // Duplicate for wrapping detection
             }
         }
 
         suit_cards.sort_by_key(|(v, _)| *v);
 
         let real_count = suit_cards.len() / 2;
-        if real_count < 4 && (real_count < 3 || joker_indices.is_empty()) {
-            continue; // At least 4 cards (or 3+Joker)
+        if real_count + max_jokers < min_len {
+            continue; // At least `min_len` cards counting usable jokers
         }
 
         let n = suit_cards.len();
-        // Try all contiguous subsequences (by sorted position) of length >= 4
-        // A "contiguous" subsequence allows at most 1 gap of size 1 (filled by joker)
-        'outer: for start in 0..n {
+        // Try all contiguous subsequences (by sorted position) of length >= min_len
+        // Gaps are filled with distinct jokers while the meld's joker budget lasts
+        for start in 0..n {
             let mut selected_indices: Vec<usize> = vec![suit_cards[start].1];
-            let mut prev_val = suit_cards[start].0 as u8;
-            let mut joker_used = false;
-            let mut joker_slot: Option<usize> = None; // which joker from joker_indices
+            let mut prev_val = suit_cards[start].0;
+            let mut jokers_used = 0;
 
             for (cur_val, cur_hand_idx) in suit_cards.iter().skip(start + 1).copied() {
                 if selected_indices.contains(&cur_hand_idx) {
@@ -169,30 +203,32 @@ pub fn find_all_escala_candidates(hand: &[Card]) -> Vec<MeldCandidate> {
                     // Consecutive
                     selected_indices.push(cur_hand_idx);
                     prev_val = cur_val;
-                } else if gap == 2 && !joker_used && !joker_indices.is_empty() {
-                    // Gap of 1, fill with joker
-                    joker_used = true;
-                    joker_slot = Some(joker_indices[0]); // take first available joker
-                    selected_indices.push(joker_slot.unwrap());
+                } else if jokers_used + (gap as usize - 1) <= max_jokers {
+                    // Missing values, one joker each; a wild two already in the run is taken
+                    let fill: Vec<usize> = joker_indices
+                        .iter()
+                        .copied()
+                        .filter(|i| !selected_indices.contains(i) && *i != cur_hand_idx)
+                        .take(gap as usize - 1)
+                        .collect();
+                    if fill.len() < gap as usize - 1 {
+                        break;
+                    }
+                    jokers_used += fill.len();
+                    selected_indices.extend(fill);
                     selected_indices.push(cur_hand_idx);
                     prev_val = cur_val;
                 } else {
-                    // Gap too large or second gap — end of this run
+                    // Gap exceeds the remaining joker budget — end of this run
                     break;
                 }
 
-                // Emit all sub-runs ending at current position with len >= 4
-                if selected_indices.len() >= 4 {
-                    // Emit all suffixes of selected_indices that cover >= 4 cards
-                    emit_subruns(
-                        &selected_indices,
-                        MeldType::Escala,
-                        &joker_slot,
-                        &mut candidates,
-                    );
+                // Emit all sub-runs ending at current position with len >= min_len
+                if selected_indices.len() >= min_len {
+                    emit_subruns(&selected_indices, min_len, &mut candidates);
                 }
 
-                if selected_indices.len() == 13 {
+                if selected_indices.len() >= 13 {
                     // Maximum escala reached
                     break;
                 }
@@ -207,43 +243,37 @@ pub fn find_all_escala_candidates(hand: &[Card]) -> Vec<MeldCandidate> {
     candidates
 }
 
-/// Emits all sub-run windows of exactly length 4 from `indices`.
-/// Escalas must be exactly 4 cards at bajada time; extensions happen via shedding.
-fn emit_subruns(
-    indices: &[usize],
-    meld_type: MeldType,
-    joker_slot: &Option<usize>,
-    out: &mut Vec<MeldCandidate>,
-) {
-    let len = indices.len();
-    // Emit all windows of exactly 4 cards
-    for start in 0..=(len.saturating_sub(4)) {
-        let sub = &indices[start..start + 4];
-        // Validate joker appears at most once in this sub-window
-        if let Some(j) = joker_slot {
-            let joker_count = sub.iter().filter(|&&x| x == *j).count();
-            if joker_count > 1 {
-                continue;
-            }
+/// Emits all sub-run windows of `min_len` to [`MIN_ESCALA_LEN`] cards from `indices`.
+/// Escalas are at most 4 cards at bajada time; extensions happen via shedding.
+/// Each joker in `indices` is a distinct hand card, so windows never reuse one.
+fn emit_subruns(indices: &[usize], min_len: usize, out: &mut Vec<MeldCandidate>) {
+    for size in min_len..=MIN_ESCALA_LEN.max(min_len) {
+        for sub in indices.windows(size) {
+            out.push(MeldCandidate::new(MeldType::Escala, sub.to_vec()));
         }
-        out.push(MeldCandidate::new(meld_type, sub.to_vec()));
     }
 }
 
 // ─── Bajada Solver ────────────────────────────────────────────────────────────
 
-/// Finds the best set of melds from `hand` satisfying `req_trios` trios and `req_escalas` escalas.
+/// Finds the best set of melds from `hand` satisfying `req_trios` trios and `req_escalas` escalas
+/// of at least `min_escala_len` cards, see [`RuleSet::min_escala_len`].
 ///
 /// - Easy: returns the first valid solution found.
 /// - Medium/Hard: evaluates all solutions and returns the one minimising remaining hand points.
+///   With [`RuleSet::extra_melds`] that includes laying down further melds when they help,
+///   always keeping a card to discard.
 pub fn find_best_bajada(
     hand: &[Card],
     req_trios: usize,
     req_escalas: usize,
+    min_escala_len: usize,
     minimize_points: bool,
+    rules: &RuleSet,
 ) -> Option<Vec<MeldCandidate>> {
-    let trios = find_all_trio_candidates(hand);
-    let escalas = find_all_escala_candidates(hand);
+    let trios = find_all_trio_candidates(hand, rules.jokers.per_trio, rules.twos);
+    let escalas =
+        find_all_escala_candidates(hand, rules.jokers.per_escala, min_escala_len, rules.twos);
 
     let mut best_solution: Option<Vec<MeldCandidate>> = None;
     let mut best_score = HandScore {
@@ -260,9 +290,11 @@ pub fn find_best_bajada(
         0,
         req_trios,
         req_escalas,
+        min_escala_len,
         0u16,
         &mut current,
         minimize_points,
+        rules,
         &mut best_solution,
         &mut best_score,
     );
@@ -279,24 +311,38 @@ fn solve(
     chosen_escalas: usize,
     req_trios: usize,
     req_escalas: usize,
+    min_escala_len: usize,
     used_mask: HandMask,
     current: &mut Vec<MeldCandidate>,
     minimize_points: bool,
+    rules: &RuleSet,
     best_solution: &mut Option<Vec<MeldCandidate>>,
     best_score: &mut HandScore,
 ) {
     // ── Base case ──
     if chosen_trios == req_trios && chosen_escalas == req_escalas {
-        let score = score_remaining_hand(hand, used_mask);
-        if !minimize_points {
-            // Easy: take first valid solution and stop
-            *best_solution = Some(current.clone());
-            *best_score = score;
-            return;
-        }
-        if score < *best_score {
-            *best_score = score;
-            *best_solution = Some(current.clone());
+        if rules.extra_melds && minimize_points {
+            add_extra_melds(
+                hand,
+                trios,
+                escalas,
+                0,
+                used_mask,
+                current,
+                rules,
+                best_solution,
+                best_score,
+            );
+        } else {
+            record_solution(
+                hand,
+                used_mask,
+                current,
+                minimize_points,
+                rules,
+                best_solution,
+                best_score,
+            );
         }
         return;
     }
@@ -310,7 +356,7 @@ fn solve(
     let remaining_cards = (hand.len() as u32).saturating_sub(used_mask.count_ones());
     let still_needed_trios = req_trios.saturating_sub(chosen_trios);
     let still_needed_escalas = req_escalas.saturating_sub(chosen_escalas);
-    let min_cards_needed = (still_needed_trios * 3 + still_needed_escalas * 4) as u32;
+    let min_cards_needed = (still_needed_trios * 3 + still_needed_escalas * min_escala_len) as u32;
     if remaining_cards < min_cards_needed {
         return;
     }
@@ -328,9 +374,11 @@ fn solve(
                     chosen_escalas,
                     req_trios,
                     req_escalas,
+                    min_escala_len,
                     used_mask | trio.mask,
                     current,
                     minimize_points,
+                    rules,
                     best_solution,
                     best_score,
                 );
@@ -355,9 +403,11 @@ fn solve(
                     chosen_escalas + 1,
                     req_trios,
                     req_escalas,
+                    min_escala_len,
                     used_mask | escala.mask,
                     current,
                     minimize_points,
+                    rules,
                     best_solution,
                     best_score,
                 );
@@ -370,14 +420,100 @@ fn solve(
     }
 }
 
+/// Keeps `current` if it is the first or the best bajada so far.
+fn record_solution(
+    hand: &[Card],
+    used_mask: HandMask,
+    current: &[MeldCandidate],
+    minimize_points: bool,
+    rules: &RuleSet,
+    best_solution: &mut Option<Vec<MeldCandidate>>,
+    best_score: &mut HandScore,
+) {
+    let jokers_used: usize = current
+        .iter()
+        .map(|meld| meld_wildcards(hand, meld, rules.twos))
+        .sum();
+    if jokers_used > rules.jokers.per_bajada {
+        return;
+    }
+    let score = score_remaining_hand(hand, used_mask, rules);
+    if !minimize_points {
+        // Easy: take first valid solution and stop
+        *best_solution = Some(current.to_vec());
+        *best_score = score;
+        return;
+    }
+    if score < *best_score {
+        *best_score = score;
+        *best_solution = Some(current.to_vec());
+    }
+}
+
+/// Tries the required melds in `current` alone and with every set of further melds that
+/// fits the rest of the hand and leaves a card to discard. Candidates are numbered trios
+/// first, then escalas; only those from `next` on are added so each set is tried once.
+#[allow(clippy::too_many_arguments)]
+fn add_extra_melds(
+    hand: &[Card],
+    trios: &[MeldCandidate],
+    escalas: &[MeldCandidate],
+    next: usize,
+    used_mask: HandMask,
+    current: &mut Vec<MeldCandidate>,
+    rules: &RuleSet,
+    best_solution: &mut Option<Vec<MeldCandidate>>,
+    best_score: &mut HandScore,
+) {
+    record_solution(
+        hand,
+        used_mask,
+        current,
+        true,
+        rules,
+        best_solution,
+        best_score,
+    );
+
+    for (i, meld) in trios.iter().chain(escalas).enumerate().skip(next) {
+        let mask = used_mask | meld.mask;
+        if meld.mask & used_mask != 0 || mask.count_ones() as usize >= hand.len() {
+            continue;
+        }
+        current.push(meld.clone());
+        add_extra_melds(
+            hand,
+            trios,
+            escalas,
+            i + 1,
+            mask,
+            current,
+            rules,
+            best_solution,
+            best_score,
+        );
+        current.pop();
+    }
+}
+
+/// Jokers and wild twos standing in for another card in `meld`.
+fn meld_wildcards(hand: &[Card], meld: &MeldCandidate, twos: TwoBehavior) -> usize {
+    let cards: Vec<Card> = meld.card_indices.iter().map(|&i| hand[i]).collect();
+    match meld.meld_type {
+        MeldType::Trio => trio_wildcards(&cards, twos),
+        MeldType::Escala => escala_wildcards(&cards, ESCALA_CORTA_LEN, twos),
+    }
+    .unwrap_or_default()
+}
+
 /// Scores the cards NOT included in the bajada (lower is better).
-pub fn score_remaining_hand(hand: &[Card], used_mask: HandMask) -> HandScore {
+pub fn score_remaining_hand(hand: &[Card], used_mask: HandMask, rules: &RuleSet) -> HandScore {
     let mut remaining_points = 0u32;
     let mut remaining_cards: Vec<&Card> = Vec::new();
 
     for (i, card) in hand.iter().enumerate() {
         if (used_mask >> i as u16) & 1 == 0 {
-            remaining_points += card.points();
+            remaining_points += rules.card_points(card);
             remaining_cards.push(card);
         }
     }
@@ -426,7 +562,8 @@ fn count_partial_melds(cards: &[&Card]) -> usize {
 
 // ─── Shedding Helpers ─────────────────────────────────────────────────────────
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum ShedPosition {
     ExtendLeft,    // Prepend to escala
     ExtendRight,   // Append to escala
@@ -434,22 +571,56 @@ pub enum ShedPosition {
 }
 
 /// Checks if `card` can be legally shed onto `meld`.
-/// Returns the position if valid, `None` otherwise.
-pub fn can_shed(card: &Card, meld: &[Card]) -> Option<ShedPosition> {
+/// Returns the preferred position if valid, `None` otherwise.
+pub fn can_shed(
+    card: &Card,
+    meld: &[Card],
+    limits: &JokerLimits,
+    twos: TwoBehavior,
+) -> Option<ShedPosition> {
+    shed_positions(card, meld, limits, twos).first().copied()
+}
+
+/// Every position at which `card` can be legally shed onto `meld`, preferred first.
+///
+/// A joker fits at either end of an escala; its value is implied by where it sits. A wild two
+/// fits wherever a two or a joker would.
+pub fn shed_positions(
+    card: &Card,
+    meld: &[Card],
+    limits: &JokerLimits,
+    twos: TwoBehavior,
+) -> Vec<ShedPosition> {
+    find_shed_positions(card, meld, limits, twos).unwrap_or_default()
+}
+
+fn find_shed_positions(
+    card: &Card,
+    meld: &[Card],
+    limits: &JokerLimits,
+    twos: TwoBehavior,
+) -> Option<Vec<ShedPosition>> {
     if meld.is_empty() {
         return None;
     }
 
-    let joker_count = meld.iter().filter(|c| c.is_joker()).count();
+    // Detect meld type heuristically, reading wild twos as the jokers they stand in for
+    let trio_reading = read_wild_twos(meld, twos, MeldType::Trio);
+    let is_trio = is_meld_trio(&trio_reading, limits.per_trio);
+    let meld = if is_trio {
+        trio_reading
+    } else {
+        read_wild_twos(meld, twos, MeldType::Escala)
+    };
+    let is_escala = !is_trio && is_meld_escala(&meld, limits.per_escala);
 
-    // Detect meld type heuristically
-    let is_trio = is_meld_trio(meld);
-    let is_escala = !is_trio && is_meld_escala(meld);
+    let joker_count = meld.iter().filter(|c| c.is_joker()).count();
+    let is_wild = is_wildcard(card, twos);
 
     if is_trio {
-        // Must match the trio's value; result must not have > 1 joker
-        if card.is_joker() && joker_count >= 1 {
-            return None; // would create 2 jokers
+        // Must match the trio's value; result must stay within the joker limit
+        if card.is_joker() && joker_count >= limits.per_trio {
+            return None; // would exceed the joker limit
         }
         if let Card::Standard { value, .. } = card {
             let trio_value = meld.iter().find_map(|c| {
@@ -459,13 +630,13 @@ pub fn can_shed(card: &Card, meld: &[Card]) -> Option<ShedPosition> {
                     None
                 }
             })?;
-            if value == trio_value {
-                return Some(ShedPosition::TrioExtension);
+            if value == trio_value || (is_wild && joker_count < limits.per_trio) {
+                return Some(vec![ShedPosition::TrioExtension]);
             }
         }
         if card.is_joker() {
-            // Joker can extend a valid trio if < 1 joker already present
-            return Some(ShedPosition::TrioExtension);
+            // Joker can extend a valid trio while under the joker limit
+            return Some(vec![ShedPosition::TrioExtension]);
         }
         return None;
     }
@@ -479,36 +650,42 @@ pub fn can_shed(card: &Card, meld: &[Card]) -> Option<ShedPosition> {
             }
         })?;
 
-        let first_val = escala_first_value(meld)?;
-        let last_val = escala_last_value(meld)?;
+        let first_val = escala_first_value(&meld)?;
+        let last_val = escala_last_value(&meld)?;
 
         match card {
             Card::Standard {
                 suit: card_suit,
                 value,
             } => {
-                if *card_suit != suit {
-                    return None;
-                }
                 let v_u8 = *value as u8;
                 let v = if v_u8 == 14 { 1 } else { v_u8 };
 
                 let prev_of_first = if first_val == 1 { 13 } else { first_val - 1 };
                 let next_of_last = if last_val == 13 { 1 } else { last_val + 1 };
 
-                if v == prev_of_first {
-                    return Some(ShedPosition::ExtendLeft);
+                let mut positions = Vec::new();
+                if *card_suit == suit && v == prev_of_first {
+                    positions.push(ShedPosition::ExtendLeft);
                 }
-                if v == next_of_last {
-                    return Some(ShedPosition::ExtendRight);
+                if *card_suit == suit && v == next_of_last {
+                    positions.push(ShedPosition::ExtendRight);
                 }
-                None
+                if is_wild && joker_count < limits.per_escala && meld.len() < 13 {
+                    // Anywhere a joker would go
+                    for position in [ShedPosition::ExtendRight, ShedPosition::ExtendLeft] {
+                        if !positions.contains(&position) {
+                            positions.push(position);
+                        }
+                    }
+                }
+                Some(positions)
             }
             Card::Joker => {
-                // Joker can extend at either end, only if meld has 0 jokers
-                if joker_count == 0 {
-                    // Allow both ends; pick ExtendRight by convention
-                    Some(ShedPosition::ExtendRight)
+                // Joker can extend at either end, only while under the joker limit
+                if joker_count < limits.per_escala && meld.len() < 13 {
+                    // ExtendRight is preferred when the client doesn't pick an end
+                    Some(vec![ShedPosition::ExtendRight, ShedPosition::ExtendLeft])
                 } else {
                     None
                 }
@@ -519,13 +696,51 @@ pub fn can_shed(card: &Card, meld: &[Card]) -> Option<ShedPosition> {
     }
 }
 
+/// `meld` with every two that stands in for another card turned into a joker, reading it as
+/// `kind`. A trío only keeps twos that are its own value; an escala keeps a two that sits where
+/// the run's two belongs.
+fn read_wild_twos(meld: &[Card], twos: TwoBehavior, kind: MeldType) -> Vec<Card> {
+    let is_two = |card: &Card| {
+        matches!(
+            card,
+            Card::Standard {
+                value: Value::Two,
+                ..
+            }
+        )
+    };
+    // The first card that is neither a two nor a joker fixes the run's values
+    let anchor = meld.iter().enumerate().find_map(|(i, card)| match card {
+        Card::Standard { value, .. } if !is_two(card) => Some((i, seq_val(*value as u8))),
+        _ => None,
+    });
+    let Some((anchor_idx, anchor_val)) = anchor.filter(|_| twos == TwoBehavior::WildTwo) else {
+        // Twos are never wild here, or the meld is made of twos and jokers
+        return meld.to_vec();
+    };
+
+    meld.iter()
+        .enumerate()
+        .map(|(i, card)| {
+            let value_here =
+                (anchor_val as i32 - 1 + i as i32 - anchor_idx as i32).rem_euclid(13) + 1;
+            let own_value = kind == MeldType::Escala && value_here == 2;
+            if is_two(card) && !own_value {
+                Card::Joker
+            } else {
+                *card
+            }
+        })
+        .collect()
+}
+
 /// Heuristic to detect if an existing meld on the table is a trio.
-fn is_meld_trio(meld: &[Card]) -> bool {
+fn is_meld_trio(meld: &[Card], max_jokers: usize) -> bool {
     if meld.len() < 3 {
         return false;
     }
     let jokers = meld.iter().filter(|c| c.is_joker()).count();
-    if jokers > 1 {
+    if jokers > max_jokers {
         return false;
     }
     let mut value: Option<Value> = None;
@@ -541,9 +756,9 @@ fn is_meld_trio(meld: &[Card]) -> bool {
     value.is_some()
 }
 
-/// Heuristic to detect if an existing meld on the table is an escala.
-fn is_meld_escala(meld: &[Card]) -> bool {
-    crate::engine::rules::is_valid_escala(meld)
+/// Heuristic to detect if an existing meld on the table is an escala, escalas cortas included.
+fn is_meld_escala(meld: &[Card], max_jokers: usize) -> bool {
+    crate::rules::is_valid_escala(meld, max_jokers, ESCALA_CORTA_LEN, TwoBehavior::Natural)
 }
 
 fn seq_val(v: u8) -> u8 {
@@ -588,12 +803,14 @@ fn escala_last_value(meld: &[Card]) -> Option<u8> {
 pub fn find_sheddable_cards(
     hand: &[Card],
     all_bajadas: &[(&str, &Vec<Vec<Card>>)],
+    limits: &JokerLimits,
+    twos: TwoBehavior,
 ) -> Vec<ShedAction> {
     let mut actions = Vec::new();
     for (i, card) in hand.iter().enumerate() {
         for (player_id, combos) in all_bajadas {
             for (combo_idx, combo) in combos.iter().enumerate() {
-                if let Some(position) = can_shed(card, combo) {
+                if let Some(position) = can_shed(card, combo, limits, twos) {
                     actions.push(ShedAction {
                         hand_index: i,
                         target_player_id: player_id.to_string(),
@@ -620,7 +837,7 @@ pub struct ShedAction {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::engine::card::{Suit, Value};
+    use crate::card::{Suit, Value};
 
     fn std(suit: Suit, value: Value) -> Card {
         Card::Standard { suit, value }
@@ -635,7 +852,7 @@ mod tests {
             std(Suit::Clubs, Value::Five),
             std(Suit::Spades, Value::Five),
         ];
-        let candidates = find_all_trio_candidates(&hand);
+        let candidates = find_all_trio_candidates(&hand, 1, TwoBehavior::Natural);
         assert!(!candidates.is_empty(), "Should find at least one trio");
         assert!(candidates.iter().all(|c| c.meld_type == MeldType::Trio));
     }
@@ -647,7 +864,7 @@ mod tests {
             std(Suit::Clubs, Value::Five),
             Card::Joker,
         ];
-        let candidates = find_all_trio_candidates(&hand);
+        let candidates = find_all_trio_candidates(&hand, 1, TwoBehavior::Natural);
         assert!(!candidates.is_empty(), "Should find joker-enhanced trio");
     }
 
@@ -655,7 +872,7 @@ mod tests {
     fn trio_rejects_when_no_pair_plus_joker() {
         // Only 1 standard card + joker: can't form trio
         let hand = vec![std(Suit::Hearts, Value::Five), Card::Joker];
-        let candidates = find_all_trio_candidates(&hand);
+        let candidates = find_all_trio_candidates(&hand, 1, TwoBehavior::Natural);
         assert!(
             candidates.is_empty(),
             "Should not form trio with <2 standard cards"
@@ -670,7 +887,7 @@ mod tests {
             std(Suit::Hearts, Value::Five), // idx 1
             std(Suit::Clubs, Value::Five),  // idx 2
         ];
-        let candidates = find_all_trio_candidates(&hand);
+        let candidates = find_all_trio_candidates(&hand, 1, TwoBehavior::Natural);
         // All candidates must have non-overlapping indices per candidate
         for c in &candidates {
             let unique: std::collections::HashSet<usize> = c.card_indices.iter().cloned().collect();
@@ -689,7 +906,7 @@ mod tests {
             std(Suit::Clubs, Value::Seven),  // idx 1
             std(Suit::Spades, Value::Seven), // idx 2
         ];
-        let candidates = find_all_trio_candidates(&hand);
+        let candidates = find_all_trio_candidates(&hand, 1, TwoBehavior::Natural);
         // The 3-card trio should have mask 0b111 = 7
         assert!(candidates.iter().any(|c| c.mask == 0b111));
     }
@@ -704,7 +921,7 @@ mod tests {
             std(Suit::Hearts, Value::Five),
             std(Suit::Hearts, Value::Six),
         ];
-        let candidates = find_all_escala_candidates(&hand);
+        let candidates = find_all_escala_candidates(&hand, 1, MIN_ESCALA_LEN, TwoBehavior::Natural);
         assert!(!candidates.is_empty(), "Should find the escala");
         assert!(candidates.iter().all(|c| c.meld_type == MeldType::Escala));
     }
@@ -717,7 +934,7 @@ mod tests {
             Card::Joker,
             std(Suit::Hearts, Value::Six),
         ];
-        let candidates = find_all_escala_candidates(&hand);
+        let candidates = find_all_escala_candidates(&hand, 1, MIN_ESCALA_LEN, TwoBehavior::Natural);
         assert!(!candidates.is_empty(), "Should find joker-gap escala");
     }
 
@@ -729,7 +946,7 @@ mod tests {
             std(Suit::Hearts, Value::Five),
             std(Suit::Hearts, Value::Six),
         ];
-        let candidates = find_all_escala_candidates(&hand);
+        let candidates = find_all_escala_candidates(&hand, 1, MIN_ESCALA_LEN, TwoBehavior::Natural);
         // No escala should span Hearts and Spades
         for c in &candidates {
             if c.card_indices.contains(&1) {
@@ -749,7 +966,7 @@ mod tests {
             std(Suit::Hearts, Value::Ace),   // idx 3
             std(Suit::Hearts, Value::Two),   // idx 4
         ];
-        let candidates = find_all_escala_candidates(&hand);
+        let candidates = find_all_escala_candidates(&hand, 1, MIN_ESCALA_LEN, TwoBehavior::Natural);
 
         assert!(
             candidates.iter().any(|c| {
@@ -785,7 +1002,7 @@ mod tests {
             std(Suit::Clubs, Value::Five),
             std(Suit::Clubs, Value::Six),
         ];
-        let candidates = find_all_escala_candidates(&hand);
+        let candidates = find_all_escala_candidates(&hand, 1, MIN_ESCALA_LEN, TwoBehavior::Natural);
         let masks: Vec<HandMask> = candidates.iter().map(|c| c.mask).collect();
         let unique: std::collections::HashSet<HandMask> = masks.iter().cloned().collect();
         assert_eq!(
@@ -814,7 +1031,7 @@ mod tests {
             std(Suit::Hearts, Value::Three),  // 10
             std(Suit::Clubs, Value::Six),     // 11
         ];
-        let result = find_best_bajada(&hand, 2, 0, false);
+        let result = find_best_bajada(&hand, 2, 0, MIN_ESCALA_LEN, false, &RuleSet::default());
         assert!(result.is_some(), "Should find 2 trios for round 1");
         let melds = result.unwrap();
         assert_eq!(melds.len(), 2);
@@ -841,7 +1058,7 @@ mod tests {
             std(Suit::Diamonds, Value::Jack), // 10
             std(Suit::Clubs, Value::Ten),     // 11
         ];
-        let result = find_best_bajada(&hand, 1, 1, false);
+        let result = find_best_bajada(&hand, 1, 1, MIN_ESCALA_LEN, false, &RuleSet::default());
         assert!(result.is_some(), "Should find 1 trio + 1 escala");
         let melds = result.unwrap();
         assert_eq!(melds.len(), 2);
@@ -858,7 +1075,7 @@ mod tests {
             std(Suit::Clubs, Value::Three),
             std(Suit::Spades, Value::Four),
         ];
-        let result = find_best_bajada(&hand, 2, 0, false);
+        let result = find_best_bajada(&hand, 2, 0, MIN_ESCALA_LEN, false, &RuleSet::default());
         assert!(
             result.is_none(),
             "Shouldn't find 2 trios in 3 unrelated cards"
@@ -882,7 +1099,7 @@ mod tests {
             std(Suit::Clubs, Value::Ace),
             std(Suit::Spades, Value::Two),
         ];
-        let result = find_best_bajada(&hand, 2, 0, false);
+        let result = find_best_bajada(&hand, 2, 0, MIN_ESCALA_LEN, false, &RuleSet::default());
         if let Some(melds) = result {
             let total_cards: usize = melds.iter().map(|m| m.card_indices.len()).sum();
             let unique: std::collections::HashSet<usize> = melds
@@ -920,7 +1137,7 @@ mod tests {
         ];
         // With minimize=true, should prefer trio of Fives + trio of Twos → leaves Aces (high pts) unheld...
         // Actually let's just verify it returns SOME valid solution correctly and 2 melds don't overlap
-        let result = find_best_bajada(&hand, 2, 0, true);
+        let result = find_best_bajada(&hand, 2, 0, MIN_ESCALA_LEN, true, &RuleSet::default());
         assert!(result.is_some());
         let melds = result.unwrap();
         assert_eq!(melds.len(), 2);
@@ -937,7 +1154,10 @@ mod tests {
             std(Suit::Spades, Value::Seven),
         ];
         let card = std(Suit::Diamonds, Value::Seven);
-        assert_eq!(can_shed(&card, &meld), Some(ShedPosition::TrioExtension));
+        assert_eq!(
+            can_shed(&card, &meld, &JokerLimits::default(), TwoBehavior::Natural),
+            Some(ShedPosition::TrioExtension)
+        );
     }
 
     #[test]
@@ -948,7 +1168,10 @@ mod tests {
             std(Suit::Spades, Value::Seven),
         ];
         let card = std(Suit::Diamonds, Value::Eight);
-        assert_eq!(can_shed(&card, &meld), None);
+        assert_eq!(
+            can_shed(&card, &meld, &JokerLimits::default(), TwoBehavior::Natural),
+            None
+        );
     }
 
     #[test]
@@ -960,7 +1183,10 @@ mod tests {
             std(Suit::Hearts, Value::Six),
         ];
         let card = std(Suit::Hearts, Value::Seven);
-        assert_eq!(can_shed(&card, &meld), Some(ShedPosition::ExtendRight));
+        assert_eq!(
+            can_shed(&card, &meld, &JokerLimits::default(), TwoBehavior::Natural),
+            Some(ShedPosition::ExtendRight)
+        );
     }
 
     #[test]
@@ -972,7 +1198,10 @@ mod tests {
             std(Suit::Clubs, Value::Eight),
         ];
         let card = std(Suit::Clubs, Value::Four);
-        assert_eq!(can_shed(&card, &meld), Some(ShedPosition::ExtendLeft));
+        assert_eq!(
+            can_shed(&card, &meld, &JokerLimits::default(), TwoBehavior::Natural),
+            Some(ShedPosition::ExtendLeft)
+        );
     }
 
     #[test]
@@ -984,7 +1213,10 @@ mod tests {
             std(Suit::Hearts, Value::Six),
         ];
         let card = std(Suit::Clubs, Value::Seven); // wrong suit
-        assert_eq!(can_shed(&card, &meld), None);
+        assert_eq!(
+            can_shed(&card, &meld, &JokerLimits::default(), TwoBehavior::Natural),
+            None
+        );
     }
 
     #[test]
@@ -996,9 +1228,188 @@ mod tests {
         ];
         let joker = Card::Joker;
         assert_eq!(
-            can_shed(&joker, &meld),
+            can_shed(&joker, &meld, &JokerLimits::default(), TwoBehavior::Natural),
             None,
             "Should not allow 2nd joker in trio"
         );
     }
+
+    #[test]
+    fn joker_limits_widen_candidates() {
+        let hand = vec![std(Suit::Hearts, Value::Seven), Card::Joker, Card::Joker];
+        assert!(find_all_trio_candidates(&hand, 1, TwoBehavior::Natural).is_empty());
+        assert_eq!(
+            find_all_trio_candidates(&hand, 2, TwoBehavior::Natural).len(),
+            1
+        );
+
+        let hand = vec![
+            std(Suit::Hearts, Value::Three),
+            Card::Joker,
+            Card::Joker,
+            std(Suit::Hearts, Value::Six),
+        ];
+        assert!(
+            find_all_escala_candidates(&hand, 1, MIN_ESCALA_LEN, TwoBehavior::Natural).is_empty()
+        );
+        assert_eq!(
+            find_all_escala_candidates(&hand, 2, MIN_ESCALA_LEN, TwoBehavior::Natural).len(),
+            1
+        );
+    }
+
+    #[test]
+    fn shed_allows_second_joker_when_limit_permits() {
+        let meld = vec![
+            std(Suit::Hearts, Value::Seven),
+            Card::Joker,
+            std(Suit::Spades, Value::Seven),
+        ];
+        let limits = JokerLimits {
+            per_trio: 2,
+            ..JokerLimits::default()
+        };
+        assert_eq!(
+            can_shed(&Card::Joker, &meld, &limits, TwoBehavior::Natural),
+            Some(ShedPosition::TrioExtension)
+        );
+    }
+
+    #[test]
+    fn best_bajada_adds_extra_melds_when_allowed() {
+        let mut hand = vec![
+            std(Suit::Hearts, Value::Seven),
+            std(Suit::Clubs, Value::Seven),
+            std(Suit::Spades, Value::Seven),
+            std(Suit::Hearts, Value::Nine),
+            std(Suit::Clubs, Value::Nine),
+            std(Suit::Diamonds, Value::Nine),
+            std(Suit::Spades, Value::Three),
+            std(Suit::Spades, Value::Four),
+            std(Suit::Spades, Value::Five),
+            std(Suit::Spades, Value::Six),
+            std(Suit::Diamonds, Value::King),
+        ];
+        let rules = RuleSet {
+            extra_melds: true,
+            ..RuleSet::default()
+        };
+        let count = |hand: &[Card], rules: &RuleSet, minimize| {
+            find_best_bajada(hand, 2, 0, MIN_ESCALA_LEN, minimize, rules).map(|m| m.len())
+        };
+        assert_eq!(count(&hand, &RuleSet::default(), true), Some(2));
+        assert_eq!(count(&hand, &rules, false), Some(2));
+        assert_eq!(count(&hand, &rules, true), Some(3));
+
+        // Without the king the escala would leave nothing to discard
+        hand.pop();
+        assert_eq!(count(&hand, &rules, true), Some(2));
+    }
+
+    #[test]
+    fn best_bajada_respects_bajada_joker_limit() {
+        let hand = vec![
+            std(Suit::Hearts, Value::Seven),
+            Card::Joker,
+            Card::Joker,
+            std(Suit::Clubs, Value::Nine),
+            Card::Joker,
+            std(Suit::Spades, Value::Nine),
+        ];
+        let rules = RuleSet {
+            jokers: JokerLimits {
+                per_trio: 2,
+                per_escala: 1,
+                per_bajada: 2,
+            },
+            ..RuleSet::default()
+        };
+        assert!(find_best_bajada(&hand, 2, 0, MIN_ESCALA_LEN, true, &rules).is_none());
+
+        let rules = RuleSet {
+            jokers: JokerLimits {
+                per_bajada: 3,
+                ..rules.jokers
+            },
+            ..rules
+        };
+        assert!(find_best_bajada(&hand, 2, 0, MIN_ESCALA_LEN, true, &rules).is_some());
+    }
+
+    #[test]
+    fn joker_can_extend_either_end_of_escala() {
+        let meld = vec![
+            std(Suit::Hearts, Value::Three),
+            std(Suit::Hearts, Value::Four),
+            std(Suit::Hearts, Value::Five),
+            std(Suit::Hearts, Value::Six),
+        ];
+        assert_eq!(
+            shed_positions(
+                &Card::Joker,
+                &meld,
+                &JokerLimits::default(),
+                TwoBehavior::Natural
+            ),
+            vec![ShedPosition::ExtendRight, ShedPosition::ExtendLeft]
+        );
+        assert!(
+            shed_positions(
+                &std(Suit::Hearts, Value::Nine),
+                &meld,
+                &JokerLimits::default(),
+                TwoBehavior::Natural
+            )
+            .is_empty()
+        );
+    }
+
+    #[test]
+    fn wild_twos_join_melds_and_extend_them() {
+        let wild = TwoBehavior::WildTwo;
+        let hand = vec![
+            std(Suit::Hearts, Value::Nine),
+            std(Suit::Spades, Value::Nine),
+            std(Suit::Clubs, Value::Two),
+        ];
+        assert!(find_all_trio_candidates(&hand, 1, TwoBehavior::Natural).is_empty());
+        assert_eq!(find_all_trio_candidates(&hand, 1, wild).len(), 1);
+
+        let hand = vec![
+            std(Suit::Hearts, Value::Four),
+            std(Suit::Hearts, Value::Five),
+            std(Suit::Hearts, Value::Seven),
+            std(Suit::Clubs, Value::Two),
+        ];
+        let escalas = find_all_escala_candidates(&hand, 1, MIN_ESCALA_LEN, wild);
+        assert_eq!(escalas.len(), 1);
+        assert_eq!(escalas[0].card_indices, vec![0, 1, 3, 2]);
+
+        // The two already stands in for the six, so the escala has no joker to spare
+        let meld = vec![
+            std(Suit::Hearts, Value::Four),
+            std(Suit::Hearts, Value::Five),
+            std(Suit::Clubs, Value::Two),
+            std(Suit::Hearts, Value::Seven),
+        ];
+        let limits = JokerLimits::default();
+        let eight = std(Suit::Hearts, Value::Eight);
+        assert_eq!(
+            can_shed(&eight, &meld, &limits, wild),
+            Some(ShedPosition::ExtendRight)
+        );
+        assert_eq!(can_shed(&Card::Joker, &meld, &limits, wild), None);
+
+        let natural = [
+            std(Suit::Hearts, Value::Four),
+            std(Suit::Hearts, Value::Five),
+        ];
+        let meld = [&natural[..], &[std(Suit::Hearts, Value::Six)]].concat();
+        let two = std(Suit::Spades, Value::Two);
+        assert!(shed_positions(&two, &meld, &limits, TwoBehavior::Natural).is_empty());
+        assert_eq!(
+            shed_positions(&two, &meld, &limits, wild),
+            vec![ShedPosition::ExtendRight, ShedPosition::ExtendLeft]
+        );
+    }
 }
@@ -0,0 +1,263 @@
+use crate::card::{Card, Suit, Value};
+use rand::seq::SliceRandom;
+// use rand::thread_rng; // rand 0.9 removed this from root
+use rand::rng;
+use rand::{Rng, SeedableRng, rngs::ChaCha12Rng};
+
+/// Jokers in a standard Carioca deck, two from each pack.
+pub const STANDARD_JOKERS: usize = 4;
+
+#[derive(Clone)]
+pub struct Deck {
+    cards: Vec<Card>,
+}
+
+impl Deck {
+    /// Creates a standard Carioca deck consisting of two standard 52-card decks
+    /// plus 4 jokers, totaling 108 cards.
+    pub fn new() -> Self {
+        Self::with_jokers(STANDARD_JOKERS)
+    }
+
+    /// Two standard 52-card decks plus `jokers` jokers, for tables that play with more or
+    /// fewer than [`STANDARD_JOKERS`].
+    pub fn with_jokers(jokers: usize) -> Self {
+        let mut cards = Vec::with_capacity(104 + jokers);
+
+        for _ in 0..2 {
+            for suit in [Suit::Hearts, Suit::Diamonds, Suit::Clubs, Suit::Spades] {
+                for value in [
+                    Value::Two,
+                    Value::Three,
+                    Value::Four,
+                    Value::Five,
+                    Value::Six,
+                    Value::Seven,
+                    Value::Eight,
+                    Value::Nine,
+                    Value::Ten,
+                    Value::Jack,
+                    Value::Queen,
+                    Value::King,
+                    Value::Ace,
+                ] {
+                    cards.push(Card::Standard { suit, value });
+                }
+            }
+        }
+        cards.extend(std::iter::repeat_n(Card::Joker, jokers));
+
+        Self { cards }
+    }
+
+    /// A shuffled deck whose first draws are `top`, in that order, for scripted deals.
+    /// Fails if `top` asks for more copies of a card than the deck holds.
+    pub fn stacked(top: &[Card]) -> Result<Self, &'static str> {
+        let mut deck = Self::new();
+        deck.shuffle();
+        for card in top {
+            let i = deck
+                .cards
+                .iter()
+                .position(|c| c == card)
+                .ok_or("Scripted deck uses a card more often than the deck holds")?;
+            deck.cards.remove(i);
+        }
+        // `draw` pops from the end, so the first scripted card goes last
+        deck.cards.extend(top.iter().rev());
+        Ok(deck)
+    }
+
+    /// A shuffled deck of whatever `used` leaves over from a deck with `jokers` jokers, e.g.
+    /// after laying out a position by hand. Fails if `used` holds more copies of a card than
+    /// the deck does.
+    pub fn remainder(used: &[Card], jokers: usize) -> Result<Self, &'static str> {
+        let mut deck = Self::with_jokers(jokers);
+        for card in used {
+            let i = deck
+                .cards
+                .iter()
+                .position(|c| c == card)
+                .ok_or("A card is used more often than the deck holds")?;
+            deck.cards.remove(i);
+        }
+        deck.shuffle();
+        Ok(deck)
+    }
+
+    /// Exactly these cards; the last one is drawn first.
+    pub fn from_cards(cards: Vec<Card>) -> Self {
+        Self { cards }
+    }
+
+    pub fn shuffle(&mut self) {
+        let mut rng = rng();
+        self.cards.shuffle(&mut rng);
+    }
+
+    /// Shuffles reproducibly: the same seed always gives the same order, whichever version
+    /// of rand the server is built with.
+    pub fn shuffle_with_seed(&mut self, seed: u64) {
+        self.shuffle_with(&mut ChaCha12Rng::seed_from_u64(seed));
+    }
+
+    /// Shuffles with the positions `rng` picks, by [`fisher_yates`] rather than rand's own
+    /// shuffle, which may change between releases.
+    pub fn shuffle_with<R: Rng + ?Sized>(&mut self, rng: &mut R) {
+        fisher_yates(&mut self.cards, rng);
+    }
+
+    pub fn draw(&mut self) -> Option<Card> {
+        self.cards.pop()
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.cards.len()
+    }
+
+    /// The undrawn cards; the next draw is the last one.
+    pub fn cards(&self) -> &[Card] {
+        &self.cards
+    }
+}
+
+impl Default for Deck {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// rand 0.10's slice shuffle, written out so seeded decks keep their order: each swap
+/// position is taken from a `u32` drawn for several positions at once, exactly as rand did
+/// when the first seeds were stored.
+fn fisher_yates<T, R: Rng + ?Sized>(items: &mut [T], rng: &mut R) {
+    let (mut chunk, mut chunk_left) = (0u32, 1u8);
+    for i in 0..items.len() {
+        let choices = i as u32 + 1;
+        chunk_left = match chunk_left.checked_sub(1) {
+            Some(left) => left,
+            None => {
+                let (bound, positions) = chunk_bound(choices);
+                chunk = below(rng, bound);
+                positions - 1
+            }
+        };
+        let j = if chunk_left == 0 {
+            chunk
+        } else {
+            let j = chunk % choices;
+            chunk /= choices;
+            j
+        };
+        items.swap(i, j as usize);
+    }
+}
+
+/// The product of `choices`, `choices + 1`, ... up to the last that fits a `u32`, and how
+/// many factors that is: one draw below it picks that many positions in a row.
+fn chunk_bound(choices: u32) -> (u32, u8) {
+    let (mut product, mut next) = (choices, choices + 1);
+    while let Some(p) = product.checked_mul(next) {
+        product = p;
+        next += 1;
+    }
+    (product, (next - choices) as u8)
+}
+
+/// A number below `bound` by Canon's method, as rand samples a `u32` range.
+fn below<R: Rng + ?Sized>(rng: &mut R, bound: u32) -> u32 {
+    let widen = |x: u32| {
+        let wide = u64::from(x) * u64::from(bound);
+        ((wide >> 32) as u32, wide as u32)
+    };
+    let (mut result, low) = widen(rng.next_u32());
+    if low > bound.wrapping_neg() {
+        let (carry, _) = widen(rng.next_u32());
+        result += u32::from(low.checked_add(carry).is_none());
+    }
+    result
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deck_creation() {
+        let deck = Deck::new();
+        assert_eq!(deck.remaining(), 108);
+
+        let jokers = deck.cards.iter().filter(|c| c.is_joker()).count();
+        assert_eq!(jokers, 4);
+    }
+
+    #[test]
+    fn test_stacked_deck_draws_top_first() {
+        let top = [
+            Card::Joker,
+            Card::Standard {
+                suit: Suit::Hearts,
+                value: Value::Seven,
+            },
+        ];
+        let mut deck = Deck::stacked(&top).unwrap();
+        assert_eq!(deck.remaining(), 108);
+        assert_eq!(deck.draw(), Some(top[0]));
+        assert_eq!(deck.draw(), Some(top[1]));
+
+        assert!(Deck::stacked(&[Card::Joker; 5]).is_err());
+    }
+
+    #[test]
+    fn test_joker_count_is_configurable() {
+        for jokers in [0, 2, 6] {
+            let deck = Deck::with_jokers(jokers);
+            assert_eq!(deck.remaining(), 104 + jokers);
+            assert_eq!(deck.cards.iter().filter(|c| c.is_joker()).count(), jokers);
+        }
+        assert!(Deck::remainder(&[Card::Joker; 3], 2).is_err());
+        assert_eq!(
+            Deck::remainder(&[Card::Joker; 3], 6).unwrap().remaining(),
+            107
+        );
+    }
+
+    #[test]
+    fn test_seeded_shuffle_is_reproducible() {
+        let mut first = Deck::new();
+        let mut second = Deck::new();
+        first.shuffle_with_seed(42);
+        second.shuffle_with_seed(42);
+        assert_eq!(first.cards(), second.cards());
+
+        second.shuffle_with_seed(43);
+        assert_ne!(first.cards(), second.cards());
+    }
+
+    #[test]
+    fn test_seeded_shuffle_deals_a_fixed_order() {
+        let mut deck = Deck::new();
+        deck.shuffle_with_seed(42);
+        let top: Vec<String> = deck
+            .cards()
+            .iter()
+            .rev()
+            .take(10)
+            .map(Card::to_string)
+            .collect();
+        assert_eq!(
+            top,
+            ["4♦", "A♠", "Q♠", "J♥", "10♠", "A♥", "3♣", "2♣", "🃏", "7♦"],
+            "Changing this order breaks the replays of every stored game"
+        );
+    }
+
+    #[test]
+    fn test_deck_draw() {
+        let mut deck = Deck::new();
+        let initial_len = deck.remaining();
+
+        let card = deck.draw();
+        assert!(card.is_some());
+        assert_eq!(deck.remaining(), initial_len - 1);
+    }
+}
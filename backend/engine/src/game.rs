@@ -0,0 +1,2052 @@
+use crate::card::Card;
+use crate::combo_finder::{ShedPosition, shed_positions};
+use crate::deck::Deck;
+use crate::hand_sort::{SortBy, sorted_hand};
+use crate::rule_set::{CariocaBonus, RuleSet, TieBreak, TurnRotation};
+use crate::shuffle::ShuffleSeed;
+use crate::timing::GameTiming;
+use rand::{RngExt, SeedableRng, rngs::ChaCha12Rng};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::time::Instant;
+
+/// How many of the latest actions [`GameState::recent_actions`] holds.
+pub const RECENT_ACTIONS_KEPT: usize = 10;
+
+/// Tracks the most recent action taken by any player, broadcast to all clients.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LastAction {
+    pub player_id: String,
+    pub action_type: String,
+    pub card: Option<Card>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RoundType {
+    TwoTrios,          // 2 tríos (6 cartas)
+    OneTrioOneEscala,  // 1 trío y 1 escala (7 cartas)
+    TwoEscalas,        // 2 escalas (8 cartas)
+    ThreeTrios,        // 3 tríos (9 cartas)
+    TwoTriosOneEscala, // 2 tríos y 1 escala (10 cartas)
+    OneTrioTwoEscalas, // 1 trío y 2 escalas(11 cartas)
+    ThreeEscalas,      // 3 escalas (12 cartas)
+    FourTrios,         // 4 tríos (12 cartas)
+    EscalaReal,        // Escala completa (12/13 cartas depending on variation)
+}
+
+impl RoundType {
+    pub fn all_rounds() -> Vec<RoundType> {
+        vec![
+            RoundType::TwoTrios,
+            RoundType::OneTrioOneEscala,
+            RoundType::TwoEscalas,
+            RoundType::ThreeTrios,
+            RoundType::TwoTriosOneEscala,
+            RoundType::OneTrioTwoEscalas,
+            RoundType::ThreeEscalas,
+            RoundType::FourTrios,
+            RoundType::EscalaReal,
+        ]
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            RoundType::TwoTrios => "2 Tríos (6 cards)",
+            RoundType::OneTrioOneEscala => "1 Trío, 1 Escala (7 cards)",
+            RoundType::TwoEscalas => "2 Escalas (8 cards)",
+            RoundType::ThreeTrios => "3 Tríos (9 cards)",
+            RoundType::TwoTriosOneEscala => "2 Tríos, 1 Escala (10 cards)",
+            RoundType::OneTrioTwoEscalas => "1 Trío, 2 Escalas (11 cards)",
+            RoundType::ThreeEscalas => "3 Escalas (12 cards)",
+            RoundType::FourTrios => "4 Tríos (12 cards)",
+            RoundType::EscalaReal => "Escala Real (13 cards, same suit)",
+        }
+    }
+
+    pub fn get_requirements(&self) -> (usize, usize) {
+        // Returns (required_trios, required_escalas)
+        match self {
+            RoundType::TwoTrios => (2, 0),
+            RoundType::OneTrioOneEscala => (1, 1),
+            RoundType::TwoEscalas => (0, 2),
+            RoundType::ThreeTrios => (3, 0),
+            RoundType::TwoTriosOneEscala => (2, 1),
+            RoundType::OneTrioTwoEscalas => (1, 2),
+            RoundType::ThreeEscalas => (0, 3),
+            RoundType::FourTrios => (4, 0),
+            RoundType::EscalaReal => (0, 1), // Special case 13 cards
+        }
+    }
+}
+
+/// How the round winner got rid of their last card.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WinningPlay {
+    Discard,
+    Shed,
+    /// Dropped the whole hand and got rid of the last card in the same turn
+    Carioca,
+}
+
+/// One player's activity over a finished round, for the end-of-round screen.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlayerRoundStats {
+    pub player_id: String,
+    pub turns_taken: u32,
+    pub dropped_hand: bool,
+    pub cards_shed: u32,
+    /// Penalty points the player got out of their hand by shedding
+    pub points_shed: u32,
+    /// Mean time the player took per finished turn; `None` if they finished none
+    pub average_turn_ms: Option<u64>,
+}
+
+/// The cards a player was left holding when a round ended, which their penalty was scored on.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FinalHand {
+    pub player_id: String,
+    pub cards: Vec<Card>,
+}
+
+#[derive(Debug, Clone)]
+pub struct RoundEndResult {
+    pub finished_round_index: usize,
+    pub finished_round_name: String,
+    /// Empty when the round ended in a stalemate
+    pub winner_id: String,
+    pub player_scores: Vec<(String, u32, u32)>,
+    pub next_round_index: usize,
+    pub next_round_name: String,
+    pub is_game_over: bool,
+    pub is_stalemate: bool,
+    pub player_stats: Vec<PlayerRoundStats>,
+    /// In seat order; empty for whoever went out
+    pub final_hands: Vec<FinalHand>,
+    /// `None` for a stalemate
+    pub winning_play: Option<WinningPlay>,
+    pub round_duration_ms: u64,
+    /// Since the game began, including breaks between rounds
+    pub game_duration_ms: u64,
+    /// How a tie for the lowest total was settled; only at game over, and only if the rules
+    /// break ties
+    pub tie_break: Option<TieBreakOutcome>,
+}
+
+/// The result of applying [`RuleSet::tie_break`] to players tied for the win.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TieBreakOutcome {
+    pub policy: TieBreak,
+    /// Everyone who was on the lowest total, in seat order
+    pub tied: Vec<String>,
+    /// `None` if the policy couldn't separate them either
+    pub winner_id: Option<String>,
+}
+
+#[derive(Clone)]
+pub struct GameState {
+    pub players: Vec<PlayerState>,
+    pub current_round: RoundType,
+    pub round_index: usize,
+    pub current_turn: usize, // Index in the players array
+    pub deck: Deck,
+    pub discard_pile: Vec<Card>,
+    pub is_game_over: bool,
+    pub is_waiting_for_next_round: bool,
+    pub last_action: Option<LastAction>,
+    /// The last [`RECENT_ACTIONS_KEPT`] actions, oldest first and across rounds, so a
+    /// client that missed some updates can catch up
+    pub recent_actions: VecDeque<LastAction>,
+    pub rules: RuleSet,
+    /// Turns completed since the deck ran out this round
+    pub stalled_turns: u32,
+    pub timing: GameTiming,
+    /// What the current round's deck was shuffled with, for games dealt from `game_seed`;
+    /// `None` otherwise
+    pub deck_seed: Option<u64>,
+    /// Set while the table is on hold (a player dropped, or everyone agreed to a break);
+    /// moves and readying up are refused
+    pub is_paused: bool,
+    /// When set, each round's shuffle seed is derived from it, so a whole game follows from
+    /// this one seed. For simulations and tests: 64 bits can be searched for from the cards
+    /// in view, so real tables are dealt from `shuffle_key` instead
+    pub game_seed: Option<u64>,
+    /// Without a `game_seed`, the secret every round's [`ShuffleSeed`] is derived from,
+    /// drawn from the CSPRNG at the first deal
+    pub shuffle_key: Option<ShuffleSeed>,
+    /// What the current round's deck was shuffled with, for games dealt from `shuffle_key`
+    pub round_seed: Option<ShuffleSeed>,
+    /// Who was tied for the win when sudden-death rounds began, see [`TieBreak::SuddenDeath`]
+    pub sudden_death: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlayerState {
+    pub id: String,
+    /// In the player's chosen order: draws land at the end, and cards leaving the hand
+    /// never disturb the relative order of the rest.
+    pub hand: Vec<Card>,
+    pub points: u32,
+    pub has_dropped_hand: bool, // "bajado"
+    pub dropped_combinations: Vec<Vec<Card>>,
+    pub turns_played: u32, // How many full turns (draw+discard) this player has completed this round
+    pub cards_shed: u32,
+    pub points_shed: u32,
+    pub has_drawn_this_turn: bool,
+    pub dropped_hand_this_turn: bool,
+    pub is_ready_for_next_round: bool,
+    /// Cards forced into the hand this round for running out of time
+    pub penalty_cards: u32,
+    /// Rounds this game the player went out in
+    pub rounds_won: u32,
+    /// `penalty_cards` summed over the game
+    pub game_penalty_cards: u32,
+}
+
+/// Moves that can't be made are refused with a stable message key such as
+/// `not_your_turn`, which the server's catalog turns into the player's language.
+impl GameState {
+    pub fn new(player_ids: Vec<String>) -> Self {
+        Self::with_rules(player_ids, RuleSet::default())
+    }
+
+    pub fn with_rules(player_ids: Vec<String>, rules: RuleSet) -> Self {
+        let timing = GameTiming::new(player_ids.len(), Instant::now());
+        let players = player_ids
+            .into_iter()
+            .map(|id| PlayerState {
+                id,
+                hand: Vec::new(),
+                points: 0,
+                has_dropped_hand: false,
+                dropped_combinations: Vec::new(),
+                turns_played: 0,
+                cards_shed: 0,
+                points_shed: 0,
+                has_drawn_this_turn: false,
+                dropped_hand_this_turn: false,
+                is_ready_for_next_round: false,
+                penalty_cards: 0,
+                rounds_won: 0,
+                game_penalty_cards: 0,
+            })
+            .collect();
+
+        Self {
+            players,
+            current_round: RoundType::TwoTrios,
+            round_index: 0,
+            current_turn: 0,
+            deck: Deck::with_jokers(rules.deck_jokers),
+            discard_pile: Vec::new(),
+            is_game_over: false,
+            is_waiting_for_next_round: false,
+            last_action: None,
+            recent_actions: VecDeque::new(),
+            rules,
+            stalled_turns: 0,
+            timing,
+            deck_seed: None,
+            is_paused: false,
+            game_seed: None,
+            shuffle_key: None,
+            round_seed: None,
+            sudden_death: None,
+        }
+    }
+
+    pub fn start_round(&mut self) {
+        let mut deck = Deck::with_jokers(self.rules.deck_jokers);
+        let Some(game_seed) = self.game_seed else {
+            let key = *self.shuffle_key.get_or_insert_with(ShuffleSeed::random);
+            let seed = key.for_round(self.round_index);
+            seed.shuffle(&mut deck);
+            self.start_round_with_deck(deck);
+            self.round_seed = Some(seed);
+            return;
+        };
+        // The round index'th draw, so rounds differ and no two game seeds overlap
+        let mut rng = ChaCha12Rng::seed_from_u64(game_seed);
+        (0..self.round_index).for_each(|_| {
+            rng.random::<u64>();
+        });
+        let seed = rng.random();
+        deck.shuffle_with_seed(seed);
+        self.start_round_with_deck(deck);
+        self.deck_seed = Some(seed);
+    }
+
+    /// Deals the round from `deck` as given, e.g. a stacked deck for a scripted tutorial.
+    pub fn start_round_with_deck(&mut self, deck: Deck) {
+        self.deck = deck;
+        self.deck_seed = None;
+        self.round_seed = None;
+        self.discard_pile.clear();
+        self.last_action = None;
+        self.stalled_turns = 0;
+        self.timing.start_round(Instant::now());
+
+        for player in &mut self.players {
+            player.hand.clear();
+            player.has_dropped_hand = false;
+            player.dropped_combinations.clear();
+            player.turns_played = 0;
+            player.cards_shed = 0;
+            player.points_shed = 0;
+            player.has_drawn_this_turn = false;
+            player.dropped_hand_this_turn = false;
+            player.is_ready_for_next_round = false;
+            player.penalty_cards = 0;
+            // Deal 12 cards to each player
+            for _ in 0..12 {
+                if let Some(card) = self.deck.draw() {
+                    player.hand.push(card);
+                }
+            }
+        }
+
+        // Top card to discard pile
+        if let Some(card) = self.deck.draw() {
+            self.discard_pile.push(card);
+        }
+    }
+
+    fn record_action(&mut self, action: LastAction) {
+        if self.recent_actions.len() == RECENT_ACTIONS_KEPT {
+            self.recent_actions.pop_front();
+        }
+        self.recent_actions.push_back(action.clone());
+        self.last_action = Some(action);
+    }
+
+    /// The discard cards everyone may see under the rule set, oldest first; the last is the top.
+    pub fn visible_discards(&self) -> &[Card] {
+        let pile = &self.discard_pile;
+        let visible = self.rules.discard_visibility.visible_count(pile.len());
+        &pile[pile.len() - visible..]
+    }
+
+    /// Player ids in the order turns pass around the table.
+    pub fn turn_order(&self) -> Vec<String> {
+        self.players.iter().map(|p| p.id.clone()).collect()
+    }
+
+    /// Who plays once the current turn ends; `None` between rounds and after the game.
+    pub fn next_player_id(&self) -> Option<&str> {
+        if self.is_waiting_for_next_round || self.is_game_over || self.players.is_empty() {
+            return None;
+        }
+        Some(&self.players[self.next_seat()].id)
+    }
+
+    fn next_seat(&self) -> usize {
+        (self.current_turn + 1) % self.players.len()
+    }
+
+    pub fn current_player(&mut self) -> Option<&mut PlayerState> {
+        let idx = self.current_turn;
+        self.players.get_mut(idx)
+    }
+
+    pub fn draw_from_deck(&mut self) -> Result<(), &'static str> {
+        if self.is_game_over {
+            return Err("game_over");
+        }
+        if self.is_paused {
+            return Err("game_paused");
+        }
+        if self.is_waiting_for_next_round {
+            return Err("waiting_for_next_round");
+        }
+
+        let card = self.deck.draw().ok_or("deck_empty")?;
+        let player = self.current_player().ok_or("invalid_turn")?;
+        if player.has_drawn_this_turn {
+            return Err("already_drew");
+        }
+
+        let pid = player.id.clone();
+        player.hand.push(card);
+        player.has_drawn_this_turn = true;
+        self.record_action(LastAction {
+            player_id: pid,
+            action_type: "drew_from_deck".to_string(),
+            card: None,
+        });
+        Ok(())
+    }
+
+    pub fn draw_from_discard(&mut self) -> Result<(), &'static str> {
+        if self.is_game_over {
+            return Err("game_over");
+        }
+        if self.is_paused {
+            return Err("game_paused");
+        }
+        if self.is_waiting_for_next_round {
+            return Err("waiting_for_next_round");
+        }
+
+        let idx = self.current_turn;
+
+        let player = self.players.get_mut(idx).ok_or("invalid_turn")?;
+        if player.has_drawn_this_turn {
+            return Err("already_drew");
+        }
+
+        // Rule: "Si un jugador se baja no puede recoger desde el mazo de descarte"
+        if player.has_dropped_hand {
+            return Err("no_discard_after_bajada");
+        }
+
+        let card = self.discard_pile.pop().ok_or("discard_pile_empty")?;
+
+        // Re-borrow mutably after the discard pile borrow is done
+        let pid = self.players[idx].id.clone();
+        self.players[idx].hand.push(card);
+        self.players[idx].has_drawn_this_turn = true;
+        self.record_action(LastAction {
+            player_id: pid,
+            action_type: "drew_from_pozo".to_string(),
+            card: Some(card),
+        });
+        Ok(())
+    }
+
+    pub fn discard(&mut self, card_index: usize) -> Result<Option<RoundEndResult>, &'static str> {
+        if self.is_game_over {
+            return Err("game_over");
+        }
+        if self.is_paused {
+            return Err("game_paused");
+        }
+        if self.is_waiting_for_next_round {
+            return Err("waiting_for_next_round");
+        }
+
+        let idx = self.current_turn;
+
+        let player = self.players.get_mut(idx).ok_or("invalid_turn")?;
+
+        if !player.has_drawn_this_turn {
+            return Err("must_draw_before_discard");
+        }
+
+        if card_index >= player.hand.len() {
+            return Err("card_index_out_of_bounds");
+        }
+
+        let card = player.hand.remove(card_index);
+        let hand_is_empty = player.hand.is_empty();
+        let went_out_in_one_move = player.dropped_hand_this_turn;
+        let pid = player.id.clone();
+
+        self.discard_pile.push(card);
+        self.record_action(LastAction {
+            player_id: pid,
+            action_type: "discarded".to_string(),
+            card: Some(card),
+        });
+
+        self.players[idx].turns_played += 1;
+        self.timing.end_turn(idx, Instant::now());
+        self.players[idx].has_drawn_this_turn = false;
+        self.players[idx].dropped_hand_this_turn = false;
+
+        // Check if player won the round (no cards left)
+        if hand_is_empty {
+            let play = if went_out_in_one_move {
+                WinningPlay::Carioca
+            } else {
+                WinningPlay::Discard
+            };
+            let result = self.finish_round(Some((idx, play)));
+            return Ok(Some(result));
+        }
+
+        Ok(self.advance_turn())
+    }
+
+    /// Ends the turn without drawing. Only possible once the deck has run out, e.g. for a
+    /// player who already dropped their hand and so cannot take from the discard pile.
+    pub fn pass_turn(&mut self) -> Result<Option<RoundEndResult>, &'static str> {
+        if self.is_game_over {
+            return Err("game_over");
+        }
+        if self.is_paused {
+            return Err("game_paused");
+        }
+        if self.is_waiting_for_next_round {
+            return Err("waiting_for_next_round");
+        }
+        if self.deck.remaining() > 0 {
+            return Err("pass_requires_empty_deck");
+        }
+
+        let idx = self.current_turn;
+        let player = self.players.get_mut(idx).ok_or("invalid_turn")?;
+        if player.has_drawn_this_turn {
+            return Err("already_drew");
+        }
+
+        let pid = player.id.clone();
+        player.turns_played += 1;
+        player.dropped_hand_this_turn = false;
+        self.timing.end_turn(idx, Instant::now());
+        self.record_action(LastAction {
+            player_id: pid,
+            action_type: "passed".to_string(),
+            card: None,
+        });
+
+        Ok(self.advance_turn())
+    }
+
+    /// Punishes the player holding the turn for running out of time: the top card of the deck
+    /// goes into their hand and the turn passes on without a discard. A player who had
+    /// already drawn simply keeps that card.
+    pub fn skip_timed_out_turn(&mut self) -> Result<Option<RoundEndResult>, &'static str> {
+        if self.is_game_over {
+            return Err("game_over");
+        }
+        if self.is_paused {
+            return Err("game_paused");
+        }
+        if self.is_waiting_for_next_round {
+            return Err("waiting_for_next_round");
+        }
+
+        let idx = self.current_turn;
+        let penalty = match self.players.get(idx).ok_or("invalid_turn")? {
+            player if player.has_drawn_this_turn => None,
+            _ => self.deck.draw(),
+        };
+        let player = &mut self.players[idx];
+        if player.has_drawn_this_turn {
+            player.penalty_cards += 1;
+            player.game_penalty_cards += 1;
+        }
+        let action_type = match penalty {
+            Some(card) => {
+                player.hand.push(card);
+                player.penalty_cards += 1;
+                player.game_penalty_cards += 1;
+                "penalty_draw"
+            }
+            // Either the card already drawn stays, or the deck is out and nothing is added
+            None => "skipped",
+        };
+        let pid = player.id.clone();
+        player.turns_played += 1;
+        player.has_drawn_this_turn = false;
+        player.dropped_hand_this_turn = false;
+        self.timing.end_turn(idx, Instant::now());
+        self.record_action(LastAction {
+            player_id: pid,
+            action_type: action_type.to_string(),
+            card: None,
+        });
+
+        Ok(self.advance_turn())
+    }
+
+    /// Hands the turn to the next player, or ends the round once the table has stalled
+    /// for `stalemate_turns` turns after the deck ran out.
+    fn advance_turn(&mut self) -> Option<RoundEndResult> {
+        if self.deck.remaining() == 0 {
+            self.stalled_turns += 1;
+            if self.rules.stalemate_turns > 0 && self.stalled_turns >= self.rules.stalemate_turns {
+                return Some(self.end_round_stalemate());
+            }
+        }
+
+        self.current_turn = self.next_seat();
+        self.timing.start_turn(Instant::now());
+        self.players[self.current_turn].has_drawn_this_turn = false;
+        self.players[self.current_turn].dropped_hand_this_turn = false;
+        None
+    }
+
+    /// Rearranges a player's hand to follow `new_hand`.
+    ///
+    /// The client may have built the order against a slightly older hand, so it is
+    /// reconciled rather than rejected: listed cards still held take the listed order,
+    /// cards the client didn't know about yet (drawn since) stay at the end, and listed
+    /// cards no longer held are ignored.
+    pub fn reorder_hand(
+        &mut self,
+        player_id: &str,
+        new_hand: Vec<Card>,
+    ) -> Result<(), &'static str> {
+        let player = self
+            .players
+            .iter_mut()
+            .find(|p| p.id == player_id)
+            .ok_or("player_not_found")?;
+
+        // Match card by card so duplicates (two identical standard cards or two jokers)
+        // are each claimed once
+        let mut unclaimed = player.hand.clone();
+        let mut ordered = Vec::with_capacity(unclaimed.len());
+        for card in &new_hand {
+            if let Some(i) = unclaimed.iter().position(|c| c == card) {
+                ordered.push(unclaimed.remove(i));
+            }
+        }
+        ordered.extend(unclaimed);
+
+        player.hand = ordered;
+        Ok(())
+    }
+
+    /// Rearranges a player's hand automatically; see [`SortBy`] for the modes.
+    pub fn sort_hand(&mut self, player_id: &str, by: SortBy) -> Result<(), &'static str> {
+        let (limits, twos) = (self.rules.jokers, self.rules.twos);
+        let player = self
+            .players
+            .iter_mut()
+            .find(|p| p.id == player_id)
+            .ok_or("player_not_found")?;
+
+        player.hand = sorted_hand(&player.hand, by, &limits, twos);
+        Ok(())
+    }
+
+    pub fn drop_hand(
+        &mut self,
+        player_id: &str,
+        combinations: Vec<Vec<Card>>,
+    ) -> Result<(), &'static str> {
+        if self.is_game_over {
+            return Err("game_over");
+        }
+        if self.is_paused {
+            return Err("game_paused");
+        }
+        if self.is_waiting_for_next_round {
+            return Err("waiting_for_next_round");
+        }
+
+        let idx = self.current_turn;
+        let player = self.players.get_mut(idx).ok_or("invalid_turn")?;
+
+        if player.id != player_id {
+            return Err("not_your_turn");
+        }
+
+        if !player.has_drawn_this_turn {
+            return Err("must_draw_before_bajada");
+        }
+
+        if player.has_dropped_hand {
+            return Err("already_dropped");
+        }
+
+        // Verify that the player actually has all these cards in their hand
+        let mut original_hand_copy = player.hand.clone();
+        for combo in &combinations {
+            for card in combo {
+                if let Some(i) = original_hand_copy.iter().position(|c| c == card) {
+                    original_hand_copy.remove(i);
+                } else {
+                    return Err("cards_not_in_hand");
+                }
+            }
+        }
+
+        // Now mathematically validate the combinations against the round requirements.
+        let (req_trios, req_escalas) = self.current_round.get_requirements();
+
+        let limits = self.rules.jokers;
+        let twos = self.rules.twos;
+        let min_escala = self.rules.min_escala_len(&self.current_round);
+
+        // Each combo's wildcards when read as a trio and as an escala, `None` when it
+        // isn't one. Strict size enforcement: trios must be at least 3 cards,
+        // escalas at least 4 cards during initial bajada (3 for an escala corta).
+        // Wild twos count toward the joker limits.
+        let mut readings = Vec::with_capacity(combinations.len());
+        for combo in &combinations {
+            let trio = crate::rules::is_valid_trio(combo, limits.per_trio, twos)
+                .then(|| crate::rules::trio_wildcards(combo, twos).unwrap_or_default());
+            let escala = crate::rules::is_valid_escala(combo, limits.per_escala, min_escala, twos)
+                .then(|| {
+                    crate::rules::escala_wildcards(combo, min_escala, twos).unwrap_or_default()
+                });
+            if trio.is_none() && escala.is_none() {
+                return Err("invalid_combination");
+            }
+            readings.push((trio, escala));
+        }
+
+        // A combo can read both ways, e.g. two jokers and a 4♥ with escalas cortas, so
+        // like the combo finder try each reading and keep one that meets the round.
+        // Refusals rank by how close the bajada came, so the most telling one is reported.
+        let mut refusal = "round_requirements_not_met";
+        let rank = |e: &str| {
+            [
+                "round_requirements_not_met",
+                "extra_melds_leave_discard",
+                "too_many_jokers",
+            ]
+            .iter()
+            .position(|known| *known == e)
+        };
+        let mut accepted = false;
+        for as_escala in 0u32..1 << readings.len() {
+            let (mut found_trios, mut found_escalas, mut jokers_used) = (0, 0, 0);
+            let read = readings.iter().enumerate().all(|(i, (trio, escala))| {
+                let wildcards = if as_escala & (1 << i) == 0 {
+                    found_trios += 1;
+                    *trio
+                } else {
+                    found_escalas += 1;
+                    *escala
+                };
+                wildcards.map(|n| jokers_used += n).is_some()
+            });
+            if !read {
+                continue;
+            }
+
+            let exact = found_trios == req_trios && found_escalas == req_escalas;
+            let covered = found_trios >= req_trios && found_escalas >= req_escalas;
+            let outcome = if !(exact || self.rules.extra_melds && covered) {
+                Err("round_requirements_not_met")
+            } else if !exact && original_hand_copy.is_empty() {
+                Err("extra_melds_leave_discard")
+            } else if jokers_used > limits.per_bajada {
+                Err("too_many_jokers")
+            } else {
+                Ok(())
+            };
+            match outcome {
+                Ok(()) => {
+                    accepted = true;
+                    break;
+                }
+                Err(e) if rank(e) > rank(refusal) => refusal = e,
+                Err(_) => {}
+            }
+        }
+        if !accepted {
+            return Err(refusal);
+        }
+
+        // Success! Remove the evaluated cards from the real hand and store the bajada
+        player.hand = original_hand_copy;
+        player.has_dropped_hand = true;
+        player.dropped_hand_this_turn = true;
+        let pid = player.id.clone();
+        player.dropped_combinations = combinations;
+        self.record_action(LastAction {
+            player_id: pid,
+            action_type: "bajó".to_string(),
+            card: None,
+        });
+
+        Ok(())
+    }
+
+    /// Shed a single card from the current player's hand onto any dropped combo on the table.
+    ///
+    /// Rules enforced:
+    /// 1. It's this player's turn.
+    /// 2. The player has already dropped their hand (`has_dropped_hand == true`).
+    /// 3. The player must have completed at least one full turn since dropping
+    ///    (i.e. this is NOT the same turn as the bajada), unless the rule set's
+    ///    `shed_on_bajada_turn` allows it.
+    /// 4. The target player exists and has `has_dropped_hand == true`.
+    /// 5. The card is valid to shed onto the target combo (via `can_shed()`).
+    /// 6. If `position` is given, the card fits at that end; otherwise the preferred
+    ///    position is used.
+    pub fn shed_card(
+        &mut self,
+        player_id: &str,
+        hand_card_index: usize,
+        target_player_id: &str,
+        target_combo_idx: usize,
+        position: Option<ShedPosition>,
+    ) -> Result<Option<RoundEndResult>, &'static str> {
+        if self.is_game_over {
+            return Err("game_over");
+        }
+        if self.is_paused {
+            return Err("game_paused");
+        }
+        if self.is_waiting_for_next_round {
+            return Err("waiting_for_next_round");
+        }
+
+        let current_idx = self.current_turn;
+        let player = self.players.get(current_idx).ok_or("invalid_turn")?;
+
+        if player.id != player_id {
+            return Err("not_your_turn");
+        }
+        if !player.has_dropped_hand {
+            return Err("must_drop_before_shed");
+        }
+        if player.dropped_hand_this_turn && !self.rules.shed_on_bajada_turn {
+            return Err("no_shed_on_bajada_turn");
+        }
+
+        if !player.has_drawn_this_turn {
+            return Err("must_draw_before_shed");
+        }
+
+        // The card to shed
+        if hand_card_index >= player.hand.len() {
+            return Err("card_index_out_of_bounds");
+        }
+        let card = player.hand[hand_card_index];
+
+        // Find target player and validate their combo
+        let target_player_pos = self
+            .players
+            .iter()
+            .position(|p| p.id == target_player_id)
+            .ok_or("target_player_not_found")?;
+
+        let target_player = &self.players[target_player_pos];
+        if !target_player.has_dropped_hand {
+            return Err("target_not_dropped");
+        }
+        if target_combo_idx >= target_player.dropped_combinations.len() {
+            return Err("target_combo_out_of_bounds");
+        }
+
+        // Validate the card can be shed onto this combo
+        let combo = target_player.dropped_combinations[target_combo_idx].clone();
+        let positions = shed_positions(&card, &combo, &self.rules.jokers, self.rules.twos);
+        let position = match position {
+            None => *positions.first().ok_or("cannot_shed_there")?,
+            Some(wanted) if positions.contains(&wanted) => wanted,
+            Some(_) if positions.is_empty() => {
+                return Err("cannot_shed_there");
+            }
+            Some(_) => return Err("shed_position_invalid"),
+        };
+
+        // Apply the shed: remove card from hand, insert into the target combo
+        let pid = self.players[current_idx].id.clone();
+        self.players[current_idx].hand.remove(hand_card_index);
+        self.players[current_idx].cards_shed += 1;
+        self.players[current_idx].points_shed += self.rules.card_points(&card);
+        self.record_action(LastAction {
+            player_id: pid,
+            action_type: "shed".to_string(),
+            card: Some(card),
+        });
+
+        match position {
+            ShedPosition::ExtendLeft => {
+                self.players[target_player_pos].dropped_combinations[target_combo_idx]
+                    .insert(0, card);
+            }
+            ShedPosition::ExtendRight | ShedPosition::TrioExtension => {
+                self.players[target_player_pos].dropped_combinations[target_combo_idx].push(card);
+            }
+        }
+
+        // Check if the current player won by emptying their hand (shed their last card)
+        if self.players[current_idx].hand.is_empty() {
+            // Going out ends the turn without a discard
+            let player = &mut self.players[current_idx];
+            player.turns_played += 1;
+            let play = if player.dropped_hand_this_turn {
+                WinningPlay::Carioca
+            } else {
+                WinningPlay::Shed
+            };
+            let result = self.finish_round(Some((current_idx, play)));
+            return Ok(Some(result));
+        }
+
+        Ok(None)
+    }
+
+    /// Ends the round won by the current player discarding their last card.
+    pub fn end_round(&mut self) -> RoundEndResult {
+        self.finish_round(Some((self.current_turn, WinningPlay::Discard)))
+    }
+
+    /// Ends a round nobody could go out of; every hand is scored as it stands.
+    pub fn end_round_stalemate(&mut self) -> RoundEndResult {
+        self.finish_round(None)
+    }
+
+    fn finish_round(&mut self, outcome: Option<(usize, WinningPlay)>) -> RoundEndResult {
+        let winner = outcome.map(|(idx, _)| idx);
+        let winning_play = outcome.map(|(_, play)| play);
+        let finished_round_index = self.round_index;
+        let finished_round_name = self.current_round.description().to_string();
+        let winner_id = winner
+            .map(|idx| self.players[idx].id.clone())
+            .unwrap_or_default();
+
+        // Going out by shedding or dropping ends the winner's turn here
+        let now = Instant::now();
+        if let Some(idx) = winner {
+            self.timing.end_turn(idx, now);
+            self.players[idx].rounds_won += 1;
+        }
+
+        // Snapshot before the next round resets the counters
+        let player_stats = self
+            .players
+            .iter()
+            .enumerate()
+            .map(|(i, p)| PlayerRoundStats {
+                player_id: p.id.clone(),
+                turns_taken: p.turns_played,
+                dropped_hand: p.has_dropped_hand,
+                cards_shed: p.cards_shed,
+                points_shed: p.points_shed,
+                average_turn_ms: self
+                    .timing
+                    .average_turn(i)
+                    .map(|avg| avg.as_millis() as u64),
+            })
+            .collect();
+
+        let final_hands = self
+            .players
+            .iter()
+            .map(|p| FinalHand {
+                player_id: p.id.clone(),
+                cards: p.hand.clone(),
+            })
+            .collect();
+
+        // Calculate points for this round (before adding to totals)
+        let mut round_points: Vec<u32> = self
+            .players
+            .iter()
+            .map(|p| self.rules.hand_points(&p.hand))
+            .collect();
+
+        if winning_play == Some(WinningPlay::Carioca)
+            && self.rules.carioca_bonus == CariocaBonus::HalveOpponents
+        {
+            for points in &mut round_points {
+                *points /= 2;
+            }
+        }
+
+        // Add round points to totals
+        for (i, player) in self.players.iter_mut().enumerate() {
+            player.points += round_points[i];
+        }
+
+        if let (Some(idx), Some(WinningPlay::Carioca), CariocaBonus::WinnerBonus(bonus)) =
+            (winner, winning_play, self.rules.carioca_bonus)
+        {
+            self.players[idx].points = self.players[idx].points.saturating_sub(bonus);
+        }
+
+        // Build per-player scores
+        let player_scores: Vec<(String, u32, u32)> = self
+            .players
+            .iter()
+            .enumerate()
+            .map(|(i, p)| (p.id.clone(), round_points[i], p.points))
+            .collect();
+
+        // Advance round
+        self.round_index += 1;
+        let rounds = RoundType::all_rounds();
+        let is_game_over;
+        let next_round_index;
+        let next_round_name;
+
+        let more_rounds = self.round_index < self.rules.round_count(rounds.len());
+        let sudden_death = !more_rounds
+            && self.rules.tie_break == TieBreak::SuddenDeath
+            && self.leaders().len() > 1;
+        if sudden_death && self.sudden_death.is_none() {
+            self.sudden_death = Some(self.leaders().iter().map(|p| p.id.clone()).collect());
+        }
+
+        if more_rounds || sudden_death {
+            // A sudden-death round replays the last one
+            if more_rounds {
+                self.current_round = rounds[self.round_index];
+            }
+            // Without a winner, fall back to plain rotation
+            let rotation = match winner {
+                Some(_) => self.rules.turn_rotation,
+                None => TurnRotation::FixedRotation,
+            };
+            self.current_turn = rotation.starting_seat(
+                self.round_index,
+                winner.unwrap_or_default(),
+                self.players.len(),
+            );
+            next_round_index = self.round_index;
+            next_round_name = self.current_round.description().to_string();
+            is_game_over = false;
+
+            // Do not start round immediately. Wait for players to be ready.
+            self.is_waiting_for_next_round = true;
+            for player in &mut self.players {
+                player.is_ready_for_next_round = player.id.starts_with("bot_");
+            }
+        } else {
+            self.is_game_over = true;
+            is_game_over = true;
+            next_round_index = self.round_index;
+            next_round_name = "Game Over".to_string();
+        }
+
+        RoundEndResult {
+            finished_round_index,
+            finished_round_name,
+            winner_id,
+            player_scores,
+            next_round_index,
+            next_round_name,
+            is_game_over,
+            is_stalemate: winner.is_none(),
+            player_stats,
+            final_hands,
+            winning_play,
+            round_duration_ms: self.timing.round_elapsed(now).as_millis() as u64,
+            game_duration_ms: self.timing.game_elapsed(now).as_millis() as u64,
+            tie_break: if is_game_over { self.break_tie() } else { None },
+        }
+    }
+
+    /// Everyone on the lowest total, in seat order.
+    fn leaders(&self) -> Vec<&PlayerState> {
+        let best = self.players.iter().map(|p| p.points).min();
+        self.players
+            .iter()
+            .filter(|p| Some(p.points) == best)
+            .collect()
+    }
+
+    /// Settles a tie for the win by the table's [`TieBreak`]; `None` without a tie to settle.
+    fn break_tie(&self) -> Option<TieBreakOutcome> {
+        let leaders = self.leaders();
+        let tied: Vec<String> = match &self.sudden_death {
+            Some(tied) => tied.clone(),
+            None if leaders.len() > 1 => leaders.iter().map(|p| p.id.clone()).collect(),
+            None => return None,
+        };
+        // Higher keys win
+        let key = |p: &PlayerState| match self.rules.tie_break {
+            TieBreak::Shared => None,
+            TieBreak::MostRoundsWon => Some(i64::from(p.rounds_won)),
+            TieBreak::FewestPenaltyCards => Some(-i64::from(p.game_penalty_cards)),
+            TieBreak::SuddenDeath => Some(0),
+        };
+        let best = leaders.iter().map(|p| key(p)).max()??;
+        let mut best_players = leaders.iter().filter(|p| key(p) == Some(best));
+        let winner_id = match (best_players.next(), best_players.next()) {
+            (Some(only), None) => Some(only.id.clone()),
+            _ => None,
+        };
+        Some(TieBreakOutcome {
+            policy: self.rules.tie_break,
+            tied,
+            winner_id,
+        })
+    }
+
+    pub fn mark_player_ready(&mut self, player_id: &str) -> Result<(), &'static str> {
+        if !self.is_waiting_for_next_round {
+            return Err("not_waiting_for_next_round");
+        }
+        if self.is_paused {
+            return Err("game_paused");
+        }
+
+        let player = self
+            .players
+            .iter_mut()
+            .find(|p| p.id == player_id)
+            .ok_or("player_not_found")?;
+
+        player.is_ready_for_next_round = true;
+
+        let all_ready = self.players.iter().all(|p| p.is_ready_for_next_round);
+        if all_ready {
+            self.is_waiting_for_next_round = false;
+            self.start_round();
+        }
+
+        Ok(())
+    }
+
+    /// Readies everyone still holding up the table (e.g. when the countdown runs out)
+    /// and starts the next round.
+    pub fn force_ready_all(&mut self) -> Result<(), &'static str> {
+        if !self.is_waiting_for_next_round {
+            return Err("not_waiting_for_next_round");
+        }
+
+        for player in &mut self.players {
+            player.is_ready_for_next_round = true;
+        }
+        self.is_waiting_for_next_round = false;
+        self.start_round();
+
+        Ok(())
+    }
+
+    /// Ends the game where it stands, e.g. when every human has walked away. Returns each
+    /// player's running total, with the round in progress left unscored.
+    pub fn abandon(&mut self) -> Vec<(String, u32)> {
+        self.is_game_over = true;
+        self.is_waiting_for_next_round = false;
+        self.players
+            .iter()
+            .map(|p| (p.id.clone(), p.points))
+            .collect()
+    }
+}
+
+// ---------------------------------------------
+// Validation Logic
+// ---------------------------------------------
+// Validation delegates to crate::rules
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_game_initialization() {
+        let players = vec!["alice".to_string(), "bob".to_string()];
+        let mut game = GameState::new(players);
+
+        assert_eq!(game.players.len(), 2);
+        assert_eq!(game.current_round, RoundType::TwoTrios);
+        assert_eq!(game.round_index, 0);
+
+        game.start_round();
+
+        // After start_round, players should have 12 cards each
+        assert_eq!(game.players[0].hand.len(), 12);
+        assert_eq!(game.players[1].hand.len(), 12);
+
+        // Discard pile should have 1 card
+        assert_eq!(game.discard_pile.len(), 1);
+
+        // Deck should have 108 - (12 * 2) - 1 = 83 cards
+        assert_eq!(game.deck.remaining(), 83);
+    }
+
+    #[test]
+    fn test_valid_turn_progression() {
+        let players = vec!["alice".to_string(), "bob".to_string()];
+        let mut game = GameState::new(players);
+        game.start_round();
+
+        assert_eq!(game.current_turn, 0); // Alice's turn
+
+        // Alice draws
+        assert!(game.draw_from_deck().is_ok());
+        assert_eq!(game.players[0].hand.len(), 13);
+
+        // Alice discards
+        assert!(game.discard(0).is_ok());
+        assert_eq!(game.players[0].hand.len(), 12);
+
+        // Now it's Bob's turn
+        assert_eq!(game.current_turn, 1);
+    }
+
+    #[test]
+    fn test_4_player_initialization() {
+        let players = vec![
+            "p1".to_string(),
+            "p2".to_string(),
+            "p3".to_string(),
+            "p4".to_string(),
+        ];
+        let mut game = GameState::new(players);
+        game.start_round();
+
+        assert_eq!(game.players.len(), 4);
+
+        // Each player gets 12 cards -> 48 cards dealt
+        for i in 0..4 {
+            assert_eq!(game.players[i].hand.len(), 12);
+        }
+
+        // 1 card in discard pile
+        assert_eq!(game.discard_pile.len(), 1);
+
+        // Deck should have 108 - (12 * 4) - 1 = 59 cards remaining
+        assert_eq!(game.deck.remaining(), 59);
+
+        // Turn progression wraps after 4
+        assert_eq!(game.current_turn, 0);
+        assert!(game.draw_from_deck().is_ok());
+        assert!(game.discard(0).is_ok());
+        assert_eq!(game.current_turn, 1);
+    }
+
+    // ── Helper: build a minimal 2-player game with alice already bajado ──
+
+    fn std(suit: crate::card::Suit, value: crate::card::Value) -> Card {
+        Card::Standard { suit, value }
+    }
+
+    /// Sets up a 2-player game (alice=0, bob=1) where alice has already dropped
+    /// a trio of Fives and is on her second turn (turns_played > 0).
+    fn game_with_alice_bajado() -> GameState {
+        use crate::card::{Suit, Value};
+        let mut game = GameState::new(vec!["alice".to_string(), "bob".to_string()]);
+        game.start_round();
+
+        // Give alice a known hand: 7♥ and a bunch of filler
+        game.players[0].hand = vec![
+            std(Suit::Hearts, Value::Seven), // idx 0 — will try to shed
+            std(Suit::Clubs, Value::Two),    // idx 1
+            std(Suit::Spades, Value::Three), // idx 2
+        ];
+
+        // Set alice as already bajado with a trio of Fives
+        game.players[0].has_dropped_hand = true;
+        game.players[0].dropped_combinations = vec![vec![
+            std(Suit::Hearts, Value::Five),
+            std(Suit::Clubs, Value::Five),
+            std(Suit::Spades, Value::Five),
+        ]];
+        game.players[0].turns_played = 1; // She's already had turns since dropping
+
+        // Give bob a bajado escala 3-4-5-6 ♦ so alice can shed onto it
+        game.players[1].has_dropped_hand = true;
+        game.players[1].dropped_combinations = vec![vec![
+            std(Suit::Diamonds, Value::Three),
+            std(Suit::Diamonds, Value::Four),
+            std(Suit::Diamonds, Value::Five),
+            std(Suit::Diamonds, Value::Six),
+        ]];
+
+        // It's alice's turn, and she has drawn a card so she can shed
+        game.current_turn = 0;
+        game.players[0].has_drawn_this_turn = true;
+        game
+    }
+
+    #[test]
+    fn shed_card_extends_own_trio() {
+        use crate::card::{Suit, Value};
+        let mut game = game_with_alice_bajado();
+
+        // Add 5♦ to alice's hand
+        game.players[0].hand.push(std(Suit::Diamonds, Value::Five));
+        let five_idx = game.players[0].hand.len() - 1;
+
+        // Shed onto her own trio of Fives
+        let result = game.shed_card("alice", five_idx, "alice", 0, None);
+        assert!(result.is_ok(), "Should shed a matching Five onto town trio");
+
+        // Trio should now have 4 cards
+        assert_eq!(game.players[0].dropped_combinations[0].len(), 4);
+        // Hand should shrink
+        assert_eq!(game.players[0].hand.len(), 3); // was 4, now 3
+    }
+
+    #[test]
+    fn going_out_by_shed_reports_round_stats() {
+        use crate::card::{Suit, Value};
+        let mut game = game_with_alice_bajado();
+        game.players[0].hand = vec![std(Suit::Diamonds, Value::Seven)];
+
+        let result = game
+            .shed_card("alice", 0, "bob", 0, None)
+            .unwrap()
+            .expect("Shedding the last card ends the round");
+
+        assert_eq!(result.winning_play, Some(WinningPlay::Shed));
+        let alice = &result.player_stats[0];
+        assert_eq!(alice.player_id, "alice");
+        assert!(alice.dropped_hand);
+        assert_eq!(alice.cards_shed, 1);
+        assert_eq!(alice.points_shed, 7);
+        assert_eq!(alice.turns_taken, 2);
+        assert!(alice.average_turn_ms.is_some(), "The winning turn is timed");
+        assert_eq!(result.player_stats[1].cards_shed, 0);
+        assert_eq!(result.player_stats[1].average_turn_ms, None);
+
+        // Counters start over with the next round
+        game.force_ready_all().unwrap();
+        assert_eq!(game.players[0].cards_shed, 0);
+    }
+
+    #[test]
+    fn shedding_on_the_bajada_turn_follows_the_rule_set() {
+        use crate::card::{Suit, Value};
+        let mut game = game_with_alice_bajado();
+        game.players[0].dropped_hand_this_turn = true;
+        game.players[0].hand = vec![std(Suit::Diamonds, Value::Seven)];
+        assert_eq!(
+            game.shed_card("alice", 0, "bob", 0, None).unwrap_err(),
+            "no_shed_on_bajada_turn"
+        );
+
+        game.rules.shed_on_bajada_turn = true;
+        let result = game
+            .shed_card("alice", 0, "bob", 0, None)
+            .unwrap()
+            .expect("Shedding the last card ends the round");
+        assert_eq!(result.winning_play, Some(WinningPlay::Carioca));
+    }
+
+    #[test]
+    fn shed_card_extends_opponent_escala_right() {
+        use crate::card::{Suit, Value};
+        let mut game = game_with_alice_bajado();
+
+        // 7♦ extends bob's 3-4-5-6♦ escala on the right
+        // Give alice 2 cards so she doesn't empty hand and trigger end_round()
+        game.players[0].hand = vec![
+            std(Suit::Diamonds, Value::Seven),
+            std(Suit::Clubs, Value::King),
+        ];
+        let result = game.shed_card("alice", 0, "bob", 0, None);
+        assert!(result.is_ok(), "Should shed 7♦ onto bob's escala");
+        assert_eq!(game.players[1].dropped_combinations[0].len(), 5);
+        // Last card should be 7♦
+        assert_eq!(
+            game.players[1].dropped_combinations[0].last().unwrap(),
+            &std(Suit::Diamonds, Value::Seven)
+        );
+    }
+
+    #[test]
+    fn shed_card_extends_opponent_escala_left() {
+        use crate::card::{Suit, Value};
+        let mut game = game_with_alice_bajado();
+
+        // 2♦ extends bob's 3-4-5-6♦ escala on the left
+        // Give alice 2 cards so she doesn't empty hand and trigger end_round()
+        game.players[0].hand = vec![
+            std(Suit::Diamonds, Value::Two),
+            std(Suit::Clubs, Value::King),
+        ];
+        let result = game.shed_card("alice", 0, "bob", 0, None);
+        assert!(
+            result.is_ok(),
+            "Should shed 2♦ onto bob's escala on the left"
+        );
+        assert_eq!(game.players[1].dropped_combinations[0].len(), 5);
+        // First card should be 2♦
+        assert_eq!(
+            game.players[1].dropped_combinations[0].first().unwrap(),
+            &std(Suit::Diamonds, Value::Two)
+        );
+    }
+
+    #[test]
+    fn shed_joker_at_requested_end() {
+        use crate::card::{Suit, Value};
+        let mut game = game_with_alice_bajado();
+        game.players[0].hand = vec![Card::Joker, std(Suit::Clubs, Value::King)];
+
+        assert_eq!(
+            game.shed_card("alice", 0, "bob", 0, Some(ShedPosition::TrioExtension))
+                .unwrap_err(),
+            "shed_position_invalid"
+        );
+        game.shed_card("alice", 0, "bob", 0, Some(ShedPosition::ExtendLeft))
+            .unwrap();
+        let escala = &game.players[1].dropped_combinations[0];
+        assert_eq!(
+            escala.first(),
+            Some(&Card::Joker),
+            "Joker now stands for the 2♦"
+        );
+
+        // The joker occupies the low end, so the next card down is the Ace
+        game.players[0].hand = vec![
+            std(Suit::Diamonds, Value::Ace),
+            std(Suit::Diamonds, Value::Two),
+            std(Suit::Clubs, Value::King),
+        ];
+        assert!(game.shed_card("alice", 1, "bob", 0, None).is_err());
+        game.shed_card("alice", 0, "bob", 0, Some(ShedPosition::ExtendLeft))
+            .unwrap();
+        assert_eq!(game.players[1].dropped_combinations[0].len(), 6);
+    }
+
+    #[test]
+    fn shed_ace_left_on_escala_starting_with_two() {
+        use crate::card::{Suit, Value};
+        let mut game = game_with_alice_bajado();
+
+        // bob's combo is 3-4-5-6. Let's make it 2-3-4-5 instead.
+        game.players[1].dropped_combinations = vec![vec![
+            std(Suit::Diamonds, Value::Two),
+            std(Suit::Diamonds, Value::Three),
+            std(Suit::Diamonds, Value::Four),
+            std(Suit::Diamonds, Value::Five),
+        ]];
+
+        game.players[0].hand = vec![
+            std(Suit::Diamonds, Value::Ace), // We want to shed this
+            std(Suit::Clubs, Value::King),
+        ];
+
+        let result = game.shed_card("alice", 0, "bob", 0, None);
+        assert!(
+            result.is_ok(),
+            "Should shed A♦ onto bob's 2-3-4-5♦ escala on the left"
+        );
+        assert_eq!(game.players[1].dropped_combinations[0].len(), 5);
+        // First card should be A♦
+        assert_eq!(
+            game.players[1].dropped_combinations[0].first().unwrap(),
+            &std(Suit::Diamonds, Value::Ace)
+        );
+    }
+
+    #[test]
+    fn shed_card_rejected_before_bajada() {
+        use crate::card::{Suit, Value};
+        let mut game = GameState::new(vec!["alice".to_string(), "bob".to_string()]);
+        game.start_round();
+        game.players[0].hand = vec![std(Suit::Diamonds, Value::Seven)];
+        game.players[0].has_dropped_hand = false; // NOT dropped yet
+        game.current_turn = 0;
+
+        // Bob must have bajado to be target
+        game.players[1].has_dropped_hand = true;
+        game.players[1].dropped_combinations = vec![vec![
+            std(Suit::Diamonds, Value::Five),
+            std(Suit::Diamonds, Value::Six),
+            std(Suit::Diamonds, Value::Eight),
+            std(Suit::Diamonds, Value::Nine),
+        ]];
+
+        let result = game.shed_card("alice", 0, "bob", 0, None);
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err(), "must_drop_before_shed");
+    }
+
+    #[test]
+    fn shed_card_rejected_for_invalid_card() {
+        use crate::card::{Suit, Value};
+        let mut game = game_with_alice_bajado();
+
+        // 7♥ cannot shed onto bob's 3-4-5-6♦ escala (wrong suit)
+        game.players[0].hand = vec![std(Suit::Hearts, Value::Seven)];
+        let result = game.shed_card("alice", 0, "bob", 0, None);
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err(), "cannot_shed_there");
+    }
+
+    #[test]
+    fn force_ready_all_starts_next_round() {
+        let mut game = GameState::new(vec!["alice".to_string(), "bob".to_string()]);
+        game.start_round();
+        game.players[0].hand.clear();
+        game.end_round();
+        assert!(game.is_waiting_for_next_round);
+
+        game.mark_player_ready("alice").unwrap();
+        assert!(game.is_waiting_for_next_round, "Bob is still not ready");
+
+        game.force_ready_all().unwrap();
+        assert!(!game.is_waiting_for_next_round);
+        assert_eq!(game.round_index, 1);
+        assert_eq!(game.players[1].hand.len(), 12);
+    }
+
+    #[test]
+    fn round_limit_ends_the_game_early() {
+        let rules = RuleSet {
+            round_limit: 2,
+            ..RuleSet::default()
+        };
+        let mut game = GameState::with_rules(vec!["alice".to_string(), "bob".to_string()], rules);
+        game.start_round();
+
+        assert!(!game.end_round().is_game_over);
+        game.force_ready_all().unwrap();
+        let result = game.end_round();
+        assert!(result.is_game_over);
+        assert_eq!(result.finished_round_index, 1);
+        assert!(game.is_game_over);
+    }
+
+    #[test]
+    fn ties_for_the_win_follow_the_tie_break_rule() {
+        let tied_game = |tie_break: TieBreak| {
+            let rules = RuleSet {
+                round_limit: 1,
+                tie_break,
+                ..RuleSet::default()
+            };
+            let mut game =
+                GameState::with_rules(vec!["alice".to_string(), "bob".to_string()], rules);
+            game.start_round();
+            for player in &mut game.players {
+                player.hand.clear();
+            }
+            game.current_turn = 1;
+            game
+        };
+        let winner = |result: RoundEndResult| result.tie_break.and_then(|t| t.winner_id);
+
+        assert_eq!(tied_game(TieBreak::Shared).end_round().tie_break, None);
+        assert_eq!(
+            winner(tied_game(TieBreak::MostRoundsWon).end_round()),
+            Some("bob".to_string())
+        );
+        let mut game = tied_game(TieBreak::FewestPenaltyCards);
+        game.players[1].game_penalty_cards = 2;
+        let result = game.end_round();
+        assert_eq!(
+            result.tie_break,
+            Some(TieBreakOutcome {
+                policy: TieBreak::FewestPenaltyCards,
+                tied: vec!["alice".to_string(), "bob".to_string()],
+                winner_id: Some("alice".to_string()),
+            })
+        );
+    }
+
+    #[test]
+    fn sudden_death_replays_the_last_round_until_the_tie_breaks() {
+        let rules = RuleSet {
+            round_limit: 1,
+            tie_break: TieBreak::SuddenDeath,
+            ..RuleSet::default()
+        };
+        let mut game = GameState::with_rules(vec!["alice".to_string(), "bob".to_string()], rules);
+        game.start_round();
+        for player in &mut game.players {
+            player.hand.clear();
+        }
+        let result = game.end_round();
+        assert!(!result.is_game_over);
+        assert_eq!(game.current_round, RoundType::TwoTrios);
+        assert_eq!(game.sudden_death.as_ref().map(Vec::len), Some(2));
+
+        game.force_ready_all().unwrap();
+        game.current_turn = 0;
+        game.players[0].hand.clear();
+        let result = game.end_round();
+        assert!(result.is_game_over);
+        assert_eq!(result.finished_round_index, 1);
+        let outcome = result.tie_break.unwrap();
+        assert_eq!(outcome.policy, TieBreak::SuddenDeath);
+        assert_eq!(outcome.tied.len(), 2);
+        assert_eq!(outcome.winner_id.as_deref(), Some("alice"));
+    }
+
+    #[test]
+    fn abandoning_keeps_running_totals() {
+        let mut game = GameState::new(vec!["alice".to_string(), "bob".to_string()]);
+        game.start_round();
+        game.players[0].points = 40;
+        game.players[1].points = 15;
+
+        let totals = game.abandon();
+        assert!(game.is_game_over);
+        assert_eq!(totals, [("alice".to_string(), 40), ("bob".to_string(), 15)]);
+    }
+
+    #[test]
+    fn end_round_applies_turn_rotation() {
+        use crate::rule_set::TurnRotation;
+        let players = vec!["p1".to_string(), "p2".to_string(), "p3".to_string()];
+
+        for (rotation, expected) in [
+            (TurnRotation::FixedRotation, 1),
+            (TurnRotation::WinnerStarts, 2),
+            (TurnRotation::DealerLeft, 0),
+        ] {
+            let rules = RuleSet {
+                turn_rotation: rotation,
+                ..RuleSet::default()
+            };
+            let mut game = GameState::with_rules(players.clone(), rules);
+            game.start_round();
+            game.current_turn = 2; // p3 goes out
+            game.players[2].hand.clear();
+            game.end_round();
+            assert_eq!(game.current_turn, expected, "{:?}", rotation);
+        }
+    }
+
+    /// Empties the deck of a fresh 2-player game so only passes and discard draws remain.
+    fn game_with_empty_deck(stalemate_turns: u32) -> GameState {
+        let rules = RuleSet {
+            stalemate_turns,
+            ..RuleSet::default()
+        };
+        let mut game = GameState::with_rules(vec!["alice".to_string(), "bob".to_string()], rules);
+        game.start_round();
+        while game.deck.draw().is_some() {}
+        game
+    }
+
+    #[test]
+    fn timed_out_turns_draw_a_penalty_card_and_move_on() {
+        let mut game = GameState::new(vec!["alice".to_string(), "bob".to_string()]);
+        game.start_round();
+        let deck_before = game.deck.remaining();
+
+        assert!(game.skip_timed_out_turn().unwrap().is_none());
+        assert_eq!(game.players[0].hand.len(), 13);
+        assert_eq!(game.players[0].penalty_cards, 1);
+        assert_eq!(game.deck.remaining(), deck_before - 1);
+        assert_eq!(game.current_turn, 1);
+        assert_eq!(
+            game.last_action.as_ref().unwrap().action_type,
+            "penalty_draw"
+        );
+
+        // Having drawn already, the card taken is the penalty
+        game.draw_from_deck().unwrap();
+        game.skip_timed_out_turn().unwrap();
+        assert_eq!(game.players[1].hand.len(), 13);
+        assert_eq!(game.players[1].penalty_cards, 1);
+        assert_eq!(game.last_action.as_ref().unwrap().action_type, "skipped");
+        assert_eq!(game.current_turn, 0);
+    }
+
+    #[test]
+    fn next_player_follows_the_turn_order() {
+        let ids = ["alice", "bob", "carol"].map(String::from).to_vec();
+        let mut game = GameState::new(ids.clone());
+        game.start_round();
+        assert_eq!(game.turn_order(), ids);
+        assert_eq!(game.next_player_id(), Some("bob"));
+
+        game.current_turn = 2;
+        assert_eq!(game.next_player_id(), Some("alice"));
+        game.is_waiting_for_next_round = true;
+        assert_eq!(game.next_player_id(), None);
+    }
+
+    #[test]
+    fn recent_actions_keep_a_window_of_the_latest() {
+        let mut game = GameState::new(vec!["alice".to_string(), "bob".to_string()]);
+        game.start_round();
+        for _ in 0..RECENT_ACTIONS_KEPT {
+            game.draw_from_deck().unwrap();
+            game.discard(0).unwrap();
+        }
+
+        assert_eq!(game.recent_actions.len(), RECENT_ACTIONS_KEPT);
+        let latest = game.recent_actions.back().unwrap();
+        assert_eq!(latest.action_type, "discarded");
+        assert_eq!(latest.player_id, "bob");
+        assert_eq!(
+            game.last_action.as_ref().unwrap().player_id,
+            latest.player_id
+        );
+        assert_eq!(game.recent_actions[0].action_type, "drew_from_deck");
+    }
+
+    #[test]
+    fn pass_turn_requires_empty_deck() {
+        let mut game = GameState::new(vec!["alice".to_string(), "bob".to_string()]);
+        game.start_round();
+        assert_eq!(game.pass_turn().unwrap_err(), "pass_requires_empty_deck");
+    }
+
+    #[test]
+    fn passing_advances_the_turn() {
+        let mut game = game_with_empty_deck(8);
+        assert_eq!(game.draw_from_deck().unwrap_err(), "deck_empty");
+
+        assert!(game.pass_turn().unwrap().is_none());
+        assert_eq!(game.current_turn, 1);
+        assert_eq!(game.stalled_turns, 1);
+    }
+
+    #[test]
+    fn stalemate_ends_round_scoring_all_hands() {
+        let mut game = game_with_empty_deck(3);
+
+        assert!(game.pass_turn().unwrap().is_none());
+        // A discard-pile draw still counts towards the stalemate
+        game.draw_from_discard().unwrap();
+        assert!(game.discard(0).unwrap().is_none());
+        let result = game.pass_turn().unwrap().expect("Round should end");
+
+        assert!(result.is_stalemate);
+        assert!(result.winner_id.is_empty());
+        assert!(result.player_scores.iter().all(|(_, round, _)| *round > 0));
+        // The revealed hands account for every penalty point
+        for (hand, (id, round, _)) in result.final_hands.iter().zip(&result.player_scores) {
+            assert_eq!(&hand.player_id, id);
+            let points = game.rules.hand_points(&hand.cards);
+            assert_eq!(points, *round);
+        }
+        assert!(game.is_waiting_for_next_round);
+        assert_eq!(game.current_turn, 1, "Falls back to fixed rotation");
+    }
+
+    /// Alice holds two trios plus one spare card and has drawn; her bajada leaves only the spare.
+    fn game_ready_for_carioca(bonus: CariocaBonus) -> (GameState, Vec<Vec<Card>>) {
+        use crate::card::{Suit, Value};
+        let rules = RuleSet {
+            carioca_bonus: bonus,
+            ..RuleSet::default()
+        };
+        let mut game = GameState::with_rules(vec!["alice".to_string(), "bob".to_string()], rules);
+        game.start_round();
+
+        let combos = vec![
+            vec![
+                std(Suit::Hearts, Value::Five),
+                std(Suit::Clubs, Value::Five),
+                std(Suit::Spades, Value::Five),
+            ],
+            vec![
+                std(Suit::Hearts, Value::Nine),
+                std(Suit::Clubs, Value::Nine),
+                std(Suit::Spades, Value::Nine),
+            ],
+        ];
+        game.players[0].hand = combos.concat();
+        game.players[0].hand.push(std(Suit::Diamonds, Value::Two));
+        game.players[0].has_drawn_this_turn = true;
+        game.players[0].turns_played = 1;
+        game.players[1].hand = vec![
+            std(Suit::Hearts, Value::King),
+            std(Suit::Clubs, Value::Ace),
+            std(Suit::Spades, Value::Three),
+        ]; // 33 points
+        game.players[1].points = 10;
+        (game, combos)
+    }
+
+    #[test]
+    fn carioca_halves_opponent_penalties() {
+        let (mut game, combos) = game_ready_for_carioca(CariocaBonus::HalveOpponents);
+        game.drop_hand("alice", combos).unwrap();
+        let result = game.discard(0).unwrap().expect("Alice went out");
+
+        assert_eq!(result.winning_play, Some(WinningPlay::Carioca));
+        assert_eq!(result.player_scores[1], ("bob".to_string(), 16, 26));
+    }
+
+    #[test]
+    fn carioca_winner_bonus_never_goes_negative() {
+        let (mut game, combos) = game_ready_for_carioca(CariocaBonus::WinnerBonus(15));
+        game.players[0].points = 40;
+        game.drop_hand("alice", combos).unwrap();
+        game.discard(0).unwrap();
+        assert_eq!(game.players[0].points, 25);
+        assert_eq!(game.players[1].points, 43, "Opponents score as usual");
+
+        let (mut game, combos) = game_ready_for_carioca(CariocaBonus::WinnerBonus(15));
+        game.drop_hand("alice", combos).unwrap();
+        game.discard(0).unwrap();
+        assert_eq!(game.players[0].points, 0);
+    }
+
+    #[test]
+    fn going_out_over_several_turns_is_not_a_carioca() {
+        let (mut game, combos) = game_ready_for_carioca(CariocaBonus::HalveOpponents);
+        game.drop_hand("alice", combos).unwrap();
+        game.players[0].dropped_hand_this_turn = false; // as if the bajada was an earlier turn
+        let result = game.discard(0).unwrap().unwrap();
+
+        assert_eq!(result.winning_play, Some(WinningPlay::Discard));
+        assert_eq!(result.player_scores[1].1, 33);
+    }
+
+    #[test]
+    fn force_ready_all_requires_waiting_state() {
+        let mut game = GameState::new(vec!["alice".to_string(), "bob".to_string()]);
+        game.start_round();
+        assert_eq!(
+            game.force_ready_all().unwrap_err(),
+            "not_waiting_for_next_round"
+        );
+    }
+
+    #[test]
+    fn bajada_respects_joker_limits() {
+        use crate::card::{Suit, Value};
+        use crate::rule_set::JokerLimits;
+        let combos = vec![
+            vec![
+                std(Suit::Hearts, Value::Five),
+                Card::Joker,
+                std(Suit::Spades, Value::Five),
+            ],
+            vec![std(Suit::Hearts, Value::Nine), Card::Joker, Card::Joker],
+        ];
+        let game_with = |jokers: JokerLimits| {
+            let rules = RuleSet {
+                jokers,
+                ..RuleSet::default()
+            };
+            let mut game =
+                GameState::with_rules(vec!["alice".to_string(), "bob".to_string()], rules);
+            game.start_round();
+            game.players[0].hand = combos.concat();
+            game.players[0].hand.push(std(Suit::Diamonds, Value::Two));
+            game.players[0].has_drawn_this_turn = true;
+            game
+        };
+
+        let mut game = game_with(JokerLimits::default());
+        assert_eq!(
+            game.drop_hand("alice", combos.clone()).unwrap_err(),
+            "invalid_combination"
+        );
+
+        let mut game = game_with(JokerLimits {
+            per_trio: 2,
+            ..JokerLimits::default()
+        });
+        assert_eq!(
+            game.drop_hand("alice", combos.clone()).unwrap_err(),
+            "too_many_jokers"
+        );
+
+        let mut game = game_with(JokerLimits {
+            per_trio: 2,
+            per_escala: 1,
+            per_bajada: 3,
+        });
+        assert!(game.drop_hand("alice", combos).is_ok());
+    }
+
+    #[test]
+    fn rounds_are_dealt_with_the_configured_jokers() {
+        let rules = RuleSet {
+            deck_jokers: 6,
+            ..RuleSet::default()
+        };
+        let mut game = GameState::with_rules(vec!["alice".to_string(), "bob".to_string()], rules);
+        game.game_seed = Some(3);
+        game.start_round();
+        let jokers = game
+            .deck
+            .cards()
+            .iter()
+            .chain(&game.discard_pile)
+            .chain(game.players.iter().flat_map(|p| &p.hand))
+            .filter(|c| c.is_joker())
+            .count();
+        assert_eq!(jokers, 6);
+    }
+
+    #[test]
+    fn escalas_cortas_count_only_where_the_rules_allow() {
+        use crate::card::{Suit, Value};
+        use crate::combo_finder::find_best_bajada;
+        use crate::rule_set::EscalaCorta;
+        let combos = vec![
+            vec![
+                std(Suit::Hearts, Value::Five),
+                std(Suit::Hearts, Value::Six),
+                std(Suit::Hearts, Value::Seven),
+            ],
+            vec![
+                std(Suit::Clubs, Value::Jack),
+                std(Suit::Clubs, Value::Queen),
+                std(Suit::Clubs, Value::King),
+            ],
+        ];
+        let game_with = |escala_corta: EscalaCorta| {
+            let rules = RuleSet {
+                escala_corta,
+                ..RuleSet::default()
+            };
+            let mut game =
+                GameState::with_rules(vec!["alice".to_string(), "bob".to_string()], rules);
+            game.start_round();
+            game.current_round = RoundType::TwoEscalas;
+            game.players[0].hand = combos.concat();
+            game.players[0].hand.push(std(Suit::Diamonds, Value::Two));
+            game.players[0].has_drawn_this_turn = true;
+            game
+        };
+
+        let mut game = game_with(EscalaCorta::Off);
+        assert_eq!(
+            game.drop_hand("alice", combos.clone()).unwrap_err(),
+            "invalid_combination"
+        );
+        let mut game = game_with(EscalaCorta::Rounds(vec![RoundType::ThreeEscalas]));
+        assert!(game.drop_hand("alice", combos.clone()).is_err());
+
+        let mut game = game_with(EscalaCorta::Rounds(vec![RoundType::TwoEscalas]));
+        let min_escala = game.rules.min_escala_len(&game.current_round);
+        let hand = game.players[0].hand.clone();
+        let found = find_best_bajada(&hand, 0, 2, min_escala, true, &game.rules);
+        assert_eq!(found.map(|melds| melds.len()), Some(2));
+        assert!(game.drop_hand("alice", combos).is_ok());
+    }
+
+    #[test]
+    fn a_meld_that_reads_both_ways_counts_as_the_round_needs() {
+        use crate::card::{Suit, Value};
+        use crate::combo_finder::find_best_bajada;
+        use crate::rule_set::{EscalaCorta, JokerLimits};
+        // Two jokers and a 4♥ are a trio of fours and an escala corta alike
+        let combos = vec![
+            vec![Card::Joker, Card::Joker, std(Suit::Hearts, Value::Four)],
+            vec![
+                std(Suit::Hearts, Value::Queen),
+                std(Suit::Hearts, Value::King),
+                std(Suit::Hearts, Value::Ace),
+            ],
+        ];
+        let rules = RuleSet {
+            escala_corta: EscalaCorta::Always,
+            deck_jokers: 6,
+            jokers: JokerLimits {
+                per_trio: 2,
+                per_escala: 2,
+                per_bajada: 4,
+            },
+            ..RuleSet::default()
+        };
+        let mut game = GameState::with_rules(vec!["alice".to_string(), "bob".to_string()], rules);
+        game.start_round();
+        game.current_round = RoundType::TwoEscalas;
+        game.players[0].hand = combos.concat();
+        game.players[0].hand.push(std(Suit::Diamonds, Value::Two));
+        game.players[0].has_drawn_this_turn = true;
+
+        let min_escala = game.rules.min_escala_len(&game.current_round);
+        let hand = game.players[0].hand.clone();
+        let found = find_best_bajada(&hand, 0, 2, min_escala, true, &game.rules);
+        assert_eq!(found.map(|melds| melds.len()), Some(2));
+        assert_eq!(game.drop_hand("alice", combos), Ok(()));
+        assert_eq!(game.players[0].hand, vec![std(Suit::Diamonds, Value::Two)]);
+    }
+
+    #[test]
+    fn extra_melds_need_their_rule_and_a_card_left() {
+        use crate::card::{Suit, Value};
+        let trios = vec![
+            vec![
+                std(Suit::Hearts, Value::Seven),
+                std(Suit::Clubs, Value::Seven),
+                std(Suit::Spades, Value::Seven),
+            ],
+            vec![
+                std(Suit::Hearts, Value::Nine),
+                std(Suit::Clubs, Value::Nine),
+                std(Suit::Diamonds, Value::Nine),
+            ],
+        ];
+        let escala = vec![
+            std(Suit::Spades, Value::Three),
+            std(Suit::Spades, Value::Four),
+            std(Suit::Spades, Value::Five),
+            std(Suit::Spades, Value::Six),
+        ];
+        let mut combos = trios.clone();
+        combos.push(escala);
+        let game_with = |extra_melds: bool, leftover: bool| {
+            let rules = RuleSet {
+                extra_melds,
+                ..RuleSet::default()
+            };
+            let mut game =
+                GameState::with_rules(vec!["alice".to_string(), "bob".to_string()], rules);
+            game.start_round();
+            game.players[0].hand = combos.concat();
+            if leftover {
+                game.players[0].hand.push(std(Suit::Diamonds, Value::King));
+            }
+            game.players[0].has_drawn_this_turn = true;
+            game
+        };
+
+        assert_eq!(
+            game_with(false, true)
+                .drop_hand("alice", combos.clone())
+                .unwrap_err(),
+            "round_requirements_not_met"
+        );
+        assert_eq!(
+            game_with(true, false)
+                .drop_hand("alice", combos.clone())
+                .unwrap_err(),
+            "extra_melds_leave_discard"
+        );
+        // Extras never stand in for a required meld
+        let mut one_trio = vec![trios[0].clone()];
+        one_trio.push(combos[2].clone());
+        assert!(game_with(true, true).drop_hand("alice", one_trio).is_err());
+
+        let mut game = game_with(true, true);
+        assert!(game.drop_hand("alice", combos).is_ok());
+        assert_eq!(game.players[0].dropped_combinations.len(), 3);
+        assert_eq!(game.players[0].hand.len(), 1);
+    }
+
+    #[test]
+    fn hand_order_survives_draws_and_plays() {
+        use crate::card::{Suit, Value};
+        let mut game = GameState::new(vec!["alice".to_string(), "bob".to_string()]);
+        game.start_round();
+        let mut order = game.players[0].hand.clone();
+        order.reverse();
+        game.reorder_hand("alice", order.clone()).unwrap();
+
+        game.draw_from_deck().unwrap();
+        let drawn = *game.players[0].hand.last().unwrap();
+        assert_eq!(
+            game.players[0].hand[..12],
+            order[..],
+            "Draws land at the end"
+        );
+
+        game.discard(3).unwrap();
+        order.remove(3);
+        order.push(drawn);
+        assert_eq!(
+            game.players[0].hand, order,
+            "Discards keep the rest in place"
+        );
+
+        game.players[0].hand = vec![
+            std(Suit::Hearts, Value::Two),
+            std(Suit::Hearts, Value::Three),
+            Card::Joker,
+        ];
+        // Built before the joker was drawn, and still listing a card since played
+        let stale = vec![
+            std(Suit::Clubs, Value::King),
+            std(Suit::Hearts, Value::Three),
+            std(Suit::Hearts, Value::Two),
+        ];
+        game.reorder_hand("alice", stale).unwrap();
+        assert_eq!(
+            game.players[0].hand,
+            vec![
+                std(Suit::Hearts, Value::Three),
+                std(Suit::Hearts, Value::Two),
+                Card::Joker,
+            ]
+        );
+    }
+
+    #[test]
+    fn paused_game_refuses_moves() {
+        let mut game = GameState::new(vec!["alice".to_string(), "bob".to_string()]);
+        game.start_round();
+        game.is_paused = true;
+
+        assert_eq!(game.draw_from_deck(), Err("game_paused"));
+        assert_eq!(game.draw_from_discard(), Err("game_paused"));
+        assert!(
+            game.reorder_hand("alice", Vec::new()).is_ok(),
+            "Hands can still be arranged"
+        );
+
+        game.is_paused = false;
+        assert!(game.draw_from_deck().is_ok());
+    }
+}
@@ -0,0 +1,178 @@
+use serde::{Deserialize, Serialize};
+
+use crate::card::{Card, Suit};
+use crate::combo_finder::{
+    MeldCandidate, ShedPosition, can_shed, find_all_escala_candidates, find_all_trio_candidates,
+};
+use crate::rule_set::{JokerLimits, TwoBehavior};
+use crate::rules::{MIN_ESCALA_LEN, is_wildcard};
+
+/// How a `SortHand` request arranges the cards.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SortBy {
+    /// Grouped by suit, ascending value within each suit.
+    Suit,
+    /// Ascending value, suits in a fixed order among equal values.
+    Value,
+    /// Cards forming escalas and trios first, each meld kept together, then the rest by suit.
+    Melds,
+}
+
+/// Returns `hand` rearranged according to `by`. Jokers not claimed by a meld go last.
+pub fn sorted_hand(
+    hand: &[Card],
+    by: SortBy,
+    limits: &JokerLimits,
+    twos: TwoBehavior,
+) -> Vec<Card> {
+    match by {
+        SortBy::Suit => sorted_by_suit(hand.to_vec()),
+        SortBy::Value => {
+            let mut cards = hand.to_vec();
+            cards.sort_by_key(|card| match card {
+                Card::Standard { suit, value } => (0, *value as u8, suit_rank(*suit)),
+                Card::Joker => (1, 0, 0),
+            });
+            cards
+        }
+        SortBy::Melds => sorted_by_melds(hand, limits, twos),
+    }
+}
+
+fn sorted_by_suit(mut cards: Vec<Card>) -> Vec<Card> {
+    cards.sort_by_key(|card| match card {
+        Card::Standard { suit, value } => (0, suit_rank(*suit), *value as u8),
+        Card::Joker => (1, 0, 0),
+    });
+    cards
+}
+
+fn sorted_by_melds(hand: &[Card], limits: &JokerLimits, twos: TwoBehavior) -> Vec<Card> {
+    let joker_count = |m: &MeldCandidate| {
+        m.card_indices
+            .iter()
+            .filter(|&&i| is_wildcard(&hand[i], twos))
+            .count()
+    };
+    // Escalas before trios, natural melds before ones leaning on jokers
+    let mut escalas = find_all_escala_candidates(hand, limits.per_escala, MIN_ESCALA_LEN, twos);
+    escalas.sort_by_key(joker_count);
+    let mut trios = find_all_trio_candidates(hand, limits.per_trio, twos);
+    trios.sort_by_key(joker_count);
+
+    let mut used = vec![false; hand.len()];
+    let mut melds: Vec<Vec<Card>> = Vec::new();
+    for candidate in escalas.iter().chain(trios.iter()) {
+        if candidate.card_indices.iter().any(|&i| used[i]) {
+            continue;
+        }
+        for &i in &candidate.card_indices {
+            used[i] = true;
+        }
+        melds.push(candidate.card_indices.iter().map(|&i| hand[i]).collect());
+    }
+
+    // Grow each meld with loose standard cards that extend it, as if shedding onto it
+    for meld in &mut melds {
+        while let Some((i, position)) = hand.iter().enumerate().find_map(|(i, card)| {
+            if used[i] || is_wildcard(card, twos) {
+                return None;
+            }
+            can_shed(card, meld, limits, twos).map(|position| (i, position))
+        }) {
+            used[i] = true;
+            match position {
+                ShedPosition::ExtendLeft => meld.insert(0, hand[i]),
+                _ => meld.push(hand[i]),
+            }
+        }
+    }
+
+    let rest = hand
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| !used[*i])
+        .map(|(_, card)| *card)
+        .collect();
+    melds
+        .concat()
+        .into_iter()
+        .chain(sorted_by_suit(rest))
+        .collect()
+}
+
+fn suit_rank(suit: Suit) -> u8 {
+    match suit {
+        Suit::Hearts => 0,
+        Suit::Diamonds => 1,
+        Suit::Clubs => 2,
+        Suit::Spades => 3,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::card::Value;
+
+    fn std(suit: Suit, value: Value) -> Card {
+        Card::Standard { suit, value }
+    }
+
+    #[test]
+    fn sorts_by_suit_and_by_value() {
+        let hand = vec![
+            Card::Joker,
+            std(Suit::Spades, Value::Two),
+            std(Suit::Hearts, Value::King),
+            std(Suit::Hearts, Value::Three),
+        ];
+        let limits = JokerLimits::default();
+
+        assert_eq!(
+            sorted_hand(&hand, SortBy::Suit, &limits, TwoBehavior::Natural),
+            vec![hand[3], hand[2], hand[1], Card::Joker]
+        );
+        assert_eq!(
+            sorted_hand(&hand, SortBy::Value, &limits, TwoBehavior::Natural),
+            vec![hand[1], hand[3], hand[2], Card::Joker]
+        );
+    }
+
+    #[test]
+    fn melds_are_grouped_together() {
+        let hand = vec![
+            std(Suit::Clubs, Value::Nine),
+            std(Suit::Hearts, Value::Five),
+            std(Suit::Spades, Value::Nine),
+            std(Suit::Hearts, Value::Seven),
+            std(Suit::Diamonds, Value::Two),
+            std(Suit::Hearts, Value::Six),
+            std(Suit::Hearts, Value::Nine),
+            std(Suit::Hearts, Value::Eight),
+            std(Suit::Hearts, Value::Four),
+        ];
+        let sorted = sorted_hand(
+            &hand,
+            SortBy::Melds,
+            &JokerLimits::default(),
+            TwoBehavior::Natural,
+        );
+
+        assert_eq!(
+            sorted,
+            vec![
+                std(Suit::Hearts, Value::Four),
+                std(Suit::Hearts, Value::Five),
+                std(Suit::Hearts, Value::Six),
+                std(Suit::Hearts, Value::Seven),
+                std(Suit::Hearts, Value::Eight),
+                // The 9♥ completes the trio rather than stretching the escala
+                std(Suit::Clubs, Value::Nine),
+                std(Suit::Spades, Value::Nine),
+                std(Suit::Hearts, Value::Nine),
+                std(Suit::Diamonds, Value::Two),
+            ]
+        );
+    }
+}
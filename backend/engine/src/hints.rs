@@ -0,0 +1,118 @@
+use crate::card::Card;
+use crate::combo_finder::{find_all_escala_candidates, find_all_trio_candidates, find_best_bajada};
+use crate::game::RoundType;
+use crate::rule_set::RuleSet;
+
+/// Most detailed hint there is: the solver's whole bajada.
+pub const MAX_HINT_LEVEL: u8 = 3;
+
+/// What a hint reveals about a hand, more the higher the level:
+/// 1. whether the round's bajada can be laid down;
+/// 2. also which cards belong to some meld the round asks for;
+/// 3. also the bajada the solver would lay down.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hint {
+    pub level: u8,
+    pub bajada_possible: bool,
+    /// Hand indices, in order; level 2 and up
+    pub meld_cards: Option<Vec<usize>>,
+    /// Level 3 only; `None` there too when no bajada is possible
+    pub combinations: Option<Vec<Vec<Card>>>,
+}
+
+/// Works out the hint for `hand` in `round`. `level` must be 1 to [`MAX_HINT_LEVEL`].
+pub fn hint(hand: &[Card], round: &RoundType, rules: &RuleSet, level: u8) -> Hint {
+    let (req_trios, req_escalas) = round.get_requirements();
+    let min_escala = rules.min_escala_len(round);
+    let bajada = find_best_bajada(hand, req_trios, req_escalas, min_escala, true, rules);
+
+    let meld_cards = (level >= 2).then(|| {
+        let mut candidates = Vec::new();
+        if req_trios > 0 {
+            candidates.extend(find_all_trio_candidates(
+                hand,
+                rules.jokers.per_trio,
+                rules.twos,
+            ));
+        }
+        if req_escalas > 0 {
+            let escalas =
+                find_all_escala_candidates(hand, rules.jokers.per_escala, min_escala, rules.twos);
+            candidates.extend(escalas);
+        }
+        let mut indices: Vec<usize> = candidates
+            .into_iter()
+            .flat_map(|meld| meld.card_indices)
+            .collect();
+        indices.sort_unstable();
+        indices.dedup();
+        indices
+    });
+
+    let combinations = if level >= 3 {
+        bajada.as_ref().map(|melds| {
+            melds
+                .iter()
+                .map(|meld| meld.card_indices.iter().map(|&i| hand[i]).collect())
+                .collect()
+        })
+    } else {
+        None
+    };
+
+    Hint {
+        level,
+        bajada_possible: bajada.is_some(),
+        meld_cards,
+        combinations,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::card::{Suit, Value};
+
+    fn std(suit: Suit, value: Value) -> Card {
+        Card::Standard { suit, value }
+    }
+
+    fn two_trios_hand() -> Vec<Card> {
+        vec![
+            std(Suit::Hearts, Value::Five),
+            std(Suit::Clubs, Value::Five),
+            std(Suit::Spades, Value::Five),
+            std(Suit::Hearts, Value::King),
+            std(Suit::Hearts, Value::Nine),
+            std(Suit::Clubs, Value::Nine),
+            std(Suit::Diamonds, Value::Nine),
+            std(Suit::Spades, Value::Two),
+        ]
+    }
+
+    #[test]
+    fn each_level_reveals_more() {
+        let hand = two_trios_hand();
+        let rules = RuleSet::default();
+
+        let first = hint(&hand, &RoundType::TwoTrios, &rules, 1);
+        assert!(first.bajada_possible);
+        assert_eq!((first.meld_cards, first.combinations), (None, None));
+
+        let second = hint(&hand, &RoundType::TwoTrios, &rules, 2);
+        assert_eq!(second.meld_cards, Some(vec![0, 1, 2, 4, 5, 6]));
+        assert_eq!(second.combinations, None);
+
+        let third = hint(&hand, &RoundType::TwoTrios, &rules, 3);
+        assert_eq!(third.combinations.map(|c| c.len()), Some(2));
+    }
+
+    #[test]
+    fn only_the_rounds_meld_types_count() {
+        let hand = two_trios_hand();
+        let hint = hint(&hand, &RoundType::TwoEscalas, &RuleSet::default(), 3);
+        assert!(!hint.bajada_possible);
+        assert_eq!(hint.meld_cards, Some(vec![]));
+        assert_eq!(hint.combinations, None);
+    }
+}
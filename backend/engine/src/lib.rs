@@ -0,0 +1,35 @@
+//! Carioca's rules, free of any networking or storage.
+//!
+//! A round lives in [`GameState`], built from a [`RuleSet`] and driven one [`Action`] at a
+//! time through its methods (`draw_from_deck`, `drop_hand`, `shed_card`, `discard`, ...).
+//! [`combo_finder`] is the solver behind bajadas and sheds, [`bot`] picks moves for computer
+//! players, [`analysis`] scores them, and [`sim`] plays whole games headless.
+//!
+//! The server crate wraps this one; anything else that wants to deal, check or play a hand
+//! of Carioca can depend on it directly.
+
+pub mod action;
+pub mod analysis;
+pub mod bot;
+pub mod card;
+pub mod combo_finder;
+pub mod deck;
+pub mod game;
+pub mod hand_sort;
+pub mod hints;
+pub mod podium;
+pub mod points;
+pub mod replay;
+pub mod rule_set;
+pub mod rules;
+pub mod shuffle;
+pub mod sim;
+pub mod snapshot;
+pub mod timing;
+pub mod transcript;
+
+pub use action::Action;
+pub use bot::{BotDifficulty, play_bot_turn};
+pub use combo_finder::find_best_bajada;
+pub use game::{GameState, PlayerState, RoundType};
+pub use rule_set::RuleSet;
@@ -0,0 +1,276 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::game::TieBreakOutcome;
+use crate::transcript::Transcript;
+
+/// The end-of-game screen: final places, how each round was scored and the awards.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Podium {
+    /// Best first; tied totals share a place, except that a tie broken for the win puts its
+    /// winner first and the others second
+    pub standings: Vec<Standing>,
+    /// Column order of `rounds[..].points`
+    pub seats: Vec<String>,
+    pub rounds: Vec<RoundRow>,
+    pub awards: Vec<Award>,
+    pub tie_break: Option<TieBreakOutcome>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Standing {
+    /// 1-based
+    pub place: usize,
+    pub player_id: String,
+    pub total_points: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RoundRow {
+    pub round_index: usize,
+    pub round_name: String,
+    /// Points each seat took that round
+    pub points: Vec<u32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AwardKind {
+    /// Shed the most cards onto the table over the game
+    MostSheds,
+    /// Dropped their hand on the earliest turn of any round
+    FastestBajada,
+}
+
+/// Goes to every player tied for it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Award {
+    pub kind: AwardKind,
+    pub player_id: String,
+    /// Cards shed, or the turn the bajada came on
+    pub value: u32,
+}
+
+impl Podium {
+    /// `final_totals` in seat order.
+    pub fn new(
+        final_totals: &[(String, u32)],
+        transcript: &Transcript,
+        tie_break: Option<TieBreakOutcome>,
+    ) -> Self {
+        let mut ranked: Vec<&(String, u32)> = final_totals.iter().collect();
+        ranked.sort_by_key(|(_, total)| *total);
+        let mut standings: Vec<Standing> = Vec::with_capacity(ranked.len());
+        for (i, (id, total)) in ranked.into_iter().enumerate() {
+            let place = match standings.last() {
+                Some(prev) if prev.total_points == *total => prev.place,
+                _ => i + 1,
+            };
+            standings.push(Standing {
+                place,
+                player_id: id.clone(),
+                total_points: *total,
+            });
+        }
+        if let Some(winner) = tie_break.as_ref().and_then(|t| t.winner_id.as_ref()) {
+            for standing in &mut standings {
+                if standing.place == 1 && &standing.player_id != winner {
+                    standing.place = 2;
+                }
+            }
+            standings.sort_by_key(|s| s.place);
+        }
+
+        let seats: Vec<String> = final_totals.iter().map(|(id, _)| id.clone()).collect();
+        let rounds = transcript
+            .rounds
+            .iter()
+            .filter(|round| !round.scores.is_empty())
+            .map(|round| RoundRow {
+                round_index: round.round_index,
+                round_name: round.round_name.clone(),
+                points: seats
+                    .iter()
+                    .map(|id| {
+                        round
+                            .scores
+                            .iter()
+                            .find(|s| &s.player_id == id)
+                            .map_or(0, |s| s.round_points)
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        Self {
+            standings,
+            awards: awards(&seats, transcript),
+            seats,
+            rounds,
+            tie_break,
+        }
+    }
+}
+
+fn awards(seats: &[String], transcript: &Transcript) -> Vec<Award> {
+    let mut sheds: HashMap<&str, u32> = HashMap::new();
+    // Fewest turns any player needed to drop their hand in a round
+    let mut bajadas: HashMap<&str, u32> = HashMap::new();
+    for round in &transcript.rounds {
+        let mut turns: HashMap<&str, u32> = HashMap::new();
+        for play in &round.moves {
+            let player = play.player_id.as_str();
+            match play.action.as_str() {
+                "shed" => *sheds.entry(player).or_default() += 1,
+                "bajó" => {
+                    let turn = turns.get(player).copied().unwrap_or_default() + 1;
+                    let best = bajadas.entry(player).or_insert(turn);
+                    *best = (*best).min(turn);
+                }
+                // Whatever ends a turn
+                "discarded" | "passed" | "penalty_draw" | "skipped" => {
+                    *turns.entry(player).or_default() += 1
+                }
+                _ => {}
+            }
+        }
+    }
+
+    let mut awards = Vec::new();
+    if let Some(most) = sheds.values().copied().max() {
+        awards.extend(winners(seats, &sheds, most, AwardKind::MostSheds));
+    }
+    if let Some(fastest) = bajadas.values().copied().min() {
+        awards.extend(winners(seats, &bajadas, fastest, AwardKind::FastestBajada));
+    }
+    awards
+}
+
+fn winners(
+    seats: &[String],
+    values: &HashMap<&str, u32>,
+    best: u32,
+    kind: AwardKind,
+) -> Vec<Award> {
+    seats
+        .iter()
+        .filter(|id| values.get(id.as_str()) == Some(&best))
+        .map(|id| Award {
+            kind,
+            player_id: id.clone(),
+            value: best,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transcript::{Move, RoundScore, RoundTranscript};
+
+    fn play(player_id: &str, action: &str) -> Move {
+        Move {
+            player_id: player_id.to_string(),
+            action: action.to_string(),
+            card: None,
+            combinations: Vec::new(),
+        }
+    }
+
+    fn round(round_index: usize, moves: Vec<Move>, scores: &[(&str, u32, u32)]) -> RoundTranscript {
+        RoundTranscript {
+            round_index,
+            round_name: format!("Round {}", round_index + 1),
+            deck_seed: None,
+            deal: Vec::new(),
+            first_discard: None,
+            moves,
+            scores: scores
+                .iter()
+                .map(|(id, round_points, total_points)| RoundScore {
+                    player_id: id.to_string(),
+                    round_points: *round_points,
+                    total_points: *total_points,
+                })
+                .collect(),
+            winner_id: None,
+        }
+    }
+
+    #[test]
+    fn ranks_scores_and_awards_the_game() {
+        let transcript = Transcript {
+            rounds: vec![
+                round(
+                    0,
+                    vec![
+                        play("ana", "discarded"),
+                        play("beto", "bajó"),
+                        play("beto", "discarded"),
+                        play("ana", "bajó"),
+                        play("ana", "shed"),
+                        play("ana", "discarded"),
+                    ],
+                    &[("ana", 0, 0), ("beto", 20, 20), ("carla", 35, 35)],
+                ),
+                round(
+                    1,
+                    vec![play("carla", "bajó"), play("beto", "shed")],
+                    &[("ana", 20, 20), ("beto", 0, 20), ("carla", 10, 45)],
+                ),
+            ],
+        };
+        let totals = [
+            ("ana".to_string(), 20),
+            ("beto".to_string(), 20),
+            ("carla".to_string(), 45),
+        ];
+        let podium = Podium::new(&totals, &transcript, None);
+
+        let places: Vec<(usize, &str)> = podium
+            .standings
+            .iter()
+            .map(|s| (s.place, s.player_id.as_str()))
+            .collect();
+        assert_eq!(places, [(1, "ana"), (1, "beto"), (3, "carla")]);
+        assert_eq!(podium.rounds[0].points, [0, 20, 35]);
+        assert_eq!(podium.rounds[1].points, [20, 0, 10]);
+
+        let awarded: Vec<(AwardKind, &str, u32)> = podium
+            .awards
+            .iter()
+            .map(|a| (a.kind, a.player_id.as_str(), a.value))
+            .collect();
+        assert_eq!(
+            awarded,
+            [
+                (AwardKind::MostSheds, "ana", 1),
+                (AwardKind::MostSheds, "beto", 1),
+                (AwardKind::FastestBajada, "beto", 1),
+                (AwardKind::FastestBajada, "carla", 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn a_broken_tie_seats_its_winner_alone() {
+        let totals = [
+            ("ana".to_string(), 20),
+            ("beto".to_string(), 20),
+            ("carla".to_string(), 45),
+        ];
+        let tie_break = TieBreakOutcome {
+            policy: crate::rule_set::TieBreak::MostRoundsWon,
+            tied: vec!["ana".to_string(), "beto".to_string()],
+            winner_id: Some("beto".to_string()),
+        };
+        let podium = Podium::new(&totals, &Transcript::default(), Some(tie_break));
+
+        let places: Vec<(usize, &str)> = podium
+            .standings
+            .iter()
+            .map(|s| (s.place, s.player_id.as_str()))
+            .collect();
+        assert_eq!(places, [(1, "beto"), (2, "ana"), (3, "carla")]);
+    }
+}
@@ -0,0 +1,83 @@
+use crate::card::Card;
+use serde::{Deserialize, Serialize};
+
+/// Penalty values for cards left in hand. Number cards always count their face value;
+/// the rest differ between families.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PointsTable {
+    pub joker: u32,
+    pub ace: u32,
+    /// Jack, Queen and King
+    pub face_cards: u32,
+    /// A two while twos are wild, see [`crate::rule_set::TwoBehavior::WildTwo`]
+    pub wild_two: u32,
+}
+
+impl Default for PointsTable {
+    fn default() -> Self {
+        Self {
+            joker: 50,
+            ace: 20,
+            face_cards: 10,
+            wild_two: 20,
+        }
+    }
+}
+
+pub fn calculate_hand_points(hand: &[Card], table: &PointsTable) -> u32 {
+    hand.iter().map(|card| card.points(table)).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::card::{Suit, Value};
+
+    #[test]
+    fn test_calculate_points() {
+        let hand = vec![
+            Card::Standard {
+                suit: Suit::Hearts,
+                value: Value::Two,
+            }, // 2
+            Card::Standard {
+                suit: Suit::Spades,
+                value: Value::Ten,
+            }, // 10
+            Card::Joker, // 50
+            Card::Standard {
+                suit: Suit::Diamonds,
+                value: Value::Ace,
+            }, // 20
+        ];
+
+        assert_eq!(calculate_hand_points(&hand, &PointsTable::default()), 82);
+    }
+
+    #[test]
+    fn test_custom_points_table() {
+        let hand = vec![
+            Card::Joker,
+            Card::Standard {
+                suit: Suit::Diamonds,
+                value: Value::Ace,
+            },
+            Card::Standard {
+                suit: Suit::Clubs,
+                value: Value::King,
+            },
+            Card::Standard {
+                suit: Suit::Clubs,
+                value: Value::Ten,
+            },
+        ];
+        let table = PointsTable {
+            joker: 30,
+            ace: 15,
+            face_cards: 5,
+            ..PointsTable::default()
+        };
+
+        assert_eq!(calculate_hand_points(&hand, &table), 60);
+    }
+}
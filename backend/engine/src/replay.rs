@@ -0,0 +1,276 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
+use crate::action::Action;
+use crate::card::Card;
+use crate::game::GameState;
+use crate::hand_sort::SortBy;
+use crate::rule_set::RuleSet;
+use crate::shuffle::ShuffleSeed;
+use crate::sim;
+
+/// Something that changed the table, in the order the room applied it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum Input {
+    Move {
+        player_id: String,
+        action: Action,
+    },
+    ReorderHand {
+        player_id: String,
+        hand: Vec<Card>,
+    },
+    SortHand {
+        player_id: String,
+        by: SortBy,
+    },
+    Ready {
+        player_id: String,
+    },
+    /// The ready countdown ran out and the next round was dealt
+    ForceReady,
+    /// The turn holder ran out of time and took a penalty card
+    TimedOut,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Step {
+    pub input: Input,
+    /// [`state_hash`] of the table once the input was applied
+    pub state_hash: u64,
+}
+
+/// Everything needed to play a game again: who sat where, the rules, the seed every deal
+/// follows from and each accepted input with the state it led to.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplayLog {
+    pub players: Vec<String>,
+    pub rules: RuleSet,
+    /// For games dealt from a `game_seed`; unused when `shuffle_key` is set
+    #[serde(default)]
+    pub game_seed: u64,
+    /// For games dealt from a secret key
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shuffle_key: Option<ShuffleSeed>,
+    /// [`state_hash`] of the first deal
+    pub initial_hash: u64,
+    pub steps: Vec<Step>,
+}
+
+/// Where a replay parted ways with the recording.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub enum Mismatch {
+    /// The seed deals a different first round
+    Deal,
+    /// The engine refuses the input at `step`
+    Rejected { step: usize, error: String },
+    /// The input at `step` leads to a different table
+    Diverged { step: usize },
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Mismatch::Deal => write!(f, "the seed deals a different first round"),
+            Mismatch::Rejected { step, error } => write!(f, "step {} is rejected: {}", step, error),
+            Mismatch::Diverged { step } => write!(f, "the table differs after step {}", step),
+        }
+    }
+}
+
+impl ReplayLog {
+    /// Starts recording a game dealt from its `game_seed` or `shuffle_key`. `None` for
+    /// tables that weren't, e.g. a stacked tutorial deck or a position laid out by hand.
+    pub fn start(state: &GameState) -> Option<Self> {
+        let seeded = state.game_seed.is_some() && state.deck_seed.is_some();
+        let keyed = state.shuffle_key.is_some() && state.round_seed.is_some();
+        if state.round_index != 0 || !(seeded || keyed) {
+            return None;
+        }
+        Some(Self {
+            players: state.players.iter().map(|p| p.id.clone()).collect(),
+            rules: state.rules.clone(),
+            game_seed: state.game_seed.unwrap_or_default(),
+            shuffle_key: state
+                .game_seed
+                .is_none()
+                .then_some(state.shuffle_key)
+                .flatten(),
+            initial_hash: state_hash(state),
+            steps: Vec::new(),
+        })
+    }
+
+    /// Notes an input the room just applied to `state`.
+    pub fn record(&mut self, input: Input, state: &GameState) {
+        self.steps.push(Step {
+            input,
+            state_hash: state_hash(state),
+        });
+    }
+}
+
+/// Applies an input the way the room did when it was recorded.
+pub fn apply(state: &mut GameState, input: &Input) -> Result<(), &'static str> {
+    match input {
+        Input::Move { player_id, action } => {
+            let holder = state.players.get(state.current_turn).map(|p| p.id.as_str());
+            if holder != Some(player_id.as_str()) {
+                return Err("not_your_turn");
+            }
+            sim::apply(state, player_id, action.clone()).map(|_| ())
+        }
+        Input::ReorderHand { player_id, hand } => state.reorder_hand(player_id, hand.clone()),
+        Input::SortHand { player_id, by } => state.sort_hand(player_id, *by),
+        Input::Ready { player_id } => state.mark_player_ready(player_id),
+        Input::ForceReady => state.force_ready_all(),
+        Input::TimedOut => state.skip_timed_out_turn().map(|_| ()),
+    }
+}
+
+/// Deals the game again from its seed and plays every recorded input, checking the table
+/// against the recording after each one.
+pub fn verify(log: &ReplayLog) -> Result<(), Mismatch> {
+    let mut state = GameState::with_rules(log.players.clone(), log.rules.clone());
+    state.game_seed = log.shuffle_key.is_none().then_some(log.game_seed);
+    state.shuffle_key = log.shuffle_key;
+    state.start_round();
+    if state_hash(&state) != log.initial_hash {
+        return Err(Mismatch::Deal);
+    }
+
+    for (step, recorded) in log.steps.iter().enumerate() {
+        apply(&mut state, &recorded.input).map_err(|error| Mismatch::Rejected {
+            step,
+            error: error.to_string(),
+        })?;
+        if state_hash(&state) != recorded.state_hash {
+            return Err(Mismatch::Diverged { step });
+        }
+    }
+    Ok(())
+}
+
+/// Fingerprint of everything the inputs can change: hands, melds, piles, scores and whose
+/// turn it is. Clocks and pauses are left out. Stable across runs and builds, so recorded
+/// hashes can be checked later.
+pub fn state_hash(state: &GameState) -> u64 {
+    let mut hasher = Fnv::default();
+    state.round_index.hash(&mut hasher);
+    state.current_turn.hash(&mut hasher);
+    state.is_waiting_for_next_round.hash(&mut hasher);
+    state.is_game_over.hash(&mut hasher);
+    state.stalled_turns.hash(&mut hasher);
+    state.deck.cards().hash(&mut hasher);
+    state.discard_pile.hash(&mut hasher);
+    for player in &state.players {
+        player.id.hash(&mut hasher);
+        player.hand.hash(&mut hasher);
+        player.points.hash(&mut hasher);
+        player.has_dropped_hand.hash(&mut hasher);
+        player.dropped_combinations.hash(&mut hasher);
+        player.has_drawn_this_turn.hash(&mut hasher);
+        player.is_ready_for_next_round.hash(&mut hasher);
+        player.penalty_cards.hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+/// 64-bit FNV-1a; unlike `DefaultHasher` its output is fixed.
+struct Fnv(u64);
+
+impl Default for Fnv {
+    fn default() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for Fnv {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sim::{Policy, RandomPolicy};
+
+    /// A whole game of random play, recorded the way a room records it.
+    fn recorded_game(seed: u64) -> ReplayLog {
+        let ids: Vec<String> = (1..=3).map(|n| format!("p{}", n)).collect();
+        let mut game = GameState::with_rules(ids, RuleSet::default());
+        game.game_seed = Some(seed);
+        game.start_round();
+        play_out(game, seed)
+    }
+
+    fn play_out(mut game: GameState, seed: u64) -> ReplayLog {
+        let mut log = ReplayLog::start(&game).expect("dealt from a seed");
+        let mut policies: Vec<RandomPolicy> = (0..3).map(|n| RandomPolicy::new(seed + n)).collect();
+
+        while !game.is_game_over {
+            let input = if game.is_waiting_for_next_round {
+                let waiting = game.players.iter().find(|p| !p.is_ready_for_next_round);
+                Input::Ready {
+                    player_id: waiting.unwrap().id.clone(),
+                }
+            } else {
+                let player_id = game.players[game.current_turn].id.clone();
+                let action = policies[game.current_turn].act(&game, &player_id).unwrap();
+                Input::Move { player_id, action }
+            };
+            apply(&mut game, &input).unwrap();
+            log.record(input, &game);
+        }
+        log
+    }
+
+    #[test]
+    fn a_recorded_game_replays_to_the_same_tables() {
+        let log = recorded_game(7);
+        assert!(log.steps.len() > 100);
+        assert_eq!(verify(&log), Ok(()));
+    }
+
+    #[test]
+    fn a_game_dealt_from_a_secret_key_replays_too() {
+        let mut game = GameState::new(vec!["p1".into(), "p2".into()]);
+        game.start_round();
+        let mut log = play_out(game, 3);
+        assert!(log.shuffle_key.is_some());
+        assert_eq!(verify(&log), Ok(()));
+
+        log.shuffle_key = Some(ShuffleSeed::random());
+        assert_eq!(verify(&log), Err(Mismatch::Deal));
+    }
+
+    #[test]
+    fn tampering_is_caught_where_it_happens() {
+        let mut log = recorded_game(11);
+        log.steps[40].state_hash ^= 1;
+        assert_eq!(verify(&log), Err(Mismatch::Diverged { step: 40 }));
+
+        let mut log = recorded_game(11);
+        log.game_seed += 1;
+        assert_eq!(verify(&log), Err(Mismatch::Deal));
+
+        let mut log = recorded_game(11);
+        let first_move = log
+            .steps
+            .iter()
+            .position(|s| matches!(s.input, Input::Move { .. }))
+            .unwrap();
+        log.steps.remove(first_move);
+        assert!(verify(&log).is_err());
+    }
+}
@@ -0,0 +1,477 @@
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+use crate::card::{Card, Value};
+use crate::deck::STANDARD_JOKERS;
+use crate::game::RoundType;
+use crate::points::PointsTable;
+use crate::rules::{ESCALA_CORTA_LEN, MIN_ESCALA_LEN};
+
+/// Who opens the next round once one ends.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TurnRotation {
+    /// Round N is opened by seat N (mod players), regardless of results.
+    #[default]
+    FixedRotation,
+    /// The player who went out opens the next round.
+    WinnerStarts,
+    /// The player who went out deals next; the seat to the dealer's left opens.
+    DealerLeft,
+}
+
+impl TurnRotation {
+    /// Seat that opens the round numbered `next_round_index`.
+    pub fn starting_seat(&self, next_round_index: usize, winner: usize, players: usize) -> usize {
+        match self {
+            TurnRotation::FixedRotation => next_round_index % players,
+            TurnRotation::WinnerStarts => winner,
+            TurnRotation::DealerLeft => (winner + 1) % players,
+        }
+    }
+}
+
+impl FromStr for TurnRotation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "fixed_rotation" => Ok(TurnRotation::FixedRotation),
+            "winner_starts" => Ok(TurnRotation::WinnerStarts),
+            "dealer_left" => Ok(TurnRotation::DealerLeft),
+            other => Err(format!("Unknown turn rotation: {}", other)),
+        }
+    }
+}
+
+/// Reward for a "carioca": dropping the whole hand and discarding the last card in one turn.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "points", rename_all = "snake_case")]
+pub enum CariocaBonus {
+    /// Scored like any other win.
+    #[default]
+    None,
+    /// Opponents only take half their hand's penalty (rounded down).
+    HalveOpponents,
+    /// The winner's running total drops by this many points (never below zero).
+    WinnerBonus(u32),
+}
+
+impl FromStr for CariocaBonus {
+    type Err = String;
+
+    /// Accepts `none`, `halve_opponents` or `winner_bonus:<points>`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once(':') {
+            None if s == "none" => Ok(CariocaBonus::None),
+            None if s == "halve_opponents" => Ok(CariocaBonus::HalveOpponents),
+            Some(("winner_bonus", points)) => points
+                .parse()
+                .map(CariocaBonus::WinnerBonus)
+                .map_err(|_| format!("Invalid carioca bonus points: {}", points)),
+            _ => Err(format!("Unknown carioca bonus: {}", s)),
+        }
+    }
+}
+
+/// How much of the discard pile players may look through.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "count", rename_all = "snake_case")]
+pub enum DiscardVisibility {
+    /// Only the card on top, which anyone may still take.
+    #[default]
+    TopOnly,
+    /// The top card plus those under it, up to this many cards in total.
+    Recent(usize),
+    /// Every card discarded this round.
+    All,
+}
+
+impl DiscardVisibility {
+    /// How many cards from the top of a pile of `pile_len` are public.
+    pub fn visible_count(&self, pile_len: usize) -> usize {
+        match self {
+            DiscardVisibility::TopOnly => pile_len.min(1),
+            DiscardVisibility::Recent(count) => pile_len.min((*count).max(1)),
+            DiscardVisibility::All => pile_len,
+        }
+    }
+}
+
+impl FromStr for DiscardVisibility {
+    type Err = String;
+
+    /// Accepts `top_only`, `all` or `recent:<count>`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once(':') {
+            None if s == "top_only" => Ok(DiscardVisibility::TopOnly),
+            None if s == "all" => Ok(DiscardVisibility::All),
+            Some(("recent", count)) => count
+                .parse()
+                .map(DiscardVisibility::Recent)
+                .map_err(|_| format!("Invalid discard visibility count: {}", count)),
+            _ => Err(format!("Unknown discard visibility: {}", s)),
+        }
+    }
+}
+
+/// What happens to a game every human has walked away from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AbandonmentPolicy {
+    /// The game is discarded: no standings, no rating changes.
+    #[default]
+    Void,
+    /// The running totals stand as the final result, as if the game had been played out.
+    CurrentTotals,
+}
+
+impl AbandonmentPolicy {
+    /// Whether an abandoned game still produces a result that counts.
+    pub fn counts_result(&self) -> bool {
+        matches!(self, AbandonmentPolicy::CurrentTotals)
+    }
+}
+
+impl FromStr for AbandonmentPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "void" => Ok(AbandonmentPolicy::Void),
+            "current_totals" => Ok(AbandonmentPolicy::CurrentTotals),
+            other => Err(format!("Unknown abandonment policy: {}", other)),
+        }
+    }
+}
+
+/// What the server does for a player whose turn clock runs out.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimeoutPolicy {
+    /// A bot plays the turn out for them.
+    #[default]
+    AutoPlay,
+    /// They take the top card of the deck as a penalty and lose the turn.
+    PenaltyDraw,
+}
+
+impl FromStr for TimeoutPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto_play" => Ok(TimeoutPolicy::AutoPlay),
+            "penalty_draw" => Ok(TimeoutPolicy::PenaltyDraw),
+            other => Err(format!("Unknown timeout policy: {}", other)),
+        }
+    }
+}
+
+/// Whether escalas may be three cards long ("escala corta") instead of four.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "rounds", rename_all = "snake_case")]
+pub enum EscalaCorta {
+    /// Escalas are always at least four cards.
+    #[default]
+    Off,
+    /// Three-card escalas count in every round.
+    Always,
+    /// Three-card escalas count in these rounds only.
+    Rounds(Vec<RoundType>),
+}
+
+impl EscalaCorta {
+    pub fn allows(&self, round: &RoundType) -> bool {
+        match self {
+            EscalaCorta::Off => false,
+            EscalaCorta::Always => true,
+            EscalaCorta::Rounds(rounds) => rounds.contains(round),
+        }
+    }
+}
+
+impl FromStr for EscalaCorta {
+    type Err = String;
+
+    /// Accepts `off`, `always` or `rounds:<n>,<n>,...` with rounds numbered from 1.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once(':') {
+            None if s == "off" => Ok(EscalaCorta::Off),
+            None if s == "always" => Ok(EscalaCorta::Always),
+            Some(("rounds", numbers)) => {
+                let all = RoundType::all_rounds();
+                numbers
+                    .split(',')
+                    .map(|n| {
+                        n.trim()
+                            .parse::<usize>()
+                            .ok()
+                            .and_then(|n| all.get(n.checked_sub(1)?).copied())
+                            .ok_or_else(|| format!("Invalid escala corta round: {}", n))
+                    })
+                    .collect::<Result<_, _>>()
+                    .map(EscalaCorta::Rounds)
+            }
+            _ => Err(format!("Unknown escala corta setting: {}", s)),
+        }
+    }
+}
+
+/// How a tie for the lowest total is settled once the last round is scored.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TieBreak {
+    /// Tied players share the win.
+    #[default]
+    Shared,
+    /// Whoever of them went out in the most rounds.
+    MostRoundsWon,
+    /// Whoever of them took the fewest penalty cards for running out of time.
+    FewestPenaltyCards,
+    /// The table plays the last round again, scored as usual, until one player leads alone.
+    SuddenDeath,
+}
+
+impl FromStr for TieBreak {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "shared" => Ok(TieBreak::Shared),
+            "most_rounds_won" => Ok(TieBreak::MostRoundsWon),
+            "fewest_penalty_cards" => Ok(TieBreak::FewestPenaltyCards),
+            "sudden_death" => Ok(TieBreak::SuddenDeath),
+            other => Err(format!("Unknown tie break: {}", other)),
+        }
+    }
+}
+
+/// What a two can do in a meld.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TwoBehavior {
+    /// A two is only ever a two.
+    #[default]
+    Natural,
+    /// A two may also stand in for any card, like a joker and within the same joker limits.
+    /// Left in hand it scores [`PointsTable::wild_two`].
+    WildTwo,
+}
+
+impl FromStr for TwoBehavior {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "natural" => Ok(TwoBehavior::Natural),
+            "wild" | "wild_two" => Ok(TwoBehavior::WildTwo),
+            other => Err(format!("Unknown two behavior: {}", other)),
+        }
+    }
+}
+
+/// How many jokers a meld (and a whole bajada) may contain when it is laid down or extended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JokerLimits {
+    pub per_trio: usize,
+    pub per_escala: usize,
+    /// Across every meld of a single bajada ("no se valen los juegos con más de 2 comodines").
+    pub per_bajada: usize,
+}
+
+impl Default for JokerLimits {
+    fn default() -> Self {
+        Self {
+            per_trio: 1,
+            per_escala: 1,
+            per_bajada: 2,
+        }
+    }
+}
+
+/// House-rule options that vary between tables. The default reproduces the rules in `rules.md`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RuleSet {
+    pub turn_rotation: TurnRotation,
+    /// Turns completed after the deck runs out before the round is called a stalemate
+    /// and every hand is scored as it stands. `0` never calls it.
+    pub stalemate_turns: u32,
+    pub carioca_bonus: CariocaBonus,
+    pub points: PointsTable,
+    pub jokers: JokerLimits,
+    /// Whether cards may be shed in the same turn the bajada is laid down.
+    pub shed_on_bajada_turn: bool,
+    pub discard_visibility: DiscardVisibility,
+    pub abandonment: AbandonmentPolicy,
+    pub timeout: TimeoutPolicy,
+    /// Rounds a game lasts, counting from the first; `0` plays all nine.
+    pub round_limit: usize,
+    /// Most detailed hint players may ask for, see `crate::hints`; `0` turns hints off.
+    pub max_hint_level: u8,
+    pub escala_corta: EscalaCorta,
+    /// Whether a bajada may include complete melds beyond what the round asks for.
+    pub extra_melds: bool,
+    /// Jokers shuffled into the two packs, [`STANDARD_JOKERS`] by default.
+    pub deck_jokers: usize,
+    pub twos: TwoBehavior,
+    pub tie_break: TieBreak,
+}
+
+impl RuleSet {
+    /// How many of the `available` rounds a game plays.
+    pub fn round_count(&self, available: usize) -> usize {
+        match self.round_limit {
+            0 => available,
+            limit => limit.min(available),
+        }
+    }
+
+    /// Penalty for `card` left in hand at the end of a round.
+    pub fn card_points(&self, card: &Card) -> u32 {
+        match card {
+            Card::Standard {
+                value: Value::Two, ..
+            } if self.twos == TwoBehavior::WildTwo => self.points.wild_two,
+            _ => card.points(&self.points),
+        }
+    }
+
+    /// Penalty for everything in `hand`.
+    pub fn hand_points(&self, hand: &[Card]) -> u32 {
+        hand.iter().map(|card| self.card_points(card)).sum()
+    }
+
+    /// Fewest cards an escala needs in `round`.
+    pub fn min_escala_len(&self, round: &RoundType) -> usize {
+        if self.escala_corta.allows(round) {
+            ESCALA_CORTA_LEN
+        } else {
+            MIN_ESCALA_LEN
+        }
+    }
+}
+
+impl Default for RuleSet {
+    fn default() -> Self {
+        Self {
+            turn_rotation: TurnRotation::default(),
+            stalemate_turns: 8,
+            carioca_bonus: CariocaBonus::default(),
+            points: PointsTable::default(),
+            jokers: JokerLimits::default(),
+            shed_on_bajada_turn: false,
+            discard_visibility: DiscardVisibility::default(),
+            abandonment: AbandonmentPolicy::default(),
+            timeout: TimeoutPolicy::default(),
+            round_limit: 0,
+            max_hint_level: crate::hints::MAX_HINT_LEVEL,
+            escala_corta: EscalaCorta::default(),
+            extra_melds: false,
+            deck_jokers: STANDARD_JOKERS,
+            twos: TwoBehavior::default(),
+            tie_break: TieBreak::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::card::Suit;
+
+    #[test]
+    fn wild_twos_score_their_own_penalty() {
+        let two = Card::Standard {
+            suit: Suit::Hearts,
+            value: Value::Two,
+        };
+        let hand = [two, Card::Joker];
+        assert_eq!(RuleSet::default().hand_points(&hand), 52);
+
+        let rules = RuleSet {
+            twos: "wild".parse().unwrap(),
+            ..RuleSet::default()
+        };
+        assert_eq!(rules.card_points(&two), 20);
+        assert_eq!(rules.hand_points(&hand), 70);
+    }
+
+    #[test]
+    fn starting_seat_per_strategy() {
+        assert_eq!(TurnRotation::FixedRotation.starting_seat(5, 0, 4), 1);
+        assert_eq!(TurnRotation::WinnerStarts.starting_seat(5, 2, 4), 2);
+        assert_eq!(TurnRotation::DealerLeft.starting_seat(5, 3, 4), 0);
+    }
+
+    #[test]
+    fn parses_strategy_names() {
+        assert_eq!(
+            "winner_starts".parse::<TurnRotation>(),
+            Ok(TurnRotation::WinnerStarts)
+        );
+        assert!("clockwise".parse::<TurnRotation>().is_err());
+    }
+
+    #[test]
+    fn parses_carioca_bonus() {
+        assert_eq!("none".parse(), Ok(CariocaBonus::None));
+        assert_eq!("halve_opponents".parse(), Ok(CariocaBonus::HalveOpponents));
+        assert_eq!("winner_bonus:25".parse(), Ok(CariocaBonus::WinnerBonus(25)));
+        assert!("winner_bonus:lots".parse::<CariocaBonus>().is_err());
+    }
+
+    #[test]
+    fn escala_corta_per_round() {
+        let rounds: EscalaCorta = "rounds:3,7".parse().unwrap();
+        assert_eq!(
+            rounds,
+            EscalaCorta::Rounds(vec![RoundType::TwoEscalas, RoundType::ThreeEscalas])
+        );
+        let rules = RuleSet {
+            escala_corta: rounds,
+            ..RuleSet::default()
+        };
+        assert_eq!(
+            rules.min_escala_len(&RoundType::TwoEscalas),
+            ESCALA_CORTA_LEN
+        );
+        assert_eq!(
+            rules.min_escala_len(&RoundType::OneTrioOneEscala),
+            MIN_ESCALA_LEN
+        );
+
+        assert_eq!("always".parse(), Ok(EscalaCorta::Always));
+        assert!("rounds:0".parse::<EscalaCorta>().is_err());
+        assert!("rounds:10".parse::<EscalaCorta>().is_err());
+    }
+
+    #[test]
+    fn discard_visibility_counts() {
+        assert_eq!(DiscardVisibility::TopOnly.visible_count(7), 1);
+        assert_eq!(DiscardVisibility::TopOnly.visible_count(0), 0);
+        assert_eq!(DiscardVisibility::Recent(3).visible_count(7), 3);
+        assert_eq!(DiscardVisibility::Recent(3).visible_count(2), 2);
+        assert_eq!(DiscardVisibility::All.visible_count(7), 7);
+        assert_eq!("recent:5".parse(), Ok(DiscardVisibility::Recent(5)));
+        assert!("everything".parse::<DiscardVisibility>().is_err());
+    }
+
+    #[test]
+    fn parses_abandonment_policy() {
+        assert_eq!("void".parse(), Ok(AbandonmentPolicy::Void));
+        assert_eq!(
+            "current_totals".parse(),
+            Ok(AbandonmentPolicy::CurrentTotals)
+        );
+        assert!("forfeit".parse::<AbandonmentPolicy>().is_err());
+        assert!(AbandonmentPolicy::CurrentTotals.counts_result());
+    }
+
+    #[test]
+    fn parses_timeout_policy() {
+        assert_eq!("auto_play".parse(), Ok(TimeoutPolicy::AutoPlay));
+        assert_eq!("penalty_draw".parse(), Ok(TimeoutPolicy::PenaltyDraw));
+        assert!("forfeit".parse::<TimeoutPolicy>().is_err());
+    }
+}
@@ -0,0 +1,456 @@
+use crate::card::{Card, Value};
+use crate::rule_set::TwoBehavior;
+// use std::collections::{HashMap, HashSet};
+
+/// Whether `card` may stand in for another card in a meld: a joker always, a two only while
+/// twos are wild.
+pub fn is_wildcard(card: &Card, twos: TwoBehavior) -> bool {
+    match card {
+        Card::Joker => true,
+        Card::Standard {
+            value: Value::Two, ..
+        } => twos == TwoBehavior::WildTwo,
+        Card::Standard { .. } => false,
+    }
+}
+
+/// Represents a set of cards attempting to be played as a 'Trío'
+pub fn is_valid_trio(cards: &[Card], max_jokers: usize, twos: TwoBehavior) -> bool {
+    // The standard rules allow 1 joker per combination: "solo está permitido el uso de un
+    // comodín al bajarse"; variants raise the limit through the rule set.
+    trio_wildcards(cards, twos).is_some_and(|wildcards| wildcards <= max_jokers)
+}
+
+/// How many cards of a 'Trío' stand in for another card, or `None` if `cards` aren't one.
+/// Wild twos count unless the trío is of twos.
+pub fn trio_wildcards(cards: &[Card], twos: TwoBehavior) -> Option<usize> {
+    if cards.len() < 3 {
+        return None; // Trio must be at least 3 cards
+    }
+
+    let mut jokers = 0;
+    let mut wild_twos = 0;
+    let mut standard_value: Option<Value> = None;
+
+    for card in cards {
+        match card {
+            Card::Joker => {
+                jokers += 1;
+            }
+            Card::Standard {
+                value: Value::Two, ..
+            } if twos == TwoBehavior::WildTwo => {
+                wild_twos += 1;
+            }
+            Card::Standard { value, .. } => {
+                if let Some(v) = standard_value {
+                    if v != *value {
+                        return None; // All standard cards must have the same value
+                    }
+                } else {
+                    standard_value = Some(*value);
+                }
+            }
+        }
+    }
+
+    match standard_value {
+        Some(_) => Some(jokers + wild_twos),
+        // Nothing but twos and jokers: a trío of twos
+        None if wild_twos > 0 => Some(jokers),
+        None => None,
+    }
+}
+
+/// Fewest cards in an escala under the standard rules.
+pub const MIN_ESCALA_LEN: usize = 4;
+/// Fewest cards in an escala corta, for tables whose rule set allows one.
+pub const ESCALA_CORTA_LEN: usize = 3;
+
+/// Represents a set of cards attempting to be played as an 'Escala' of at least `min_len`
+/// cards, [`MIN_ESCALA_LEN`] unless the round allows an escala corta.
+pub fn is_valid_escala(
+    cards: &[Card],
+    max_jokers: usize,
+    min_len: usize,
+    twos: TwoBehavior,
+) -> bool {
+    escala_wildcards(cards, min_len, twos).is_some_and(|wildcards| wildcards <= max_jokers)
+}
+
+/// Fewest cards of an 'Escala' that stand in for another card, or `None` if `cards` aren't
+/// one. With wild twos at most one two can be itself; the others are wildcards.
+pub fn escala_wildcards(cards: &[Card], min_len: usize, twos: TwoBehavior) -> Option<usize> {
+    if cards.len() < min_len {
+        return None; // Escala must be at least 4 cards (3 for an escala corta)
+    }
+
+    if cards.len() > 13 {
+        return None; // Longer than a full A-K run
+    }
+
+    // We need to count jokers and separate standard cards
+    let mut jokers = 0;
+    let mut wild_twos = 0;
+    let mut values = Vec::new();
+    for card in cards {
+        match card {
+            Card::Joker => jokers += 1,
+            Card::Standard {
+                value: Value::Two, ..
+            } if twos == TwoBehavior::WildTwo => wild_twos += 1,
+            Card::Standard { value, .. } => values.push(*value),
+        }
+    }
+
+    if wild_twos > 0 {
+        // Cheapest reading first: one two as itself
+        let mut with_two = values.clone();
+        with_two.push(Value::Two);
+        if fits_escala(&with_two, jokers + wild_twos - 1) {
+            return Some(jokers + wild_twos - 1);
+        }
+    }
+    fits_escala(&values, jokers + wild_twos).then_some(jokers + wild_twos)
+}
+
+/// Whether cards of these values form a consecutive run with `jokers` filling the gaps.
+fn fits_escala(standard_values: &[Value], jokers: usize) -> bool {
+    if standard_values.is_empty() {
+        return false;
+    }
+
+    // Check if all cards share the same suit (simplest case first. Rules say "misma o distinta pinta" for normal escalas??
+    // Actually, rules say: "una escala de 4 cartas consecutivas de la misma o distinta pinta".
+    // "donde si se puede haber 2 escalas de la misma pinta".
+    // Wait, let's look at the standard rules again: typically Escalas are same suit. But the text says: "misma o distinta pinta".
+    // For now, let's assume standard rummy runs (consecutive, same suit OR we allow mixed suits? "misma o distinta pinta" usually means
+    // it can be mixed suits in some Chilean regions. Let's implement the strict consecutive values first).
+
+    // Let's sort the standard cards by value to check for consecutiveness.
+    // Handling the "Ace can wrap around" (2-A-K-Q) is complex.
+    // For MVP, we'll just check if they can form a consecutive sequence with the available jokers.
+
+    let mut values: Vec<u8> = standard_values
+        .iter()
+        .map(|v| {
+            let v_u8 = *v as u8;
+            if v_u8 == 14 { 1 } else { v_u8 }
+        })
+        .collect();
+    values.sort_unstable();
+
+    // Check for duplicates
+    for i in 0..values.len().saturating_sub(1) {
+        if values[i] == values[i + 1] {
+            return false; // Duplicates not allowed in escala
+        }
+    }
+
+    // Modular sequence gap check to support wrap around (e.g. K-A-2)
+    let mut max_gap = 0;
+    for i in 0..values.len() {
+        let v1 = values[i];
+        let v2 = values[(i + 1) % values.len()];
+        let gap = if i == values.len() - 1 {
+            v2 + 13 - v1
+        } else {
+            v2 - v1
+        };
+        if gap > max_gap {
+            max_gap = gap;
+        }
+    }
+
+    let span = 13 - max_gap + 1;
+    let needed_jokers = span - values.len() as u8;
+
+    needed_jokers as usize <= jokers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::card::Suit;
+
+    #[test]
+    fn test_valid_trio_no_joker() {
+        let cards = vec![
+            Card::Standard {
+                suit: Suit::Hearts,
+                value: Value::Five,
+            },
+            Card::Standard {
+                suit: Suit::Clubs,
+                value: Value::Five,
+            },
+            Card::Standard {
+                suit: Suit::Spades,
+                value: Value::Five,
+            },
+        ];
+        assert!(is_valid_trio(&cards, 1, TwoBehavior::Natural));
+    }
+
+    #[test]
+    fn test_valid_trio_with_joker() {
+        let cards = vec![
+            Card::Standard {
+                suit: Suit::Hearts,
+                value: Value::Five,
+            },
+            Card::Joker,
+            Card::Standard {
+                suit: Suit::Spades,
+                value: Value::Five,
+            },
+        ];
+        assert!(is_valid_trio(&cards, 1, TwoBehavior::Natural));
+    }
+
+    #[test]
+    fn test_invalid_trio_mixed_values() {
+        let cards = vec![
+            Card::Standard {
+                suit: Suit::Hearts,
+                value: Value::Five,
+            },
+            Card::Standard {
+                suit: Suit::Clubs,
+                value: Value::Six,
+            },
+            Card::Standard {
+                suit: Suit::Spades,
+                value: Value::Five,
+            },
+        ];
+        assert!(!is_valid_trio(&cards, 1, TwoBehavior::Natural));
+    }
+
+    #[test]
+    fn test_invalid_trio_too_many_jokers() {
+        let cards = vec![
+            Card::Standard {
+                suit: Suit::Hearts,
+                value: Value::Five,
+            },
+            Card::Joker,
+            Card::Joker,
+        ];
+        assert!(!is_valid_trio(&cards, 1, TwoBehavior::Natural));
+    }
+
+    #[test]
+    fn test_valid_escala_no_joker() {
+        let cards = vec![
+            Card::Standard {
+                suit: Suit::Hearts,
+                value: Value::Three,
+            },
+            Card::Standard {
+                suit: Suit::Hearts,
+                value: Value::Four,
+            },
+            Card::Standard {
+                suit: Suit::Hearts,
+                value: Value::Five,
+            },
+            Card::Standard {
+                suit: Suit::Hearts,
+                value: Value::Six,
+            },
+        ];
+        assert!(is_valid_escala(
+            &cards,
+            1,
+            MIN_ESCALA_LEN,
+            TwoBehavior::Natural
+        ));
+    }
+
+    #[test]
+    fn test_escala_corta_needs_its_rule() {
+        let cards = vec![
+            Card::Standard {
+                suit: Suit::Hearts,
+                value: Value::Three,
+            },
+            Card::Standard {
+                suit: Suit::Hearts,
+                value: Value::Four,
+            },
+            Card::Standard {
+                suit: Suit::Hearts,
+                value: Value::Five,
+            },
+        ];
+        assert!(!is_valid_escala(
+            &cards,
+            1,
+            MIN_ESCALA_LEN,
+            TwoBehavior::Natural
+        ));
+        assert!(is_valid_escala(
+            &cards,
+            1,
+            ESCALA_CORTA_LEN,
+            TwoBehavior::Natural
+        ));
+    }
+
+    #[test]
+    fn test_valid_escala_with_joker_gap() {
+        let cards = vec![
+            Card::Standard {
+                suit: Suit::Hearts,
+                value: Value::Three,
+            },
+            Card::Standard {
+                suit: Suit::Hearts,
+                value: Value::Four,
+            },
+            Card::Joker,
+            Card::Standard {
+                suit: Suit::Hearts,
+                value: Value::Six,
+            },
+        ];
+        assert!(is_valid_escala(
+            &cards,
+            1,
+            MIN_ESCALA_LEN,
+            TwoBehavior::Natural
+        ));
+    }
+
+    #[test]
+    fn test_valid_escala_wrapping_k_a_2() {
+        let cards = vec![
+            Card::Standard {
+                suit: Suit::Spades,
+                value: Value::King,
+            },
+            Card::Standard {
+                suit: Suit::Spades,
+                value: Value::Ace,
+            },
+            Card::Standard {
+                suit: Suit::Spades,
+                value: Value::Two,
+            },
+            Card::Standard {
+                suit: Suit::Spades,
+                value: Value::Three,
+            },
+        ];
+        assert!(is_valid_escala(
+            &cards,
+            1,
+            MIN_ESCALA_LEN,
+            TwoBehavior::Natural
+        ));
+    }
+
+    #[test]
+    fn test_joker_limit_is_configurable() {
+        let trio = vec![
+            Card::Standard {
+                suit: Suit::Hearts,
+                value: Value::Five,
+            },
+            Card::Joker,
+            Card::Joker,
+        ];
+        assert!(!is_valid_trio(&trio, 1, TwoBehavior::Natural));
+        assert!(is_valid_trio(&trio, 2, TwoBehavior::Natural));
+
+        let escala = vec![
+            Card::Standard {
+                suit: Suit::Clubs,
+                value: Value::Three,
+            },
+            Card::Joker,
+            Card::Joker,
+            Card::Standard {
+                suit: Suit::Clubs,
+                value: Value::Six,
+            },
+        ];
+        assert!(!is_valid_escala(
+            &escala,
+            1,
+            MIN_ESCALA_LEN,
+            TwoBehavior::Natural
+        ));
+        assert!(is_valid_escala(
+            &escala,
+            2,
+            MIN_ESCALA_LEN,
+            TwoBehavior::Natural
+        ));
+        assert!(!is_valid_escala(
+            &escala,
+            0,
+            MIN_ESCALA_LEN,
+            TwoBehavior::Natural
+        ));
+    }
+
+    #[test]
+    fn test_wild_twos_stand_in_within_the_joker_limit() {
+        let card = |suit, value| Card::Standard { suit, value };
+        let two = card(Suit::Clubs, Value::Two);
+        let wild = TwoBehavior::WildTwo;
+
+        let trio = vec![
+            card(Suit::Hearts, Value::Nine),
+            card(Suit::Spades, Value::Nine),
+            two,
+        ];
+        assert!(!is_valid_trio(&trio, 1, TwoBehavior::Natural));
+        assert!(is_valid_trio(&trio, 1, wild));
+        assert!(!is_valid_trio(
+            &[trio.clone(), vec![Card::Joker]].concat(),
+            1,
+            wild
+        ));
+
+        let twos = vec![
+            two,
+            card(Suit::Hearts, Value::Two),
+            card(Suit::Spades, Value::Two),
+        ];
+        assert_eq!(
+            trio_wildcards(&twos, wild),
+            Some(0),
+            "A trío of twos is natural"
+        );
+
+        let gap = vec![
+            card(Suit::Hearts, Value::Four),
+            card(Suit::Hearts, Value::Five),
+            two,
+            card(Suit::Hearts, Value::Seven),
+        ];
+        assert!(!is_valid_escala(
+            &gap,
+            1,
+            MIN_ESCALA_LEN,
+            TwoBehavior::Natural
+        ));
+        assert_eq!(escala_wildcards(&gap, MIN_ESCALA_LEN, wild), Some(1));
+
+        let low = vec![
+            card(Suit::Hearts, Value::Ace),
+            card(Suit::Hearts, Value::Two),
+            card(Suit::Hearts, Value::Three),
+            two,
+        ];
+        assert_eq!(
+            escala_wildcards(&low, MIN_ESCALA_LEN, wild),
+            Some(1),
+            "One two plays itself, the other fills in for the four"
+        );
+        assert!(!is_valid_escala(&low, 0, MIN_ESCALA_LEN, wild));
+    }
+}
@@ -0,0 +1,158 @@
+use rand::{Rng, SeedableRng, rngs::ChaCha12Rng};
+use serde::{Deserialize, Deserializer, Serialize, Serializer, de};
+use std::fmt;
+
+use crate::deck::Deck;
+use crate::game::GameState;
+use crate::rule_set::RuleSet;
+use crate::transcript::RoundTranscript;
+
+/// 256 bits a deck is shuffled from with ChaCha12. Unlike a `u64` seed, it can't be
+/// searched for from the cards a player has seen, so it is safe to keep for the whole game
+/// and hand to auditors once it is over.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct ShuffleSeed([u8; 32]);
+
+impl ShuffleSeed {
+    /// A fresh seed from the thread's CSPRNG, itself seeded by the operating system.
+    pub fn random() -> Self {
+        Self(rand::random())
+    }
+
+    /// The seed round `round_index` of a game keyed with `self` is shuffled from: that
+    /// round's block of the key's ChaCha12 stream, so no round's seed gives away another's.
+    pub fn for_round(&self, round_index: usize) -> Self {
+        let mut rng = ChaCha12Rng::from_seed(self.0);
+        let mut seed = [0; 32];
+        for _ in 0..=round_index {
+            rng.fill_bytes(&mut seed);
+        }
+        Self(seed)
+    }
+
+    pub fn shuffle(&self, deck: &mut Deck) {
+        deck.shuffle_with(&mut ChaCha12Rng::from_seed(self.0));
+    }
+
+    pub fn to_hex(&self) -> String {
+        self.0.iter().map(|byte| format!("{:02x}", byte)).collect()
+    }
+
+    pub fn from_hex(hex: &str) -> Option<Self> {
+        if hex.len() != 64 || !hex.is_ascii() {
+            return None;
+        }
+        let mut bytes = [0; 32];
+        for (byte, pair) in bytes.iter_mut().zip(hex.as_bytes().chunks(2)) {
+            *byte = u8::from_str_radix(std::str::from_utf8(pair).ok()?, 16).ok()?;
+        }
+        Some(Self(bytes))
+    }
+}
+
+/// Never prints the seed itself, so it can't leak through a log line mid-game.
+impl fmt::Debug for ShuffleSeed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ShuffleSeed(..)")
+    }
+}
+
+impl Serialize for ShuffleSeed {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_hex())
+    }
+}
+
+impl<'de> Deserialize<'de> for ShuffleSeed {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let hex = String::deserialize(deserializer)?;
+        Self::from_hex(&hex).ok_or_else(|| de::Error::custom("expected 64 hex digits"))
+    }
+}
+
+/// Whether a deck of `jokers` jokers shuffled from `seed` deals exactly the hands and first
+/// discard `round` recorded.
+pub fn deals(seed: &ShuffleSeed, jokers: usize, round: &RoundTranscript) -> bool {
+    let players = round
+        .deal
+        .iter()
+        .map(|hand| hand.player_id.clone())
+        .collect();
+    let rules = RuleSet {
+        deck_jokers: jokers,
+        ..RuleSet::default()
+    };
+    let mut state = GameState::with_rules(players, rules);
+    let mut deck = Deck::with_jokers(jokers);
+    seed.shuffle(&mut deck);
+    state.start_round_with_deck(deck);
+
+    state
+        .players
+        .iter()
+        .zip(&round.deal)
+        .all(|(player, dealt)| player.hand == dealt.cards)
+        && state.discard_pile.first() == round.first_discard.as_ref()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transcript::Transcript;
+
+    #[test]
+    fn rounds_get_their_own_seeds_and_hex_round_trips() {
+        let key = ShuffleSeed::random();
+        assert_ne!(key.for_round(0), key.for_round(1));
+        assert_eq!(key.for_round(3), key.for_round(3));
+
+        let json = serde_json::to_string(&key).unwrap();
+        assert_eq!(json.len(), 66);
+        assert_eq!(serde_json::from_str::<ShuffleSeed>(&json).unwrap(), key);
+        assert!(ShuffleSeed::from_hex("zz").is_none());
+        assert_eq!(format!("{:?}", key), "ShuffleSeed(..)");
+    }
+
+    #[test]
+    fn a_recorded_deal_is_checked_against_its_seed() {
+        let mut game = GameState::new(vec!["ana".into(), "beto".into(), "carla".into()]);
+        game.start_round();
+        let mut transcript = Transcript::default();
+        transcript.sync_round(&game);
+        let round = &transcript.rounds[0];
+        let seed = game.round_seed.expect("dealt from the game's key");
+        assert_eq!(round.deck_seed, None);
+
+        assert!(deals(&seed, game.rules.deck_jokers, round));
+        assert!(!deals(
+            &ShuffleSeed::random(),
+            game.rules.deck_jokers,
+            round
+        ));
+        assert!(!deals(&seed, game.rules.deck_jokers + 2, round));
+    }
+
+    #[test]
+    fn a_fixed_key_always_deals_the_same_cards() {
+        let key = ShuffleSeed::from_hex(&"07".repeat(32)).unwrap();
+        assert_eq!(
+            key.for_round(2).to_hex(),
+            "8eb37e6d70194780cfddd76b1a73e1ce9fde3484cf0e365e68c4c49b250b6055"
+        );
+
+        let mut deck = Deck::new();
+        key.for_round(0).shuffle(&mut deck);
+        let top: Vec<String> = deck
+            .cards()
+            .iter()
+            .rev()
+            .take(10)
+            .map(|c| c.to_string())
+            .collect();
+        assert_eq!(
+            top,
+            ["4♦", "5♦", "5♣", "10♥", "8♥", "4♣", "J♥", "8♠", "10♣", "Q♥"],
+            "Stored seeds must keep reproducing the deals they were recorded with"
+        );
+    }
+}
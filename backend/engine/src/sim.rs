@@ -0,0 +1,297 @@
+use rand::prelude::IndexedRandom;
+use rand::{RngExt, SeedableRng, rngs::StdRng};
+use std::collections::HashMap;
+
+use crate::action::{Action, DiscardPayload};
+use crate::bot::{self, BotDifficulty};
+use crate::card::Card;
+use crate::deck::Deck;
+use crate::game::{GameState, RoundEndResult};
+use crate::rule_set::RuleSet;
+
+/// Upper bound on actions in one game; a game still running by then is stuck.
+const MAX_ACTIONS: usize = 20_000;
+
+/// Chooses a seat's next action. Only consulted on that seat's turn.
+pub trait Policy {
+    fn act(&mut self, game: &GameState, player_id: &str) -> Option<Action>;
+}
+
+/// Plays like the server's bots. Their coin flips aren't seeded, so games are only
+/// reproducible move for move with [`RandomPolicy`] seats.
+pub struct BotPolicy(pub BotDifficulty);
+
+impl Policy for BotPolicy {
+    fn act(&mut self, game: &GameState, player_id: &str) -> Option<Action> {
+        bot::play_bot_turn(game, player_id, self.0)
+    }
+}
+
+/// Draws from either pile and discards at random; never drops a hand.
+pub struct RandomPolicy {
+    rng: StdRng,
+}
+
+impl RandomPolicy {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+}
+
+impl Policy for RandomPolicy {
+    fn act(&mut self, game: &GameState, player_id: &str) -> Option<Action> {
+        let player = game.players.iter().find(|p| p.id == player_id)?;
+        if player.has_drawn_this_turn {
+            let card_index = self.rng.random_range(0..player.hand.len());
+            return Some(Action::Discard {
+                payload: DiscardPayload { card_index },
+            });
+        }
+
+        let mut options = Vec::new();
+        if game.deck.remaining() > 0 {
+            options.push(Action::DrawFromDeck);
+        }
+        if !game.discard_pile.is_empty() && !player.has_dropped_hand {
+            options.push(Action::DrawFromDiscard);
+        }
+        Some(
+            options
+                .choose(&mut self.rng)
+                .cloned()
+                .unwrap_or(Action::PassTurn),
+        )
+    }
+}
+
+/// How a simulated game went.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GameOutcome {
+    /// Final totals in seat order; lowest wins
+    pub final_totals: Vec<(String, u32)>,
+    /// Who went out each round; `None` for a stalemate
+    pub round_winners: Vec<Option<String>>,
+    pub actions: usize,
+    /// Policy actions the engine refused, each replaced by a fallback move
+    pub rejected_actions: usize,
+}
+
+/// Plays a full game from `seed` with one policy per seat, under the default rules.
+/// Panics as soon as an invariant breaks or the game fails to finish.
+pub fn run_random_game(seed: u64, policies: Vec<Box<dyn Policy>>) -> GameOutcome {
+    run_game(seed, policies, RuleSet::default())
+}
+
+pub fn run_game(seed: u64, mut policies: Vec<Box<dyn Policy>>, rules: RuleSet) -> GameOutcome {
+    let ids: Vec<String> = (1..=policies.len()).map(|n| format!("p{}", n)).collect();
+    let mut game = GameState::with_rules(ids, rules);
+    game.game_seed = Some(seed);
+    game.start_round();
+
+    let mut round_winners = Vec::new();
+    let mut actions = 0;
+    let mut rejected_actions = 0;
+    check_invariants(&game, actions);
+
+    while !game.is_game_over {
+        assert!(
+            actions < MAX_ACTIONS,
+            "Game {} still running after {} actions",
+            seed,
+            actions
+        );
+        if game.is_waiting_for_next_round {
+            game.force_ready_all().unwrap();
+            check_invariants(&game, actions);
+            continue;
+        }
+
+        let seat = game.current_turn;
+        let player_id = game.players[seat].id.clone();
+        let outcome = match policies[seat].act(&game, &player_id) {
+            Some(action) => apply(&mut game, &player_id, action),
+            None => Err("No action chosen"),
+        };
+        let result = match outcome {
+            Ok(result) => result,
+            Err(_) => {
+                rejected_actions += 1;
+                let action = fallback(&game, &player_id);
+                apply(&mut game, &player_id, action)
+                    .unwrap_or_else(|e| panic!("Fallback move rejected in game {}: {}", seed, e))
+            }
+        };
+        actions += 1;
+        if let Some(result) = result {
+            round_winners.push((!result.is_stalemate).then_some(result.winner_id));
+        }
+        check_invariants(&game, actions);
+    }
+
+    GameOutcome {
+        final_totals: game
+            .players
+            .iter()
+            .map(|p| (p.id.clone(), p.points))
+            .collect(),
+        round_winners,
+        actions,
+        rejected_actions,
+    }
+}
+
+/// Applies a move the way a room would.
+pub fn apply(
+    game: &mut GameState,
+    player_id: &str,
+    action: Action,
+) -> Result<Option<RoundEndResult>, &'static str> {
+    match action {
+        Action::DrawFromDeck => game.draw_from_deck().map(|_| None),
+        Action::DrawFromDiscard => game.draw_from_discard().map(|_| None),
+        Action::Discard { payload } => game.discard(payload.card_index),
+        Action::PassTurn => game.pass_turn(),
+        Action::DropHand { payload } => game
+            .drop_hand(player_id, payload.combinations)
+            .map(|_| None),
+        Action::ShedCard { payload } => game.shed_card(
+            player_id,
+            payload.hand_card_index,
+            &payload.target_player_id,
+            payload.target_combo_idx,
+            payload.position,
+        ),
+    }
+}
+
+/// A move that is always legal: draw (or pass on an empty deck), then discard.
+fn fallback(game: &GameState, player_id: &str) -> Action {
+    let drawn = game
+        .players
+        .iter()
+        .find(|p| p.id == player_id)
+        .is_some_and(|p| p.has_drawn_this_turn);
+    if drawn {
+        Action::Discard {
+            payload: DiscardPayload { card_index: 0 },
+        }
+    } else if game.deck.remaining() > 0 {
+        Action::DrawFromDeck
+    } else {
+        Action::PassTurn
+    }
+}
+
+/// Every card of the two decks is somewhere exactly once, and the table is in a playable
+/// shape.
+fn check_invariants(game: &GameState, step: usize) {
+    let mut counts: HashMap<Card, i32> = HashMap::new();
+    for card in Deck::with_jokers(game.rules.deck_jokers).cards() {
+        *counts.entry(*card).or_default() += 1;
+    }
+    let in_play = game
+        .deck
+        .cards()
+        .iter()
+        .chain(&game.discard_pile)
+        .chain(game.players.iter().flat_map(|p| &p.hand))
+        .chain(
+            game.players
+                .iter()
+                .flat_map(|p| p.dropped_combinations.iter().flatten()),
+        );
+    for card in in_play {
+        *counts.entry(*card).or_default() -= 1;
+    }
+    let unbalanced: Vec<_> = counts.iter().filter(|(_, n)| **n != 0).collect();
+    assert!(
+        unbalanced.is_empty(),
+        "Cards not conserved after step {}: {:?}",
+        step,
+        unbalanced
+    );
+
+    assert!(game.current_turn < game.players.len());
+    let playing = !game.is_game_over && !game.is_waiting_for_next_round;
+    for player in &game.players {
+        // Twelve dealt plus the one drawn is the most a hand can hold between discards
+        assert!(
+            player.hand.len() <= 13,
+            "{} holds {} cards after step {}",
+            player.id,
+            player.hand.len(),
+            step
+        );
+        assert!(
+            !playing || !player.hand.is_empty(),
+            "{} has an empty hand mid-round after step {}",
+            player.id,
+            step
+        );
+        assert_eq!(
+            player.has_dropped_hand,
+            !player.dropped_combinations.is_empty(),
+            "{}'s bajada flag disagrees with the table after step {}",
+            player.id,
+            step
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn random_seats(seed: u64, seats: u64) -> Vec<Box<dyn Policy>> {
+        (0..seats)
+            .map(|seat| Box::new(RandomPolicy::new(seed + seat)) as Box<dyn Policy>)
+            .collect()
+    }
+
+    #[test]
+    fn random_games_finish_and_replay_exactly() {
+        for seed in 0..5 {
+            let outcome = run_random_game(seed, random_seats(seed, 4));
+            assert_eq!(outcome.round_winners.len(), 9, "Every round is played");
+            assert_eq!(outcome.rejected_actions, 0);
+            assert_eq!(outcome, run_random_game(seed, random_seats(seed, 4)));
+        }
+    }
+
+    #[test]
+    fn bots_play_full_games() {
+        for seed in 0..3 {
+            let policies: Vec<Box<dyn Policy>> = vec![
+                Box::new(BotPolicy(BotDifficulty::Easy)),
+                Box::new(BotPolicy(BotDifficulty::Medium)),
+                Box::new(BotPolicy(BotDifficulty::Hard)),
+            ];
+            let outcome = run_random_game(seed, policies);
+            assert_eq!(outcome.final_totals.len(), 3);
+            assert!(
+                outcome.round_winners.iter().any(Option::is_some),
+                "Bots go out at least once in a game"
+            );
+        }
+    }
+
+    #[test]
+    fn bots_play_by_wild_twos() {
+        let rules = RuleSet {
+            twos: crate::rule_set::TwoBehavior::WildTwo,
+            ..RuleSet::default()
+        };
+        for seed in 0..3 {
+            let policies: Vec<Box<dyn Policy>> = vec![
+                Box::new(BotPolicy(BotDifficulty::Medium)),
+                Box::new(BotPolicy(BotDifficulty::Hard)),
+                Box::new(RandomPolicy::new(seed)),
+            ];
+            let outcome = run_game(seed, policies, rules.clone());
+            assert_eq!(outcome.round_winners.len(), 9);
+            assert_eq!(outcome.rejected_actions, 0);
+        }
+    }
+}
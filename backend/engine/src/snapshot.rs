@@ -0,0 +1,225 @@
+use serde::{Deserialize, Serialize};
+use std::time::Instant;
+
+use crate::card::Card;
+use crate::deck::Deck;
+use crate::game::{GameState, RoundType};
+use crate::rule_set::RuleSet;
+
+/// A hand-built position to resume play from, for reproducing tricky scenarios such as a
+/// joker swap or a nearly empty deck. Cards are checked against the two-deck composition;
+/// the combinations already on the table are taken as given.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GameSnapshot {
+    pub players: Vec<PlayerSnapshot>,
+    #[serde(default)]
+    pub round_index: usize,
+    /// Seat holding the turn
+    #[serde(default)]
+    pub current_turn: usize,
+    /// Undrawn cards, the next draw last; when omitted, whatever the position leaves over
+    /// is shuffled
+    #[serde(default)]
+    pub deck: Option<Vec<Card>>,
+    /// Oldest first; the last card is the top
+    #[serde(default)]
+    pub discard_pile: Vec<Card>,
+    /// Defaults to the server's rule set
+    #[serde(default)]
+    pub rules: Option<RuleSet>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlayerSnapshot {
+    pub id: String,
+    pub hand: Vec<Card>,
+    /// Total from earlier rounds
+    #[serde(default)]
+    pub points: u32,
+    /// A player with combinations down has dropped their hand
+    #[serde(default)]
+    pub dropped_combinations: Vec<Vec<Card>>,
+    #[serde(default)]
+    pub turns_played: u32,
+    #[serde(default)]
+    pub has_drawn_this_turn: bool,
+}
+
+impl GameSnapshot {
+    /// The position `state` is in, deck order and rules included, so play can resume from
+    /// it later. Clocks, ready flags and the round's history are not kept.
+    pub fn from_state(state: &GameState) -> Self {
+        Self {
+            players: state
+                .players
+                .iter()
+                .map(|p| PlayerSnapshot {
+                    id: p.id.clone(),
+                    hand: p.hand.clone(),
+                    points: p.points,
+                    dropped_combinations: p.dropped_combinations.clone(),
+                    turns_played: p.turns_played,
+                    has_drawn_this_turn: p.has_drawn_this_turn,
+                })
+                .collect(),
+            round_index: state.round_index,
+            current_turn: state.current_turn,
+            deck: Some(state.deck.cards().to_vec()),
+            discard_pile: state.discard_pile.clone(),
+            rules: Some(state.rules.clone()),
+        }
+    }
+
+    pub fn into_state(self, default_rules: RuleSet) -> Result<GameState, &'static str> {
+        if self.players.len() < 2 {
+            return Err("A game needs at least two players");
+        }
+        if self.current_turn >= self.players.len() {
+            return Err("The turn must belong to one of the players");
+        }
+        let round = *RoundType::all_rounds()
+            .get(self.round_index)
+            .ok_or("There is no round with that index")?;
+        let mut ids: Vec<&str> = self.players.iter().map(|p| p.id.as_str()).collect();
+        ids.sort_unstable();
+        ids.dedup();
+        if ids.len() != self.players.len() {
+            return Err("Player ids must be unique");
+        }
+
+        let mut used: Vec<Card> = self.discard_pile.clone();
+        for player in &self.players {
+            used.extend(&player.hand);
+            used.extend(player.dropped_combinations.iter().flatten());
+        }
+        let rules = self.rules.unwrap_or(default_rules);
+        let deck = match self.deck {
+            Some(cards) => {
+                used.extend(&cards);
+                Deck::remainder(&used, rules.deck_jokers)?;
+                Deck::from_cards(cards)
+            }
+            None => Deck::remainder(&used, rules.deck_jokers)?,
+        };
+
+        let ids = self.players.iter().map(|p| p.id.clone()).collect();
+        let mut state = GameState::with_rules(ids, rules);
+        for (player, snapshot) in state.players.iter_mut().zip(self.players) {
+            player.hand = snapshot.hand;
+            player.points = snapshot.points;
+            player.has_dropped_hand = !snapshot.dropped_combinations.is_empty();
+            player.dropped_combinations = snapshot.dropped_combinations;
+            player.turns_played = snapshot.turns_played;
+            player.has_drawn_this_turn = snapshot.has_drawn_this_turn;
+        }
+        state.current_round = round;
+        state.round_index = self.round_index;
+        state.current_turn = self.current_turn;
+        state.deck = deck;
+        state.discard_pile = self.discard_pile;
+        state.timing.start_round(Instant::now());
+        Ok(state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::card::{Suit, Value};
+
+    fn card(suit: Suit, value: Value) -> Card {
+        Card::Standard { suit, value }
+    }
+
+    fn snapshot(json: serde_json::Value) -> GameSnapshot {
+        serde_json::from_value(json).expect("valid snapshot JSON")
+    }
+
+    #[test]
+    fn builds_the_position_and_deals_out_the_rest() {
+        let ace = card(Suit::Spades, Value::Ace);
+        let state = snapshot(serde_json::json!({
+            "players": [
+                { "id": "ana", "hand": [ace, "Joker"], "points": 40 },
+                {
+                    "id": "bot_easy",
+                    "hand": [ace],
+                    "dropped_combinations": [[
+                        card(Suit::Hearts, Value::Two),
+                        card(Suit::Clubs, Value::Two),
+                        card(Suit::Spades, Value::Two)
+                    ]]
+                }
+            ],
+            "round_index": 2,
+            "current_turn": 1,
+            "discard_pile": ["Joker"]
+        }))
+        .into_state(RuleSet::default())
+        .unwrap();
+
+        assert_eq!(state.current_round, RoundType::TwoEscalas);
+        assert_eq!(state.current_turn, 1);
+        assert_eq!(state.players[0].points, 40);
+        assert!(state.players[1].has_dropped_hand);
+        assert!(!state.players[0].has_dropped_hand);
+        assert_eq!(state.deck.remaining(), 108 - 7);
+    }
+
+    #[test]
+    fn keeps_a_given_deck_in_order() {
+        let next = card(Suit::Hearts, Value::King);
+        let mut state = snapshot(serde_json::json!({
+            "players": [{ "id": "ana", "hand": [] }, { "id": "beto", "hand": [] }],
+            "deck": ["Joker", next]
+        }))
+        .into_state(RuleSet::default())
+        .unwrap();
+
+        assert_eq!(state.deck.remaining(), 2);
+        state.draw_from_deck().unwrap();
+        assert_eq!(state.players[0].hand, [next]);
+    }
+
+    #[test]
+    fn a_dealt_game_resumes_from_its_snapshot() {
+        let ids = vec!["ana".to_string(), "beto".to_string()];
+        let mut state = GameState::with_rules(ids, RuleSet::default());
+        state.start_round();
+        state.draw_from_deck().unwrap();
+
+        let saved = serde_json::to_string(&GameSnapshot::from_state(&state)).unwrap();
+        let resumed = serde_json::from_str::<GameSnapshot>(&saved)
+            .unwrap()
+            .into_state(RuleSet::default())
+            .unwrap();
+
+        assert_eq!(resumed.deck.cards(), state.deck.cards());
+        assert_eq!(resumed.discard_pile, state.discard_pile);
+        assert_eq!(resumed.current_turn, state.current_turn);
+        for (resumed, original) in resumed.players.iter().zip(&state.players) {
+            assert_eq!(resumed.hand, original.hand);
+            assert_eq!(resumed.has_drawn_this_turn, original.has_drawn_this_turn);
+        }
+    }
+
+    #[test]
+    fn rejects_impossible_positions() {
+        let joker_hand = serde_json::json!(["Joker", "Joker", "Joker", "Joker", "Joker"]);
+        let too_many_jokers = snapshot(serde_json::json!({
+            "players": [{ "id": "ana", "hand": joker_hand }, { "id": "beto", "hand": [] }]
+        }));
+        assert!(too_many_jokers.into_state(RuleSet::default()).is_err());
+
+        let bad_turn = snapshot(serde_json::json!({
+            "players": [{ "id": "ana", "hand": [] }, { "id": "beto", "hand": [] }],
+            "current_turn": 2
+        }));
+        assert!(bad_turn.into_state(RuleSet::default()).is_err());
+
+        let duplicate = snapshot(serde_json::json!({
+            "players": [{ "id": "ana", "hand": [] }, { "id": "ana", "hand": [] }]
+        }));
+        assert!(duplicate.into_state(RuleSet::default()).is_err());
+    }
+}
@@ -0,0 +1,98 @@
+use std::time::{Duration, Instant};
+
+/// Wall-clock bookkeeping for a game: when it, the current round and the current turn
+/// began, and how long each seat spent on every turn this round.
+#[derive(Debug, Clone)]
+pub struct GameTiming {
+    game_started: Instant,
+    round_started: Instant,
+    /// `None` between a turn ending and the next one starting
+    turn_started: Option<Instant>,
+    /// Per seat, one entry per finished turn this round
+    turns: Vec<Vec<Duration>>,
+}
+
+impl GameTiming {
+    pub fn new(players: usize, now: Instant) -> Self {
+        Self {
+            game_started: now,
+            round_started: now,
+            turn_started: None,
+            turns: vec![Vec::new(); players],
+        }
+    }
+
+    /// Clears the per-turn record and starts the clock on the round's first turn.
+    pub fn start_round(&mut self, now: Instant) {
+        self.round_started = now;
+        self.turn_started = Some(now);
+        for turns in &mut self.turns {
+            turns.clear();
+        }
+    }
+
+    pub fn start_turn(&mut self, now: Instant) {
+        self.turn_started = Some(now);
+    }
+
+    /// Records the turn `seat` just finished. Does nothing if no turn is running.
+    pub fn end_turn(&mut self, seat: usize, now: Instant) {
+        if let (Some(started), Some(turns)) = (self.turn_started.take(), self.turns.get_mut(seat)) {
+            turns.push(now.saturating_duration_since(started));
+        }
+    }
+
+    /// How long each of `seat`'s finished turns took this round, oldest first.
+    pub fn turn_durations(&self, seat: usize) -> &[Duration] {
+        self.turns.get(seat).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    /// `None` until `seat` has finished a turn this round.
+    pub fn average_turn(&self, seat: usize) -> Option<Duration> {
+        let turns = self.turn_durations(seat);
+        let total: Duration = turns.iter().sum();
+        (!turns.is_empty()).then(|| total / turns.len() as u32)
+    }
+
+    pub fn round_elapsed(&self, now: Instant) -> Duration {
+        now.saturating_duration_since(self.round_started)
+    }
+
+    pub fn game_elapsed(&self, now: Instant) -> Duration {
+        now.saturating_duration_since(self.game_started)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn averages_finished_turns_per_seat() {
+        let t0 = Instant::now();
+        let secs = |s| t0 + Duration::from_secs(s);
+        let mut timing = GameTiming::new(2, t0);
+        timing.start_round(t0);
+
+        timing.end_turn(0, secs(10));
+        timing.start_turn(secs(10));
+        timing.end_turn(1, secs(14));
+        timing.start_turn(secs(14));
+        timing.end_turn(0, secs(34));
+        // Already ended; a second report for the same turn is ignored
+        timing.end_turn(0, secs(40));
+
+        assert_eq!(
+            timing.turn_durations(0),
+            [Duration::from_secs(10), Duration::from_secs(20)]
+        );
+        assert_eq!(timing.average_turn(0), Some(Duration::from_secs(15)));
+        assert_eq!(timing.average_turn(1), Some(Duration::from_secs(4)));
+        assert_eq!(timing.round_elapsed(secs(40)), Duration::from_secs(40));
+
+        timing.start_round(secs(60));
+        assert_eq!(timing.average_turn(0), None);
+        assert_eq!(timing.round_elapsed(secs(65)), Duration::from_secs(5));
+        assert_eq!(timing.game_elapsed(secs(65)), Duration::from_secs(65));
+    }
+}
@@ -0,0 +1,312 @@
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+
+use crate::card::Card;
+use crate::game::{GameState, RoundEndResult};
+
+/// A game's record for sharing and bug reports: each round's deal, every move with the
+/// cards involved, and the round scores. The room builds it up as the game is played.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Transcript {
+    pub rounds: Vec<RoundTranscript>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoundTranscript {
+    pub round_index: usize,
+    pub round_name: String,
+    /// `None` when the round was dealt from a stacked deck, e.g. in the tutorial, or from a
+    /// secret [`ShuffleSeed`](crate::shuffle::ShuffleSeed), which is kept apart from the
+    /// transcript
+    pub deck_seed: Option<u64>,
+    /// Every hand as dealt, in seat order
+    pub deal: Vec<DealtHand>,
+    pub first_discard: Option<Card>,
+    pub moves: Vec<Move>,
+    /// Empty until the round ends
+    pub scores: Vec<RoundScore>,
+    /// `None` for a stalemate or a round that never finished
+    pub winner_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DealtHand {
+    pub player_id: String,
+    pub cards: Vec<Card>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Move {
+    pub player_id: String,
+    /// As in `LastAction::action_type`, e.g. `discarded`
+    pub action: String,
+    /// The card drawn, discarded or shed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub card: Option<Card>,
+    /// The combinations put down, for a bajada
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub combinations: Vec<Vec<Card>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoundScore {
+    pub player_id: String,
+    pub round_points: u32,
+    pub total_points: u32,
+}
+
+/// Everyone's running total once a round was scored, one column per seat.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScoreboardRow {
+    pub round_index: usize,
+    pub round_name: String,
+    pub totals: Vec<u32>,
+}
+
+impl RoundTranscript {
+    fn is_finished(&self) -> bool {
+        !self.scores.is_empty()
+    }
+}
+
+impl Transcript {
+    /// Opens an entry for the round being played, once it has been dealt.
+    pub fn sync_round(&mut self, state: &GameState) {
+        let dealt = !state.is_waiting_for_next_round && !state.is_game_over;
+        if !dealt || self.rounds.last().is_some_and(|round| !round.is_finished()) {
+            return;
+        }
+
+        // Only the deck's top card has been turned over so far
+        let first_discard = state.discard_pile.first().copied();
+        self.rounds.push(RoundTranscript {
+            round_index: state.round_index,
+            round_name: state.current_round.description().to_string(),
+            deck_seed: state.deck_seed,
+            deal: state
+                .players
+                .iter()
+                .map(|p| DealtHand {
+                    player_id: p.id.clone(),
+                    cards: p.hand.clone(),
+                })
+                .collect(),
+            first_discard,
+            moves: Vec::new(),
+            scores: Vec::new(),
+            winner_id: None,
+        });
+    }
+
+    /// Records the move the engine just applied, from `state.last_action`.
+    pub fn record_move(&mut self, state: &GameState) {
+        let (Some(action), Some(round)) = (&state.last_action, self.rounds.last_mut()) else {
+            return;
+        };
+        let player = state.players.iter().find(|p| p.id == action.player_id);
+        let card = match action.action_type.as_str() {
+            // Hidden from the table at the time, but the drawn card lands at the end of the hand
+            "drew_from_deck" | "penalty_draw" => player.and_then(|p| p.hand.last().copied()),
+            _ => action.card,
+        };
+        let combinations = match action.action_type.as_str() {
+            "bajó" => player
+                .map(|p| p.dropped_combinations.clone())
+                .unwrap_or_default(),
+            _ => Vec::new(),
+        };
+
+        round.moves.push(Move {
+            player_id: action.player_id.clone(),
+            action: action.action_type.clone(),
+            card,
+            combinations,
+        });
+    }
+
+    pub fn end_round(&mut self, result: &RoundEndResult) {
+        let Some(round) = self.rounds.last_mut() else {
+            return;
+        };
+        round.scores = result
+            .player_scores
+            .iter()
+            .map(|(id, round_points, total_points)| RoundScore {
+                player_id: id.clone(),
+                round_points: *round_points,
+                total_points: *total_points,
+            })
+            .collect();
+        round.winner_id = (!result.winner_id.is_empty()).then(|| result.winner_id.clone());
+    }
+
+    /// Running totals after each finished round, in `seats` order.
+    pub fn scoreboard(&self, seats: &[String]) -> Vec<ScoreboardRow> {
+        self.rounds
+            .iter()
+            .filter(|round| round.is_finished())
+            .map(|round| ScoreboardRow {
+                round_index: round.round_index,
+                round_name: round.round_name.clone(),
+                totals: seats
+                    .iter()
+                    .map(|id| {
+                        round
+                            .scores
+                            .iter()
+                            .find(|s| &s.player_id == id)
+                            .map_or(0, |s| s.total_points)
+                    })
+                    .collect(),
+            })
+            .collect()
+    }
+
+    /// Plain-text rendering, one line per move.
+    pub fn render_text(&self) -> String {
+        let mut out = String::new();
+        for round in &self.rounds {
+            let _ = write!(out, "Round {}: {}", round.round_index + 1, round.round_name);
+            if let Some(seed) = round.deck_seed {
+                let _ = write!(out, " (seed {})", seed);
+            }
+            out.push('\n');
+
+            out.push_str("Deal:\n");
+            for hand in &round.deal {
+                let _ = writeln!(out, "  {}: {}", hand.player_id, cards(&hand.cards));
+            }
+            if let Some(card) = round.first_discard {
+                let _ = writeln!(out, "  Discard pile: {}", card);
+            }
+
+            out.push_str("Moves:\n");
+            for play in &round.moves {
+                let _ = write!(out, "  {} {}", play.player_id, describe(&play.action));
+                if let Some(card) = play.card {
+                    let _ = write!(out, " {}", card);
+                }
+                for combination in &play.combinations {
+                    let _ = write!(out, " [{}]", cards(combination));
+                }
+                out.push('\n');
+            }
+
+            if round.is_finished() {
+                match &round.winner_id {
+                    Some(winner) => {
+                        let _ = writeln!(out, "Scores ({} went out):", winner);
+                    }
+                    None => out.push_str("Scores (stalemate):\n"),
+                }
+                for score in &round.scores {
+                    let _ = writeln!(
+                        out,
+                        "  {}: +{} = {}",
+                        score.player_id, score.round_points, score.total_points
+                    );
+                }
+            } else {
+                out.push_str("Not finished\n");
+            }
+            out.push('\n');
+        }
+        out
+    }
+}
+
+fn cards(cards: &[Card]) -> String {
+    cards
+        .iter()
+        .map(Card::to_string)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn describe(action: &str) -> &str {
+    match action {
+        "drew_from_deck" => "drew from the deck",
+        "drew_from_pozo" => "took from the discard pile",
+        "discarded" => "discarded",
+        "passed" => "passed",
+        "penalty_draw" => "ran out of time and drew",
+        "skipped" => "ran out of time",
+        "bajó" => "dropped their hand",
+        "shed" => "shed",
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn game() -> GameState {
+        let mut game = GameState::new(vec!["ana".to_string(), "beto".to_string()]);
+        game.start_round();
+        game
+    }
+
+    #[test]
+    fn records_the_deal_and_the_cards_moved() {
+        let mut game = game();
+        let mut transcript = Transcript::default();
+        transcript.sync_round(&game);
+        transcript.sync_round(&game);
+        assert_eq!(transcript.rounds.len(), 1, "One entry per round");
+
+        let round = &transcript.rounds[0];
+        assert_eq!(round.deck_seed, game.deck_seed);
+        assert_eq!(round.deal[0].cards, game.players[0].hand);
+        assert_eq!(round.first_discard, game.discard_pile.last().copied());
+
+        game.draw_from_deck().unwrap();
+        transcript.record_move(&game);
+        let drawn = *game.players[0].hand.last().unwrap();
+        game.discard(0).unwrap();
+        let discarded = *game.discard_pile.last().unwrap();
+        transcript.record_move(&game);
+
+        let moves = &transcript.rounds[0].moves;
+        assert_eq!(moves[0].action, "drew_from_deck");
+        assert_eq!(moves[0].card, Some(drawn));
+        assert_eq!(moves[1].card, Some(discarded));
+
+        let text = transcript.render_text();
+        assert!(text.contains("Round 1"));
+        assert!(text.contains(&format!("ana drew from the deck {}", drawn)));
+        assert!(text.contains("Not finished"));
+    }
+
+    #[test]
+    fn opens_the_next_round_only_once_it_is_dealt() {
+        let mut game = game();
+        let mut transcript = Transcript::default();
+        transcript.sync_round(&game);
+
+        let result = game.end_round();
+        transcript.end_round(&result);
+        transcript.sync_round(&game);
+        assert_eq!(
+            transcript.rounds.len(),
+            1,
+            "Still waiting for players to ready up"
+        );
+        assert_eq!(transcript.rounds[0].winner_id.as_deref(), Some("ana"));
+        assert_eq!(transcript.rounds[0].scores.len(), 2);
+
+        game.force_ready_all().unwrap();
+        transcript.sync_round(&game);
+        assert_eq!(transcript.rounds.len(), 2);
+        assert_eq!(transcript.rounds[1].round_index, 1);
+
+        let seats = ["beto".to_string(), "ana".to_string()];
+        let scoreboard = transcript.scoreboard(&seats);
+        assert_eq!(scoreboard.len(), 1, "Only finished rounds are listed");
+        let totals = [game.players[1].points, game.players[0].points];
+        assert_eq!(
+            scoreboard[0].totals, totals,
+            "Columns follow the seats asked for"
+        );
+    }
+}
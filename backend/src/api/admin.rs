@@ -0,0 +1,373 @@
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use uuid::Uuid;
+
+use crate::api::auth::Role;
+use crate::api::events::ClientMessage;
+use crate::api::maintenance::{self, MaintenanceNotice};
+use crate::api::problem::Problem;
+use crate::api::server::AppState;
+use crate::db::models::{AuditEntry, Report, Sanction};
+use crate::db::repo;
+use crate::db::seed_vault::RoundSeed;
+use crate::engine::shuffle;
+use crate::engine::transcript::Transcript;
+
+#[derive(Deserialize)]
+pub struct BotPerformanceQuery {
+    /// Only rounds finished at or after this time (epoch seconds); all of them by default
+    pub since: Option<i64>,
+}
+
+#[derive(Deserialize)]
+pub struct ReportsQuery {
+    pub status: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct ChatLine {
+    pub user_id: String,
+    pub text: String,
+    pub created_at_ms: i64,
+}
+
+/// Everything a moderator needs to judge a report in one response.
+#[derive(Serialize)]
+pub struct ReportDetail {
+    pub report: Report,
+    pub audit_log: Vec<AuditEntry>,
+    /// Raw (unfiltered) chat of the reported game, extracted from the audit log
+    pub chat: Vec<ChatLine>,
+}
+
+#[derive(Deserialize)]
+pub struct ResolveReportPayload {
+    pub resolution: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SanctionKind {
+    Mute,
+    Ban,
+}
+
+impl SanctionKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SanctionKind::Mute => "mute",
+            SanctionKind::Ban => "ban",
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct SanctionPayload {
+    pub kind: SanctionKind,
+    pub reason: String,
+    /// Omit for a permanent sanction
+    pub duration_secs: Option<u64>,
+}
+
+#[derive(Serialize)]
+pub struct ShuffleAudit {
+    pub room_id: String,
+    pub deck_jokers: i64,
+    pub rounds: Vec<AuditedRound>,
+}
+
+#[derive(Serialize)]
+pub struct AuditedRound {
+    pub round_index: usize,
+    /// Hex; shuffling a fresh deck with it as a ChaCha12 key deals the round
+    pub seed: String,
+    /// Whether the seed deals exactly the hands and first discard the transcript recorded
+    pub matches_transcript: bool,
+}
+
+#[derive(Deserialize)]
+pub struct MaintenancePayload {
+    pub enabled: bool,
+    /// Shown to players and to clients turned away; a generic notice when left out
+    pub message: Option<String>,
+    /// Games still running after this long are saved and their rooms closed; without it
+    /// they are left to finish
+    pub drain_seconds: Option<u64>,
+}
+
+#[derive(Serialize)]
+pub struct MaintenanceStatus {
+    /// `None` when the server is not in maintenance
+    pub notice: Option<MaintenanceNotice>,
+    /// Rooms still open, finished ones included until they are cleaned up
+    pub open_rooms: usize,
+}
+
+#[derive(Deserialize)]
+pub struct SetRolePayload {
+    pub role: Role,
+}
+
+pub async fn list_reports(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<ReportsQuery>,
+) -> impl IntoResponse {
+    Json(repo::list_reports(&state.db, query.status.as_deref()).await)
+}
+
+pub async fn get_report(
+    State(state): State<Arc<AppState>>,
+    Path(report_id): Path<String>,
+) -> impl IntoResponse {
+    let Some(report) = repo::get_report(&state.db, &report_id).await else {
+        return Problem::new(StatusCode::NOT_FOUND, "Report not found").into_response();
+    };
+
+    let audit_log = repo::get_audit_entries_for_room(&state.db, &report.room_id).await;
+    let chat = audit_log
+        .iter()
+        .filter_map(|entry| match serde_json::from_str(&entry.action) {
+            Ok(ClientMessage::Chat { payload }) => Some(ChatLine {
+                user_id: entry.user_id.clone(),
+                text: payload.text,
+                created_at_ms: entry.created_at_ms,
+            }),
+            _ => None,
+        })
+        .collect();
+
+    Json(ReportDetail {
+        report,
+        audit_log,
+        chat,
+    })
+    .into_response()
+}
+
+pub async fn resolve_report(
+    State(state): State<Arc<AppState>>,
+    Path(report_id): Path<String>,
+    Json(payload): Json<ResolveReportPayload>,
+) -> impl IntoResponse {
+    match repo::resolve_report(&state.db, &report_id, &payload.resolution).await {
+        Ok(true) => StatusCode::NO_CONTENT.into_response(),
+        Ok(false) => Problem::new(StatusCode::NOT_FOUND, "Report not found").into_response(),
+        Err(_) => Problem::new(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Failed to resolve report",
+        )
+        .into_response(),
+    }
+}
+
+pub async fn list_collusion_flags(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<ReportsQuery>,
+) -> impl IntoResponse {
+    Json(repo::list_collusion_flags(&state.db, query.status.as_deref()).await)
+}
+
+pub async fn resolve_collusion_flag(
+    State(state): State<Arc<AppState>>,
+    Path(flag_id): Path<i64>,
+    Json(payload): Json<ResolveReportPayload>,
+) -> impl IntoResponse {
+    match repo::resolve_collusion_flag(&state.db, flag_id, &payload.resolution).await {
+        Ok(true) => StatusCode::NO_CONTENT.into_response(),
+        Ok(false) => Problem::new(StatusCode::NOT_FOUND, "Flag not found").into_response(),
+        Err(_) => Problem::new(StatusCode::INTERNAL_SERVER_ERROR, "Failed to resolve flag")
+            .into_response(),
+    }
+}
+
+pub async fn add_sanction(
+    State(state): State<Arc<AppState>>,
+    Path(user_id): Path<String>,
+    Json(payload): Json<SanctionPayload>,
+) -> impl IntoResponse {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64;
+
+    let sanction = Sanction {
+        id: Uuid::new_v4().to_string(),
+        user_id,
+        kind: payload.kind.as_str().to_string(),
+        reason: payload.reason,
+        expires_at: payload.duration_secs.map(|secs| now + secs as i64),
+        created_at: now,
+    };
+
+    if repo::insert_sanction(&state.db, &sanction).await.is_err() {
+        return Problem::new(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Failed to create sanction",
+        )
+        .into_response();
+    }
+
+    (StatusCode::CREATED, Json(sanction)).into_response()
+}
+
+pub async fn lift_sanction(
+    State(state): State<Arc<AppState>>,
+    Path((user_id, kind)): Path<(String, SanctionKind)>,
+) -> impl IntoResponse {
+    match repo::lift_sanctions(&state.db, &user_id, kind.as_str()).await {
+        Ok(_) => StatusCode::NO_CONTENT.into_response(),
+        Err(_) => Problem::new(StatusCode::INTERNAL_SERVER_ERROR, "Failed to lift sanction")
+            .into_response(),
+    }
+}
+
+/// Status of the background maintenance jobs.
+pub async fn list_jobs(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    Json(state.scheduler.status())
+}
+
+/// Connection and room usage against the configured caps, with admission counters.
+pub async fn capacity(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    Json(state.capacity.metrics())
+}
+
+/// How each bot difficulty has done against humans, to check heuristic changes against
+/// real play.
+pub async fn bot_performance(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<BotPerformanceQuery>,
+) -> impl IntoResponse {
+    Json(repo::get_bot_performance(&state.db, query.since.unwrap_or(0)).await)
+}
+
+/// Finished games whose replay check failed, newest first.
+pub async fn replay_flags(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    Json(repo::list_replay_flags(&state.db).await)
+}
+
+/// The seeds a finished game's rounds were shuffled from, each checked against the deal
+/// the transcript recorded, for settling complaints about rigged hands.
+pub async fn shuffle_seeds(
+    State(state): State<Arc<AppState>>,
+    Path(room_id): Path<String>,
+) -> impl IntoResponse {
+    let Some(record) = repo::get_shuffle_seeds(&state.db, &room_id).await else {
+        return Problem::new(
+            StatusCode::NOT_FOUND,
+            "No shuffle seeds stored for this game",
+        )
+        .into_response();
+    };
+    let Some(seeds) = state
+        .seed_vault
+        .open(&record.sealed)
+        .and_then(|json| serde_json::from_slice::<Vec<RoundSeed>>(&json).ok())
+    else {
+        return Problem::new(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "The seeds were sealed under another key",
+        )
+        .into_response();
+    };
+    let transcript: Transcript = repo::get_game_transcript(&state.db, &room_id)
+        .await
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default();
+
+    let jokers = record.deck_jokers as usize;
+    let rounds = seeds
+        .iter()
+        .map(|round| AuditedRound {
+            round_index: round.round_index,
+            seed: round.seed.to_hex(),
+            matches_transcript: transcript
+                .rounds
+                .iter()
+                .find(|dealt| dealt.round_index == round.round_index)
+                .is_some_and(|dealt| shuffle::deals(&round.seed, jokers, dealt)),
+        })
+        .collect();
+    Json(ShuffleAudit {
+        room_id,
+        deck_jokers: record.deck_jokers,
+        rounds,
+    })
+    .into_response()
+}
+
+/// Per-IP rate limits on login and WebSocket upgrades, with how often they kicked in.
+pub async fn rate_limits(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    Json(state.rate_limits.metrics())
+}
+
+/// How far the audit writer is behind, and how many entries it had to drop.
+pub async fn audit(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    Json(state.audit.metrics())
+}
+
+/// Histograms of how long rooms take to handle player actions, by action type and room size.
+pub async fn action_latency(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    Json(state.action_latency.metrics())
+}
+
+pub async fn maintenance(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    Json(MaintenanceStatus {
+        notice: state.maintenance.notice(),
+        open_rooms: state.active_rooms.lock().await.len(),
+    })
+}
+
+/// Turns maintenance on or off. Turning it on again replaces the notice and deadline.
+pub async fn set_maintenance(
+    State(state): State<Arc<AppState>>,
+    Json(payload): Json<MaintenancePayload>,
+) -> impl IntoResponse {
+    if !payload.enabled {
+        if state.maintenance.end() {
+            println!("[Maintenance] Over, new games may start again");
+            maintenance::announce(&state, None).await;
+        }
+        return maintenance(State(state)).await.into_response();
+    }
+
+    let message = payload
+        .message
+        .unwrap_or_else(|| maintenance::DEFAULT_MESSAGE.to_string());
+    let drain = payload.drain_seconds.map(Duration::from_secs);
+    let (generation, notice) = state.maintenance.begin(message, drain);
+    println!("[Maintenance] Started: {}", notice.message);
+    maintenance::announce(&state, Some(notice)).await;
+    if let Some(drain) = drain {
+        tokio::spawn(maintenance::drain_after(state.clone(), generation, drain));
+    }
+    maintenance(State(state)).await.into_response()
+}
+
+/// Games saved when maintenance closed their rooms, newest first.
+pub async fn room_snapshots(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    Json(repo::list_room_snapshots(&state.db).await)
+}
+
+pub async fn set_role(
+    State(state): State<Arc<AppState>>,
+    Path(user_id): Path<String>,
+    Json(payload): Json<SetRolePayload>,
+) -> impl IntoResponse {
+    // Takes effect on the user's next login, when a new token is issued
+    let updated = repo::set_user_role(&state.db, &user_id, payload.role.as_str()).await;
+    state.user_cache.invalidate(&user_id);
+    match updated {
+        Ok(true) => StatusCode::NO_CONTENT.into_response(),
+        Ok(false) => Problem::new(StatusCode::NOT_FOUND, "User not found").into_response(),
+        Err(_) => {
+            Problem::new(StatusCode::INTERNAL_SERVER_ERROR, "Failed to update role").into_response()
+        }
+    }
+}
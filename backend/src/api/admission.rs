@@ -0,0 +1,284 @@
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use tokio::sync::Notify;
+
+/// Caps how many of something (sockets, rooms) the server holds at once. Callers over the
+/// cap wait in a first-come, first-served queue, and are turned away once that is full too.
+pub struct Gate {
+    capacity: usize,
+    max_queue: usize,
+    state: Mutex<GateState>,
+    /// Woken whenever a slot frees up or the queue moves
+    changed: Notify,
+}
+
+#[derive(Default)]
+struct GateState {
+    in_use: usize,
+    waiting: VecDeque<u64>,
+    next_ticket: u64,
+    admitted_total: u64,
+    queued_total: u64,
+    rejected_total: u64,
+    peak_in_use: usize,
+}
+
+/// Admission counters for one gate, as shown in the admin API.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct GateMetrics {
+    /// Zero means unlimited
+    pub capacity: usize,
+    pub in_use: usize,
+    pub queued: usize,
+    pub peak_in_use: usize,
+    pub admitted_total: u64,
+    /// Admissions that had to wait for a slot first
+    pub queued_total: u64,
+    /// Turned away because the queue was full
+    pub rejected_total: u64,
+}
+
+/// A held slot, released when dropped.
+pub struct Permit {
+    gate: Arc<Gate>,
+}
+
+impl Drop for Permit {
+    fn drop(&mut self) {
+        self.gate.state.lock().unwrap().in_use -= 1;
+        self.gate.changed.notify_waiters();
+    }
+}
+
+/// A place in the queue, redeemed with [`Ticket::wait`].
+pub struct Ticket {
+    gate: Arc<Gate>,
+    id: u64,
+}
+
+impl Ticket {
+    /// 1-based place in the queue
+    pub fn position(&self) -> usize {
+        self.gate.position(self.id).unwrap_or(1)
+    }
+
+    /// Waits for the ticket's turn, calling `on_position` whenever its place changes.
+    pub async fn wait(self, mut on_position: impl FnMut(usize)) -> Permit {
+        let mut reported = None;
+        loop {
+            // Registered before checking, so a release in between isn't missed
+            let changed = self.gate.changed.notified();
+            {
+                let mut state = self.gate.state.lock().unwrap();
+                if state.waiting.front() == Some(&self.id) && self.gate.has_room(&state) {
+                    state.waiting.pop_front();
+                    let permit = self.gate.admit(&mut state);
+                    drop(state);
+                    // Whoever is next may have moved up
+                    self.gate.changed.notify_waiters();
+                    return permit;
+                }
+            }
+            let position = self.position();
+            if reported != Some(position) {
+                on_position(position);
+                reported = Some(position);
+            }
+            changed.await;
+        }
+    }
+}
+
+impl Drop for Ticket {
+    // Gives up the place if the wait was abandoned, e.g. the client disconnected
+    fn drop(&mut self) {
+        let mut state = self.gate.state.lock().unwrap();
+        if let Some(index) = state.waiting.iter().position(|&id| id == self.id) {
+            state.waiting.remove(index);
+            drop(state);
+            self.gate.changed.notify_waiters();
+        }
+    }
+}
+
+pub enum Admission {
+    Admitted(Permit),
+    Queued(Ticket),
+    /// Over capacity with a full queue
+    Full,
+}
+
+impl Gate {
+    /// `capacity` of zero admits everyone.
+    pub fn new(capacity: usize, max_queue: usize) -> Arc<Self> {
+        Arc::new(Self {
+            capacity,
+            max_queue,
+            state: Mutex::new(GateState::default()),
+            changed: Notify::new(),
+        })
+    }
+
+    /// Takes a free slot if nobody is queued ahead, otherwise a place in the queue.
+    pub fn enter(self: &Arc<Self>) -> Admission {
+        let mut state = self.state.lock().unwrap();
+        if state.waiting.is_empty() && self.has_room(&state) {
+            return Admission::Admitted(self.admit(&mut state));
+        }
+        if state.waiting.len() >= self.max_queue {
+            state.rejected_total += 1;
+            return Admission::Full;
+        }
+
+        let id = state.next_ticket;
+        state.next_ticket += 1;
+        state.waiting.push_back(id);
+        state.queued_total += 1;
+        Admission::Queued(Ticket {
+            gate: self.clone(),
+            id,
+        })
+    }
+
+    pub fn metrics(&self) -> GateMetrics {
+        let state = self.state.lock().unwrap();
+        GateMetrics {
+            capacity: self.capacity,
+            in_use: state.in_use,
+            queued: state.waiting.len(),
+            peak_in_use: state.peak_in_use,
+            admitted_total: state.admitted_total,
+            queued_total: state.queued_total,
+            rejected_total: state.rejected_total,
+        }
+    }
+
+    fn has_room(&self, state: &GateState) -> bool {
+        self.capacity == 0 || state.in_use < self.capacity
+    }
+
+    fn admit(self: &Arc<Self>, state: &mut GateState) -> Permit {
+        state.in_use += 1;
+        state.admitted_total += 1;
+        state.peak_in_use = state.peak_in_use.max(state.in_use);
+        Permit { gate: self.clone() }
+    }
+
+    fn position(&self, ticket: u64) -> Option<usize> {
+        let state = self.state.lock().unwrap();
+        state
+            .waiting
+            .iter()
+            .position(|&id| id == ticket)
+            .map(|i| i + 1)
+    }
+}
+
+/// The server's capacity limits.
+#[derive(Clone)]
+pub struct Capacity {
+    /// Open WebSocket connections, players and spectators alike
+    pub sockets: Arc<Gate>,
+    /// Running game rooms
+    pub rooms: Arc<Gate>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CapacityMetrics {
+    pub sockets: GateMetrics,
+    pub rooms: GateMetrics,
+}
+
+impl Capacity {
+    pub fn new(max_sockets: usize, max_rooms: usize, max_queue: usize) -> Self {
+        Self {
+            sockets: Gate::new(max_sockets, max_queue),
+            rooms: Gate::new(max_rooms, max_queue),
+        }
+    }
+
+    pub fn metrics(&self) -> CapacityMetrics {
+        CapacityMetrics {
+            sockets: self.sockets.metrics(),
+            rooms: self.rooms.metrics(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn admitted(admission: Admission) -> Permit {
+        match admission {
+            Admission::Admitted(permit) => permit,
+            _ => panic!("Expected a free slot"),
+        }
+    }
+
+    fn queued(admission: Admission) -> Ticket {
+        match admission {
+            Admission::Queued(ticket) => ticket,
+            _ => panic!("Expected a place in the queue"),
+        }
+    }
+
+    #[tokio::test]
+    async fn queues_in_order_and_admits_as_slots_free() {
+        let gate = Gate::new(1, 2);
+        let first = admitted(gate.enter());
+        let second = queued(gate.enter());
+        let third = queued(gate.enter());
+        assert!(matches!(gate.enter(), Admission::Full));
+        assert_eq!((second.position(), third.position()), (1, 2));
+
+        let positions = Arc::new(Mutex::new(Vec::new()));
+        let seen = positions.clone();
+        let third = tokio::spawn(async move { third.wait(|p| seen.lock().unwrap().push(p)).await });
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        drop(first);
+        let second = second.wait(|_| {}).await;
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert!(
+            !third.is_finished(),
+            "Still one slot, held by the second caller"
+        );
+        assert_eq!(*positions.lock().unwrap(), [2, 1]);
+
+        drop(second);
+        let _third = tokio::time::timeout(Duration::from_secs(1), third)
+            .await
+            .expect("Admitted once the slot frees")
+            .unwrap();
+
+        let metrics = gate.metrics();
+        assert_eq!(metrics.in_use, 1);
+        assert_eq!(metrics.queued, 0);
+        assert_eq!(metrics.admitted_total, 3);
+        assert_eq!(metrics.queued_total, 2);
+        assert_eq!(metrics.rejected_total, 1);
+        assert_eq!(metrics.peak_in_use, 1);
+    }
+
+    #[test]
+    fn abandoned_tickets_leave_the_queue() {
+        let gate = Gate::new(1, 5);
+        let _held = admitted(gate.enter());
+        let first = queued(gate.enter());
+        let second = queued(gate.enter());
+
+        drop(first);
+        assert_eq!(second.position(), 1);
+        assert_eq!(gate.metrics().queued, 1);
+    }
+
+    #[test]
+    fn zero_capacity_is_unlimited() {
+        let gate = Gate::new(0, 0);
+        let permits: Vec<Permit> = (0..100).map(|_| admitted(gate.enter())).collect();
+        assert_eq!(gate.metrics().in_use, permits.len());
+    }
+}
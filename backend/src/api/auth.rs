@@ -10,11 +10,13 @@ use argon2::{
     password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
     Argon2
 };
-use jsonwebtoken::{encode, Header, EncodingKey};
+use jsonwebtoken::{decode, encode, DecodingKey, Header, EncodingKey, Validation};
 use std::time::{SystemTime, UNIX_EPOCH};
 use uuid::Uuid;
 
+use crate::api::problem::Problem;
 use crate::api::server::AppState;
+use crate::api::validation;
 use crate::db::models::User;
 use crate::db::repo;
 
@@ -28,33 +30,118 @@ pub struct AuthPayload {
 pub struct AuthResponse {
     pub token: String,
     pub user_id: String,
+    pub role: Role,
 }
 
-#[derive(Serialize, Deserialize)]
-struct Claims {
-    sub: String,
-    exp: usize,
+/// Access level, ordered so `role >= Role::Moderator` reads naturally.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    #[default]
+    Player,
+    Moderator,
+    Admin,
+}
+
+impl Role {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::Player => "player",
+            Role::Moderator => "moderator",
+            Role::Admin => "admin",
+        }
+    }
+
+    /// Unknown values (e.g. a hand-edited DB row) degrade to the least privileged role.
+    pub fn parse(value: &str) -> Self {
+        match value {
+            "moderator" => Role::Moderator,
+            "admin" => Role::Admin,
+            _ => Role::Player,
+        }
+    }
+}
+
+// Every token kind is signed with the same secret, so each refuses the others' fields:
+// a token decodes only as the kind it was issued as
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Claims {
+    pub sub: String,
+    pub exp: usize,
+    /// Tokens issued before roles existed carry none and are treated as players
+    #[serde(default)]
+    pub role: Role,
+}
+
+/// Read-only access to a single room, shared as a link. Carries no `sub`, so it can
+/// never pass as a login token (and vice versa).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SpectateClaims {
+    pub room_id: String,
+    pub exp: usize,
+}
+
+/// Lets a player back into a game they hold a seat in.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ReconnectClaims {
+    pub sub: String,
+    pub room_id: String,
+    pub exp: usize,
+}
+
+/// Admits whoever opens it to one private scheduled game, once. Carries neither `sub` nor
+/// `room_id`, so it passes as no other token.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct InviteClaims {
+    pub game_id: i64,
+    /// Identifies the link, which is marked used on its first redemption
+    pub jti: String,
+    pub exp: usize,
 }
 
 // In a real app, load this from ENV
 const JWT_SECRET: &[u8] = b"super_secret_carioca_key_mvp";
 
+/// How long a spectate link keeps working
+const SPECTATE_TTL_SECS: u64 = 60 * 60 * 12;
+
+/// How long a reconnect token keeps working
+const RECONNECT_TTL_SECS: u64 = 60 * 60 * 2;
+
+/// How long an invite link keeps working if nobody uses it
+const INVITE_TTL_SECS: u64 = 60 * 60 * 48;
+
 pub async fn register(
     State(state): State<Arc<AppState>>,
     Json(payload): Json<AuthPayload>,
 ) -> impl IntoResponse {
     let password = match payload.password {
         Some(p) => p,
-        None => return (StatusCode::BAD_REQUEST, "Missing password").into_response(),
+        None => return Problem::keyed(StatusCode::BAD_REQUEST, "missing_password").into_response(),
     };
 
     if payload.username.is_empty() {
-        return (StatusCode::BAD_REQUEST, "Missing username").into_response();
+        return Problem::keyed(StatusCode::BAD_REQUEST, "missing_username").into_response();
+    }
+
+    if let Err(e) = validation::validate_credentials(&payload.username, &password) {
+        return Problem::from(e).into_response();
     }
 
-    // Check if user exists
-    if repo::get_user(&state.db, &payload.username).await.is_some() {
-        return (StatusCode::CONFLICT, "Username already exists").into_response();
+    if !state.word_filter.is_clean_username(&payload.username) {
+        return Problem::keyed(StatusCode::BAD_REQUEST, "username_not_allowed").into_response();
+    }
+
+    // Check if user exists; former names stay with the account that renamed away from them
+    if repo::find_user_by_any_username(&state.db, &payload.username)
+        .await
+        .is_some()
+    {
+        return Problem::keyed(StatusCode::CONFLICT, "username_taken").into_response();
     }
 
     // Hash password
@@ -62,23 +149,31 @@ pub async fn register(
     let argon2 = Argon2::default();
     let password_hash = match argon2.hash_password(password.as_bytes(), &salt) {
         Ok(hash) => hash.to_string(),
-        Err(_) => return (StatusCode::INTERNAL_SERVER_ERROR, "Failed to hash password").into_response(),
+        Err(_) => {
+            return Problem::new(StatusCode::INTERNAL_SERVER_ERROR, "Failed to hash password")
+                .into_response();
+        }
     };
 
+    // Admins are only ever promoted at startup, from accounts that already exist
+    let role = Role::Player;
+
     let user = User {
         id: Uuid::new_v4().to_string(),
         username: payload.username.clone(),
         password_hash,
         created_at: SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() as i64,
+        role: role.as_str().to_string(),
     };
 
     if repo::insert_user(&state.db, &user).await.is_err() {
-        return (StatusCode::INTERNAL_SERVER_ERROR, "Failed to create user").into_response();
+        return Problem::new(StatusCode::INTERNAL_SERVER_ERROR, "Failed to create user")
+            .into_response();
     }
 
-    let token = create_jwt(&user.id);
+    let token = create_jwt(&user.id, role);
 
-    (StatusCode::CREATED, Json(AuthResponse { token, user_id: user.id })).into_response()
+    (StatusCode::CREATED, Json(AuthResponse { token, user_id: user.id, role })).into_response()
 }
 
 pub async fn login(
@@ -87,33 +182,129 @@ pub async fn login(
 ) -> impl IntoResponse {
     let password = match payload.password {
         Some(p) => p,
-        None => return (StatusCode::BAD_REQUEST, "Missing password").into_response(),
+        None => return Problem::keyed(StatusCode::BAD_REQUEST, "missing_password").into_response(),
     };
 
-    let user = match repo::get_user(&state.db, &payload.username).await {
+    if let Err(e) = validation::validate_credentials(&payload.username, &password) {
+        return Problem::from(e).into_response();
+    }
+
+    let user = match state.user_cache.get_user(&state.db, &payload.username).await {
         Some(u) => u,
-        None => return (StatusCode::UNAUTHORIZED, "Invalid credentials").into_response(),
+        None => {
+            return Problem::keyed(StatusCode::UNAUTHORIZED, "invalid_credentials").into_response();
+        }
     };
 
     // Verify password
     let parsed_hash = match PasswordHash::new(&user.password_hash) {
         Ok(hash) => hash,
-        Err(_) => return (StatusCode::INTERNAL_SERVER_ERROR, "Invalid db hash").into_response(),
+        Err(_) => {
+            return Problem::new(StatusCode::INTERNAL_SERVER_ERROR, "Invalid db hash")
+                .into_response();
+        }
     };
 
     if Argon2::default()
         .verify_password(password.as_bytes(), &parsed_hash)
         .is_err()
     {
-        return (StatusCode::UNAUTHORIZED, "Invalid credentials").into_response();
+        return Problem::keyed(StatusCode::UNAUTHORIZED, "invalid_credentials").into_response();
     }
 
-    let token = create_jwt(&user.id);
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() as i64;
+    if repo::has_active_sanction(&state.db, &user.id, "ban", now).await {
+        return Problem::keyed(StatusCode::FORBIDDEN, "account_banned").into_response();
+    }
+
+    let role = Role::parse(&user.role);
+    let token = create_jwt(&user.id, role);
+
+    (StatusCode::OK, Json(AuthResponse { token, user_id: user.id, role })).into_response()
+}
 
-    (StatusCode::OK, Json(AuthResponse { token, user_id: user.id })).into_response()
+/// Validates signature and expiry, returning the claims of a token we issued.
+pub fn decode_jwt(token: &str) -> Option<Claims> {
+    decode::<Claims>(token, &DecodingKey::from_secret(JWT_SECRET), &Validation::default())
+        .ok()
+        .map(|data| data.claims)
 }
 
-fn create_jwt(user_id: &str) -> String {
+/// Signs a spectate token for `room_id`, returning it with its expiry (epoch seconds).
+pub fn create_spectate_token(room_id: &str) -> (String, usize) {
+    let expiration = SystemTime::now()
+        .checked_add(std::time::Duration::from_secs(SPECTATE_TTL_SECS))
+        .expect("valid timestamp")
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as usize;
+
+    let claims = SpectateClaims {
+        room_id: room_id.to_string(),
+        exp: expiration,
+    };
+
+    let token = encode(&Header::default(), &claims, &EncodingKey::from_secret(JWT_SECRET)).unwrap();
+    (token, expiration)
+}
+
+pub fn decode_spectate_token(token: &str) -> Option<SpectateClaims> {
+    decode::<SpectateClaims>(token, &DecodingKey::from_secret(JWT_SECRET), &Validation::default())
+        .ok()
+        .map(|data| data.claims)
+}
+
+/// Signs a reconnect token for `user_id`'s seat in `room_id`, returning it with its expiry.
+pub fn create_reconnect_token(user_id: &str, room_id: &str) -> (String, usize) {
+    let expiration = SystemTime::now()
+        .checked_add(std::time::Duration::from_secs(RECONNECT_TTL_SECS))
+        .expect("valid timestamp")
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as usize;
+
+    let claims = ReconnectClaims {
+        sub: user_id.to_string(),
+        room_id: room_id.to_string(),
+        exp: expiration,
+    };
+
+    let token = encode(&Header::default(), &claims, &EncodingKey::from_secret(JWT_SECRET)).unwrap();
+    (token, expiration)
+}
+
+pub fn decode_reconnect_token(token: &str) -> Option<ReconnectClaims> {
+    decode::<ReconnectClaims>(token, &DecodingKey::from_secret(JWT_SECRET), &Validation::default())
+        .ok()
+        .map(|data| data.claims)
+}
+
+/// Signs invite link `jti` to scheduled game `game_id`, returning it with its expiry.
+pub fn create_invite_token(game_id: i64, jti: &str) -> (String, usize) {
+    let expiration = SystemTime::now()
+        .checked_add(std::time::Duration::from_secs(INVITE_TTL_SECS))
+        .expect("valid timestamp")
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as usize;
+
+    let claims = InviteClaims {
+        game_id,
+        jti: jti.to_string(),
+        exp: expiration,
+    };
+
+    let token = encode(&Header::default(), &claims, &EncodingKey::from_secret(JWT_SECRET)).unwrap();
+    (token, expiration)
+}
+
+pub fn decode_invite_token(token: &str) -> Option<InviteClaims> {
+    decode::<InviteClaims>(token, &DecodingKey::from_secret(JWT_SECRET), &Validation::default())
+        .ok()
+        .map(|data| data.claims)
+}
+
+fn create_jwt(user_id: &str, role: Role) -> String {
     let expiration = SystemTime::now()
         .checked_add(std::time::Duration::from_secs(60 * 60 * 24)) // 24 hours
         .expect("valid timestamp")
@@ -124,7 +315,34 @@ fn create_jwt(user_id: &str) -> String {
     let claims = Claims {
         sub: user_id.to_string(),
         exp: expiration,
+        role,
     };
 
     encode(&Header::default(), &claims, &EncodingKey::from_secret(JWT_SECRET)).unwrap()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn each_token_decodes_only_as_its_own_kind() {
+        let login = create_jwt("anita", Role::Player);
+        let spectate = create_spectate_token("room").0;
+        let reconnect = create_reconnect_token("anita", "room").0;
+        let invite = create_invite_token(7, "link").0;
+
+        let kinds = |token: &str| {
+            [
+                decode_jwt(token).is_some(),
+                decode_spectate_token(token).is_some(),
+                decode_reconnect_token(token).is_some(),
+                decode_invite_token(token).is_some(),
+            ]
+        };
+        assert_eq!(kinds(&login), [true, false, false, false]);
+        assert_eq!(kinds(&spectate), [false, true, false, false]);
+        assert_eq!(kinds(&reconnect), [false, false, true, false]);
+        assert_eq!(kinds(&invite), [false, false, false, true]);
+    }
+}
@@ -0,0 +1,99 @@
+use axum::{
+    extract::{FromRef, FromRequestParts, Request, State},
+    http::{StatusCode, header, request::Parts},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use std::sync::Arc;
+
+use crate::api::auth::{self, Role};
+use crate::api::problem::Problem;
+use crate::api::server::AppState;
+use crate::db::repo;
+
+/// The caller identified by the `Authorization: Bearer <jwt>` header.
+#[derive(Debug, Clone)]
+pub struct AuthUser {
+    pub user_id: String,
+    pub role: Role,
+}
+
+impl<S> FromRequestParts<S> for AuthUser
+where
+    Arc<AppState>: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = Problem;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        // Already resolved by `require_role` further up the stack
+        if let Some(user) = parts.extensions.get::<AuthUser>() {
+            return Ok(user.clone());
+        }
+
+        let token = parts
+            .headers
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .ok_or_else(|| Problem::keyed(StatusCode::UNAUTHORIZED, "missing_token"))?;
+
+        let claims = auth::decode_jwt(token)
+            .ok_or_else(|| Problem::keyed(StatusCode::UNAUTHORIZED, "invalid_token"))?;
+        let state = Arc::<AppState>::from_ref(state);
+        let role = current_role(&state, &claims.sub)
+            .await
+            .ok_or_else(|| Problem::new(StatusCode::UNAUTHORIZED, "Unknown user"))?;
+
+        Ok(AuthUser {
+            user_id: claims.sub,
+            role,
+        })
+    }
+}
+
+/// The role `user_id` holds now. Tokens keep the role they were issued with for a day, so
+/// promotions and demotions are read from the account instead, skipping the user cache so
+/// a demotion holds from the next request; `None` if it's gone.
+pub async fn current_role(state: &AppState, user_id: &str) -> Option<Role> {
+    let user = repo::get_user_by_id(&state.db, user_id).await?;
+    Some(Role::parse(&user.role))
+}
+
+/// State of a [`require_role`] layer: the least role let through.
+#[derive(Clone)]
+pub struct RoleGate {
+    required: Role,
+    state: Arc<AppState>,
+}
+
+impl RoleGate {
+    pub fn new(required: Role, state: &Arc<AppState>) -> Self {
+        Self {
+            required,
+            state: state.clone(),
+        }
+    }
+}
+
+impl FromRef<RoleGate> for Arc<AppState> {
+    fn from_ref(gate: &RoleGate) -> Self {
+        gate.state.clone()
+    }
+}
+
+/// Route layer rejecting callers below the gate's role:
+/// `.route_layer(from_fn_with_state(RoleGate::new(Role::Moderator, &state), require_role))`
+pub async fn require_role(
+    State(gate): State<RoleGate>,
+    user: AuthUser,
+    mut req: Request,
+    next: Next,
+) -> Response {
+    if user.role < gate.required {
+        return Problem::keyed(StatusCode::FORBIDDEN, "insufficient_role").into_response();
+    }
+
+    req.extensions_mut().insert(user);
+    next.run(req).await
+}
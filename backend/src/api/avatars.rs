@@ -0,0 +1,324 @@
+use axum::{
+    Json,
+    body::Bytes,
+    extract::{Path, State},
+    http::{StatusCode, header},
+    response::IntoResponse,
+};
+use serde::Serialize;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::api::authz::AuthUser;
+use crate::api::problem::Problem;
+use crate::api::server::AppState;
+use crate::db::repo;
+use crate::imaging::{Image, jpeg, png};
+use crate::matchmaking::bot_identity;
+
+/// Largest upload accepted, in bytes.
+pub const MAX_UPLOAD_BYTES: usize = 2 * 1024 * 1024;
+/// Largest avatar stored, in bytes. Heavier uploads are scaled down until they fit.
+pub const MAX_AVATAR_BYTES: usize = 256 * 1024;
+/// Longest side stored, in pixels. Larger uploads are scaled down to it.
+pub const MAX_AVATAR_SIDE: u32 = 512;
+/// Uploads that need scaling are decoded in full first, so bigger ones are refused.
+const MAX_SOURCE_PIXELS: usize = 4096 * 4096;
+/// Below this side an image that still won't fit is refused instead.
+const MIN_SCALED_SIDE: u32 = 64;
+const JPEG_QUALITY: u8 = 85;
+
+#[derive(Serialize)]
+pub struct AvatarUploaded {
+    pub url: String,
+}
+
+/// Where a player's current avatar is served. The version changes with every upload, so
+/// the image can be cached for good.
+pub fn avatar_url(user_id: &str, version: i64) -> String {
+    format!("/api/avatars/{}?v={}", user_id, version)
+}
+
+/// Content type and dimensions of a PNG or JPEG, read from its header.
+fn sniff(image: &[u8]) -> Result<(&'static str, u32, u32), &'static str> {
+    const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+    if image.starts_with(PNG_SIGNATURE) {
+        // The IHDR chunk always comes first: length, type, then width and height
+        if image.len() < 24 || &image[12..16] != b"IHDR" {
+            return Err("Malformed PNG");
+        }
+        let width = u32::from_be_bytes([image[16], image[17], image[18], image[19]]);
+        let height = u32::from_be_bytes([image[20], image[21], image[22], image[23]]);
+        return Ok(("image/png", width, height));
+    }
+
+    if image.starts_with(&[0xFF, 0xD8]) {
+        let mut i = 2;
+        while i + 4 <= image.len() {
+            if image[i] != 0xFF {
+                return Err("Malformed JPEG");
+            }
+            let marker = image[i + 1];
+            let length = u16::from_be_bytes([image[i + 2], image[i + 3]]) as usize;
+            // Start-of-frame markers; C4, C8 and CC share the range but aren't frames
+            let is_frame = (0xC0..=0xCF).contains(&marker) && ![0xC4, 0xC8, 0xCC].contains(&marker);
+            if is_frame {
+                if i + 9 > image.len() {
+                    break;
+                }
+                let height = u16::from_be_bytes([image[i + 5], image[i + 6]]) as u32;
+                let width = u16::from_be_bytes([image[i + 7], image[i + 8]]) as u32;
+                return Ok(("image/jpeg", width, height));
+            }
+            i += 2 + length;
+        }
+        return Err("Malformed JPEG");
+    }
+
+    Err("Avatars must be PNG or JPEG images")
+}
+
+/// Checks that an encoded image is within the stored limits and returns its content type.
+pub fn validate_avatar(image: &[u8]) -> Result<&'static str, &'static str> {
+    if image.len() > MAX_AVATAR_BYTES {
+        return Err("Avatars may be at most 256 KB");
+    }
+    let (content_type, width, height) = sniff(image)?;
+    if width == 0 || height == 0 {
+        return Err("The image is empty");
+    }
+    if width > MAX_AVATAR_SIDE || height > MAX_AVATAR_SIDE {
+        return Err("Avatars may be at most 512×512 pixels");
+    }
+    Ok(content_type)
+}
+
+/// The image to store for an upload, with its content type. The upload is always decoded
+/// in full and encoded afresh in the same format, scaled down until it fits, so nothing
+/// but pixels is ever served back.
+pub fn fit_avatar(image: &[u8]) -> Result<(&'static str, Vec<u8>), &'static str> {
+    if image.len() > MAX_UPLOAD_BYTES {
+        return Err("Avatars may be at most 2 MB");
+    }
+    let (content_type, width, height) = sniff(image)?;
+    if width == 0 || height == 0 {
+        return Err("The image is empty");
+    }
+
+    let is_png = content_type == "image/png";
+    let decoded = if is_png {
+        png::decode(image, MAX_SOURCE_PIXELS)?
+    } else {
+        jpeg::decode(image, MAX_SOURCE_PIXELS)?
+    };
+    let encode = |scaled: &Image| {
+        if is_png {
+            png::encode(scaled)
+        } else {
+            jpeg::encode(scaled, JPEG_QUALITY)
+        }
+    };
+    let mut side = MAX_AVATAR_SIDE.min(decoded.width.max(decoded.height));
+    loop {
+        let scaled = encode(&decoded.fit_within(side));
+        if scaled.len() <= MAX_AVATAR_BYTES {
+            return Ok((content_type, scaled));
+        }
+        side = side * 3 / 4;
+        if side < MIN_SCALED_SIDE {
+            return Err("The image can't be made small enough");
+        }
+    }
+}
+
+/// Replaces the caller's avatar with the posted image (the raw PNG or JPEG bytes), scaled
+/// down first if it is too large.
+pub async fn upload_avatar(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    image: Bytes,
+) -> impl IntoResponse {
+    let (content_type, image) = match tokio::task::spawn_blocking(move || fit_avatar(&image)).await
+    {
+        Ok(Ok(fitted)) => fitted,
+        Ok(Err(e)) => return Problem::new(StatusCode::UNPROCESSABLE_ENTITY, e).into_response(),
+        Err(_) => {
+            return Problem::new(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to process avatar",
+            )
+            .into_response();
+        }
+    };
+    let version = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as i64;
+
+    if repo::upsert_avatar(&state.db, &user.user_id, content_type, &image, version)
+        .await
+        .is_err()
+    {
+        return Problem::new(StatusCode::INTERNAL_SERVER_ERROR, "Failed to save avatar")
+            .into_response();
+    }
+    Json(AvatarUploaded {
+        url: avatar_url(&user.user_id, version),
+    })
+    .into_response()
+}
+
+pub async fn delete_avatar(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+) -> impl IntoResponse {
+    match repo::delete_avatar(&state.db, &user.user_id).await {
+        Ok(true) => StatusCode::NO_CONTENT.into_response(),
+        Ok(false) => Problem::new(StatusCode::NOT_FOUND, "No avatar uploaded").into_response(),
+        Err(_) => Problem::new(StatusCode::INTERNAL_SERVER_ERROR, "Failed to delete avatar")
+            .into_response(),
+    }
+}
+
+pub async fn get_avatar(
+    State(state): State<Arc<AppState>>,
+    Path(user_id): Path<String>,
+) -> impl IntoResponse {
+    match repo::get_avatar(&state.db, &user_id).await {
+        Some(avatar) => (
+            [
+                (header::CONTENT_TYPE, avatar.content_type),
+                (
+                    header::CACHE_CONTROL,
+                    "public, max-age=31536000, immutable".to_string(),
+                ),
+                (header::X_CONTENT_TYPE_OPTIONS, "nosniff".to_string()),
+            ],
+            avatar.image,
+        )
+            .into_response(),
+        None => Problem::new(StatusCode::NOT_FOUND, "No avatar uploaded").into_response(),
+    }
+}
+
+/// The generated avatar of a bot name, as referenced by `BotIdentity::avatar_url`.
+pub async fn get_bot_avatar(Path(index): Path<usize>) -> impl IntoResponse {
+    match bot_identity::avatar_svg(index) {
+        Some(svg) => (
+            [
+                (header::CONTENT_TYPE, "image/svg+xml"),
+                (header::CACHE_CONTROL, "public, max-age=86400"),
+                (header::X_CONTENT_TYPE_OPTIONS, "nosniff"),
+            ],
+            svg,
+        )
+            .into_response(),
+        None => Problem::new(StatusCode::NOT_FOUND, "No such bot").into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn png(width: u32, height: u32) -> Vec<u8> {
+        let mut image = b"\x89PNG\r\n\x1a\n\x00\x00\x00\x0dIHDR".to_vec();
+        image.extend(width.to_be_bytes());
+        image.extend(height.to_be_bytes());
+        image.extend([8, 6, 0, 0, 0]);
+        image
+    }
+
+    fn jpeg(width: u16, height: u16) -> Vec<u8> {
+        let mut image = vec![0xFF, 0xD8];
+        // An APP0 segment to skip over before the frame header
+        image.extend([0xFF, 0xE0, 0x00, 0x04, 0x00, 0x00]);
+        image.extend([0xFF, 0xC0, 0x00, 0x11, 0x08]);
+        image.extend(height.to_be_bytes());
+        image.extend(width.to_be_bytes());
+        image
+    }
+
+    #[test]
+    fn reads_png_and_jpeg_headers() {
+        assert_eq!(sniff(&png(128, 64)), Ok(("image/png", 128, 64)));
+        assert_eq!(sniff(&jpeg(200, 300)), Ok(("image/jpeg", 200, 300)));
+        assert!(sniff(b"GIF89a").is_err());
+        assert!(sniff(&png(1, 1)[..20]).is_err());
+    }
+
+    #[test]
+    fn refuses_large_or_empty_images() {
+        assert_eq!(validate_avatar(&png(512, 512)), Ok("image/png"));
+        assert!(validate_avatar(&png(513, 10)).is_err());
+        assert!(validate_avatar(&jpeg(0, 10)).is_err());
+        let mut heavy = png(64, 64);
+        heavy.resize(MAX_AVATAR_BYTES + 1, 0);
+        assert!(validate_avatar(&heavy).is_err());
+    }
+
+    fn photo(width: u32, height: u32) -> Image {
+        let mut pixels = Vec::new();
+        for y in 0..height {
+            for x in 0..width {
+                pixels.extend([(x % 256) as u8, (y % 256) as u8, ((x ^ y) % 256) as u8, 255]);
+            }
+        }
+        Image {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    #[test]
+    fn scales_oversized_images_down_instead_of_refusing_them() {
+        let small = png::encode(&photo(40, 30));
+        let (content_type, fitted) = fit_avatar(&small).unwrap();
+        assert_eq!(content_type, "image/png");
+        assert_eq!(png::decode(&fitted, usize::MAX), Ok(photo(40, 30)));
+
+        let (content_type, fitted) = fit_avatar(&png::encode(&photo(1024, 600))).unwrap();
+        assert_eq!(content_type, "image/png");
+        assert_eq!(validate_avatar(&fitted), Ok("image/png"));
+        let decoded = png::decode(&fitted, usize::MAX).unwrap();
+        assert_eq!((decoded.width, decoded.height), (512, 300));
+
+        let (content_type, fitted) = fit_avatar(&jpeg::encode(&photo(600, 900), 95)).unwrap();
+        assert_eq!(content_type, "image/jpeg");
+        assert_eq!(sniff(&fitted), Ok(("image/jpeg", 341, 512)));
+    }
+
+    #[test]
+    fn refuses_uploads_too_large_to_scale() {
+        assert_eq!(
+            fit_avatar(&png(5000, 5000)),
+            Err("The image is too large to process")
+        );
+        let mut heavy = png(64, 64);
+        heavy.resize(MAX_UPLOAD_BYTES + 1, 0);
+        assert_eq!(fit_avatar(&heavy), Err("Avatars may be at most 2 MB"));
+        assert!(
+            fit_avatar(&png(600, 600)).is_err(),
+            "No pixel data to scale"
+        );
+    }
+
+    #[test]
+    fn stores_only_the_pixels_of_an_upload() {
+        assert!(
+            fit_avatar(&png(64, 64)).is_err(),
+            "A header alone is refused"
+        );
+        assert!(
+            fit_avatar(&jpeg(64, 64)).is_err(),
+            "A header alone is refused"
+        );
+
+        let mut polyglot = png::encode(&photo(32, 32));
+        polyglot.extend(b"<script>alert(1)</script>");
+        let (_, fitted) = fit_avatar(&polyglot).unwrap();
+        assert!(!fitted.windows(8).any(|w| w == b"<script>"));
+        assert_eq!(png::decode(&fitted, usize::MAX), Ok(photo(32, 32)));
+    }
+}
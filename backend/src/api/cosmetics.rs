@@ -0,0 +1,88 @@
+use axum::{Json, extract::State, http::StatusCode, response::IntoResponse};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use std::sync::Arc;
+
+use crate::api::authz::AuthUser;
+use crate::api::problem::Problem;
+use crate::api::server::AppState;
+use crate::db::models::Cosmetics;
+use crate::db::repo;
+
+/// Card backs on offer; the first is everyone's default.
+pub const CARD_BACKS: &[&str] = &["clasico", "azul", "rojo", "copihue", "araucaria"];
+/// Table felts on offer; the first is everyone's default.
+pub const TABLE_THEMES: &[&str] = &["pano_verde", "madera", "noche"];
+
+impl Default for Cosmetics {
+    fn default() -> Self {
+        Self {
+            card_back: CARD_BACKS[0].to_string(),
+            table_theme: TABLE_THEMES[0].to_string(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct CosmeticsCatalog {
+    pub card_backs: &'static [&'static str],
+    pub table_themes: &'static [&'static str],
+}
+
+/// Fields left out keep their current value.
+#[derive(Deserialize)]
+pub struct SetCosmeticsPayload {
+    pub card_back: Option<String>,
+    pub table_theme: Option<String>,
+}
+
+/// What a player shows at the table; bots and players who never chose get the defaults.
+pub async fn load(pool: &SqlitePool, user_id: &str) -> Cosmetics {
+    repo::get_cosmetics(pool, user_id).await.unwrap_or_default()
+}
+
+pub async fn catalog() -> impl IntoResponse {
+    Json(CosmeticsCatalog {
+        card_backs: CARD_BACKS,
+        table_themes: TABLE_THEMES,
+    })
+}
+
+pub async fn get_cosmetics(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+) -> impl IntoResponse {
+    Json(load(&state.db, &user.user_id).await)
+}
+
+pub async fn set_cosmetics(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Json(payload): Json<SetCosmeticsPayload>,
+) -> impl IntoResponse {
+    let mut cosmetics = load(&state.db, &user.user_id).await;
+    if let Some(card_back) = payload.card_back {
+        if !CARD_BACKS.contains(&card_back.as_str()) {
+            return Problem::new(StatusCode::BAD_REQUEST, "Unknown card back").into_response();
+        }
+        cosmetics.card_back = card_back;
+    }
+    if let Some(table_theme) = payload.table_theme {
+        if !TABLE_THEMES.contains(&table_theme.as_str()) {
+            return Problem::new(StatusCode::BAD_REQUEST, "Unknown table theme").into_response();
+        }
+        cosmetics.table_theme = table_theme;
+    }
+
+    if repo::upsert_cosmetics(&state.db, &user.user_id, &cosmetics)
+        .await
+        .is_err()
+    {
+        return Problem::new(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Failed to save cosmetics",
+        )
+        .into_response();
+    }
+    Json(cosmetics).into_response()
+}
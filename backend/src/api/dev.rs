@@ -0,0 +1,67 @@
+use axum::{Json, extract::State, http::StatusCode, response::IntoResponse};
+use serde::Serialize;
+use std::sync::Arc;
+
+use crate::api::admission::Admission;
+use crate::api::auth;
+use crate::api::problem::Problem;
+use crate::api::server::AppState;
+use crate::engine::snapshot::GameSnapshot;
+use crate::matchmaking::room::Room;
+
+#[derive(Serialize)]
+pub struct StagedRoom {
+    pub room_id: String,
+    /// One per human seat; bots (`bot_` ids) play on their own
+    pub seats: Vec<StagedSeat>,
+}
+
+#[derive(Serialize)]
+pub struct StagedSeat {
+    pub user_id: String,
+    /// Connect as this user with `/ws?token=<login>&rejoin=<reconnect_token>` to play the seat
+    pub reconnect_token: String,
+}
+
+/// Opens a room playing on from the posted position. Only routed when
+/// `CARIOCA_DEV_ENDPOINTS` is on.
+pub async fn load_state(
+    State(state): State<Arc<AppState>>,
+    Json(snapshot): Json<GameSnapshot>,
+) -> impl IntoResponse {
+    let game_state = match snapshot.into_state(state.config.rules.clone()) {
+        Ok(game_state) => game_state,
+        Err(e) => return Problem::new(StatusCode::UNPROCESSABLE_ENTITY, e).into_response(),
+    };
+    let Admission::Admitted(permit) = state.capacity.rooms.enter() else {
+        return Problem::keyed(StatusCode::SERVICE_UNAVAILABLE, "server_full").into_response();
+    };
+
+    let room_id = uuid::Uuid::new_v4().to_string();
+    let seats = game_state
+        .players
+        .iter()
+        .filter(|p| !p.id.starts_with("bot_"))
+        .map(|p| StagedSeat {
+            user_id: p.id.clone(),
+            reconnect_token: auth::create_reconnect_token(&p.id, &room_id).0,
+        })
+        .collect();
+
+    let (tx, rx) = tokio::sync::mpsc::channel(100);
+    let room = Room::new_staged(
+        room_id.clone(),
+        game_state,
+        rx,
+        tx.clone(),
+        state.room_services(),
+    );
+    tokio::spawn(async move {
+        room.run().await;
+        drop(permit);
+    });
+    state.active_rooms.lock().await.insert(room_id.clone(), tx);
+
+    println!("Staged room {} from a posted position", room_id);
+    Json(StagedRoom { room_id, seats }).into_response()
+}
@@ -1,40 +1,228 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
+use crate::api::maintenance::MaintenanceNotice;
+use crate::api::rtt::ConnectionQuality;
+use crate::db::models::Cosmetics;
+use crate::engine::action::Action;
+pub use crate::engine::action::{DiscardPayload, DropHandPayload, ShedCardPayload};
+use crate::engine::analysis::{Highlight, PlayReview};
 use crate::engine::card::Card;
-use crate::engine::game::{LastAction, PlayerState};
+use crate::engine::game::{FinalHand, LastAction, PlayerRoundStats, PlayerState, WinningPlay};
+use crate::engine::hand_sort::SortBy;
+use crate::engine::podium::Podium;
+use crate::engine::transcript::ScoreboardRow;
+use crate::matchmaking::bot_identity::BotIdentity;
+use crate::matchmaking::browser::RoomListing;
+use crate::ranking::head_to_head::HeadToHead;
+use crate::ranking::season::RatingChange;
+use crate::ranking::titles::PlayerTitle;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type")]
 pub enum ClientMessage {
     DrawFromDeck,
     DrawFromDiscard,
-    Discard { payload: DiscardPayload },
-    DropHand { payload: DropHandPayload },
-    ShedCard { payload: ShedCardPayload },
-    ReorderHand { payload: ReorderHandPayload },
+    Discard {
+        payload: DiscardPayload,
+    },
+    /// End the turn without drawing; only allowed once the deck is empty
+    PassTurn,
+    DropHand {
+        payload: DropHandPayload,
+    },
+    ShedCard {
+        payload: ShedCardPayload,
+    },
+    ReorderHand {
+        payload: ReorderHandPayload,
+    },
+    /// Let the server arrange the hand; the new order arrives in the next state update
+    SortHand {
+        payload: SortHandPayload,
+    },
     ReadyForNextRound,
+    Chat {
+        payload: ChatPayload,
+    },
+    ReportPlayer {
+        payload: ReportPlayerPayload,
+    },
+    /// Ask for a break, or agree to one already asked for; it starts once every connected
+    /// human has agreed
+    RequestPause,
+    /// Turn down the break being voted on
+    DeclinePause,
+    /// End a break early
+    ResumeGame,
+    /// Ask for the running totals of every finished round
+    GetScores,
+    /// Ask for help with one's own hand, answered with a `Hint`
+    RequestHint {
+        payload: RequestHintPayload,
+    },
+    /// Switch the language this connection's messages arrive in, e.g. `en`; may be sent
+    /// first thing instead of (or to override) the `locale` given when connecting
+    SetLocale {
+        payload: SetLocalePayload,
+    },
+    /// Answer to a `Ping`, sent as soon as it arrives
+    Pong {
+        payload: PongPayload,
+    },
+    /// Let the table be spectated or not; spectating stays off while any player objects
+    AllowSpectators {
+        payload: AllowSpectatorsPayload,
+    },
+    /// Pick the house rules and turn length while a `RuleVoteOpened` vote is running;
+    /// voting again replaces the earlier choice
+    VoteRules {
+        payload: VoteRulesPayload,
+    },
+}
+
+/// A `ClientMessage` as it arrives on the socket. Hot-seat connections name the seat
+/// acting; everyone else leaves `seat_id` out.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ClientEnvelope {
+    #[serde(default)]
+    pub seat_id: Option<String>,
+    /// Any string the client picks; echoed on errors the message causes and logged with them
+    #[serde(default)]
+    pub correlation_id: Option<String>,
+    #[serde(flatten)]
+    pub action: ClientMessage,
+}
+
+impl ClientMessage {
+    /// Social/meta messages any seated player may send regardless of whose turn it is.
+    /// They never change the game state.
+    pub fn is_out_of_turn(&self) -> bool {
+        matches!(
+            self,
+            ClientMessage::Chat { .. }
+                | ClientMessage::ReportPlayer { .. }
+                | ClientMessage::RequestPause
+                | ClientMessage::DeclinePause
+                | ClientMessage::ResumeGame
+                | ClientMessage::GetScores
+                | ClientMessage::RequestHint { .. }
+                | ClientMessage::SetLocale { .. }
+                | ClientMessage::Pong { .. }
+                | ClientMessage::AllowSpectators { .. }
+                | ClientMessage::VoteRules { .. }
+        )
+    }
+
+    /// Plays that change the table, as opposed to arranging a hand or socializing.
+    pub fn is_move(&self) -> bool {
+        matches!(
+            self,
+            ClientMessage::DrawFromDeck
+                | ClientMessage::DrawFromDiscard
+                | ClientMessage::Discard { .. }
+                | ClientMessage::PassTurn
+                | ClientMessage::DropHand { .. }
+                | ClientMessage::ShedCard { .. }
+        )
+    }
+
+    /// Rearranging one's own hand, which nobody else at the table can see.
+    pub fn arranges_hand(&self) -> bool {
+        matches!(
+            self,
+            ClientMessage::ReorderHand { .. } | ClientMessage::SortHand { .. }
+        )
+    }
+
+    /// Actions only the player holding the turn may take. Readying up between rounds
+    /// is open to everyone at the table.
+    pub fn requires_turn(&self) -> bool {
+        !self.is_out_of_turn() && !matches!(self, ClientMessage::ReadyForNextRound)
+    }
+
+    /// The message's type as it is tagged on the wire, e.g. `DropHand`.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ClientMessage::DrawFromDeck => "DrawFromDeck",
+            ClientMessage::DrawFromDiscard => "DrawFromDiscard",
+            ClientMessage::Discard { .. } => "Discard",
+            ClientMessage::PassTurn => "PassTurn",
+            ClientMessage::DropHand { .. } => "DropHand",
+            ClientMessage::ShedCard { .. } => "ShedCard",
+            ClientMessage::ReorderHand { .. } => "ReorderHand",
+            ClientMessage::SortHand { .. } => "SortHand",
+            ClientMessage::ReadyForNextRound => "ReadyForNextRound",
+            ClientMessage::Chat { .. } => "Chat",
+            ClientMessage::ReportPlayer { .. } => "ReportPlayer",
+            ClientMessage::RequestPause => "RequestPause",
+            ClientMessage::DeclinePause => "DeclinePause",
+            ClientMessage::ResumeGame => "ResumeGame",
+            ClientMessage::GetScores => "GetScores",
+            ClientMessage::RequestHint { .. } => "RequestHint",
+            ClientMessage::SetLocale { .. } => "SetLocale",
+            ClientMessage::Pong { .. } => "Pong",
+            ClientMessage::AllowSpectators { .. } => "AllowSpectators",
+            ClientMessage::VoteRules { .. } => "VoteRules",
+        }
+    }
+
+    /// The engine's view of a move; `None` for everything [`Self::is_move`] rejects.
+    pub fn as_action(&self) -> Option<Action> {
+        Some(match self {
+            ClientMessage::DrawFromDeck => Action::DrawFromDeck,
+            ClientMessage::DrawFromDiscard => Action::DrawFromDiscard,
+            ClientMessage::Discard { payload } => Action::Discard {
+                payload: payload.clone(),
+            },
+            ClientMessage::PassTurn => Action::PassTurn,
+            ClientMessage::DropHand { payload } => Action::DropHand {
+                payload: payload.clone(),
+            },
+            ClientMessage::ShedCard { payload } => Action::ShedCard {
+                payload: payload.clone(),
+            },
+            _ => return None,
+        })
+    }
+}
+
+impl From<Action> for ClientMessage {
+    fn from(action: Action) -> Self {
+        match action {
+            Action::DrawFromDeck => ClientMessage::DrawFromDeck,
+            Action::DrawFromDiscard => ClientMessage::DrawFromDiscard,
+            Action::Discard { payload } => ClientMessage::Discard { payload },
+            Action::PassTurn => ClientMessage::PassTurn,
+            Action::DropHand { payload } => ClientMessage::DropHand { payload },
+            Action::ShedCard { payload } => ClientMessage::ShedCard { payload },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SetLocalePayload {
+    /// A language tag such as `es-CL` or `en`; anything unsupported falls back to Spanish
+    pub locale: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct DiscardPayload {
-    pub card_index: usize,
+pub struct PongPayload {
+    /// The `nonce` of the `Ping` being answered
+    pub nonce: u64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct DropHandPayload {
-    pub combinations: Vec<Vec<Card>>,
+pub struct AllowSpectatorsPayload {
+    pub allowed: bool,
 }
 
-/// Shed a single card from hand onto an existing table combo.
-/// The position (left/right/trio-ext) is derived server-side by `can_shed()`.
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ShedCardPayload {
-    /// Index into the current player's hand
-    pub hand_card_index: usize,
-    /// ID of the player whose bajada we are extending
-    pub target_player_id: String,
-    /// Index into that player's `dropped_combinations`
-    pub target_combo_idx: usize,
+pub struct VoteRulesPayload {
+    /// Id of one of the presets on the ballot, e.g. `rapida`
+    pub preset: String,
+    /// One of the turn lengths on the ballot; zero is untimed
+    pub turn_seconds: u64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -42,6 +230,45 @@ pub struct ReorderHandPayload {
     pub hand: Vec<Card>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SortHandPayload {
+    pub by: SortBy,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RequestHintPayload {
+    /// 1: is a bajada possible; 2: also which cards belong to a meld; 3: also the bajada
+    pub level: u8,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChatPayload {
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReportPlayerPayload {
+    pub reported_player_id: String,
+    pub reason: String,
+}
+
+/// A set of house rules on a `RuleVoteOpened` ballot.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RulePresetOption {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+}
+
+/// A `LastAction` as sent to one connection: `action_type` stays the code clients key on,
+/// `label` describes it in the connection's language.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActionView {
+    #[serde(flatten)]
+    pub action: LastAction,
+    pub label: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PlayerScore {
     pub id: String,
@@ -54,10 +281,198 @@ pub struct PlayerScore {
 pub enum ServerMessage {
     Error {
         message: String,
+        /// Machine-readable reason, when the error has one
+        #[serde(skip_serializing_if = "Option::is_none")]
+        code: Option<String>,
+        /// The `correlation_id` of the client message that caused the error
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        correlation_id: Option<String>,
+    },
+    /// Measures the connection's round trip; answer with a `Pong` carrying the same nonce
+    Ping {
+        nonce: u64,
+    },
+    /// This device took control of the seat, or handed it to another of the player's
+    /// devices and now only mirrors it; sent only once the player has a second device
+    SeatControl {
+        in_control: bool,
+    },
+    /// Game browser connections: every public table, first thing and again whenever the
+    /// connection fell too far behind on updates
+    RoomList {
+        rooms: Vec<RoomListing>,
+    },
+    /// Game browser connections: a table appeared or changed
+    RoomListed {
+        room: RoomListing,
+    },
+    /// Game browser connections: a table's room closed
+    RoomUnlisted {
+        room_id: String,
+    },
+    /// The server went into maintenance: no new games start until it is over. Sent again
+    /// with `None` once it is
+    ServerMaintenance {
+        notice: Option<MaintenanceNotice>,
+    },
+    /// The game was saved for maintenance and the room closed; nothing was recorded
+    GameSuspended {
+        notice: Option<MaintenanceNotice>,
+    },
+    /// The player is waiting in the lobby for a table
+    QueueStatus {
+        queue: String,
+        region: Option<String>,
+        /// When they joined the queue; kept across a server restart
+        joined_at_epoch_ms: u64,
+        /// Their place was held for them through a server restart
+        restored: bool,
+        /// First in line because their last game was called off on them
+        #[serde(default)]
+        prioritized: bool,
+    },
+    /// The server is at capacity; the connection or new table waits in line
+    ServerFull {
+        /// 1-based place in the queue
+        position: usize,
+    },
+    /// A player's connection to the room came or went
+    PlayerConnectionChanged {
+        player_id: String,
+        status: ConnectionStatus,
+    },
+    /// A player dropped mid-game; nobody can play until they are back or the deadline passes
+    GamePaused {
+        player_id: String,
+        resume_deadline_epoch_ms: u64,
+    },
+    /// Someone asked for a break; it starts once everyone in `waiting_on` agrees too
+    PauseRequested {
+        requested_by: String,
+        agreed: Vec<String>,
+        waiting_on: Vec<String>,
+        /// When the vote lapses if not everyone has agreed
+        vote_deadline_epoch_ms: u64,
+    },
+    PauseDeclined {
+        player_id: String,
+    },
+    /// Everyone agreed to a break; play resumes by the deadline or when someone ends it
+    BreakStarted {
+        resume_deadline_epoch_ms: u64,
+    },
+    /// Play goes on; bots now play the turns of anyone in `substituted`
+    GameResumed {
+        substituted: Vec<String>,
+    },
+    /// Sent to players when someone starts or stops watching, or spectating is turned on
+    /// or off
+    SpectatorsChanged {
+        count: usize,
+        allowed: bool,
+    },
+    /// Sent to a spectator as the players turn spectating off, or turned away on arrival;
+    /// the stream ends after it
+    SpectatingDisallowed,
+    /// The table votes on its rules before the first deal; ties and missing votes keep
+    /// the defaults
+    RuleVoteOpened {
+        presets: Vec<RulePresetOption>,
+        turn_seconds: Vec<u64>,
+        default_preset: String,
+        default_turn_seconds: u64,
+        waiting_on: Vec<String>,
+        vote_deadline_epoch_ms: u64,
+    },
+    RuleVoteCast {
+        player_id: String,
+        preset: String,
+        turn_seconds: u64,
+        waiting_on: Vec<String>,
+    },
+    /// The vote closed; the game is dealt under these rules
+    RulesChosen {
+        preset: String,
+        turn_seconds: u64,
+    },
+    /// A player laid their bajada down
+    PlayerDroppedHand {
+        player_id: String,
+        combinations: Vec<Vec<Card>>,
+    },
+    /// A player added a card to a meld on the table
+    CardShed {
+        player_id: String,
+        card: Card,
+        /// Whose bajada the meld belongs to
+        target_player_id: String,
+        target_combo_idx: usize,
+        /// The meld with the card in place
+        combination: Vec<Card>,
+    },
+    /// Sent after the last `RoundEnded`, once the result has been stored and rated
+    GameOver {
+        podium: Podium,
+        /// Empty for unranked games; bots are not rated
+        rating_changes: Vec<RatingChange>,
+        /// How far each human's turns fell from the full-information oracle's, in seat order
+        #[serde(default)]
+        play_review: Vec<PlayReview>,
+    },
+    /// Reply to `GetScores`
+    Scoreboard {
+        /// Column order of `rounds[..].totals`
+        seats: Vec<String>,
+        /// One row per finished round
+        rounds: Vec<ScoreboardRow>,
+    },
+    /// Nobody at the table acted for too long; the game is over and the room closes
+    GameAbandoned {
+        /// Running totals when the game was called off; unplayed rounds are not scored
+        player_scores: Vec<PlayerScore>,
+        /// Whether the totals stand as a result, per the table's abandonment policy
+        counted: bool,
+    },
+    /// Admin observers only: the whole table, hidden information included
+    ObservedState {
+        players: Vec<PlayerState>,
+        round_index: usize,
+        current_turn_index: usize,
+        /// Undrawn cards; the next draw is the last one
+        deck: Vec<Card>,
+        discard_pile: Vec<Card>,
+        deck_seed: Option<u64>,
+        is_game_over: bool,
+        is_waiting_for_next_round: bool,
+    },
+    /// Admin observers only: an action as the room processed it, rejected ones included
+    ObservedAction {
+        user_id: String,
+        action: ClientMessage,
+        /// Why the room rejected it; `None` when accepted
+        error: Option<String>,
+        created_at_ms: i64,
     },
     MatchFound {
         room_id: String,
+        /// Passed as `rejoin` when connecting, takes this seat back, even once the login
+        /// token has expired
+        #[serde(default)]
+        reconnect_token: String,
+        /// Epoch seconds after which the reconnect token stops working
+        #[serde(default)]
+        reconnect_expires_at: usize,
         players: Vec<String>,
+        /// Declared region of each human at the table
+        regions: HashMap<String, String>,
+        /// The table mixes regions, so expect higher latency
+        cross_region: bool,
+        /// The receiving player's record against each opponent they have met before
+        #[serde(default)]
+        head_to_head: Vec<HeadToHead>,
+        /// Title each human at the table picked to show, where they picked one
+        #[serde(default)]
+        titles: HashMap<String, PlayerTitle>,
     },
     GameStateUpdate {
         // The array of cards belonging to the player receiving this message
@@ -67,13 +482,38 @@ pub enum ServerMessage {
         current_round_index: usize,
         current_round_rules: String,
         current_turn_index: usize,
+        /// Player ids in the order turns pass around the table
+        turn_order: Vec<String>,
+        /// Who plays after the current turn; `None` between rounds and after the game
+        next_player_id: Option<String>,
         discard_pile_top: Option<Card>,
+        /// Public part of the discard pile per the rule set, oldest first and ending with the top
+        discard_pile_recent: Vec<Card>,
         is_game_over: bool,
         is_waiting_for_next_round: bool,
         // Structured round requirements for frontend combo validation
         required_trios: usize,
         required_escalas: usize,
-        last_action: Option<LastAction>,
+        last_action: Option<ActionView>,
+        /// Up to the last ten actions, oldest first and ending with `last_action`
+        #[serde(default)]
+        recent_actions: Vec<ActionView>,
+        /// When the current turn times out (base time plus time bank); `None` without a timer
+        turn_deadline_epoch_ms: Option<u64>,
+        /// Milliseconds left in the current player's time bank
+        time_bank_remaining: Option<u64>,
+        /// On hot-seat connections, the seat whose hand `my_hand` is and who acts next
+        active_seat_id: Option<String>,
+        /// Players at this table declared different regions
+        cross_region: bool,
+        /// People watching through a spectate link
+        #[serde(default)]
+        spectators: usize,
+        /// Whether the players let anyone spectate
+        #[serde(default)]
+        spectators_allowed: bool,
+        /// Dropped players the game is paused for
+        paused_for: Vec<String>,
     },
     RoundEnded {
         round_index: usize,
@@ -83,12 +523,81 @@ pub enum ServerMessage {
         next_round_index: usize,
         next_round_name: String,
         is_game_over: bool,
+        /// Nobody went out before the table stalled; `winner_id` is empty
+        is_stalemate: bool,
+        player_stats: Vec<PlayerRoundStats>,
+        /// What everyone was left holding, so the penalties can be checked card by card
+        final_hands: Vec<FinalHand>,
+        winning_play: Option<WinningPlay>,
+        round_duration_ms: u64,
+        /// Since the game began, including breaks between rounds
+        game_duration_ms: u64,
+        /// Up to two moments worth showing again, the bigger first
+        #[serde(default)]
+        highlights: Vec<Highlight>,
+    },
+    /// One step of a streamed deal: `count` cards went to player `to`. Sent at round start
+    /// when the room streams deals; the cards themselves arrive in the next state update
+    CardDealt {
+        to: String,
+        count: u32,
+    },
+    Chat {
+        player_id: String,
+        /// Already passed through the word filter
+        text: String,
+    },
+    ReportReceived {
+        reported_player_id: String,
+    },
+    /// The current player is running out of time
+    TurnWarning {
+        player_id: String,
+        remaining_ms: u64,
+    },
+    /// Players who have not readied up by the deadline are readied automatically
+    NextRoundCountdown {
+        deadline_epoch_ms: u64,
+        seconds: u64,
+    },
+    /// Tutorial prompt for the learner, in their locale
+    TutorialHint {
+        step: usize,
+        text: String,
+        /// `ClientMessage` type that completes the step, if any
+        expect: Option<String>,
+    },
+    /// Answer to `RequestHint`, for the asker only
+    Hint {
+        level: u8,
+        bajada_possible: bool,
+        /// Hand indices of cards that belong to some meld the round asks for; level 2 and up
+        #[serde(skip_serializing_if = "Option::is_none")]
+        meld_cards: Option<Vec<usize>>,
+        /// The bajada the solver would lay down; level 3, when one is possible
+        #[serde(skip_serializing_if = "Option::is_none")]
+        combinations: Option<Vec<Vec<Card>>>,
+        /// The move expected to leave the fewest points, see `engine::analysis`; level 3,
+        /// on the asker's turn
+        #[serde(skip_serializing_if = "Option::is_none")]
+        suggestion: Option<ClientMessage>,
+    },
+    /// Teaching games: why a bot just made its move, in English
+    BotExplanation {
+        player_id: String,
+        /// `ClientMessage` type of the move
+        action: String,
+        text: String,
     },
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SanitizedPlayerState {
     pub id: String,
+    pub connection: ConnectionStatus,
+    /// How quickly the player's connection answers pings; `None` for bots and until measured
+    #[serde(default)]
+    pub connection_quality: Option<ConnectionQuality>,
     pub hand_count: usize, // Hide actual cards
     pub has_dropped_hand: bool,
     pub points: u32,
@@ -97,12 +606,43 @@ pub struct SanitizedPlayerState {
     pub has_drawn_this_turn: bool,
     pub dropped_hand_this_turn: bool,
     pub is_ready_for_next_round: bool,
+    /// Cards forced on the player this round for running out of time
+    pub penalty_cards: u32,
+    /// Card back and table theme the player picked
+    pub cosmetics: Cosmetics,
+    /// `None` when the player has no avatar
+    pub avatar_url: Option<String>,
+    /// Title the player picked to show; `None` when they show none
+    #[serde(default)]
+    pub title: Option<PlayerTitle>,
+    /// Name, avatar and difficulty to show for a bot; `None` for humans
+    pub bot: Option<BotIdentity>,
+}
+
+/// Whether a seat's player is at the table. Bots are always connected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ConnectionStatus {
+    Connected,
+    /// Back after dropping out earlier in the game
+    Reconnected,
+    /// Dropped out, or never showed up
+    Disconnected,
 }
 
 impl SanitizedPlayerState {
-    pub fn from_player_state(state: &PlayerState) -> Self {
+    pub fn from_player_state(
+        state: &PlayerState,
+        connection: ConnectionStatus,
+        connection_quality: Option<ConnectionQuality>,
+        cosmetics: Cosmetics,
+        avatar_url: Option<String>,
+        title: Option<PlayerTitle>,
+        bot: Option<BotIdentity>,
+    ) -> Self {
         Self {
             id: state.id.clone(),
+            connection,
+            connection_quality,
             hand_count: state.hand.len(),
             has_dropped_hand: state.has_dropped_hand,
             points: state.points,
@@ -111,6 +651,11 @@ impl SanitizedPlayerState {
             has_drawn_this_turn: state.has_drawn_this_turn,
             dropped_hand_this_turn: state.dropped_hand_this_turn,
             is_ready_for_next_round: state.is_ready_for_next_round,
+            penalty_cards: state.penalty_cards,
+            cosmetics,
+            avatar_url: avatar_url.or_else(|| bot.as_ref().map(|b| b.avatar_url.clone())),
+            title,
+            bot,
         }
     }
 }
@@ -0,0 +1,210 @@
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use futures_util::future::join_all;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot};
+
+use crate::api::auth::{self, Role};
+use crate::api::authz::AuthUser;
+use crate::api::problem::Problem;
+use crate::api::server::AppState;
+use crate::db::models::{GameResult, GameResultPlayer};
+use crate::db::repo;
+use crate::engine::transcript::{RoundTranscript, Transcript};
+use crate::matchmaking::room::{RoomEvent, SeatSummary};
+
+/// How long a busy room gets to answer before it is left out of the listing.
+const ROOM_REPLY_TIMEOUT: Duration = Duration::from_secs(2);
+
+#[derive(Serialize)]
+pub struct ActiveGame {
+    pub room_id: String,
+    pub players: Vec<String>,
+    pub round_index: usize,
+    pub round_name: String,
+    /// Whose turn it is; `None` between rounds
+    pub current_turn: Option<String>,
+    /// Pass as `rejoin` when connecting to `/ws` to take the seat back
+    pub reconnect_token: String,
+    /// Epoch seconds after which the reconnect token stops working
+    pub reconnect_expires_at: usize,
+}
+
+/// The games the caller holds a seat in that are still being played.
+pub async fn active_games(State(state): State<Arc<AppState>>, user: AuthUser) -> impl IntoResponse {
+    let rooms: Vec<(String, mpsc::Sender<RoomEvent>)> = state
+        .active_rooms
+        .lock()
+        .await
+        .iter()
+        .map(|(room_id, room_tx)| (room_id.clone(), room_tx.clone()))
+        .collect();
+
+    let mut games = Vec::new();
+    for (room_id, seat) in seats_in(rooms, &user.user_id).await {
+        let (reconnect_token, reconnect_expires_at) =
+            auth::create_reconnect_token(&user.user_id, &room_id);
+        games.push(ActiveGame {
+            room_id,
+            players: seat.players,
+            round_index: seat.round_index,
+            round_name: seat.round_name,
+            current_turn: seat.current_turn,
+            reconnect_token,
+            reconnect_expires_at,
+        });
+    }
+    Json(games)
+}
+
+#[derive(Deserialize)]
+pub struct TranscriptQuery {
+    /// `text` for a plain-text rendering; JSON otherwise
+    pub format: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct GameTranscript {
+    pub result: GameResult,
+    /// Final totals in seat order
+    pub players: Vec<GameResultPlayer>,
+    pub rounds: Vec<RoundTranscript>,
+}
+
+/// A finished game's deals, moves and scores, for the players who sat at it and moderators.
+pub async fn transcript(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Path(room_id): Path<String>,
+    Query(query): Query<TranscriptQuery>,
+) -> impl IntoResponse {
+    let Some(result) = repo::get_game_result(&state.read_db, &room_id).await else {
+        return Problem::new(StatusCode::NOT_FOUND, "Game not found or not finished")
+            .into_response();
+    };
+    let players = repo::get_game_result_players(&state.read_db, &room_id).await;
+    if user.role < Role::Moderator && !players.iter().any(|p| p.user_id == user.user_id) {
+        return Problem::new(
+            StatusCode::FORBIDDEN,
+            "Only players of the game can export it",
+        )
+        .into_response();
+    }
+    let transcript: Transcript = repo::get_game_transcript(&state.read_db, &room_id)
+        .await
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default();
+
+    if query.format.as_deref() == Some("text") {
+        let mut text = format!(
+            "Game {} ({}, {} rounds played)\n\n",
+            result.room_id, result.outcome, result.rounds_played
+        );
+        text.push_str(&transcript.render_text());
+        text.push_str("Final totals:\n");
+        for player in &players {
+            text.push_str(&format!("  {}: {}\n", player.user_id, player.total));
+        }
+        return text.into_response();
+    }
+
+    Json(GameTranscript {
+        result,
+        players,
+        rounds: transcript.rounds,
+    })
+    .into_response()
+}
+
+/// The user's seat in `room_id`, if the room is running and they hold one.
+pub async fn seat_in(state: &AppState, room_id: &str, user_id: &str) -> Option<SeatSummary> {
+    let room_tx = state.active_rooms.lock().await.get(room_id).cloned()?;
+    ask_seat(&room_tx, user_id).await
+}
+
+/// Asks every room at once for the user's seat, so a busy room holds up the answer by at
+/// most `ROOM_REPLY_TIMEOUT` rather than that much per room ahead of it.
+async fn seats_in(
+    rooms: Vec<(String, mpsc::Sender<RoomEvent>)>,
+    user_id: &str,
+) -> Vec<(String, SeatSummary)> {
+    let seats = join_all(rooms.iter().map(|(_, room_tx)| ask_seat(room_tx, user_id))).await;
+    rooms
+        .into_iter()
+        .zip(seats)
+        .filter_map(|((room_id, _), seat)| Some((room_id, seat?)))
+        .collect()
+}
+
+/// A room that can't take the question or answer it within `ROOM_REPLY_TIMEOUT` counts as
+/// no seat.
+async fn ask_seat(room_tx: &mpsc::Sender<RoomEvent>, user_id: &str) -> Option<SeatSummary> {
+    let (reply_tx, reply_rx) = oneshot::channel();
+    let ask = async {
+        room_tx
+            .send(RoomEvent::DescribeSeat(user_id.to_string(), reply_tx))
+            .await
+            .ok()?;
+        reply_rx.await.ok()?
+    };
+    tokio::time::timeout(ROOM_REPLY_TIMEOUT, ask).await.ok()?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn seat() -> SeatSummary {
+        SeatSummary {
+            players: vec!["ana".to_string(), "beto".to_string()],
+            hot_seats: Vec::new(),
+            regions: Default::default(),
+            round_index: 0,
+            round_name: "2 Tríos (6 cards)".to_string(),
+            current_turn: Some("ana".to_string()),
+        }
+    }
+
+    #[tokio::test]
+    async fn busy_rooms_hold_up_the_listing_once_between_them() {
+        // Two rooms whose queues are full, two that take the question but never answer
+        let mut rooms = Vec::new();
+        let mut held = Vec::new();
+        for n in 0..4 {
+            let (room_tx, room_rx) = mpsc::channel(1);
+            if n < 2 {
+                let (reply_tx, _) = oneshot::channel();
+                room_tx
+                    .try_send(RoomEvent::DescribeSeat("someone".to_string(), reply_tx))
+                    .unwrap();
+            }
+            held.push(room_rx);
+            rooms.push((format!("busy-{}", n), room_tx));
+        }
+        let (room_tx, mut room_rx) = mpsc::channel(1);
+        tokio::spawn(async move {
+            while let Some(RoomEvent::DescribeSeat(_, reply)) = room_rx.recv().await {
+                let _ = reply.send(Some(seat()));
+            }
+        });
+        rooms.push(("playing".to_string(), room_tx));
+
+        let started = std::time::Instant::now();
+        let seats = seats_in(rooms, "ana").await;
+        let elapsed = started.elapsed();
+        assert!(
+            elapsed >= ROOM_REPLY_TIMEOUT && elapsed < 2 * ROOM_REPLY_TIMEOUT,
+            "took {:?}",
+            elapsed
+        );
+        let ids: Vec<&str> = seats.iter().map(|(id, _)| id.as_str()).collect();
+        assert_eq!(ids, ["playing"]);
+        drop(held);
+    }
+}
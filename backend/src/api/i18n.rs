@@ -0,0 +1,603 @@
+use serde::{Deserialize, Serialize};
+
+/// Languages the server can answer in. Chilean Spanish is the house default.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Locale {
+    #[default]
+    #[serde(rename = "es-CL")]
+    EsCl,
+    #[serde(rename = "en")]
+    En,
+}
+
+impl Locale {
+    /// The bare language, e.g. "es", as the word filter's lists are keyed.
+    pub fn language(self) -> &'static str {
+        match self {
+            Locale::EsCl => "es",
+            Locale::En => "en",
+        }
+    }
+
+    /// Lenient BCP 47-ish parsing: "en", "en-US", "EN_gb" → En; anything else → the default.
+    pub fn parse(tag: &str) -> Self {
+        let primary = tag
+            .split(['-', '_'])
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        match primary.as_str() {
+            "en" => Locale::En,
+            _ => Locale::EsCl,
+        }
+    }
+}
+
+/// Catalog entry: message key, English text, Chilean Spanish text.
+///
+/// The engine and rooms refuse moves by key, and REST handlers name the entry they answer
+/// with through `Problem::keyed`.
+struct Entry {
+    key: &'static str,
+    en: &'static str,
+    es: &'static str,
+}
+
+const fn entry(key: &'static str, en: &'static str, es: &'static str) -> Entry {
+    Entry { key, en, es }
+}
+
+const CATALOG: &[Entry] = &[
+    // ── Turn flow ──
+    entry("not_your_turn", "Not your turn", "No es tu turno"),
+    entry("invalid_turn", "Invalid turn", "Turno inválido"),
+    entry("game_over", "Game is over", "El juego terminó"),
+    entry(
+        "waiting_for_next_round",
+        "Waiting for other players to be ready for the next round",
+        "Esperando que los demás jugadores estén listos para la siguiente ronda",
+    ),
+    entry(
+        "not_waiting_for_next_round",
+        "Game is not waiting for next round",
+        "El juego no está esperando la siguiente ronda",
+    ),
+    entry(
+        "player_not_found",
+        "Player not found",
+        "Jugador no encontrado",
+    ),
+    // ── Drawing ──
+    entry(
+        "already_drew",
+        "You have already drawn a card this turn",
+        "Ya robaste una carta este turno",
+    ),
+    entry("deck_empty", "Deck is empty", "El mazo está vacío"),
+    entry(
+        "discard_pile_empty",
+        "Discard pile is empty",
+        "El pozo está vacío",
+    ),
+    entry(
+        "pass_requires_empty_deck",
+        "You can only pass once the deck is empty",
+        "Solo puedes pasar cuando el mazo está vacío",
+    ),
+    entry(
+        "no_discard_after_bajada",
+        "Cannot draw from discard after dropping hand",
+        "No puedes sacar del pozo después de bajarte",
+    ),
+    // ── Discarding ──
+    entry(
+        "must_draw_before_discard",
+        "You must draw a card before discarding",
+        "Debes robar una carta antes de botar",
+    ),
+    entry(
+        "card_index_out_of_bounds",
+        "Card index out of bounds",
+        "Índice de carta inválido",
+    ),
+    // ── Bajada ──
+    entry(
+        "must_draw_before_bajada",
+        "You must draw a card before trying to drop your hand",
+        "Debes robar una carta antes de bajarte",
+    ),
+    entry("already_dropped", "Hand already dropped", "Ya te bajaste"),
+    entry(
+        "cards_not_in_hand",
+        "Combinations contain cards not in player's hand",
+        "Las combinaciones tienen cartas que no están en tu mano",
+    ),
+    entry(
+        "invalid_combination",
+        "Invalid combination: trios must be at least 3 cards, escalas at least 4",
+        "Combinación inválida: los tríos necesitan al menos 3 cartas y las escalas al menos 4",
+    ),
+    entry(
+        "round_requirements_not_met",
+        "Combinations do not match the current round requirements",
+        "Las combinaciones no cumplen los requisitos de la ronda",
+    ),
+    entry(
+        "too_many_jokers",
+        "Too many jokers in one bajada",
+        "Demasiados comodines en una sola bajada",
+    ),
+    entry(
+        "extra_melds_leave_discard",
+        "Extra melds must leave a card to discard",
+        "Las combinaciones extra deben dejar una carta para botar",
+    ),
+    // ── Shedding ──
+    entry(
+        "must_drop_before_shed",
+        "You must drop your hand before shedding cards",
+        "Debes bajarte antes de botar cartas en juegos",
+    ),
+    entry(
+        "shed_position_invalid",
+        "This card cannot be shed at that position",
+        "Esta carta no se puede botar en esa posición",
+    ),
+    entry(
+        "no_shed_on_bajada_turn",
+        "You cannot shed cards on the same turn you drop your hand",
+        "No puedes botar cartas en juegos el mismo turno en que te bajas",
+    ),
+    entry(
+        "must_draw_before_shed",
+        "You must draw a card before shedding cards",
+        "Debes robar una carta antes de botar en juegos",
+    ),
+    entry(
+        "target_player_not_found",
+        "Target player not found",
+        "Jugador objetivo no encontrado",
+    ),
+    entry(
+        "target_not_dropped",
+        "Target player has not dropped their hand yet",
+        "Ese jugador todavía no se baja",
+    ),
+    entry(
+        "target_combo_out_of_bounds",
+        "Target combo index out of bounds",
+        "Índice de combinación inválido",
+    ),
+    entry(
+        "cannot_shed_there",
+        "This card cannot be shed onto that combo",
+        "Esa carta no se puede agregar a esa combinación",
+    ),
+    // ── Hot seat ──
+    entry(
+        "unknown_seat",
+        "This connection does not control that seat",
+        "Esta conexión no controla ese asiento",
+    ),
+    entry(
+        "mirroring_device",
+        "This device is only mirroring the seat",
+        "Este dispositivo solo refleja el asiento",
+    ),
+    entry(
+        "game_not_running",
+        "That game is no longer running",
+        "Esa partida ya no está en curso",
+    ),
+    entry(
+        "scheduled_game_cancelled",
+        "That scheduled game was called off",
+        "Esa partida programada se canceló",
+    ),
+    entry(
+        "server_full",
+        "The server is full, please try again later",
+        "El servidor está lleno, inténtalo más tarde",
+    ),
+    // ── Moderation ──
+    entry("muted", "You are muted", "Estás silenciado"),
+    entry(
+        "report_self",
+        "You cannot report yourself",
+        "No puedes reportarte a ti mismo",
+    ),
+    entry(
+        "report_target_not_in_room",
+        "Reported player is not in this room",
+        "El jugador reportado no está en esta sala",
+    ),
+    // ── Input validation (`{limit}` is filled in by the caller) ──
+    entry(
+        "hand_too_large",
+        "A hand cannot contain more than {limit} cards",
+        "Una mano no puede tener más de {limit} cartas",
+    ),
+    entry(
+        "too_many_combinations",
+        "A bajada cannot contain more than {limit} combinations",
+        "Una bajada no puede tener más de {limit} combinaciones",
+    ),
+    entry(
+        "combination_too_large",
+        "A combination cannot contain more than {limit} cards",
+        "Una combinación no puede tener más de {limit} cartas",
+    ),
+    entry(
+        "invalid_hint_level",
+        "Hint level must be 1, 2 or 3",
+        "El nivel de pista debe ser 1, 2 o 3",
+    ),
+    entry(
+        "hint_level_not_allowed",
+        "Hints at that level are turned off at this table",
+        "Las pistas de ese nivel están desactivadas en esta mesa",
+    ),
+    entry(
+        "game_paused",
+        "The game is paused",
+        "La partida está en pausa",
+    ),
+    entry(
+        "breaks_disabled",
+        "Breaks are turned off on this server",
+        "Las pausas están desactivadas en este servidor",
+    ),
+    entry(
+        "already_paused",
+        "The game is already paused",
+        "La partida ya está en pausa",
+    ),
+    entry(
+        "no_pause_requested",
+        "Nobody has asked for a break",
+        "Nadie ha pedido una pausa",
+    ),
+    entry(
+        "too_many_reorders",
+        "You are rearranging your hand too often; wait for the next turn",
+        "Estás reordenando tu mano demasiado seguido; espera al siguiente turno",
+    ),
+    entry(
+        "not_on_break",
+        "The game is not on a break",
+        "La partida no está en pausa",
+    ),
+    entry(
+        "spectating_disallowed",
+        "Spectating is turned off at this table",
+        "Esta mesa no permite espectadores",
+    ),
+    entry(
+        "rule_vote_running",
+        "The game starts once the rules are voted on",
+        "La partida empieza cuando se voten las reglas",
+    ),
+    entry(
+        "no_rule_vote",
+        "The rules are not being voted on",
+        "No se están votando las reglas",
+    ),
+    entry(
+        "not_a_rule_voter",
+        "You are not voting on this table's rules",
+        "No votas las reglas de esta mesa",
+    ),
+    entry(
+        "unknown_rule_preset",
+        "Unknown rule preset",
+        "Reglas desconocidas",
+    ),
+    entry(
+        "turn_length_not_on_ballot",
+        "That turn length is not on the ballot",
+        "Esa duración de turno no está entre las opciones",
+    ),
+    entry(
+        "empty_chat_message",
+        "Chat messages cannot be empty",
+        "Los mensajes no pueden estar vacíos",
+    ),
+    entry(
+        "chat_message_too_long",
+        "Chat messages cannot exceed {limit} characters",
+        "Los mensajes no pueden superar los {limit} caracteres",
+    ),
+    entry(
+        "report_reason_too_long",
+        "Report reasons cannot exceed {limit} characters",
+        "El motivo del reporte no puede superar los {limit} caracteres",
+    ),
+    entry(
+        "username_too_long",
+        "Username cannot exceed {limit} characters",
+        "El nombre de usuario no puede superar los {limit} caracteres",
+    ),
+    entry(
+        "password_too_long",
+        "Password cannot exceed {limit} characters",
+        "La contraseña no puede superar los {limit} caracteres",
+    ),
+    // ── Accounts ──
+    entry(
+        "missing_username",
+        "Missing username",
+        "Falta el nombre de usuario",
+    ),
+    entry(
+        "missing_password",
+        "Missing password",
+        "Falta la contraseña",
+    ),
+    entry(
+        "username_taken",
+        "Username already exists",
+        "Ese nombre de usuario ya existe",
+    ),
+    entry(
+        "username_not_allowed",
+        "Username contains disallowed words",
+        "El nombre de usuario contiene palabras no permitidas",
+    ),
+    entry(
+        "invalid_credentials",
+        "Invalid credentials",
+        "Usuario o contraseña incorrectos",
+    ),
+    entry("account_banned", "Account banned", "Cuenta suspendida"),
+    entry(
+        "missing_token",
+        "Missing bearer token",
+        "Falta el token de acceso",
+    ),
+    entry("invalid_token", "Invalid token", "Token inválido"),
+    entry(
+        "insufficient_role",
+        "Insufficient role",
+        "No tienes permiso para hacer esto",
+    ),
+    entry(
+        "rate_limited",
+        "Too many requests",
+        "Demasiadas solicitudes; intenta más tarde",
+    ),
+    // ── Round names (`RoundType::description`) ──
+    entry("round_two_trios", "2 Tríos (6 cards)", "2 Tríos (6 cartas)"),
+    entry(
+        "round_one_trio_one_escala",
+        "1 Trío, 1 Escala (7 cards)",
+        "1 Trío y 1 Escala (7 cartas)",
+    ),
+    entry(
+        "round_two_escalas",
+        "2 Escalas (8 cards)",
+        "2 Escalas (8 cartas)",
+    ),
+    entry(
+        "round_three_trios",
+        "3 Tríos (9 cards)",
+        "3 Tríos (9 cartas)",
+    ),
+    entry(
+        "round_two_trios_one_escala",
+        "2 Tríos, 1 Escala (10 cards)",
+        "2 Tríos y 1 Escala (10 cartas)",
+    ),
+    entry(
+        "round_one_trio_two_escalas",
+        "1 Trío, 2 Escalas (11 cards)",
+        "1 Trío y 2 Escalas (11 cartas)",
+    ),
+    entry(
+        "round_three_escalas",
+        "3 Escalas (12 cards)",
+        "3 Escalas (12 cartas)",
+    ),
+    entry(
+        "round_four_trios",
+        "4 Tríos (12 cards)",
+        "4 Tríos (12 cartas)",
+    ),
+    entry(
+        "round_escala_real",
+        "Escala Real (13 cards, same suit)",
+        "Escala Real (13 cartas del mismo palo)",
+    ),
+    entry("round_game_over", "Game Over", "Fin del juego"),
+    // ── Actions (`LastAction::action_type`) ──
+    entry(
+        "action_drew_from_deck",
+        "Drew from the deck",
+        "Robó del mazo",
+    ),
+    entry(
+        "action_drew_from_pozo",
+        "Took the top discard",
+        "Sacó del pozo",
+    ),
+    entry("action_discarded", "Discarded", "Botó una carta"),
+    entry("action_passed", "Passed", "Pasó"),
+    entry(
+        "action_penalty_draw",
+        "Ran out of time and drew a penalty card",
+        "Se le acabó el tiempo y robó una carta de castigo",
+    ),
+    entry("action_skipped", "Ran out of time", "Se le acabó el tiempo"),
+    entry("action_dropped_hand", "Dropped their hand", "¡Se bajó!"),
+    entry(
+        "action_shed",
+        "Added a card to a meld",
+        "Agregó una carta a un juego",
+    ),
+];
+
+/// Key used for errors that have no catalog entry; the raw text is passed through.
+pub const UNKNOWN_ERROR_KEY: &str = "unknown_error";
+
+/// A message ready to put on the wire: a stable key for clients plus localized text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LocalizedMessage {
+    pub key: &'static str,
+    pub text: String,
+}
+
+/// Localizes an engine or room error given by its key; an unknown key passes through as
+/// the text.
+pub fn localize_error(key: &str, locale: Locale) -> LocalizedMessage {
+    localize_key(key, locale, None).unwrap_or_else(|| LocalizedMessage {
+        key: UNKNOWN_ERROR_KEY,
+        text: key.to_string(),
+    })
+}
+
+/// A round name (`RoundType::description`) in `locale`; unknown text passes through.
+pub fn translate(english: &str, locale: Locale) -> String {
+    match CATALOG.iter().find(|e| e.en == english) {
+        Some(entry) => text_of(entry, locale).to_string(),
+        None => english.to_string(),
+    }
+}
+
+/// Describes a `LastAction::action_type`, which clients key on, in `locale`.
+pub fn action_label(action_type: &str, locale: Locale) -> String {
+    let key = match action_type {
+        "drew_from_deck" => "action_drew_from_deck",
+        "drew_from_pozo" => "action_drew_from_pozo",
+        "discarded" => "action_discarded",
+        "passed" => "action_passed",
+        "penalty_draw" => "action_penalty_draw",
+        "skipped" => "action_skipped",
+        "bajó" => "action_dropped_hand",
+        "shed" => "action_shed",
+        other => return other.to_string(),
+    };
+    localize_error(key, locale).text
+}
+
+/// Localizes a message by key, substituting `{limit}` when present.
+pub fn localize_key(key: &str, locale: Locale, limit: Option<usize>) -> Option<LocalizedMessage> {
+    let entry = CATALOG.iter().find(|e| e.key == key)?;
+    let mut text = text_of(entry, locale).to_string();
+    if let Some(limit) = limit {
+        text = text.replace("{limit}", &limit.to_string());
+    }
+    Some(LocalizedMessage {
+        key: entry.key,
+        text,
+    })
+}
+
+fn text_of(entry: &Entry, locale: Locale) -> &'static str {
+    match locale {
+        Locale::EsCl => entry.es,
+        Locale::En => entry.en,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_locale_tags() {
+        assert_eq!(Locale::parse("en"), Locale::En);
+        assert_eq!(Locale::parse("en-US"), Locale::En);
+        assert_eq!(Locale::parse("EN_gb"), Locale::En);
+        assert_eq!(Locale::parse("es-CL"), Locale::EsCl);
+        assert_eq!(Locale::parse("fr"), Locale::EsCl);
+        assert_eq!(Locale::parse(""), Locale::EsCl);
+    }
+
+    #[test]
+    fn localizes_engine_errors() {
+        let msg = localize_error("not_your_turn", Locale::EsCl);
+        assert_eq!(msg.key, "not_your_turn");
+        assert_eq!(msg.text, "No es tu turno");
+
+        let msg = localize_error("not_your_turn", Locale::En);
+        assert_eq!(msg.text, "Not your turn");
+    }
+
+    #[test]
+    fn unknown_errors_pass_through() {
+        let msg = localize_error("something_new", Locale::EsCl);
+        assert_eq!(msg.key, UNKNOWN_ERROR_KEY);
+        assert_eq!(msg.text, "something_new");
+    }
+
+    #[test]
+    fn every_engine_refusal_is_a_catalog_key() {
+        let source = include_str!("../../engine/src/game.rs");
+        let refusals = source
+            .split(['(', ')'])
+            .zip(source.split(['(', ')']).skip(1))
+            .filter(|(before, _)| before.ends_with("Err") || before.ends_with("ok_or"))
+            .filter_map(|(_, inside)| inside.strip_prefix('"')?.strip_suffix('"'));
+        let mut count = 0;
+        for key in refusals {
+            assert!(localize_key(key, Locale::EsCl, None).is_some(), "{}", key);
+            count += 1;
+        }
+        assert!(count > 30, "Found only {} refusals", count);
+    }
+
+    #[test]
+    fn labels_actions_in_each_language() {
+        assert_eq!(action_label("bajó", Locale::EsCl), "¡Se bajó!");
+        assert_eq!(action_label("bajó", Locale::En), "Dropped their hand");
+        assert_eq!(
+            action_label("drew_from_pozo", Locale::En),
+            "Took the top discard"
+        );
+        assert_eq!(action_label("something_new", Locale::En), "something_new");
+    }
+
+    #[test]
+    fn translates_every_round_name() {
+        for round in crate::engine::game::RoundType::all_rounds() {
+            let name = round.description();
+            assert_eq!(translate(name, Locale::En), name);
+            assert_ne!(
+                translate(name, Locale::EsCl),
+                name,
+                "{} has no translation",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn substitutes_limits() {
+        let msg = localize_key("hand_too_large", Locale::EsCl, Some(32)).unwrap();
+        assert_eq!(msg.text, "Una mano no puede tener más de 32 cartas");
+    }
+
+    #[test]
+    fn catalog_keys_are_unique() {
+        let mut keys: Vec<&str> = CATALOG.iter().map(|e| e.key).collect();
+        keys.sort_unstable();
+        let before = keys.len();
+        keys.dedup();
+        assert_eq!(before, keys.len());
+    }
+
+    #[test]
+    fn covers_every_validation_code() {
+        for code in [
+            "hand_too_large",
+            "too_many_combinations",
+            "combination_too_large",
+            "empty_chat_message",
+            "chat_message_too_long",
+            "report_reason_too_long",
+            "username_too_long",
+            "password_too_long",
+        ] {
+            assert!(localize_key(code, Locale::En, None).is_some(), "{}", code);
+        }
+    }
+}
@@ -0,0 +1,255 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::api::auth;
+use crate::api::authz::AuthUser;
+use crate::api::problem::Problem;
+use crate::api::server::AppState;
+use crate::db::models::{GameResultPlayer, League, LeagueFixture, LeaguePlayer};
+use crate::db::repo;
+use crate::tournament::director;
+use crate::tournament::league::{self, FixtureResult, LeagueStanding};
+
+const MAX_LEAGUE_NAME_CHARS: usize = 64;
+const MAX_LEAGUE_PLAYERS: usize = 20;
+
+#[derive(Deserialize)]
+pub struct CreateLeaguePayload {
+    pub name: String,
+    pub usernames: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct LeagueDetail {
+    pub league: League,
+    pub players: Vec<LeaguePlayer>,
+    /// By matchday
+    pub fixtures: Vec<LeagueFixture>,
+}
+
+#[derive(Serialize)]
+pub struct LeagueStandings {
+    pub league: League,
+    pub standings: Vec<LeagueStanding>,
+    /// Earliest matchday with fixtures still to play; `None` once the league is complete
+    pub current_matchday: Option<i64>,
+    /// Each player's next fixture, earliest matchday first
+    pub next_fixtures: Vec<NextFixture>,
+}
+
+#[derive(Serialize)]
+pub struct NextFixture {
+    pub user_id: String,
+    pub fixture_id: i64,
+    pub matchday: i64,
+    pub opponent_id: String,
+}
+
+#[derive(Serialize)]
+pub struct FixtureRoom {
+    pub room_id: String,
+    /// Connect with `/ws?token=<login>&rejoin=<reconnect_token>` to take the seat
+    pub reconnect_token: String,
+}
+
+pub async fn list_leagues(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    Json(repo::list_leagues(&state.db).await)
+}
+
+pub async fn get_league(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> impl IntoResponse {
+    let Some(league) = repo::get_league(&state.db, id).await else {
+        return Problem::new(StatusCode::NOT_FOUND, "League not found").into_response();
+    };
+    Json(LeagueDetail {
+        players: repo::get_league_players(&state.db, id).await,
+        fixtures: repo::get_league_fixtures(&state.db, id).await,
+        league,
+    })
+    .into_response()
+}
+
+/// Sets up a league for the given players with the whole round-robin schedule.
+pub async fn create_league(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Json(payload): Json<CreateLeaguePayload>,
+) -> impl IntoResponse {
+    let name = payload.name.trim();
+    if name.is_empty() || name.chars().count() > MAX_LEAGUE_NAME_CHARS {
+        return Problem::new(
+            StatusCode::BAD_REQUEST,
+            "A league name must be 1 to 64 characters",
+        )
+        .into_response();
+    }
+    if !(2..=MAX_LEAGUE_PLAYERS).contains(&payload.usernames.len()) {
+        return Problem::new(StatusCode::BAD_REQUEST, "A league needs 2 to 20 players")
+            .into_response();
+    }
+
+    let mut players = Vec::with_capacity(payload.usernames.len());
+    for username in &payload.usernames {
+        let Some(member) = state.user_cache.get_user(&state.db, username).await else {
+            return Problem::new(
+                StatusCode::NOT_FOUND,
+                &format!("Unknown player {}", username),
+            )
+            .into_response();
+        };
+        if players.contains(&member.id) {
+            return Problem::new(StatusCode::BAD_REQUEST, "Players must be unique").into_response();
+        }
+        players.push(member.id);
+    }
+
+    let fixtures: Vec<(i64, String, String)> = league::round_robin(players.len())
+        .into_iter()
+        .enumerate()
+        .flat_map(|(day, pairings)| {
+            let players = &players;
+            pairings
+                .into_iter()
+                .map(move |(a, b)| (day as i64 + 1, players[a].clone(), players[b].clone()))
+        })
+        .collect();
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64;
+    match repo::insert_league(&state.db, name, &user.user_id, now, &players, &fixtures).await {
+        Ok(league) => (StatusCode::CREATED, Json(league)).into_response(),
+        Err(_) => Problem::new(StatusCode::INTERNAL_SERVER_ERROR, "Failed to create league")
+            .into_response(),
+    }
+}
+
+/// The table from every counted result so far, with what each player should play next.
+pub async fn standings(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> impl IntoResponse {
+    let Some(league) = repo::get_league(&state.read_db, id).await else {
+        return Problem::new(StatusCode::NOT_FOUND, "League not found").into_response();
+    };
+    let players: Vec<String> = repo::get_league_players(&state.read_db, id)
+        .await
+        .into_iter()
+        .map(|p| p.user_id)
+        .collect();
+    let results = fixture_results(repo::get_league_results(&state.read_db, id).await);
+    let played: HashSet<&str> = results.keys().map(String::as_str).collect();
+
+    let remaining: Vec<LeagueFixture> = repo::get_league_fixtures(&state.read_db, id)
+        .await
+        .into_iter()
+        .filter(|f| !f.room_id.as_deref().is_some_and(|r| played.contains(r)))
+        .collect();
+    let next_fixtures = players
+        .iter()
+        .filter_map(|user_id| {
+            let fixture = remaining
+                .iter()
+                .find(|f| &f.home_id == user_id || &f.away_id == user_id)?;
+            let opponent_id = if &fixture.home_id == user_id {
+                &fixture.away_id
+            } else {
+                &fixture.home_id
+            };
+            Some(NextFixture {
+                user_id: user_id.clone(),
+                fixture_id: fixture.id,
+                matchday: fixture.matchday,
+                opponent_id: opponent_id.clone(),
+            })
+        })
+        .collect();
+
+    let results: Vec<FixtureResult> = results.into_values().collect();
+    Json(LeagueStandings {
+        standings: league::standings(&players, &results),
+        current_matchday: remaining.first().map(|f| f.matchday),
+        next_fixtures,
+        league,
+    })
+    .into_response()
+}
+
+/// Opens a room for one of the caller's fixtures, or hands back the one already running.
+pub async fn play_fixture(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Path((id, fixture_id)): Path<(i64, i64)>,
+) -> impl IntoResponse {
+    let Some(fixture) = repo::get_league_fixture(&state.db, id, fixture_id).await else {
+        return Problem::new(StatusCode::NOT_FOUND, "Fixture not found").into_response();
+    };
+    if user.user_id != fixture.home_id && user.user_id != fixture.away_id {
+        return Problem::new(StatusCode::FORBIDDEN, "Not your fixture").into_response();
+    }
+
+    if let Some(room_id) = fixture.room_id {
+        let counted = repo::get_game_result(&state.db, &room_id)
+            .await
+            .is_some_and(|r| r.counted);
+        if counted {
+            return Problem::new(StatusCode::CONFLICT, "The fixture has been played")
+                .into_response();
+        }
+        if state.active_rooms.lock().await.contains_key(&room_id) {
+            return Json(fixture_room(&user.user_id, room_id)).into_response();
+        }
+    }
+
+    let players = [fixture.home_id, fixture.away_id];
+    let Some(room_id) = director::open_match(&state, &players).await else {
+        return Problem::keyed(StatusCode::SERVICE_UNAVAILABLE, "server_full").into_response();
+    };
+    if repo::set_league_fixture_room(&state.db, fixture.id, &room_id)
+        .await
+        .is_err()
+    {
+        return Problem::new(StatusCode::INTERNAL_SERVER_ERROR, "Failed to save fixture")
+            .into_response();
+    }
+    Json(fixture_room(&user.user_id, room_id)).into_response()
+}
+
+fn fixture_room(user_id: &str, room_id: String) -> FixtureRoom {
+    FixtureRoom {
+        reconnect_token: auth::create_reconnect_token(user_id, &room_id).0,
+        room_id,
+    }
+}
+
+/// Seat-ordered totals grouped into one result per room.
+fn fixture_results(rows: Vec<GameResultPlayer>) -> HashMap<String, FixtureResult> {
+    let mut by_room: HashMap<String, Vec<GameResultPlayer>> = HashMap::new();
+    for row in rows {
+        by_room.entry(row.room_id.clone()).or_default().push(row);
+    }
+    by_room
+        .into_iter()
+        .filter_map(|(room_id, seats)| match seats.as_slice() {
+            [a, b] => {
+                let result = FixtureResult {
+                    players: [a.user_id.clone(), b.user_id.clone()],
+                    totals: [a.total, b.total],
+                };
+                Some((room_id, result))
+            }
+            _ => None,
+        })
+        .collect()
+}
@@ -0,0 +1,159 @@
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::oneshot;
+
+use crate::api::server::AppState;
+use crate::db::repo;
+use crate::engine::snapshot::GameSnapshot;
+use crate::matchmaking::room::RoomEvent;
+
+/// The notice when the admin didn't write one.
+pub const DEFAULT_MESSAGE: &str = "The server is down for maintenance, please try again later";
+
+/// What players and connecting clients are told while the server is in maintenance.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MaintenanceNotice {
+    pub message: String,
+    pub started_at_epoch_ms: u64,
+    /// When games still running are saved and their rooms closed; `None` lets them finish
+    pub drain_deadline_epoch_ms: Option<u64>,
+}
+
+/// Whether the server is in maintenance: no new matches start, and games already running
+/// either finish or are saved at the drain deadline.
+#[derive(Clone, Default)]
+pub struct Maintenance {
+    /// Bumped on every change, so a drain scheduled for an earlier window can tell
+    window: Arc<Mutex<(u64, Option<MaintenanceNotice>)>>,
+}
+
+impl Maintenance {
+    pub fn notice(&self) -> Option<MaintenanceNotice> {
+        self.window.lock().unwrap().1.clone()
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.window.lock().unwrap().1.is_some()
+    }
+
+    /// Starts maintenance, or replaces the notice and deadline of the one under way.
+    /// Returns the notice and an id that only this call's window answers to.
+    pub fn begin(&self, message: String, drain: Option<Duration>) -> (u64, MaintenanceNotice) {
+        let now = epoch_ms();
+        let (generation, current) = &mut *self.window.lock().unwrap();
+        let notice = MaintenanceNotice {
+            message,
+            started_at_epoch_ms: current
+                .as_ref()
+                .map_or(now, |notice| notice.started_at_epoch_ms),
+            drain_deadline_epoch_ms: drain.map(|drain| now + drain.as_millis() as u64),
+        };
+        *generation += 1;
+        *current = Some(notice.clone());
+        (*generation, notice)
+    }
+
+    /// Returns whether maintenance was on.
+    pub fn end(&self) -> bool {
+        let (generation, current) = &mut *self.window.lock().unwrap();
+        *generation += 1;
+        current.take().is_some()
+    }
+
+    /// Whether the window `begin` handed out `generation` for is still the current one.
+    pub fn is_current(&self, generation: u64) -> bool {
+        let (current, notice) = &*self.window.lock().unwrap();
+        notice.is_some() && *current == generation
+    }
+}
+
+/// Tells every running room about maintenance starting, changing or (`None`) ending.
+pub async fn announce(state: &AppState, notice: Option<MaintenanceNotice>) {
+    let rooms: Vec<_> = state.active_rooms.lock().await.values().cloned().collect();
+    for room in rooms {
+        let _ = room.send(RoomEvent::Maintenance(notice.clone())).await;
+    }
+}
+
+/// Waits out the drain period of window `generation`, then saves every game still running
+/// and closes its room. Does nothing if maintenance ended or was rescheduled meanwhile.
+pub async fn drain_after(state: Arc<AppState>, generation: u64, drain: Duration) {
+    tokio::time::sleep(drain).await;
+    if !state.maintenance.is_current(generation) {
+        return;
+    }
+
+    let rooms: Vec<_> = state
+        .active_rooms
+        .lock()
+        .await
+        .iter()
+        .map(|(room_id, tx)| (room_id.clone(), tx.clone()))
+        .collect();
+    let mut saved = 0;
+    for (room_id, room) in rooms {
+        let (reply, snapshot) = oneshot::channel();
+        if room.send(RoomEvent::Suspend(reply)).await.is_err() {
+            continue;
+        }
+        let Ok(Some(snapshot)) = snapshot.await else {
+            continue;
+        };
+        if let Err(e) = store(&state, &room_id, &snapshot).await {
+            eprintln!("[Maintenance] Failed to save room {}: {}", room_id, e);
+            continue;
+        }
+        state.active_rooms.lock().await.remove(&room_id);
+        saved += 1;
+    }
+    println!("[Maintenance] Drain deadline passed, saved {} games", saved);
+}
+
+async fn store(state: &AppState, room_id: &str, snapshot: &GameSnapshot) -> Result<(), String> {
+    let json = serde_json::to_string(snapshot).map_err(|e| e.to_string())?;
+    repo::insert_room_snapshot(&state.db, room_id, &json, unix_now())
+        .await
+        .map_err(|e| e.to_string())
+}
+
+fn epoch_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64
+}
+
+fn unix_now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rescheduling_supersedes_the_earlier_window() {
+        let maintenance = Maintenance::default();
+        assert!(!maintenance.is_active());
+
+        let (first, notice) = maintenance.begin("Back soon".into(), None);
+        assert_eq!(notice.drain_deadline_epoch_ms, None);
+        let (second, rescheduled) =
+            maintenance.begin("Back in ten".into(), Some(Duration::from_secs(600)));
+        assert!(!maintenance.is_current(first));
+        assert!(maintenance.is_current(second));
+        assert_eq!(rescheduled.started_at_epoch_ms, notice.started_at_epoch_ms);
+        assert!(rescheduled.drain_deadline_epoch_ms.is_some());
+        assert_eq!(maintenance.notice(), Some(rescheduled));
+
+        assert!(maintenance.end());
+        assert!(!maintenance.is_current(second));
+        assert!(!maintenance.end());
+        let (third, _) = maintenance.begin("Again".into(), None);
+        assert!(third > second);
+    }
+}
@@ -1,4 +1,33 @@
+pub mod admin;
+pub mod admission;
 pub mod auth;
+pub mod authz;
+pub mod avatars;
+pub mod cosmetics;
+pub mod dev;
 pub mod events;
+pub mod games;
+pub mod i18n;
+pub mod leagues;
+pub mod maintenance;
+pub mod openapi;
+pub mod practice;
+pub mod problem;
+pub mod push;
+pub mod queues;
+pub mod rate_limit;
+pub mod request_id;
+pub mod rooms;
+pub mod rtt;
+pub mod scheduled_games;
+pub mod seasons;
 pub mod server;
+pub mod spectate;
+pub mod stats;
+pub mod titles;
+pub mod tls;
+pub mod tournaments;
+pub mod usernames;
+pub mod validation;
+pub mod webhooks;
 pub mod ws;
@@ -0,0 +1,920 @@
+use axum::{
+    Json,
+    response::{Html, IntoResponse},
+};
+use serde_json::{Map, Value, json};
+
+/// Version of the REST API described; bump alongside breaking changes.
+const API_VERSION: &str = "1.0.0";
+
+/// Swagger UI release the docs page loads.
+const SWAGGER_UI_VERSION: &str = "5.17.14";
+
+/// The OpenAPI document for the REST API.
+///
+/// Written by hand rather than derived: keep it in step with the handlers and the
+/// payload types it names when either changes. A test fails when `build_router` serves a
+/// route this leaves out.
+pub fn document() -> Value {
+    json!({
+        "openapi": "3.1.0",
+        "info": {
+            "title": "Carioca API",
+            "version": API_VERSION,
+            "description": "REST endpoints of the Carioca server. Gameplay itself runs over \
+                the `/ws` WebSocket. Errors are `application/problem+json`.",
+        },
+        "tags": [
+            { "name": "auth" },
+            { "name": "profile" },
+            { "name": "rooms" },
+            { "name": "stats" },
+            { "name": "community" },
+            { "name": "notifications" },
+            { "name": "admin" },
+        ],
+        "paths": paths(),
+        "components": {
+            "securitySchemes": {
+                "bearer": { "type": "http", "scheme": "bearer", "bearerFormat": "JWT" },
+            },
+            "responses": {
+                "Problem": {
+                    "description": "The request failed",
+                    "content": {
+                        "application/problem+json": { "schema": schema_ref("Problem") },
+                    },
+                },
+            },
+            "schemas": schemas(),
+        },
+    })
+}
+
+pub async fn openapi_json() -> impl IntoResponse {
+    Json(document())
+}
+
+/// Interactive docs rendering `/api/openapi.json`.
+pub async fn docs() -> impl IntoResponse {
+    Html(format!(
+        r##"<!doctype html>
+<html>
+<head>
+  <meta charset="utf-8">
+  <title>Carioca API</title>
+  <link rel="stylesheet" href="https://unpkg.com/swagger-ui-dist@{v}/swagger-ui.css">
+</head>
+<body>
+  <div id="docs"></div>
+  <script src="https://unpkg.com/swagger-ui-dist@{v}/swagger-ui-bundle.js"></script>
+  <script>SwaggerUIBundle({{ url: "/api/openapi.json", dom_id: "#docs" }});</script>
+</body>
+</html>"##,
+        v = SWAGGER_UI_VERSION
+    ))
+}
+
+fn schema_ref(name: &str) -> Value {
+    json!({ "$ref": format!("#/components/schemas/{}", name) })
+}
+
+fn json_body(schema: Value) -> Value {
+    json!({ "content": { "application/json": { "schema": schema } } })
+}
+
+fn path_param(name: &str, description: &str) -> Value {
+    json!({
+        "name": name,
+        "in": "path",
+        "required": true,
+        "description": description,
+        "schema": { "type": "string" },
+    })
+}
+
+/// One operation. `ok` maps success statuses to their body schema (`Value::Null` for none);
+/// every operation may also fail with a problem document.
+fn operation(
+    tag: &str,
+    summary: &str,
+    authenticated: bool,
+    parameters: Vec<Value>,
+    request: Option<Value>,
+    ok: &[(&str, Value)],
+) -> Value {
+    let mut responses = Map::new();
+    for (status, schema) in ok {
+        let mut response = json!({ "description": "Success" });
+        if !schema.is_null() {
+            response["content"] = json_body(schema.clone())["content"].take();
+        }
+        responses.insert(status.to_string(), response);
+    }
+    responses.insert(
+        "default".to_string(),
+        json!({ "$ref": "#/components/responses/Problem" }),
+    );
+
+    let mut op = json!({
+        "tags": [tag],
+        "summary": summary,
+        "responses": responses,
+    });
+    if authenticated {
+        op["security"] = json!([{ "bearer": [] }]);
+    }
+    if !parameters.is_empty() {
+        op["parameters"] = Value::Array(parameters);
+    }
+    if let Some(schema) = request {
+        op["requestBody"] = json_body(schema);
+        op["requestBody"]["required"] = json!(true);
+    }
+    op
+}
+
+fn paths() -> Value {
+    let user_id = || path_param("user_id", "Account id");
+    let mut paths = json!({
+        "/api/auth/register": {
+            "post": operation("auth", "Create an account and log in", false, vec![],
+                Some(schema_ref("AuthPayload")), &[("201", schema_ref("AuthResponse"))]),
+        },
+        "/api/auth/login": {
+            "post": operation("auth", "Log in", false, vec![],
+                Some(schema_ref("AuthPayload")), &[("200", schema_ref("AuthResponse"))]),
+        },
+        "/api/me/username": {
+            "put": operation("profile", "Rename the caller, at most once every 30 days", true,
+                vec![], Some(schema_ref("RenamePayload")),
+                &[("200", schema_ref("RenameResponse"))]),
+        },
+        "/api/me/bot-level": {
+            "get": operation("profile", "The caller's adaptive practice level and next opponents",
+                true, vec![], None, &[("200", schema_ref("BotLevel"))]),
+        },
+        "/api/me/cosmetics": {
+            "get": operation("profile", "The caller's card back and table theme", true, vec![],
+                None, &[("200", schema_ref("Cosmetics"))]),
+            "put": operation("profile", "Change the caller's card back or table theme", true,
+                vec![], Some(schema_ref("SetCosmeticsPayload")),
+                &[("200", schema_ref("Cosmetics"))]),
+        },
+        "/api/cosmetics": {
+            "get": operation("profile", "Card backs and table themes on offer", false, vec![],
+                None, &[("200", schema_ref("CosmeticsCatalog"))]),
+        },
+        "/api/titles": {
+            "get": operation("profile", "Every title players can earn", false, vec![], None,
+                &[("200", json!({ "type": "array", "items": schema_ref("Title") }))]),
+        },
+        "/api/me/titles": {
+            "get": operation("profile", "The caller's earned titles and the one they show",
+                true, vec![], None, &[("200", schema_ref("TitleProfile"))]),
+        },
+        "/api/me/title": {
+            "put": operation("profile", "Show one of the caller's earned titles, or none", true,
+                vec![], Some(schema_ref("SelectTitlePayload")),
+                &[("200", schema_ref("TitleProfile"))]),
+        },
+        "/api/users/{user_id}/titles": {
+            "get": operation("profile", "A player's earned titles and the one they show", false,
+                vec![user_id()], None, &[("200", schema_ref("TitleProfile"))]),
+        },
+        "/api/profile/avatar": {
+            "post": {
+                "tags": ["profile"],
+                "summary": "Set the caller's avatar (PNG or JPEG, at most 2 MB; scaled down to 256 KB and 512×512)",
+                "security": [{ "bearer": [] }],
+                "requestBody": {
+                    "required": true,
+                    "content": {
+                        "image/png": { "schema": { "type": "string", "format": "binary" } },
+                        "image/jpeg": { "schema": { "type": "string", "format": "binary" } },
+                    },
+                },
+                "responses": {
+                    "200": {
+                        "description": "Success",
+                        "content": {
+                            "application/json": { "schema": schema_ref("AvatarUploaded") },
+                        },
+                    },
+                    "default": { "$ref": "#/components/responses/Problem" },
+                },
+            },
+            "delete": operation("profile", "Remove the caller's avatar", true, vec![], None,
+                &[("204", Value::Null)]),
+        },
+        "/api/avatars/{user_id}": {
+            "get": {
+                "tags": ["profile"],
+                "summary": "A player's avatar image; cacheable for good under its versioned URL",
+                "parameters": [user_id()],
+                "responses": {
+                    "200": {
+                        "description": "The image",
+                        "content": {
+                            "image/png": { "schema": { "type": "string", "format": "binary" } },
+                            "image/jpeg": { "schema": { "type": "string", "format": "binary" } },
+                        },
+                    },
+                    "default": { "$ref": "#/components/responses/Problem" },
+                },
+            },
+        },
+        "/api/games/active": {
+            "get": operation("rooms", "Games the caller holds a seat in", true, vec![], None,
+                &[("200", json!({ "type": "array", "items": schema_ref("ActiveGame") }))]),
+        },
+        "/api/games/{id}/transcript": {
+            "get": operation("rooms", "Round-by-round record of a finished game", false,
+                vec![path_param("id", "Room id")], None, &[("200", schema_ref("GameTranscript"))]),
+        },
+        "/api/rooms/{id}/spectate": {
+            "post": operation("rooms", "Create a read-only spectate link", true,
+                vec![path_param("id", "Room id")], None, &[("200", schema_ref("SpectateLink"))]),
+        },
+        "/api/rooms": {
+            "get": operation("rooms", "Public tables being played, for the game browser", false,
+                vec![
+                    json!({ "name": "round", "in": "query", "schema": { "type": "integer" } }),
+                    json!({ "name": "players", "in": "query", "schema": { "type": "integer" } }),
+                    json!({ "name": "spectators_allowed", "in": "query",
+                        "schema": { "type": "boolean" } }),
+                    json!({ "name": "queue", "in": "query", "schema": { "type": "string" } }),
+                    json!({ "name": "friends", "in": "query",
+                        "description": "Comma-separated user ids, any of whom must be seated",
+                        "schema": { "type": "string" } }),
+                    json!({ "name": "sort", "in": "query", "schema": {
+                        "type": "string", "enum": ["newest", "round", "players", "spectators"],
+                    } }),
+                ],
+                None, &[("200", json!({ "type": "array", "items": schema_ref("RoomListing") }))]),
+        },
+        "/api/queues": {
+            "get": operation("rooms", "Matchmaking queues and their house rules", false, vec![],
+                None, &[("200", json!({ "type": "array", "items": { "type": "object" } }))]),
+        },
+        "/api/stats": {
+            "get": operation("stats", "Server-wide statistics, recomputed hourly", false, vec![],
+                None, &[("200", schema_ref("GlobalStats"))]),
+        },
+        "/api/head-to-head/{user_id}/{opponent_id}": {
+            "get": operation("stats", "Record between two players", false,
+                vec![user_id(), path_param("opponent_id", "The other player's account id")],
+                None, &[("200", schema_ref("HeadToHead"))]),
+        },
+        "/api/seasons": {
+            "get": operation("stats", "Ranked seasons, newest first", false, vec![], None,
+                &[("200", json!({ "type": "array", "items": schema_ref("Season") }))]),
+        },
+        "/api/seasons/{id}/leaderboard": {
+            "get": operation("stats", "A season's leaderboard", false,
+                vec![
+                    path_param("id", "Season id"),
+                    json!({ "name": "limit", "in": "query", "schema": { "type": "integer" } }),
+                ],
+                None, &[("200", schema_ref("LeaderboardResponse"))]),
+        },
+    });
+    let paths_map = paths.as_object_mut().expect("paths is an object");
+    for more in [community_paths(), notification_paths(), admin_paths()] {
+        let Value::Object(more) = more else {
+            unreachable!("path groups are objects")
+        };
+        paths_map.extend(more);
+    }
+    paths
+}
+
+/// Tournaments, leagues and scheduled games.
+fn community_paths() -> Value {
+    let id = |what: &str| path_param("id", what);
+    let objects = || json!({ "type": "array", "items": { "type": "object" } });
+    json!({
+        "/api/tournaments": {
+            "get": operation("community", "Tournaments, newest first", false, vec![], None,
+                &[("200", objects())]),
+            "post": operation("community", "Create a tournament (moderators)", true, vec![],
+                Some(schema_ref("NamePayload")), &[("201", json!({ "type": "object" }))]),
+        },
+        "/api/tournaments/{id}": {
+            "get": operation("community", "A tournament with its players and bracket", false,
+                vec![id("Tournament id")], None, &[("200", json!({ "type": "object" }))]),
+        },
+        "/api/tournaments/{id}/register": {
+            "post": operation("community", "Enter the caller in a tournament", true,
+                vec![id("Tournament id")], None, &[("204", Value::Null)]),
+            "delete": operation("community", "Withdraw the caller from a tournament", true,
+                vec![id("Tournament id")], None, &[("204", Value::Null)]),
+        },
+        "/api/tournaments/{id}/start": {
+            "post": operation("community", "Close registration and draw the bracket (moderators)",
+                true, vec![id("Tournament id")], None, &[("200", json!({ "type": "object" }))]),
+        },
+        "/api/leagues": {
+            "get": operation("community", "Leagues, newest first", false, vec![], None,
+                &[("200", objects())]),
+            "post": operation("community", "Create a round-robin league (moderators)", true,
+                vec![], Some(schema_ref("CreateLeaguePayload")),
+                &[("201", json!({ "type": "object" }))]),
+        },
+        "/api/leagues/{id}": {
+            "get": operation("community", "A league with its players and fixtures", false,
+                vec![id("League id")], None, &[("200", json!({ "type": "object" }))]),
+        },
+        "/api/leagues/{id}/standings": {
+            "get": operation("community", "A league's table and the fixtures due next", false,
+                vec![id("League id")], None, &[("200", json!({ "type": "object" }))]),
+        },
+        "/api/leagues/{id}/fixtures/{fixture_id}/play": {
+            "post": operation("community", "Open (or rejoin) the room for one of the caller's \
+                fixtures", true,
+                vec![id("League id"), path_param("fixture_id", "Fixture id")], None,
+                &[("200", schema_ref("FixtureRoom"))]),
+        },
+        "/api/scheduled-games": {
+            "get": operation("community", "Upcoming games the caller may join", true, vec![],
+                None, &[("200", objects())]),
+            "post": operation("community", "Schedule a game", true, vec![],
+                Some(schema_ref("ScheduleGamePayload")), &[("201", json!({ "type": "object" }))]),
+        },
+        "/api/scheduled-games/{id}": {
+            "get": operation("community", "A scheduled game with its RSVPs", false,
+                vec![id("Scheduled game id")], None, &[("200", json!({ "type": "object" }))]),
+        },
+        "/api/scheduled-games/{id}/rsvp": {
+            "post": operation("community", "RSVP the caller", true,
+                vec![id("Scheduled game id")], None, &[("204", Value::Null)]),
+            "delete": operation("community", "Withdraw the caller's RSVP", true,
+                vec![id("Scheduled game id")], None, &[("204", Value::Null)]),
+        },
+        "/api/scheduled-games/{id}/invite-links": {
+            "post": operation("community", "Create a single-use invite link (host only)", true,
+                vec![id("Scheduled game id")], None, &[("201", schema_ref("InviteLink"))]),
+        },
+        "/api/invites/{token}": {
+            "post": operation("community", "Redeem an invite link, RSVPing the caller", true,
+                vec![path_param("token", "Token from the invite link")], None,
+                &[("200", json!({ "type": "object" }))]),
+        },
+    })
+}
+
+/// Webhooks, web push and server-sent spectating.
+fn notification_paths() -> Value {
+    json!({
+        "/api/me/webhook": {
+            "get": operation("notifications", "The caller's webhook", true, vec![], None,
+                &[("200", schema_ref("Webhook"))]),
+            "put": operation("notifications", "Register or replace the caller's webhook", true,
+                vec![], Some(schema_ref("SetWebhookPayload")), &[("200", schema_ref("Webhook"))]),
+            "delete": operation("notifications", "Remove the caller's webhook", true, vec![],
+                None, &[("204", Value::Null)]),
+        },
+        "/api/push/vapid-key": {
+            "get": operation("notifications", "The key browsers subscribe to web push with",
+                false, vec![], None, &[("200", object_of("public_key"))]),
+        },
+        "/api/me/push/subscriptions": {
+            "post": operation("notifications", "Subscribe a browser to the caller's pushes",
+                true, vec![], Some(schema_ref("PushSubscriptionPayload")),
+                &[("204", Value::Null)]),
+            "delete": operation("notifications", "Unsubscribe a browser", true, vec![],
+                Some(schema_ref("PushSubscriptionPayload")), &[("204", Value::Null)]),
+        },
+        "/api/me/push/pending": {
+            "get": operation("notifications", "What the caller's last pushes were about", true,
+                vec![], None,
+                &[("200", json!({ "type": "array", "items": { "type": "object" } }))]),
+        },
+        "/api/spectate/events": {
+            "get": {
+                "tags": ["rooms"],
+                "summary": "Spectate a room over server-sent events; each event is a \
+                    `ServerMessage`",
+                "parameters": [json!({ "name": "token", "in": "query", "required": true,
+                    "description": "Token from a spectate link",
+                    "schema": { "type": "string" } })],
+                "responses": {
+                    "200": {
+                        "description": "The event stream",
+                        "content": { "text/event-stream": { "schema": { "type": "string" } } },
+                    },
+                    "default": { "$ref": "#/components/responses/Problem" },
+                },
+            },
+        },
+        "/api/avatars/bots/{index}": {
+            "get": {
+                "tags": ["profile"],
+                "summary": "A bot's generated avatar",
+                "parameters": [path_param("index", "Bot name index")],
+                "responses": {
+                    "200": {
+                        "description": "The image",
+                        "content": { "image/svg+xml": { "schema": { "type": "string" } } },
+                    },
+                    "default": { "$ref": "#/components/responses/Problem" },
+                },
+            },
+        },
+    })
+}
+
+/// Moderation (moderators and admins) and server administration (admins only).
+fn admin_paths() -> Value {
+    let objects = || json!({ "type": "array", "items": { "type": "object" } });
+    let status = || {
+        json!({ "name": "status", "in": "query", "description": "Only entries in this state",
+            "schema": { "type": "string" } })
+    };
+    let user = || path_param("id", "Account id");
+    json!({
+        "/api/admin/reports": {
+            "get": operation("admin", "Player reports", true, vec![status()], None,
+                &[("200", objects())]),
+        },
+        "/api/admin/reports/{id}": {
+            "get": operation("admin", "A report with the game's audit log and raw chat", true,
+                vec![path_param("id", "Report id")], None, &[("200", json!({ "type": "object" }))]),
+        },
+        "/api/admin/reports/{id}/resolve": {
+            "post": operation("admin", "Close a report", true,
+                vec![path_param("id", "Report id")], Some(schema_ref("ResolvePayload")),
+                &[("204", Value::Null)]),
+        },
+        "/api/admin/collusion-flags": {
+            "get": operation("admin", "Pairs of players flagged for soft play", true,
+                vec![status()], None, &[("200", objects())]),
+        },
+        "/api/admin/collusion-flags/{id}/resolve": {
+            "post": operation("admin", "Close a collusion flag", true,
+                vec![path_param("id", "Flag id")], Some(schema_ref("ResolvePayload")),
+                &[("204", Value::Null)]),
+        },
+        "/api/admin/usernames/{username}": {
+            "get": operation("admin", "The account that uses or used a name", true,
+                vec![path_param("username", "Current or former username")], None,
+                &[("200", json!({ "type": "object" }))]),
+        },
+        "/api/admin/users/{id}/sanctions": {
+            "post": operation("admin", "Mute or ban a player", true, vec![user()],
+                Some(schema_ref("SanctionPayload")), &[("201", json!({ "type": "object" }))]),
+        },
+        "/api/admin/users/{id}/sanctions/{kind}": {
+            "delete": operation("admin", "Lift a mute or ban", true,
+                vec![user(), path_param("kind", "`mute` or `ban`")], None,
+                &[("204", Value::Null)]),
+        },
+        "/api/admin/users/{id}/role": {
+            "put": operation("admin", "Change a player's role (admins)", true, vec![user()],
+                Some(schema_ref("SetRolePayload")), &[("204", Value::Null)]),
+        },
+        "/api/admin/jobs": {
+            "get": operation("admin", "Background jobs and when they last ran (admins)", true,
+                vec![], None, &[("200", objects())]),
+        },
+        "/api/admin/capacity": {
+            "get": operation("admin", "Connection and room usage against the caps (admins)",
+                true, vec![], None, &[("200", json!({ "type": "object" }))]),
+        },
+        "/api/admin/rate-limits": {
+            "get": operation("admin", "Per-IP rate limits and how often they applied (admins)",
+                true, vec![], None, &[("200", json!({ "type": "object" }))]),
+        },
+        "/api/admin/audit": {
+            "get": operation("admin", "Audit log writer backlog and dropped entries (admins)",
+                true, vec![], None, &[("200", json!({ "type": "object" }))]),
+        },
+        "/api/admin/action-latency": {
+            "get": operation("admin", "How long rooms take to handle actions (admins)", true,
+                vec![], None, &[("200", json!({ "type": "object" }))]),
+        },
+        "/api/admin/bots": {
+            "get": operation("admin", "How each bot difficulty fares against humans (admins)",
+                true,
+                vec![json!({ "name": "since", "in": "query", "description": "Epoch seconds",
+                    "schema": { "type": "integer" } })],
+                None, &[("200", objects())]),
+        },
+        "/api/admin/replay-flags": {
+            "get": operation("admin", "Finished games whose replay check failed (admins)", true,
+                vec![], None, &[("200", objects())]),
+        },
+        "/api/admin/games/{room_id}/shuffle-seeds": {
+            "get": operation("admin", "A game's shuffle seeds, checked against its deals \
+                (admins)", true, vec![path_param("room_id", "Room id")], None,
+                &[("200", json!({ "type": "object" }))]),
+        },
+        "/api/admin/maintenance": {
+            "get": operation("admin", "Whether the server is in maintenance (admins)", true,
+                vec![], None, &[("200", json!({ "type": "object" }))]),
+            "put": operation("admin", "Start or end maintenance (admins)", true, vec![],
+                Some(schema_ref("MaintenancePayload")), &[("200", json!({ "type": "object" }))]),
+        },
+        "/api/admin/room-snapshots": {
+            "get": operation("admin", "Games saved when maintenance closed their rooms (admins)",
+                true, vec![], None, &[("200", objects())]),
+        },
+    })
+}
+
+/// An object holding one required string property.
+fn object_of(property: &str) -> Value {
+    json!({
+        "type": "object",
+        "required": [property],
+        "properties": { property: { "type": "string" } },
+    })
+}
+
+fn schemas() -> Value {
+    let string = || json!({ "type": "string" });
+    let integer = || json!({ "type": "integer" });
+    let number = || json!({ "type": "number" });
+    let nullable = |kind: &str| json!({ "type": [kind, "null"] });
+    let array_of = |item: Value| json!({ "type": "array", "items": item });
+    let object = |required: &[&str], properties: Value| {
+        let mut schema = json!({ "type": "object", "required": required });
+        schema["properties"] = properties;
+        schema
+    };
+
+    let mut schemas = json!({
+        "Problem": object(&["title", "status", "code", "message"], json!({
+            "title": string(),
+            "status": integer(),
+            "code": { "type": "string", "description": "Same keys as the WebSocket `Error`" },
+            "message": string(),
+            "details": { "type": "object" },
+            "request_id": string(),
+        })),
+        "AuthPayload": object(&["username", "password"], json!({
+            "username": { "type": "string", "maxLength": 32 },
+            "password": { "type": "string", "maxLength": 128 },
+        })),
+        "AuthResponse": object(&["token", "user_id", "role"], json!({
+            "token": string(),
+            "user_id": string(),
+            "role": { "type": "string", "enum": ["player", "moderator", "admin"] },
+        })),
+        "RenamePayload": object(&["username"], json!({
+            "username": { "type": "string", "maxLength": 32 },
+        })),
+        "RenameResponse": object(&["username", "next_rename_at"], json!({
+            "username": string(),
+            "next_rename_at": { "type": "integer", "description": "Epoch seconds" },
+        })),
+        "BotLevel": object(&["level", "streak", "bots"], json!({
+            "level": { "type": "integer", "minimum": 0, "maximum": 6 },
+            "streak": { "type": "integer", "description": "Wins positive, losses negative" },
+            "bots": array_of(json!({ "type": "string", "enum": ["Easy", "Medium", "Hard"] })),
+        })),
+        "Cosmetics": object(&["card_back", "table_theme"], json!({
+            "card_back": string(),
+            "table_theme": string(),
+        })),
+        "SetCosmeticsPayload": object(&[], json!({
+            "card_back": string(),
+            "table_theme": string(),
+        })),
+        "CosmeticsCatalog": object(&["card_backs", "table_themes"], json!({
+            "card_backs": array_of(string()),
+            "table_themes": array_of(string()),
+        })),
+        "Title": object(&["id", "name", "description"], json!({
+            "id": string(),
+            "name": string(),
+            "description": string(),
+        })),
+        "PlayerTitle": object(&["id", "name"], json!({
+            "id": string(),
+            "name": string(),
+        })),
+        "TitleProfile": object(&["selected", "earned"], json!({
+            "selected": { "oneOf": [schema_ref("PlayerTitle"), { "type": "null" }] },
+            "earned": array_of(object(&["title_id", "earned_at"], json!({
+                "title_id": string(),
+                "earned_at": { "type": "integer", "description": "Epoch seconds" },
+            }))),
+        })),
+        "SelectTitlePayload": object(&["title_id"], json!({
+            "title_id": nullable("string"),
+        })),
+        "AvatarUploaded": object(&["url"], json!({ "url": string() })),
+        "ActiveGame": object(
+            &["room_id", "players", "round_index", "round_name", "reconnect_token",
+              "reconnect_expires_at"],
+            json!({
+                "room_id": string(),
+                "players": array_of(string()),
+                "round_index": integer(),
+                "round_name": string(),
+                "current_turn": nullable("string"),
+                "reconnect_token": { "type": "string", "description": "Pass as `rejoin` to `/ws`" },
+                "reconnect_expires_at": { "type": "integer", "description": "Epoch seconds" },
+            }),
+        ),
+        "RoomListing": object(
+            &["room_id", "players", "round_index", "round_name", "queue", "spectators",
+              "spectators_allowed", "is_game_over", "listed_at_ms"],
+            json!({
+                "room_id": string(),
+                "players": array_of(string()),
+                "round_index": integer(),
+                "round_name": string(),
+                "queue": string(),
+                "spectators": integer(),
+                "spectators_allowed": { "type": "boolean" },
+                "is_game_over": { "type": "boolean" },
+                "listed_at_ms": { "type": "integer", "description": "Epoch milliseconds" },
+            }),
+        ),
+        "GameResult": object(
+            &["room_id", "outcome", "counted", "ranked", "rounds_played", "finished_at"],
+            json!({
+                "room_id": string(),
+                "outcome": { "type": "string", "enum": ["completed", "abandoned"] },
+                "counted": { "type": "boolean" },
+                "ranked": { "type": "boolean" },
+                "rounds_played": integer(),
+                "finished_at": integer(),
+            }),
+        ),
+        "GameResultPlayer": object(&["room_id", "user_id", "seat", "total"], json!({
+            "room_id": string(),
+            "user_id": string(),
+            "seat": integer(),
+            "total": integer(),
+        })),
+        "GameTranscript": object(&["result", "players", "rounds"], json!({
+            "result": schema_ref("GameResult"),
+            "players": array_of(schema_ref("GameResultPlayer")),
+            "rounds": array_of(json!({ "type": "object" })),
+        })),
+        "SpectateLink": object(&["token", "path", "expires_at"], json!({
+            "token": string(),
+            "path": { "type": "string", "description": "WebSocket path to connect to" },
+            "expires_at": integer(),
+        })),
+        "GlobalStats": object(
+            &["computed_at", "window_days", "games", "games_per_day", "round_types", "jokers"],
+            json!({
+                "computed_at": integer(),
+                "window_days": integer(),
+                "games": integer(),
+                "games_per_day": array_of(object(&["day_start", "games"], json!({
+                    "day_start": integer(),
+                    "games": integer(),
+                }))),
+                "average_round_turns": nullable("number"),
+                "round_types": array_of(object(
+                    &["round_index", "round_name", "rounds", "bajada_rate", "average_turns"],
+                    json!({
+                        "round_index": integer(),
+                        "round_name": string(),
+                        "rounds": integer(),
+                        "bajada_rate": number(),
+                        "average_turns": number(),
+                    }),
+                )),
+                "jokers": object(
+                    &["bajadas", "bajadas_with_jokers", "jokers_played", "jokers_per_game"],
+                    json!({
+                        "bajadas": integer(),
+                        "bajadas_with_jokers": integer(),
+                        "jokers_played": integer(),
+                        "jokers_per_game": number(),
+                    }),
+                ),
+            }),
+        ),
+        "HeadToHead": object(
+            &["opponent_id", "games", "wins", "losses", "draws", "average_margin", "recent"],
+            json!({
+                "opponent_id": string(),
+                "games": integer(),
+                "wins": integer(),
+                "losses": integer(),
+                "draws": integer(),
+                "average_margin": number(),
+                "recent": array_of(object(
+                    &["room_id", "finished_at", "total", "opponent_total"],
+                    json!({
+                        "room_id": string(),
+                        "finished_at": integer(),
+                        "total": integer(),
+                        "opponent_total": integer(),
+                    }),
+                )),
+            }),
+        ),
+        "Season": object(&["id", "starts_at", "ends_at"], json!({
+            "id": integer(),
+            "starts_at": integer(),
+            "ends_at": integer(),
+            "closed_at": nullable("integer"),
+        })),
+        "LeaderboardResponse": object(&["season", "entries"], json!({
+            "season": schema_ref("Season"),
+            "entries": array_of(object(&["rank", "user_id", "username", "rating", "games"], json!({
+                "rank": integer(),
+                "user_id": string(),
+                "username": string(),
+                "rating": integer(),
+                "games": integer(),
+            }))),
+        })),
+    });
+    let Value::Object(payloads) = payload_schemas() else {
+        unreachable!("schema groups are objects")
+    };
+    schemas
+        .as_object_mut()
+        .expect("schemas is an object")
+        .extend(payloads);
+    schemas
+}
+
+/// Bodies of the community, notification and admin endpoints.
+fn payload_schemas() -> Value {
+    let string = || json!({ "type": "string" });
+    let integer = || json!({ "type": "integer" });
+    let object = |required: &[&str], properties: Value| {
+        let mut schema = json!({ "type": "object", "required": required });
+        schema["properties"] = properties;
+        schema
+    };
+
+    json!({
+        "NamePayload": object(&["name"], json!({
+            "name": { "type": "string", "maxLength": 64 },
+        })),
+        "CreateLeaguePayload": object(&["name", "usernames"], json!({
+            "name": string(),
+            "usernames": { "type": "array", "items": string(), "minItems": 2, "maxItems": 20 },
+        })),
+        "FixtureRoom": object(&["room_id", "reconnect_token"], json!({
+            "room_id": string(),
+            "reconnect_token": { "type": "string", "description": "Pass as `rejoin` to `/ws`" },
+        })),
+        "ScheduleGamePayload": object(&["name", "starts_at"], json!({
+            "name": string(),
+            "starts_at": { "type": "integer", "description": "Epoch seconds" },
+            "max_players": { "type": "integer", "minimum": 2, "maximum": 4 },
+            "public": { "type": "boolean" },
+            "invite": { "type": "array", "items": string(), "description": "Usernames" },
+        })),
+        "InviteLink": object(&["token", "redeem_path", "expires_at"], json!({
+            "token": string(),
+            "redeem_path": string(),
+            "expires_at": { "type": "integer", "description": "Epoch seconds" },
+        })),
+        "Webhook": object(&["user_id", "url", "secret", "created_at"], json!({
+            "user_id": string(),
+            "url": string(),
+            "secret": { "type": "string", "description": "Sent back as `X-Carioca-Secret`" },
+            "created_at": integer(),
+        })),
+        "SetWebhookPayload": object(&["url"], json!({ "url": string() })),
+        "PushSubscriptionPayload": object(&["endpoint"], json!({
+            "endpoint": { "type": "string", "description": "An `https://` push service URL" },
+        })),
+        "ResolvePayload": object(&["resolution"], json!({ "resolution": string() })),
+        "SanctionPayload": object(&["kind", "reason"], json!({
+            "kind": { "type": "string", "enum": ["mute", "ban"] },
+            "reason": string(),
+            "duration_secs": { "type": "integer", "description": "Permanent when left out" },
+        })),
+        "SetRolePayload": object(&["role"], json!({
+            "role": { "type": "string", "enum": ["player", "moderator", "admin"] },
+        })),
+        "MaintenancePayload": object(&["enabled"], json!({
+            "enabled": { "type": "boolean" },
+            "message": string(),
+            "drain_seconds": integer(),
+        })),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn collect_refs<'a>(value: &'a Value, refs: &mut Vec<&'a str>) {
+        match value {
+            Value::Object(map) => {
+                if let Some(Value::String(target)) = map.get("$ref") {
+                    refs.push(target);
+                }
+                map.values().for_each(|v| collect_refs(v, refs));
+            }
+            Value::Array(items) => items.iter().for_each(|v| collect_refs(v, refs)),
+            _ => {}
+        }
+    }
+
+    #[test]
+    fn every_reference_resolves() {
+        let doc = document();
+        let mut refs = Vec::new();
+        collect_refs(&doc, &mut refs);
+        assert!(!refs.is_empty());
+        for target in refs {
+            let pointer = target.strip_prefix('#').expect("Only local references");
+            assert!(doc.pointer(pointer).is_some(), "{} is dangling", target);
+        }
+    }
+
+    /// Served on purpose without an entry: infrastructure, the socket and developer tools.
+    const UNDOCUMENTED: &[&str] = &[
+        "/health",
+        "/api/openapi.json",
+        "/api/docs",
+        "/ws",
+        "/api/dev/rooms",
+    ];
+
+    /// Every `(path, method)` `build_router` serves, read from its source.
+    fn served_routes() -> Vec<(String, String)> {
+        let source = include_str!("server.rs");
+        let start = source.find("fn build_router").expect("build_router exists");
+        let end = start + source[start..].find("\n}\n").expect("build_router ends");
+        let mut routes = Vec::new();
+        let mut rest = &source[start..end];
+        while let Some(at) = rest.find(".route(") {
+            rest = &rest[at + ".route(".len()..];
+            let mut depth = 1;
+            let close = rest
+                .char_indices()
+                .find(|&(_, c)| {
+                    depth += match c {
+                        '(' => 1,
+                        ')' => -1,
+                        _ => 0,
+                    };
+                    depth == 0
+                })
+                .expect("route call closes")
+                .0;
+            let call = &rest[..close];
+            let path = call.split('"').nth(1).expect("route path literal");
+            for method in ["get", "post", "put", "delete", "patch"] {
+                let found = call.match_indices(&format!("{}(", method)).any(|(i, _)| {
+                    !call[..i].ends_with(|c: char| c.is_alphanumeric() || c == '_' || c == ':')
+                });
+                if found {
+                    routes.push((path.to_string(), method.to_string()));
+                }
+            }
+            rest = &rest[close..];
+        }
+        routes
+    }
+
+    #[test]
+    fn every_served_route_is_documented() {
+        let doc = document();
+        let routes = served_routes();
+        assert!(routes.len() > 40, "read too few routes: {:?}", routes);
+        let missing: Vec<String> = routes
+            .iter()
+            .filter(|(path, _)| !UNDOCUMENTED.contains(&path.as_str()))
+            .filter(|(path, method)| !doc["paths"][path][method].is_object())
+            .map(|(path, method)| format!("{} {}", method.to_uppercase(), path))
+            .collect();
+        assert!(missing.is_empty(), "Missing from the spec: {:#?}", missing);
+
+        for (path, methods) in doc["paths"].as_object().unwrap() {
+            for method in methods.as_object().unwrap().keys() {
+                assert!(
+                    routes.iter().any(|(p, m)| p == path && m == method),
+                    "{} {} is documented but not served",
+                    method.to_uppercase(),
+                    path
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn documents_the_main_areas() {
+        let doc = document();
+        for path in [
+            "/api/auth/login",
+            "/api/me/username",
+            "/api/games/active",
+            "/api/stats",
+        ] {
+            assert!(doc["paths"][path].is_object(), "{} is missing", path);
+        }
+        let login = &doc["paths"]["/api/auth/login"]["post"];
+        assert_eq!(
+            login["responses"]["200"]["content"]["application/json"]["schema"]["$ref"],
+            "#/components/schemas/AuthResponse"
+        );
+    }
+}
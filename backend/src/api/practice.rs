@@ -0,0 +1,28 @@
+use axum::{Json, extract::State, response::IntoResponse};
+use serde::Serialize;
+use std::sync::Arc;
+
+use crate::api::authz::AuthUser;
+use crate::api::server::AppState;
+use crate::engine::bot::BotDifficulty;
+use crate::matchmaking::adaptive;
+
+#[derive(Serialize)]
+pub struct BotLevelResponse {
+    /// Rung on the practice ladder, 0 (all easy) to 6 (all hard)
+    pub level: i64,
+    /// Consecutive practice wins (positive) or losses (negative) at this level
+    pub streak: i64,
+    /// Who the next instant game without `bots` is played against
+    pub bots: Vec<BotDifficulty>,
+}
+
+/// The caller's adaptive practice level.
+pub async fn bot_level(State(state): State<Arc<AppState>>, user: AuthUser) -> impl IntoResponse {
+    let level = adaptive::load(&state.db, &user.user_id).await;
+    Json(BotLevelResponse {
+        level: level.level,
+        streak: level.streak,
+        bots: level.bots(),
+    })
+}
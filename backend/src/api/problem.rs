@@ -0,0 +1,258 @@
+use axum::{
+    body::{Body, to_bytes},
+    extract::Request,
+    http::{HeaderValue, StatusCode, header},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use serde::Serialize;
+use serde_json::{Value, json};
+
+use crate::api::i18n::{self, Locale};
+use crate::api::request_id::RequestId;
+use crate::api::validation::ValidationError;
+
+pub const PROBLEM_CONTENT_TYPE: &str = "application/problem+json";
+
+/// Plain-text error bodies longer than this are cut off rather than buffered whole.
+const MAX_PLAIN_ERROR_BYTES: usize = 4 * 1024;
+
+/// An error response body in the style of RFC 9457 (`application/problem+json`). `code` is
+/// the same message key the WebSocket layer puts in `ServerMessage::Error`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Problem {
+    /// The status' reason phrase, e.g. "Not Found"
+    pub title: String,
+    pub status: u16,
+    pub code: String,
+    /// English, like the rest of the REST API
+    pub message: String,
+    /// Extra machine-readable context, such as the limit that was exceeded
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+}
+
+impl Problem {
+    /// An uncatalogued error, coded after its status: `not_found`, `conflict` and so on.
+    pub fn new(status: StatusCode, message: &str) -> Self {
+        Self {
+            title: status.canonical_reason().unwrap_or("Error").to_string(),
+            status: status.as_u16(),
+            code: status_code_key(status),
+            message: message.to_string(),
+            details: None,
+            request_id: None,
+        }
+    }
+
+    /// The error under catalog `key`, with its English text as the message.
+    pub fn keyed(status: StatusCode, key: &str) -> Self {
+        match i18n::localize_key(key, Locale::En, None) {
+            Some(localized) => Self {
+                code: localized.key.to_string(),
+                ..Self::new(status, &localized.text)
+            },
+            None => Self::new(status, key),
+        }
+    }
+}
+
+/// Rejected input is a 400 naming the failed check.
+impl From<ValidationError> for Problem {
+    fn from(e: ValidationError) -> Self {
+        Self {
+            code: e.code.to_string(),
+            message: e.message,
+            details: e.limit.map(|limit| json!({ "limit": limit })),
+            ..Self::new(StatusCode::BAD_REQUEST, "")
+        }
+    }
+}
+
+impl IntoResponse for Problem {
+    fn into_response(self) -> Response {
+        let status = StatusCode::from_u16(self.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+        let body = serde_json::to_vec(&self).unwrap_or_default();
+        (
+            status,
+            [(
+                header::CONTENT_TYPE,
+                HeaderValue::from_static(PROBLEM_CONTENT_TYPE),
+            )],
+            body,
+        )
+            .into_response()
+    }
+}
+
+/// `not_found`, `too_many_requests` and so on, for errors without a catalog entry.
+fn status_code_key(status: StatusCode) -> String {
+    status
+        .canonical_reason()
+        .unwrap_or("error")
+        .to_ascii_lowercase()
+        .replace(['-', '\''], "")
+        .replace(' ', "_")
+}
+
+/// Rewrites plain-text and empty error responses, such as axum's own extractor and router
+/// rejections, into problem documents coded after their status; errors with a catalog entry
+/// are built as a [`Problem::keyed`] instead. Every problem is stamped with the request id,
+/// and other responses pass through untouched.
+pub async fn render(req: Request, next: Next) -> Response {
+    let request_id = req.extensions().get::<RequestId>().map(|id| id.0.clone());
+    let response = next.run(req).await;
+    let status = response.status();
+    if !status.is_client_error() && !status.is_server_error() {
+        return response;
+    }
+
+    let content_type = response.headers().get(header::CONTENT_TYPE);
+    if content_type.is_some_and(|value| value == PROBLEM_CONTENT_TYPE) {
+        return stamp_request_id(response, request_id).await;
+    }
+    let plain = match content_type {
+        None => true,
+        Some(content_type) => content_type.as_bytes().starts_with(b"text/plain"),
+    };
+    if !plain {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let text = to_bytes(body, MAX_PLAIN_ERROR_BYTES)
+        .await
+        .unwrap_or_default();
+    let text = String::from_utf8_lossy(&text);
+    let message = match text.trim() {
+        "" => status.canonical_reason().unwrap_or("Error"),
+        text => text,
+    };
+    let problem = Problem {
+        request_id,
+        ..Problem::new(status, message)
+    };
+
+    let rendered = problem.into_response();
+    parts.headers.remove(header::CONTENT_LENGTH);
+    parts.headers.insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static(PROBLEM_CONTENT_TYPE),
+    );
+    Response::from_parts(parts, Body::new(rendered.into_body()))
+}
+
+/// Adds the request id to a problem a handler built itself.
+async fn stamp_request_id(response: Response, request_id: Option<String>) -> Response {
+    let Some(request_id) = request_id else {
+        return response;
+    };
+    let (mut parts, body) = response.into_parts();
+    let bytes = to_bytes(body, usize::MAX).await.unwrap_or_default();
+    let body = match serde_json::from_slice::<Value>(&bytes) {
+        Ok(Value::Object(mut problem)) => {
+            problem
+                .entry("request_id")
+                .or_insert(Value::String(request_id));
+            parts.headers.remove(header::CONTENT_LENGTH);
+            serde_json::to_vec(&problem).unwrap_or_default()
+        }
+        _ => bytes.to_vec(),
+    };
+    Response::from_parts(parts, Body::from(body))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shares_codes_with_the_websocket_catalog() {
+        let problem = Problem::keyed(StatusCode::UNAUTHORIZED, "invalid_credentials");
+        assert_eq!(problem.code, "invalid_credentials");
+        assert_eq!(problem.message, "Invalid credentials");
+        assert_eq!(problem.title, "Unauthorized");
+
+        let problem = Problem::new(StatusCode::UNAUTHORIZED, "Invalid credentials");
+        assert_eq!(
+            problem.code, "unauthorized",
+            "Codes never come from the text"
+        );
+
+        let problem = Problem::new(StatusCode::NOT_FOUND, "League not found");
+        assert_eq!(
+            problem.code, "not_found",
+            "Uncatalogued messages fall back to the status"
+        );
+        assert_eq!(problem.message, "League not found");
+        assert_eq!(
+            status_code_key(StatusCode::UNPROCESSABLE_ENTITY),
+            "unprocessable_entity"
+        );
+    }
+
+    #[test]
+    fn validation_errors_keep_their_code_and_limit() {
+        let e = crate::api::validation::validate_credentials(&"x".repeat(100), "").unwrap_err();
+        let problem = Problem::from(e);
+        assert_eq!(
+            (problem.status, problem.code.as_str()),
+            (400, "username_too_long")
+        );
+        assert_eq!(problem.details, Some(json!({ "limit": 32 })));
+    }
+
+    #[test]
+    fn handlers_answer_with_problems_under_catalog_keys() {
+        let handlers = [
+            include_str!("admin.rs"),
+            include_str!("auth.rs"),
+            include_str!("authz.rs"),
+            include_str!("avatars.rs"),
+            include_str!("cosmetics.rs"),
+            include_str!("dev.rs"),
+            include_str!("games.rs"),
+            include_str!("leagues.rs"),
+            include_str!("push.rs"),
+            include_str!("rate_limit.rs"),
+            include_str!("scheduled_games.rs"),
+            include_str!("seasons.rs"),
+            include_str!("spectate.rs"),
+            include_str!("stats.rs"),
+            include_str!("titles.rs"),
+            include_str!("tournaments.rs"),
+            include_str!("usernames.rs"),
+            include_str!("webhooks.rs"),
+            include_str!("ws.rs"),
+        ];
+        let mut keyed = 0;
+        for source in handlers {
+            let code = source.split("#[cfg(test)]").next().unwrap();
+            let compact = code.replace(char::is_whitespace, "");
+            for (at, _) in compact.match_indices("(StatusCode::") {
+                let before = &compact[..at];
+                if before.ends_with("Problem::new") || before.ends_with("Problem::keyed") {
+                    continue;
+                }
+                let (_, body) = compact[at..].split_once(',').unwrap();
+                assert!(
+                    !body.starts_with('"') && !body.starts_with("format!"),
+                    "Plain-text error: {}",
+                    &compact[at..(at + 60).min(compact.len())]
+                );
+            }
+            for call in code.split("Problem::keyed(").skip(1) {
+                let key = call.split('"').nth(1).unwrap();
+                assert!(
+                    i18n::localize_key(key, Locale::En, None).is_some(),
+                    "{}",
+                    key
+                );
+                keyed += 1;
+            }
+        }
+        assert!(keyed >= 15, "Found only {} keyed errors", keyed);
+    }
+}
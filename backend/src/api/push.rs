@@ -0,0 +1,83 @@
+use axum::{Json, extract::State, http::StatusCode, response::IntoResponse};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::api::authz::AuthUser;
+use crate::api::problem::Problem;
+use crate::api::server::AppState;
+use crate::db::repo;
+use crate::notify::http::HttpUrl;
+
+#[derive(Serialize)]
+pub struct VapidKeyResponse {
+    pub public_key: String,
+}
+
+/// A browser `PushSubscription`, as returned by its `toJSON()`. Only the endpoint is kept:
+/// pushes carry no payload, so the encryption keys are never needed.
+#[derive(Deserialize)]
+pub struct PushSubscriptionPayload {
+    pub endpoint: String,
+}
+
+/// The application server key browsers subscribe with.
+pub async fn vapid_key(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    match state.notifier.vapid_public_key() {
+        Some(key) => Json(VapidKeyResponse {
+            public_key: key.to_string(),
+        })
+        .into_response(),
+        None => Problem::new(StatusCode::NOT_FOUND, "Web push is not enabled").into_response(),
+    }
+}
+
+pub async fn subscribe(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Json(payload): Json<PushSubscriptionPayload>,
+) -> impl IntoResponse {
+    match HttpUrl::parse(&payload.endpoint) {
+        Ok(url) if url.tls => {}
+        Ok(_) => {
+            return Problem::new(StatusCode::BAD_REQUEST, "Push endpoints must use https://")
+                .into_response();
+        }
+        Err(e) => return Problem::new(StatusCode::BAD_REQUEST, e).into_response(),
+    }
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64;
+    match repo::upsert_push_subscription(&state.db, &user.user_id, &payload.endpoint, now).await {
+        Ok(()) => StatusCode::NO_CONTENT.into_response(),
+        Err(_) => Problem::new(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Failed to save subscription",
+        )
+        .into_response(),
+    }
+}
+
+pub async fn unsubscribe(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Json(payload): Json<PushSubscriptionPayload>,
+) -> impl IntoResponse {
+    match repo::delete_user_push_subscription(&state.db, &user.user_id, &payload.endpoint).await {
+        Ok(true) => StatusCode::NO_CONTENT.into_response(),
+        Ok(false) => Problem::new(StatusCode::NOT_FOUND, "Subscription not found").into_response(),
+        Err(_) => Problem::new(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Failed to delete subscription",
+        )
+        .into_response(),
+    }
+}
+
+/// What the caller's pushes were about, for the service worker to show; each event is
+/// returned once.
+pub async fn pending(State(state): State<Arc<AppState>>, user: AuthUser) -> impl IntoResponse {
+    Json(state.notifier.take_pending_pushes(&user.user_id))
+}
@@ -0,0 +1,34 @@
+use axum::{Json, extract::State, response::IntoResponse};
+use serde::Serialize;
+use std::sync::Arc;
+
+use crate::api::server::AppState;
+use crate::engine::rule_set::RuleSet;
+use crate::matchmaking::queues::PRESETS;
+
+#[derive(Serialize)]
+pub struct QueueInfo {
+    pub id: &'static str,
+    pub name: &'static str,
+    pub description: &'static str,
+    /// The rules a table formed in this queue plays by
+    pub rules: RuleSet,
+    /// Players waiting for a table right now
+    pub waiting: usize,
+}
+
+/// The matchmaking queues, for picking one with `/ws?queue=<id>`.
+pub async fn list_queues(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    let waiting = state.lobby.waiting_counts().await;
+    let queues: Vec<QueueInfo> = PRESETS
+        .iter()
+        .map(|preset| QueueInfo {
+            id: preset.id,
+            name: preset.name,
+            description: preset.description,
+            rules: preset.rules(&state.config.rules),
+            waiting: waiting.get(preset.id).copied().unwrap_or_default(),
+        })
+        .collect();
+    Json(queues)
+}
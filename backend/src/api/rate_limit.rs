@@ -0,0 +1,239 @@
+use axum::{
+    extract::{ConnectInfo, Request, State, connect_info::Connected},
+    http::{HeaderValue, StatusCode, header},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    serve::IncomingStream,
+};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::net::TcpListener;
+
+use crate::api::problem::Problem;
+use crate::api::tls::TlsListener;
+
+/// The peer address of a TCP or TLS connection, recorded per connection by the server.
+#[derive(Debug, Clone, Copy)]
+pub struct ClientAddr(pub SocketAddr);
+
+impl Connected<IncomingStream<'_, TcpListener>> for ClientAddr {
+    fn connect_info(stream: IncomingStream<'_, TcpListener>) -> Self {
+        Self(*stream.remote_addr())
+    }
+}
+
+impl Connected<IncomingStream<'_, TlsListener>> for ClientAddr {
+    fn connect_info(stream: IncomingStream<'_, TlsListener>) -> Self {
+        Self(*stream.remote_addr())
+    }
+}
+
+/// A token bucket per client IP: each address may burst up to `per_minute` requests, then
+/// gets one more every `60 / per_minute` seconds.
+pub struct RateLimiter {
+    per_minute: u32,
+    /// Read the client address from `X-Forwarded-For`, for servers behind a proxy
+    trust_forwarded: bool,
+    buckets: Mutex<HashMap<IpAddr, Bucket>>,
+    allowed_total: AtomicU64,
+    limited_total: AtomicU64,
+}
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+/// Counters for one limiter, as shown in the admin API.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RateLimitMetrics {
+    /// Zero means unlimited
+    pub per_minute: u32,
+    /// Addresses with a partly spent bucket
+    pub tracked_addresses: usize,
+    pub allowed_total: u64,
+    /// Answered with 429
+    pub limited_total: u64,
+}
+
+impl RateLimiter {
+    /// `per_minute` of zero lets everything through.
+    pub fn new(per_minute: u32, trust_forwarded: bool) -> Arc<Self> {
+        Arc::new(Self {
+            per_minute,
+            trust_forwarded,
+            buckets: Mutex::new(HashMap::new()),
+            allowed_total: AtomicU64::new(0),
+            limited_total: AtomicU64::new(0),
+        })
+    }
+
+    /// Spends a token for `ip`, or says how long until the next one.
+    pub fn check(&self, ip: IpAddr, now: Instant) -> Result<(), Duration> {
+        if self.per_minute == 0 {
+            return Ok(());
+        }
+
+        let capacity = self.per_minute as f64;
+        let refill_per_sec = capacity / 60.0;
+        let mut buckets = self.buckets.lock().unwrap();
+        let bucket = buckets.entry(ip).or_insert(Bucket {
+            tokens: capacity,
+            updated: now,
+        });
+        let elapsed = now.saturating_duration_since(bucket.updated).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * refill_per_sec).min(capacity);
+        bucket.updated = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            self.allowed_total.fetch_add(1, Ordering::Relaxed);
+            Ok(())
+        } else {
+            self.limited_total.fetch_add(1, Ordering::Relaxed);
+            Err(Duration::from_secs_f64(
+                (1.0 - bucket.tokens) / refill_per_sec,
+            ))
+        }
+    }
+
+    /// Forgets addresses whose bucket has refilled, returning how many were dropped.
+    pub fn prune(&self, now: Instant) -> usize {
+        let full_after = Duration::from_secs(60);
+        let mut buckets = self.buckets.lock().unwrap();
+        let before = buckets.len();
+        buckets.retain(|_, bucket| now.saturating_duration_since(bucket.updated) < full_after);
+        before - buckets.len()
+    }
+
+    pub fn metrics(&self) -> RateLimitMetrics {
+        RateLimitMetrics {
+            per_minute: self.per_minute,
+            tracked_addresses: self.buckets.lock().unwrap().len(),
+            allowed_total: self.allowed_total.load(Ordering::Relaxed),
+            limited_total: self.limited_total.load(Ordering::Relaxed),
+        }
+    }
+
+    fn client_ip(&self, req: &Request) -> Option<IpAddr> {
+        if self.trust_forwarded {
+            // The proxy appends the address it saw, so the last entry is the trustworthy one
+            let forwarded = req
+                .headers()
+                .get("x-forwarded-for")
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.rsplit(',').next())
+                .and_then(|last| last.trim().parse().ok());
+            if forwarded.is_some() {
+                return forwarded;
+            }
+        }
+        req.extensions()
+            .get::<ConnectInfo<ClientAddr>>()
+            .map(|ConnectInfo(ClientAddr(addr))| addr.ip())
+    }
+}
+
+/// The server's per-IP limits.
+#[derive(Clone)]
+pub struct RateLimits {
+    /// Registration and login
+    pub auth: Arc<RateLimiter>,
+    /// WebSocket upgrades
+    pub ws: Arc<RateLimiter>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RateLimitsMetrics {
+    pub auth: RateLimitMetrics,
+    pub ws: RateLimitMetrics,
+}
+
+impl RateLimits {
+    pub fn new(auth_per_minute: u32, ws_per_minute: u32, trust_forwarded: bool) -> Self {
+        Self {
+            auth: RateLimiter::new(auth_per_minute, trust_forwarded),
+            ws: RateLimiter::new(ws_per_minute, trust_forwarded),
+        }
+    }
+
+    pub fn metrics(&self) -> RateLimitsMetrics {
+        RateLimitsMetrics {
+            auth: self.auth.metrics(),
+            ws: self.ws.metrics(),
+        }
+    }
+
+    pub fn prune(&self) -> usize {
+        let now = Instant::now();
+        self.auth.prune(now) + self.ws.prune(now)
+    }
+}
+
+/// Route layer answering 429 with `Retry-After` once the caller's address runs out of
+/// requests. Requests whose address can't be told (a Unix socket without a trusted proxy
+/// header) are let through.
+pub async fn limit(State(limiter): State<Arc<RateLimiter>>, req: Request, next: Next) -> Response {
+    let Some(ip) = limiter.client_ip(&req) else {
+        return next.run(req).await;
+    };
+    match limiter.check(ip, Instant::now()) {
+        Ok(()) => next.run(req).await,
+        Err(retry_after) => {
+            let mut response =
+                Problem::keyed(StatusCode::TOO_MANY_REQUESTS, "rate_limited").into_response();
+            let secs = retry_after.as_secs_f64().ceil().max(1.0) as u64;
+            response
+                .headers_mut()
+                .insert(header::RETRY_AFTER, HeaderValue::from(secs));
+            response
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bursts_then_refills_per_address() {
+        let limiter = RateLimiter::new(2, false);
+        let (ana, beto): (IpAddr, IpAddr) =
+            ("10.0.0.1".parse().unwrap(), "10.0.0.2".parse().unwrap());
+        let start = Instant::now();
+
+        assert!(limiter.check(ana, start).is_ok());
+        assert!(limiter.check(ana, start).is_ok());
+        let wait = limiter.check(ana, start).unwrap_err();
+        assert_eq!(wait.as_secs(), 30, "Two a minute refill one every 30s");
+        assert!(
+            limiter.check(beto, start).is_ok(),
+            "Addresses don't share buckets"
+        );
+
+        assert!(limiter.check(ana, start + Duration::from_secs(30)).is_ok());
+        let metrics = limiter.metrics();
+        assert_eq!((metrics.allowed_total, metrics.limited_total), (4, 1));
+
+        assert_eq!(
+            limiter.prune(start + Duration::from_secs(60)),
+            1,
+            "beto refilled"
+        );
+        assert_eq!(limiter.metrics().tracked_addresses, 1);
+    }
+
+    #[test]
+    fn zero_is_unlimited() {
+        let limiter = RateLimiter::new(0, false);
+        let ip: IpAddr = "10.0.0.1".parse().unwrap();
+        for _ in 0..100 {
+            assert!(limiter.check(ip, Instant::now()).is_ok());
+        }
+        assert_eq!(limiter.metrics().tracked_addresses, 0);
+    }
+}
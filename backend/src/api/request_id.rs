@@ -0,0 +1,83 @@
+use axum::{
+    extract::Request,
+    http::{HeaderName, HeaderValue, header},
+    middleware::Next,
+    response::Response,
+};
+use uuid::Uuid;
+
+pub static REQUEST_ID_HEADER: HeaderName = HeaderName::from_static("x-request-id");
+
+/// Longest client-supplied id kept; anything longer (or not plain ASCII) is replaced.
+const MAX_REQUEST_ID_LEN: usize = 64;
+
+/// The id of the request being handled, for handlers that want to log it.
+#[derive(Debug, Clone)]
+pub struct RequestId(pub String);
+
+/// Takes a usable `x-request-id` from the caller, or generates one.
+fn pick_request_id(incoming: Option<&HeaderValue>) -> String {
+    incoming
+        .and_then(|value| value.to_str().ok())
+        .filter(|id| {
+            !id.is_empty()
+                && id.len() <= MAX_REQUEST_ID_LEN
+                && id.bytes().all(|b| b.is_ascii_graphic())
+        })
+        .map(str::to_string)
+        .unwrap_or_else(|| Uuid::new_v4().to_string())
+}
+
+/// Tags every request with an `x-request-id`, keeping the caller's if it sent one, and
+/// echoes it on the response so a failure the client reports can be found in the logs.
+/// Runs ahead of the trace layer, whose spans record the header.
+pub async fn propagate(mut req: Request, next: Next) -> Response {
+    let id = pick_request_id(req.headers().get(&REQUEST_ID_HEADER));
+    let value = HeaderValue::from_str(&id).expect("request ids are visible ASCII");
+    req.headers_mut()
+        .insert(REQUEST_ID_HEADER.clone(), value.clone());
+    // Spans record request headers; keep tokens out of them
+    if let Some(authorization) = req.headers_mut().get_mut(header::AUTHORIZATION) {
+        authorization.set_sensitive(true);
+    }
+    req.extensions_mut().insert(RequestId(id.clone()));
+
+    let method = req.method().clone();
+    let path = req.uri().path().to_string();
+    let mut response = next.run(req).await;
+    if response.status().is_server_error() {
+        println!(
+            "[Http] {} {} failed with {} (request {})",
+            method,
+            path,
+            response.status(),
+            id
+        );
+    }
+    response
+        .headers_mut()
+        .insert(REQUEST_ID_HEADER.clone(), value);
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_sane_ids_and_replaces_the_rest() {
+        let given = HeaderValue::from_static("client-42");
+        assert_eq!(pick_request_id(Some(&given)), "client-42");
+
+        let long = HeaderValue::from_str(&"x".repeat(MAX_REQUEST_ID_LEN + 1)).unwrap();
+        for bad in [
+            HeaderValue::from_static(""),
+            HeaderValue::from_static("a b"),
+            long,
+        ] {
+            let id = pick_request_id(Some(&bad));
+            assert!(Uuid::parse_str(&id).is_ok(), "{:?} should be replaced", bad);
+        }
+        assert!(Uuid::parse_str(&pick_request_id(None)).is_ok());
+    }
+}
@@ -0,0 +1,18 @@
+use axum::{
+    Json,
+    extract::{Query, State},
+    response::IntoResponse,
+};
+use std::sync::Arc;
+
+use crate::api::server::AppState;
+use crate::matchmaking::browser::BrowserQuery;
+
+/// The public tables being played, filtered and sorted for the game browser. `/ws?mode=browser`
+/// streams changes to the same listing.
+pub async fn list_rooms(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<BrowserQuery>,
+) -> impl IntoResponse {
+    Json(state.directory.list(&query))
+}
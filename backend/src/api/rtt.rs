@@ -0,0 +1,126 @@
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Round trips averaged into a connection's RTT
+const RTT_WINDOW: usize = 8;
+/// Up to this RTT a connection is `Good`
+const GOOD_RTT: Duration = Duration::from_millis(150);
+/// Up to this RTT a connection is `Fair`; anything slower is `Poor`
+const FAIR_RTT: Duration = Duration::from_millis(400);
+
+/// How responsive a player's connection is, coarse enough to show next to their name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ConnectionQuality {
+    Good,
+    Fair,
+    Poor,
+}
+
+impl ConnectionQuality {
+    pub fn from_rtt(rtt: Duration) -> Self {
+        if rtt <= GOOD_RTT {
+            ConnectionQuality::Good
+        } else if rtt <= FAIR_RTT {
+            ConnectionQuality::Fair
+        } else {
+            ConnectionQuality::Poor
+        }
+    }
+}
+
+/// Pings sent down one connection and the round trips of the ones answered.
+#[derive(Debug, Default)]
+pub struct RttTracker {
+    next_nonce: u64,
+    /// Sent and not yet answered, oldest first
+    outstanding: VecDeque<(u64, Instant)>,
+    /// Latest round trips, oldest first
+    samples: VecDeque<Duration>,
+}
+
+impl RttTracker {
+    /// Notes a ping sent at `now` and returns the nonce to send with it. A ping still
+    /// unanswered when the next one goes out counts as lost, with a round trip of at least
+    /// the time it has waited.
+    pub fn ping(&mut self, now: Instant) -> u64 {
+        while let Some((_, sent)) = self.outstanding.pop_front() {
+            self.sample(now - sent);
+        }
+        self.next_nonce += 1;
+        self.outstanding.push_back((self.next_nonce, now));
+        self.next_nonce
+    }
+
+    /// Notes the answer to ping `nonce`; returns its round trip, or `None` for a nonce that
+    /// is not outstanding.
+    pub fn pong(&mut self, nonce: u64, now: Instant) -> Option<Duration> {
+        let index = self.outstanding.iter().position(|(n, _)| *n == nonce)?;
+        let (_, sent) = self.outstanding.remove(index)?;
+        let rtt = now - sent;
+        self.sample(rtt);
+        Some(rtt)
+    }
+
+    /// Mean of the latest round trips; `None` before any.
+    pub fn rtt(&self) -> Option<Duration> {
+        let total: Duration = self.samples.iter().sum();
+        (!self.samples.is_empty()).then(|| total / self.samples.len() as u32)
+    }
+
+    pub fn quality(&self) -> Option<ConnectionQuality> {
+        self.rtt().map(ConnectionQuality::from_rtt)
+    }
+
+    fn sample(&mut self, rtt: Duration) {
+        if self.samples.len() == RTT_WINDOW {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(rtt);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    #[test]
+    fn rtt_averages_the_latest_round_trips() {
+        let start = Instant::now();
+        let mut tracker = RttTracker::default();
+        assert_eq!(tracker.quality(), None);
+
+        let first = tracker.ping(start);
+        assert_eq!(tracker.pong(first, start + ms(100)), Some(ms(100)));
+        assert_eq!(tracker.pong(first, start + ms(150)), None);
+        let second = tracker.ping(start + ms(1000));
+        tracker.pong(second, start + ms(1300));
+        assert_eq!(tracker.rtt(), Some(ms(200)));
+        assert_eq!(tracker.quality(), Some(ConnectionQuality::Fair));
+
+        for n in 0..RTT_WINDOW as u64 {
+            let sent = start + ms(2000 + n * 1000);
+            let nonce = tracker.ping(sent);
+            tracker.pong(nonce, sent + ms(50));
+        }
+        assert_eq!(tracker.rtt(), Some(ms(50)));
+        assert_eq!(tracker.quality(), Some(ConnectionQuality::Good));
+    }
+
+    #[test]
+    fn unanswered_pings_count_as_slow_round_trips() {
+        let start = Instant::now();
+        let mut tracker = RttTracker::default();
+        let lost = tracker.ping(start);
+        let answered = tracker.ping(start + ms(5000));
+        tracker.pong(answered, start + ms(5100));
+
+        assert_eq!(tracker.pong(lost, start + ms(5200)), None);
+        assert_eq!(tracker.rtt(), Some(ms(2550)));
+        assert_eq!(tracker.quality(), Some(ConnectionQuality::Poor));
+    }
+}
@@ -0,0 +1,316 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::api::auth;
+use crate::api::authz::AuthUser;
+use crate::api::problem::Problem;
+use crate::api::server::AppState;
+use crate::db::models::{ScheduledGame, ScheduledGameRsvp};
+use crate::db::repo;
+use crate::matchmaking::lobby::TABLE_SIZE;
+use crate::matchmaking::scheduled::SEAT_RANGE;
+use crate::notify::push::PushEvent;
+
+const MAX_NAME_CHARS: usize = 64;
+/// How far ahead a game may be scheduled.
+const MAX_LEAD_SECS: i64 = 30 * 24 * 60 * 60;
+
+#[derive(Deserialize)]
+pub struct ScheduleGamePayload {
+    pub name: String,
+    /// Unix seconds
+    pub starts_at: i64,
+    pub max_players: Option<usize>,
+    /// Defaults to public; a private game only takes the host and `invite`
+    pub public: Option<bool>,
+    /// Usernames to invite
+    #[serde(default)]
+    pub invite: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct ScheduledGameDetail {
+    pub game: ScheduledGame,
+    /// Earliest first; the first `max_players` are seated
+    pub rsvps: Vec<ScheduledGameRsvp>,
+}
+
+/// Upcoming games the caller may join.
+pub async fn list_scheduled_games(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+) -> impl IntoResponse {
+    Json(repo::list_upcoming_scheduled_games(&state.db, &user.user_id).await)
+}
+
+pub async fn get_scheduled_game(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Path(id): Path<i64>,
+) -> impl IntoResponse {
+    let game = match visible_game(&state, id, &user.user_id).await {
+        Ok(game) => game,
+        Err(response) => return response,
+    };
+    Json(ScheduledGameDetail {
+        rsvps: repo::get_scheduled_game_rsvps(&state.db, id).await,
+        game,
+    })
+    .into_response()
+}
+
+/// Sets a game for a future time, with the host RSVPed, and lets invitees know.
+pub async fn schedule_game(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Json(payload): Json<ScheduleGamePayload>,
+) -> impl IntoResponse {
+    let now = unix_now();
+    let name = payload.name.trim();
+    if name.is_empty() || name.chars().count() > MAX_NAME_CHARS {
+        return Problem::new(
+            StatusCode::BAD_REQUEST,
+            "A game name must be 1 to 64 characters",
+        )
+        .into_response();
+    }
+    if payload.starts_at <= now || payload.starts_at > now + MAX_LEAD_SECS {
+        return Problem::new(
+            StatusCode::BAD_REQUEST,
+            "The start time must be within the next 30 days",
+        )
+        .into_response();
+    }
+    let max_players = payload.max_players.unwrap_or(TABLE_SIZE);
+    if !SEAT_RANGE.contains(&max_players) {
+        return Problem::new(StatusCode::BAD_REQUEST, "A game seats 2 to 4 players")
+            .into_response();
+    }
+
+    let mut invited = Vec::with_capacity(payload.invite.len());
+    for username in &payload.invite {
+        match state.user_cache.get_user(&state.db, username).await {
+            Some(guest) => invited.push(guest.id),
+            None => {
+                return Problem::new(
+                    StatusCode::NOT_FOUND,
+                    &format!("Unknown player {}", username),
+                )
+                .into_response();
+            }
+        }
+    }
+
+    let game = ScheduledGame {
+        id: 0,
+        name: name.to_string(),
+        created_by: user.user_id,
+        starts_at: payload.starts_at,
+        max_players: max_players as i64,
+        public: payload.public.unwrap_or(true),
+        status: "scheduled".to_string(),
+        room_id: None,
+        created_at: now,
+    };
+    let game = match repo::insert_scheduled_game(&state.db, &game, &invited).await {
+        Ok(game) => game,
+        Err(_) => {
+            return Problem::new(StatusCode::INTERNAL_SERVER_ERROR, "Failed to schedule game")
+                .into_response();
+        }
+    };
+
+    for guest in &invited {
+        state
+            .notifier
+            .push(guest, PushEvent::GameScheduled { game_id: game.id });
+    }
+    (StatusCode::CREATED, Json(game)).into_response()
+}
+
+/// Claims one of the game's seats.
+pub async fn rsvp(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Path(id): Path<i64>,
+) -> impl IntoResponse {
+    let game = match visible_game(&state, id, &user.user_id).await {
+        Ok(game) => game,
+        Err(response) => return response,
+    };
+    if game.status != "scheduled" {
+        return Problem::new(StatusCode::CONFLICT, "The game has already started").into_response();
+    }
+    match repo::add_scheduled_game_rsvp(&state.db, &game, &user.user_id, unix_now()).await {
+        Ok(true) => StatusCode::NO_CONTENT.into_response(),
+        Ok(false) => Problem::new(StatusCode::CONFLICT, "The game is full").into_response(),
+        Err(_) => Problem::new(StatusCode::INTERNAL_SERVER_ERROR, "Failed to RSVP").into_response(),
+    }
+}
+
+pub async fn withdraw_rsvp(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Path(id): Path<i64>,
+) -> impl IntoResponse {
+    let game = match visible_game(&state, id, &user.user_id).await {
+        Ok(game) => game,
+        Err(response) => return response,
+    };
+    if game.status != "scheduled" {
+        return Problem::new(StatusCode::CONFLICT, "The game has already started").into_response();
+    }
+    match repo::delete_scheduled_game_rsvp(&state.db, id, &user.user_id).await {
+        Ok(true) => StatusCode::NO_CONTENT.into_response(),
+        Ok(false) => Problem::new(StatusCode::NOT_FOUND, "No RSVP to withdraw").into_response(),
+        Err(_) => Problem::new(StatusCode::INTERNAL_SERVER_ERROR, "Failed to withdraw RSVP")
+            .into_response(),
+    }
+}
+
+#[derive(Serialize)]
+pub struct InviteLink {
+    /// Goes in the URL shared with the guest
+    pub token: String,
+    /// Where the guest's client posts the token, once they are logged in
+    pub redeem_path: String,
+    /// Epoch seconds after which the link stops working
+    pub expires_at: usize,
+}
+
+#[derive(Serialize)]
+pub struct RedeemedInvite {
+    pub game: ScheduledGame,
+    /// Earliest first; the first `max_players` are seated
+    pub rsvps: Vec<ScheduledGameRsvp>,
+    /// Claims a seat, see `POST /api/scheduled-games/{id}/rsvp`
+    pub rsvp_path: String,
+    /// WebSocket path that waits for the game to start, once RSVPed
+    pub ws_path: String,
+}
+
+/// Issues a single-use link inviting whoever opens it to the caller's game, for sharing
+/// outside the app.
+pub async fn create_invite_link(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Path(id): Path<i64>,
+) -> impl IntoResponse {
+    let game = match visible_game(&state, id, &user.user_id).await {
+        Ok(game) => game,
+        Err(response) => return response,
+    };
+    if game.created_by != user.user_id {
+        return Problem::new(StatusCode::FORBIDDEN, "Only the host can invite players")
+            .into_response();
+    }
+    if game.status != "scheduled" {
+        return Problem::new(StatusCode::CONFLICT, "The game has already started").into_response();
+    }
+
+    let jti = uuid::Uuid::new_v4().to_string();
+    let created =
+        repo::insert_scheduled_game_invite_link(&state.db, &jti, id, &user.user_id, unix_now())
+            .await;
+    if created.is_err() {
+        return Problem::new(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Failed to create invite link",
+        )
+        .into_response();
+    }
+    let (token, expires_at) = auth::create_invite_token(id, &jti);
+    (
+        StatusCode::CREATED,
+        Json(InviteLink {
+            redeem_path: format!("/api/invites/{}", token),
+            token,
+            expires_at,
+        }),
+    )
+        .into_response()
+}
+
+/// Checks an invite link and invites the caller to its game, using the link up.
+pub async fn redeem_invite(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Path(token): Path<String>,
+) -> impl IntoResponse {
+    let Some(claims) = auth::decode_invite_token(&token) else {
+        return Problem::new(
+            StatusCode::GONE,
+            "This invite link is invalid or has expired",
+        )
+        .into_response();
+    };
+    let Some(game) = repo::get_scheduled_game(&state.db, claims.game_id).await else {
+        return Problem::new(StatusCode::NOT_FOUND, "Scheduled game not found").into_response();
+    };
+    if game.status != "scheduled" {
+        return Problem::new(StatusCode::CONFLICT, "The game has already started").into_response();
+    }
+    let redeemed = repo::redeem_scheduled_game_invite_link(
+        &state.db,
+        &claims.jti,
+        game.id,
+        &user.user_id,
+        unix_now(),
+    )
+    .await;
+    match redeemed {
+        Ok(true) => {}
+        Ok(false) => {
+            return Problem::new(StatusCode::GONE, "This invite link has already been used")
+                .into_response();
+        }
+        Err(_) => {
+            return Problem::new(StatusCode::INTERNAL_SERVER_ERROR, "Failed to redeem invite")
+                .into_response();
+        }
+    }
+
+    Json(RedeemedInvite {
+        rsvps: repo::get_scheduled_game_rsvps(&state.db, game.id).await,
+        rsvp_path: format!("/api/scheduled-games/{}/rsvp", game.id),
+        ws_path: format!("/ws?scheduled={}", game.id),
+        game,
+    })
+    .into_response()
+}
+
+/// The game, if it is public or the caller hosts it or was invited. Private games are
+/// reported missing to everyone else.
+async fn visible_game(
+    state: &AppState,
+    id: i64,
+    user_id: &str,
+) -> Result<ScheduledGame, axum::response::Response> {
+    let not_found =
+        || Problem::new(StatusCode::NOT_FOUND, "Scheduled game not found").into_response();
+    let game = repo::get_scheduled_game(&state.db, id)
+        .await
+        .ok_or_else(not_found)?;
+    if game.public
+        || game.created_by == user_id
+        || repo::is_invited_to_scheduled_game(&state.db, id, user_id).await
+    {
+        Ok(game)
+    } else {
+        Err(not_found())
+    }
+}
+
+fn unix_now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64
+}
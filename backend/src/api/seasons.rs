@@ -0,0 +1,57 @@
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use crate::api::problem::Problem;
+use crate::api::server::AppState;
+use crate::db::models::{LeaderboardEntry, Season};
+use crate::db::repo;
+use crate::ranking::season;
+
+const DEFAULT_LEADERBOARD_SIZE: i64 = 50;
+const MAX_LEADERBOARD_SIZE: i64 = 200;
+
+#[derive(Deserialize)]
+pub struct LeaderboardQuery {
+    pub limit: Option<i64>,
+}
+
+#[derive(Serialize)]
+pub struct LeaderboardResponse {
+    pub season: Season,
+    pub entries: Vec<LeaderboardEntry>,
+}
+
+pub async fn list_seasons(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    Json(repo::list_seasons(&state.read_db).await)
+}
+
+/// Standings for a season by id, or for the open one with `current`.
+pub async fn leaderboard(
+    State(state): State<Arc<AppState>>,
+    Path(season_id): Path<String>,
+    Query(query): Query<LeaderboardQuery>,
+) -> impl IntoResponse {
+    let season = match season_id.as_str() {
+        "current" => repo::get_open_season(&state.read_db).await,
+        id => match id.parse() {
+            Ok(id) => repo::get_season(&state.read_db, id).await,
+            Err(_) => None,
+        },
+    };
+    let Some(season) = season else {
+        return Problem::new(StatusCode::NOT_FOUND, "Season not found").into_response();
+    };
+
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_LEADERBOARD_SIZE)
+        .clamp(1, MAX_LEADERBOARD_SIZE);
+    let entries = season::leaderboard(&state.read_db, &season, limit).await;
+    Json(LeaderboardResponse { season, entries }).into_response()
+}
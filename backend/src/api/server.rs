@@ -1,64 +1,1490 @@
 use axum::{
-    routing::{get, post},
     Router,
+    extract::DefaultBodyLimit,
+    middleware,
+    routing::{delete, get, post, put},
+};
+use sqlx::{
+    SqlitePool,
+    sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePoolOptions},
 };
-use sqlx::{sqlite::SqlitePoolOptions, SqlitePool};
-use std::sync::Arc;
 use std::collections::HashMap;
+use std::fmt;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
 use tokio::net::TcpListener;
 use tokio::sync::Mutex;
+use tokio::task::JoinHandle;
 use tower_http::cors::CorsLayer;
-use tower_http::trace::TraceLayer;
+use tower_http::trace::{DefaultMakeSpan, TraceLayer};
 
-use crate::api::auth;
+use crate::api::admin;
+use crate::api::admission::Capacity;
+use crate::api::auth::{self, Role};
+use crate::api::authz::{RoleGate, require_role};
+use crate::api::avatars;
+use crate::api::cosmetics;
+use crate::api::dev;
+use crate::api::games;
+use crate::api::leagues;
+use crate::api::maintenance::Maintenance;
+use crate::api::openapi;
+use crate::api::practice;
+use crate::api::problem;
+use crate::api::push;
+use crate::api::queues;
+use crate::api::rate_limit::{self, ClientAddr, RateLimits};
+use crate::api::request_id;
+use crate::api::rooms;
+use crate::api::scheduled_games;
+use crate::api::seasons;
+use crate::api::spectate;
+use crate::api::stats;
+use crate::api::titles;
+use crate::api::tls::{self, TlsListener};
+use crate::api::tournaments;
+use crate::api::usernames;
+use crate::api::webhooks;
 use crate::api::ws;
+use crate::config::Config;
+use crate::db::audit::AuditLog;
+use crate::db::seed_vault::SeedVault;
+use crate::db::user_cache::UserCache;
 
-use crate::matchmaking::lobby::Lobby;
-use crate::matchmaking::room::RoomEvent;
+use crate::matchmaking::browser::RoomDirectory;
+use crate::matchmaking::latency::ActionLatency;
+use crate::matchmaking::lobby::{self, Lobby, QueueEntry};
+use crate::matchmaking::room::{RoomEvent, RoomServices};
+use crate::matchmaking::scheduled;
+use crate::matchmaking::turn_timer::TurnTimerConfig;
+use crate::moderation::word_filter::WordFilter;
+use crate::notify::http::ClientSettings;
+use crate::notify::notifier::{Notifier, NotifierSettings};
+use crate::notify::push::VapidKeys;
+use crate::ranking::season;
+use crate::scheduler::jobs;
+use crate::scheduler::runner::Scheduler;
+use crate::tournament::director;
 use tokio::sync::mpsc;
 
+/// How often the season end date is checked.
+const SEASON_CHECK_INTERVAL: Duration = Duration::from_secs(60 * 60);
+/// How often finished rooms are shut down and dropped from `active_rooms`.
+const ROOM_CLEANUP_INTERVAL: Duration = Duration::from_secs(5 * 60);
+/// How often finished tournament matches are collected and the next ones opened.
+const TOURNAMENT_ADVANCE_INTERVAL: Duration = Duration::from_secs(30);
+/// How often scheduled games are checked for their start time.
+const SCHEDULED_GAME_CHECK_INTERVAL: Duration = Duration::from_secs(15);
+/// How often addresses that stopped hitting the rate limits are forgotten.
+const RATE_LIMIT_PRUNE_INTERVAL: Duration = Duration::from_secs(5 * 60);
+/// How often the global statistics are recomputed.
+const STATS_AGGREGATION_INTERVAL: Duration = Duration::from_secs(60 * 60);
+/// How often recent games are scanned for collusion.
+const COLLUSION_SCAN_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+
 #[derive(Clone)]
 pub struct AppState {
+    pub config: Arc<Config>,
     pub db: SqlitePool,
+    /// Read-only pool for leaderboards, history and stats; may lag `db` when it is a replica.
+    pub read_db: SqlitePool,
     pub lobby: Lobby,
     // Active rooms mapped by Room ID, storing the Sender channel to communicate with the Room Actor
     pub active_rooms: Arc<Mutex<HashMap<String, mpsc::Sender<RoomEvent>>>>,
+    pub audit: AuditLog,
+    pub word_filter: Arc<WordFilter>,
+    pub notifier: Notifier,
+    pub scheduler: Scheduler,
+    pub capacity: Capacity,
+    pub rate_limits: RateLimits,
+    pub action_latency: ActionLatency,
+    pub directory: RoomDirectory,
+    pub maintenance: Maintenance,
+    pub seed_vault: SeedVault,
+    pub user_cache: UserCache,
+}
+
+impl AppState {
+    /// The shared dependencies handed to every room spawned by this server.
+    pub fn room_services(&self) -> RoomServices {
+        RoomServices {
+            db: self.db.clone(),
+            audit: self.audit.clone(),
+            word_filter: self.word_filter.clone(),
+            turn_timer: TurnTimerConfig::from_config(&self.config),
+            ready_countdown: Duration::from_secs(self.config.ready_countdown_seconds),
+            rules: self.config.rules.clone(),
+            notifier: self.notifier.clone(),
+            push_idle_turn: Duration::from_secs(self.config.push_idle_turn_seconds),
+            seasons: self.config.season_settings(),
+            idle_ttl: Duration::from_secs(self.config.idle_room_ttl_seconds),
+            disconnect_grace: Duration::from_secs(self.config.disconnect_grace_seconds),
+            break_length: Duration::from_secs(self.config.break_max_seconds),
+            stream_deals: self.config.stream_deals,
+            action_latency: self.action_latency.clone(),
+            directory: self.directory.clone(),
+            seed_vault: self.seed_vault.clone(),
+        }
+    }
+}
+
+/// Where the server ended up listening, e.g. the real port when `port = 0` was requested.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BoundAddr {
+    Tcp(SocketAddr),
+    Tls(SocketAddr),
+    Unix(PathBuf),
+}
+
+impl fmt::Display for BoundAddr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BoundAddr::Tcp(addr) => write!(f, "http://{}", addr),
+            BoundAddr::Tls(addr) => write!(f, "https://{}", addr),
+            BoundAddr::Unix(path) => write!(f, "unix:{}", path.display()),
+        }
+    }
 }
 
-pub async fn start_server(db_url: &str) {
-    let pool = SqlitePoolOptions::new()
-        .max_connections(5)
-        .connect(db_url)
+/// Opens the pool everything is written through. A database file is put in WAL mode, so
+/// readers on either pool see the last commit instead of waiting out an open write.
+async fn connect_write_pool(config: &Config) -> SqlitePool {
+    let mut options =
+        SqliteConnectOptions::from_str(&config.database_url).expect("Invalid database URL");
+    // Every connection to `sqlite::memory:` opens its own private database
+    let max_connections = if config.database_url.contains(":memory:") {
+        1
+    } else {
+        options = options.journal_mode(SqliteJournalMode::Wal);
+        5
+    };
+    SqlitePoolOptions::new()
+        .max_connections(max_connections)
+        .connect_with(options)
         .await
-        .expect("Failed to connect to SQLite");
+        .expect("Failed to connect to SQLite")
+}
+
+/// Opens the pool the heavy read paths use, so they do not queue for the connections game
+/// results are written on.
+async fn connect_read_pool(config: &Config, pool: &SqlitePool) -> SqlitePool {
+    let url = config
+        .database_read_url
+        .as_deref()
+        .unwrap_or(&config.database_url);
+    // A second pool on `sqlite::memory:` would open a separate, empty database
+    if url.contains(":memory:") {
+        return pool.clone();
+    }
+    let options = SqliteConnectOptions::from_str(url)
+        .expect("Invalid read database URL")
+        .read_only(true);
+    SqlitePoolOptions::new()
+        .max_connections(config.database_read_connections)
+        .connect_with(options)
+        .await
+        .expect("Failed to connect to the read database")
+}
+
+/// Connects the database, binds the listener described by `config` and spawns the server.
+///
+/// Returns once the socket is bound, so callers (and tests) know the actual address
+/// before any request is made.
+pub async fn start_server(config: &Config) -> (BoundAddr, JoinHandle<()>) {
+    let pool = connect_write_pool(config).await;
 
     // Run migrations/table creation
-    crate::db::repo::create_user_table(&pool).await.expect("Failed to create user table");
+    crate::db::repo::create_user_table(&pool)
+        .await
+        .expect("Failed to create user table");
+    crate::db::repo::create_audit_table(&pool)
+        .await
+        .expect("Failed to create audit table");
+    crate::db::repo::create_moderation_tables(&pool)
+        .await
+        .expect("Failed to create moderation tables");
+    crate::db::repo::create_notification_tables(&pool)
+        .await
+        .expect("Failed to create notification tables");
+    crate::db::repo::create_season_tables(&pool)
+        .await
+        .expect("Failed to create season tables");
+    crate::db::repo::create_game_result_tables(&pool)
+        .await
+        .expect("Failed to create game result tables");
+    crate::db::repo::create_tournament_tables(&pool)
+        .await
+        .expect("Failed to create tournament tables");
+    crate::db::repo::create_league_tables(&pool)
+        .await
+        .expect("Failed to create league tables");
+    crate::db::repo::create_scheduled_game_tables(&pool)
+        .await
+        .expect("Failed to create scheduled game tables");
+    crate::db::repo::create_global_stats_table(&pool)
+        .await
+        .expect("Failed to create global stats table");
+    crate::db::repo::create_cosmetics_table(&pool)
+        .await
+        .expect("Failed to create cosmetics table");
+    crate::db::repo::create_avatar_table(&pool)
+        .await
+        .expect("Failed to create avatar table");
+    crate::db::repo::create_username_history_table(&pool)
+        .await
+        .expect("Failed to create username history table");
+    crate::db::repo::create_bot_level_table(&pool)
+        .await
+        .expect("Failed to create bot level table");
+    crate::db::repo::create_bot_stats_table(&pool)
+        .await
+        .expect("Failed to create bot stats table");
+    crate::db::repo::create_replay_flag_table(&pool)
+        .await
+        .expect("Failed to create replay flag table");
+    crate::db::repo::create_room_snapshot_table(&pool)
+        .await
+        .expect("Failed to create room snapshot table");
+    crate::db::repo::create_matchmaking_queue_table(&pool)
+        .await
+        .expect("Failed to create matchmaking queue table");
+    crate::db::repo::create_requeue_priority_table(&pool)
+        .await
+        .expect("Failed to create requeue priority table");
+    crate::db::repo::create_collusion_flag_table(&pool)
+        .await
+        .expect("Failed to create collusion flag table");
+    crate::db::repo::create_shuffle_seed_table(&pool)
+        .await
+        .expect("Failed to create shuffle seed table");
+    crate::db::repo::create_title_tables(&pool)
+        .await
+        .expect("Failed to create title tables");
+
+    let read_pool = connect_read_pool(config, &pool).await;
+
+    for username in &config.admin_usernames {
+        crate::db::repo::set_role_by_username(&pool, username, Role::Admin.as_str())
+            .await
+            .expect("Failed to promote configured admin");
+    }
+
+    let audit = AuditLog::spawn(pool.clone());
+
+    season::roll_over(&pool, unix_now(), config.season_settings())
+        .await
+        .expect("Failed to open the ranked season");
+
+    let webhook_tls = tls::load_client_config(&config.webhook_ca_file)
+        .map_err(|e| println!("[Webhook] https:// callbacks disabled: {}", e))
+        .ok();
+    let vapid = config.vapid_private_key_file.as_ref().map(|path| {
+        VapidKeys::load(
+            path,
+            config.vapid_public_key.clone(),
+            config.vapid_subject.clone(),
+        )
+        .expect("Failed to load VAPID key")
+    });
+    let notifier = Notifier::spawn(
+        pool.clone(),
+        NotifierSettings {
+            http: ClientSettings {
+                tls: webhook_tls,
+                allow_private_addresses: config.webhook_allow_private,
+                timeout: Duration::from_secs(config.webhook_timeout_seconds),
+            },
+            vapid,
+        },
+    );
+
+    let word_filter = match &config.word_filter_file {
+        Some(path) => WordFilter::from_file(path).expect("Failed to load word filter"),
+        None => WordFilter::with_defaults(),
+    };
+
+    let seed_vault = match &config.shuffle_seed_key_file {
+        Some(path) => {
+            let secret = std::fs::read(path).expect("Failed to read shuffle seed key");
+            SeedVault::new(secret.trim_ascii())
+        }
+        None => {
+            println!(
+                "[Shuffle] No seed key configured, seeds stored now can't be read after a restart"
+            );
+            SeedVault::ephemeral()
+        }
+    };
+
+    let lobby = Lobby::new(config.lobby_settings());
+    restore_lobby(&pool, &lobby).await;
 
     let state = Arc::new(AppState {
+        config: Arc::new(config.clone()),
         db: pool,
-        lobby: Lobby::new(),
+        read_db: read_pool,
+        lobby,
         active_rooms: Arc::new(Mutex::new(HashMap::new())),
+        audit,
+        word_filter: Arc::new(word_filter),
+        notifier,
+        scheduler: Scheduler::new(),
+        capacity: Capacity::new(
+            config.max_connections,
+            config.max_rooms,
+            config.admission_queue_limit,
+        ),
+        rate_limits: RateLimits::new(
+            config.rate_limit_auth_per_minute,
+            config.rate_limit_ws_per_minute,
+            config.rate_limit_trust_forwarded,
+        ),
+        action_latency: ActionLatency::default(),
+        directory: RoomDirectory::default(),
+        maintenance: Maintenance::default(),
+        seed_vault,
+        user_cache: UserCache::new(config.user_cache_capacity),
+    });
+    schedule_maintenance(&state);
+
+    let app = build_router(state);
+
+    let tls_config = match (&config.tls_cert_path, &config.tls_key_path) {
+        (Some(cert), Some(key)) => {
+            Some(tls::load_server_config(cert, key).expect("Failed to load TLS configuration"))
+        }
+        (None, None) => None,
+        _ => panic!("CARIOCA_TLS_CERT and CARIOCA_TLS_KEY must be set together"),
+    };
+
+    if let Some(path) = &config.unix_socket {
+        // A stale socket file from a previous run would make bind fail
+        let _ = std::fs::remove_file(path);
+        if tls_config.is_some() {
+            println!(
+                "TLS is not applied to Unix sockets; terminate it in front of {:?}",
+                path
+            );
+        }
+        let listener = tokio::net::UnixListener::bind(path).expect("Failed to bind to Unix socket");
+
+        let handle = tokio::spawn(async move {
+            axum::serve(listener, app).await.expect("Server failed");
+        });
+
+        return (BoundAddr::Unix(path.clone()), handle);
+    }
+
+    let listener = TcpListener::bind((config.host.as_str(), config.port))
+        .await
+        .unwrap_or_else(|e| panic!("Failed to bind to {}:{}: {}", config.host, config.port, e));
+    let addr = listener
+        .local_addr()
+        .expect("Listener has no local address");
+
+    if let Some(tls_config) = tls_config {
+        let listener =
+            TlsListener::new(listener, tls_config).expect("Failed to start TLS listener");
+        let handle = tokio::spawn(async move {
+            axum::serve(
+                listener,
+                app.into_make_service_with_connect_info::<ClientAddr>(),
+            )
+            .await
+            .expect("Server failed");
+        });
+
+        return (BoundAddr::Tls(addr), handle);
+    }
+
+    let handle = tokio::spawn(async move {
+        axum::serve(
+            listener,
+            app.into_make_service_with_connect_info::<ClientAddr>(),
+        )
+        .await
+        .expect("Server failed");
     });
 
+    (BoundAddr::Tcp(addr), handle)
+}
+
+fn unix_now() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64
+}
+
+/// Gives players who were waiting for a match when the server last stopped their place
+/// back for when they reconnect.
+async fn restore_lobby(pool: &SqlitePool, lobby: &Lobby) {
+    let cutoff_ms = unix_now() * 1000 - lobby::QUEUE_ENTRY_TTL.as_millis() as i64;
+    let queued = crate::db::repo::load_queued_players(pool, cutoff_ms)
+        .await
+        .expect("Failed to load the matchmaking queue");
+    let entries: Vec<QueueEntry> = queued
+        .into_iter()
+        .filter_map(|player| {
+            Some(QueueEntry {
+                queue: crate::matchmaking::queues::find(&player.queue)?.id,
+                user_id: player.user_id,
+                region: player.region,
+                joined_at_ms: player.joined_at_ms as u64,
+            })
+        })
+        .collect();
+    if !entries.is_empty() {
+        println!(
+            "[Lobby] Restored {} players' places in the queue",
+            entries.len()
+        );
+    }
+    lobby.restore(entries).await;
+}
+
+fn schedule_maintenance(state: &Arc<AppState>) {
+    let rooms = state.active_rooms.clone();
+    state
+        .scheduler
+        .every("room_cleanup", ROOM_CLEANUP_INTERVAL, move || {
+            let rooms = rooms.clone();
+            async move { jobs::close_finished_rooms(&rooms).await }
+        });
+
+    let pool = state.db.clone();
+    let settings = state.config.season_settings();
+    state
+        .scheduler
+        .every("season_rollover", SEASON_CHECK_INTERVAL, move || {
+            let pool = pool.clone();
+            async move { jobs::roll_over_seasons(&pool, settings).await }
+        });
+
+    let rate_limits = state.rate_limits.clone();
+    state
+        .scheduler
+        .every("rate_limit_prune", RATE_LIMIT_PRUNE_INTERVAL, move || {
+            let rate_limits = rate_limits.clone();
+            async move { Ok(format!("forgot {} addresses", rate_limits.prune())) }
+        });
+
+    let pool = state.db.clone();
+    state
+        .scheduler
+        .every("global_stats", STATS_AGGREGATION_INTERVAL, move || {
+            let pool = pool.clone();
+            async move { jobs::aggregate_global_stats(&pool).await }
+        });
+
+    let pool = state.db.clone();
+    state
+        .scheduler
+        .every("collusion_scan", COLLUSION_SCAN_INTERVAL, move || {
+            let pool = pool.clone();
+            async move { jobs::detect_collusion(&pool).await }
+        });
+
+    let app = state.clone();
+    state.scheduler.every(
+        "tournament_advance",
+        TOURNAMENT_ADVANCE_INTERVAL,
+        move || {
+            let app = app.clone();
+            async move { director::advance(&app).await }
+        },
+    );
+
+    let app = state.clone();
+    state.scheduler.every(
+        "scheduled_games",
+        SCHEDULED_GAME_CHECK_INTERVAL,
+        move || {
+            let app = app.clone();
+            async move { scheduled::start_due_games(&app).await }
+        },
+    );
+}
+
+fn build_router(state: Arc<AppState>) -> Router {
     let cors = CorsLayer::permissive();
 
-    let app = Router::new()
-        .route("/health", get(|| async { "OK" }))
+    let moderation = Router::new()
+        .route("/api/admin/reports", get(admin::list_reports))
+        .route("/api/admin/usernames/{username}", get(usernames::lookup))
+        .route("/api/admin/reports/{id}", get(admin::get_report))
+        .route(
+            "/api/admin/collusion-flags",
+            get(admin::list_collusion_flags),
+        )
+        .route(
+            "/api/admin/collusion-flags/{id}/resolve",
+            post(admin::resolve_collusion_flag),
+        )
+        .route(
+            "/api/admin/reports/{id}/resolve",
+            post(admin::resolve_report),
+        )
+        .route("/api/admin/users/{id}/sanctions", post(admin::add_sanction))
+        .route(
+            "/api/admin/users/{id}/sanctions/{kind}",
+            delete(admin::lift_sanction),
+        )
+        .route("/api/tournaments", post(tournaments::create_tournament))
+        .route(
+            "/api/tournaments/{id}/start",
+            post(tournaments::start_tournament),
+        )
+        .route("/api/leagues", post(leagues::create_league))
+        .route_layer(middleware::from_fn_with_state(
+            RoleGate::new(Role::Moderator, &state),
+            require_role,
+        ));
+
+    let admin = Router::new()
+        .route("/api/admin/users/{id}/role", put(admin::set_role))
+        .route("/api/admin/jobs", get(admin::list_jobs))
+        .route("/api/admin/capacity", get(admin::capacity))
+        .route("/api/admin/rate-limits", get(admin::rate_limits))
+        .route("/api/admin/audit", get(admin::audit))
+        .route("/api/admin/action-latency", get(admin::action_latency))
+        .route("/api/admin/bots", get(admin::bot_performance))
+        .route("/api/admin/replay-flags", get(admin::replay_flags))
+        .route(
+            "/api/admin/games/{room_id}/shuffle-seeds",
+            get(admin::shuffle_seeds),
+        )
+        .route(
+            "/api/admin/maintenance",
+            get(admin::maintenance).put(admin::set_maintenance),
+        )
+        .route("/api/admin/room-snapshots", get(admin::room_snapshots))
+        .route_layer(middleware::from_fn_with_state(
+            RoleGate::new(Role::Admin, &state),
+            require_role,
+        ));
+
+    // Off unless explicitly enabled, and admin-only even then
+    let mut dev = Router::new();
+    if state.config.dev_endpoints {
+        dev = dev
+            .route("/api/dev/rooms", post(dev::load_state))
+            .route_layer(middleware::from_fn_with_state(
+                RoleGate::new(Role::Admin, &state),
+                require_role,
+            ));
+    }
+
+    let auth_routes = Router::new()
         .route("/api/auth/register", post(auth::register))
         .route("/api/auth/login", post(auth::login))
-        .route("/ws", get(ws::ws_handler))
-        .layer(TraceLayer::new_for_http())
+        .route_layer(middleware::from_fn_with_state(
+            state.rate_limits.auth.clone(),
+            rate_limit::limit,
+        ));
+
+    let ws_route = Router::new().route("/ws", get(ws::ws_handler)).route_layer(
+        middleware::from_fn_with_state(state.rate_limits.ws.clone(), rate_limit::limit),
+    );
+
+    Router::new()
+        .route("/health", get(|| async { "OK" }))
+        .route("/api/openapi.json", get(openapi::openapi_json))
+        .route("/api/docs", get(openapi::docs))
+        .route("/api/games/active", get(games::active_games))
+        .route("/api/games/{id}/transcript", get(games::transcript))
+        .route("/api/rooms/{id}/spectate", post(spectate::create_link))
+        .route("/api/spectate/events", get(spectate::events))
+        .route(
+            "/api/me/webhook",
+            get(webhooks::get_webhook)
+                .put(webhooks::set_webhook)
+                .delete(webhooks::delete_webhook),
+        )
+        .route("/api/queues", get(queues::list_queues))
+        .route("/api/rooms", get(rooms::list_rooms))
+        .route("/api/cosmetics", get(cosmetics::catalog))
+        .route(
+            "/api/profile/avatar",
+            post(avatars::upload_avatar)
+                .delete(avatars::delete_avatar)
+                .layer(DefaultBodyLimit::max(avatars::MAX_UPLOAD_BYTES)),
+        )
+        .route("/api/avatars/{user_id}", get(avatars::get_avatar))
+        .route("/api/avatars/bots/{index}", get(avatars::get_bot_avatar))
+        .route("/api/me/username", put(usernames::rename))
+        .route("/api/me/bot-level", get(practice::bot_level))
+        .route(
+            "/api/me/cosmetics",
+            get(cosmetics::get_cosmetics).put(cosmetics::set_cosmetics),
+        )
+        .route("/api/titles", get(titles::catalog))
+        .route("/api/me/titles", get(titles::get_titles))
+        .route("/api/me/title", put(titles::select_title))
+        .route("/api/users/{user_id}/titles", get(titles::user_titles))
+        .route("/api/seasons", get(seasons::list_seasons))
+        .route("/api/stats", get(stats::global_stats))
+        .route(
+            "/api/head-to-head/{user_id}/{opponent_id}",
+            get(stats::head_to_head),
+        )
+        .route("/api/tournaments", get(tournaments::list_tournaments))
+        .route("/api/leagues", get(leagues::list_leagues))
+        .route(
+            "/api/scheduled-games",
+            get(scheduled_games::list_scheduled_games).post(scheduled_games::schedule_game),
+        )
+        .route(
+            "/api/scheduled-games/{id}",
+            get(scheduled_games::get_scheduled_game),
+        )
+        .route(
+            "/api/scheduled-games/{id}/invite-links",
+            post(scheduled_games::create_invite_link),
+        )
+        .route("/api/invites/{token}", post(scheduled_games::redeem_invite))
+        .route(
+            "/api/scheduled-games/{id}/rsvp",
+            post(scheduled_games::rsvp).delete(scheduled_games::withdraw_rsvp),
+        )
+        .route("/api/leagues/{id}", get(leagues::get_league))
+        .route("/api/leagues/{id}/standings", get(leagues::standings))
+        .route(
+            "/api/leagues/{id}/fixtures/{fixture_id}/play",
+            post(leagues::play_fixture),
+        )
+        .route("/api/tournaments/{id}", get(tournaments::get_tournament))
+        .route(
+            "/api/tournaments/{id}/register",
+            post(tournaments::register).delete(tournaments::unregister),
+        )
+        .route("/api/seasons/{id}/leaderboard", get(seasons::leaderboard))
+        .route("/api/push/vapid-key", get(push::vapid_key))
+        .route(
+            "/api/me/push/subscriptions",
+            post(push::subscribe).delete(push::unsubscribe),
+        )
+        .route("/api/me/push/pending", get(push::pending))
+        .merge(moderation)
+        .merge(admin)
+        .merge(dev)
+        .merge(auth_routes)
+        .merge(ws_route)
+        .layer(
+            TraceLayer::new_for_http().make_span_with(DefaultMakeSpan::new().include_headers(true)),
+        )
+        .layer(middleware::from_fn(problem::render))
+        .layer(middleware::from_fn(request_id::propagate))
         .layer(cors)
-        .with_state(state);
+        .with_state(state)
+}
 
-    let listener = TcpListener::bind("0.0.0.0:3000")
-        .await
-        .expect("Failed to bind to port 3000");
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
 
-    println!("Server running on http://0.0.0.0:3000");
+    async fn start_test_server() -> (SocketAddr, JoinHandle<()>) {
+        let config = Config {
+            host: "127.0.0.1".to_string(),
+            port: 0,
+            ..Config::default()
+        };
 
-    axum::serve(listener, app)
-        .await
-        .expect("Server failed");
+        let (addr, handle) = start_server(&config).await;
+        let BoundAddr::Tcp(addr) = addr else {
+            panic!("Expected a TCP address, got {}", addr);
+        };
+        (addr, handle)
+    }
+
+    async fn get(addr: SocketAddr, path: &str) -> String {
+        send_get(addr, path, "").await
+    }
+
+    async fn get_authorized(addr: SocketAddr, path: &str, token: &str) -> String {
+        send_get(addr, path, &format!("Authorization: Bearer {}\r\n", token)).await
+    }
+
+    async fn send_get(addr: SocketAddr, path: &str, headers: &str) -> String {
+        send(addr, "GET", path, headers, "").await
+    }
+
+    async fn send_json(
+        addr: SocketAddr,
+        method: &str,
+        path: &str,
+        token: Option<&str>,
+        body: serde_json::Value,
+    ) -> String {
+        let mut headers = "Content-Type: application/json\r\n".to_string();
+        if let Some(token) = token {
+            headers.push_str(&format!("Authorization: Bearer {}\r\n", token));
+        }
+        send(addr, method, path, &headers, &body.to_string()).await
+    }
+
+    async fn send(addr: SocketAddr, method: &str, path: &str, headers: &str, body: &str) -> String {
+        let mut stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+        let request = format!(
+            "{} {} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\nContent-Length: {}\r\n{}\r\n{}",
+            method,
+            path,
+            body.len(),
+            headers,
+            body
+        );
+        stream.write_all(request.as_bytes()).await.unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        response
+    }
+
+    /// The JSON body of a `Connection: close` response.
+    fn json_body(response: &str) -> serde_json::Value {
+        let body = response.split_once("\r\n\r\n").expect("headers end").1;
+        serde_json::from_str(body).unwrap_or_else(|_| panic!("not JSON: {}", response))
+    }
+
+    /// Registers an account; returns its login token and id.
+    async fn register(addr: SocketAddr, username: &str) -> (String, String) {
+        let credentials = serde_json::json!({ "username": username, "password": "hunter22" });
+        let response = send_json(addr, "POST", "/api/auth/register", None, credentials).await;
+        let body = json_body(&response);
+        (
+            body["token"].as_str().unwrap().to_string(),
+            body["user_id"].as_str().unwrap().to_string(),
+        )
+    }
+
+    /// Starts on a fresh database file whose "root" account was promoted at start-up, and
+    /// returns root's login token too. Remove the file once done.
+    async fn start_server_with_admin(name: &str) -> (SocketAddr, JoinHandle<()>, String, PathBuf) {
+        let path = std::env::temp_dir().join(format!("carioca-{}-{}.db", name, std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let config = Config {
+            host: "127.0.0.1".to_string(),
+            port: 0,
+            database_url: format!("sqlite://{}?mode=rwc", path.display()),
+            admin_usernames: vec!["root".to_string()],
+            ..Config::default()
+        };
+        let (BoundAddr::Tcp(addr), handle) = start_server(&config).await else {
+            panic!("Expected a TCP address");
+        };
+        let (token, _) = register(addr, "root").await;
+        handle.abort();
+        let (BoundAddr::Tcp(addr), handle) = start_server(&config).await else {
+            panic!("Expected a TCP address");
+        };
+        (addr, handle, token, path)
+    }
+
+    /// Asks to upgrade `/ws?{query}`; returns the response head and the connection.
+    async fn open_ws(addr: SocketAddr, query: &str) -> (String, tokio::net::TcpStream) {
+        let mut stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+        let request = format!(
+            "GET /ws?{} HTTP/1.1\r\nHost: localhost\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\nSec-WebSocket-Version: 13\r\n\r\n",
+            query
+        );
+        stream.write_all(request.as_bytes()).await.unwrap();
+        let mut head = Vec::new();
+        while !head.ends_with(b"\r\n\r\n") {
+            head.push(stream.read_u8().await.unwrap());
+        }
+        (String::from_utf8(head).unwrap(), stream)
+    }
+
+    /// Reads server messages until one of type `kind` arrives, and returns it.
+    async fn ws_message(stream: &mut tokio::net::TcpStream, kind: &str) -> serde_json::Value {
+        let read = async {
+            loop {
+                let opcode = stream.read_u8().await.unwrap() & 0x0F;
+                let length = match stream.read_u8().await.unwrap() & 0x7F {
+                    126 => stream.read_u16().await.unwrap() as usize,
+                    127 => stream.read_u64().await.unwrap() as usize,
+                    length => length as usize,
+                };
+                let mut payload = vec![0; length];
+                stream.read_exact(&mut payload).await.unwrap();
+                assert_ne!(opcode, 8, "Closed while waiting for {}", kind);
+                if opcode != 1 {
+                    continue;
+                }
+                let message: serde_json::Value = serde_json::from_slice(&payload).unwrap();
+                if message["type"] == kind {
+                    return message;
+                }
+            }
+        };
+        tokio::time::timeout(std::time::Duration::from_secs(10), read)
+            .await
+            .unwrap_or_else(|_| panic!("No {} arrived", kind))
+    }
+
+    /// Sends a client message in a masked text frame, as clients must.
+    async fn ws_send(stream: &mut tokio::net::TcpStream, message: serde_json::Value) {
+        let payload = message.to_string().into_bytes();
+        let mask = [0x5A, 0x17, 0xC3, 0x08];
+        let mut frame = vec![0x81];
+        if payload.len() < 126 {
+            frame.push(0x80 | payload.len() as u8);
+        } else {
+            frame.push(0x80 | 126);
+            frame.extend((payload.len() as u16).to_be_bytes());
+        }
+        frame.extend(mask);
+        frame.extend(payload.iter().zip(mask.iter().cycle()).map(|(b, m)| b ^ m));
+        stream.write_all(&frame).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn serves_health_on_ephemeral_port() {
+        let (addr, handle) = start_test_server().await;
+        assert_ne!(addr.port(), 0, "Should report the port the OS picked");
+
+        let response = get(addr, "/health").await;
+        assert!(response.starts_with("HTTP/1.1 200"), "got: {}", response);
+        assert!(response.ends_with("OK"));
+
+        handle.abort();
+    }
+
+    #[tokio::test]
+    async fn read_pool_opens_the_database_read_only() {
+        let path = std::env::temp_dir().join(format!("carioca-read-{}.db", std::process::id()));
+        let config = Config {
+            database_url: format!("sqlite://{}?mode=rwc", path.display()),
+            ..Config::default()
+        };
+        let pool = SqlitePoolOptions::new()
+            .connect(&config.database_url)
+            .await
+            .unwrap();
+        crate::db::repo::create_global_stats_table(&pool)
+            .await
+            .unwrap();
+
+        let read_pool = connect_read_pool(&config, &pool).await;
+        assert!(
+            sqlx::query("SELECT * FROM global_stats")
+                .fetch_all(&read_pool)
+                .await
+                .is_ok()
+        );
+        assert!(
+            sqlx::query("CREATE TABLE scratch (id INTEGER)")
+                .execute(&read_pool)
+                .await
+                .is_err()
+        );
+
+        read_pool.close().await;
+        pool.close().await;
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[tokio::test]
+    async fn reads_go_ahead_while_a_write_is_open() {
+        let path = std::env::temp_dir().join(format!("carioca-wal-{}.db", std::process::id()));
+        let config = Config {
+            database_url: format!("sqlite://{}?mode=rwc", path.display()),
+            ..Config::default()
+        };
+        let pool = connect_write_pool(&config).await;
+        sqlx::query("CREATE TABLE scratch (id INTEGER)")
+            .execute(&pool)
+            .await
+            .unwrap();
+        let read_pool = connect_read_pool(&config, &pool).await;
+        let count = |pool: SqlitePool| async move {
+            let row: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM scratch")
+                .fetch_one(&pool)
+                .await
+                .expect("The read should not wait for the write");
+            row.0
+        };
+
+        let mut write = pool.begin().await.unwrap();
+        sqlx::query("INSERT INTO scratch (id) VALUES (1)")
+            .execute(&mut *write)
+            .await
+            .unwrap();
+        assert_eq!(count(read_pool.clone()).await, 0);
+        assert_eq!(count(pool.clone()).await, 0, "Nor on the write pool");
+
+        // A reader mid-transaction doesn't hold up the commit either
+        let mut read = read_pool.begin().await.unwrap();
+        sqlx::query("SELECT COUNT(*) FROM scratch")
+            .fetch_one(&mut *read)
+            .await
+            .unwrap();
+        tokio::time::timeout(Duration::from_secs(1), write.commit())
+            .await
+            .expect("The commit should not wait for the reader")
+            .unwrap();
+        read.rollback().await.unwrap();
+        assert_eq!(count(read_pool.clone()).await, 1);
+
+        read_pool.close().await;
+        pool.close().await;
+        for suffix in ["", "-wal", "-shm"] {
+            let _ = std::fs::remove_file(format!("{}{}", path.display(), suffix));
+        }
+    }
+
+    #[tokio::test]
+    async fn admin_names_are_promoted_at_startup_only_and_roles_are_read_fresh() {
+        let path = std::env::temp_dir().join(format!("carioca-admins-{}.db", std::process::id()));
+        let config = Config {
+            host: "127.0.0.1".to_string(),
+            port: 0,
+            database_url: format!("sqlite://{}?mode=rwc", path.display()),
+            admin_usernames: vec!["root".to_string()],
+            ..Config::default()
+        };
+        let credentials = serde_json::json!({ "username": "root", "password": "hunter22" });
+        let tcp = |addr: BoundAddr| match addr {
+            BoundAddr::Tcp(addr) => addr,
+            other => panic!("Expected a TCP address, got {}", other),
+        };
+
+        let (addr, handle) = start_server(&config).await;
+        let addr = tcp(addr);
+        let registered = send_json(
+            addr,
+            "POST",
+            "/api/auth/register",
+            None,
+            credentials.clone(),
+        )
+        .await;
+        let registered = json_body(&registered);
+        assert_eq!(
+            registered["role"], "player",
+            "Claiming a listed name grants nothing"
+        );
+        let player_token = registered["token"].as_str().unwrap().to_string();
+        let user_id = registered["user_id"].as_str().unwrap().to_string();
+        let response = get_authorized(addr, "/api/admin/jobs", &player_token).await;
+        assert!(response.starts_with("HTTP/1.1 403"), "got: {}", response);
+        handle.abort();
+
+        // The account exists now, so the next start-up promotes it
+        let (addr, handle) = start_server(&config).await;
+        let addr = tcp(addr);
+        let response = get_authorized(addr, "/api/admin/jobs", &player_token).await;
+        assert!(
+            response.starts_with("HTTP/1.1 200"),
+            "The role is read from the account, not the token, got: {}",
+            response
+        );
+        let logged_in = send_json(addr, "POST", "/api/auth/login", None, credentials).await;
+        let admin_token = json_body(&logged_in)["token"].as_str().unwrap().to_string();
+
+        let demote = send_json(
+            addr,
+            "PUT",
+            &format!("/api/admin/users/{}/role", user_id),
+            Some(&admin_token),
+            serde_json::json!({ "role": "player" }),
+        )
+        .await;
+        assert!(demote.starts_with("HTTP/1.1 204"), "got: {}", demote);
+        let response = get_authorized(addr, "/api/admin/jobs", &admin_token).await;
+        assert!(
+            response.starts_with("HTTP/1.1 403"),
+            "Demotions apply to tokens already issued, got: {}",
+            response
+        );
+
+        handle.abort();
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[tokio::test]
+    async fn admin_routes_require_a_token() {
+        let (addr, handle) = start_test_server().await;
+
+        let response = get(addr, "/api/admin/reports").await;
+        assert!(response.starts_with("HTTP/1.1 401"), "got: {}", response);
+
+        handle.abort();
+    }
+
+    #[tokio::test]
+    async fn active_games_need_a_login_token() {
+        let (addr, handle) = start_test_server().await;
+        let token = auth::create_reconnect_token("ana", "room").0;
+
+        let response = get(addr, "/api/games/active").await;
+        assert!(response.starts_with("HTTP/1.1 401"), "got: {}", response);
+        let response = get_authorized(addr, "/api/games/active", &token).await;
+        assert!(
+            response.starts_with("HTTP/1.1 401"),
+            "Reconnect tokens are not login tokens, got: {}",
+            response
+        );
+
+        handle.abort();
+    }
+
+    #[tokio::test]
+    async fn a_reconnect_token_alone_takes_back_only_its_own_seat() {
+        let (addr, handle) = start_test_server().await;
+        let (token, user_id) = register(addr, "anita").await;
+        let (head, mut socket) =
+            open_ws(addr, &format!("token={}&mode=instant&bots=easy", token)).await;
+        assert!(head.starts_with("HTTP/1.1 101"), "got: {}", head);
+        let found = ws_message(&mut socket, "MatchFound").await;
+        let room_id = found["payload"]["room_id"].as_str().unwrap().to_string();
+        let rejoin = found["payload"]["reconnect_token"]
+            .as_str()
+            .unwrap()
+            .to_string();
+        drop(socket);
+
+        let (head, mut socket) = open_ws(addr, &format!("rejoin={}", rejoin)).await;
+        assert!(head.starts_with("HTTP/1.1 101"), "got: {}", head);
+        let found = ws_message(&mut socket, "MatchFound").await;
+        assert_eq!(found["payload"]["room_id"], room_id.as_str());
+        ws_message(&mut socket, "GameStateUpdate").await;
+
+        // Signed for the same player, but for a table they don't sit at
+        let elsewhere = auth::create_reconnect_token(&user_id, "some-other-room").0;
+        let (head, mut socket) = open_ws(addr, &format!("rejoin={}", elsewhere)).await;
+        assert!(head.starts_with("HTTP/1.1 101"), "got: {}", head);
+        let error = ws_message(&mut socket, "Error").await;
+        assert_eq!(error["payload"]["code"], "game_not_running");
+
+        handle.abort();
+    }
+
+    #[tokio::test]
+    async fn reconnect_tokens_only_work_for_their_own_player() {
+        let (addr, handle) = start_test_server().await;
+        let (_, anita) = register(addr, "anita").await;
+        let (beto_token, _) = register(addr, "beto").await;
+        let rejoin = auth::create_reconnect_token(&anita, "room").0;
+
+        let (head, _) = open_ws(addr, &format!("token={}&rejoin={}", beto_token, rejoin)).await;
+        assert!(head.starts_with("HTTP/1.1 401"), "got: {}", head);
+        let (head, _) = open_ws(addr, "rejoin=not-a-token").await;
+        assert!(head.starts_with("HTTP/1.1 401"), "got: {}", head);
+        let (head, _) = open_ws(addr, "").await;
+        assert!(
+            head.starts_with("HTTP/1.1 401"),
+            "No token at all, got: {}",
+            head
+        );
+
+        handle.abort();
+    }
+
+    #[tokio::test]
+    async fn a_banned_player_cannot_rejoin_with_a_reconnect_token() {
+        let (addr, handle, admin_token, path) = start_server_with_admin("rejoin-ban").await;
+        let (_, anita) = register(addr, "anita").await;
+        let ban = send_json(
+            addr,
+            "POST",
+            &format!("/api/admin/users/{}/sanctions", anita),
+            Some(&admin_token),
+            serde_json::json!({ "kind": "ban", "reason": "cheating" }),
+        )
+        .await;
+        assert!(ban.starts_with("HTTP/1.1 201"), "got: {}", ban);
+
+        let rejoin = auth::create_reconnect_token(&anita, "room").0;
+        let (head, _) = open_ws(addr, &format!("rejoin={}", rejoin)).await;
+        assert!(head.starts_with("HTTP/1.1 403"), "got: {}", head);
+
+        handle.abort();
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[tokio::test]
+    async fn banned_players_are_turned_away_and_muted_ones_cannot_chat() {
+        let (addr, handle, admin_token, path) = start_server_with_admin("sanctions").await;
+        let (anita_token, anita) = register(addr, "anita").await;
+        let (beto_token, beto) = register(addr, "beto").await;
+        let sanction = |user: String, kind: &'static str| {
+            let admin_token = admin_token.clone();
+            async move {
+                let path = format!("/api/admin/users/{}/sanctions", user);
+                let body = serde_json::json!({ "kind": kind, "reason": "abuse" });
+                let response = send_json(addr, "POST", &path, Some(&admin_token), body).await;
+                assert!(response.starts_with("HTTP/1.1 201"), "got: {}", response);
+            }
+        };
+        sanction(anita, "ban").await;
+        sanction(beto, "mute").await;
+
+        let credentials = serde_json::json!({ "username": "anita", "password": "hunter22" });
+        let login = send_json(addr, "POST", "/api/auth/login", None, credentials).await;
+        assert!(login.starts_with("HTTP/1.1 403"), "got: {}", login);
+        let (head, _) = open_ws(addr, &format!("token={}", anita_token)).await;
+        assert!(head.starts_with("HTTP/1.1 403"), "got: {}", head);
+
+        let (head, mut socket) = open_ws(addr, &format!("token={}", beto_token)).await;
+        assert!(head.starts_with("HTTP/1.1 101"), "got: {}", head);
+        ws_send(
+            &mut socket,
+            serde_json::json!({ "type": "Chat", "payload": { "text": "hola" } }),
+        )
+        .await;
+        let error = ws_message(&mut socket, "Error").await;
+        assert_eq!(error["payload"]["code"], "muted");
+
+        handle.abort();
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[tokio::test]
+    async fn invite_links_are_single_use_host_only_and_bound_to_their_game() {
+        let (addr, handle, _, path) = start_server_with_admin("invite-links").await;
+        let (host, _) = register(addr, "anita").await;
+        let (guest, _) = register(addr, "bruno").await;
+        let (other, _) = register(addr, "carla").await;
+        let starts_at = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+            + 3600;
+        let schedule = |name: &'static str, public: bool| {
+            let body =
+                serde_json::json!({ "name": name, "starts_at": starts_at, "public": public });
+            let host = host.clone();
+            async move {
+                let response =
+                    send_json(addr, "POST", "/api/scheduled-games", Some(&host), body).await;
+                assert!(response.starts_with("HTTP/1.1 201"), "got: {}", response);
+                json_body(&response)["id"].as_i64().unwrap()
+            }
+        };
+        let first = schedule("Viernes", true).await;
+        let second = schedule("Sábado", false).await;
+        let create_link = |game: i64, token: String| async move {
+            let path = format!("/api/scheduled-games/{}/invite-links", game);
+            send_json(addr, "POST", &path, Some(&token), serde_json::json!({})).await
+        };
+        let redeem = |link: String, token: String| async move {
+            let path = format!("/api/invites/{}", link);
+            send_json(addr, "POST", &path, Some(&token), serde_json::json!({})).await
+        };
+
+        let refused = create_link(first, guest.clone()).await;
+        assert!(refused.starts_with("HTTP/1.1 403"), "got: {}", refused);
+
+        let created = create_link(first, host.clone()).await;
+        assert!(created.starts_with("HTTP/1.1 201"), "got: {}", created);
+        let link = json_body(&created)["token"].as_str().unwrap().to_string();
+        for _ in 0..2 {
+            let redeemed = redeem(link.clone(), guest.clone()).await;
+            assert!(redeemed.starts_with("HTTP/1.1 200"), "got: {}", redeemed);
+            assert_eq!(json_body(&redeemed)["game"]["id"], first);
+        }
+        let taken = redeem(link.clone(), other.clone()).await;
+        assert!(taken.starts_with("HTTP/1.1 410"), "got: {}", taken);
+
+        // A validly signed link naming another game still only opens the game it was made for
+        let created = create_link(first, host.clone()).await;
+        let link = json_body(&created)["token"].as_str().unwrap().to_string();
+        let jti = auth::decode_invite_token(&link).unwrap().jti;
+        let moved = auth::create_invite_token(second, &jti).0;
+        let refused = redeem(moved, other.clone()).await;
+        assert!(refused.starts_with("HTTP/1.1 410"), "got: {}", refused);
+        let hidden =
+            get_authorized(addr, &format!("/api/scheduled-games/{}", second), &other).await;
+        assert!(hidden.starts_with("HTTP/1.1 404"), "got: {}", hidden);
+
+        let db = sqlx::SqlitePool::connect(&format!("sqlite://{}", path.display()))
+            .await
+            .unwrap();
+        crate::db::repo::update_scheduled_game_status(&db, first, "started", Some("room"))
+            .await
+            .unwrap();
+        let late = redeem(link, other.clone()).await;
+        assert!(late.starts_with("HTTP/1.1 409"), "got: {}", late);
+        let late = create_link(first, host.clone()).await;
+        assert!(late.starts_with("HTTP/1.1 409"), "got: {}", late);
+
+        db.close().await;
+        handle.abort();
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[tokio::test]
+    async fn share_links_are_refused_while_a_player_objects_to_spectators() {
+        let (addr, handle) = start_test_server().await;
+        let (token, _) = register(addr, "anita").await;
+        let (_, mut socket) =
+            open_ws(addr, &format!("token={}&mode=instant&bots=easy", token)).await;
+        let found = ws_message(&mut socket, "MatchFound").await;
+        let link_path = format!(
+            "/api/rooms/{}/spectate",
+            found["payload"]["room_id"].as_str().unwrap()
+        );
+        let share = |token: String| {
+            let link_path = link_path.clone();
+            async move {
+                send_json(
+                    addr,
+                    "POST",
+                    &link_path,
+                    Some(&token),
+                    serde_json::json!({}),
+                )
+                .await
+            }
+        };
+        let shared = share(token.clone()).await;
+        assert!(shared.starts_with("HTTP/1.1 200"), "got: {}", shared);
+
+        for allowed in [false, true] {
+            let vote =
+                serde_json::json!({ "type": "AllowSpectators", "payload": { "allowed": allowed } });
+            ws_send(&mut socket, vote).await;
+            let changed = ws_message(&mut socket, "SpectatorsChanged").await;
+            assert_eq!(changed["payload"]["allowed"], allowed);
+            let shared = share(token.clone()).await;
+            let status = if allowed {
+                "HTTP/1.1 200"
+            } else {
+                "HTTP/1.1 403"
+            };
+            assert!(shared.starts_with(status), "got: {}", shared);
+        }
+
+        handle.abort();
+    }
+
+    #[tokio::test]
+    async fn hot_seat_messages_must_name_one_of_the_connections_seats() {
+        let (addr, handle) = start_test_server().await;
+        let (token, user_id) = register(addr, "anita").await;
+        let (_, mut socket) = open_ws(addr, &format!("token={}&mode=hotseat&seats=2", token)).await;
+        let state = ws_message(&mut socket, "GameStateUpdate").await;
+        let acting = state["payload"]["active_seat_id"]
+            .as_str()
+            .unwrap()
+            .to_string();
+        assert!(acting == user_id || acting == format!("{}#2", user_id));
+
+        let foreign = format!("{}#3", user_id);
+        for seat_id in [None, Some("someone-else"), Some(foreign.as_str())] {
+            let draw = serde_json::json!({ "type": "DrawFromDeck", "seat_id": seat_id, "correlation_id": "c1" });
+            ws_send(&mut socket, draw).await;
+            let error = ws_message(&mut socket, "Error").await;
+            assert_eq!(
+                error["payload"]["code"], "unknown_seat",
+                "seat {:?}",
+                seat_id
+            );
+            assert_eq!(error["payload"]["correlation_id"], "c1");
+        }
+
+        let draw = serde_json::json!({ "type": "DrawFromDeck", "seat_id": acting });
+        ws_send(&mut socket, draw).await;
+        let state = ws_message(&mut socket, "GameStateUpdate").await;
+        assert_eq!(state["payload"]["active_seat_id"], acting.as_str());
+        assert_eq!(state["payload"]["my_hand"].as_array().unwrap().len(), 13);
+
+        handle.abort();
+    }
+
+    #[tokio::test]
+    async fn the_spectator_event_stream_carries_no_hand() {
+        let (addr, handle) = start_test_server().await;
+        let (token, _) = register(addr, "anita").await;
+        let (_, mut socket) =
+            open_ws(addr, &format!("token={}&mode=instant&bots=easy", token)).await;
+        let found = ws_message(&mut socket, "MatchFound").await;
+        let ballot = ws_message(&mut socket, "RuleVoteOpened").await["payload"].clone();
+        let vote = serde_json::json!({ "type": "VoteRules", "payload": {
+            "preset": ballot["default_preset"],
+            "turn_seconds": ballot["default_turn_seconds"],
+        } });
+        ws_send(&mut socket, vote).await;
+        // Once dealt, the player sees their own hand
+        while ws_message(&mut socket, "GameStateUpdate").await["payload"]["my_hand"]
+            .as_array()
+            .unwrap()
+            .is_empty()
+        {}
+        let link_path = format!(
+            "/api/rooms/{}/spectate",
+            found["payload"]["room_id"].as_str().unwrap()
+        );
+        let link = send_json(
+            addr,
+            "POST",
+            &link_path,
+            Some(&token),
+            serde_json::json!({}),
+        )
+        .await;
+        let events_path = json_body(&link)["events_path"]
+            .as_str()
+            .unwrap()
+            .to_string();
+
+        let mut stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+        let request = format!("GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", events_path);
+        stream.write_all(request.as_bytes()).await.unwrap();
+        let read = async {
+            let mut received = Vec::new();
+            loop {
+                let mut chunk = [0u8; 4096];
+                let n = stream.read(&mut chunk).await.unwrap();
+                assert_ne!(n, 0, "The stream ended early");
+                received.extend_from_slice(&chunk[..n]);
+                let text = String::from_utf8_lossy(&received).into_owned();
+                let event = text
+                    .lines()
+                    .filter_map(|line| line.strip_prefix("data: "))
+                    .filter_map(|data| serde_json::from_str::<serde_json::Value>(data).ok())
+                    .find(|msg| msg["type"] == "GameStateUpdate");
+                if let Some(event) = event {
+                    return (text, event);
+                }
+            }
+        };
+        let (text, state) = tokio::time::timeout(std::time::Duration::from_secs(10), read)
+            .await
+            .expect("No state update arrived");
+        assert!(text.starts_with("HTTP/1.1 200"), "got: {}", text);
+        assert!(text.contains("text/event-stream"), "got: {}", text);
+
+        let payload = &state["payload"];
+        assert_eq!(payload["my_hand"], serde_json::json!([]));
+        let players = payload["players"].as_array().unwrap();
+        assert!(!players.is_empty());
+        for player in players {
+            assert!(player["hand_count"].as_u64().unwrap() > 0);
+            assert!(player.get("hand").is_none(), "got: {}", player);
+        }
+
+        handle.abort();
+    }
+
+    #[tokio::test]
+    async fn share_link_spectators_are_read_only_and_see_no_hand() {
+        let (addr, handle) = start_test_server().await;
+        let (token, user_id) = register(addr, "anita").await;
+        let (_, mut socket) =
+            open_ws(addr, &format!("token={}&mode=instant&bots=easy", token)).await;
+        let found = ws_message(&mut socket, "MatchFound").await;
+        let link_path = format!(
+            "/api/rooms/{}/spectate",
+            found["payload"]["room_id"].as_str().unwrap()
+        );
+        let link = send_json(
+            addr,
+            "POST",
+            &link_path,
+            Some(&token),
+            serde_json::json!({}),
+        )
+        .await;
+        let spectate = json_body(&link)["token"].as_str().unwrap().to_string();
+
+        let (head, mut watching) = open_ws(addr, &format!("spectate={}", spectate)).await;
+        assert!(head.starts_with("HTTP/1.1 101"), "got: {}", head);
+        let state = ws_message(&mut watching, "GameStateUpdate").await;
+        assert_eq!(state["payload"]["my_hand"], serde_json::json!([]));
+        for player in state["payload"]["players"].as_array().unwrap() {
+            assert!(player.get("hand").is_none(), "got: {}", player);
+        }
+
+        let chat = |text: &str| serde_json::json!({ "type": "Chat", "payload": { "text": text } });
+        ws_send(&mut watching, chat("desde la galería")).await;
+        tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+        ws_send(&mut socket, chat("hola")).await;
+        for stream in [&mut socket, &mut watching] {
+            let heard = ws_message(stream, "Chat").await;
+            assert_eq!(heard["payload"]["player_id"], user_id.as_str());
+            assert_eq!(heard["payload"]["text"], "hola");
+        }
+
+        handle.abort();
+    }
+
+    #[tokio::test]
+    async fn errors_carry_their_catalog_key_and_the_rest_fall_back_to_the_status() {
+        let (addr, handle) = start_test_server().await;
+        register(addr, "ana").await;
+
+        let wrong = serde_json::json!({ "username": "ana", "password": "wrong-one" });
+        let response = send_json(addr, "POST", "/api/auth/login", None, wrong).await;
+        assert!(
+            response.contains("application/problem+json"),
+            "{}",
+            response
+        );
+        let problem = json_body(&response);
+        assert_eq!(problem["code"], "invalid_credentials");
+        assert_eq!(problem["message"], "Invalid credentials");
+        assert!(problem["request_id"].is_string());
+
+        let response = send_json(
+            addr,
+            "GET",
+            "/api/games/active",
+            Some("garbage"),
+            serde_json::json!({}),
+        )
+        .await;
+        assert_eq!(json_body(&response)["code"], "invalid_token");
+
+        let response = send_json(
+            addr,
+            "GET",
+            "/api/no-such-route",
+            None,
+            serde_json::json!({}),
+        )
+        .await;
+        let problem = json_body(&response);
+        assert_eq!(
+            (problem["status"].as_u64(), problem["code"].as_str()),
+            (Some(404), Some("not_found"))
+        );
+
+        handle.abort();
+    }
+
+    #[tokio::test]
+    async fn every_documented_route_reaches_a_handler() {
+        let (addr, handle) = start_test_server().await;
+        let doc = crate::api::openapi::document();
+        let mut checked = 0;
+        for (path, methods) in doc["paths"].as_object().unwrap() {
+            let concrete: String = path
+                .split('/')
+                .map(|segment| match segment.starts_with('{') {
+                    true => "1",
+                    false => segment,
+                })
+                .collect::<Vec<_>>()
+                .join("/");
+            for method in methods.as_object().unwrap().keys() {
+                let method = method.to_uppercase();
+                let response =
+                    send_json(addr, &method, &concrete, None, serde_json::json!({})).await;
+                let status = response.split(' ').nth(1).unwrap_or_default();
+                // The router's own misses carry no message of their own
+                let unrouted = status == "405"
+                    || (status == "404" && json_body(&response)["message"] == "Not Found");
+                assert!(!unrouted, "{} {} is not served: {}", method, path, response);
+                checked += 1;
+            }
+        }
+        assert!(checked > 40, "Checked only {} routes", checked);
+
+        handle.abort();
+    }
 }
@@ -0,0 +1,158 @@
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{
+        IntoResponse,
+        sse::{Event, KeepAlive, Sse},
+    },
+};
+use futures_util::stream;
+use serde::{Deserialize, Serialize};
+use std::convert::Infallible;
+use std::sync::Arc;
+use tokio::sync::{mpsc, oneshot};
+
+use crate::api::admission::{Admission, Permit};
+use crate::api::auth;
+use crate::api::authz::AuthUser;
+use crate::api::events::ServerMessage;
+use crate::api::problem::Problem;
+use crate::api::server::AppState;
+use crate::matchmaking::room::RoomEvent;
+
+#[derive(Serialize)]
+pub struct SpectateLink {
+    pub token: String,
+    /// WebSocket path a spectator connects to
+    pub path: String,
+    /// Server-sent events path streaming the same updates, for clients that can't open
+    /// a WebSocket
+    pub events_path: String,
+    /// Epoch seconds after which the link stops working
+    pub expires_at: usize,
+}
+
+/// Issues a read-only share link for a room the caller is playing in.
+pub async fn create_link(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Path(room_id): Path<String>,
+) -> impl IntoResponse {
+    let room_tx = state.active_rooms.lock().await.get(&room_id).cloned();
+    let Some(room_tx) = room_tx else {
+        return Problem::new(StatusCode::NOT_FOUND, "Room not found").into_response();
+    };
+
+    let (reply_tx, reply_rx) = oneshot::channel();
+    if room_tx
+        .send(RoomEvent::HasPlayer(user.user_id, reply_tx))
+        .await
+        .is_err()
+    {
+        return Problem::new(StatusCode::NOT_FOUND, "Room not found").into_response();
+    }
+    if !reply_rx.await.unwrap_or(false) {
+        return Problem::new(
+            StatusCode::FORBIDDEN,
+            "Only players at the table can share it",
+        )
+        .into_response();
+    }
+    let (reply_tx, reply_rx) = oneshot::channel();
+    let _ = room_tx.send(RoomEvent::AllowsSpectators(reply_tx)).await;
+    if !reply_rx.await.unwrap_or(false) {
+        return Problem::new(
+            StatusCode::FORBIDDEN,
+            "Spectating is turned off at this table",
+        )
+        .into_response();
+    }
+
+    let (token, expires_at) = auth::create_spectate_token(&room_id);
+    Json(SpectateLink {
+        path: format!("/ws?spectate={}", token),
+        events_path: format!("/api/spectate/events?token={}", token),
+        token,
+        expires_at,
+    })
+    .into_response()
+}
+
+#[derive(Deserialize)]
+pub struct SpectateEventsQuery {
+    /// The spectate token from a share link; `EventSource` can't send headers
+    pub token: String,
+}
+
+/// Leaves the room and frees the connection slot once the event stream is dropped,
+/// i.e. when the client goes away.
+struct SpectatorGuard {
+    room_tx: mpsc::Sender<RoomEvent>,
+    spectator_id: String,
+    _permit: Permit,
+}
+
+impl Drop for SpectatorGuard {
+    fn drop(&mut self) {
+        let room_tx = self.room_tx.clone();
+        let spectator_id = std::mem::take(&mut self.spectator_id);
+        tokio::spawn(async move {
+            let _ = room_tx.send(RoomEvent::SpectatorLeft(spectator_id)).await;
+        });
+    }
+}
+
+/// Read-only spectating over server-sent events, for networks whose proxies block
+/// WebSockets. Each event's data is a `ServerMessage` exactly as the `/ws?spectate=` mode
+/// sends it; the room feeds both through its spectator list, which ends the stream of a
+/// client that falls a full queue behind rather than wait for it.
+pub async fn events(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<SpectateEventsQuery>,
+) -> impl IntoResponse {
+    let Some(claims) = auth::decode_spectate_token(&query.token) else {
+        return Problem::keyed(StatusCode::UNAUTHORIZED, "invalid_token").into_response();
+    };
+    let room_tx = state
+        .active_rooms
+        .lock()
+        .await
+        .get(&claims.room_id)
+        .cloned();
+    let Some(room_tx) = room_tx else {
+        return Problem::new(StatusCode::NOT_FOUND, "Room not found").into_response();
+    };
+    // Counts against the same cap as WebSocket spectators, who don't queue either
+    let Admission::Admitted(permit) = state.capacity.sockets.enter() else {
+        return Problem::keyed(StatusCode::SERVICE_UNAVAILABLE, "server_full").into_response();
+    };
+
+    let (client_tx, client_rx) = mpsc::channel::<ServerMessage>(100);
+    let spectator_id = uuid::Uuid::new_v4().to_string();
+    if room_tx
+        .send(RoomEvent::SpectatorJoined(spectator_id.clone(), client_tx))
+        .await
+        .is_err()
+    {
+        return Problem::new(StatusCode::NOT_FOUND, "Room not found").into_response();
+    }
+
+    let guard = SpectatorGuard {
+        room_tx,
+        spectator_id,
+        _permit: permit,
+    };
+    let updates = stream::unfold((client_rx, guard), |(mut rx, guard)| async move {
+        let msg = rx.recv().await?;
+        let event = Event::default()
+            .json_data(&msg)
+            .unwrap_or_else(|_| Event::default().comment("unserializable message"));
+        Some((Ok::<_, Infallible>(event), (rx, guard)))
+    });
+
+    // Comments every few seconds keep idle proxies from cutting the stream
+    Sse::new(updates)
+        .keep_alive(KeepAlive::default())
+        .into_response()
+}
@@ -0,0 +1,32 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::{StatusCode, header},
+    response::IntoResponse,
+};
+use std::sync::Arc;
+
+use crate::api::problem::Problem;
+use crate::api::server::AppState;
+use crate::db::repo;
+use crate::ranking::head_to_head;
+
+/// The latest global statistics, refreshed hourly.
+pub async fn global_stats(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    match repo::get_global_stats(&state.read_db).await {
+        Some(json) => ([(header::CONTENT_TYPE, "application/json")], json).into_response(),
+        None => Problem::new(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "Statistics have not been computed yet",
+        )
+        .into_response(),
+    }
+}
+
+/// `user_id`'s lifetime record against `opponent_id`.
+pub async fn head_to_head(
+    State(state): State<Arc<AppState>>,
+    Path((user_id, opponent_id)): Path<(String, String)>,
+) -> impl IntoResponse {
+    Json(head_to_head::between(&state.read_db, &user_id, &opponent_id).await)
+}
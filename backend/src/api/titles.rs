@@ -0,0 +1,75 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use std::sync::Arc;
+
+use crate::api::authz::AuthUser;
+use crate::api::problem::Problem;
+use crate::api::server::AppState;
+use crate::db::models::EarnedTitle;
+use crate::db::repo;
+use crate::ranking::titles::{self, PlayerTitle, TITLES, Title};
+
+/// A player's badges and the one they show next to their name.
+#[derive(Serialize)]
+pub struct TitleProfile {
+    pub selected: Option<PlayerTitle>,
+    /// Oldest first
+    pub earned: Vec<EarnedTitle>,
+}
+
+#[derive(Deserialize)]
+pub struct SelectTitlePayload {
+    /// One of the caller's earned titles; `null` shows none
+    pub title_id: Option<String>,
+}
+
+async fn profile(pool: &SqlitePool, user_id: &str) -> TitleProfile {
+    TitleProfile {
+        selected: titles::shown(pool, user_id).await,
+        earned: repo::get_earned_titles(pool, user_id).await,
+    }
+}
+
+pub async fn catalog() -> Json<&'static [Title]> {
+    Json(TITLES)
+}
+
+pub async fn get_titles(State(state): State<Arc<AppState>>, user: AuthUser) -> impl IntoResponse {
+    Json(profile(&state.db, &user.user_id).await)
+}
+
+pub async fn user_titles(
+    State(state): State<Arc<AppState>>,
+    Path(user_id): Path<String>,
+) -> impl IntoResponse {
+    Json(profile(&state.read_db, &user_id).await)
+}
+
+pub async fn select_title(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Json(payload): Json<SelectTitlePayload>,
+) -> impl IntoResponse {
+    if let Some(title_id) = &payload.title_id {
+        let earned = repo::get_earned_titles(&state.db, &user.user_id).await;
+        if !earned.iter().any(|title| &title.title_id == title_id) {
+            return Problem::new(StatusCode::BAD_REQUEST, "You have not earned that title")
+                .into_response();
+        }
+    }
+
+    if repo::set_selected_title(&state.db, &user.user_id, payload.title_id.as_deref())
+        .await
+        .is_err()
+    {
+        return Problem::new(StatusCode::INTERNAL_SERVER_ERROR, "Failed to save title")
+            .into_response();
+    }
+    Json(profile(&state.db, &user.user_id).await).into_response()
+}
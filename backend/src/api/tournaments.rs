@@ -0,0 +1,151 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::api::authz::AuthUser;
+use crate::api::problem::Problem;
+use crate::api::server::AppState;
+use crate::db::models::{Tournament, TournamentPlayer};
+use crate::db::repo;
+use crate::tournament::bracket::Bracket;
+use crate::tournament::director;
+
+const MAX_TOURNAMENT_NAME_CHARS: usize = 64;
+
+#[derive(Deserialize)]
+pub struct CreateTournamentPayload {
+    pub name: String,
+}
+
+#[derive(Serialize)]
+pub struct TournamentDetail {
+    pub tournament: Tournament,
+    /// Earliest registration first
+    pub players: Vec<TournamentPlayer>,
+    /// `None` until the tournament starts
+    pub bracket: Option<Bracket>,
+}
+
+pub async fn list_tournaments(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    Json(repo::list_tournaments(&state.db).await)
+}
+
+pub async fn get_tournament(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> impl IntoResponse {
+    let Some(tournament) = repo::get_tournament(&state.db, id).await else {
+        return Problem::new(StatusCode::NOT_FOUND, "Tournament not found").into_response();
+    };
+    let players = repo::get_tournament_players(&state.db, id).await;
+    let bracket = tournament
+        .bracket
+        .as_deref()
+        .and_then(|json| serde_json::from_str(json).ok());
+    Json(TournamentDetail {
+        tournament,
+        players,
+        bracket,
+    })
+    .into_response()
+}
+
+/// Opens a tournament for registration.
+pub async fn create_tournament(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Json(payload): Json<CreateTournamentPayload>,
+) -> impl IntoResponse {
+    let name = payload.name.trim();
+    if name.is_empty() || name.chars().count() > MAX_TOURNAMENT_NAME_CHARS {
+        return Problem::new(
+            StatusCode::BAD_REQUEST,
+            "A tournament name must be 1 to 64 characters",
+        )
+        .into_response();
+    }
+
+    match repo::insert_tournament(&state.db, name, &user.user_id, unix_now()).await {
+        Ok(tournament) => (StatusCode::CREATED, Json(tournament)).into_response(),
+        Err(_) => Problem::new(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Failed to create tournament",
+        )
+        .into_response(),
+    }
+}
+
+/// Closes registration, seeds the bracket and opens the first round's rooms.
+pub async fn start_tournament(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> impl IntoResponse {
+    let Some(tournament) = repo::get_tournament(&state.db, id).await else {
+        return Problem::new(StatusCode::NOT_FOUND, "Tournament not found").into_response();
+    };
+    if tournament.status != "registering" {
+        return Problem::new(StatusCode::CONFLICT, "The tournament has already started")
+            .into_response();
+    }
+
+    match director::start(&state, &tournament).await {
+        Ok(bracket) => Json(bracket).into_response(),
+        Err(e) => Problem::new(StatusCode::UNPROCESSABLE_ENTITY, &e).into_response(),
+    }
+}
+
+pub async fn register(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Path(id): Path<i64>,
+) -> impl IntoResponse {
+    if let Err(response) = open_for_registration(&state, id).await {
+        return response;
+    }
+    match repo::register_tournament_player(&state.db, id, &user.user_id, unix_now()).await {
+        Ok(_) => StatusCode::NO_CONTENT.into_response(),
+        Err(_) => {
+            Problem::new(StatusCode::INTERNAL_SERVER_ERROR, "Failed to register").into_response()
+        }
+    }
+}
+
+pub async fn unregister(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Path(id): Path<i64>,
+) -> impl IntoResponse {
+    if let Err(response) = open_for_registration(&state, id).await {
+        return response;
+    }
+    match repo::unregister_tournament_player(&state.db, id, &user.user_id).await {
+        Ok(true) => StatusCode::NO_CONTENT.into_response(),
+        Ok(false) => Problem::new(StatusCode::NOT_FOUND, "Not registered").into_response(),
+        Err(_) => {
+            Problem::new(StatusCode::INTERNAL_SERVER_ERROR, "Failed to unregister").into_response()
+        }
+    }
+}
+
+async fn open_for_registration(state: &AppState, id: i64) -> Result<(), axum::response::Response> {
+    match repo::get_tournament(&state.db, id).await {
+        None => Err(Problem::new(StatusCode::NOT_FOUND, "Tournament not found").into_response()),
+        Some(t) if t.status != "registering" => {
+            Err(Problem::new(StatusCode::CONFLICT, "Registration is closed").into_response())
+        }
+        Some(_) => Ok(()),
+    }
+}
+
+fn unix_now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64
+}
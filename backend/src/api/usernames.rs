@@ -0,0 +1,151 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::api::authz::AuthUser;
+use crate::api::problem::Problem;
+use crate::api::server::AppState;
+use crate::api::validation;
+use crate::db::models::UsernameChange;
+use crate::db::repo;
+
+/// Minimum time between two renames of the same account.
+pub const RENAME_COOLDOWN_SECS: i64 = 60 * 60 * 24 * 30;
+
+#[derive(Deserialize)]
+pub struct RenamePayload {
+    pub username: String,
+}
+
+#[derive(Serialize)]
+pub struct RenameResponse {
+    pub username: String,
+    /// Epoch seconds of the earliest next rename
+    pub next_rename_at: i64,
+}
+
+/// An account found by a current or former name, with every name it has had.
+#[derive(Serialize)]
+pub struct UsernameLookup {
+    pub user_id: String,
+    pub username: String,
+    /// Oldest first
+    pub history: Vec<UsernameChange>,
+}
+
+/// When the account may next be renamed, if the cooldown since `last_change` hasn't run out.
+fn rename_blocked_until(last_change: Option<i64>, now: i64) -> Option<i64> {
+    let next = last_change? + RENAME_COOLDOWN_SECS;
+    (next > now).then_some(next)
+}
+
+/// Renames the caller. Former names stay reserved for the account that held them, so
+/// nobody can take over a name that older games and reports were filed under.
+pub async fn rename(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Json(payload): Json<RenamePayload>,
+) -> impl IntoResponse {
+    let username = payload.username.trim().to_string();
+    if username.is_empty() {
+        return Problem::keyed(StatusCode::BAD_REQUEST, "missing_username").into_response();
+    }
+    if let Err(e) = validation::validate_credentials(&username, "") {
+        return Problem::from(e).into_response();
+    }
+    if !state.word_filter.is_clean_username(&username) {
+        return Problem::keyed(StatusCode::BAD_REQUEST, "username_not_allowed").into_response();
+    }
+    // Names in the admin list are promoted on every start-up
+    if state.config.admin_usernames.contains(&username) {
+        return Problem::keyed(StatusCode::CONFLICT, "username_taken").into_response();
+    }
+
+    let Some(current) = state
+        .user_cache
+        .get_user_by_id(&state.db, &user.user_id)
+        .await
+    else {
+        return Problem::new(StatusCode::NOT_FOUND, "Unknown user").into_response();
+    };
+    if current.username == username {
+        return Problem::new(StatusCode::BAD_REQUEST, "That is already your username")
+            .into_response();
+    }
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64;
+    let last_change = repo::last_username_change(&state.db, &user.user_id).await;
+    if let Some(next) = rename_blocked_until(last_change, now) {
+        return Problem::new(
+            StatusCode::TOO_MANY_REQUESTS,
+            &format!("You can change your username again at {}", next),
+        )
+        .into_response();
+    }
+
+    if let Some(holder) = repo::find_user_by_any_username(&state.db, &username).await
+        && holder.id != user.user_id
+    {
+        return Problem::keyed(StatusCode::CONFLICT, "username_taken").into_response();
+    }
+
+    let renamed =
+        repo::rename_user(&state.db, &user.user_id, &current.username, &username, now).await;
+    state.user_cache.invalidate(&user.user_id);
+    match renamed {
+        Ok(()) => Json(RenameResponse {
+            username,
+            next_rename_at: now + RENAME_COOLDOWN_SECS,
+        })
+        .into_response(),
+        // The unique index caught a concurrent rename or registration
+        Err(_) => Problem::keyed(StatusCode::CONFLICT, "username_taken").into_response(),
+    }
+}
+
+/// Resolves a current or former username to its account, for moderators working from
+/// an old report or transcript.
+pub async fn lookup(
+    State(state): State<Arc<AppState>>,
+    Path(username): Path<String>,
+) -> impl IntoResponse {
+    let Some(account) = repo::find_user_by_any_username(&state.db, &username).await else {
+        return Problem::new(StatusCode::NOT_FOUND, "No account has used that name")
+            .into_response();
+    };
+    let history = repo::get_username_history(&state.db, &account.id).await;
+    Json(UsernameLookup {
+        user_id: account.id,
+        username: account.username,
+        history,
+    })
+    .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renames_wait_out_the_cooldown() {
+        let now = 1_000_000_000;
+        assert_eq!(rename_blocked_until(None, now), None);
+        assert_eq!(
+            rename_blocked_until(Some(now - 60), now),
+            Some(now - 60 + RENAME_COOLDOWN_SECS)
+        );
+        assert_eq!(
+            rename_blocked_until(Some(now - RENAME_COOLDOWN_SECS), now),
+            None
+        );
+    }
+}
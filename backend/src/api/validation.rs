@@ -0,0 +1,247 @@
+use serde::Serialize;
+
+use crate::api::events::ClientMessage;
+use crate::api::i18n::{self, Locale};
+
+/// Largest WebSocket message (and frame) accepted from a client.
+/// The biggest legitimate payload, a `DropHand` of a full hand, is well under 4 KiB.
+pub const MAX_WS_MESSAGE_BYTES: usize = 16 * 1024;
+/// A hand never exceeds 13 cards mid-turn; leave headroom for variants that add penalty cards.
+pub const MAX_HAND_CARDS: usize = 32;
+/// The most demanding round asks for 4 melds.
+pub const MAX_DROP_COMBINATIONS: usize = 8;
+/// A full escala real is 13 cards.
+pub const MAX_CARDS_PER_COMBINATION: usize = 13;
+pub const MAX_USERNAME_CHARS: usize = 32;
+/// Bounds the cost of hashing untrusted passwords with Argon2.
+pub const MAX_PASSWORD_CHARS: usize = 128;
+pub const MAX_CHAT_CHARS: usize = 280;
+pub const MAX_REPORT_REASON_CHARS: usize = 500;
+
+/// A rejected input, sent back to the client before anything reaches the engine.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ValidationError {
+    pub code: &'static str,
+    pub message: String,
+    /// The bound that was exceeded, so the message can be re-rendered in another locale
+    #[serde(skip)]
+    pub limit: Option<usize>,
+}
+
+impl ValidationError {
+    fn new(code: &'static str, message: String) -> Self {
+        Self {
+            code,
+            message,
+            limit: None,
+        }
+    }
+
+    fn with_limit(code: &'static str, message: String, limit: usize) -> Self {
+        Self {
+            code,
+            message,
+            limit: Some(limit),
+        }
+    }
+
+    /// The message in `locale`, falling back to the English text for uncatalogued codes.
+    pub fn localized_message(&self, locale: Locale) -> String {
+        i18n::localize_key(self.code, locale, self.limit)
+            .map(|m| m.text)
+            .unwrap_or_else(|| self.message.clone())
+    }
+}
+
+pub fn validate_client_message(msg: &ClientMessage) -> Result<(), ValidationError> {
+    match msg {
+        ClientMessage::ReorderHand { payload } if payload.hand.len() > MAX_HAND_CARDS => {
+            return Err(ValidationError::with_limit(
+                "hand_too_large",
+                format!("A hand cannot contain more than {} cards", MAX_HAND_CARDS),
+                MAX_HAND_CARDS,
+            ));
+        }
+        ClientMessage::DropHand { payload } => {
+            if payload.combinations.len() > MAX_DROP_COMBINATIONS {
+                return Err(ValidationError::with_limit(
+                    "too_many_combinations",
+                    format!(
+                        "A bajada cannot contain more than {} combinations",
+                        MAX_DROP_COMBINATIONS
+                    ),
+                    MAX_DROP_COMBINATIONS,
+                ));
+            }
+            if payload
+                .combinations
+                .iter()
+                .any(|combo| combo.len() > MAX_CARDS_PER_COMBINATION)
+            {
+                return Err(ValidationError::with_limit(
+                    "combination_too_large",
+                    format!(
+                        "A combination cannot contain more than {} cards",
+                        MAX_CARDS_PER_COMBINATION
+                    ),
+                    MAX_CARDS_PER_COMBINATION,
+                ));
+            }
+        }
+        ClientMessage::Chat { payload } if payload.text.trim().is_empty() => {
+            return Err(ValidationError::new(
+                "empty_chat_message",
+                "Chat messages cannot be empty".to_string(),
+            ));
+        }
+        ClientMessage::Chat { payload } if payload.text.chars().count() > MAX_CHAT_CHARS => {
+            return Err(ValidationError::with_limit(
+                "chat_message_too_long",
+                format!("Chat messages cannot exceed {} characters", MAX_CHAT_CHARS),
+                MAX_CHAT_CHARS,
+            ));
+        }
+        ClientMessage::ReportPlayer { payload }
+            if payload.reason.chars().count() > MAX_REPORT_REASON_CHARS =>
+        {
+            return Err(ValidationError::with_limit(
+                "report_reason_too_long",
+                format!(
+                    "Report reasons cannot exceed {} characters",
+                    MAX_REPORT_REASON_CHARS
+                ),
+                MAX_REPORT_REASON_CHARS,
+            ));
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+pub fn validate_credentials(username: &str, password: &str) -> Result<(), ValidationError> {
+    if username.chars().count() > MAX_USERNAME_CHARS {
+        return Err(ValidationError::with_limit(
+            "username_too_long",
+            format!("Username cannot exceed {} characters", MAX_USERNAME_CHARS),
+            MAX_USERNAME_CHARS,
+        ));
+    }
+    if password.chars().count() > MAX_PASSWORD_CHARS {
+        return Err(ValidationError::with_limit(
+            "password_too_long",
+            format!("Password cannot exceed {} characters", MAX_PASSWORD_CHARS),
+            MAX_PASSWORD_CHARS,
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::events::{
+        ChatPayload, DropHandPayload, ReorderHandPayload, ReportPlayerPayload,
+    };
+    use crate::engine::card::Card;
+
+    #[test]
+    fn rejects_oversized_reorder() {
+        let msg = ClientMessage::ReorderHand {
+            payload: ReorderHandPayload {
+                hand: vec![Card::Joker; MAX_HAND_CARDS + 1],
+            },
+        };
+        assert_eq!(
+            validate_client_message(&msg).unwrap_err().code,
+            "hand_too_large"
+        );
+    }
+
+    #[test]
+    fn rejects_too_many_combinations() {
+        let msg = ClientMessage::DropHand {
+            payload: DropHandPayload {
+                combinations: vec![vec![Card::Joker; 3]; MAX_DROP_COMBINATIONS + 1],
+            },
+        };
+        assert_eq!(
+            validate_client_message(&msg).unwrap_err().code,
+            "too_many_combinations"
+        );
+    }
+
+    #[test]
+    fn rejects_oversized_combination() {
+        let msg = ClientMessage::DropHand {
+            payload: DropHandPayload {
+                combinations: vec![vec![Card::Joker; MAX_CARDS_PER_COMBINATION + 1]],
+            },
+        };
+        assert_eq!(
+            validate_client_message(&msg).unwrap_err().code,
+            "combination_too_large"
+        );
+    }
+
+    #[test]
+    fn accepts_regular_messages() {
+        assert!(validate_client_message(&ClientMessage::DrawFromDeck).is_ok());
+        let msg = ClientMessage::ReorderHand {
+            payload: ReorderHandPayload {
+                hand: vec![Card::Joker; 13],
+            },
+        };
+        assert!(validate_client_message(&msg).is_ok());
+    }
+
+    #[test]
+    fn rejects_empty_and_long_chat() {
+        let chat = |text: String| ClientMessage::Chat {
+            payload: ChatPayload { text },
+        };
+        assert_eq!(
+            validate_client_message(&chat("   ".to_string()))
+                .unwrap_err()
+                .code,
+            "empty_chat_message"
+        );
+        assert_eq!(
+            validate_client_message(&chat("a".repeat(MAX_CHAT_CHARS + 1)))
+                .unwrap_err()
+                .code,
+            "chat_message_too_long"
+        );
+        assert!(validate_client_message(&chat("suerte!".to_string())).is_ok());
+    }
+
+    #[test]
+    fn rejects_long_report_reason() {
+        let msg = ClientMessage::ReportPlayer {
+            payload: ReportPlayerPayload {
+                reported_player_id: "bob".to_string(),
+                reason: "x".repeat(MAX_REPORT_REASON_CHARS + 1),
+            },
+        };
+        assert_eq!(
+            validate_client_message(&msg).unwrap_err().code,
+            "report_reason_too_long"
+        );
+    }
+
+    #[test]
+    fn rejects_long_credentials() {
+        let long_name = "a".repeat(MAX_USERNAME_CHARS + 1);
+        assert_eq!(
+            validate_credentials(&long_name, "pw").unwrap_err().code,
+            "username_too_long"
+        );
+        let long_password = "p".repeat(MAX_PASSWORD_CHARS + 1);
+        assert_eq!(
+            validate_credentials("alice", &long_password)
+                .unwrap_err()
+                .code,
+            "password_too_long"
+        );
+        assert!(validate_credentials("alice", "secret").is_ok());
+    }
+}
@@ -0,0 +1,67 @@
+use axum::{Json, extract::State, http::StatusCode, response::IntoResponse};
+use serde::Deserialize;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use uuid::Uuid;
+
+use crate::api::authz::AuthUser;
+use crate::api::problem::Problem;
+use crate::api::server::AppState;
+use crate::db::models::Webhook;
+use crate::db::repo;
+use crate::notify::http::HttpUrl;
+
+#[derive(Deserialize)]
+pub struct SetWebhookPayload {
+    pub url: String,
+}
+
+pub async fn get_webhook(State(state): State<Arc<AppState>>, user: AuthUser) -> impl IntoResponse {
+    match repo::get_webhook(&state.db, &user.user_id).await {
+        Some(webhook) => Json(webhook).into_response(),
+        None => Problem::new(StatusCode::NOT_FOUND, "No webhook registered").into_response(),
+    }
+}
+
+/// Registers the caller's callback URL with a fresh secret, replacing any previous one.
+pub async fn set_webhook(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Json(payload): Json<SetWebhookPayload>,
+) -> impl IntoResponse {
+    if let Err(e) = HttpUrl::parse(&payload.url) {
+        return Problem::new(StatusCode::BAD_REQUEST, e).into_response();
+    }
+
+    let webhook = Webhook {
+        user_id: user.user_id,
+        url: payload.url,
+        secret: Uuid::new_v4().simple().to_string(),
+        created_at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as i64,
+    };
+
+    if repo::upsert_webhook(&state.db, &webhook).await.is_err() {
+        return Problem::new(StatusCode::INTERNAL_SERVER_ERROR, "Failed to save webhook")
+            .into_response();
+    }
+
+    Json(webhook).into_response()
+}
+
+pub async fn delete_webhook(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+) -> impl IntoResponse {
+    match repo::delete_webhook(&state.db, &user.user_id).await {
+        Ok(true) => StatusCode::NO_CONTENT.into_response(),
+        Ok(false) => Problem::new(StatusCode::NOT_FOUND, "No webhook registered").into_response(),
+        Err(_) => Problem::new(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Failed to delete webhook",
+        )
+        .into_response(),
+    }
+}
@@ -1,53 +1,357 @@
 use axum::{
+    Json,
     extract::{
         Query, State,
         ws::{Message, WebSocket, WebSocketUpgrade},
     },
+    http::{HeaderMap, StatusCode, header},
     response::IntoResponse,
 };
-use futures_util::{sink::SinkExt, stream::StreamExt};
-use jsonwebtoken::{DecodingKey, Validation, decode};
+use futures_util::{
+    sink::SinkExt,
+    stream::{SplitStream, StreamExt},
+};
 use serde::Deserialize;
-use std::sync::Arc;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::{mpsc, oneshot};
 
+use crate::api::admission::{Admission, Permit};
+use crate::api::auth::{self, Role};
+use crate::api::authz;
+use crate::api::events::{ClientMessage, ServerMessage};
+use crate::api::games;
+use crate::api::i18n::{self, Locale};
+use crate::api::problem::Problem;
+use crate::api::rtt::{ConnectionQuality, RttTracker};
 use crate::api::server::AppState;
+use crate::api::validation;
+use crate::db::models::QueuedPlayer;
+use crate::db::repo;
+use crate::engine::bot::BotDifficulty;
+use crate::matchmaking::adaptive;
+use crate::matchmaking::browser::{BrowserQuery, BrowserUpdate};
+use crate::matchmaking::devices::AttachMode;
+use crate::matchmaking::lobby::{self, JoinOutcome, SearchOptions, Seating, Table};
+use crate::matchmaking::queues::{self, QueuePreset};
+use crate::matchmaking::room::{Room, RoomEvent};
+use crate::matchmaking::tutorial::TUTOR_BOT_ID;
+use crate::notify::push::PushEvent;
+use crate::ranking::head_to_head;
+use crate::ranking::{rating, season, titles};
 
 #[derive(Deserialize)]
 pub struct WsQuery {
-    pub token: String,
+    /// Login token; required unless connecting with `spectate` or `rejoin`
+    pub token: Option<String>,
+    /// Spectate token from a share link: watch that room read-only, no login needed
+    pub spectate: Option<String>,
+    /// Language for server messages, e.g. `es-CL` or `en`; falls back to `Accept-Language`
+    pub locale: Option<String>,
+    /// `browser` follows the game browser's listing of public tables instead of playing;
+    /// `tutorial` starts a scripted lesson against the tutor bot instead of matchmaking;
+    /// `hotseat` seats `seats` local players on this one connection (pass-and-play);
+    /// `instant` starts an unrated game against `bots` straight away
+    pub mode: Option<String>,
+    pub seats: Option<usize>,
+    /// Difficulties of the instant-play opponents, e.g. `easy,hard`; by default the player's
+    /// adaptive level picks them
+    pub bots: Option<String>,
+    /// Instant games only: the bots explain each move with a `BotExplanation`
+    pub teaching: Option<bool>,
+    /// Region or latency class, e.g. `eu-west`; matchmaking prefers tables within it
+    pub region: Option<String>,
+    /// Matchmaking queue to wait in, one of `GET /api/queues`; decides the table's rules
+    pub queue: Option<String>,
+    /// Reconnect token from `MatchFound` or `GET /api/games/active`: take that seat back
+    /// instead of matchmaking. Enough on its own when the login token has expired
+    pub rejoin: Option<String>,
+    /// With `rejoin`, when the seat is already open on another device: `control` (default)
+    /// takes it over and leaves the other device watching, `mirror` only watches
+    pub device: Option<String>,
+    /// Scheduled game the player RSVPed to: wait on this connection until it starts
+    pub scheduled: Option<i64>,
+    /// Matchmaking with priority after a game was called off on the player: keep away from
+    /// whoever abandoned that game
+    pub avoid_abandoners: Option<bool>,
+    /// Admins only: attach to this room id and receive every hand, the deck and the action log
+    pub observe: Option<String>,
 }
 
-#[derive(Deserialize)]
-struct Claims {
-    sub: String,
-    #[allow(dead_code)]
-    exp: usize,
+/// Seat counts a hot-seat table supports.
+const HOT_SEAT_RANGE: std::ops::RangeInclusive<usize> = 2..=4;
+
+/// Longest region name a client may declare.
+const MAX_REGION_CHARS: usize = 32;
+/// How often a queued player asks the lobby to reconsider their options.
+const LOBBY_RETRY_INTERVAL: Duration = Duration::from_secs(1);
+/// How often a player waiting for a scheduled game checks whether it has started.
+const SCHEDULED_POLL_INTERVAL: Duration = Duration::from_secs(2);
+/// How often a seated player's connection is pinged to measure its round trip.
+const PING_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Region names are compared case-insensitively and limited to letters, digits and `-`.
+fn normalize_region(region: &str) -> Option<String> {
+    let region = region.trim().to_ascii_lowercase();
+    let valid = !region.is_empty()
+        && region.len() <= MAX_REGION_CHARS
+        && region
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-');
+    valid.then_some(region)
 }
 
-const JWT_SECRET: &[u8] = b"super_secret_carioca_key_mvp";
+/// Ids for a pass-and-play table: the account itself, then numbered guests.
+fn hot_seat_ids(user_id: &str, seats: usize) -> Vec<String> {
+    std::iter::once(user_id.to_string())
+        .chain((2..=seats).map(|n| format!("{}#{}", user_id, n)))
+        .collect()
+}
 
 pub async fn ws_handler(
     ws: WebSocketUpgrade,
     Query(query): Query<WsQuery>,
+    headers: HeaderMap,
     State(state): State<Arc<AppState>>,
 ) -> impl IntoResponse {
+    if let Some(spectate) = query.spectate.as_deref() {
+        let Some(claims) = auth::decode_spectate_token(spectate) else {
+            return Problem::keyed(StatusCode::UNAUTHORIZED, "invalid_token").into_response();
+        };
+        // Spectators don't queue; there is nothing to hold a place in line for
+        let Admission::Admitted(permit) = state.capacity.sockets.enter() else {
+            return server_full();
+        };
+        return ws
+            .max_message_size(validation::MAX_WS_MESSAGE_BYTES)
+            .max_frame_size(validation::MAX_WS_MESSAGE_BYTES)
+            .on_upgrade(move |socket| handle_spectator(socket, state, claims.room_id, permit))
+            .into_response();
+    }
+
     // Basic JWT Validation here for WS
-    let validation = Validation::default();
-    let token_data = match decode::<Claims>(
-        &query.token,
-        &DecodingKey::from_secret(JWT_SECRET),
-        &validation,
-    ) {
-        Ok(c) => c,
-        Err(_) => return axum::http::StatusCode::UNAUTHORIZED.into_response(),
+    let claims = query.token.as_deref().and_then(auth::decode_jwt);
+
+    if let Some(room_id) = query.observe {
+        let Some(claims) = claims else {
+            return Problem::keyed(StatusCode::UNAUTHORIZED, "invalid_token").into_response();
+        };
+        if authz::current_role(&state, &claims.sub).await < Some(Role::Admin) {
+            return Problem::keyed(StatusCode::FORBIDDEN, "insufficient_role").into_response();
+        }
+        let Admission::Admitted(permit) = state.capacity.sockets.enter() else {
+            return server_full();
+        };
+        println!("Admin {} observing room {}", claims.sub, room_id);
+        return ws
+            .max_message_size(validation::MAX_WS_MESSAGE_BYTES)
+            .max_frame_size(validation::MAX_WS_MESSAGE_BYTES)
+            .on_upgrade(move |socket| handle_observer(socket, state, room_id, permit))
+            .into_response();
+    }
+
+    if query.mode.as_deref() == Some("browser") {
+        if claims.is_none() {
+            return Problem::keyed(StatusCode::UNAUTHORIZED, "invalid_token").into_response();
+        }
+        let Admission::Admitted(permit) = state.capacity.sockets.enter() else {
+            return server_full();
+        };
+        return ws
+            .max_message_size(validation::MAX_WS_MESSAGE_BYTES)
+            .max_frame_size(validation::MAX_WS_MESSAGE_BYTES)
+            .on_upgrade(move |socket| handle_browser(socket, state, permit))
+            .into_response();
+    }
+
+    let reconnect = match query.rejoin.as_deref() {
+        Some(token) => match auth::decode_reconnect_token(token) {
+            Some(reconnect) if claims.as_ref().is_none_or(|c| c.sub == reconnect.sub) => {
+                Some(reconnect)
+            }
+            _ => return Problem::keyed(StatusCode::UNAUTHORIZED, "invalid_token").into_response(),
+        },
+        None => None,
+    };
+    // A reconnect token is bound to one seat, so it stands in for a login that expired
+    // mid-game
+    let (user_id, rejoin) = match (claims, reconnect) {
+        (Some(claims), reconnect) => (claims.sub, reconnect.map(|r| r.room_id)),
+        (None, Some(reconnect)) => (reconnect.sub, Some(reconnect.room_id)),
+        (None, None) => {
+            return Problem::keyed(StatusCode::UNAUTHORIZED, "missing_token").into_response();
+        }
+    };
+
+    if repo::has_active_sanction(&state.db, &user_id, "ban", unix_now()).await {
+        return Problem::keyed(StatusCode::FORBIDDEN, "account_banned").into_response();
+    }
+
+    // Games already running go on, so players may still take their seats back
+    if rejoin.is_none()
+        && let Some(notice) = state.maintenance.notice()
+    {
+        return (axum::http::StatusCode::SERVICE_UNAVAILABLE, Json(notice)).into_response();
+    }
+
+    if let Some(game_id) = query.scheduled
+        && !repo::has_scheduled_game_rsvp(&state.db, game_id, &user_id)
+            .await
+            .unwrap_or(false)
+    {
+        return Problem::new(
+            StatusCode::FORBIDDEN,
+            "Only players who RSVPed can join this game",
+        )
+        .into_response();
+    }
+
+    let locale = query
+        .locale
+        .as_deref()
+        .or_else(|| {
+            headers
+                .get(header::ACCEPT_LANGUAGE)
+                .and_then(|v| v.to_str().ok())
+        })
+        .map(Locale::parse)
+        .unwrap_or_default();
+    let tutorial = query.mode.as_deref() == Some("tutorial");
+    let hot_seats = if query.mode.as_deref() == Some("hotseat") {
+        match query.seats.filter(|n| HOT_SEAT_RANGE.contains(n)) {
+            Some(seats) => hot_seat_ids(&user_id, seats),
+            None => {
+                return Problem::new(StatusCode::BAD_REQUEST, "A hot-seat table has 2 to 4 seats")
+                    .into_response();
+            }
+        }
+    } else {
+        Vec::new()
+    };
+    let instant = if query.mode.as_deref() == Some("instant") {
+        match query.bots.as_deref().map(lobby::parse_bot_mix) {
+            Some(None) => {
+                return Problem::new(
+                    StatusCode::BAD_REQUEST,
+                    "Bots must be easy, medium or hard, one to three of them",
+                )
+                .into_response();
+            }
+            Some(mix) => mix,
+            None => Some(adaptive::load(&state.db, &user_id).await.bots()),
+        }
+    } else {
+        None
+    };
+    let region = match query.region.as_deref().map(normalize_region) {
+        Some(None) => {
+            return Problem::new(StatusCode::BAD_REQUEST, "Invalid region").into_response();
+        }
+        Some(region) => region,
+        None => None,
+    };
+    let Some(queue) = queues::find(query.queue.as_deref().unwrap_or(queues::DEFAULT_QUEUE)) else {
+        return Problem::new(StatusCode::BAD_REQUEST, "Unknown queue").into_response();
+    };
+    let Some(attach) = query
+        .device
+        .as_deref()
+        .map_or(Some(AttachMode::default()), AttachMode::parse)
+    else {
+        return Problem::new(StatusCode::BAD_REQUEST, "Unknown device mode").into_response();
+    };
+
+    let admission = state.capacity.sockets.enter();
+    if matches!(admission, Admission::Full) {
+        return server_full();
+    }
+    let session = Session {
+        user_id,
+        locale,
+        tutorial,
+        hot_seats,
+        instant,
+        teaching: query.teaching.unwrap_or(false),
+        region,
+        queue,
+        rejoin,
+        attach,
+        scheduled: query.scheduled,
+        avoid_abandoners: query.avoid_abandoners.unwrap_or(false),
     };
 
-    let user_id = token_data.claims.sub.clone();
+    ws.max_message_size(validation::MAX_WS_MESSAGE_BYTES)
+        .max_frame_size(validation::MAX_WS_MESSAGE_BYTES)
+        .on_upgrade(move |socket| handle_socket(socket, state, session, admission))
+        .into_response()
+}
+
+/// Over capacity with the queue full too.
+fn server_full() -> axum::response::Response {
+    Problem::keyed(StatusCode::SERVICE_UNAVAILABLE, "server_full").into_response()
+}
+
+/// Tells the client no new games start while the server is in maintenance; returns
+/// whether it is.
+async fn refuse_for_maintenance(
+    state: &Arc<AppState>,
+    client_tx: &mpsc::Sender<ServerMessage>,
+) -> bool {
+    let Some(notice) = state.maintenance.notice() else {
+        return false;
+    };
+    let _ = client_tx
+        .send(ServerMessage::ServerMaintenance {
+            notice: Some(notice),
+        })
+        .await;
+    true
+}
 
-    ws.on_upgrade(move |socket| handle_socket(socket, state, user_id))
+/// What a player connection asked for when it opened.
+struct Session {
+    user_id: String,
+    locale: Locale,
+    tutorial: bool,
+    hot_seats: Vec<String>,
+    /// Instant-play opponents; `None` goes through matchmaking
+    instant: Option<Vec<BotDifficulty>>,
+    /// Bots in the instant game explain their moves
+    teaching: bool,
+    region: Option<String>,
+    queue: &'static QueuePreset,
+    /// Room the player is returning to
+    rejoin: Option<String>,
+    /// How to share the seat with the player's other devices
+    attach: AttachMode,
+    /// Scheduled game the player is waiting for
+    scheduled: Option<i64>,
+    /// Keep away from whoever abandoned the player's last game, if it was called off
+    avoid_abandoners: bool,
 }
 
-async fn handle_socket(socket: WebSocket, state: Arc<AppState>, user_id: String) {
+async fn handle_socket(
+    socket: WebSocket,
+    state: Arc<AppState>,
+    session: Session,
+    admission: Admission,
+) {
+    let Session {
+        user_id,
+        locale,
+        tutorial,
+        mut hot_seats,
+        instant,
+        teaching,
+        region,
+        queue,
+        rejoin,
+        attach,
+        scheduled,
+        avoid_abandoners,
+    } = session;
     let (mut sender, mut receiver) = socket.split();
 
     // Create an mpsc channel to receive ServerMessages from the Room Actor (and other places)
@@ -66,67 +370,284 @@ async fn handle_socket(socket: WebSocket, state: Arc<AppState>, user_id: String)
         }
     });
 
-    println!("User {} connecting to Lobby...", user_id);
-    let matched_players = state.lobby.join(user_id.clone()).await;
-
-    let mut current_room_id: Option<String> = None;
-
-    if let Some(players) = matched_players {
-        println!("Match found! Players: {:?}", players);
-
-        let room_id = uuid::Uuid::new_v4().to_string();
+    // Held for as long as the connection stays open
+    let _socket_permit = match admission {
+        Admission::Admitted(permit) => permit,
+        Admission::Queued(ticket) => {
+            let wait = ticket.wait(|position| {
+                let _ = client_tx.try_send(ServerMessage::ServerFull { position });
+            });
+            tokio::select! {
+                permit = wait => permit,
+                _ = client_gone(&mut receiver) => {
+                    send_task.abort();
+                    return;
+                }
+            }
+        }
+        Admission::Full => return,
+    };
 
-        let (tx, rx) = tokio::sync::mpsc::channel(100);
-        let room =
-            crate::matchmaking::room::Room::new(room_id.clone(), players.clone(), rx, tx.clone());
+    let seating = if let Some(room_id) = rejoin {
+        println!("User {} rejoining room {}...", user_id, room_id);
+        match games::seat_in(&state, &room_id, &user_id).await {
+            Some(seat) => {
+                hot_seats = seat.hot_seats;
+                Some(Seating {
+                    room_id,
+                    players: seat.players,
+                    regions: seat.regions,
+                })
+            }
+            None => {
+                let gone = i18n::localize_error("game_not_running", locale);
+                let _ = client_tx
+                    .send(ServerMessage::Error {
+                        message: gone.text,
+                        code: Some(gone.key.to_string()),
+                        correlation_id: None,
+                    })
+                    .await;
+                None
+            }
+        }
+    } else if let Some(game_id) = scheduled {
+        println!("User {} waiting for scheduled game {}...", user_id, game_id);
+        wait_for_scheduled_game(&state, &user_id, game_id, &client_tx, locale, &mut receiver).await
+    } else if !hot_seats.is_empty() {
+        println!("User {} opening a hot-seat table...", user_id);
+        let kind = RoomKind::HotSeat(hot_seats.clone());
+        open_room(&state, kind, HashMap::new(), &client_tx, locale)
+            .await
+            .map(|room_id| Seating {
+                room_id,
+                players: hot_seats.clone(),
+                regions: HashMap::new(),
+            })
+    } else if let Some(bots) = instant {
+        println!("User {} starting an instant game...", user_id);
+        let players = lobby::instant_table(&user_id, &bots);
+        let kind = RoomKind::Instant(players.clone(), queue, teaching);
+        open_room(&state, kind, HashMap::new(), &client_tx, locale)
+            .await
+            .map(|room_id| Seating {
+                room_id,
+                players,
+                regions: HashMap::new(),
+            })
+    } else if tutorial {
+        println!("User {} starting the tutorial...", user_id);
+        let kind = RoomKind::Tutorial(user_id.clone());
+        open_room(&state, kind, HashMap::new(), &client_tx, locale)
+            .await
+            .map(|room_id| Seating {
+                room_id,
+                players: vec![user_id.clone(), TUTOR_BOT_ID.to_string()],
+                regions: HashMap::new(),
+            })
+    } else {
+        println!("User {} connecting to Lobby...", user_id);
+        let restored = state.lobby.restored(&user_id, queue.id).await;
+        let options = search_options(&state, &user_id, avoid_abandoners).await;
+        let prioritized = options.priority;
+        match state
+            .lobby
+            .join_with(user_id.clone(), region.clone(), queue.id, options)
+            .await
+        {
+            Some(JoinOutcome::Host(table)) => {
+                let _ = repo::delete_queued_player(&state.db, &user_id).await;
+                host_table(&state, table, &client_tx, locale).await
+            }
+            Some(JoinOutcome::Queued(seat_rx)) => {
+                let player = QueuedPlayer {
+                    user_id: user_id.clone(),
+                    queue: queue.id.to_string(),
+                    region,
+                    joined_at_ms: restored
+                        .as_ref()
+                        .map_or_else(unix_now_ms, |entry| entry.joined_at_ms as i64),
+                };
+                enter_queue(&state, player, restored.is_some(), prioritized, &client_tx).await;
+                let seating =
+                    wait_for_table(&state, &user_id, seat_rx, &client_tx, locale, &mut receiver)
+                        .await;
+                // Seated, or they left; either way they are no longer searching
+                let _ = repo::delete_queued_player(&state.db, &user_id).await;
+                seating
+            }
+            None => None,
+        }
+    };
 
-        tokio::spawn(async move {
-            room.run().await;
-        });
+    let mut current_room_id: Option<String> = None;
+    // Tells this connection apart from the player's other devices at the same table
+    let device_id = uuid::Uuid::new_v4().to_string();
 
-        state
-            .active_rooms
-            .lock()
-            .await
-            .insert(room_id.clone(), tx.clone());
+    if let Some(seating) = seating {
+        println!("Match found! Players: {:?}", seating.players);
 
         // Notify the client that a match was found securely
+        let cross_region = seating.cross_region();
+        let head_to_head = head_to_head::for_table(&state.db, &user_id, &seating.players).await;
+        let mut shown_titles = HashMap::new();
+        for player in &seating.players {
+            if let Some(title) = titles::shown(&state.db, player).await {
+                shown_titles.insert(player.clone(), title);
+            }
+        }
+        let (reconnect_token, reconnect_expires_at) =
+            auth::create_reconnect_token(&user_id, &seating.room_id);
         let _ = client_tx
             .send(crate::api::events::ServerMessage::MatchFound {
-                room_id: room_id.clone(),
-                players: players.clone(),
+                room_id: seating.room_id.clone(),
+                reconnect_token,
+                reconnect_expires_at,
+                players: seating.players,
+                regions: seating.regions,
+                cross_region,
+                head_to_head,
+                titles: shown_titles,
             })
             .await;
 
         // Now crucially, register this player's channel with the new room so it receives GameStateUpdates!
-        let _ = tx
-            .send(crate::matchmaking::room::RoomEvent::PlayerJoined(
-                user_id.clone(),
-                client_tx.clone(),
-            ))
-            .await;
+        let room_tx = state
+            .active_rooms
+            .lock()
+            .await
+            .get(&seating.room_id)
+            .cloned();
+        if let Some(room_tx) = room_tx {
+            let _ = room_tx
+                .send(crate::matchmaking::room::RoomEvent::PlayerJoined(
+                    user_id.clone(),
+                    device_id.clone(),
+                    client_tx.clone(),
+                    locale,
+                    attach,
+                ))
+                .await;
+        }
 
-        current_room_id = Some(room_id);
+        current_room_id = Some(seating.room_id);
     }
 
+    let rtt = Arc::new(Mutex::new(RttTracker::default()));
+    let ping_rtt = rtt.clone();
+    let ping_tx = client_tx.clone();
+    let ping_state = state.clone();
+    let ping_user_id = user_id.clone();
+    let ping_room_id = current_room_id.clone();
+    let ping_task = tokio::spawn(async move {
+        let mut ticks = tokio::time::interval(PING_INTERVAL);
+        loop {
+            ticks.tick().await;
+            // Pings left unanswered can only make the connection look worse
+            let mut nonce = 0;
+            let changed = RttChange::measure(&ping_rtt, |rtt| nonce = rtt.ping(Instant::now()));
+            if let Some(room_id) = &ping_room_id {
+                report_rtt(&ping_state, room_id, &ping_user_id, changed).await;
+            }
+            if ping_tx.send(ServerMessage::Ping { nonce }).await.is_err() {
+                break;
+            }
+        }
+    });
+
     // Spawn a task to handle inbound messages from the client
     let inbound_user_id = user_id.clone();
     let inbound_state = state.clone();
     let inbound_room_id = current_room_id.clone();
+    let inbound_client_tx = client_tx.clone();
+    let inbound_device_id = device_id.clone();
 
     let mut recv_task = tokio::spawn(async move {
+        let mut locale = locale;
         while let Some(msg) = receiver.next().await {
             if let Ok(Message::Text(text)) = msg {
-                match serde_json::from_str::<crate::api::events::ClientMessage>(&text) {
-                    Ok(action) => {
+                match serde_json::from_str::<crate::api::events::ClientEnvelope>(&text) {
+                    Ok(envelope) => {
+                        let action = envelope.action;
+                        let correlation_id = envelope.correlation_id;
+                        if let ClientMessage::Pong { payload } = &action {
+                            let changed = RttChange::measure(&rtt, |rtt| {
+                                rtt.pong(payload.nonce, Instant::now());
+                            });
+                            if let Some(room_id) = &inbound_room_id {
+                                report_rtt(&inbound_state, room_id, &inbound_user_id, changed)
+                                    .await;
+                            }
+                            continue;
+                        }
+                        // Hot-seat connections must say which of their seats is acting
+                        let actor = match envelope.seat_id {
+                            None if hot_seats.is_empty() => inbound_user_id.clone(),
+                            Some(seat)
+                                if hot_seats.contains(&seat)
+                                    || (hot_seats.is_empty() && seat == inbound_user_id) =>
+                            {
+                                seat
+                            }
+                            _ => {
+                                let unknown = i18n::localize_error("unknown_seat", locale);
+                                let _ = inbound_client_tx
+                                    .send(crate::api::events::ServerMessage::Error {
+                                        message: unknown.text,
+                                        code: Some(unknown.key.to_string()),
+                                        correlation_id: correlation_id.clone(),
+                                    })
+                                    .await;
+                                continue;
+                            }
+                        };
+
+                        if let Err(e) = validation::validate_client_message(&action) {
+                            let _ = inbound_client_tx
+                                .send(crate::api::events::ServerMessage::Error {
+                                    message: e.localized_message(locale),
+                                    code: Some(e.code.to_string()),
+                                    correlation_id: correlation_id.clone(),
+                                })
+                                .await;
+                            continue;
+                        }
+
+                        // The room hears of it too, for the messages it sends
+                        if let crate::api::events::ClientMessage::SetLocale { payload } = &action {
+                            locale = Locale::parse(&payload.locale);
+                        }
+
+                        if matches!(action, crate::api::events::ClientMessage::Chat { .. })
+                            && repo::has_active_sanction(
+                                &inbound_state.db,
+                                &inbound_user_id,
+                                "mute",
+                                unix_now(),
+                            )
+                            .await
+                        {
+                            let muted = i18n::localize_error("muted", locale);
+                            let _ = inbound_client_tx
+                                .send(crate::api::events::ServerMessage::Error {
+                                    message: muted.text,
+                                    code: Some(muted.key.to_string()),
+                                    correlation_id: correlation_id.clone(),
+                                })
+                                .await;
+                            continue;
+                        }
+
                         if let Some(room_id) = &inbound_room_id
                             && let Some(room_tx) =
                                 inbound_state.active_rooms.lock().await.get(room_id)
                         {
                             let _ = room_tx
                                 .send(crate::matchmaking::room::RoomEvent::PlayerAction(
-                                    inbound_user_id.clone(),
+                                    actor,
                                     action,
+                                    correlation_id,
+                                    Some(inbound_device_id.clone()),
                                 ))
                                 .await;
                         }
@@ -149,6 +670,7 @@ async fn handle_socket(socket: WebSocket, state: Arc<AppState>, user_id: String)
         _ = (&mut send_task) => recv_task.abort(),
         _ = (&mut recv_task) => send_task.abort(),
     };
+    ping_task.abort();
 
     println!("User {} disconnected.", user_id);
     state.lobby.leave(&user_id).await;
@@ -159,7 +681,451 @@ async fn handle_socket(socket: WebSocket, state: Arc<AppState>, user_id: String)
         let _ = room_tx
             .send(crate::matchmaking::room::RoomEvent::PlayerLeft(
                 user_id.clone(),
+                device_id,
             ))
             .await;
     }
 }
+
+/// What a ping or pong changed about a connection's measured round trip.
+struct RttChange {
+    quality: Option<ConnectionQuality>,
+    rtt: Option<Duration>,
+}
+
+impl RttChange {
+    fn measure(tracker: &Mutex<RttTracker>, update: impl FnOnce(&mut RttTracker)) -> Self {
+        let mut tracker = tracker.lock().unwrap();
+        let (quality, rtt) = (tracker.quality(), tracker.rtt());
+        update(&mut tracker);
+        Self {
+            quality: tracker.quality().filter(|after| quality != Some(*after)),
+            rtt: tracker.rtt().filter(|after| rtt != Some(*after)),
+        }
+    }
+}
+
+/// Tells the room how a player's connection changed: the table shows its quality, and the
+/// turn timer allows for its round trip.
+async fn report_rtt(state: &AppState, room_id: &str, user_id: &str, change: RttChange) {
+    if change.quality.is_none() && change.rtt.is_none() {
+        return;
+    }
+    let room_tx = state.active_rooms.lock().await.get(room_id).cloned();
+    let Some(room_tx) = room_tx else {
+        return;
+    };
+    if let Some(quality) = change.quality {
+        let _ = room_tx
+            .send(RoomEvent::ConnectionQuality(user_id.to_string(), quality))
+            .await;
+    }
+    if let Some(rtt) = change.rtt {
+        let _ = room_tx
+            .send(RoomEvent::RoundTrip(user_id.to_string(), rtt))
+            .await;
+    }
+}
+
+enum RoomKind {
+    /// A matchmade table, playing by its queue's preset
+    Regular(Vec<String>, &'static QueuePreset),
+    HotSeat(Vec<String>),
+    /// The requester against bots, unrated, voting on the rules from the chosen queue's
+    /// preset; whether the bots explain their moves
+    Instant(Vec<String>, &'static QueuePreset, bool),
+    /// The learner's id
+    Tutorial(String),
+}
+
+/// Spawns a room actor and registers it in `active_rooms`, returning its id. Waits for a
+/// free room slot when the server is at capacity, keeping the client posted on its place;
+/// returns `None`, after telling the client, if even the queue is full.
+async fn open_room(
+    state: &Arc<AppState>,
+    kind: RoomKind,
+    regions: HashMap<String, String>,
+    client_tx: &mpsc::Sender<ServerMessage>,
+    locale: Locale,
+) -> Option<String> {
+    if refuse_for_maintenance(state, client_tx).await {
+        return None;
+    }
+    let permit = match state.capacity.rooms.enter() {
+        Admission::Admitted(permit) => permit,
+        Admission::Queued(ticket) => {
+            ticket
+                .wait(|position| {
+                    let _ = client_tx.try_send(ServerMessage::ServerFull { position });
+                })
+                .await
+        }
+        Admission::Full => {
+            let full = i18n::localize_error("server_full", locale);
+            let _ = client_tx
+                .send(ServerMessage::Error {
+                    message: full.text,
+                    code: Some(full.key.to_string()),
+                    correlation_id: None,
+                })
+                .await;
+            return None;
+        }
+    };
+
+    let room_id = uuid::Uuid::new_v4().to_string();
+    let (tx, rx) = tokio::sync::mpsc::channel(100);
+    let mut services = state.room_services();
+    let mut room = match kind {
+        RoomKind::Regular(players, preset) => {
+            services.rules = preset.rules(&services.rules);
+            let mut room = Room::new(room_id.clone(), players, rx, tx.clone(), services);
+            room.queue = Some(preset.id);
+            room
+        }
+        RoomKind::Instant(players, preset, teaching) => {
+            let mut room = Room::new(room_id.clone(), players, rx, tx.clone(), services);
+            room.open_rule_vote(preset);
+            room.casual = true;
+            room.teaching = teaching;
+            room
+        }
+        RoomKind::HotSeat(seats) => {
+            Room::new_hot_seat(room_id.clone(), seats, rx, tx.clone(), services)
+        }
+        RoomKind::Tutorial(learner) => {
+            Room::new_tutorial(room_id.clone(), learner, rx, tx.clone(), services)
+        }
+    };
+    room.regions = regions;
+
+    tokio::spawn(async move {
+        room.run().await;
+        drop(permit);
+    });
+
+    state.active_rooms.lock().await.insert(room_id.clone(), tx);
+    Some(room_id)
+}
+
+/// Opens the room for a table the lobby formed and sends the other players to it.
+async fn host_table(
+    state: &Arc<AppState>,
+    table: Table,
+    client_tx: &mpsc::Sender<ServerMessage>,
+    locale: Locale,
+) -> Option<Seating> {
+    let preset = queues::find(table.queue).expect("tables only form in known queues");
+    let kind = RoomKind::Regular(table.players.clone(), preset);
+    let room_id = open_room(state, kind, table.regions.clone(), client_tx, locale).await?;
+
+    for player in table.players.iter().filter(|id| !id.starts_with("bot_")) {
+        let event = PushEvent::MatchFound {
+            room_id: room_id.clone(),
+        };
+        state.notifier.push(player, event);
+    }
+
+    Some(table.seat(&room_id))
+}
+
+/// Stores the player's place in the queue, so it survives a restart, and tells them
+/// where they stand.
+async fn enter_queue(
+    state: &Arc<AppState>,
+    player: QueuedPlayer,
+    restored: bool,
+    prioritized: bool,
+    client_tx: &mpsc::Sender<ServerMessage>,
+) {
+    if let Err(e) = repo::upsert_queued_player(&state.db, &player).await {
+        eprintln!("[Lobby] Failed to store {}'s place: {}", player.user_id, e);
+    }
+    let _ = client_tx
+        .send(ServerMessage::QueueStatus {
+            queue: player.queue,
+            region: player.region,
+            joined_at_epoch_ms: player.joined_at_ms as u64,
+            restored,
+            prioritized,
+        })
+        .await;
+}
+
+/// How the player's search departs from the usual: the head start they are owed for a
+/// game called off on them, used up by this search, and wider matching while they are
+/// still in placement.
+async fn search_options(
+    state: &Arc<AppState>,
+    user_id: &str,
+    avoid_abandoners: bool,
+) -> SearchOptions {
+    let not_before = unix_now() - lobby::REQUEUE_PRIORITY_TTL.as_secs() as i64;
+    let granted = repo::take_requeue_priority(&state.db, user_id, not_before).await;
+    if let Some(granted) = &granted {
+        println!(
+            "User {} searching with priority ({})",
+            user_id, granted.reason
+        );
+    }
+    let activity = season::activity(&state.db, user_id, unix_now()).await;
+    let placement = rating::is_provisional(activity.ranked_games);
+    SearchOptions {
+        priority: granted.is_some(),
+        any_region: granted.is_some() || placement,
+        avoid: match granted {
+            Some(granted) if avoid_abandoners => {
+                serde_json::from_str(&granted.abandoners).unwrap_or_default()
+            }
+            _ => Vec::new(),
+        },
+    }
+}
+
+/// Waits in the lobby until another player seats us, or our own retry forms a table.
+/// Returns `None` if the client disconnects first.
+async fn wait_for_table(
+    state: &Arc<AppState>,
+    user_id: &str,
+    mut seat_rx: oneshot::Receiver<Seating>,
+    client_tx: &mpsc::Sender<ServerMessage>,
+    locale: Locale,
+    receiver: &mut SplitStream<WebSocket>,
+) -> Option<Seating> {
+    let mut retry = tokio::time::interval(LOBBY_RETRY_INTERVAL);
+    loop {
+        tokio::select! {
+            seating = &mut seat_rx => return seating.ok(),
+            _ = retry.tick() => {
+                if refuse_for_maintenance(state, client_tx).await {
+                    state.lobby.leave(user_id).await;
+                    return None;
+                }
+                if let Some(table) = state.lobby.retry(user_id).await {
+                    return host_table(state, table, client_tx, locale).await;
+                }
+            }
+            _ = client_gone(receiver) => return None,
+        }
+    }
+}
+
+/// Waits until the scheduled game starts and seats the player at it. Returns `None` if
+/// the game is called off, the player didn't get a seat or the client disconnects first.
+async fn wait_for_scheduled_game(
+    state: &Arc<AppState>,
+    user_id: &str,
+    game_id: i64,
+    client_tx: &mpsc::Sender<ServerMessage>,
+    locale: Locale,
+    receiver: &mut SplitStream<WebSocket>,
+) -> Option<Seating> {
+    let mut poll = tokio::time::interval(SCHEDULED_POLL_INTERVAL);
+    loop {
+        tokio::select! {
+            _ = poll.tick() => {
+                let game = repo::get_scheduled_game(&state.db, game_id).await?;
+                let error = match (game.status.as_str(), game.room_id) {
+                    ("scheduled", _) => continue,
+                    ("started", Some(room_id)) => match games::seat_in(state, &room_id, user_id).await {
+                        Some(seat) => {
+                            return Some(Seating {
+                                room_id,
+                                players: seat.players,
+                                regions: seat.regions,
+                            });
+                        }
+                        None => "game_not_running",
+                    },
+                    _ => "scheduled_game_cancelled",
+                };
+                let error = i18n::localize_error(error, locale);
+                let _ = client_tx
+                    .send(ServerMessage::Error {
+                        message: error.text,
+                        code: Some(error.key.to_string()),
+                        correlation_id: None,
+                    })
+                    .await;
+                return None;
+            }
+            _ = client_gone(receiver) => return None,
+        }
+    }
+}
+
+/// Resolves once the client disconnects. Anything it sends meanwhile is dropped; there is
+/// nothing to act on before it has a seat.
+async fn client_gone(receiver: &mut SplitStream<WebSocket>) {
+    while let Some(Ok(msg)) = receiver.next().await {
+        if matches!(msg, Message::Close(_)) {
+            break;
+        }
+    }
+}
+
+/// Streams the game browser's listing: all of it first, then each change; anything the
+/// client sends is ignored.
+async fn handle_browser(socket: WebSocket, state: Arc<AppState>, _permit: Permit) {
+    let (mut sender, mut receiver) = socket.split();
+    // Subscribed before the first listing is taken, so no change falls in between
+    let mut updates = state.directory.subscribe();
+    let directory = state.directory.clone();
+
+    let mut send_task = tokio::spawn(async move {
+        let mut msg = ServerMessage::RoomList {
+            rooms: directory.list(&BrowserQuery::default()),
+        };
+        loop {
+            if let Ok(text) = serde_json::to_string(&msg)
+                && sender.send(Message::Text(text.into())).await.is_err()
+            {
+                break;
+            }
+            msg = match updates.recv().await {
+                Ok(BrowserUpdate::Listed(room)) => ServerMessage::RoomListed { room },
+                Ok(BrowserUpdate::Unlisted(room_id)) => ServerMessage::RoomUnlisted { room_id },
+                Err(RecvError::Lagged(_)) => ServerMessage::RoomList {
+                    rooms: directory.list(&BrowserQuery::default()),
+                },
+                Err(RecvError::Closed) => break,
+            };
+        }
+    });
+    let mut recv_task = tokio::spawn(async move {
+        while let Some(Ok(msg)) = receiver.next().await {
+            if matches!(msg, Message::Close(_)) {
+                break;
+            }
+        }
+    });
+
+    tokio::select! {
+        _ = (&mut send_task) => recv_task.abort(),
+        _ = (&mut recv_task) => send_task.abort(),
+    };
+}
+
+/// Streams a room's public updates to a share-link viewer; anything they send is ignored.
+async fn handle_spectator(
+    socket: WebSocket,
+    state: Arc<AppState>,
+    room_id: String,
+    _permit: Permit,
+) {
+    let room_tx = state.active_rooms.lock().await.get(&room_id).cloned();
+    let Some(room_tx) = room_tx else {
+        return; // The game is over or never existed; dropping the socket closes it
+    };
+
+    let (mut sender, mut receiver) = socket.split();
+    let (client_tx, mut client_rx) =
+        tokio::sync::mpsc::channel::<crate::api::events::ServerMessage>(100);
+    let spectator_id = uuid::Uuid::new_v4().to_string();
+
+    let _ = room_tx
+        .send(RoomEvent::SpectatorJoined(spectator_id.clone(), client_tx))
+        .await;
+
+    let mut send_task = tokio::spawn(async move {
+        while let Some(msg) = client_rx.recv().await {
+            if let Ok(text) = serde_json::to_string(&msg)
+                && sender.send(Message::Text(text.into())).await.is_err()
+            {
+                break;
+            }
+        }
+    });
+    let mut recv_task = tokio::spawn(async move {
+        while let Some(Ok(msg)) = receiver.next().await {
+            if matches!(msg, Message::Close(_)) {
+                break;
+            }
+        }
+    });
+
+    tokio::select! {
+        _ = (&mut send_task) => recv_task.abort(),
+        _ = (&mut recv_task) => send_task.abort(),
+    };
+
+    let _ = room_tx.send(RoomEvent::SpectatorLeft(spectator_id)).await;
+}
+
+/// Streams a room's unsanitized state and action log to an admin, starting with the actions
+/// taken before they attached; anything they send is ignored.
+async fn handle_observer(
+    socket: WebSocket,
+    state: Arc<AppState>,
+    room_id: String,
+    _permit: Permit,
+) {
+    let room_tx = state.active_rooms.lock().await.get(&room_id).cloned();
+    let Some(room_tx) = room_tx else {
+        return; // The game is over or never existed; dropping the socket closes it
+    };
+
+    let (mut sender, mut receiver) = socket.split();
+    let (client_tx, mut client_rx) = mpsc::channel::<ServerMessage>(100);
+    let observer_id = uuid::Uuid::new_v4().to_string();
+
+    // Backfill from the audit log; writes still buffered there may show up only live
+    for entry in repo::get_audit_entries_for_room(&state.db, &room_id).await {
+        let Ok(action) = serde_json::from_str::<ClientMessage>(&entry.action) else {
+            continue;
+        };
+        let msg = ServerMessage::ObservedAction {
+            user_id: entry.user_id,
+            action,
+            error: entry.error,
+            created_at_ms: entry.created_at_ms,
+        };
+        if let Ok(text) = serde_json::to_string(&msg)
+            && sender.send(Message::Text(text.into())).await.is_err()
+        {
+            return;
+        }
+    }
+
+    let _ = room_tx
+        .send(RoomEvent::ObserverJoined(observer_id.clone(), client_tx))
+        .await;
+
+    let mut send_task = tokio::spawn(async move {
+        while let Some(msg) = client_rx.recv().await {
+            if let Ok(text) = serde_json::to_string(&msg)
+                && sender.send(Message::Text(text.into())).await.is_err()
+            {
+                break;
+            }
+        }
+    });
+    let mut recv_task = tokio::spawn(async move {
+        while let Some(Ok(msg)) = receiver.next().await {
+            if matches!(msg, Message::Close(_)) {
+                break;
+            }
+        }
+    });
+
+    tokio::select! {
+        _ = (&mut send_task) => recv_task.abort(),
+        _ = (&mut recv_task) => send_task.abort(),
+    };
+
+    let _ = room_tx.send(RoomEvent::ObserverLeft(observer_id)).await;
+}
+
+fn unix_now_ms() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as i64
+}
+
+fn unix_now() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64
+}
@@ -0,0 +1,342 @@
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::engine::points::PointsTable;
+use crate::engine::rule_set::{JokerLimits, RuleSet};
+use crate::matchmaking::lobby::LobbySettings;
+use crate::ranking::season::SeasonSettings;
+
+/// Runtime configuration, read from `CARIOCA_*` environment variables.
+///
+/// Every field has a development-friendly default so `cargo run` works without setup.
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub host: String,
+    pub port: u16,
+    /// When set, the server listens on this Unix socket instead of `host:port`.
+    pub unix_socket: Option<PathBuf>,
+    pub database_url: String,
+    /// Database the heavy read paths (leaderboards, history, stats) query, e.g. a replica;
+    /// the main database opened again read-only when unset.
+    pub database_read_url: Option<String>,
+    /// Connections in the read pool, kept apart from the ones game results are written on.
+    pub database_read_connections: u32,
+    /// PEM certificate chain; together with `tls_key_path` enables HTTPS/WSS.
+    pub tls_cert_path: Option<PathBuf>,
+    pub tls_key_path: Option<PathBuf>,
+    /// JSON file with per-locale blocked words and an allowlist; built-in lists otherwise.
+    pub word_filter_file: Option<PathBuf>,
+    /// Existing accounts promoted to admin at startup; register the account first, then list
+    /// it. Registering one of these names never grants admin by itself.
+    pub admin_usernames: Vec<String>,
+    /// Base thinking time per turn; `0` disables turn timers entirely.
+    pub turn_seconds: u64,
+    /// Extra time per player per game, drained once a turn overruns `turn_seconds`.
+    pub time_bank_seconds: u64,
+    /// Seconds-before-deadline at which a `TurnWarning` is broadcast.
+    pub turn_warning_seconds: Vec<u64>,
+    /// Most grace a player's measured round trip can buy them past a turn deadline; `0`
+    /// expires turns on the dot.
+    pub turn_latency_grace_max_ms: u64,
+    /// How long humans get to ready up between rounds before being auto-readied; `0` waits forever.
+    pub ready_countdown_seconds: u64,
+    /// House rules applied to every new room.
+    pub rules: RuleSet,
+    /// A room with no human action for this long is abandoned; zero never abandons.
+    pub idle_room_ttl_seconds: u64,
+    /// How long the game pauses for a dropped player before bots play their turns; `0` never pauses.
+    pub disconnect_grace_seconds: u64,
+    /// Longest break players can agree to; `0` turns breaks off.
+    pub break_max_seconds: u64,
+    /// Deals each round card by card with `CardDealt` events, for clients that animate it.
+    pub stream_deals: bool,
+    /// PEM bundle of CAs trusted for `https://` webhooks.
+    pub webhook_ca_file: PathBuf,
+    /// Lets webhooks target loopback and private networks; for local development only.
+    pub webhook_allow_private: bool,
+    /// Exposes developer endpoints such as loading a game position; never in production.
+    pub dev_endpoints: bool,
+    pub webhook_timeout_seconds: u64,
+    /// PKCS#8 PEM P-256 key signing web pushes; web push is off when unset.
+    pub vapid_private_key_file: Option<PathBuf>,
+    /// The matching public key, base64url (uncompressed point), handed to browsers.
+    pub vapid_public_key: String,
+    /// Contact URI sent to push services, e.g. `mailto:admin@example.com`.
+    pub vapid_subject: String,
+    /// Secret the seeds each game was shuffled from are encrypted with before they are
+    /// stored; when unset a key is made up at startup, and seeds stored before a restart
+    /// can no longer be read.
+    pub shuffle_seed_key_file: Option<PathBuf>,
+    /// How long a player may leave their turn idle before it is pushed to them.
+    pub push_idle_turn_seconds: u64,
+    pub season_days: u64,
+    /// Share of a rating's distance from 1000 kept when a season rolls over.
+    pub season_rating_carryover: f64,
+    /// How long a player waits for a same-region table before any region will do.
+    pub matchmaking_region_wait_seconds: u64,
+    /// How long a player waits for other humans before bots take the empty seats.
+    pub matchmaking_bot_fill_seconds: u64,
+    /// Most WebSocket connections open at once; zero is unlimited.
+    pub max_connections: usize,
+    /// Most game rooms running at once; zero is unlimited.
+    pub max_rooms: usize,
+    /// How many connections (or new rooms) may queue for a slot before more are refused.
+    pub admission_queue_limit: usize,
+    /// Registration and login attempts allowed per client IP per minute; zero is unlimited.
+    pub rate_limit_auth_per_minute: u32,
+    /// WebSocket upgrades allowed per client IP per minute; zero is unlimited.
+    pub rate_limit_ws_per_minute: u32,
+    /// Takes the client IP from `X-Forwarded-For`; only safe behind a proxy that sets it.
+    pub rate_limit_trust_forwarded: bool,
+    /// Accounts kept in memory for logins and profile reads; zero disables the cache.
+    pub user_cache_capacity: usize,
+}
+
+impl Config {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+
+        Self {
+            host: env_or("CARIOCA_HOST", defaults.host),
+            port: env_or("CARIOCA_PORT", defaults.port),
+            unix_socket: env_opt("CARIOCA_UNIX_SOCKET"),
+            database_url: env_or("CARIOCA_DATABASE_URL", defaults.database_url),
+            database_read_url: env_opt("CARIOCA_DATABASE_READ_URL"),
+            database_read_connections: env_or(
+                "CARIOCA_DATABASE_READ_CONNECTIONS",
+                defaults.database_read_connections,
+            ),
+            tls_cert_path: env_opt("CARIOCA_TLS_CERT"),
+            tls_key_path: env_opt("CARIOCA_TLS_KEY"),
+            word_filter_file: env_opt("CARIOCA_WORD_FILTER_FILE"),
+            shuffle_seed_key_file: env_opt("CARIOCA_SHUFFLE_SEED_KEY_FILE"),
+            admin_usernames: env_list("CARIOCA_ADMIN_USERNAMES"),
+            turn_seconds: env_or("CARIOCA_TURN_SECONDS", defaults.turn_seconds),
+            time_bank_seconds: env_or("CARIOCA_TIME_BANK_SECONDS", defaults.time_bank_seconds),
+            turn_warning_seconds: env_parsed_list("CARIOCA_TURN_WARNING_SECONDS")
+                .unwrap_or(defaults.turn_warning_seconds),
+            turn_latency_grace_max_ms: env_or(
+                "CARIOCA_TURN_LATENCY_GRACE_MAX_MS",
+                defaults.turn_latency_grace_max_ms,
+            ),
+            ready_countdown_seconds: env_or(
+                "CARIOCA_READY_COUNTDOWN_SECONDS",
+                defaults.ready_countdown_seconds,
+            ),
+            rules: RuleSet {
+                turn_rotation: env_or("CARIOCA_TURN_ROTATION", defaults.rules.turn_rotation),
+                stalemate_turns: env_or("CARIOCA_STALEMATE_TURNS", defaults.rules.stalemate_turns),
+                carioca_bonus: env_or("CARIOCA_CARIOCA_BONUS", defaults.rules.carioca_bonus),
+                points: PointsTable {
+                    joker: env_or("CARIOCA_JOKER_POINTS", defaults.rules.points.joker),
+                    ace: env_or("CARIOCA_ACE_POINTS", defaults.rules.points.ace),
+                    face_cards: env_or(
+                        "CARIOCA_FACE_CARD_POINTS",
+                        defaults.rules.points.face_cards,
+                    ),
+                    wild_two: env_or("CARIOCA_WILD_TWO_POINTS", defaults.rules.points.wild_two),
+                },
+                jokers: JokerLimits {
+                    per_trio: env_or(
+                        "CARIOCA_MAX_JOKERS_PER_TRIO",
+                        defaults.rules.jokers.per_trio,
+                    ),
+                    per_escala: env_or(
+                        "CARIOCA_MAX_JOKERS_PER_ESCALA",
+                        defaults.rules.jokers.per_escala,
+                    ),
+                    per_bajada: env_or(
+                        "CARIOCA_MAX_JOKERS_PER_BAJADA",
+                        defaults.rules.jokers.per_bajada,
+                    ),
+                },
+                shed_on_bajada_turn: env_or(
+                    "CARIOCA_SHED_ON_BAJADA_TURN",
+                    defaults.rules.shed_on_bajada_turn,
+                ),
+                discard_visibility: env_or(
+                    "CARIOCA_DISCARD_VISIBILITY",
+                    defaults.rules.discard_visibility,
+                ),
+                abandonment: env_or("CARIOCA_ABANDONMENT_POLICY", defaults.rules.abandonment),
+                timeout: env_or("CARIOCA_TIMEOUT_POLICY", defaults.rules.timeout),
+                round_limit: env_or("CARIOCA_ROUND_LIMIT", defaults.rules.round_limit),
+                max_hint_level: env_or("CARIOCA_MAX_HINT_LEVEL", defaults.rules.max_hint_level),
+                escala_corta: env_or("CARIOCA_ESCALA_CORTA", defaults.rules.escala_corta),
+                extra_melds: env_or("CARIOCA_EXTRA_MELDS", defaults.rules.extra_melds),
+                deck_jokers: env_or("CARIOCA_DECK_JOKERS", defaults.rules.deck_jokers),
+                twos: env_or("CARIOCA_TWOS", defaults.rules.twos),
+                tie_break: env_or("CARIOCA_TIE_BREAK", defaults.rules.tie_break),
+            },
+            idle_room_ttl_seconds: env_or(
+                "CARIOCA_IDLE_ROOM_TTL_SECONDS",
+                defaults.idle_room_ttl_seconds,
+            ),
+            disconnect_grace_seconds: env_or(
+                "CARIOCA_DISCONNECT_GRACE_SECONDS",
+                defaults.disconnect_grace_seconds,
+            ),
+            break_max_seconds: env_or("CARIOCA_BREAK_MAX_SECONDS", defaults.break_max_seconds),
+            stream_deals: env_or("CARIOCA_STREAM_DEALS", defaults.stream_deals),
+            webhook_ca_file: env_or("CARIOCA_WEBHOOK_CA_FILE", defaults.webhook_ca_file),
+            webhook_allow_private: env_or(
+                "CARIOCA_WEBHOOK_ALLOW_PRIVATE",
+                defaults.webhook_allow_private,
+            ),
+            dev_endpoints: env_or("CARIOCA_DEV_ENDPOINTS", defaults.dev_endpoints),
+            webhook_timeout_seconds: env_or(
+                "CARIOCA_WEBHOOK_TIMEOUT_SECONDS",
+                defaults.webhook_timeout_seconds,
+            ),
+            vapid_private_key_file: env_opt("CARIOCA_VAPID_PRIVATE_KEY_FILE"),
+            vapid_public_key: env_or("CARIOCA_VAPID_PUBLIC_KEY", defaults.vapid_public_key),
+            vapid_subject: env_or("CARIOCA_VAPID_SUBJECT", defaults.vapid_subject),
+            push_idle_turn_seconds: env_or(
+                "CARIOCA_PUSH_IDLE_TURN_SECONDS",
+                defaults.push_idle_turn_seconds,
+            ),
+            season_days: env_or("CARIOCA_SEASON_DAYS", defaults.season_days),
+            season_rating_carryover: env_or(
+                "CARIOCA_SEASON_RATING_CARRYOVER",
+                defaults.season_rating_carryover,
+            ),
+            matchmaking_region_wait_seconds: env_or(
+                "CARIOCA_MATCHMAKING_REGION_WAIT_SECONDS",
+                defaults.matchmaking_region_wait_seconds,
+            ),
+            matchmaking_bot_fill_seconds: env_or(
+                "CARIOCA_MATCHMAKING_BOT_FILL_SECONDS",
+                defaults.matchmaking_bot_fill_seconds,
+            ),
+            max_connections: env_or("CARIOCA_MAX_CONNECTIONS", defaults.max_connections),
+            max_rooms: env_or("CARIOCA_MAX_ROOMS", defaults.max_rooms),
+            admission_queue_limit: env_or(
+                "CARIOCA_ADMISSION_QUEUE_LIMIT",
+                defaults.admission_queue_limit,
+            ),
+            rate_limit_auth_per_minute: env_or(
+                "CARIOCA_RATE_LIMIT_AUTH_PER_MINUTE",
+                defaults.rate_limit_auth_per_minute,
+            ),
+            rate_limit_ws_per_minute: env_or(
+                "CARIOCA_RATE_LIMIT_WS_PER_MINUTE",
+                defaults.rate_limit_ws_per_minute,
+            ),
+            rate_limit_trust_forwarded: env_or(
+                "CARIOCA_RATE_LIMIT_TRUST_FORWARDED",
+                defaults.rate_limit_trust_forwarded,
+            ),
+            user_cache_capacity: env_or(
+                "CARIOCA_USER_CACHE_CAPACITY",
+                defaults.user_cache_capacity,
+            ),
+        }
+    }
+
+    pub fn lobby_settings(&self) -> LobbySettings {
+        LobbySettings {
+            region_wait: Duration::from_secs(self.matchmaking_region_wait_seconds),
+            bot_fill_wait: Duration::from_secs(self.matchmaking_bot_fill_seconds),
+        }
+    }
+
+    pub fn season_settings(&self) -> SeasonSettings {
+        SeasonSettings {
+            length_secs: self.season_days as i64 * 24 * 60 * 60,
+            carryover: self.season_rating_carryover,
+        }
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            host: "0.0.0.0".to_string(),
+            port: 3000,
+            unix_socket: None,
+            // Use an in-memory SQLite DB for the initial phase/testing
+            database_url: "sqlite::memory:".to_string(),
+            database_read_url: None,
+            database_read_connections: 8,
+            tls_cert_path: None,
+            tls_key_path: None,
+            word_filter_file: None,
+            admin_usernames: Vec::new(),
+            turn_seconds: 45,
+            time_bank_seconds: 60,
+            turn_warning_seconds: vec![10, 5],
+            turn_latency_grace_max_ms: 2000,
+            ready_countdown_seconds: 30,
+            rules: RuleSet::default(),
+            idle_room_ttl_seconds: 30 * 60,
+            disconnect_grace_seconds: 60,
+            break_max_seconds: 5 * 60,
+            stream_deals: false,
+            webhook_ca_file: PathBuf::from("/etc/ssl/certs/ca-certificates.crt"),
+            webhook_allow_private: false,
+            dev_endpoints: false,
+            webhook_timeout_seconds: 10,
+            vapid_private_key_file: None,
+            vapid_public_key: String::new(),
+            vapid_subject: "mailto:admin@localhost".to_string(),
+            shuffle_seed_key_file: None,
+            push_idle_turn_seconds: 60,
+            season_days: 90,
+            season_rating_carryover: 0.5,
+            matchmaking_region_wait_seconds: 30,
+            // Matches with bots straight away, as before humans could be paired up
+            matchmaking_bot_fill_seconds: 0,
+            max_connections: 2000,
+            max_rooms: 500,
+            admission_queue_limit: 200,
+            rate_limit_auth_per_minute: 20,
+            rate_limit_ws_per_minute: 60,
+            rate_limit_trust_forwarded: false,
+            user_cache_capacity: 10_000,
+        }
+    }
+}
+
+/// Reads and parses `key`, falling back to `default` when unset or unparsable.
+fn env_or<T: FromStr>(key: &str, default: T) -> T {
+    match std::env::var(key) {
+        Ok(raw) => raw.parse().unwrap_or_else(|_| {
+            println!("[Config] Ignoring invalid value for {}: {:?}", key, raw);
+            default
+        }),
+        Err(_) => default,
+    }
+}
+
+/// Comma-separated list; unset means empty.
+fn env_list(key: &str) -> Vec<String> {
+    std::env::var(key)
+        .map(|raw| {
+            raw.split(',')
+                .map(str::trim)
+                .filter(|item| !item.is_empty())
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Comma-separated list of values; `None` when unset, empty or any item fails to parse.
+fn env_parsed_list<T: FromStr>(key: &str) -> Option<Vec<T>> {
+    let items = env_list(key);
+    if items.is_empty() {
+        return None;
+    }
+    let parsed: Result<Vec<T>, _> = items.iter().map(|item| item.parse()).collect();
+    parsed
+        .map_err(|_| println!("[Config] Ignoring invalid list for {}: {:?}", key, items))
+        .ok()
+}
+
+fn env_opt<T: FromStr>(key: &str) -> Option<T> {
+    std::env::var(key)
+        .ok()
+        .filter(|raw| !raw.is_empty())
+        .and_then(|raw| raw.parse().ok())
+}
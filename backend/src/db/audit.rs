@@ -0,0 +1,169 @@
+use serde::Serialize;
+use sqlx::SqlitePool;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::sync::mpsc;
+
+use crate::api::events::ClientMessage;
+use crate::db::models::AuditEntry;
+use crate::db::repo;
+
+/// How many entries may wait for the writer; past that they are dropped and counted, so a
+/// stalled database can't grow the queue without bound.
+const QUEUE_CAPACITY: usize = 4096;
+
+/// Cheap, cloneable handle used by rooms to record every action they process.
+///
+/// Entries are handed to a background writer task so the room actor never
+/// waits on SQLite.
+#[derive(Clone)]
+pub struct AuditLog {
+    sender: mpsc::Sender<AuditEntry>,
+    dropped_total: Arc<AtomicU64>,
+}
+
+/// Queue counters, as shown in the admin API.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AuditMetrics {
+    /// Entries waiting to be written
+    pub queued: usize,
+    /// Entries lost because the queue was full
+    pub dropped_total: u64,
+}
+
+impl AuditLog {
+    /// Spawns the writer task and returns a handle feeding it.
+    pub fn spawn(pool: SqlitePool) -> Self {
+        Self::with_capacity(pool, QUEUE_CAPACITY)
+    }
+
+    fn with_capacity(pool: SqlitePool, capacity: usize) -> Self {
+        let (sender, mut receiver) = mpsc::channel::<AuditEntry>(capacity);
+
+        tokio::spawn(async move {
+            while let Some(entry) = receiver.recv().await {
+                if let Err(e) = repo::insert_audit_entry(&pool, &entry).await {
+                    println!(
+                        "[Audit] Failed to persist entry for {}: {}",
+                        entry.user_id, e
+                    );
+                }
+            }
+        });
+
+        Self {
+            sender,
+            dropped_total: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    pub fn record_action(
+        &self,
+        room_id: &str,
+        user_id: &str,
+        action: &ClientMessage,
+        error: Option<&str>,
+    ) {
+        let entry = AuditEntry {
+            user_id: user_id.to_string(),
+            room_id: room_id.to_string(),
+            action: serde_json::to_string(action).unwrap_or_default(),
+            accepted: error.is_none(),
+            error: error.map(str::to_string),
+            created_at_ms: now_ms(),
+        };
+
+        if self.sender.try_send(entry).is_err() {
+            self.dropped_total.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    pub fn metrics(&self) -> AuditMetrics {
+        AuditMetrics {
+            queued: self.sender.max_capacity() - self.sender.capacity(),
+            dropped_total: self.dropped_total.load(Ordering::Relaxed),
+        }
+    }
+}
+
+fn now_ms() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::sqlite::SqlitePoolOptions;
+    use std::time::Duration;
+
+    async fn audit_pool() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        repo::create_audit_table(&pool).await.unwrap();
+        pool
+    }
+
+    async fn written(pool: &SqlitePool, count: usize) -> Vec<AuditEntry> {
+        for _ in 0..100 {
+            let entries = repo::get_audit_entries_for_room(pool, "room").await;
+            if entries.len() >= count {
+                return entries;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("The writer never caught up");
+    }
+
+    #[tokio::test]
+    async fn records_accepted_and_rejected_actions() {
+        let pool = audit_pool().await;
+        let audit = AuditLog::spawn(pool.clone());
+        audit.record_action("room", "ana", &ClientMessage::DrawFromDeck, None);
+        audit.record_action(
+            "room",
+            "beto",
+            &ClientMessage::DrawFromDiscard,
+            Some("not_your_turn"),
+        );
+
+        let entries = written(&pool, 2).await;
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].user_id, "ana");
+        assert!(entries[0].accepted);
+        assert_eq!(entries[0].error, None);
+        assert!(entries[0].action.contains("DrawFromDeck"));
+        assert_eq!(entries[1].user_id, "beto");
+        assert!(!entries[1].accepted);
+        assert_eq!(entries[1].error.as_deref(), Some("not_your_turn"));
+        assert_eq!(audit.metrics().dropped_total, 0);
+    }
+
+    #[tokio::test]
+    async fn drops_and_counts_entries_once_the_queue_is_full() {
+        let pool = audit_pool().await;
+        let audit = AuditLog::with_capacity(pool.clone(), 2);
+        // The single-threaded test runtime doesn't run the writer until this task yields
+        for _ in 0..5 {
+            audit.record_action("room", "ana", &ClientMessage::PassTurn, None);
+        }
+        assert_eq!(
+            audit.metrics(),
+            AuditMetrics {
+                queued: 2,
+                dropped_total: 3
+            }
+        );
+
+        assert_eq!(written(&pool, 2).await.len(), 2);
+        audit.record_action("room", "ana", &ClientMessage::PassTurn, None);
+        assert_eq!(written(&pool, 3).await.len(), 3);
+        assert_eq!(audit.metrics().dropped_total, 3);
+    }
+}
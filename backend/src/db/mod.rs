@@ -1,2 +1,5 @@
+pub mod audit;
 pub mod models;
 pub mod repo;
+pub mod seed_vault;
+pub mod user_cache;
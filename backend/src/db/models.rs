@@ -7,4 +7,339 @@ pub struct User {
     pub username: String,
     pub password_hash: String,
     pub created_at: i64,
+    /// "player", "moderator" or "admin"
+    pub role: String,
+}
+
+/// A single `ClientMessage` as received by a room, with the outcome of applying it.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct AuditEntry {
+    pub user_id: String,
+    pub room_id: String,
+    /// The raw `ClientMessage`, serialized as JSON
+    pub action: String,
+    pub accepted: bool,
+    /// Engine/room error when the action was rejected
+    pub error: Option<String>,
+    pub created_at_ms: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Report {
+    pub id: String,
+    pub reporter_id: String,
+    pub reported_id: String,
+    pub room_id: String,
+    pub reason: String,
+    /// "open" until a moderator resolves it
+    pub status: String,
+    pub resolution: Option<String>,
+    pub created_at: i64,
+}
+
+/// A ranked season. Ratings earned during it are kept apart from every other season's.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Season {
+    pub id: i64,
+    /// Unix seconds
+    pub starts_at: i64,
+    pub ends_at: i64,
+    /// Set once the rollover has archived its standings
+    pub closed_at: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct SeasonRating {
+    pub user_id: String,
+    pub rating: i64,
+    pub games: i64,
+}
+
+/// A player's ranked play across seasons, for placement and rating decay.
+#[derive(Debug, Clone, PartialEq, Eq, FromRow)]
+pub struct RatingActivity {
+    pub user_id: String,
+    pub ranked_games: i64,
+    pub last_ranked_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct LeaderboardEntry {
+    pub rank: i64,
+    pub user_id: String,
+    pub username: String,
+    pub rating: i64,
+    pub games: i64,
+}
+
+/// A user's turn-notification callback.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Webhook {
+    pub user_id: String,
+    pub url: String,
+    /// Sent back as `X-Carioca-Secret` so the receiver can tell real calls from forged ones
+    pub secret: String,
+    pub created_at: i64,
+}
+
+/// A mute (no chat) or ban (no login/play), optionally time-limited.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Sanction {
+    pub id: String,
+    pub user_id: String,
+    /// "mute" or "ban"
+    pub kind: String,
+    pub reason: String,
+    /// Unix seconds; `None` means permanent
+    pub expires_at: Option<i64>,
+    pub created_at: i64,
+}
+
+/// How a game ended, kept for history and stats.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct GameResult {
+    pub room_id: String,
+    /// "completed" or "abandoned"
+    pub outcome: String,
+    /// Whether the standings count (and were rated, for ranked games)
+    pub counted: bool,
+    pub ranked: bool,
+    pub rounds_played: i64,
+    pub finished_at: i64,
+}
+
+/// One seat's final total in a [`GameResult`].
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct GameResultPlayer {
+    pub room_id: String,
+    pub user_id: String,
+    pub seat: i64,
+    pub total: i64,
+}
+
+/// A single-elimination tournament. Players register until a moderator starts it.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Tournament {
+    pub id: i64,
+    pub name: String,
+    /// "registering", "running" or "finished"
+    pub status: String,
+    pub created_by: String,
+    pub created_at: i64,
+    /// The bracket as JSON, once started
+    #[serde(skip_serializing)]
+    pub bracket: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct TournamentPlayer {
+    pub user_id: String,
+    pub username: String,
+    pub registered_at: i64,
+}
+
+/// A fixed group playing each other heads-up, once per pairing, over as long as it takes.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct League {
+    pub id: i64,
+    pub name: String,
+    pub created_by: String,
+    pub created_at: i64,
+}
+
+/// One scheduled pairing of a [`League`].
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct LeagueFixture {
+    pub id: i64,
+    pub league_id: i64,
+    /// 1-based; a player has at most one fixture per matchday
+    pub matchday: i64,
+    pub home_id: String,
+    pub away_id: String,
+    /// The room of the latest attempt at playing it
+    pub room_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct LeaguePlayer {
+    pub user_id: String,
+    pub username: String,
+}
+
+/// A game set for a future time. Whoever RSVPs is seated when it starts; bots take the
+/// seats left over.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ScheduledGame {
+    pub id: i64,
+    pub name: String,
+    pub created_by: String,
+    /// Unix seconds
+    pub starts_at: i64,
+    pub max_players: i64,
+    /// Anyone may RSVP; otherwise only the host and invited players
+    pub public: bool,
+    /// "scheduled", "started" or "cancelled"
+    pub status: String,
+    /// Set once started
+    pub room_id: Option<String>,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ScheduledGameRsvp {
+    pub user_id: String,
+    pub username: String,
+    pub rsvp_at: i64,
+}
+
+/// A finished game two players sat at together, with both their totals.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct SharedGame {
+    pub room_id: String,
+    pub finished_at: i64,
+    pub total: i64,
+    pub opponent_total: i64,
+}
+
+/// A player's chosen look, shown to everyone at their table. Ids come from
+/// `api::cosmetics`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, FromRow)]
+pub struct Cosmetics {
+    pub card_back: String,
+    pub table_theme: String,
+}
+
+/// One bot's round at a table with at least one human.
+#[derive(Debug, Clone, FromRow)]
+pub struct BotRoundStat {
+    pub room_id: String,
+    pub bot_id: String,
+    /// `easy`, `medium` or `hard`
+    pub difficulty: String,
+    pub round_index: i64,
+    pub went_out: bool,
+    /// Penalty points the bot took for the round
+    pub leftover_points: i64,
+    /// Turn of the round (1-based) the bot dropped its hand on; `None` if it never did
+    pub bajada_turn: Option<i64>,
+    pub finished_at: i64,
+}
+
+/// How bots of one difficulty have fared against humans, over the rounds recorded.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct BotPerformance {
+    pub difficulty: String,
+    pub rounds: i64,
+    /// Share of rounds the bot went out in
+    pub win_rate: f64,
+    pub average_leftover_points: f64,
+    /// Share of rounds the bot dropped its hand in
+    pub dropped_rate: f64,
+    /// Mean turn of the bajada, over the rounds it happened in
+    pub average_bajada_turn: Option<f64>,
+}
+
+/// A player waiting in the matchmaking lobby, kept so their place outlives a restart.
+#[derive(Debug, Clone, FromRow)]
+pub struct QueuedPlayer {
+    pub user_id: String,
+    pub queue: String,
+    pub region: Option<String>,
+    /// Epoch milliseconds
+    pub joined_at_ms: i64,
+}
+
+/// Head start in matchmaking for a player whose game was called off on them, until they
+/// next search.
+#[derive(Debug, Clone, FromRow)]
+pub struct RequeuePriority {
+    pub user_id: String,
+    /// JSON array of the user ids who abandoned that game
+    pub abandoners: String,
+    /// `server` or `opponent_left`
+    pub reason: String,
+    pub granted_at: i64,
+}
+
+/// A game the room saved instead of finishing, because the server went down for
+/// maintenance before it ended.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct RoomSnapshot {
+    pub room_id: String,
+    /// The position as `engine::snapshot::GameSnapshot` JSON; post it to
+    /// `/api/dev/rooms` to play on from there
+    pub snapshot: String,
+    pub created_at: i64,
+}
+
+/// A finished game whose replay didn't reproduce it: an engine bug or a tampered log.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct ReplayFlag {
+    pub id: i64,
+    pub room_id: String,
+    /// Where the replay parted ways, e.g. `the table differs after step 212`
+    pub reason: String,
+    /// The seed and input log, as JSON, to reproduce it with
+    pub replay: String,
+    pub created_at: i64,
+}
+
+/// Where a player stands on the practice ladder, `matchmaking::adaptive`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, FromRow)]
+pub struct BotLevel {
+    pub level: i64,
+    /// Consecutive practice wins (positive) or losses (negative) at this level
+    pub streak: i64,
+}
+
+#[derive(Debug, Clone, FromRow)]
+pub struct Avatar {
+    pub content_type: String,
+    pub image: Vec<u8>,
+    /// Changes with every upload; part of the avatar's URL
+    pub version: i64,
+}
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct UsernameChange {
+    pub old_username: String,
+    pub new_username: String,
+    pub changed_at: i64,
+}
+
+/// A pair of accounts the collusion scan thinks a moderator should look at.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct CollusionFlag {
+    pub id: i64,
+    pub user_a: String,
+    pub user_b: String,
+    /// `shed_feeding`, `win_trading` or `discard_feeding`
+    pub kind: String,
+    pub score: f64,
+    pub games: i64,
+    pub detail: String,
+    /// "open" until a moderator resolves it
+    pub status: String,
+    pub resolution: Option<String>,
+    pub created_at: i64,
+    /// Last time a scan found the pattern
+    pub updated_at: i64,
+}
+
+/// The seeds a finished game's rounds were shuffled from, encrypted with the server's
+/// seed key so only admins auditing the deal can read them.
+#[derive(Debug, Clone, FromRow)]
+pub struct ShuffleSeeds {
+    pub room_id: String,
+    /// Jokers in the deck the seeds shuffled
+    pub deck_jokers: i64,
+    /// A sealed JSON array of `RoundSeed`s, see `SeedVault`
+    pub sealed: String,
+}
+
+/// A title a player has earned; ids come from `ranking::titles`.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct EarnedTitle {
+    pub title_id: String,
+    pub earned_at: i64,
 }
@@ -1,5 +1,11 @@
 use sqlx::SqlitePool;
-use crate::db::models::User;
+use crate::db::models::{
+    AuditEntry, Avatar, BotLevel, BotPerformance, BotRoundStat, CollusionFlag, Cosmetics,
+    EarnedTitle, GameResult, GameResultPlayer, LeaderboardEntry, League, LeagueFixture,
+    LeaguePlayer, QueuedPlayer, RatingActivity, ReplayFlag, Report, RequeuePriority, RoomSnapshot,
+    Sanction, ScheduledGame, ScheduledGameRsvp, Season, SeasonRating, SharedGame, ShuffleSeeds,
+    Tournament, TournamentPlayer, User, UsernameChange, Webhook,
+};
 
 pub async fn create_user_table(pool: &SqlitePool) -> Result<(), sqlx::Error> {
     sqlx::query(
@@ -8,13 +14,44 @@ pub async fn create_user_table(pool: &SqlitePool) -> Result<(), sqlx::Error> {
             id TEXT PRIMARY KEY,
             username TEXT UNIQUE NOT NULL,
             password_hash TEXT NOT NULL,
-            created_at INTEGER NOT NULL
+            created_at INTEGER NOT NULL,
+            role TEXT NOT NULL DEFAULT 'player'
         )
         "#,
     )
     .execute(pool)
     .await?;
 
+    add_column_if_missing(pool, "users", "role", "TEXT NOT NULL DEFAULT 'player'").await?;
+
+    Ok(())
+}
+
+/// `CREATE TABLE IF NOT EXISTS` won't touch existing tables, so columns added after a
+/// database was first created need an explicit `ALTER TABLE`.
+async fn add_column_if_missing(
+    pool: &SqlitePool,
+    table: &str,
+    column: &str,
+    definition: &str,
+) -> Result<(), sqlx::Error> {
+    let exists =
+        sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM pragma_table_info(?) WHERE name = ?")
+            .bind(table)
+            .bind(column)
+            .fetch_one(pool)
+            .await?
+            > 0;
+
+    if !exists {
+        sqlx::query(&format!(
+            "ALTER TABLE {} ADD COLUMN {} {}",
+            table, column, definition
+        ))
+        .execute(pool)
+        .await?;
+    }
+
     Ok(())
 }
 
@@ -29,16 +66,2340 @@ pub async fn get_user(pool: &SqlitePool, username: &str) -> Option<User> {
 pub async fn insert_user(pool: &SqlitePool, user: &User) -> Result<(), sqlx::Error> {
     sqlx::query(
         r#"
-        INSERT INTO users (id, username, password_hash, created_at)
-        VALUES (?, ?, ?, ?)
+        INSERT INTO users (id, username, password_hash, created_at, role)
+        VALUES (?, ?, ?, ?, ?)
         "#,
     )
     .bind(&user.id)
     .bind(&user.username)
     .bind(&user.password_hash)
     .bind(user.created_at)
+    .bind(&user.role)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn set_user_role(
+    pool: &SqlitePool,
+    user_id: &str,
+    role: &str,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query("UPDATE users SET role = ? WHERE id = ?")
+        .bind(role)
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+pub async fn set_role_by_username(
+    pool: &SqlitePool,
+    username: &str,
+    role: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE users SET role = ? WHERE username = ?")
+        .bind(role)
+        .bind(username)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn create_audit_table(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS audit_log (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            user_id TEXT NOT NULL,
+            room_id TEXT NOT NULL,
+            action TEXT NOT NULL,
+            accepted INTEGER NOT NULL,
+            error TEXT,
+            created_at_ms INTEGER NOT NULL
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS idx_audit_log_room ON audit_log (room_id, created_at_ms)",
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn insert_audit_entry(pool: &SqlitePool, entry: &AuditEntry) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO audit_log (user_id, room_id, action, accepted, error, created_at_ms)
+        VALUES (?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(&entry.user_id)
+    .bind(&entry.room_id)
+    .bind(&entry.action)
+    .bind(entry.accepted)
+    .bind(&entry.error)
+    .bind(entry.created_at_ms)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn get_audit_entries_for_room(pool: &SqlitePool, room_id: &str) -> Vec<AuditEntry> {
+    sqlx::query_as::<_, AuditEntry>(
+        r#"
+        SELECT user_id, room_id, action, accepted, error, created_at_ms
+        FROM audit_log
+        WHERE room_id = ?
+        ORDER BY id
+        "#,
+    )
+    .bind(room_id)
+    .fetch_all(pool)
+    .await
+    .unwrap_or_default()
+}
+
+pub async fn create_moderation_tables(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS reports (
+            id TEXT PRIMARY KEY,
+            reporter_id TEXT NOT NULL,
+            reported_id TEXT NOT NULL,
+            room_id TEXT NOT NULL,
+            reason TEXT NOT NULL,
+            status TEXT NOT NULL,
+            resolution TEXT,
+            created_at INTEGER NOT NULL
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS sanctions (
+            id TEXT PRIMARY KEY,
+            user_id TEXT NOT NULL,
+            kind TEXT NOT NULL,
+            reason TEXT NOT NULL,
+            expires_at INTEGER,
+            created_at INTEGER NOT NULL
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn insert_report(pool: &SqlitePool, report: &Report) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO reports (id, reporter_id, reported_id, room_id, reason, status, resolution, created_at)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(&report.id)
+    .bind(&report.reporter_id)
+    .bind(&report.reported_id)
+    .bind(&report.room_id)
+    .bind(&report.reason)
+    .bind(&report.status)
+    .bind(&report.resolution)
+    .bind(report.created_at)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn list_reports(pool: &SqlitePool, status: Option<&str>) -> Vec<Report> {
+    sqlx::query_as::<_, Report>(
+        "SELECT * FROM reports WHERE (? IS NULL OR status = ?) ORDER BY created_at DESC",
+    )
+    .bind(status)
+    .bind(status)
+    .fetch_all(pool)
+    .await
+    .unwrap_or_default()
+}
+
+pub async fn get_report(pool: &SqlitePool, id: &str) -> Option<Report> {
+    sqlx::query_as::<_, Report>("SELECT * FROM reports WHERE id = ?")
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+        .unwrap_or(None)
+}
+
+pub async fn resolve_report(
+    pool: &SqlitePool,
+    id: &str,
+    resolution: &str,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query("UPDATE reports SET status = 'resolved', resolution = ? WHERE id = ?")
+        .bind(resolution)
+        .bind(id)
+        .execute(pool)
+        .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+pub async fn insert_sanction(pool: &SqlitePool, sanction: &Sanction) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO sanctions (id, user_id, kind, reason, expires_at, created_at)
+        VALUES (?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(&sanction.id)
+    .bind(&sanction.user_id)
+    .bind(&sanction.kind)
+    .bind(&sanction.reason)
+    .bind(sanction.expires_at)
+    .bind(sanction.created_at)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn lift_sanctions(
+    pool: &SqlitePool,
+    user_id: &str,
+    kind: &str,
+) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM sanctions WHERE user_id = ? AND kind = ?")
+        .bind(user_id)
+        .bind(kind)
+        .execute(pool)
+        .await?;
+
+    Ok(result.rows_affected())
+}
+
+/// True if `user_id` has a permanent or not-yet-expired sanction of `kind` at `now` (Unix seconds).
+pub async fn has_active_sanction(pool: &SqlitePool, user_id: &str, kind: &str, now: i64) -> bool {
+    sqlx::query_scalar::<_, i64>(
+        r#"
+        SELECT COUNT(*) FROM sanctions
+        WHERE user_id = ? AND kind = ? AND (expires_at IS NULL OR expires_at > ?)
+        "#,
+    )
+    .bind(user_id)
+    .bind(kind)
+    .bind(now)
+    .fetch_one(pool)
+    .await
+    .map(|count| count > 0)
+    .unwrap_or(false)
+}
+
+pub async fn create_notification_tables(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS webhooks (
+            user_id TEXT PRIMARY KEY,
+            url TEXT NOT NULL,
+            secret TEXT NOT NULL,
+            created_at INTEGER NOT NULL
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS push_subscriptions (
+            endpoint TEXT PRIMARY KEY,
+            user_id TEXT NOT NULL,
+            created_at INTEGER NOT NULL
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Registers `webhook`, replacing any callback the user had before.
+pub async fn upsert_webhook(pool: &SqlitePool, webhook: &Webhook) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO webhooks (user_id, url, secret, created_at)
+        VALUES (?, ?, ?, ?)
+        ON CONFLICT(user_id) DO UPDATE SET
+            url = excluded.url, secret = excluded.secret, created_at = excluded.created_at
+        "#,
+    )
+    .bind(&webhook.user_id)
+    .bind(&webhook.url)
+    .bind(&webhook.secret)
+    .bind(webhook.created_at)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn get_webhook(pool: &SqlitePool, user_id: &str) -> Option<Webhook> {
+    sqlx::query_as::<_, Webhook>("SELECT * FROM webhooks WHERE user_id = ?")
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await
+        .unwrap_or(None)
+}
+
+pub async fn delete_webhook(pool: &SqlitePool, user_id: &str) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM webhooks WHERE user_id = ?")
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Records a browser's push endpoint for `user_id`, moving it over if another account had it.
+pub async fn upsert_push_subscription(
+    pool: &SqlitePool,
+    user_id: &str,
+    endpoint: &str,
+    created_at: i64,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO push_subscriptions (endpoint, user_id, created_at)
+        VALUES (?, ?, ?)
+        ON CONFLICT(endpoint) DO UPDATE SET
+            user_id = excluded.user_id, created_at = excluded.created_at
+        "#,
+    )
+    .bind(endpoint)
+    .bind(user_id)
+    .bind(created_at)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn list_push_endpoints(pool: &SqlitePool, user_id: &str) -> Vec<String> {
+    sqlx::query_scalar::<_, String>("SELECT endpoint FROM push_subscriptions WHERE user_id = ?")
+        .bind(user_id)
+        .fetch_all(pool)
+        .await
+        .unwrap_or_default()
+}
+
+pub async fn delete_push_subscription(
+    pool: &SqlitePool,
+    endpoint: &str,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM push_subscriptions WHERE endpoint = ?")
+        .bind(endpoint)
+        .execute(pool)
+        .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Like [`delete_push_subscription`], but only if the endpoint belongs to `user_id`.
+pub async fn delete_user_push_subscription(
+    pool: &SqlitePool,
+    user_id: &str,
+    endpoint: &str,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM push_subscriptions WHERE endpoint = ? AND user_id = ?")
+        .bind(endpoint)
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+pub async fn create_season_tables(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS seasons (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            starts_at INTEGER NOT NULL,
+            ends_at INTEGER NOT NULL,
+            closed_at INTEGER
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS season_ratings (
+            season_id INTEGER NOT NULL,
+            user_id TEXT NOT NULL,
+            rating INTEGER NOT NULL,
+            games INTEGER NOT NULL,
+            PRIMARY KEY (season_id, user_id)
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    // Final standings of closed seasons, frozen at rollover
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS season_standings (
+            season_id INTEGER NOT NULL,
+            rank INTEGER NOT NULL,
+            user_id TEXT NOT NULL,
+            rating INTEGER NOT NULL,
+            games INTEGER NOT NULL,
+            PRIMARY KEY (season_id, user_id)
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    // Ranked play across seasons, for placement and decay
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS rating_activity (
+            user_id TEXT PRIMARY KEY,
+            ranked_games INTEGER NOT NULL,
+            last_ranked_at INTEGER NOT NULL
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// The season still open for play, even if its end date has passed but it hasn't been
+/// rolled over yet.
+pub async fn get_open_season(pool: &SqlitePool) -> Option<Season> {
+    sqlx::query_as::<_, Season>(
+        "SELECT * FROM seasons WHERE closed_at IS NULL ORDER BY starts_at DESC LIMIT 1",
+    )
+    .fetch_optional(pool)
+    .await
+    .unwrap_or(None)
+}
+
+pub async fn get_season(pool: &SqlitePool, id: i64) -> Option<Season> {
+    sqlx::query_as::<_, Season>("SELECT * FROM seasons WHERE id = ?")
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+        .unwrap_or(None)
+}
+
+pub async fn list_seasons(pool: &SqlitePool) -> Vec<Season> {
+    sqlx::query_as::<_, Season>("SELECT * FROM seasons ORDER BY starts_at DESC")
+        .fetch_all(pool)
+        .await
+        .unwrap_or_default()
+}
+
+pub async fn insert_season(
+    pool: &SqlitePool,
+    starts_at: i64,
+    ends_at: i64,
+) -> Result<Season, sqlx::Error> {
+    let id = sqlx::query("INSERT INTO seasons (starts_at, ends_at) VALUES (?, ?)")
+        .bind(starts_at)
+        .bind(ends_at)
+        .execute(pool)
+        .await?
+        .last_insert_rowid();
+
+    Ok(Season {
+        id,
+        starts_at,
+        ends_at,
+        closed_at: None,
+    })
+}
+
+pub async fn get_season_ratings(pool: &SqlitePool, season_id: i64) -> Vec<SeasonRating> {
+    sqlx::query_as::<_, SeasonRating>(
+        "SELECT user_id, rating, games FROM season_ratings WHERE season_id = ?",
+    )
+    .bind(season_id)
+    .fetch_all(pool)
+    .await
+    .unwrap_or_default()
+}
+
+pub async fn get_season_rating(
+    pool: &SqlitePool,
+    season_id: i64,
+    user_id: &str,
+) -> Option<SeasonRating> {
+    sqlx::query_as::<_, SeasonRating>(
+        "SELECT user_id, rating, games FROM season_ratings WHERE season_id = ? AND user_id = ?",
+    )
+    .bind(season_id)
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await
+    .unwrap_or(None)
+}
+
+pub async fn upsert_season_rating(
+    pool: &SqlitePool,
+    season_id: i64,
+    rating: &SeasonRating,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO season_ratings (season_id, user_id, rating, games)
+        VALUES (?, ?, ?, ?)
+        ON CONFLICT(season_id, user_id) DO UPDATE SET
+            rating = excluded.rating, games = excluded.games
+        "#,
+    )
+    .bind(season_id)
+    .bind(&rating.user_id)
+    .bind(rating.rating)
+    .bind(rating.games)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn get_rating_activity(pool: &SqlitePool, user_id: &str) -> Option<RatingActivity> {
+    sqlx::query_as::<_, RatingActivity>("SELECT * FROM rating_activity WHERE user_id = ?")
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await
+        .unwrap_or(None)
+}
+
+pub async fn upsert_rating_activity(
+    pool: &SqlitePool,
+    activity: &RatingActivity,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO rating_activity (user_id, ranked_games, last_ranked_at)
+        VALUES (?, ?, ?)
+        ON CONFLICT(user_id) DO UPDATE SET
+            ranked_games = excluded.ranked_games, last_ranked_at = excluded.last_ranked_at
+        "#,
+    )
+    .bind(&activity.user_id)
+    .bind(activity.ranked_games)
+    .bind(activity.last_ranked_at)
     .execute(pool)
     .await?;
 
     Ok(())
 }
+
+/// Archives `season`'s standings, closes it and opens the next one seeded with `carried`
+/// ratings, all in one transaction.
+pub async fn roll_over_season(
+    pool: &SqlitePool,
+    season: &Season,
+    closed_at: i64,
+    next_ends_at: i64,
+    carried: &[SeasonRating],
+) -> Result<Season, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO season_standings (season_id, rank, user_id, rating, games)
+        SELECT season_id, ROW_NUMBER() OVER (ORDER BY rating DESC, games DESC, user_id),
+               user_id, rating, games
+        FROM season_ratings WHERE season_id = ? AND games > 0
+        "#,
+    )
+    .bind(season.id)
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query("UPDATE seasons SET closed_at = ? WHERE id = ?")
+        .bind(closed_at)
+        .bind(season.id)
+        .execute(&mut *tx)
+        .await?;
+
+    let next_id = sqlx::query("INSERT INTO seasons (starts_at, ends_at) VALUES (?, ?)")
+        .bind(season.ends_at)
+        .bind(next_ends_at)
+        .execute(&mut *tx)
+        .await?
+        .last_insert_rowid();
+
+    for rating in carried {
+        sqlx::query(
+            "INSERT INTO season_ratings (season_id, user_id, rating, games) VALUES (?, ?, ?, ?)",
+        )
+        .bind(next_id)
+        .bind(&rating.user_id)
+        .bind(rating.rating)
+        .bind(rating.games)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    tx.commit().await?;
+
+    Ok(Season {
+        id: next_id,
+        starts_at: season.ends_at,
+        ends_at: next_ends_at,
+        closed_at: None,
+    })
+}
+
+/// Best-rated players of an open season, live.
+pub async fn live_leaderboard(
+    pool: &SqlitePool,
+    season_id: i64,
+    limit: i64,
+) -> Vec<LeaderboardEntry> {
+    sqlx::query_as::<_, LeaderboardEntry>(
+        r#"
+        SELECT ROW_NUMBER() OVER (ORDER BY r.rating DESC, r.games DESC, r.user_id) AS rank,
+               r.user_id, u.username, r.rating, r.games
+        FROM season_ratings r JOIN users u ON u.id = r.user_id
+        WHERE r.season_id = ? AND r.games > 0
+        ORDER BY rank LIMIT ?
+        "#,
+    )
+    .bind(season_id)
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+    .unwrap_or_default()
+}
+
+/// Final standings of a closed season.
+pub async fn archived_leaderboard(
+    pool: &SqlitePool,
+    season_id: i64,
+    limit: i64,
+) -> Vec<LeaderboardEntry> {
+    sqlx::query_as::<_, LeaderboardEntry>(
+        r#"
+        SELECT s.rank, s.user_id, u.username, s.rating, s.games
+        FROM season_standings s JOIN users u ON u.id = s.user_id
+        WHERE s.season_id = ?
+        ORDER BY s.rank LIMIT ?
+        "#,
+    )
+    .bind(season_id)
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+    .unwrap_or_default()
+}
+
+pub async fn create_game_result_tables(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS game_results (
+            room_id TEXT PRIMARY KEY,
+            outcome TEXT NOT NULL,
+            counted INTEGER NOT NULL,
+            ranked INTEGER NOT NULL,
+            rounds_played INTEGER NOT NULL,
+            finished_at INTEGER NOT NULL
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS game_result_players (
+            room_id TEXT NOT NULL,
+            user_id TEXT NOT NULL,
+            seat INTEGER NOT NULL,
+            total INTEGER NOT NULL,
+            PRIMARY KEY (room_id, user_id)
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS game_transcripts (
+            room_id TEXT PRIMARY KEY,
+            transcript TEXT NOT NULL
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Stores a finished game with every seat's final total and its transcript (as JSON),
+/// in one transaction.
+pub async fn insert_game_result(
+    pool: &SqlitePool,
+    result: &GameResult,
+    players: &[GameResultPlayer],
+    transcript: &str,
+) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO game_results (room_id, outcome, counted, ranked, rounds_played, finished_at)
+        VALUES (?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(&result.room_id)
+    .bind(&result.outcome)
+    .bind(result.counted)
+    .bind(result.ranked)
+    .bind(result.rounds_played)
+    .bind(result.finished_at)
+    .execute(&mut *tx)
+    .await?;
+
+    for player in players {
+        sqlx::query(
+            "INSERT INTO game_result_players (room_id, user_id, seat, total) VALUES (?, ?, ?, ?)",
+        )
+        .bind(&player.room_id)
+        .bind(&player.user_id)
+        .bind(player.seat)
+        .bind(player.total)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    sqlx::query("INSERT INTO game_transcripts (room_id, transcript) VALUES (?, ?)")
+        .bind(&result.room_id)
+        .bind(transcript)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await
+}
+
+pub async fn get_game_result(pool: &SqlitePool, room_id: &str) -> Option<GameResult> {
+    sqlx::query_as::<_, GameResult>("SELECT * FROM game_results WHERE room_id = ?")
+        .bind(room_id)
+        .fetch_optional(pool)
+        .await
+        .unwrap_or(None)
+}
+
+/// Final totals in seat order.
+pub async fn get_game_result_players(pool: &SqlitePool, room_id: &str) -> Vec<GameResultPlayer> {
+    sqlx::query_as::<_, GameResultPlayer>(
+        "SELECT * FROM game_result_players WHERE room_id = ? ORDER BY seat",
+    )
+    .bind(room_id)
+    .fetch_all(pool)
+    .await
+    .unwrap_or_default()
+}
+
+/// The stored transcript JSON.
+pub async fn get_game_transcript(pool: &SqlitePool, room_id: &str) -> Option<String> {
+    sqlx::query_scalar::<_, String>("SELECT transcript FROM game_transcripts WHERE room_id = ?")
+        .bind(room_id)
+        .fetch_optional(pool)
+        .await
+        .unwrap_or(None)
+}
+
+pub async fn create_tournament_tables(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS tournaments (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL,
+            status TEXT NOT NULL,
+            created_by TEXT NOT NULL,
+            created_at INTEGER NOT NULL,
+            bracket TEXT
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS tournament_players (
+            tournament_id INTEGER NOT NULL,
+            user_id TEXT NOT NULL,
+            registered_at INTEGER NOT NULL,
+            PRIMARY KEY (tournament_id, user_id)
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn insert_tournament(
+    pool: &SqlitePool,
+    name: &str,
+    created_by: &str,
+    created_at: i64,
+) -> Result<Tournament, sqlx::Error> {
+    let id = sqlx::query(
+        "INSERT INTO tournaments (name, status, created_by, created_at) VALUES (?, 'registering', ?, ?)",
+    )
+    .bind(name)
+    .bind(created_by)
+    .bind(created_at)
+    .execute(pool)
+    .await?
+    .last_insert_rowid();
+
+    Ok(Tournament {
+        id,
+        name: name.to_string(),
+        status: "registering".to_string(),
+        created_by: created_by.to_string(),
+        created_at,
+        bracket: None,
+    })
+}
+
+pub async fn get_tournament(pool: &SqlitePool, id: i64) -> Option<Tournament> {
+    sqlx::query_as::<_, Tournament>("SELECT * FROM tournaments WHERE id = ?")
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+        .unwrap_or(None)
+}
+
+pub async fn list_tournaments(pool: &SqlitePool) -> Vec<Tournament> {
+    sqlx::query_as::<_, Tournament>("SELECT * FROM tournaments ORDER BY created_at DESC")
+        .fetch_all(pool)
+        .await
+        .unwrap_or_default()
+}
+
+pub async fn list_tournaments_by_status(pool: &SqlitePool, status: &str) -> Vec<Tournament> {
+    sqlx::query_as::<_, Tournament>("SELECT * FROM tournaments WHERE status = ? ORDER BY id")
+        .bind(status)
+        .fetch_all(pool)
+        .await
+        .unwrap_or_default()
+}
+
+/// Saves the bracket (as JSON) along with the status it leaves the tournament in.
+pub async fn update_tournament(
+    pool: &SqlitePool,
+    id: i64,
+    status: &str,
+    bracket: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE tournaments SET status = ?, bracket = ? WHERE id = ?")
+        .bind(status)
+        .bind(bracket)
+        .bind(id)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+/// Returns whether the player wasn't registered yet.
+pub async fn register_tournament_player(
+    pool: &SqlitePool,
+    tournament_id: i64,
+    user_id: &str,
+    registered_at: i64,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        "INSERT OR IGNORE INTO tournament_players (tournament_id, user_id, registered_at) VALUES (?, ?, ?)",
+    )
+    .bind(tournament_id)
+    .bind(user_id)
+    .bind(registered_at)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+pub async fn unregister_tournament_player(
+    pool: &SqlitePool,
+    tournament_id: i64,
+    user_id: &str,
+) -> Result<bool, sqlx::Error> {
+    let result =
+        sqlx::query("DELETE FROM tournament_players WHERE tournament_id = ? AND user_id = ?")
+            .bind(tournament_id)
+            .bind(user_id)
+            .execute(pool)
+            .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Registered players, earliest first.
+pub async fn get_tournament_players(
+    pool: &SqlitePool,
+    tournament_id: i64,
+) -> Vec<TournamentPlayer> {
+    sqlx::query_as::<_, TournamentPlayer>(
+        r#"
+        SELECT p.user_id, u.username, p.registered_at
+        FROM tournament_players p JOIN users u ON u.id = p.user_id
+        WHERE p.tournament_id = ?
+        ORDER BY p.registered_at, p.rowid
+        "#,
+    )
+    .bind(tournament_id)
+    .fetch_all(pool)
+    .await
+    .unwrap_or_default()
+}
+
+pub async fn create_league_tables(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS leagues (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL,
+            created_by TEXT NOT NULL,
+            created_at INTEGER NOT NULL
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS league_players (
+            league_id INTEGER NOT NULL,
+            user_id TEXT NOT NULL,
+            position INTEGER NOT NULL,
+            PRIMARY KEY (league_id, user_id)
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS league_fixtures (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            league_id INTEGER NOT NULL,
+            matchday INTEGER NOT NULL,
+            home_id TEXT NOT NULL,
+            away_id TEXT NOT NULL,
+            room_id TEXT
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Stores a league with its members (in the given order) and every fixture
+/// (`(matchday, home, away)`), in one transaction.
+pub async fn insert_league(
+    pool: &SqlitePool,
+    name: &str,
+    created_by: &str,
+    created_at: i64,
+    players: &[String],
+    fixtures: &[(i64, String, String)],
+) -> Result<League, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let id = sqlx::query("INSERT INTO leagues (name, created_by, created_at) VALUES (?, ?, ?)")
+        .bind(name)
+        .bind(created_by)
+        .bind(created_at)
+        .execute(&mut *tx)
+        .await?
+        .last_insert_rowid();
+
+    for (position, user_id) in players.iter().enumerate() {
+        sqlx::query("INSERT INTO league_players (league_id, user_id, position) VALUES (?, ?, ?)")
+            .bind(id)
+            .bind(user_id)
+            .bind(position as i64)
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    for (matchday, home_id, away_id) in fixtures {
+        sqlx::query(
+            "INSERT INTO league_fixtures (league_id, matchday, home_id, away_id) VALUES (?, ?, ?, ?)",
+        )
+        .bind(id)
+        .bind(matchday)
+        .bind(home_id)
+        .bind(away_id)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    tx.commit().await?;
+
+    Ok(League {
+        id,
+        name: name.to_string(),
+        created_by: created_by.to_string(),
+        created_at,
+    })
+}
+
+pub async fn get_league(pool: &SqlitePool, id: i64) -> Option<League> {
+    sqlx::query_as::<_, League>("SELECT * FROM leagues WHERE id = ?")
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+        .unwrap_or(None)
+}
+
+pub async fn list_leagues(pool: &SqlitePool) -> Vec<League> {
+    sqlx::query_as::<_, League>("SELECT * FROM leagues ORDER BY created_at DESC")
+        .fetch_all(pool)
+        .await
+        .unwrap_or_default()
+}
+
+/// Members in the order the league was created with.
+pub async fn get_league_players(pool: &SqlitePool, league_id: i64) -> Vec<LeaguePlayer> {
+    sqlx::query_as::<_, LeaguePlayer>(
+        r#"
+        SELECT p.user_id, u.username
+        FROM league_players p JOIN users u ON u.id = p.user_id
+        WHERE p.league_id = ?
+        ORDER BY p.position
+        "#,
+    )
+    .bind(league_id)
+    .fetch_all(pool)
+    .await
+    .unwrap_or_default()
+}
+
+/// Fixtures by matchday.
+pub async fn get_league_fixtures(pool: &SqlitePool, league_id: i64) -> Vec<LeagueFixture> {
+    sqlx::query_as::<_, LeagueFixture>(
+        "SELECT * FROM league_fixtures WHERE league_id = ? ORDER BY matchday, id",
+    )
+    .bind(league_id)
+    .fetch_all(pool)
+    .await
+    .unwrap_or_default()
+}
+
+pub async fn get_league_fixture(
+    pool: &SqlitePool,
+    league_id: i64,
+    fixture_id: i64,
+) -> Option<LeagueFixture> {
+    sqlx::query_as::<_, LeagueFixture>(
+        "SELECT * FROM league_fixtures WHERE league_id = ? AND id = ?",
+    )
+    .bind(league_id)
+    .bind(fixture_id)
+    .fetch_optional(pool)
+    .await
+    .unwrap_or(None)
+}
+
+pub async fn set_league_fixture_room(
+    pool: &SqlitePool,
+    fixture_id: i64,
+    room_id: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE league_fixtures SET room_id = ? WHERE id = ?")
+        .bind(room_id)
+        .bind(fixture_id)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+/// Final totals of the league's fixtures whose game finished with a result that counts.
+pub async fn get_league_results(pool: &SqlitePool, league_id: i64) -> Vec<GameResultPlayer> {
+    sqlx::query_as::<_, GameResultPlayer>(
+        r#"
+        SELECT p.*
+        FROM league_fixtures f
+        JOIN game_results r ON r.room_id = f.room_id
+        JOIN game_result_players p ON p.room_id = f.room_id
+        WHERE f.league_id = ? AND r.counted = 1
+        ORDER BY p.room_id, p.seat
+        "#,
+    )
+    .bind(league_id)
+    .fetch_all(pool)
+    .await
+    .unwrap_or_default()
+}
+
+pub async fn create_scheduled_game_tables(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS scheduled_games (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL,
+            created_by TEXT NOT NULL,
+            starts_at INTEGER NOT NULL,
+            max_players INTEGER NOT NULL,
+            public INTEGER NOT NULL,
+            status TEXT NOT NULL,
+            room_id TEXT,
+            created_at INTEGER NOT NULL
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS scheduled_game_invites (
+            game_id INTEGER NOT NULL,
+            user_id TEXT NOT NULL,
+            PRIMARY KEY (game_id, user_id)
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS scheduled_game_rsvps (
+            game_id INTEGER NOT NULL,
+            user_id TEXT NOT NULL,
+            rsvp_at INTEGER NOT NULL,
+            PRIMARY KEY (game_id, user_id)
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS scheduled_game_invite_links (
+            id TEXT PRIMARY KEY,
+            game_id INTEGER NOT NULL,
+            created_by TEXT NOT NULL,
+            created_at INTEGER NOT NULL,
+            used_by TEXT,
+            used_at INTEGER
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Stores a scheduled game with its invitations and the host's own RSVP, in one
+/// transaction.
+pub async fn insert_scheduled_game(
+    pool: &SqlitePool,
+    game: &ScheduledGame,
+    invited: &[String],
+) -> Result<ScheduledGame, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let id = sqlx::query(
+        r#"
+        INSERT INTO scheduled_games
+            (name, created_by, starts_at, max_players, public, status, created_at)
+        VALUES (?, ?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(&game.name)
+    .bind(&game.created_by)
+    .bind(game.starts_at)
+    .bind(game.max_players)
+    .bind(game.public)
+    .bind(&game.status)
+    .bind(game.created_at)
+    .execute(&mut *tx)
+    .await?
+    .last_insert_rowid();
+
+    for user_id in invited {
+        sqlx::query(
+            "INSERT OR IGNORE INTO scheduled_game_invites (game_id, user_id) VALUES (?, ?)",
+        )
+        .bind(id)
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    sqlx::query("INSERT INTO scheduled_game_rsvps (game_id, user_id, rsvp_at) VALUES (?, ?, ?)")
+        .bind(id)
+        .bind(&game.created_by)
+        .bind(game.created_at)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+
+    Ok(ScheduledGame { id, ..game.clone() })
+}
+
+pub async fn get_scheduled_game(pool: &SqlitePool, id: i64) -> Option<ScheduledGame> {
+    sqlx::query_as::<_, ScheduledGame>("SELECT * FROM scheduled_games WHERE id = ?")
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+        .unwrap_or(None)
+}
+
+/// Games not yet started that `user_id` may join: public ones, their own and those they
+/// were invited to. Soonest first.
+pub async fn list_upcoming_scheduled_games(pool: &SqlitePool, user_id: &str) -> Vec<ScheduledGame> {
+    sqlx::query_as::<_, ScheduledGame>(
+        r#"
+        SELECT * FROM scheduled_games g
+        WHERE g.status = 'scheduled'
+          AND (g.public = 1 OR g.created_by = ? OR EXISTS (
+              SELECT 1 FROM scheduled_game_invites i WHERE i.game_id = g.id AND i.user_id = ?
+          ))
+        ORDER BY g.starts_at
+        "#,
+    )
+    .bind(user_id)
+    .bind(user_id)
+    .fetch_all(pool)
+    .await
+    .unwrap_or_default()
+}
+
+/// Games still waiting to start whose start time has come.
+pub async fn list_due_scheduled_games(pool: &SqlitePool, now: i64) -> Vec<ScheduledGame> {
+    sqlx::query_as::<_, ScheduledGame>(
+        "SELECT * FROM scheduled_games WHERE status = 'scheduled' AND starts_at <= ? ORDER BY starts_at",
+    )
+    .bind(now)
+    .fetch_all(pool)
+    .await
+    .unwrap_or_default()
+}
+
+pub async fn is_invited_to_scheduled_game(pool: &SqlitePool, game_id: i64, user_id: &str) -> bool {
+    sqlx::query_scalar::<_, i64>(
+        "SELECT COUNT(*) FROM scheduled_game_invites WHERE game_id = ? AND user_id = ?",
+    )
+    .bind(game_id)
+    .bind(user_id)
+    .fetch_one(pool)
+    .await
+    .map(|count| count > 0)
+    .unwrap_or(false)
+}
+
+/// RSVPs, earliest first.
+pub async fn get_scheduled_game_rsvps(pool: &SqlitePool, game_id: i64) -> Vec<ScheduledGameRsvp> {
+    sqlx::query_as::<_, ScheduledGameRsvp>(
+        r#"
+        SELECT r.user_id, u.username, r.rsvp_at
+        FROM scheduled_game_rsvps r JOIN users u ON u.id = r.user_id
+        WHERE r.game_id = ?
+        ORDER BY r.rsvp_at, r.rowid
+        "#,
+    )
+    .bind(game_id)
+    .fetch_all(pool)
+    .await
+    .unwrap_or_default()
+}
+
+/// Adds the RSVP unless the game already has `max_players` of them. Returns whether the
+/// player holds a seat afterwards.
+pub async fn add_scheduled_game_rsvp(
+    pool: &SqlitePool,
+    game: &ScheduledGame,
+    user_id: &str,
+    rsvp_at: i64,
+) -> Result<bool, sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT OR IGNORE INTO scheduled_game_rsvps (game_id, user_id, rsvp_at)
+        SELECT ?, ?, ?
+        WHERE (SELECT COUNT(*) FROM scheduled_game_rsvps WHERE game_id = ?) < ?
+        "#,
+    )
+    .bind(game.id)
+    .bind(user_id)
+    .bind(rsvp_at)
+    .bind(game.id)
+    .bind(game.max_players)
+    .execute(pool)
+    .await?;
+
+    has_scheduled_game_rsvp(pool, game.id, user_id).await
+}
+
+pub async fn has_scheduled_game_rsvp(
+    pool: &SqlitePool,
+    game_id: i64,
+    user_id: &str,
+) -> Result<bool, sqlx::Error> {
+    sqlx::query_scalar::<_, i64>(
+        "SELECT COUNT(*) FROM scheduled_game_rsvps WHERE game_id = ? AND user_id = ?",
+    )
+    .bind(game_id)
+    .bind(user_id)
+    .fetch_one(pool)
+    .await
+    .map(|count| count > 0)
+}
+
+pub async fn delete_scheduled_game_rsvp(
+    pool: &SqlitePool,
+    game_id: i64,
+    user_id: &str,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM scheduled_game_rsvps WHERE game_id = ? AND user_id = ?")
+        .bind(game_id)
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+pub async fn insert_scheduled_game_invite_link(
+    pool: &SqlitePool,
+    id: &str,
+    game_id: i64,
+    created_by: &str,
+    created_at: i64,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO scheduled_game_invite_links (id, game_id, created_by, created_at)
+        VALUES (?, ?, ?, ?)
+        "#,
+    )
+    .bind(id)
+    .bind(game_id)
+    .bind(created_by)
+    .bind(created_at)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Marks invite link `id` used by `user_id` and invites them to its game, in one
+/// transaction. Returns false if the link is unknown or someone else already used it;
+/// using it again oneself is fine.
+pub async fn redeem_scheduled_game_invite_link(
+    pool: &SqlitePool,
+    id: &str,
+    game_id: i64,
+    user_id: &str,
+    now: i64,
+) -> Result<bool, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let claimed = sqlx::query(
+        r#"
+        UPDATE scheduled_game_invite_links SET used_by = ?, used_at = COALESCE(used_at, ?)
+        WHERE id = ? AND game_id = ? AND (used_by IS NULL OR used_by = ?)
+        "#,
+    )
+    .bind(user_id)
+    .bind(now)
+    .bind(id)
+    .bind(game_id)
+    .bind(user_id)
+    .execute(&mut *tx)
+    .await?
+    .rows_affected();
+    if claimed == 0 {
+        return Ok(false);
+    }
+
+    sqlx::query("INSERT OR IGNORE INTO scheduled_game_invites (game_id, user_id) VALUES (?, ?)")
+        .bind(game_id)
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+    Ok(true)
+}
+
+pub async fn update_scheduled_game_status(
+    pool: &SqlitePool,
+    id: i64,
+    status: &str,
+    room_id: Option<&str>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE scheduled_games SET status = ?, room_id = ? WHERE id = ?")
+        .bind(status)
+        .bind(room_id)
+        .bind(id)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+/// Counted games both players finished, newest first; `total` is `user_id`'s.
+pub async fn get_shared_games(
+    pool: &SqlitePool,
+    user_id: &str,
+    opponent_id: &str,
+) -> Vec<SharedGame> {
+    sqlx::query_as::<_, SharedGame>(
+        r#"
+        SELECT r.room_id, r.finished_at, p.total, o.total AS opponent_total
+        FROM game_results r
+        JOIN game_result_players p ON p.room_id = r.room_id AND p.user_id = ?
+        JOIN game_result_players o ON o.room_id = r.room_id AND o.user_id = ?
+        WHERE r.counted = 1
+        ORDER BY r.finished_at DESC, r.room_id
+        "#,
+    )
+    .bind(user_id)
+    .bind(opponent_id)
+    .fetch_all(pool)
+    .await
+    .unwrap_or_default()
+}
+
+pub async fn create_global_stats_table(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    // Only the latest aggregation is kept
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS global_stats (
+            id INTEGER PRIMARY KEY CHECK (id = 1),
+            computed_at INTEGER NOT NULL,
+            stats TEXT NOT NULL
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// `(finished_at, transcript JSON)` of every game finished since `since`.
+pub async fn get_transcripts_since(pool: &SqlitePool, since: i64) -> Vec<(i64, String)> {
+    sqlx::query_as::<_, (i64, String)>(
+        r#"
+        SELECT r.finished_at, t.transcript
+        FROM game_results r JOIN game_transcripts t ON t.room_id = r.room_id
+        WHERE r.finished_at >= ?
+        "#,
+    )
+    .bind(since)
+    .fetch_all(pool)
+    .await
+    .unwrap_or_default()
+}
+
+pub async fn save_global_stats(
+    pool: &SqlitePool,
+    computed_at: i64,
+    stats: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("INSERT OR REPLACE INTO global_stats (id, computed_at, stats) VALUES (1, ?, ?)")
+        .bind(computed_at)
+        .bind(stats)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+/// The latest aggregation, as JSON.
+pub async fn get_global_stats(pool: &SqlitePool) -> Option<String> {
+    sqlx::query_scalar::<_, String>("SELECT stats FROM global_stats WHERE id = 1")
+        .fetch_optional(pool)
+        .await
+        .unwrap_or(None)
+}
+
+pub async fn create_cosmetics_table(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS user_cosmetics (
+            user_id TEXT PRIMARY KEY,
+            card_back TEXT NOT NULL,
+            table_theme TEXT NOT NULL
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// `None` until the player picks something.
+pub async fn get_cosmetics(pool: &SqlitePool, user_id: &str) -> Option<Cosmetics> {
+    sqlx::query_as::<_, Cosmetics>(
+        "SELECT card_back, table_theme FROM user_cosmetics WHERE user_id = ?",
+    )
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await
+    .unwrap_or(None)
+}
+
+pub async fn upsert_cosmetics(
+    pool: &SqlitePool,
+    user_id: &str,
+    cosmetics: &Cosmetics,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO user_cosmetics (user_id, card_back, table_theme)
+        VALUES (?, ?, ?)
+        ON CONFLICT(user_id) DO UPDATE SET
+            card_back = excluded.card_back,
+            table_theme = excluded.table_theme
+        "#,
+    )
+    .bind(user_id)
+    .bind(&cosmetics.card_back)
+    .bind(&cosmetics.table_theme)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn create_title_tables(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS user_titles (
+            user_id TEXT NOT NULL,
+            title_id TEXT NOT NULL,
+            earned_at INTEGER NOT NULL,
+            PRIMARY KEY (user_id, title_id)
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS selected_titles (
+            user_id TEXT PRIMARY KEY,
+            title_id TEXT NOT NULL
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Returns whether the title is new to the player.
+pub async fn award_title(
+    pool: &SqlitePool,
+    user_id: &str,
+    title_id: &str,
+    earned_at: i64,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        "INSERT OR IGNORE INTO user_titles (user_id, title_id, earned_at) VALUES (?, ?, ?)",
+    )
+    .bind(user_id)
+    .bind(title_id)
+    .bind(earned_at)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() == 1)
+}
+
+/// Oldest first.
+pub async fn get_earned_titles(pool: &SqlitePool, user_id: &str) -> Vec<EarnedTitle> {
+    sqlx::query_as::<_, EarnedTitle>(
+        "SELECT title_id, earned_at FROM user_titles WHERE user_id = ? ORDER BY earned_at, title_id",
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await
+    .unwrap_or_default()
+}
+
+/// `None` until the player picks one.
+pub async fn get_selected_title(pool: &SqlitePool, user_id: &str) -> Option<String> {
+    sqlx::query_scalar::<_, String>("SELECT title_id FROM selected_titles WHERE user_id = ?")
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await
+        .unwrap_or(None)
+}
+
+/// `None` goes back to showing no title.
+pub async fn set_selected_title(
+    pool: &SqlitePool,
+    user_id: &str,
+    title_id: Option<&str>,
+) -> Result<(), sqlx::Error> {
+    match title_id {
+        Some(title_id) => {
+            sqlx::query(
+                r#"
+                INSERT INTO selected_titles (user_id, title_id) VALUES (?, ?)
+                ON CONFLICT(user_id) DO UPDATE SET title_id = excluded.title_id
+                "#,
+            )
+            .bind(user_id)
+            .bind(title_id)
+            .execute(pool)
+            .await?;
+        }
+        None => {
+            sqlx::query("DELETE FROM selected_titles WHERE user_id = ?")
+                .bind(user_id)
+                .execute(pool)
+                .await?;
+        }
+    }
+
+    Ok(())
+}
+
+pub async fn create_avatar_table(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS user_avatars (
+            user_id TEXT PRIMARY KEY,
+            content_type TEXT NOT NULL,
+            image BLOB NOT NULL,
+            version INTEGER NOT NULL
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn get_avatar(pool: &SqlitePool, user_id: &str) -> Option<Avatar> {
+    sqlx::query_as::<_, Avatar>(
+        "SELECT content_type, image, version FROM user_avatars WHERE user_id = ?",
+    )
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await
+    .unwrap_or(None)
+}
+
+/// The current avatar's version, without loading the image.
+pub async fn get_avatar_version(pool: &SqlitePool, user_id: &str) -> Option<i64> {
+    sqlx::query_scalar::<_, i64>("SELECT version FROM user_avatars WHERE user_id = ?")
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await
+        .unwrap_or(None)
+}
+
+pub async fn upsert_avatar(
+    pool: &SqlitePool,
+    user_id: &str,
+    content_type: &str,
+    image: &[u8],
+    version: i64,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO user_avatars (user_id, content_type, image, version)
+        VALUES (?, ?, ?, ?)
+        ON CONFLICT(user_id) DO UPDATE SET
+            content_type = excluded.content_type,
+            image = excluded.image,
+            version = excluded.version
+        "#,
+    )
+    .bind(user_id)
+    .bind(content_type)
+    .bind(image)
+    .bind(version)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn delete_avatar(pool: &SqlitePool, user_id: &str) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM user_avatars WHERE user_id = ?")
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+pub async fn create_username_history_table(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS username_history (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            user_id TEXT NOT NULL,
+            old_username TEXT NOT NULL,
+            new_username TEXT NOT NULL,
+            changed_at INTEGER NOT NULL
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS idx_username_history_old ON username_history(old_username)",
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn get_user_by_id(pool: &SqlitePool, user_id: &str) -> Option<User> {
+    sqlx::query_as::<_, User>("SELECT * FROM users WHERE id = ?")
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await
+        .unwrap_or(None)
+}
+
+/// The account currently called `username`, or failing that the one that used it last.
+pub async fn find_user_by_any_username(pool: &SqlitePool, username: &str) -> Option<User> {
+    if let Some(user) = get_user(pool, username).await {
+        return Some(user);
+    }
+    sqlx::query_as::<_, User>(
+        r#"
+        SELECT u.* FROM username_history h
+        JOIN users u ON u.id = h.user_id
+        WHERE h.old_username = ?
+        ORDER BY h.changed_at DESC
+        LIMIT 1
+        "#,
+    )
+    .bind(username)
+    .fetch_optional(pool)
+    .await
+    .unwrap_or(None)
+}
+
+pub async fn last_username_change(pool: &SqlitePool, user_id: &str) -> Option<i64> {
+    sqlx::query_scalar::<_, Option<i64>>(
+        "SELECT MAX(changed_at) FROM username_history WHERE user_id = ?",
+    )
+    .bind(user_id)
+    .fetch_one(pool)
+    .await
+    .unwrap_or(None)
+}
+
+pub async fn get_username_history(pool: &SqlitePool, user_id: &str) -> Vec<UsernameChange> {
+    sqlx::query_as::<_, UsernameChange>(
+        r#"
+        SELECT old_username, new_username, changed_at FROM username_history
+        WHERE user_id = ?
+        ORDER BY changed_at, id
+        "#,
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await
+    .unwrap_or_default()
+}
+
+/// Changes the username and records the old one, in one transaction.
+pub async fn rename_user(
+    pool: &SqlitePool,
+    user_id: &str,
+    old_username: &str,
+    new_username: &str,
+    changed_at: i64,
+) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query("UPDATE users SET username = ? WHERE id = ?")
+        .bind(new_username)
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO username_history (user_id, old_username, new_username, changed_at)
+        VALUES (?, ?, ?, ?)
+        "#,
+    )
+    .bind(user_id)
+    .bind(old_username)
+    .bind(new_username)
+    .bind(changed_at)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await
+}
+
+pub async fn create_bot_level_table(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS user_bot_levels (
+            user_id TEXT PRIMARY KEY,
+            level INTEGER NOT NULL,
+            streak INTEGER NOT NULL
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// `None` until the player finishes a practice game.
+pub async fn get_bot_level(pool: &SqlitePool, user_id: &str) -> Option<BotLevel> {
+    sqlx::query_as::<_, BotLevel>("SELECT level, streak FROM user_bot_levels WHERE user_id = ?")
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await
+        .unwrap_or(None)
+}
+
+pub async fn upsert_bot_level(
+    pool: &SqlitePool,
+    user_id: &str,
+    level: &BotLevel,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO user_bot_levels (user_id, level, streak)
+        VALUES (?, ?, ?)
+        ON CONFLICT(user_id) DO UPDATE SET
+            level = excluded.level,
+            streak = excluded.streak
+        "#,
+    )
+    .bind(user_id)
+    .bind(level.level)
+    .bind(level.streak)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn create_bot_stats_table(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS bot_round_stats (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            room_id TEXT NOT NULL,
+            bot_id TEXT NOT NULL,
+            difficulty TEXT NOT NULL,
+            round_index INTEGER NOT NULL,
+            went_out INTEGER NOT NULL,
+            leftover_points INTEGER NOT NULL,
+            bajada_turn INTEGER,
+            finished_at INTEGER NOT NULL
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS idx_bot_round_stats_finished ON bot_round_stats(finished_at)",
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn insert_bot_round_stats(
+    pool: &SqlitePool,
+    stats: &[BotRoundStat],
+) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    for stat in stats {
+        sqlx::query(
+            r#"
+            INSERT INTO bot_round_stats
+                (room_id, bot_id, difficulty, round_index, went_out, leftover_points,
+                 bajada_turn, finished_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(&stat.room_id)
+        .bind(&stat.bot_id)
+        .bind(&stat.difficulty)
+        .bind(stat.round_index)
+        .bind(stat.went_out)
+        .bind(stat.leftover_points)
+        .bind(stat.bajada_turn)
+        .bind(stat.finished_at)
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await
+}
+
+/// Per-difficulty outcomes of the rounds finished at or after `since`.
+pub async fn get_bot_performance(pool: &SqlitePool, since: i64) -> Vec<BotPerformance> {
+    sqlx::query_as::<_, BotPerformance>(
+        r#"
+        SELECT
+            difficulty,
+            COUNT(*) AS rounds,
+            AVG(went_out) AS win_rate,
+            AVG(leftover_points) AS average_leftover_points,
+            AVG(bajada_turn IS NOT NULL) AS dropped_rate,
+            AVG(bajada_turn) AS average_bajada_turn
+        FROM bot_round_stats
+        WHERE finished_at >= ?
+        GROUP BY difficulty
+        ORDER BY difficulty
+        "#,
+    )
+    .bind(since)
+    .fetch_all(pool)
+    .await
+    .unwrap_or_default()
+}
+
+pub async fn create_replay_flag_table(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS replay_flags (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            room_id TEXT NOT NULL,
+            reason TEXT NOT NULL,
+            replay TEXT NOT NULL,
+            created_at INTEGER NOT NULL
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn insert_replay_flag(
+    pool: &SqlitePool,
+    room_id: &str,
+    reason: &str,
+    replay: &str,
+    created_at: i64,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO replay_flags (room_id, reason, replay, created_at) VALUES (?, ?, ?, ?)",
+    )
+    .bind(room_id)
+    .bind(reason)
+    .bind(replay)
+    .bind(created_at)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Newest first.
+pub async fn list_replay_flags(pool: &SqlitePool) -> Vec<ReplayFlag> {
+    sqlx::query_as::<_, ReplayFlag>("SELECT * FROM replay_flags ORDER BY id DESC")
+        .fetch_all(pool)
+        .await
+        .unwrap_or_default()
+}
+
+pub async fn create_room_snapshot_table(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS room_snapshots (
+            room_id TEXT PRIMARY KEY,
+            snapshot TEXT NOT NULL,
+            created_at INTEGER NOT NULL
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn insert_room_snapshot(
+    pool: &SqlitePool,
+    room_id: &str,
+    snapshot: &str,
+    created_at: i64,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("INSERT INTO room_snapshots (room_id, snapshot, created_at) VALUES (?, ?, ?)")
+        .bind(room_id)
+        .bind(snapshot)
+        .bind(created_at)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Newest first.
+pub async fn list_room_snapshots(pool: &SqlitePool) -> Vec<RoomSnapshot> {
+    sqlx::query_as::<_, RoomSnapshot>("SELECT * FROM room_snapshots ORDER BY created_at DESC")
+        .fetch_all(pool)
+        .await
+        .unwrap_or_default()
+}
+
+pub async fn create_matchmaking_queue_table(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS matchmaking_queue (
+            user_id TEXT PRIMARY KEY,
+            queue TEXT NOT NULL,
+            region TEXT,
+            joined_at_ms INTEGER NOT NULL
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Records the player as waiting, replacing where they waited before.
+pub async fn upsert_queued_player(
+    pool: &SqlitePool,
+    player: &QueuedPlayer,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO matchmaking_queue (user_id, queue, region, joined_at_ms)
+        VALUES (?, ?, ?, ?)
+        ON CONFLICT(user_id) DO UPDATE SET
+            queue = excluded.queue, region = excluded.region,
+            joined_at_ms = excluded.joined_at_ms
+        "#,
+    )
+    .bind(&player.user_id)
+    .bind(&player.queue)
+    .bind(&player.region)
+    .bind(player.joined_at_ms)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn delete_queued_player(pool: &SqlitePool, user_id: &str) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM matchmaking_queue WHERE user_id = ?")
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Forgets players who joined before `cutoff_ms` and returns everyone else, longest
+/// waiting first.
+pub async fn load_queued_players(
+    pool: &SqlitePool,
+    cutoff_ms: i64,
+) -> Result<Vec<QueuedPlayer>, sqlx::Error> {
+    sqlx::query("DELETE FROM matchmaking_queue WHERE joined_at_ms < ?")
+        .bind(cutoff_ms)
+        .execute(pool)
+        .await?;
+    sqlx::query_as::<_, QueuedPlayer>("SELECT * FROM matchmaking_queue ORDER BY joined_at_ms")
+        .fetch_all(pool)
+        .await
+}
+
+pub async fn create_requeue_priority_table(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS requeue_priority (
+            user_id TEXT PRIMARY KEY,
+            abandoners TEXT NOT NULL,
+            reason TEXT NOT NULL,
+            granted_at INTEGER NOT NULL
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Grants the player priority, replacing any they had not used yet.
+pub async fn grant_requeue_priority(
+    pool: &SqlitePool,
+    priority: &RequeuePriority,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO requeue_priority (user_id, abandoners, reason, granted_at)
+        VALUES (?, ?, ?, ?)
+        ON CONFLICT(user_id) DO UPDATE SET
+            abandoners = excluded.abandoners, reason = excluded.reason,
+            granted_at = excluded.granted_at
+        "#,
+    )
+    .bind(&priority.user_id)
+    .bind(&priority.abandoners)
+    .bind(&priority.reason)
+    .bind(priority.granted_at)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Uses up the player's priority, if they were granted it at or after `not_before`.
+pub async fn take_requeue_priority(
+    pool: &SqlitePool,
+    user_id: &str,
+    not_before: i64,
+) -> Option<RequeuePriority> {
+    sqlx::query_as::<_, RequeuePriority>(
+        "DELETE FROM requeue_priority WHERE user_id = ? RETURNING *",
+    )
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await
+    .ok()
+    .flatten()
+    .filter(|priority| priority.granted_at >= not_before)
+}
+
+pub async fn create_collusion_flag_table(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS collusion_flags (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            user_a TEXT NOT NULL,
+            user_b TEXT NOT NULL,
+            kind TEXT NOT NULL,
+            score REAL NOT NULL,
+            games INTEGER NOT NULL,
+            detail TEXT NOT NULL,
+            status TEXT NOT NULL,
+            resolution TEXT,
+            created_at INTEGER NOT NULL,
+            updated_at INTEGER NOT NULL,
+            UNIQUE(user_a, user_b, kind)
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Files the flag, or refreshes the evidence of one still open. A flag a moderator already
+/// resolved stays resolved.
+pub async fn upsert_collusion_flag(
+    pool: &SqlitePool,
+    flag: &CollusionFlag,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO collusion_flags
+            (user_a, user_b, kind, score, games, detail, status, created_at, updated_at)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+        ON CONFLICT(user_a, user_b, kind) DO UPDATE SET
+            score = excluded.score, games = excluded.games, detail = excluded.detail,
+            updated_at = excluded.updated_at
+        WHERE status = 'open'
+        "#,
+    )
+    .bind(&flag.user_a)
+    .bind(&flag.user_b)
+    .bind(&flag.kind)
+    .bind(flag.score)
+    .bind(flag.games)
+    .bind(&flag.detail)
+    .bind(&flag.status)
+    .bind(flag.created_at)
+    .bind(flag.updated_at)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn list_collusion_flags(pool: &SqlitePool, status: Option<&str>) -> Vec<CollusionFlag> {
+    sqlx::query_as::<_, CollusionFlag>(
+        "SELECT * FROM collusion_flags WHERE (? IS NULL OR status = ?) ORDER BY updated_at DESC",
+    )
+    .bind(status)
+    .bind(status)
+    .fetch_all(pool)
+    .await
+    .unwrap_or_default()
+}
+
+pub async fn resolve_collusion_flag(
+    pool: &SqlitePool,
+    id: i64,
+    resolution: &str,
+) -> Result<bool, sqlx::Error> {
+    let result =
+        sqlx::query("UPDATE collusion_flags SET status = 'resolved', resolution = ? WHERE id = ?")
+            .bind(resolution)
+            .bind(id)
+            .execute(pool)
+            .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Counted games finished at or after `since`.
+pub async fn get_counted_results_since(pool: &SqlitePool, since: i64) -> Vec<GameResult> {
+    sqlx::query_as::<_, GameResult>(
+        "SELECT * FROM game_results WHERE counted = 1 AND finished_at >= ? ORDER BY finished_at",
+    )
+    .bind(since)
+    .fetch_all(pool)
+    .await
+    .unwrap_or_default()
+}
+
+/// The standings of every game finished at or after `since`.
+pub async fn get_result_players_since(pool: &SqlitePool, since: i64) -> Vec<GameResultPlayer> {
+    sqlx::query_as::<_, GameResultPlayer>(
+        r#"
+        SELECT p.room_id, p.user_id, p.seat, p.total
+        FROM game_result_players p JOIN game_results r ON r.room_id = p.room_id
+        WHERE r.finished_at >= ?
+        ORDER BY p.room_id, p.seat
+        "#,
+    )
+    .bind(since)
+    .fetch_all(pool)
+    .await
+    .unwrap_or_default()
+}
+
+/// Room id and transcript of every game finished at or after `since`.
+pub async fn get_room_transcripts_since(pool: &SqlitePool, since: i64) -> Vec<(String, String)> {
+    sqlx::query_as::<_, (String, String)>(
+        r#"
+        SELECT t.room_id, t.transcript
+        FROM game_results r JOIN game_transcripts t ON t.room_id = r.room_id
+        WHERE r.finished_at >= ?
+        "#,
+    )
+    .bind(since)
+    .fetch_all(pool)
+    .await
+    .unwrap_or_default()
+}
+
+/// Accepted actions of one `ClientMessage` type logged at or after `since_ms`, oldest first.
+pub async fn get_accepted_actions_since(
+    pool: &SqlitePool,
+    action_type: &str,
+    since_ms: i64,
+) -> Vec<AuditEntry> {
+    sqlx::query_as::<_, AuditEntry>(
+        r#"
+        SELECT user_id, room_id, action, accepted, error, created_at_ms
+        FROM audit_log
+        WHERE accepted = 1 AND created_at_ms >= ? AND json_extract(action, '$.type') = ?
+        ORDER BY id
+        "#,
+    )
+    .bind(since_ms)
+    .bind(action_type)
+    .fetch_all(pool)
+    .await
+    .unwrap_or_default()
+}
+
+pub async fn create_shuffle_seed_table(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS shuffle_seeds (
+            room_id TEXT PRIMARY KEY,
+            deck_jokers INTEGER NOT NULL,
+            sealed TEXT NOT NULL
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn insert_shuffle_seeds(
+    pool: &SqlitePool,
+    seeds: &ShuffleSeeds,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("INSERT INTO shuffle_seeds (room_id, deck_jokers, sealed) VALUES (?, ?, ?)")
+        .bind(&seeds.room_id)
+        .bind(seeds.deck_jokers)
+        .bind(&seeds.sealed)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn get_shuffle_seeds(pool: &SqlitePool, room_id: &str) -> Option<ShuffleSeeds> {
+    sqlx::query_as::<_, ShuffleSeeds>("SELECT * FROM shuffle_seeds WHERE room_id = ?")
+        .bind(room_id)
+        .fetch_optional(pool)
+        .await
+        .unwrap_or(None)
+}
@@ -0,0 +1,115 @@
+use ring::aead::{Aad, CHACHA20_POLY1305, LessSafeKey, NONCE_LEN, Nonce, UnboundKey};
+use ring::hkdf::{HKDF_SHA256, Salt};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use crate::engine::shuffle::ShuffleSeed;
+
+/// Keeps keys derived from the same secret for other uses apart from the vault's
+const KEY_SALT: &[u8] = b"carioca seed vault";
+
+/// What a game's sealed seed record holds for each round it dealt.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoundSeed {
+    pub round_index: usize,
+    pub seed: ShuffleSeed,
+}
+
+/// Encrypts what only admins may read once it's stored, such as the seeds a game's decks
+/// were shuffled from.
+///
+/// ChaCha20-Poly1305 under a key HKDF-SHA256 derives from the secret, with a fresh random
+/// nonce per record. Sealed records read `nonce.ciphertext`, both hex, the ciphertext
+/// ending in the 16-byte tag.
+#[derive(Clone)]
+pub struct SeedVault {
+    key: Arc<LessSafeKey>,
+}
+
+impl SeedVault {
+    pub fn new(secret: &[u8]) -> Self {
+        let key: UnboundKey = Salt::new(HKDF_SHA256, KEY_SALT)
+            .extract(secret)
+            .expand(&[b"chacha20-poly1305"], &CHACHA20_POLY1305)
+            .expect("ChaCha20-Poly1305 keys are well within HKDF's limit")
+            .into();
+        Self {
+            key: Arc::new(LessSafeKey::new(key)),
+        }
+    }
+
+    /// A vault under a secret only this process knows; what it seals can't be opened after
+    /// a restart.
+    pub fn ephemeral() -> Self {
+        Self::new(&rand::random::<[u8; 32]>())
+    }
+
+    pub fn seal(&self, plaintext: &[u8]) -> String {
+        let nonce: [u8; NONCE_LEN] = rand::random();
+        let mut sealed = plaintext.to_vec();
+        self.key
+            .seal_in_place_append_tag(
+                Nonce::assume_unique_for_key(nonce),
+                Aad::empty(),
+                &mut sealed,
+            )
+            .expect("seed records are far below ChaCha20-Poly1305's length limit");
+        format!("{}.{}", to_hex(&nonce), to_hex(&sealed))
+    }
+
+    /// `None` if the record is malformed, was tampered with or was sealed under another
+    /// secret.
+    pub fn open(&self, sealed: &str) -> Option<Vec<u8>> {
+        let (nonce, ciphertext) = sealed.split_once('.')?;
+        let nonce = Nonce::try_assume_unique_for_key(&from_hex(nonce)?).ok()?;
+        let mut ciphertext = from_hex(ciphertext)?;
+        let plaintext = self
+            .key
+            .open_in_place(nonce, Aad::empty(), &mut ciphertext)
+            .ok()?;
+        Some(plaintext.to_vec())
+    }
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) || !hex.is_ascii() {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sealed_records_open_only_under_their_secret_and_untouched() {
+        let vault = SeedVault::new(b"audit secret");
+        let sealed = vault.seal(b"round seeds");
+        assert!(!sealed.contains(&to_hex(b"round seeds")));
+        assert_ne!(vault.seal(b"round seeds"), sealed);
+        assert_eq!(
+            vault.open(&sealed).as_deref(),
+            Some(b"round seeds".as_slice())
+        );
+
+        assert_eq!(SeedVault::new(b"another secret").open(&sealed), None);
+        let mut tampered = sealed.clone().into_bytes();
+        let flip = sealed.find('.').unwrap() + 1;
+        tampered[flip] = if tampered[flip] == b'0' { b'1' } else { b'0' };
+        assert_eq!(vault.open(&String::from_utf8(tampered).unwrap()), None);
+        assert_eq!(vault.open("not sealed"), None);
+        assert_eq!(
+            vault.open(&sealed[..sealed.len() - 2]),
+            None,
+            "Tag cut short"
+        );
+    }
+}
@@ -0,0 +1,208 @@
+use sqlx::SqlitePool;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+
+use crate::db::models::User;
+use crate::db::repo;
+
+/// Recently looked-up accounts, so logins and profile reads skip SQLite. Only accounts
+/// that exist are kept; whoever changes an account must [`UserCache::invalidate`] it.
+/// Role checks read the account directly rather than trust this.
+#[derive(Clone)]
+pub struct UserCache {
+    inner: Arc<Mutex<Lru>>,
+}
+
+/// Accounts by id, with a username index and the order they were last used in.
+struct Lru {
+    capacity: usize,
+    users: HashMap<String, (User, u64)>,
+    ids_by_username: HashMap<String, String>,
+    /// Last use of each cached id; the first entry is the one evicted next
+    recency: BTreeMap<u64, String>,
+    clock: u64,
+    /// Bumped by every invalidation; a read that started before one isn't cached
+    generation: u64,
+}
+
+impl Lru {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            users: HashMap::new(),
+            ids_by_username: HashMap::new(),
+            recency: BTreeMap::new(),
+            clock: 0,
+            generation: 0,
+        }
+    }
+
+    fn get(&mut self, user_id: &str) -> Option<User> {
+        self.clock += 1;
+        let (user, used) = self.users.get_mut(user_id)?;
+        self.recency.remove(used);
+        *used = self.clock;
+        self.recency.insert(self.clock, user_id.to_string());
+        Some(user.clone())
+    }
+
+    fn insert(&mut self, user: User) {
+        if self.capacity == 0 {
+            return;
+        }
+        self.remove(&user.id);
+        while self.users.len() >= self.capacity {
+            let Some((_, oldest)) = self.recency.pop_first() else {
+                break;
+            };
+            self.remove(&oldest);
+        }
+        self.clock += 1;
+        self.recency.insert(self.clock, user.id.clone());
+        self.ids_by_username
+            .insert(user.username.clone(), user.id.clone());
+        self.users.insert(user.id.clone(), (user, self.clock));
+    }
+
+    fn remove(&mut self, user_id: &str) {
+        if let Some((user, used)) = self.users.remove(user_id) {
+            self.recency.remove(&used);
+            self.ids_by_username.remove(&user.username);
+        }
+    }
+}
+
+impl UserCache {
+    /// Keeps up to `capacity` accounts; zero caches nothing.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(Lru::new(capacity))),
+        }
+    }
+
+    /// `repo::get_user`, served from memory when the account was looked up recently.
+    pub async fn get_user(&self, pool: &SqlitePool, username: &str) -> Option<User> {
+        let cached = {
+            let mut lru = self.inner.lock().unwrap();
+            let id = lru.ids_by_username.get(username).cloned();
+            id.and_then(|id| lru.get(&id))
+        };
+        if cached.is_some() {
+            return cached;
+        }
+        self.read_through(repo::get_user(pool, username)).await
+    }
+
+    /// `repo::get_user_by_id`, served from memory when the account was looked up recently.
+    pub async fn get_user_by_id(&self, pool: &SqlitePool, user_id: &str) -> Option<User> {
+        let cached = self.inner.lock().unwrap().get(user_id);
+        if cached.is_some() {
+            return cached;
+        }
+        self.read_through(repo::get_user_by_id(pool, user_id)).await
+    }
+
+    /// Runs `read` and caches what it found, unless an account was invalidated meanwhile:
+    /// the row may have been read before that change landed.
+    async fn read_through(&self, read: impl Future<Output = Option<User>>) -> Option<User> {
+        let generation = self.inner.lock().unwrap().generation;
+        let user = read.await?;
+        let mut lru = self.inner.lock().unwrap();
+        if lru.generation == generation {
+            lru.insert(user.clone());
+        }
+        Some(user)
+    }
+
+    /// Forgets the account, e.g. after a rename or a role change, so the next lookup reads
+    /// it afresh.
+    pub fn invalidate(&self, user_id: &str) {
+        let mut lru = self.inner.lock().unwrap();
+        lru.generation += 1;
+        lru.remove(user_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    fn user(id: &str, username: &str) -> User {
+        User {
+            id: id.to_string(),
+            username: username.to_string(),
+            password_hash: String::new(),
+            created_at: 0,
+            role: "player".to_string(),
+        }
+    }
+
+    #[test]
+    fn evicts_the_least_recently_used() {
+        let mut lru = Lru::new(2);
+        lru.insert(user("1", "ana"));
+        lru.insert(user("2", "beto"));
+        assert!(lru.get("1").is_some());
+        lru.insert(user("3", "carla"));
+        assert!(lru.get("2").is_none(), "beto went unused the longest");
+        assert!(lru.get("1").is_some());
+        assert_eq!(lru.ids_by_username.len(), 2);
+        assert_eq!(lru.recency.len(), 2);
+
+        lru.insert(user("1", "anita"));
+        assert!(!lru.ids_by_username.contains_key("ana"));
+        assert_eq!(lru.get("1").unwrap().username, "anita");
+    }
+
+    #[tokio::test]
+    async fn reads_through_and_forgets_changed_accounts() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        repo::create_user_table(&pool).await.unwrap();
+        repo::insert_user(&pool, &user("1", "ana")).await.unwrap();
+
+        let cache = UserCache::new(10);
+        assert!(cache.get_user(&pool, "nobody").await.is_none());
+        assert_eq!(cache.get_user(&pool, "ana").await.unwrap().id, "1");
+
+        repo::set_user_role(&pool, "1", "admin").await.unwrap();
+        let stale = cache.get_user_by_id(&pool, "1").await.unwrap();
+        assert_eq!(stale.role, "player", "Served from memory");
+        cache.invalidate("1");
+        assert_eq!(cache.get_user(&pool, "ana").await.unwrap().role, "admin");
+
+        assert!(UserCache::new(0).get_user(&pool, "ana").await.is_some());
+    }
+
+    #[tokio::test]
+    async fn a_read_overlapping_an_invalidation_is_not_cached() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        repo::create_user_table(&pool).await.unwrap();
+        repo::insert_user(&pool, &user("1", "ana")).await.unwrap();
+
+        let cache = UserCache::new(10);
+        // The row is read, then the role changes and is invalidated before the read caches it
+        let raced = cache
+            .read_through(async {
+                let before = repo::get_user_by_id(&pool, "1").await;
+                repo::set_user_role(&pool, "1", "admin").await.unwrap();
+                cache.invalidate("1");
+                before
+            })
+            .await;
+        assert_eq!(raced.unwrap().role, "player");
+        assert_eq!(
+            cache.get_user_by_id(&pool, "1").await.unwrap().role,
+            "admin",
+            "The stale row was not kept"
+        );
+    }
+}
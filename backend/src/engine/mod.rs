@@ -1,7 +0,0 @@
-pub mod bot;
-pub mod card;
-pub mod combo_finder;
-pub mod deck;
-pub mod game;
-pub mod points;
-pub mod rules;
@@ -0,0 +1,410 @@
+//! zlib streams (RFC 1950/1951) as PNG uses them: a full inflater, and a deflater that
+//! finds repeats with hash chains and codes them with the fixed Huffman tables.
+
+use crate::imaging::huffman::Huffman;
+
+/// Base lengths and extra bits of length codes 257..=285.
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+/// Base distances and extra bits of distance codes 0..=29.
+const DIST_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DIST_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+/// Order the code length code lengths are sent in.
+const CODE_LENGTH_ORDER: [usize; 19] = [
+    16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
+];
+
+pub fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for chunk in data.chunks(5552) {
+        for &byte in chunk {
+            a += byte as u32;
+            b += a;
+        }
+        a %= 65521;
+        b %= 65521;
+    }
+    (b << 16) | a
+}
+
+/// Bits are consumed from the least significant end of each byte.
+struct BitReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+    bit: u32,
+}
+
+impl BitReader<'_> {
+    fn bit(&mut self) -> Result<u32, &'static str> {
+        let byte = *self.data.get(self.pos).ok_or("Truncated zlib stream")?;
+        let bit = (byte >> self.bit) & 1;
+        self.bit += 1;
+        if self.bit == 8 {
+            self.bit = 0;
+            self.pos += 1;
+        }
+        Ok(bit as u32)
+    }
+
+    fn bits(&mut self, count: u8) -> Result<u32, &'static str> {
+        let mut value = 0;
+        for i in 0..count {
+            value |= self.bit()? << i;
+        }
+        Ok(value)
+    }
+
+    fn align(&mut self) {
+        if self.bit != 0 {
+            self.bit = 0;
+            self.pos += 1;
+        }
+    }
+}
+
+fn fixed_tables() -> (Huffman, Huffman) {
+    let mut lengths = [0u8; 288];
+    lengths[..144].fill(8);
+    lengths[144..256].fill(9);
+    lengths[256..280].fill(7);
+    lengths[280..].fill(8);
+    let literals = Huffman::from_lengths(&lengths).expect("fixed literal table");
+    let distances = Huffman::from_lengths(&[5; 30]).expect("fixed distance table");
+    (literals, distances)
+}
+
+fn dynamic_tables(input: &mut BitReader) -> Result<(Huffman, Huffman), &'static str> {
+    let literal_count = input.bits(5)? as usize + 257;
+    let distance_count = input.bits(5)? as usize + 1;
+    let code_length_count = input.bits(4)? as usize + 4;
+    if literal_count > 286 || distance_count > 30 {
+        return Err("Bad zlib stream");
+    }
+
+    let mut code_lengths = [0u8; 19];
+    for &index in &CODE_LENGTH_ORDER[..code_length_count] {
+        code_lengths[index] = input.bits(3)? as u8;
+    }
+    let code_length_table = Huffman::from_lengths(&code_lengths)?;
+
+    let mut lengths = vec![0u8; literal_count + distance_count];
+    let mut i = 0;
+    while i < lengths.len() {
+        let symbol = code_length_table.decode(|| input.bit())?;
+        let (value, repeat) = match symbol {
+            0..=15 => (symbol as u8, 1),
+            16 => {
+                let previous = *lengths[..i].last().ok_or("Bad zlib stream")?;
+                (previous, 3 + input.bits(2)? as usize)
+            }
+            17 => (0, 3 + input.bits(3)? as usize),
+            _ => (0, 11 + input.bits(7)? as usize),
+        };
+        if i + repeat > lengths.len() {
+            return Err("Bad zlib stream");
+        }
+        lengths[i..i + repeat].fill(value);
+        i += repeat;
+    }
+    if lengths[256] == 0 {
+        return Err("Bad zlib stream");
+    }
+    let literals = Huffman::from_lengths(&lengths[..literal_count])?;
+    let distances = Huffman::from_lengths(&lengths[literal_count..])?;
+    Ok((literals, distances))
+}
+
+/// Inflates a zlib stream, refusing to produce more than `limit` bytes.
+pub fn zlib_decompress(data: &[u8], limit: usize) -> Result<Vec<u8>, &'static str> {
+    if data.len() < 6 {
+        return Err("Truncated zlib stream");
+    }
+    let (cmf, flg) = (data[0], data[1]);
+    if cmf & 0x0F != 8 || !(cmf as u16 * 256 + flg as u16).is_multiple_of(31) || flg & 0x20 != 0 {
+        return Err("Bad zlib header");
+    }
+
+    let mut input = BitReader {
+        data: &data[2..],
+        pos: 0,
+        bit: 0,
+    };
+    let mut out = Vec::new();
+    loop {
+        let last = input.bit()? == 1;
+        match input.bits(2)? {
+            0 => {
+                input.align();
+                let header = input
+                    .data
+                    .get(input.pos..input.pos + 4)
+                    .ok_or("Truncated zlib stream")?;
+                let length = u16::from_le_bytes([header[0], header[1]]);
+                if length != !u16::from_le_bytes([header[2], header[3]]) {
+                    return Err("Bad zlib stream");
+                }
+                input.pos += 4;
+                let stored = input
+                    .data
+                    .get(input.pos..input.pos + length as usize)
+                    .ok_or("Truncated zlib stream")?;
+                if out.len() + stored.len() > limit {
+                    return Err("Image data is larger than its header says");
+                }
+                out.extend_from_slice(stored);
+                input.pos += length as usize;
+            }
+            kind @ (1 | 2) => {
+                let (literals, distances) = if kind == 1 {
+                    fixed_tables()
+                } else {
+                    dynamic_tables(&mut input)?
+                };
+                loop {
+                    let symbol = literals.decode(|| input.bit())? as usize;
+                    if symbol < 256 {
+                        if out.len() == limit {
+                            return Err("Image data is larger than its header says");
+                        }
+                        out.push(symbol as u8);
+                        continue;
+                    }
+                    if symbol == 256 {
+                        break;
+                    }
+                    let code = symbol - 257;
+                    if code >= LENGTH_BASE.len() {
+                        return Err("Bad zlib stream");
+                    }
+                    let length =
+                        LENGTH_BASE[code] as usize + input.bits(LENGTH_EXTRA[code])? as usize;
+                    let code = distances.decode(|| input.bit())? as usize;
+                    if code >= DIST_BASE.len() {
+                        return Err("Bad zlib stream");
+                    }
+                    let distance =
+                        DIST_BASE[code] as usize + input.bits(DIST_EXTRA[code])? as usize;
+                    if distance > out.len() {
+                        return Err("Bad zlib stream");
+                    }
+                    if out.len() + length > limit {
+                        return Err("Image data is larger than its header says");
+                    }
+                    let start = out.len() - distance;
+                    for i in 0..length {
+                        out.push(out[start + i]);
+                    }
+                }
+            }
+            _ => return Err("Bad zlib stream"),
+        }
+        if last {
+            break;
+        }
+    }
+
+    input.align();
+    let trailer = input
+        .data
+        .get(input.pos..input.pos + 4)
+        .ok_or("Truncated zlib stream")?;
+    if u32::from_be_bytes([trailer[0], trailer[1], trailer[2], trailer[3]]) != adler32(&out) {
+        return Err("Corrupt zlib stream");
+    }
+    Ok(out)
+}
+
+/// Bits are packed from the least significant end of each byte.
+#[derive(Default)]
+struct BitWriter {
+    out: Vec<u8>,
+    buffer: u64,
+    filled: u32,
+}
+
+impl BitWriter {
+    fn bits(&mut self, value: u32, count: u32) {
+        self.buffer |= (value as u64) << self.filled;
+        self.filled += count;
+        while self.filled >= 8 {
+            self.out.push(self.buffer as u8);
+            self.buffer >>= 8;
+            self.filled -= 8;
+        }
+    }
+
+    /// Huffman codes go out most significant bit first.
+    fn code(&mut self, code: u32, length: u32) {
+        self.bits(code.reverse_bits() >> (32 - length), length);
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.filled > 0 {
+            self.out.push(self.buffer as u8);
+        }
+        self.out
+    }
+}
+
+fn fixed_literal(out: &mut BitWriter, symbol: u32) {
+    match symbol {
+        0..=143 => out.code(0x30 + symbol, 8),
+        144..=255 => out.code(0x190 + symbol - 144, 9),
+        256..=279 => out.code(symbol - 256, 7),
+        _ => out.code(0xC0 + symbol - 280, 8),
+    }
+}
+
+fn fixed_match(out: &mut BitWriter, length: usize, distance: usize) {
+    let code = LENGTH_BASE
+        .iter()
+        .rposition(|&base| base as usize <= length)
+        .unwrap();
+    fixed_literal(out, 257 + code as u32);
+    out.bits(
+        (length - LENGTH_BASE[code] as usize) as u32,
+        LENGTH_EXTRA[code] as u32,
+    );
+    let code = DIST_BASE
+        .iter()
+        .rposition(|&base| base as usize <= distance)
+        .unwrap();
+    out.code(code as u32, 5);
+    out.bits(
+        (distance - DIST_BASE[code] as usize) as u32,
+        DIST_EXTRA[code] as u32,
+    );
+}
+
+const WINDOW: usize = 32 * 1024;
+const MIN_MATCH: usize = 3;
+const MAX_MATCH: usize = 258;
+/// How many earlier positions with the same three bytes are tried per match.
+const MAX_CHAIN: usize = 64;
+const HASH_BITS: u32 = 15;
+
+/// Deflates `data` into a zlib stream.
+pub fn zlib_compress(data: &[u8]) -> Vec<u8> {
+    let mut out = BitWriter::default();
+    out.out.extend([0x78, 0x01]);
+    // One final block with the fixed tables
+    out.bits(1, 1);
+    out.bits(1, 2);
+
+    let hash = |i: usize| {
+        let key = u32::from_le_bytes([data[i], data[i + 1], data[i + 2], 0]);
+        (key.wrapping_mul(2654435761) >> (32 - HASH_BITS)) as usize
+    };
+    // The latest position of each hash, and before that the one it displaced
+    let mut head = vec![usize::MAX; 1 << HASH_BITS];
+    let mut previous = vec![usize::MAX; data.len()];
+    let insert = |i: usize, head: &mut [usize], previous: &mut [usize]| {
+        if i + MIN_MATCH <= data.len() {
+            let h = hash(i);
+            previous[i] = head[h];
+            head[h] = i;
+        }
+    };
+
+    let mut i = 0;
+    while i < data.len() {
+        let mut best = (0, 0);
+        if i + MIN_MATCH <= data.len() {
+            let mut candidate = head[hash(i)];
+            let longest = MAX_MATCH.min(data.len() - i);
+            for _ in 0..MAX_CHAIN {
+                if candidate == usize::MAX || i - candidate > WINDOW {
+                    break;
+                }
+                let length = data[candidate..]
+                    .iter()
+                    .zip(&data[i..i + longest])
+                    .take_while(|(a, b)| a == b)
+                    .count();
+                if length > best.0 {
+                    best = (length, i - candidate);
+                    if length == longest {
+                        break;
+                    }
+                }
+                candidate = previous[candidate];
+            }
+        }
+
+        if best.0 >= MIN_MATCH {
+            fixed_match(&mut out, best.0, best.1);
+            for j in i..i + best.0 {
+                insert(j, &mut head, &mut previous);
+            }
+            i += best.0;
+        } else {
+            fixed_literal(&mut out, data[i] as u32);
+            insert(i, &mut head, &mut previous);
+            i += 1;
+        }
+    }
+    fixed_literal(&mut out, 256);
+
+    let mut out = out.finish();
+    out.extend(adler32(data).to_be_bytes());
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_repetitive_and_random_data() {
+        let mut noise = Vec::new();
+        let mut x = 12345u32;
+        for _ in 0..70_000 {
+            x = x.wrapping_mul(1103515245).wrapping_add(12345);
+            noise.push((x >> 24) as u8);
+        }
+        let stripes: Vec<u8> = (0..100_000).map(|i| (i % 7 * 30) as u8).collect();
+        for data in [Vec::new(), b"a".to_vec(), noise, stripes] {
+            let packed = zlib_compress(&data);
+            assert_eq!(zlib_decompress(&packed, data.len()).unwrap(), data);
+        }
+    }
+
+    #[test]
+    fn inflates_stored_and_dynamic_blocks() {
+        // What zlib itself writes for these at levels 0 and 9
+        let stored = [
+            0x78, 0x01, 0x01, 0x05, 0x00, 0xfa, 0xff, 0x68, 0x65, 0x6c, 0x6c, 0x6f, 0x06, 0x2c,
+            0x02, 0x15,
+        ];
+        assert_eq!(zlib_decompress(&stored, 5).unwrap(), b"hello");
+        let text: &[u8] = b"abcccaaaacaabacaaaadcaabccabaabcabadaaaabbadabaababacaabaaabacaadaacdbdbaabbcaabadbbbdabcdbaaabdacba";
+        let dynamic = [
+            0x78, 0xda, 0x2d, 0x8a, 0xc1, 0x11, 0xc0, 0x30, 0x0c, 0xc2, 0x66, 0x45, 0xb0, 0xff,
+            0x0c, 0x05, 0xa7, 0x7e, 0x60, 0x4e, 0x48, 0xd8, 0x56, 0xaf, 0x81, 0xae, 0x65, 0xb5,
+            0x90, 0xbd, 0x66, 0x06, 0xd9, 0x1f, 0x79, 0x16, 0xfa, 0xf5, 0x8e, 0x0e, 0xd9, 0xc2,
+            0xf1, 0x00, 0x35, 0x9d, 0x53, 0x22, 0xa3, 0x0f, 0x89, 0xbc, 0x26, 0x3a,
+        ];
+        assert_eq!(zlib_decompress(&dynamic, text.len()).unwrap(), text);
+    }
+
+    #[test]
+    fn refuses_corrupt_or_oversized_streams() {
+        let packed = zlib_compress(&[7; 1000]);
+        assert!(zlib_decompress(&packed, 999).is_err());
+        let mut corrupt = packed.clone();
+        *corrupt.last_mut().unwrap() ^= 1;
+        assert!(zlib_decompress(&corrupt, 1000).is_err());
+        assert!(zlib_decompress(&packed[..packed.len() - 2], 1000).is_err());
+    }
+}
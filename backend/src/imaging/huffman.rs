@@ -0,0 +1,99 @@
+//! Canonical Huffman decoding, shared by zlib and JPEG: both assign codes in order of
+//! length and then symbol, and both send a code's most significant bit first.
+
+const MAX_BITS: usize = 16;
+
+pub struct Huffman {
+    /// How many codes have each length
+    counts: [u16; MAX_BITS + 1],
+    /// Symbols ordered by code
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    /// A table from each symbol's code length, zero for symbols that don't occur.
+    pub fn from_lengths(lengths: &[u8]) -> Result<Self, &'static str> {
+        let mut counts = [0u16; MAX_BITS + 1];
+        for &length in lengths {
+            if length as usize > MAX_BITS {
+                return Err("Bad Huffman table");
+            }
+            counts[length as usize] += 1;
+        }
+        counts[0] = 0;
+        let mut symbols: Vec<(u8, u16)> = lengths
+            .iter()
+            .enumerate()
+            .filter(|(_, length)| **length > 0)
+            .map(|(symbol, &length)| (length, symbol as u16))
+            .collect();
+        symbols.sort();
+        Self::new(
+            counts,
+            symbols.into_iter().map(|(_, symbol)| symbol).collect(),
+        )
+    }
+
+    /// A table in JPEG's form: code counts for lengths 1 to 16, then the symbols in order.
+    pub fn from_counts(counts: &[u8; 16], symbols: &[u8]) -> Result<Self, &'static str> {
+        let mut all = [0u16; MAX_BITS + 1];
+        for (length, &count) in counts.iter().enumerate() {
+            all[length + 1] = count as u16;
+        }
+        Self::new(all, symbols.iter().map(|&symbol| symbol as u16).collect())
+    }
+
+    fn new(counts: [u16; MAX_BITS + 1], symbols: Vec<u16>) -> Result<Self, &'static str> {
+        if counts.iter().map(|&count| count as usize).sum::<usize>() != symbols.len() {
+            return Err("Bad Huffman table");
+        }
+        // More codes of some length than the shorter ones leave room for
+        let mut left = 1i32;
+        for &count in &counts[1..] {
+            left = left * 2 - count as i32;
+            if left < 0 {
+                return Err("Bad Huffman table");
+            }
+        }
+        Ok(Self { counts, symbols })
+    }
+
+    /// Reads one symbol, pulling bits from `bit` as the code goes on.
+    pub fn decode(
+        &self,
+        mut bit: impl FnMut() -> Result<u32, &'static str>,
+    ) -> Result<u16, &'static str> {
+        // `first` is the first code of the current length, `index` its place in `symbols`
+        let (mut code, mut first, mut index) = (0i32, 0i32, 0i32);
+        for &count in &self.counts[1..] {
+            code |= bit()? as i32;
+            let count = count as i32;
+            if code - first < count {
+                return Ok(self.symbols[(index + code - first) as usize]);
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        Err("Bad Huffman code")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_canonical_codes() {
+        // Lengths 2, 1, 3, 3 give the codes 10, 0, 110 and 111
+        let table = Huffman::from_lengths(&[2, 1, 3, 3]).unwrap();
+        let bits = [1, 1, 1, 0, 1, 0, 1, 1, 0];
+        let mut next = bits.iter().copied();
+        let mut read = || next.next().ok_or("Out of bits");
+        let symbols: Vec<u16> = (0..4).map(|_| table.decode(&mut read).unwrap()).collect();
+        assert_eq!(symbols, vec![3, 1, 0, 2]);
+
+        assert!(Huffman::from_lengths(&[1, 1, 1]).is_err());
+        assert!(Huffman::from_counts(&[1; 16], &[0]).is_err());
+    }
+}
@@ -0,0 +1,933 @@
+//! Baseline and progressive JPEG decoding (8-bit, gray or three components, any chroma
+//! subsampling), and baseline encoding with the example tables of the standard's annex K.
+
+use crate::imaging::Image;
+use crate::imaging::huffman::Huffman;
+
+/// Where in the 8×8 block each coefficient goes, in the zig-zag order they are sent in.
+const ZIGZAG: [usize; 64] = [
+    0, 1, 8, 16, 9, 2, 3, 10, 17, 24, 32, 25, 18, 11, 4, 5, 12, 19, 26, 33, 40, 48, 41, 34, 27, 20,
+    13, 6, 7, 14, 21, 28, 35, 42, 49, 56, 57, 50, 43, 36, 29, 22, 15, 23, 30, 37, 44, 51, 58, 59,
+    52, 45, 38, 31, 39, 46, 53, 60, 61, 54, 47, 55, 62, 63,
+];
+
+/// Annex K quantization tables, row by row.
+const LUMA_QUANT: [u16; 64] = [
+    16, 11, 10, 16, 24, 40, 51, 61, 12, 12, 14, 19, 26, 58, 60, 55, 14, 13, 16, 24, 40, 57, 69, 56,
+    14, 17, 22, 29, 51, 87, 80, 62, 18, 22, 37, 56, 68, 109, 103, 77, 24, 35, 55, 64, 81, 104, 113,
+    92, 49, 64, 78, 87, 103, 121, 120, 101, 72, 92, 95, 98, 112, 100, 103, 99,
+];
+const CHROMA_QUANT: [u16; 64] = [
+    17, 18, 24, 47, 99, 99, 99, 99, 18, 21, 26, 66, 99, 99, 99, 99, 24, 26, 56, 99, 99, 99, 99, 99,
+    47, 66, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99,
+    99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99,
+];
+
+/// Annex K Huffman tables: code counts per length, then the symbols.
+const LUMA_DC: ([u8; 16], [u8; 12]) = (
+    [0, 1, 5, 1, 1, 1, 1, 1, 1, 0, 0, 0, 0, 0, 0, 0],
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11],
+);
+const CHROMA_DC: ([u8; 16], [u8; 12]) = (
+    [0, 3, 1, 1, 1, 1, 1, 1, 1, 1, 1, 0, 0, 0, 0, 0],
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11],
+);
+const LUMA_AC: ([u8; 16], [u8; 162]) = (
+    [0, 2, 1, 3, 3, 2, 4, 3, 5, 5, 4, 4, 0, 0, 1, 0x7d],
+    [
+        0x01, 0x02, 0x03, 0x00, 0x04, 0x11, 0x05, 0x12, 0x21, 0x31, 0x41, 0x06, 0x13, 0x51, 0x61,
+        0x07, 0x22, 0x71, 0x14, 0x32, 0x81, 0x91, 0xa1, 0x08, 0x23, 0x42, 0xb1, 0xc1, 0x15, 0x52,
+        0xd1, 0xf0, 0x24, 0x33, 0x62, 0x72, 0x82, 0x09, 0x0a, 0x16, 0x17, 0x18, 0x19, 0x1a, 0x25,
+        0x26, 0x27, 0x28, 0x29, 0x2a, 0x34, 0x35, 0x36, 0x37, 0x38, 0x39, 0x3a, 0x43, 0x44, 0x45,
+        0x46, 0x47, 0x48, 0x49, 0x4a, 0x53, 0x54, 0x55, 0x56, 0x57, 0x58, 0x59, 0x5a, 0x63, 0x64,
+        0x65, 0x66, 0x67, 0x68, 0x69, 0x6a, 0x73, 0x74, 0x75, 0x76, 0x77, 0x78, 0x79, 0x7a, 0x83,
+        0x84, 0x85, 0x86, 0x87, 0x88, 0x89, 0x8a, 0x92, 0x93, 0x94, 0x95, 0x96, 0x97, 0x98, 0x99,
+        0x9a, 0xa2, 0xa3, 0xa4, 0xa5, 0xa6, 0xa7, 0xa8, 0xa9, 0xaa, 0xb2, 0xb3, 0xb4, 0xb5, 0xb6,
+        0xb7, 0xb8, 0xb9, 0xba, 0xc2, 0xc3, 0xc4, 0xc5, 0xc6, 0xc7, 0xc8, 0xc9, 0xca, 0xd2, 0xd3,
+        0xd4, 0xd5, 0xd6, 0xd7, 0xd8, 0xd9, 0xda, 0xe1, 0xe2, 0xe3, 0xe4, 0xe5, 0xe6, 0xe7, 0xe8,
+        0xe9, 0xea, 0xf1, 0xf2, 0xf3, 0xf4, 0xf5, 0xf6, 0xf7, 0xf8, 0xf9, 0xfa,
+    ],
+);
+const CHROMA_AC: ([u8; 16], [u8; 162]) = (
+    [0, 2, 1, 2, 4, 4, 3, 4, 7, 5, 4, 4, 0, 1, 2, 0x77],
+    [
+        0x00, 0x01, 0x02, 0x03, 0x11, 0x04, 0x05, 0x21, 0x31, 0x06, 0x12, 0x41, 0x51, 0x07, 0x61,
+        0x71, 0x13, 0x22, 0x32, 0x81, 0x08, 0x14, 0x42, 0x91, 0xa1, 0xb1, 0xc1, 0x09, 0x23, 0x33,
+        0x52, 0xf0, 0x15, 0x62, 0x72, 0xd1, 0x0a, 0x16, 0x24, 0x34, 0xe1, 0x25, 0xf1, 0x17, 0x18,
+        0x19, 0x1a, 0x26, 0x27, 0x28, 0x29, 0x2a, 0x35, 0x36, 0x37, 0x38, 0x39, 0x3a, 0x43, 0x44,
+        0x45, 0x46, 0x47, 0x48, 0x49, 0x4a, 0x53, 0x54, 0x55, 0x56, 0x57, 0x58, 0x59, 0x5a, 0x63,
+        0x64, 0x65, 0x66, 0x67, 0x68, 0x69, 0x6a, 0x73, 0x74, 0x75, 0x76, 0x77, 0x78, 0x79, 0x7a,
+        0x82, 0x83, 0x84, 0x85, 0x86, 0x87, 0x88, 0x89, 0x8a, 0x92, 0x93, 0x94, 0x95, 0x96, 0x97,
+        0x98, 0x99, 0x9a, 0xa2, 0xa3, 0xa4, 0xa5, 0xa6, 0xa7, 0xa8, 0xa9, 0xaa, 0xb2, 0xb3, 0xb4,
+        0xb5, 0xb6, 0xb7, 0xb8, 0xb9, 0xba, 0xc2, 0xc3, 0xc4, 0xc5, 0xc6, 0xc7, 0xc8, 0xc9, 0xca,
+        0xd2, 0xd3, 0xd4, 0xd5, 0xd6, 0xd7, 0xd8, 0xd9, 0xda, 0xe2, 0xe3, 0xe4, 0xe5, 0xe6, 0xe7,
+        0xe8, 0xe9, 0xea, 0xf2, 0xf3, 0xf4, 0xf5, 0xf6, 0xf7, 0xf8, 0xf9, 0xfa,
+    ],
+);
+
+/// `BASIS[x][u]` weighs frequency `u` at sample `x` in the orthonormal 8-point DCT.
+fn dct_basis() -> [[f32; 8]; 8] {
+    let mut basis = [[0f32; 8]; 8];
+    for (x, row) in basis.iter_mut().enumerate() {
+        for (u, weight) in row.iter_mut().enumerate() {
+            let scale = if u == 0 {
+                std::f32::consts::FRAC_1_SQRT_2
+            } else {
+                1.0
+            };
+            let angle = (2 * x + 1) as f32 * u as f32 * std::f32::consts::PI / 16.0;
+            *weight = scale * angle.cos() / 2.0;
+        }
+    }
+    basis
+}
+
+/// Entropy-coded data, with stuffed zero bytes removed. Reaching a marker yields zeros.
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+    byte: u32,
+    bits: u32,
+}
+
+impl Reader<'_> {
+    fn bit(&mut self) -> Result<u32, &'static str> {
+        if self.bits == 0 {
+            self.byte = match (self.data.get(self.pos), self.data.get(self.pos + 1)) {
+                (Some(0xFF), Some(0)) => {
+                    self.pos += 2;
+                    0xFF
+                }
+                (Some(0xFF), _) | (None, _) => 0,
+                (Some(&byte), _) => {
+                    self.pos += 1;
+                    byte as u32
+                }
+            };
+            self.bits = 8;
+        }
+        self.bits -= 1;
+        Ok((self.byte >> self.bits) & 1)
+    }
+
+    fn receive(&mut self, count: u8) -> Result<i32, &'static str> {
+        let mut value = 0;
+        for _ in 0..count {
+            value = (value << 1) | self.bit()? as i32;
+        }
+        Ok(value)
+    }
+
+    /// A `size`-bit magnitude category value, negative when its top bit is clear.
+    fn receive_extend(&mut self, size: u16) -> Result<i32, &'static str> {
+        if size > 16 {
+            return Err("Corrupt JPEG");
+        }
+        let value = self.receive(size as u8)?;
+        Ok(if size > 0 && value < 1 << (size - 1) {
+            value - (1 << size) + 1
+        } else {
+            value
+        })
+    }
+
+    /// Skips to just past the next restart marker.
+    fn restart(&mut self) {
+        self.bits = 0;
+        while self.pos + 1 < self.data.len() {
+            if self.data[self.pos] == 0xFF && (0xD0..=0xD7).contains(&self.data[self.pos + 1]) {
+                self.pos += 2;
+                return;
+            }
+            self.pos += 1;
+        }
+    }
+
+    /// Where the marker ending the scan starts.
+    fn scan_end(&self) -> usize {
+        let mut pos = self.pos;
+        while pos + 1 < self.data.len() {
+            let next = self.data[pos + 1];
+            if self.data[pos] == 0xFF && next != 0 && next != 0xFF && !(0xD0..=0xD7).contains(&next)
+            {
+                return pos;
+            }
+            pos += 1;
+        }
+        self.data.len()
+    }
+}
+
+struct Component {
+    id: u8,
+    h: usize,
+    v: usize,
+    quant: usize,
+    /// Blocks per row and column, padded out to whole MCUs
+    blocks_wide: usize,
+    blocks_high: usize,
+    /// 64 per block, in zig-zag order
+    coefficients: Vec<i16>,
+    predictor: i32,
+}
+
+/// The spectral band and bit position a scan carries.
+struct Scan {
+    progressive: bool,
+    start: usize,
+    end: usize,
+    high: u8,
+    low: u8,
+}
+
+impl Scan {
+    fn decode_block(
+        &self,
+        reader: &mut Reader,
+        block: &mut [i16],
+        dc: Option<&Huffman>,
+        ac: Option<&Huffman>,
+        predictor: &mut i32,
+        eob_run: &mut i32,
+    ) -> Result<(), &'static str> {
+        if self.start == 0 {
+            if self.high == 0 {
+                let size = dc.ok_or("Malformed JPEG")?.decode(|| reader.bit())?;
+                *predictor += reader.receive_extend(size)?;
+                block[0] = (*predictor << self.low) as i16;
+            } else if reader.bit()? == 1 {
+                block[0] |= 1 << self.low;
+            }
+            if self.progressive {
+                return Ok(());
+            }
+        }
+
+        let ac = ac.ok_or("Malformed JPEG")?;
+        let mut k = self.start.max(1);
+        if self.high == 0 {
+            if *eob_run > 0 {
+                *eob_run -= 1;
+                return Ok(());
+            }
+            while k <= self.end {
+                let symbol = ac.decode(|| reader.bit())?;
+                let (run, size) = ((symbol >> 4) as usize, symbol & 15);
+                if size == 0 {
+                    if run < 15 {
+                        *eob_run = (1 << run) - 1 + reader.receive(run as u8)?;
+                        break;
+                    }
+                    k += 16;
+                    continue;
+                }
+                k += run;
+                if k > self.end {
+                    return Err("Corrupt JPEG");
+                }
+                block[k] = (reader.receive_extend(size)? << self.low) as i16;
+                k += 1;
+            }
+            if !self.progressive {
+                *eob_run = 0;
+            }
+            return Ok(());
+        }
+
+        // Refinement: one more bit for coefficients already known, and new ones of ±1
+        let (plus, minus) = (1i16 << self.low, -1i16 << self.low);
+        let refine = |coefficient: &mut i16, reader: &mut Reader| -> Result<(), &'static str> {
+            if reader.bit()? == 1 && *coefficient & plus == 0 {
+                *coefficient += if *coefficient >= 0 { plus } else { minus };
+            }
+            Ok(())
+        };
+        if *eob_run == 0 {
+            while k <= self.end {
+                let symbol = ac.decode(|| reader.bit())?;
+                let (mut run, size) = ((symbol >> 4) as i32, symbol & 15);
+                let mut value = 0;
+                if size != 0 {
+                    value = if reader.bit()? == 1 { plus } else { minus };
+                } else if run != 15 {
+                    *eob_run = (1 << run) + reader.receive(run as u8)?;
+                    break;
+                }
+                while k <= self.end {
+                    if block[k] != 0 {
+                        refine(&mut block[k], reader)?;
+                    } else {
+                        run -= 1;
+                        if run < 0 {
+                            break;
+                        }
+                    }
+                    k += 1;
+                }
+                if value != 0 && k <= self.end {
+                    block[k] = value;
+                }
+                k += 1;
+            }
+        }
+        if *eob_run > 0 {
+            while k <= self.end {
+                if block[k] != 0 {
+                    refine(&mut block[k], reader)?;
+                }
+                k += 1;
+            }
+            *eob_run -= 1;
+        }
+        Ok(())
+    }
+}
+
+struct Decoder {
+    progressive: bool,
+    width: usize,
+    height: usize,
+    components: Vec<Component>,
+    quant: [[u16; 64]; 4],
+    dc_tables: [Option<Huffman>; 4],
+    ac_tables: [Option<Huffman>; 4],
+    restart_interval: usize,
+    /// From an Adobe segment: 0 means the three components are RGB rather than YCbCr
+    adobe_transform: Option<u8>,
+}
+
+impl Decoder {
+    fn max_sampling(&self) -> (usize, usize) {
+        let h = self.components.iter().map(|c| c.h).max().unwrap_or(1);
+        let v = self.components.iter().map(|c| c.v).max().unwrap_or(1);
+        (h, v)
+    }
+
+    fn read_quant_tables(&mut self, mut body: &[u8]) -> Result<(), &'static str> {
+        while let Some(&spec) = body.first() {
+            let (wide, index) = (spec >> 4 == 1, (spec & 15) as usize);
+            let size = if wide { 128 } else { 64 };
+            let values = body.get(1..1 + size).ok_or("Malformed JPEG")?;
+            let table = self.quant.get_mut(index).ok_or("Malformed JPEG")?;
+            for (k, value) in table.iter_mut().enumerate() {
+                *value = if wide {
+                    u16::from_be_bytes([values[2 * k], values[2 * k + 1]])
+                } else {
+                    values[k] as u16
+                };
+            }
+            body = &body[1 + size..];
+        }
+        Ok(())
+    }
+
+    fn read_huffman_tables(&mut self, mut body: &[u8]) -> Result<(), &'static str> {
+        while let Some(&spec) = body.first() {
+            let (class, index) = (spec >> 4, (spec & 15) as usize);
+            let counts: &[u8; 16] = body
+                .get(1..17)
+                .and_then(|c| c.try_into().ok())
+                .ok_or("Malformed JPEG")?;
+            let total = counts.iter().map(|&c| c as usize).sum::<usize>();
+            let symbols = body.get(17..17 + total).ok_or("Malformed JPEG")?;
+            let table = Some(Huffman::from_counts(counts, symbols)?);
+            match (class, index) {
+                (0, 0..4) => self.dc_tables[index] = table,
+                (1, 0..4) => self.ac_tables[index] = table,
+                _ => return Err("Malformed JPEG"),
+            }
+            body = &body[17 + total..];
+        }
+        Ok(())
+    }
+
+    fn read_frame(&mut self, body: &[u8], max_pixels: usize) -> Result<(), &'static str> {
+        if !self.components.is_empty() {
+            return Err("Malformed JPEG");
+        }
+        if body.len() < 6 || body[0] != 8 {
+            return Err("Unsupported JPEG format");
+        }
+        self.height = u16::from_be_bytes([body[1], body[2]]) as usize;
+        self.width = u16::from_be_bytes([body[3], body[4]]) as usize;
+        if self.width == 0 || self.height == 0 {
+            return Err("The image is empty");
+        }
+        if self.width * self.height > max_pixels {
+            return Err("The image is too large to process");
+        }
+        let count = body[5] as usize;
+        if count != 1 && count != 3 {
+            return Err("Unsupported JPEG format");
+        }
+        let specs = body.get(6..6 + 3 * count).ok_or("Malformed JPEG")?;
+        for spec in specs.chunks_exact(3) {
+            let (h, v) = ((spec[1] >> 4) as usize, (spec[1] & 15) as usize);
+            if !(1..=4).contains(&h) || !(1..=4).contains(&v) || spec[2] > 3 {
+                return Err("Malformed JPEG");
+            }
+            self.components.push(Component {
+                id: spec[0],
+                h,
+                v,
+                quant: spec[2] as usize,
+                blocks_wide: 0,
+                blocks_high: 0,
+                coefficients: Vec::new(),
+                predictor: 0,
+            });
+        }
+
+        let (h_max, v_max) = self.max_sampling();
+        let mcus_wide = self.width.div_ceil(8 * h_max);
+        let mcus_high = self.height.div_ceil(8 * v_max);
+        for component in &mut self.components {
+            component.blocks_wide = mcus_wide * component.h;
+            component.blocks_high = mcus_high * component.v;
+            component.coefficients = vec![0; component.blocks_wide * component.blocks_high * 64];
+        }
+        Ok(())
+    }
+
+    /// Decodes the scan whose header is `body` from the data at `data[start..]`, and returns
+    /// where the data ends.
+    fn read_scan(&mut self, body: &[u8], data: &[u8], start: usize) -> Result<usize, &'static str> {
+        let count = *body.first().ok_or("Malformed JPEG")? as usize;
+        let specs = body.get(1..1 + 2 * count).ok_or("Malformed JPEG")?;
+        let tail = body
+            .get(1 + 2 * count..4 + 2 * count)
+            .ok_or("Malformed JPEG")?;
+        let scan = Scan {
+            progressive: self.progressive,
+            start: tail[0] as usize,
+            end: tail[1] as usize,
+            high: tail[2] >> 4,
+            low: tail[2] & 15,
+        };
+        if scan.end > 63 || scan.start > scan.end || scan.low > 13 {
+            return Err("Malformed JPEG");
+        }
+        if !self.progressive && (scan.start != 0 || scan.end != 63) {
+            return Err("Malformed JPEG");
+        }
+
+        let mut members = Vec::new();
+        for spec in specs.chunks_exact(2) {
+            let index = self
+                .components
+                .iter()
+                .position(|c| c.id == spec[0])
+                .ok_or("Malformed JPEG")?;
+            members.push((
+                index,
+                (spec[1] >> 4) as usize & 3,
+                (spec[1] & 15) as usize & 3,
+            ));
+        }
+        if members.is_empty() || (scan.start > 0 && members.len() > 1) {
+            return Err("Malformed JPEG");
+        }
+
+        // (component, block) in the order the scan sends them, as one unit per MCU
+        let (h_max, v_max) = self.max_sampling();
+        let mut units: Vec<Vec<(usize, usize)>> = Vec::new();
+        if let [(index, _, _)] = members[..] {
+            let c = &self.components[index];
+            let wide = (self.width * c.h).div_ceil(h_max).div_ceil(8);
+            let high = (self.height * c.v).div_ceil(v_max).div_ceil(8);
+            for y in 0..high {
+                for x in 0..wide {
+                    units.push(vec![(index, y * c.blocks_wide + x)]);
+                }
+            }
+        } else {
+            let mcus_wide = self.width.div_ceil(8 * h_max);
+            let mcus_high = self.height.div_ceil(8 * v_max);
+            for mcu_y in 0..mcus_high {
+                for mcu_x in 0..mcus_wide {
+                    let mut unit = Vec::new();
+                    for &(index, _, _) in &members {
+                        let c = &self.components[index];
+                        for v in 0..c.v {
+                            for h in 0..c.h {
+                                let (x, y) = (mcu_x * c.h + h, mcu_y * c.v + v);
+                                unit.push((index, y * c.blocks_wide + x));
+                            }
+                        }
+                    }
+                    units.push(unit);
+                }
+            }
+        }
+
+        let mut reader = Reader {
+            data,
+            pos: start,
+            byte: 0,
+            bits: 0,
+        };
+        let mut eob_run = 0;
+        for component in &mut self.components {
+            component.predictor = 0;
+        }
+        for (n, unit) in units.iter().enumerate() {
+            if self.restart_interval > 0 && n > 0 && n % self.restart_interval == 0 {
+                reader.restart();
+                eob_run = 0;
+                for component in &mut self.components {
+                    component.predictor = 0;
+                }
+            }
+            for &(index, block) in unit {
+                let &(_, dc, ac) = members.iter().find(|m| m.0 == index).unwrap();
+                let component = &mut self.components[index];
+                scan.decode_block(
+                    &mut reader,
+                    &mut component.coefficients[block * 64..(block + 1) * 64],
+                    self.dc_tables[dc].as_ref(),
+                    self.ac_tables[ac].as_ref(),
+                    &mut component.predictor,
+                    &mut eob_run,
+                )?;
+            }
+        }
+        Ok(reader.scan_end())
+    }
+
+    /// Turns the coefficients into pixels.
+    fn finish(&self) -> Image {
+        let basis = dct_basis();
+        let planes: Vec<Vec<u8>> = self
+            .components
+            .iter()
+            .map(|c| {
+                let stride = c.blocks_wide * 8;
+                let mut plane = vec![0u8; stride * c.blocks_high * 8];
+                let quant = &self.quant[c.quant];
+                for (b, block) in c.coefficients.chunks_exact(64).enumerate() {
+                    let mut spectrum = [0f32; 64];
+                    for k in 0..64 {
+                        spectrum[ZIGZAG[k]] = block[k] as f32 * quant[k] as f32;
+                    }
+                    let samples = inverse_dct(&spectrum, &basis);
+                    let (bx, by) = (b % c.blocks_wide * 8, b / c.blocks_wide * 8);
+                    for y in 0..8 {
+                        for x in 0..8 {
+                            let value = (samples[y * 8 + x] + 128.0).round().clamp(0.0, 255.0);
+                            plane[(by + y) * stride + bx + x] = value as u8;
+                        }
+                    }
+                }
+                plane
+            })
+            .collect();
+
+        let (h_max, v_max) = self.max_sampling();
+        let mut pixels = vec![0u8; self.width * self.height * 4];
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let mut sample = [0f32; 3];
+                for (i, c) in self.components.iter().enumerate() {
+                    let stride = c.blocks_wide * 8;
+                    sample[i] = planes[i][y * c.v / v_max * stride + x * c.h / h_max] as f32;
+                }
+                let rgb = match (self.components.len(), self.adobe_transform) {
+                    (1, _) => [sample[0]; 3],
+                    (_, Some(0)) => sample,
+                    _ => {
+                        let (luma, cb, cr) = (sample[0], sample[1] - 128.0, sample[2] - 128.0);
+                        [
+                            luma + 1.402 * cr,
+                            luma - 0.344136 * cb - 0.714136 * cr,
+                            luma + 1.772 * cb,
+                        ]
+                    }
+                };
+                let at = (y * self.width + x) * 4;
+                for (i, value) in rgb.iter().enumerate() {
+                    pixels[at + i] = value.round().clamp(0.0, 255.0) as u8;
+                }
+                pixels[at + 3] = 255;
+            }
+        }
+        Image {
+            width: self.width as u32,
+            height: self.height as u32,
+            pixels,
+        }
+    }
+}
+
+fn inverse_dct(spectrum: &[f32; 64], basis: &[[f32; 8]; 8]) -> [f32; 64] {
+    let mut rows = [0f32; 64];
+    for y in 0..8 {
+        let frequencies = &spectrum[y * 8..y * 8 + 8];
+        for x in 0..8 {
+            rows[y * 8 + x] = (0..8).map(|u| basis[x][u] * frequencies[u]).sum();
+        }
+    }
+    let mut out = [0f32; 64];
+    for x in 0..8 {
+        for y in 0..8 {
+            out[y * 8 + x] = (0..8).map(|v| basis[y][v] * rows[v * 8 + x]).sum();
+        }
+    }
+    out
+}
+
+fn forward_dct(samples: &[f32; 64], basis: &[[f32; 8]; 8]) -> [f32; 64] {
+    let mut rows = [0f32; 64];
+    for y in 0..8 {
+        for u in 0..8 {
+            rows[y * 8 + u] = (0..8).map(|x| basis[x][u] * samples[y * 8 + x]).sum();
+        }
+    }
+    let mut out = [0f32; 64];
+    for u in 0..8 {
+        for v in 0..8 {
+            out[v * 8 + u] = (0..8).map(|y| basis[y][v] * rows[y * 8 + u]).sum();
+        }
+    }
+    out
+}
+
+pub fn decode(data: &[u8], max_pixels: usize) -> Result<Image, &'static str> {
+    if !data.starts_with(&[0xFF, 0xD8]) {
+        return Err("Malformed JPEG");
+    }
+    let mut decoder = Decoder {
+        progressive: false,
+        width: 0,
+        height: 0,
+        components: Vec::new(),
+        quant: [[1; 64]; 4],
+        dc_tables: Default::default(),
+        ac_tables: Default::default(),
+        restart_interval: 0,
+        adobe_transform: None,
+    };
+    let mut scanned = false;
+    let mut i = 2;
+    while i + 1 < data.len() {
+        if data[i] != 0xFF {
+            return Err("Malformed JPEG");
+        }
+        let marker = data[i + 1];
+        match marker {
+            0xFF => {
+                i += 1;
+                continue;
+            }
+            0xD9 => break,
+            0x01 | 0xD0..=0xD8 => {
+                i += 2;
+                continue;
+            }
+            _ => {}
+        }
+        let length = data
+            .get(i + 2..i + 4)
+            .map(|b| u16::from_be_bytes([b[0], b[1]]) as usize)
+            .ok_or("Malformed JPEG")?;
+        let body = data
+            .get(i + 4..i + 2 + length.max(2))
+            .ok_or("Malformed JPEG")?;
+        i += 2 + length.max(2);
+        match marker {
+            0xDB => decoder.read_quant_tables(body)?,
+            0xC4 => decoder.read_huffman_tables(body)?,
+            0xDD => {
+                decoder.restart_interval =
+                    u16::from_be_bytes([*body.first().unwrap_or(&0), *body.get(1).unwrap_or(&0)])
+                        as usize
+            }
+            0xC0..=0xC2 => {
+                decoder.progressive = marker == 0xC2;
+                decoder.read_frame(body, max_pixels)?;
+            }
+            0xC3 | 0xC5..=0xC7 | 0xC9..=0xCB | 0xCD..=0xCF => {
+                return Err("Unsupported JPEG format");
+            }
+            0xEE if body.starts_with(b"Adobe") && body.len() >= 12 => {
+                decoder.adobe_transform = Some(body[11]);
+            }
+            0xDA => {
+                if decoder.components.is_empty() {
+                    return Err("Malformed JPEG");
+                }
+                i = decoder.read_scan(body, data, i)?;
+                scanned = true;
+            }
+            _ => {}
+        }
+    }
+    if !scanned {
+        return Err("Malformed JPEG");
+    }
+    Ok(decoder.finish())
+}
+
+/// Entropy-coded output, with 0xFF bytes stuffed and Huffman codes from a table's counts.
+#[derive(Default)]
+struct Writer {
+    out: Vec<u8>,
+    buffer: u32,
+    filled: u32,
+}
+
+impl Writer {
+    fn bits(&mut self, value: u32, count: u32) {
+        for i in (0..count).rev() {
+            self.buffer = (self.buffer << 1) | ((value >> i) & 1);
+            self.filled += 1;
+            if self.filled == 8 {
+                self.out.push(self.buffer as u8);
+                if self.buffer == 0xFF {
+                    self.out.push(0);
+                }
+                self.buffer = 0;
+                self.filled = 0;
+            }
+        }
+    }
+
+    /// Pads the last byte with ones.
+    fn finish(mut self) -> Vec<u8> {
+        if self.filled > 0 {
+            self.bits(0x7F, 8 - self.filled);
+        }
+        self.out
+    }
+}
+
+/// (code, length) of each symbol.
+fn codes(counts: &[u8; 16], symbols: &[u8]) -> [(u32, u32); 256] {
+    let mut codes = [(0, 0); 256];
+    let (mut code, mut next) = (0u32, 0);
+    for (length, &count) in counts.iter().enumerate() {
+        for &symbol in &symbols[next..next + count as usize] {
+            codes[symbol as usize] = (code, length as u32 + 1);
+            code += 1;
+        }
+        next += count as usize;
+        code <<= 1;
+    }
+    codes
+}
+
+/// The magnitude category of a coefficient and the bits that follow it.
+fn category(value: i32) -> (u32, u32) {
+    let size = 32 - value.unsigned_abs().leading_zeros();
+    let bits = if value < 0 { value - 1 } else { value } as u32 & ((1 << size) - 1);
+    (size, bits)
+}
+
+fn segment(out: &mut Vec<u8>, marker: u8, body: &[u8]) {
+    out.extend([0xFF, marker]);
+    out.extend((body.len() as u16 + 2).to_be_bytes());
+    out.extend(body);
+}
+
+/// Huffman-codes one quantized block, in zig-zag order.
+fn write_block(
+    writer: &mut Writer,
+    block: &[i32; 64],
+    predictor: &mut i32,
+    dc_codes: &[(u32, u32); 256],
+    ac_codes: &[(u32, u32); 256],
+) {
+    let (size, bits) = category(block[0] - *predictor);
+    *predictor = block[0];
+    let (code, length) = dc_codes[size as usize];
+    writer.bits(code, length);
+    writer.bits(bits, size);
+
+    let mut run = 0;
+    for &value in &block[1..] {
+        if value == 0 {
+            run += 1;
+            continue;
+        }
+        while run > 15 {
+            let (code, length) = ac_codes[0xF0];
+            writer.bits(code, length);
+            run -= 16;
+        }
+        let (size, bits) = category(value);
+        let (code, length) = ac_codes[(run << 4 | size) as usize];
+        writer.bits(code, length);
+        writer.bits(bits, size);
+        run = 0;
+    }
+    if run > 0 {
+        let (code, length) = ac_codes[0x00];
+        writer.bits(code, length);
+    }
+}
+
+/// Encodes as baseline YCbCr with 4:2:0 chroma subsampling; alpha is dropped. `quality`
+/// runs from 1 to 100 as in libjpeg.
+pub fn encode(image: &Image, quality: u8) -> Vec<u8> {
+    let quality = quality.clamp(1, 100) as u32;
+    let scale = if quality < 50 {
+        5000 / quality
+    } else {
+        200 - 2 * quality
+    };
+    let scaled = |table: &[u16; 64]| -> [u16; 64] {
+        let mut out = [0; 64];
+        for (value, &position) in out.iter_mut().zip(&ZIGZAG) {
+            *value = ((table[position] as u32 * scale + 50) / 100).clamp(1, 255) as u16;
+        }
+        out
+    };
+    let quant = [scaled(&LUMA_QUANT), scaled(&CHROMA_QUANT)];
+    let (width, height) = (image.width as usize, image.height as usize);
+
+    let mut out = vec![0xFF, 0xD8];
+    segment(&mut out, 0xE0, b"JFIF\0\x01\x01\0\0\x01\0\x01\0\0");
+    let mut tables = Vec::new();
+    for (index, table) in quant.iter().enumerate() {
+        tables.push(index as u8);
+        tables.extend(table.iter().map(|&q| q as u8));
+    }
+    segment(&mut out, 0xDB, &tables);
+    let mut frame = vec![8];
+    frame.extend((height as u16).to_be_bytes());
+    frame.extend((width as u16).to_be_bytes());
+    frame.extend([3, 1, 0x22, 0, 2, 0x11, 1, 3, 0x11, 1]);
+    segment(&mut out, 0xC0, &frame);
+    let mut tables = Vec::new();
+    for (spec, counts, symbols) in [
+        (0x00, &LUMA_DC.0, &LUMA_DC.1[..]),
+        (0x10, &LUMA_AC.0, &LUMA_AC.1[..]),
+        (0x01, &CHROMA_DC.0, &CHROMA_DC.1[..]),
+        (0x11, &CHROMA_AC.0, &CHROMA_AC.1[..]),
+    ] {
+        tables.push(spec);
+        tables.extend(counts);
+        tables.extend(symbols);
+    }
+    segment(&mut out, 0xC4, &tables);
+    segment(&mut out, 0xDA, &[3, 1, 0x00, 2, 0x11, 3, 0x11, 0, 63, 0]);
+
+    let dc_codes = [
+        codes(&LUMA_DC.0, &LUMA_DC.1),
+        codes(&CHROMA_DC.0, &CHROMA_DC.1),
+    ];
+    let ac_codes = [
+        codes(&LUMA_AC.0, &LUMA_AC.1),
+        codes(&CHROMA_AC.0, &CHROMA_AC.1),
+    ];
+    let basis = dct_basis();
+    let quantize = |samples: &[f32; 64], table: &[u16; 64]| -> [i32; 64] {
+        let spectrum = forward_dct(samples, &basis);
+        let mut block = [0i32; 64];
+        for (k, value) in block.iter_mut().enumerate() {
+            *value = (spectrum[ZIGZAG[k]] / table[k] as f32).round() as i32;
+        }
+        block
+    };
+
+    let mut writer = Writer::default();
+    let mut predictors = [0i32; 3];
+    for mcu_y in (0..height).step_by(16) {
+        for mcu_x in (0..width).step_by(16) {
+            // 16×16 of each component, edge MCUs repeating the last row and column
+            let mut planes = [[0f32; 256]; 3];
+            for y in 0..16 {
+                for x in 0..16 {
+                    let (sx, sy) = ((mcu_x + x).min(width - 1), (mcu_y + y).min(height - 1));
+                    let at = (sy * width + sx) * 4;
+                    let [r, g, b] = [0, 1, 2].map(|i| image.pixels[at + i] as f32);
+                    planes[0][y * 16 + x] = 0.299 * r + 0.587 * g + 0.114 * b - 128.0;
+                    planes[1][y * 16 + x] = -0.168736 * r - 0.331264 * g + 0.5 * b;
+                    planes[2][y * 16 + x] = 0.5 * r - 0.418688 * g - 0.081312 * b;
+                }
+            }
+
+            for (top, left) in [(0, 0), (0, 8), (8, 0), (8, 8)] {
+                let mut samples = [0f32; 64];
+                for (i, sample) in samples.iter_mut().enumerate() {
+                    *sample = planes[0][(top + i / 8) * 16 + left + i % 8];
+                }
+                let block = quantize(&samples, &quant[0]);
+                write_block(
+                    &mut writer,
+                    &block,
+                    &mut predictors[0],
+                    &dc_codes[0],
+                    &ac_codes[0],
+                );
+            }
+            for component in 1..3 {
+                let plane = &planes[component];
+                let mut samples = [0f32; 64];
+                for (i, sample) in samples.iter_mut().enumerate() {
+                    let at = (i / 8) * 32 + (i % 8) * 2;
+                    *sample = (plane[at] + plane[at + 1] + plane[at + 16] + plane[at + 17]) / 4.0;
+                }
+                let block = quantize(&samples, &quant[1]);
+                let predictor = &mut predictors[component];
+                write_block(&mut writer, &block, predictor, &dc_codes[1], &ac_codes[1]);
+            }
+        }
+    }
+    out.extend(writer.finish());
+    out.extend([0xFF, 0xD9]);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gradient(width: u32, height: u32) -> Image {
+        let mut pixels = Vec::new();
+        for y in 0..height {
+            for x in 0..width {
+                pixels.extend([(x * 255 / width) as u8, (y * 255 / height) as u8, 90, 255]);
+            }
+        }
+        Image {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    /// The mean and the largest difference between two same-sized images' samples.
+    fn errors(a: &Image, b: &Image) -> (f32, u8) {
+        let differences: Vec<u8> = a
+            .pixels
+            .iter()
+            .zip(&b.pixels)
+            .map(|(x, y)| x.abs_diff(*y))
+            .collect();
+        let mean = differences.iter().map(|&d| d as f32).sum::<f32>() / differences.len() as f32;
+        (mean, *differences.iter().max().unwrap())
+    }
+
+    #[test]
+    fn round_trips_within_the_quantization_error() {
+        let image = gradient(150, 90);
+        let encoded = encode(&image, 90);
+        let decoded = decode(&encoded, 150 * 90).unwrap();
+        assert_eq!((decoded.width, decoded.height), (150, 90));
+        let (mean, largest) = errors(&image, &decoded);
+        assert!(mean < 1.0 && largest <= 6, "{mean} {largest}");
+        assert!(encode(&image, 30).len() < encoded.len());
+    }
+
+    #[test]
+    fn refuses_bad_or_oversized_images() {
+        let encoded = encode(&gradient(20, 20), 75);
+        assert!(decode(&encoded, 399).is_err());
+        assert!(decode(&encoded[..100], 400).is_err());
+        assert!(decode(b"\xFF\xD8\xFF\xD9", 400).is_err());
+        let mut lossless = encoded.clone();
+        let frame = lossless.windows(2).position(|w| w == [0xFF, 0xC0]).unwrap();
+        lossless[frame + 1] = 0xC3;
+        assert!(decode(&lossless, 400).is_err());
+    }
+}
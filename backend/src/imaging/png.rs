@@ -0,0 +1,506 @@
+//! PNG decoding for every standard colour type, bit depth and interlacing, and encoding
+//! of 8-bit RGB or RGBA.
+
+use crate::imaging::Image;
+use crate::imaging::deflate::{zlib_compress, zlib_decompress};
+
+pub const SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+/// Column and row of the first pixel of each Adam7 pass, then the steps between pixels.
+const ADAM7: [(usize, usize, usize, usize); 7] = [
+    (0, 0, 8, 8),
+    (4, 0, 8, 8),
+    (0, 4, 4, 8),
+    (2, 0, 4, 4),
+    (0, 2, 2, 4),
+    (1, 0, 2, 2),
+    (0, 1, 1, 2),
+];
+
+pub fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+struct Header {
+    width: usize,
+    height: usize,
+    depth: u8,
+    color: u8,
+    channels: usize,
+}
+
+impl Header {
+    fn bits_per_pixel(&self) -> usize {
+        self.channels * self.depth as usize
+    }
+
+    fn row_bytes(&self, width: usize) -> usize {
+        (width * self.bits_per_pixel()).div_ceil(8)
+    }
+}
+
+fn paeth(a: u8, b: u8, c: u8) -> u8 {
+    let p = a as i16 + b as i16 - c as i16;
+    let (pa, pb, pc) = (
+        (p - a as i16).abs(),
+        (p - b as i16).abs(),
+        (p - c as i16).abs(),
+    );
+    if pa <= pb && pa <= pc {
+        a
+    } else if pb <= pc {
+        b
+    } else {
+        c
+    }
+}
+
+/// Reverses the row filters of one (sub-)image in place, dropping the filter bytes.
+fn unfilter(
+    data: &[u8],
+    row_bytes: usize,
+    rows: usize,
+    bpp: usize,
+) -> Result<Vec<u8>, &'static str> {
+    let mut out = vec![0u8; row_bytes * rows];
+    for y in 0..rows {
+        let filter = data[y * (row_bytes + 1)];
+        let line = &data[y * (row_bytes + 1) + 1..(y + 1) * (row_bytes + 1)];
+        let (done, rest) = out.split_at_mut(y * row_bytes);
+        let previous = if y == 0 {
+            None
+        } else {
+            Some(&done[(y - 1) * row_bytes..])
+        };
+        let current = &mut rest[..row_bytes];
+        for x in 0..row_bytes {
+            let a = if x >= bpp { current[x - bpp] } else { 0 };
+            let b = previous.map_or(0, |row| row[x]);
+            let c = if x >= bpp {
+                previous.map_or(0, |row| row[x - bpp])
+            } else {
+                0
+            };
+            current[x] = line[x].wrapping_add(match filter {
+                0 => 0,
+                1 => a,
+                2 => b,
+                3 => ((a as u16 + b as u16) / 2) as u8,
+                4 => paeth(a, b, c),
+                _ => return Err("Bad PNG filter"),
+            });
+        }
+    }
+    Ok(out)
+}
+
+/// Expands a row of samples to RGBA, honouring the palette and the transparent colour.
+fn to_rgba(
+    header: &Header,
+    row: &[u8],
+    width: usize,
+    palette: &[[u8; 4]],
+    transparent: Option<[u16; 3]>,
+    out: &mut [u8],
+) {
+    let depth = header.depth as usize;
+    let sample = |index: usize| -> u16 {
+        match depth {
+            16 => u16::from_be_bytes([row[index * 2], row[index * 2 + 1]]),
+            8 => row[index] as u16,
+            _ => {
+                let bit = index * depth;
+                let byte = row[bit / 8];
+                ((byte >> (8 - depth - bit % 8)) & ((1 << depth) - 1) as u8) as u16
+            }
+        }
+    };
+    let scale = |value: u16| -> u8 {
+        match depth {
+            16 => (value >> 8) as u8,
+            _ => (value as u32 * 255 / ((1 << depth) - 1)) as u8,
+        }
+    };
+
+    for x in 0..width {
+        let pixel = &mut out[x * 4..x * 4 + 4];
+        let base = x * header.channels;
+        match header.color {
+            0 => {
+                let gray = sample(base);
+                let alpha = if transparent == Some([gray, gray, gray]) {
+                    0
+                } else {
+                    255
+                };
+                pixel.copy_from_slice(&[scale(gray), scale(gray), scale(gray), alpha]);
+            }
+            2 => {
+                let rgb = [sample(base), sample(base + 1), sample(base + 2)];
+                let alpha = if transparent == Some(rgb) { 0 } else { 255 };
+                pixel.copy_from_slice(&[scale(rgb[0]), scale(rgb[1]), scale(rgb[2]), alpha]);
+            }
+            3 => {
+                let entry = palette.get(sample(base) as usize).copied();
+                pixel.copy_from_slice(&entry.unwrap_or([0, 0, 0, 255]));
+            }
+            4 => {
+                let (gray, alpha) = (scale(sample(base)), scale(sample(base + 1)));
+                pixel.copy_from_slice(&[gray, gray, gray, alpha]);
+            }
+            _ => {
+                for (channel, value) in pixel.iter_mut().enumerate() {
+                    *value = scale(sample(base + channel));
+                }
+            }
+        }
+    }
+}
+
+pub fn decode(data: &[u8], max_pixels: usize) -> Result<Image, &'static str> {
+    if !data.starts_with(SIGNATURE) {
+        return Err("Malformed PNG");
+    }
+    let mut header = None;
+    let mut interlaced = false;
+    let mut palette: Vec<[u8; 4]> = Vec::new();
+    let mut transparency: &[u8] = &[];
+    let mut compressed = Vec::new();
+
+    let mut i = SIGNATURE.len();
+    loop {
+        let length = data
+            .get(i..i + 4)
+            .map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]) as usize)
+            .ok_or("Malformed PNG")?;
+        let kind = data.get(i + 4..i + 8).ok_or("Malformed PNG")?;
+        let body = data.get(i + 8..i + 8 + length).ok_or("Malformed PNG")?;
+        i += 12 + length;
+        match kind {
+            b"IHDR" => {
+                if body.len() != 13 {
+                    return Err("Malformed PNG");
+                }
+                let width = u32::from_be_bytes([body[0], body[1], body[2], body[3]]) as usize;
+                let height = u32::from_be_bytes([body[4], body[5], body[6], body[7]]) as usize;
+                let (depth, color) = (body[8], body[9]);
+                let channels = match (color, depth) {
+                    (0, 1 | 2 | 4 | 8 | 16) | (3, 1 | 2 | 4 | 8) => 1,
+                    (4, 8 | 16) => 2,
+                    (2, 8 | 16) => 3,
+                    (6, 8 | 16) => 4,
+                    _ => return Err("Unsupported PNG format"),
+                };
+                if width == 0 || height == 0 {
+                    return Err("The image is empty");
+                }
+                if width.saturating_mul(height) > max_pixels {
+                    return Err("The image is too large to process");
+                }
+                if body[10] != 0 || body[11] != 0 || body[12] > 1 {
+                    return Err("Unsupported PNG format");
+                }
+                interlaced = body[12] == 1;
+                header = Some(Header {
+                    width,
+                    height,
+                    depth,
+                    color,
+                    channels,
+                });
+            }
+            b"PLTE" => {
+                palette = body
+                    .chunks_exact(3)
+                    .map(|c| [c[0], c[1], c[2], 255])
+                    .collect();
+            }
+            b"tRNS" => transparency = body,
+            b"IDAT" => compressed.extend_from_slice(body),
+            b"IEND" => break,
+            _ => {}
+        }
+    }
+
+    let header = header.ok_or("Malformed PNG")?;
+    if header.color == 3 {
+        for (entry, &alpha) in palette.iter_mut().zip(transparency) {
+            entry[3] = alpha;
+        }
+    }
+    let word = |k: usize| {
+        transparency
+            .get(k..k + 2)
+            .map(|b| u16::from_be_bytes([b[0], b[1]]))
+    };
+    let transparent = match header.color {
+        0 => word(0).map(|gray| [gray; 3]),
+        2 => match (word(0), word(2), word(4)) {
+            (Some(r), Some(g), Some(b)) => Some([r, g, b]),
+            _ => None,
+        },
+        _ => None,
+    };
+
+    // Each pass is its own little image: (first column, first row, column step, row step)
+    let passes: Vec<(usize, usize, usize, usize)> = if interlaced {
+        ADAM7.to_vec()
+    } else {
+        vec![(0, 0, 1, 1)]
+    };
+    let pass_size = |&(x0, y0, dx, dy): &(usize, usize, usize, usize)| {
+        (
+            (header.width + dx - 1 - x0) / dx,
+            (header.height + dy - 1 - y0) / dy,
+        )
+    };
+    let expected: usize = passes
+        .iter()
+        .map(pass_size)
+        .filter(|&(w, h)| w > 0 && h > 0)
+        .map(|(w, h)| (header.row_bytes(w) + 1) * h)
+        .sum();
+    let raw = zlib_decompress(&compressed, expected)?;
+    if raw.len() != expected {
+        return Err("Truncated PNG");
+    }
+
+    let bpp = header.bits_per_pixel().div_ceil(8);
+    let mut pixels = vec![0u8; header.width * header.height * 4];
+    let mut row = vec![0u8; header.width * 4];
+    let mut offset = 0;
+    for pass in &passes {
+        let (width, height) = pass_size(pass);
+        if width == 0 || height == 0 {
+            continue;
+        }
+        let row_bytes = header.row_bytes(width);
+        let size = (row_bytes + 1) * height;
+        let samples = unfilter(&raw[offset..offset + size], row_bytes, height, bpp)?;
+        offset += size;
+
+        let &(x0, y0, dx, dy) = pass;
+        for y in 0..height {
+            let line = &samples[y * row_bytes..(y + 1) * row_bytes];
+            to_rgba(&header, line, width, &palette, transparent, &mut row);
+            let target = (y0 + y * dy) * header.width;
+            for x in 0..width {
+                let at = (target + x0 + x * dx) * 4;
+                pixels[at..at + 4].copy_from_slice(&row[x * 4..x * 4 + 4]);
+            }
+        }
+    }
+
+    Ok(Image {
+        width: header.width as u32,
+        height: header.height as u32,
+        pixels,
+    })
+}
+
+fn chunk(out: &mut Vec<u8>, kind: &[u8; 4], body: &[u8]) {
+    out.extend((body.len() as u32).to_be_bytes());
+    let start = out.len();
+    out.extend(kind);
+    out.extend(body);
+    let crc = crc32(&out[start..]);
+    out.extend(crc.to_be_bytes());
+}
+
+/// Encodes as RGBA, or as RGB when every pixel is opaque. Each row gets whichever filter
+/// leaves the smallest residues, the usual heuristic.
+pub fn encode(image: &Image) -> Vec<u8> {
+    let opaque = image.pixels.chunks_exact(4).all(|p| p[3] == 255);
+    let channels = if opaque { 3 } else { 4 };
+    let width = image.width as usize;
+    let row_bytes = width * channels;
+
+    let mut rows = vec![0u8; row_bytes * image.height as usize];
+    for (pixel, out) in image
+        .pixels
+        .chunks_exact(4)
+        .zip(rows.chunks_exact_mut(channels))
+    {
+        out.copy_from_slice(&pixel[..channels]);
+    }
+
+    let mut filtered = Vec::with_capacity((row_bytes + 1) * image.height as usize);
+    let mut candidate = vec![0u8; row_bytes];
+    let mut best = vec![0u8; row_bytes];
+    for y in 0..image.height as usize {
+        let current = &rows[y * row_bytes..(y + 1) * row_bytes];
+        let previous = (y > 0).then(|| &rows[(y - 1) * row_bytes..y * row_bytes]);
+        let mut best_filter = (0u8, u64::MAX);
+        for filter in 0..5u8 {
+            for x in 0..row_bytes {
+                let a = if x >= channels {
+                    current[x - channels]
+                } else {
+                    0
+                };
+                let b = previous.map_or(0, |row| row[x]);
+                let c = if x >= channels {
+                    previous.map_or(0, |row| row[x - channels])
+                } else {
+                    0
+                };
+                let predicted = match filter {
+                    0 => 0,
+                    1 => a,
+                    2 => b,
+                    3 => ((a as u16 + b as u16) / 2) as u8,
+                    _ => paeth(a, b, c),
+                };
+                candidate[x] = current[x].wrapping_sub(predicted);
+            }
+            let cost = candidate
+                .iter()
+                .map(|&v| (v as i8).unsigned_abs() as u64)
+                .sum();
+            if cost < best_filter.1 {
+                best_filter = (filter, cost);
+                best.copy_from_slice(&candidate);
+            }
+        }
+        filtered.push(best_filter.0);
+        filtered.extend_from_slice(&best);
+    }
+
+    let mut header = Vec::with_capacity(13);
+    header.extend(image.width.to_be_bytes());
+    header.extend(image.height.to_be_bytes());
+    header.extend([8, if opaque { 2 } else { 6 }, 0, 0, 0]);
+
+    let mut out = SIGNATURE.to_vec();
+    chunk(&mut out, b"IHDR", &header);
+    chunk(&mut out, b"IDAT", &zlib_compress(&filtered));
+    chunk(&mut out, b"IEND", &[]);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A PNG holding `raw` (filter bytes included) under the given header fields.
+    fn png(
+        width: u32,
+        height: u32,
+        depth: u8,
+        color: u8,
+        interlace: u8,
+        extra: &[(&[u8; 4], &[u8])],
+        raw: &[u8],
+    ) -> Vec<u8> {
+        let mut header = Vec::new();
+        header.extend(width.to_be_bytes());
+        header.extend(height.to_be_bytes());
+        header.extend([depth, color, 0, 0, interlace]);
+        let mut out = SIGNATURE.to_vec();
+        chunk(&mut out, b"IHDR", &header);
+        for (kind, body) in extra {
+            chunk(&mut out, kind, body);
+        }
+        chunk(&mut out, b"IDAT", &zlib_compress(raw));
+        chunk(&mut out, b"IEND", &[]);
+        out
+    }
+
+    #[test]
+    fn checksums_match_the_spec() {
+        assert_eq!(crc32(b"IEND"), 0xAE42_6082);
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+    }
+
+    #[test]
+    fn round_trips_rgb_and_rgba() {
+        let mut image = Image {
+            width: 5,
+            height: 3,
+            pixels: (0..60).map(|i| (i * 37 % 256) as u8).collect(),
+        };
+        image.pixels.chunks_exact_mut(4).for_each(|p| p[3] = 255);
+        let opaque = encode(&image);
+        assert_eq!(opaque[25], 2, "Stored as RGB");
+        assert_eq!(decode(&opaque, 100).unwrap(), image);
+
+        image.pixels[7] = 10;
+        let translucent = encode(&image);
+        assert_eq!(translucent[25], 6, "Stored as RGBA");
+        assert_eq!(decode(&translucent, 100).unwrap(), image);
+    }
+
+    #[test]
+    fn expands_palettes_gray_and_sixteen_bit_samples() {
+        // 2-bit palette, the second entry half transparent; the second row uses the up filter
+        let palette = png(
+            3,
+            2,
+            2,
+            3,
+            0,
+            &[
+                (b"PLTE", &[255, 0, 0, 0, 255, 0, 0, 0, 255]),
+                (b"tRNS", &[255, 128]),
+            ],
+            &[0, 0b00_01_10_00, 2, 0b00_11_11_00],
+        );
+        let image = decode(&palette, 100).unwrap();
+        assert_eq!(
+            image.pixels,
+            vec![
+                255, 0, 0, 255, 0, 255, 0, 128, 0, 0, 255, 255, //
+                0, 255, 0, 128, 0, 255, 0, 128, 0, 255, 0, 128,
+            ]
+        );
+
+        let gray = png(2, 1, 4, 0, 0, &[(b"tRNS", &[0, 15])], &[0, 0xF7]);
+        assert_eq!(
+            decode(&gray, 100).unwrap().pixels,
+            vec![255, 255, 255, 0, 119, 119, 119, 255]
+        );
+
+        let deep = png(1, 1, 16, 4, 0, &[], &[0, 0x12, 0x34, 0x80, 0x00]);
+        assert_eq!(
+            decode(&deep, 100).unwrap().pixels,
+            vec![0x12, 0x12, 0x12, 0x80]
+        );
+    }
+
+    #[test]
+    fn places_interlaced_passes() {
+        // A 3×3 gray image; passes 2 and 3 are empty at this size
+        let raw = [
+            0, 1, // pass 1: (0, 0)
+            0, 2, // pass 4: (2, 0)
+            0, 3, 4, // pass 5: row 2, columns 0 and 2
+            0, 5, 0, 6, // pass 6: column 1 of rows 0 and 2
+            0, 7, 8, 9, // pass 7: row 1
+        ];
+        let image = decode(&png(3, 3, 8, 0, 1, &[], &raw), 100).unwrap();
+        let grays: Vec<u8> = image.pixels.chunks_exact(4).map(|p| p[0]).collect();
+        assert_eq!(grays, vec![1, 5, 2, 7, 8, 9, 3, 6, 4]);
+    }
+
+    #[test]
+    fn refuses_bad_or_oversized_images() {
+        let image = png(2, 2, 8, 0, 0, &[], &[0, 1, 2, 0, 3, 4]);
+        assert!(decode(&image, 4).is_ok());
+        assert!(decode(&image, 3).is_err());
+        assert!(decode(&png(2, 2, 8, 0, 0, &[], &[0, 1, 2]), 4).is_err());
+        assert!(decode(&png(2, 2, 8, 0, 0, &[], &[5, 1, 2, 0, 3, 4]), 4).is_err());
+        assert!(decode(&png(2, 2, 3, 0, 0, &[], &[0, 1, 2, 0, 3, 4]), 4).is_err());
+        assert!(decode(&image[..40], 4).is_err());
+    }
+}
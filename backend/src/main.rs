@@ -1,12 +1,24 @@
 pub mod api;
+pub mod config;
 pub mod db;
-pub mod engine;
+pub mod imaging;
 pub mod matchmaking;
+pub mod moderation;
+pub mod notify;
+pub mod ranking;
+pub mod scheduler;
+pub mod stats;
+pub mod tournament;
+
+pub use carioca_engine as engine;
 
 #[tokio::main]
 async fn main() {
     println!("Starting Carioca Backend MVP...");
-    
-    // Use an in-memory SQLite DB for the initial phase/testing
-    api::server::start_server("sqlite::memory:").await;
+
+    let config = config::Config::from_env();
+    let (addr, server) = api::server::start_server(&config).await;
+    println!("Server running on {}", addr);
+
+    server.await.expect("Server task panicked");
 }
@@ -0,0 +1,126 @@
+use sqlx::SqlitePool;
+
+use crate::db::models::BotLevel;
+use crate::db::repo;
+use crate::engine::bot::BotDifficulty;
+
+use BotDifficulty::{Easy, Hard, Medium};
+
+/// Practice opponents from gentlest to toughest; a player's adaptive level indexes this.
+pub const LADDER: [[BotDifficulty; 3]; 7] = [
+    [Easy, Easy, Easy],
+    [Easy, Easy, Medium],
+    [Easy, Medium, Medium],
+    [Medium, Medium, Medium],
+    [Medium, Medium, Hard],
+    [Medium, Hard, Hard],
+    [Hard, Hard, Hard],
+];
+/// Where new players start: medium opponents and one easy one.
+pub const STARTING_LEVEL: i64 = 2;
+/// Consecutive wins (or losses) that move a player one rung up (or down).
+pub const STREAK_TO_MOVE: i64 = 2;
+
+impl Default for BotLevel {
+    fn default() -> Self {
+        Self {
+            level: STARTING_LEVEL,
+            streak: 0,
+        }
+    }
+}
+
+impl BotLevel {
+    /// The opponents this level faces in practice.
+    pub fn bots(&self) -> Vec<BotDifficulty> {
+        LADDER[self.level.clamp(0, LADDER.len() as i64 - 1) as usize].to_vec()
+    }
+
+    /// Counts a practice result. `streak` runs positive on wins and negative on losses;
+    /// reaching [`STREAK_TO_MOVE`] either way changes the level and starts the count over.
+    pub fn after_game(self, won: bool) -> Self {
+        let streak = match (won, self.streak) {
+            (true, s) if s > 0 => s + 1,
+            (true, _) => 1,
+            (false, s) if s < 0 => s - 1,
+            (false, _) => -1,
+        };
+        let top = LADDER.len() as i64 - 1;
+        if streak >= STREAK_TO_MOVE && self.level < top {
+            Self {
+                level: self.level + 1,
+                streak: 0,
+            }
+        } else if streak <= -STREAK_TO_MOVE && self.level > 0 {
+            Self {
+                level: self.level - 1,
+                streak: 0,
+            }
+        } else {
+            Self {
+                level: self.level,
+                streak,
+            }
+        }
+    }
+}
+
+/// The player's adaptive level; players who never practised start at [`STARTING_LEVEL`].
+pub async fn load(pool: &SqlitePool, user_id: &str) -> BotLevel {
+    repo::get_bot_level(pool, user_id).await.unwrap_or_default()
+}
+
+/// Nudges the player's level after a finished practice game. A game counts as won when
+/// nobody at the table finished with fewer points.
+pub async fn record(
+    pool: &SqlitePool,
+    user_id: &str,
+    final_totals: &[(String, u32)],
+) -> Result<BotLevel, sqlx::Error> {
+    let Some(own) = final_totals.iter().find(|(id, _)| id == user_id) else {
+        return Ok(load(pool, user_id).await);
+    };
+    let won = final_totals.iter().all(|(_, total)| *total >= own.1);
+    let level = load(pool, user_id).await.after_game(won);
+    repo::upsert_bot_level(pool, user_id, &level).await?;
+    Ok(level)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn streaks_move_the_level_one_rung() {
+        let start = BotLevel::default();
+        let once = start.after_game(true);
+        assert_eq!((once.level, once.streak), (STARTING_LEVEL, 1));
+        let twice = once.after_game(true);
+        assert_eq!((twice.level, twice.streak), (STARTING_LEVEL + 1, 0));
+
+        // A loss breaks a winning streak instead of adding to it
+        let mixed = start.after_game(true).after_game(false);
+        assert_eq!((mixed.level, mixed.streak), (STARTING_LEVEL, -1));
+        let down = mixed.after_game(false);
+        assert_eq!((down.level, down.streak), (STARTING_LEVEL - 1, 0));
+    }
+
+    #[test]
+    fn the_ladder_has_ends() {
+        let bottom = BotLevel {
+            level: 0,
+            streak: -1,
+        }
+        .after_game(false);
+        assert_eq!(bottom.level, 0);
+        assert_eq!(bottom.bots(), vec![Easy, Easy, Easy]);
+
+        let top = BotLevel {
+            level: 6,
+            streak: 1,
+        }
+        .after_game(true);
+        assert_eq!(top.level, 6);
+        assert_eq!(top.bots(), vec![Hard, Hard, Hard]);
+    }
+}
@@ -0,0 +1,115 @@
+use serde::{Deserialize, Serialize};
+
+use crate::engine::bot::BotDifficulty;
+
+/// Names bots play under. A room deals them out so no two bots at a table share one.
+const NAMES: &[&str] = &[
+    "Pancho",
+    "Rosita",
+    "Don Lucho",
+    "Tía Chela",
+    "Manolo",
+    "Javiera",
+    "Nacho",
+    "Coni",
+    "Tío Memo",
+    "Pili",
+    "Beto",
+    "Cata",
+    "Chago",
+    "Marisol",
+    "Pelao",
+    "Fran",
+];
+
+/// Circle colours of the generated avatars, picked by name.
+const AVATAR_COLORS: &[&str] = &[
+    "#c0392b", "#2471a3", "#1e8449", "#b9770e", "#7d3c98", "#117a65",
+];
+
+/// How a bot presents at the table, in place of its raw id.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BotIdentity {
+    pub display_name: String,
+    pub avatar_url: String,
+    /// Shown as a badge next to the name
+    pub difficulty: BotDifficulty,
+}
+
+/// Reads a bot's strength off its id (`bot_hard`, `bot_easy_2`, ...). Unknown bots play easy.
+pub fn difficulty_of(bot_id: &str) -> BotDifficulty {
+    if bot_id.contains("hard") {
+        BotDifficulty::Hard
+    } else if bot_id.contains("medium") {
+        BotDifficulty::Medium
+    } else {
+        BotDifficulty::Easy
+    }
+}
+
+/// FNV-1a, so a room's bots keep their names across restarts and builds.
+fn fnv1a(text: &str) -> u64 {
+    text.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// The identity of the `ordinal`-th bot (0-based, in seat order) at `room_id`.
+pub fn identity(room_id: &str, bot_id: &str, ordinal: usize) -> BotIdentity {
+    // Consecutive ordinals from a room-chosen start never collide within a table
+    let name_index = (fnv1a(room_id) as usize + ordinal) % NAMES.len();
+    BotIdentity {
+        display_name: NAMES[name_index].to_string(),
+        avatar_url: format!("/api/avatars/bots/{}", name_index),
+        difficulty: difficulty_of(bot_id),
+    }
+}
+
+/// A generated avatar for the bot name at `index`: its initials on a coloured circle.
+pub fn avatar_svg(index: usize) -> Option<String> {
+    let name = NAMES.get(index)?;
+    let initials: String = name
+        .split_whitespace()
+        .filter_map(|word| word.chars().next())
+        .take(2)
+        .collect();
+    let color = AVATAR_COLORS[index % AVATAR_COLORS.len()];
+    Some(format!(
+        concat!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 64 64">"#,
+            r#"<circle cx="32" cy="32" r="32" fill="{}"/>"#,
+            r##"<text x="32" y="41" font-family="sans-serif" font-size="24" fill="#fff" "##,
+            r#"text-anchor="middle">{}</text></svg>"#
+        ),
+        color, initials
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bots_at_a_table_get_distinct_stable_names() {
+        let bots = ["bot_easy", "bot_medium", "bot_hard"];
+        let names: Vec<String> = bots
+            .iter()
+            .enumerate()
+            .map(|(i, id)| identity("room-1", id, i).display_name)
+            .collect();
+        assert_eq!(names.len(), 3);
+        assert!(names[0] != names[1] && names[1] != names[2] && names[0] != names[2]);
+        assert_eq!(identity("room-1", "bot_easy", 0).display_name, names[0]);
+
+        let hard = identity("room-1", "bot_hard_2", 2);
+        assert_eq!(hard.difficulty, BotDifficulty::Hard);
+        assert!(hard.avatar_url.starts_with("/api/avatars/bots/"));
+    }
+
+    #[test]
+    fn avatars_show_initials() {
+        let svg = avatar_svg(2).unwrap();
+        assert!(svg.contains(">DL</text>"), "Don Lucho: {}", svg);
+        assert!(avatar_svg(NAMES.len()).is_none());
+    }
+}
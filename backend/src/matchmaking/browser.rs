@@ -0,0 +1,243 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::sync::broadcast;
+
+/// Updates a live browser can fall behind by before it misses some.
+const UPDATE_BUFFER: usize = 256;
+
+/// A public table as the game browser shows it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RoomListing {
+    pub room_id: String,
+    /// Seats in turn order, bots included
+    pub players: Vec<String>,
+    pub round_index: usize,
+    pub round_name: String,
+    /// The queue whose preset the table plays by, see `GET /api/queues`
+    pub queue: String,
+    pub spectators: usize,
+    /// Whether players at the table may share a spectate link
+    pub spectators_allowed: bool,
+    pub is_game_over: bool,
+    /// When the table first showed up in the browser
+    pub listed_at_ms: u64,
+}
+
+/// A change to the browser, for live clients.
+#[derive(Debug, Clone)]
+pub enum BrowserUpdate {
+    /// A table appeared or something shown about it changed
+    Listed(RoomListing),
+    /// The room closed
+    Unlisted(String),
+}
+
+/// Narrows and orders the listing; every filter left out lets everything through.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct BrowserQuery {
+    /// 0-based round being played
+    pub round: Option<usize>,
+    /// Seats at the table
+    pub players: Option<usize>,
+    pub spectators_allowed: Option<bool>,
+    /// Queue id, e.g. `rapida`
+    pub queue: Option<String>,
+    /// Comma-separated user ids; only tables where at least one of them is seated
+    pub friends: Option<String>,
+    #[serde(default)]
+    pub sort: BrowserSort,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BrowserSort {
+    /// Most recently listed first
+    #[default]
+    Newest,
+    /// Furthest into the game first
+    Round,
+    /// Most seats first
+    Players,
+    /// Most watched first
+    Spectators,
+}
+
+impl BrowserQuery {
+    pub fn matches(&self, listing: &RoomListing) -> bool {
+        let friends: Vec<&str> = self
+            .friends
+            .as_deref()
+            .map(|ids| {
+                ids.split(',')
+                    .map(str::trim)
+                    .filter(|id| !id.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default();
+        self.round.is_none_or(|round| listing.round_index == round)
+            && self.players.is_none_or(|n| listing.players.len() == n)
+            && self
+                .spectators_allowed
+                .is_none_or(|allowed| listing.spectators_allowed == allowed)
+            && self
+                .queue
+                .as_deref()
+                .is_none_or(|queue| listing.queue == queue)
+            && (friends.is_empty()
+                || listing
+                    .players
+                    .iter()
+                    .any(|p| friends.contains(&p.as_str())))
+    }
+
+    /// The listings this query lets through, in its order. Ties go to the newest table.
+    pub fn apply(&self, listings: impl IntoIterator<Item = RoomListing>) -> Vec<RoomListing> {
+        let mut rooms: Vec<RoomListing> =
+            listings.into_iter().filter(|l| self.matches(l)).collect();
+        rooms.sort_by(|a, b| {
+            let key = match self.sort {
+                BrowserSort::Newest => std::cmp::Ordering::Equal,
+                BrowserSort::Round => b.round_index.cmp(&a.round_index),
+                BrowserSort::Players => b.players.len().cmp(&a.players.len()),
+                BrowserSort::Spectators => b.spectators.cmp(&a.spectators),
+            };
+            key.then(b.listed_at_ms.cmp(&a.listed_at_ms))
+                .then_with(|| a.room_id.cmp(&b.room_id))
+        });
+        rooms
+    }
+}
+
+/// The public tables running on this server, kept up to date by the rooms themselves.
+#[derive(Clone)]
+pub struct RoomDirectory {
+    listings: Arc<Mutex<HashMap<String, RoomListing>>>,
+    updates: broadcast::Sender<BrowserUpdate>,
+}
+
+impl Default for RoomDirectory {
+    fn default() -> Self {
+        Self {
+            listings: Arc::default(),
+            updates: broadcast::channel(UPDATE_BUFFER).0,
+        }
+    }
+}
+
+impl RoomDirectory {
+    /// Lists a room or refreshes its listing, telling live browsers if anything changed.
+    /// `listed_at_ms` is set here the first time and kept afterwards.
+    pub fn publish(&self, mut listing: RoomListing) {
+        let mut listings = self.listings.lock().unwrap();
+        listing.listed_at_ms = match listings.get(&listing.room_id) {
+            Some(current) => current.listed_at_ms,
+            None => epoch_ms(),
+        };
+        if listings.get(&listing.room_id) == Some(&listing) {
+            return;
+        }
+        listings.insert(listing.room_id.clone(), listing.clone());
+        let _ = self.updates.send(BrowserUpdate::Listed(listing));
+    }
+
+    pub fn remove(&self, room_id: &str) {
+        if self.listings.lock().unwrap().remove(room_id).is_some() {
+            let _ = self
+                .updates
+                .send(BrowserUpdate::Unlisted(room_id.to_string()));
+        }
+    }
+
+    pub fn list(&self, query: &BrowserQuery) -> Vec<RoomListing> {
+        let listings = self.listings.lock().unwrap();
+        query.apply(listings.values().cloned())
+    }
+
+    /// Changes from now on; pair with [`Self::list`] for where things stand.
+    pub fn subscribe(&self) -> broadcast::Receiver<BrowserUpdate> {
+        self.updates.subscribe()
+    }
+}
+
+fn epoch_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn listing(room_id: &str, players: &[&str], round_index: usize) -> RoomListing {
+        RoomListing {
+            room_id: room_id.to_string(),
+            players: players.iter().map(|p| p.to_string()).collect(),
+            round_index,
+            round_name: String::new(),
+            queue: "clasica".to_string(),
+            spectators: 0,
+            spectators_allowed: true,
+            is_game_over: false,
+            listed_at_ms: 0,
+        }
+    }
+
+    fn ids(rooms: &[RoomListing]) -> Vec<&str> {
+        rooms.iter().map(|r| r.room_id.as_str()).collect()
+    }
+
+    #[test]
+    fn queries_filter_and_sort_the_listing() {
+        let mut watched = listing("c", &["ana", "bot_1", "bot_2"], 1);
+        watched.spectators = 3;
+        let mut rapid = listing("b", &["ana", "ben"], 5);
+        rapid.queue = "rapida".to_string();
+        let rooms = vec![listing("a", &["cruz", "dani"], 0), rapid, watched];
+
+        let query = BrowserQuery {
+            friends: Some("ana, zoe".into()),
+            sort: BrowserSort::Round,
+            ..BrowserQuery::default()
+        };
+        assert_eq!(ids(&query.apply(rooms.clone())), ["b", "c"]);
+
+        let query = BrowserQuery {
+            players: Some(2),
+            queue: Some("clasica".into()),
+            ..BrowserQuery::default()
+        };
+        assert_eq!(ids(&query.apply(rooms.clone())), ["a"]);
+
+        let query = BrowserQuery {
+            sort: BrowserSort::Spectators,
+            ..BrowserQuery::default()
+        };
+        assert_eq!(ids(&query.apply(rooms)), ["c", "a", "b"]);
+    }
+
+    #[test]
+    fn live_browsers_hear_only_of_changes() {
+        let directory = RoomDirectory::default();
+        let mut updates = directory.subscribe();
+
+        directory.publish(listing("a", &["ana", "ben"], 0));
+        directory.publish(listing("a", &["ana", "ben"], 0));
+        directory.publish(listing("a", &["ana", "ben"], 1));
+        directory.remove("a");
+        directory.remove("a");
+
+        let mut seen = Vec::new();
+        while let Ok(update) = updates.try_recv() {
+            seen.push(match update {
+                BrowserUpdate::Listed(listing) => format!("listed {}", listing.round_index),
+                BrowserUpdate::Unlisted(room_id) => format!("unlisted {}", room_id),
+            });
+        }
+        assert_eq!(seen, ["listed 0", "listed 1", "unlisted a"]);
+        assert!(directory.list(&BrowserQuery::default()).is_empty());
+    }
+}
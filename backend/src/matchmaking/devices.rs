@@ -0,0 +1,132 @@
+use tokio::sync::mpsc;
+
+use crate::api::events::ServerMessage;
+
+/// How a connection attaches to a seat its player already has open elsewhere.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AttachMode {
+    /// Take the seat over; the device playing until now keeps watching
+    #[default]
+    Control,
+    /// Watch the seat without acting
+    Mirror,
+}
+
+impl AttachMode {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "control" => Some(AttachMode::Control),
+            "mirror" => Some(AttachMode::Mirror),
+            _ => None,
+        }
+    }
+}
+
+/// Every connection one player has open to a room, keyed by a per-connection id. All of
+/// them receive the player's messages; only the controlling one may act.
+#[derive(Debug, Clone)]
+pub struct Devices {
+    /// In the order they attached
+    channels: Vec<(String, mpsc::Sender<ServerMessage>)>,
+    controller: String,
+}
+
+impl Devices {
+    pub fn new(device_id: String, sender: mpsc::Sender<ServerMessage>) -> Self {
+        Self {
+            controller: device_id.clone(),
+            channels: vec![(device_id, sender)],
+        }
+    }
+
+    /// Adds a device. Taking control returns the device that gave it up.
+    pub fn attach(
+        &mut self,
+        device_id: String,
+        sender: mpsc::Sender<ServerMessage>,
+        mode: AttachMode,
+    ) -> Option<String> {
+        self.channels.retain(|(id, _)| *id != device_id);
+        self.channels.push((device_id.clone(), sender));
+        match mode {
+            AttachMode::Control if self.controller != device_id => {
+                Some(std::mem::replace(&mut self.controller, device_id))
+            }
+            _ => None,
+        }
+    }
+
+    /// Removes a device. When it was in control, the longest-attached device left takes
+    /// over and is returned.
+    pub fn detach(&mut self, device_id: &str) -> Option<String> {
+        self.channels.retain(|(id, _)| id != device_id);
+        if self.controller != device_id {
+            return None;
+        }
+        let (next, _) = self.channels.first()?;
+        self.controller = next.clone();
+        Some(next.clone())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.channels.is_empty()
+    }
+
+    pub fn controls(&self, device_id: &str) -> bool {
+        self.controller == device_id
+    }
+
+    pub fn sender_of(&self, device_id: &str) -> Option<&mpsc::Sender<ServerMessage>> {
+        self.channels
+            .iter()
+            .find(|(id, _)| id == device_id)
+            .map(|(_, sender)| sender)
+    }
+
+    pub fn senders(&self) -> impl Iterator<Item = &mpsc::Sender<ServerMessage>> {
+        self.channels.iter().map(|(_, sender)| sender)
+    }
+
+    /// Sends `msg` to every device.
+    pub async fn send(&self, msg: ServerMessage) {
+        for sender in self.senders() {
+            let _ = sender.send(msg.clone()).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn channel() -> mpsc::Sender<ServerMessage> {
+        mpsc::channel(1).0
+    }
+
+    #[test]
+    fn control_moves_to_the_device_that_asks_for_it() {
+        let mut devices = Devices::new("phone".into(), channel());
+        assert_eq!(
+            devices.attach("tablet".into(), channel(), AttachMode::Mirror),
+            None
+        );
+        assert!(devices.controls("phone"));
+
+        let handed_over = devices.attach("laptop".into(), channel(), AttachMode::Control);
+        assert_eq!(handed_over.as_deref(), Some("phone"));
+        assert!(devices.controls("laptop"));
+        assert_eq!(devices.senders().count(), 3);
+    }
+
+    #[test]
+    fn the_oldest_device_left_takes_over_from_a_controller_that_leaves() {
+        let mut devices = Devices::new("phone".into(), channel());
+        devices.attach("tablet".into(), channel(), AttachMode::Mirror);
+        devices.attach("laptop".into(), channel(), AttachMode::Control);
+
+        assert_eq!(devices.detach("tablet"), None);
+        assert_eq!(devices.detach("laptop").as_deref(), Some("phone"));
+        assert_eq!(devices.detach("phone"), None);
+        assert!(devices.is_empty());
+    }
+}
@@ -0,0 +1,122 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Upper bounds of the histogram buckets, in microseconds. Slower actions land in a last,
+/// unbounded bucket.
+pub const BUCKET_BOUNDS_US: [u64; 12] = [
+    250, 500, 1_000, 2_500, 5_000, 10_000, 25_000, 50_000, 100_000, 250_000, 500_000, 1_000_000,
+];
+
+/// How long rooms take to handle player actions, from the room receiving one to the last
+/// update about it being sent, per action type and room size.
+#[derive(Clone, Default)]
+pub struct ActionLatency {
+    histograms: Arc<Mutex<BTreeMap<(&'static str, usize), Histogram>>>,
+}
+
+#[derive(Default)]
+struct Histogram {
+    /// One per bound in [`BUCKET_BOUNDS_US`], plus the unbounded one
+    counts: [u64; BUCKET_BOUNDS_US.len() + 1],
+    sum_us: u64,
+    max_us: u64,
+}
+
+/// One histogram as shown in the admin API.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LatencyHistogram {
+    /// The `ClientMessage` type, e.g. `DropHand`
+    pub action: &'static str,
+    /// Players seated in the room
+    pub room_size: usize,
+    pub count: u64,
+    pub sum_us: u64,
+    pub max_us: u64,
+    /// Cumulative, like Prometheus: each bucket counts every action at or under its bound
+    pub buckets: Vec<LatencyBucket>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LatencyBucket {
+    /// `None` for the last bucket, which has no bound
+    pub le_us: Option<u64>,
+    pub count: u64,
+}
+
+impl ActionLatency {
+    pub fn record(&self, action: &'static str, room_size: usize, elapsed: Duration) {
+        let us = elapsed.as_micros().min(u64::MAX as u128) as u64;
+        let bucket = BUCKET_BOUNDS_US
+            .iter()
+            .position(|&bound| us <= bound)
+            .unwrap_or(BUCKET_BOUNDS_US.len());
+
+        let mut histograms = self.histograms.lock().unwrap();
+        let histogram = histograms.entry((action, room_size)).or_default();
+        histogram.counts[bucket] += 1;
+        histogram.sum_us = histogram.sum_us.saturating_add(us);
+        histogram.max_us = histogram.max_us.max(us);
+    }
+
+    /// Every histogram with at least one action, by action type then room size.
+    pub fn metrics(&self) -> Vec<LatencyHistogram> {
+        let histograms = self.histograms.lock().unwrap();
+        histograms
+            .iter()
+            .map(|(&(action, room_size), histogram)| {
+                let bounds = BUCKET_BOUNDS_US.iter().map(|&bound| Some(bound));
+                let mut running = 0;
+                let buckets = bounds
+                    .chain([None])
+                    .zip(histogram.counts)
+                    .map(|(le_us, count)| {
+                        running += count;
+                        LatencyBucket {
+                            le_us,
+                            count: running,
+                        }
+                    })
+                    .collect();
+                LatencyHistogram {
+                    action,
+                    room_size,
+                    count: running,
+                    sum_us: histogram.sum_us,
+                    max_us: histogram.max_us,
+                    buckets,
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn histograms_are_kept_per_action_and_room_size() {
+        let latency = ActionLatency::default();
+        latency.record("Discard", 4, Duration::from_micros(300));
+        latency.record("Discard", 4, Duration::from_millis(3));
+        latency.record("Discard", 2, Duration::from_micros(100));
+        latency.record("DropHand", 4, Duration::from_secs(2));
+
+        let metrics = latency.metrics();
+        let keys: Vec<(&str, usize)> = metrics.iter().map(|h| (h.action, h.room_size)).collect();
+        assert_eq!(keys, [("Discard", 2), ("Discard", 4), ("DropHand", 4)]);
+
+        let discards = &metrics[1];
+        assert_eq!(discards.count, 2);
+        assert_eq!(discards.sum_us, 3_300);
+        assert_eq!(discards.max_us, 3_000);
+        let cumulative: Vec<u64> = discards.buckets.iter().map(|b| b.count).collect();
+        assert_eq!(cumulative, [0, 1, 1, 1, 2, 2, 2, 2, 2, 2, 2, 2, 2]);
+
+        let slow = metrics[2].buckets.last().unwrap();
+        assert_eq!((slow.le_us, slow.count), (None, 1));
+        assert_eq!(metrics[2].buckets[BUCKET_BOUNDS_US.len() - 1].count, 0);
+    }
+}
@@ -1,48 +1,600 @@
-use std::collections::VecDeque;
+use std::collections::{HashMap, VecDeque};
 use std::sync::Arc;
-use tokio::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio::sync::{Mutex, oneshot};
+
+use crate::engine::bot::BotDifficulty;
+
+/// How long a player's place in the queue outlives the server stopping. Players who come
+/// back later start waiting afresh.
+pub const QUEUE_ENTRY_TTL: Duration = Duration::from_secs(30 * 60);
+
+/// How long a player keeps priority after a game was called off on them.
+pub const REQUEUE_PRIORITY_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// Seats at a regular table.
+pub const TABLE_SIZE: usize = 4;
+/// Fill-ins for seats no human took, in the order they are used.
+pub const BOT_IDS: [&str; TABLE_SIZE - 1] = ["bot_easy", "bot_medium", "bot_hard"];
+
+/// Parses an instant-play bot mix such as `easy,hard,hard`: one to three opponents.
+pub fn parse_bot_mix(raw: &str) -> Option<Vec<BotDifficulty>> {
+    let mix = raw
+        .split(',')
+        .map(|level| match level.trim().to_ascii_lowercase().as_str() {
+            "easy" => Some(BotDifficulty::Easy),
+            "medium" => Some(BotDifficulty::Medium),
+            "hard" => Some(BotDifficulty::Hard),
+            _ => None,
+        })
+        .collect::<Option<Vec<_>>>()?;
+    (1..TABLE_SIZE).contains(&mix.len()).then_some(mix)
+}
+
+/// Seats `user_id` against bots of the given difficulties, skipping the queue. Bot ids
+/// follow [`BOT_IDS`], numbered when a difficulty repeats (`bot_hard`, `bot_hard_2`).
+pub fn instant_table(user_id: &str, bots: &[BotDifficulty]) -> Vec<String> {
+    let mut players = vec![user_id.to_string()];
+    for (i, difficulty) in bots.iter().enumerate() {
+        let base = match difficulty {
+            BotDifficulty::Easy => BOT_IDS[0],
+            BotDifficulty::Medium => BOT_IDS[1],
+            BotDifficulty::Hard => BOT_IDS[2],
+        };
+        let same_before = bots[..i].iter().filter(|d| *d == difficulty).count();
+        players.push(match same_before {
+            0 => base.to_string(),
+            n => format!("{}_{}", base, n + 1),
+        });
+    }
+    players
+}
+
+/// How long the lobby holds out for better matches.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LobbySettings {
+    /// Wait before a player will be seated with players from other regions
+    pub region_wait: Duration,
+    /// Wait before the empty seats are filled with bots; zero matches with bots immediately
+    pub bot_fill_wait: Duration,
+}
+
+/// Where a waiting player was placed once their table came together.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Seating {
+    pub room_id: String,
+    pub players: Vec<String>,
+    /// Declared region of each human at the table; players who declared none are left out
+    pub regions: HashMap<String, String>,
+}
+
+impl Seating {
+    /// Whether the table mixes regions, so players should expect higher latency.
+    pub fn cross_region(&self) -> bool {
+        mixes_regions(&self.regions)
+    }
+}
+
+/// Whether players declared more than one region between them.
+pub fn mixes_regions(regions: &HashMap<String, String>) -> bool {
+    let mut regions = regions.values();
+    regions
+        .next()
+        .is_some_and(|first| regions.any(|region| region != first))
+}
+
+/// A table the lobby put together. Whoever gets it opens the room, then calls
+/// [`Table::seat`] to tell the other players where to go.
+pub struct Table {
+    pub players: Vec<String>,
+    pub regions: HashMap<String, String>,
+    /// Queue the table formed in, which decides its rules
+    pub queue: &'static str,
+    guests: Vec<oneshot::Sender<Seating>>,
+}
+
+impl Table {
+    /// Hands `room_id` to every other player at the table and returns the host's seating.
+    pub fn seat(self, room_id: &str) -> Seating {
+        let seating = Seating {
+            room_id: room_id.to_string(),
+            players: self.players,
+            regions: self.regions,
+        };
+        for guest in self.guests {
+            let _ = guest.send(seating.clone());
+        }
+        seating
+    }
+}
+
+pub enum JoinOutcome {
+    /// A table is ready and this player opens the room for it
+    Host(Table),
+    /// Not enough players yet: the seating arrives here, or call [`Lobby::retry`]
+    /// periodically so the lobby can relax its preferences as the wait grows
+    Queued(oneshot::Receiver<Seating>),
+}
+
+/// A player's place in the queue as stored, so it survives a restart.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueueEntry {
+    pub user_id: String,
+    pub queue: &'static str,
+    pub region: Option<String>,
+    /// When they joined, epoch milliseconds
+    pub joined_at_ms: u64,
+}
+
+/// How a player's search departs from the usual first come, first served one.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SearchOptions {
+    /// Their last game was called off through no fault of their own (the server went down
+    /// on it, or an opponent walked out): they go to the front of the queue
+    pub priority: bool,
+    /// Seated across regions straight away, as prioritized players and players still in
+    /// placement are, so they find a table sooner
+    pub any_region: bool,
+    /// Players not to seat them with, such as whoever abandoned their last game
+    pub avoid: Vec<String>,
+}
+
+struct Waiting {
+    user_id: String,
+    region: Option<String>,
+    queue: &'static str,
+    since: Instant,
+    /// Players this one is never seated with
+    avoid: Vec<String>,
+    seat_tx: oneshot::Sender<Seating>,
+}
 
 #[derive(Clone)]
 pub struct Lobby {
-    // Queue of user IDs waiting for a match
-    waiting_players: Arc<Mutex<VecDeque<String>>>,
+    // Players waiting for a match, longest wait first
+    waiting_players: Arc<Mutex<VecDeque<Waiting>>>,
+    /// Players who were waiting when the server last stopped and haven't reconnected yet
+    restored: Arc<Mutex<HashMap<String, QueueEntry>>>,
+    settings: LobbySettings,
 }
 
 impl Lobby {
-    pub fn new() -> Self {
+    pub fn new(settings: LobbySettings) -> Self {
         Self {
             waiting_players: Arc::new(Mutex::new(VecDeque::new())),
+            restored: Arc::default(),
+            settings,
         }
     }
 
-    pub async fn join(&self, user_id: String) -> Option<Vec<String>> {
-        let queue = self.waiting_players.lock().await;
+    /// Holds on to the places of players who were waiting when the server stopped, until
+    /// they reconnect. Entries older than [`QUEUE_ENTRY_TTL`] are dropped.
+    pub async fn restore(&self, entries: Vec<QueueEntry>) {
+        let cutoff = epoch_ms().saturating_sub(QUEUE_ENTRY_TTL.as_millis() as u64);
+        let mut restored = self.restored.lock().await;
+        for entry in entries.into_iter().filter(|e| e.joined_at_ms >= cutoff) {
+            restored.insert(entry.user_id.clone(), entry);
+        }
+    }
+
+    /// The place the player held in `queue_id` before the server stopped, if they haven't
+    /// reclaimed it yet.
+    pub async fn restored(&self, user_id: &str, queue_id: &str) -> Option<QueueEntry> {
+        let restored = self.restored.lock().await;
+        restored
+            .get(user_id)
+            .filter(|e| e.queue == queue_id)
+            .cloned()
+    }
+
+    /// Puts the player in the queue, or seats them straight away if a table can be formed.
+    /// `region` is the player's declared region; `None` plays with anyone. Players are only
+    /// seated with others who picked the same `queue`. A player coming back to a place
+    /// [restored](Self::restore) in the same queue keeps the time they have waited.
+    pub async fn join(
+        &self,
+        user_id: String,
+        region: Option<String>,
+        queue_id: &'static str,
+    ) -> Option<JoinOutcome> {
+        self.join_with(user_id, region, queue_id, SearchOptions::default())
+            .await
+    }
+
+    /// Like [`Self::join`], searching as `options` say.
+    pub async fn join_with(
+        &self,
+        user_id: String,
+        region: Option<String>,
+        queue_id: &'static str,
+        options: SearchOptions,
+    ) -> Option<JoinOutcome> {
+        let mut queue = self.waiting_players.lock().await;
 
         // Prevent duplicate joins
-        if queue.contains(&user_id) {
+        if queue.iter().any(|w| w.user_id == user_id) {
             return None;
         }
 
-        // MVP: Immediately match the player with 3 bots (Easy, Medium, Hard)
-        // so we don't have to wait for 4 real players to test the game.
-        let matched = vec![
-            user_id.clone(),
-            "bot_easy".to_string(),
-            "bot_medium".to_string(),
-            "bot_hard".to_string(),
-        ];
+        let now = Instant::now();
+        let since = match self.restored.lock().await.remove(&user_id) {
+            Some(entry) if entry.queue == queue_id => {
+                let waited = epoch_ms().saturating_sub(entry.joined_at_ms);
+                now.checked_sub(Duration::from_millis(waited))
+                    .unwrap_or(now)
+            }
+            _ => now,
+        };
+        // As if they had already waited out their region preference
+        let since = match options.any_region {
+            true => since
+                .checked_sub(self.settings.region_wait)
+                .unwrap_or(since),
+            false => since,
+        };
+        let avoid = options.avoid;
+        let joining = Joining {
+            user_id: &user_id,
+            region: &region,
+            queue: queue_id,
+            since,
+            avoid: &avoid,
+        };
+        if let Some(table) = self.form_table(&mut queue, joining, now) {
+            return Some(JoinOutcome::Host(table));
+        }
+
+        let (seat_tx, seat_rx) = oneshot::channel();
+        let waiting = Waiting {
+            user_id,
+            region,
+            queue: queue_id,
+            since,
+            avoid,
+            seat_tx,
+        };
+        match options.priority {
+            true => queue.push_front(waiting),
+            false => queue.push_back(waiting),
+        }
+        Some(JoinOutcome::Queued(seat_rx))
+    }
+
+    /// Tries again to form a table for a queued player, with the preferences their wait
+    /// so far allows. Returns `None` if they keep waiting or are no longer queued.
+    pub async fn retry(&self, user_id: &str) -> Option<Table> {
+        let mut queue = self.waiting_players.lock().await;
+        let index = queue.iter().position(|w| w.user_id == user_id)?;
+        let waiting = queue.remove(index)?;
 
-        Some(matched)
+        let joining = Joining {
+            user_id: &waiting.user_id,
+            region: &waiting.region,
+            queue: waiting.queue,
+            since: waiting.since,
+            avoid: &waiting.avoid,
+        };
+        let table = self.form_table(&mut queue, joining, Instant::now());
+        if table.is_none() {
+            // Keeps their place in the queue
+            let index = index.min(queue.len());
+            queue.insert(index, waiting);
+        }
+        table
     }
 
     pub async fn leave(&self, user_id: &str) {
         let mut queue = self.waiting_players.lock().await;
-        queue.retain(|id| id != user_id);
+        queue.retain(|w| w.user_id != user_id);
+    }
+
+    /// How many players are waiting in each queue that has anyone in it.
+    pub async fn waiting_counts(&self) -> HashMap<&'static str, usize> {
+        let queue = self.waiting_players.lock().await;
+        let mut counts = HashMap::new();
+        for waiting in queue.iter().filter(|w| !w.seat_tx.is_closed()) {
+            *counts.entry(waiting.queue).or_default() += 1;
+        }
+        counts
     }
+
+    /// Seats the joining player with the longest-waiting compatible players, filling with
+    /// bots once the wait allows it. Players taken for the table are removed from the queue.
+    fn form_table(
+        &self,
+        queue: &mut VecDeque<Waiting>,
+        joining: Joining,
+        now: Instant,
+    ) -> Option<Table> {
+        let Joining {
+            user_id,
+            region,
+            queue: queue_id,
+            since,
+            avoid,
+        } = joining;
+        // Players whose connection went away without leaving can't be seated
+        queue.retain(|w| !w.seat_tx.is_closed());
+
+        let patient = |since: Instant| now.duration_since(since) >= self.settings.region_wait;
+        let avoids = |avoid: &[String], other: &str| avoid.iter().any(|id| id == other);
+        let mut compatible: Vec<usize> = Vec::new();
+        for (i, w) in queue.iter().enumerate() {
+            if compatible.len() == TABLE_SIZE - 1 {
+                break;
+            }
+            let same_region = region.is_none()
+                || w.region.is_none()
+                || w.region == *region
+                || patient(since)
+                || patient(w.since);
+            // Nobody at the table may be someone another of them avoids
+            let clashes = avoids(avoid, &w.user_id)
+                || avoids(&w.avoid, user_id)
+                || compatible.iter().any(|&j| {
+                    avoids(&queue[j].avoid, &w.user_id) || avoids(&w.avoid, &queue[j].user_id)
+                });
+            if w.queue == queue_id && same_region && !clashes {
+                compatible.push(i);
+            }
+        }
+
+        let full = compatible.len() == TABLE_SIZE - 1;
+        if !full && now.duration_since(since) < self.settings.bot_fill_wait {
+            return None;
+        }
+
+        let mut players = vec![user_id.to_string()];
+        let mut regions = HashMap::new();
+        if let Some(region) = region {
+            regions.insert(user_id.to_string(), region.clone());
+        }
+        let mut guests = Vec::new();
+        // Back to front so earlier indices stay valid
+        let mut taken: Vec<Waiting> = compatible
+            .into_iter()
+            .rev()
+            .filter_map(|i| queue.remove(i))
+            .collect();
+        taken.reverse();
+        for waiting in taken {
+            if let Some(region) = waiting.region {
+                regions.insert(waiting.user_id.clone(), region);
+            }
+            players.push(waiting.user_id);
+            guests.push(waiting.seat_tx);
+        }
+
+        let bots = TABLE_SIZE - players.len();
+        players.extend(BOT_IDS[..bots].iter().map(|id| id.to_string()));
+
+        Some(Table {
+            players,
+            regions,
+            queue: queue_id,
+            guests,
+        })
+    }
+}
+
+fn epoch_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64
+}
+
+/// The player a table is being formed for.
+struct Joining<'a> {
+    user_id: &'a str,
+    region: &'a Option<String>,
+    queue: &'static str,
+    since: Instant,
+    avoid: &'a [String],
 }
 
 impl Default for Lobby {
     fn default() -> Self {
-        Self::new()
+        Self::new(LobbySettings::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matchmaking::queues::DEFAULT_QUEUE;
+
+    fn waiting_lobby(region_wait: Duration) -> Lobby {
+        Lobby::new(LobbySettings {
+            region_wait,
+            bot_fill_wait: Duration::from_secs(60),
+        })
+    }
+
+    async fn queue(lobby: &Lobby, user_id: &str, region: &str) -> oneshot::Receiver<Seating> {
+        match lobby
+            .join(user_id.to_string(), Some(region.to_string()), DEFAULT_QUEUE)
+            .await
+        {
+            Some(JoinOutcome::Queued(rx)) => rx,
+            _ => panic!("{} should have been queued", user_id),
+        }
+    }
+
+    #[test]
+    fn instant_tables_name_each_bot_once() {
+        let mix = parse_bot_mix("hard, easy,HARD").unwrap();
+        assert_eq!(
+            instant_table("ana", &mix),
+            ["ana", "bot_hard", "bot_easy", "bot_hard_2"]
+        );
+        assert!(parse_bot_mix("").is_none());
+        assert!(
+            parse_bot_mix("easy,easy,easy,easy").is_none(),
+            "Four bots don't fit"
+        );
+        assert!(parse_bot_mix("impossible").is_none());
+    }
+
+    #[tokio::test]
+    async fn fills_with_bots_immediately_by_default() {
+        let lobby = Lobby::default();
+        let Some(JoinOutcome::Host(table)) =
+            lobby.join("ana".to_string(), None, DEFAULT_QUEUE).await
+        else {
+            panic!("Should be matched straight away");
+        };
+        assert_eq!(table.players, ["ana", "bot_easy", "bot_medium", "bot_hard"]);
+    }
+
+    #[tokio::test]
+    async fn prefers_players_from_the_same_region() {
+        let lobby = waiting_lobby(Duration::from_secs(30));
+        let mut ana = queue(&lobby, "ana", "eu").await;
+        let _beto = queue(&lobby, "beto", "sa").await;
+        let mut caro = queue(&lobby, "caro", "eu").await;
+        // Only two other eu players are waiting
+        let _dani = queue(&lobby, "dani", "eu").await;
+
+        let Some(JoinOutcome::Host(table)) = lobby
+            .join("eva".to_string(), Some("eu".to_string()), DEFAULT_QUEUE)
+            .await
+        else {
+            panic!("Four eu players should make a table");
+        };
+        assert_eq!(table.players, ["eva", "ana", "caro", "dani"]);
+        let seating = table.seat("room-1");
+        assert!(!seating.cross_region());
+        assert_eq!(ana.try_recv().unwrap(), seating);
+        assert_eq!(caro.try_recv().unwrap().room_id, "room-1");
+        assert!(lobby.retry("beto").await.is_none(), "beto keeps waiting");
+    }
+
+    #[tokio::test]
+    async fn falls_back_across_regions_after_the_wait() {
+        let lobby = waiting_lobby(Duration::ZERO);
+        let _ana = queue(&lobby, "ana", "eu").await;
+        let _beto = queue(&lobby, "beto", "sa").await;
+        let _caro = queue(&lobby, "caro", "na").await;
+
+        let Some(JoinOutcome::Host(table)) = lobby
+            .join("dani".to_string(), Some("eu".to_string()), DEFAULT_QUEUE)
+            .await
+        else {
+            panic!("Region preference has already expired");
+        };
+        assert_eq!(table.players, ["dani", "ana", "beto", "caro"]);
+        assert!(table.seat("room-1").cross_region());
+    }
+
+    #[tokio::test]
+    async fn fills_with_bots_once_the_wait_runs_out() {
+        let lobby = Lobby::new(LobbySettings {
+            region_wait: Duration::ZERO,
+            bot_fill_wait: Duration::from_millis(20),
+        });
+        let _ana = queue(&lobby, "ana", "eu").await;
+        let mut beto = queue(&lobby, "beto", "eu").await;
+        assert!(lobby.retry("ana").await.is_none(), "Still worth waiting");
+
+        tokio::time::sleep(Duration::from_millis(25)).await;
+        let table = lobby.retry("ana").await.expect("Bots fill the table");
+        assert_eq!(table.players, ["ana", "beto", "bot_easy", "bot_medium"]);
+        table.seat("room-1");
+        assert_eq!(beto.try_recv().unwrap().room_id, "room-1");
+    }
+
+    #[tokio::test]
+    async fn a_restored_player_keeps_their_wait() {
+        let lobby = waiting_lobby(Duration::ZERO);
+        let entry = |user_id: &str, waited: Duration| QueueEntry {
+            user_id: user_id.to_string(),
+            queue: DEFAULT_QUEUE,
+            region: None,
+            joined_at_ms: epoch_ms() - waited.as_millis() as u64,
+        };
+        lobby
+            .restore(vec![
+                entry("ana", Duration::from_secs(90)),
+                entry("beto", Duration::from_secs(90)),
+                entry("caro", QUEUE_ENTRY_TTL + Duration::from_secs(1)),
+            ])
+            .await;
+        assert!(lobby.restored("ana", "rapida").await.is_none());
+        assert!(lobby.restored("caro", DEFAULT_QUEUE).await.is_none());
+
+        let Some(JoinOutcome::Host(table)) =
+            lobby.join("ana".to_string(), None, DEFAULT_QUEUE).await
+        else {
+            panic!("ana has waited long enough for bots");
+        };
+        assert_eq!(table.players.len(), TABLE_SIZE);
+        assert!(lobby.restored("ana", DEFAULT_QUEUE).await.is_none());
+
+        let Some(JoinOutcome::Queued(_)) = lobby.join("beto".to_string(), None, "rapida").await
+        else {
+            panic!("A different queue starts the wait over");
+        };
+    }
+
+    #[tokio::test]
+    async fn priority_jumps_the_queue_and_keeps_away_from_the_abandoner() {
+        let lobby = waiting_lobby(Duration::from_secs(30));
+        let _ana = queue(&lobby, "ana", "eu").await;
+        let _beto = queue(&lobby, "beto", "sa").await;
+        let _caro = queue(&lobby, "caro", "eu").await;
+
+        let options = SearchOptions {
+            priority: true,
+            any_region: true,
+            avoid: vec!["caro".to_string()],
+        };
+        let Some(JoinOutcome::Queued(_dani)) = lobby
+            .join_with(
+                "dani".to_string(),
+                Some("sa".to_string()),
+                DEFAULT_QUEUE,
+                options,
+            )
+            .await
+        else {
+            panic!("Only two players dani may sit with are waiting");
+        };
+        let _eva = queue(&lobby, "eva", "eu").await;
+
+        let Some(JoinOutcome::Host(table)) = lobby
+            .join("fede".to_string(), Some("eu".to_string()), DEFAULT_QUEUE)
+            .await
+        else {
+            panic!("fede, dani, ana and eva make a table");
+        };
+        assert_eq!(table.players, ["fede", "dani", "ana", "eva"]);
+        assert!(table.seat("room-1").cross_region());
+    }
+
+    #[tokio::test]
+    async fn only_seats_players_from_the_same_queue() {
+        let lobby = waiting_lobby(Duration::ZERO);
+        let _ana = queue(&lobby, "ana", "eu").await;
+        let mut others = Vec::new();
+        for user_id in ["beto", "caro", "dani"] {
+            match lobby.join(user_id.to_string(), None, "rapida").await {
+                Some(JoinOutcome::Queued(rx)) => others.push(rx),
+                _ => panic!("{} should have been queued", user_id),
+            }
+        }
+        let counts = lobby.waiting_counts().await;
+        assert_eq!((counts[DEFAULT_QUEUE], counts["rapida"]), (1, 3));
+
+        let Some(JoinOutcome::Host(table)) = lobby.join("eva".to_string(), None, "rapida").await
+        else {
+            panic!("Four players in rapida make a table");
+        };
+        assert_eq!(table.queue, "rapida");
+        assert_eq!(table.players, ["eva", "beto", "caro", "dani"]);
+        assert!(
+            lobby.retry("ana").await.is_none(),
+            "ana keeps waiting in the default queue"
+        );
     }
 }
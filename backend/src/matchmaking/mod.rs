@@ -1,2 +1,13 @@
+pub mod adaptive;
+pub mod bot_identity;
+pub mod browser;
+pub mod devices;
+pub mod latency;
 pub mod lobby;
+pub mod queues;
 pub mod room;
+pub mod rule_vote;
+pub mod scheduled;
+pub mod spectators;
+pub mod turn_timer;
+pub mod tutorial;
@@ -0,0 +1,69 @@
+use crate::engine::rule_set::{DiscardVisibility, RuleSet};
+
+/// Queue players land in when they don't pick one.
+pub const DEFAULT_QUEUE: &str = "clasica";
+
+/// A matchmaking queue and the house rules its tables play by. Presets adjust the server's
+/// configured rules, so point values and the like stay as the operator set them.
+#[derive(Debug)]
+pub struct QueuePreset {
+    pub id: &'static str,
+    pub name: &'static str,
+    pub description: &'static str,
+    adjust: fn(&mut RuleSet),
+}
+
+impl QueuePreset {
+    pub fn rules(&self, base: &RuleSet) -> RuleSet {
+        let mut rules = base.clone();
+        (self.adjust)(&mut rules);
+        rules
+    }
+}
+
+pub const PRESETS: &[QueuePreset] = &[
+    QueuePreset {
+        id: DEFAULT_QUEUE,
+        name: "Clásica chilena",
+        description: "All nine rounds under the server's rules",
+        adjust: |_| {},
+    },
+    QueuePreset {
+        id: "rapida",
+        name: "Rápida 4 rondas",
+        description: "Only the first four rounds, up to three tríos",
+        adjust: |rules| rules.round_limit = 4,
+    },
+    QueuePreset {
+        id: "pozo_abierto",
+        name: "Pozo abierto",
+        description: "The whole discard pile is public, and cards may be shed the turn you drop",
+        adjust: |rules| {
+            rules.discard_visibility = DiscardVisibility::All;
+            rules.shed_on_bajada_turn = true;
+        },
+    },
+];
+
+pub fn find(id: &str) -> Option<&'static QueuePreset> {
+    PRESETS.iter().find(|preset| preset.id == id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn presets_adjust_the_base_rules() {
+        let base = RuleSet {
+            stalemate_turns: 3,
+            ..RuleSet::default()
+        };
+        assert_eq!(find(DEFAULT_QUEUE).unwrap().rules(&base), base);
+
+        let quick = find("rapida").unwrap().rules(&base);
+        assert_eq!(quick.round_limit, 4);
+        assert_eq!(quick.stalemate_turns, 3, "Other rules are left alone");
+        assert!(find("nope").is_none());
+    }
+}
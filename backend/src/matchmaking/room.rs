@@ -1,24 +1,234 @@
-use crate::api::events::{ClientMessage, PlayerScore, SanitizedPlayerState, ServerMessage};
-use crate::engine::game::GameState;
-use tokio::sync::mpsc;
+use crate::api::avatars;
+use crate::api::cosmetics;
+use crate::api::events::{
+    ActionView, ClientMessage, ConnectionStatus, PlayerScore, ReorderHandPayload, RulePresetOption,
+    SanitizedPlayerState, ServerMessage, VoteRulesPayload,
+};
+use crate::api::i18n::{self, Locale};
+use crate::api::maintenance::MaintenanceNotice;
+use crate::api::rtt::ConnectionQuality;
+use crate::db::audit::AuditLog;
+use crate::db::models::{
+    BotRoundStat, Cosmetics, GameResult, GameResultPlayer, Report, RequeuePriority, ShuffleSeeds,
+};
+use crate::db::repo;
+use crate::db::seed_vault::{RoundSeed, SeedVault};
+use crate::engine::analysis::{self, PlayReview};
+use crate::engine::bot;
+use crate::engine::card::Card;
+use crate::engine::deck::Deck;
+use crate::engine::game::{GameState, LastAction};
+use crate::engine::hints;
+use crate::engine::podium::Podium;
+use crate::engine::replay::{self, Input, ReplayLog};
+use crate::engine::rule_set::{RuleSet, TimeoutPolicy};
+use crate::engine::snapshot::GameSnapshot;
+use crate::engine::transcript::Transcript;
+use crate::matchmaking::adaptive;
+use crate::matchmaking::bot_identity;
+use crate::matchmaking::browser::{RoomDirectory, RoomListing};
+use crate::matchmaking::devices::{AttachMode, Devices};
+use crate::matchmaking::latency::ActionLatency;
+use crate::matchmaking::lobby;
+use crate::matchmaking::queues::{self, QueuePreset};
+use crate::matchmaking::rule_vote::{Ballot, RuleVote};
+use crate::matchmaking::spectators::Spectators;
+use crate::matchmaking::turn_timer::{TimerEvent, TurnTimer, TurnTimerConfig};
+use crate::matchmaking::tutorial::{self, TUTOR_BOT_ID, Tutorial, TutorialScript};
+use crate::moderation::word_filter::WordFilter;
+use crate::notify::notifier::Notifier;
+use crate::notify::push::PushEvent;
+use crate::notify::webhook::WebhookEvent;
+use crate::ranking::season::{self, RatingChange, SeasonSettings};
+use crate::ranking::titles::{self, PlayerTitle};
+use sqlx::SqlitePool;
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio::sync::{mpsc, oneshot};
+use tokio::task::JoinHandle;
 
-#[derive(Debug, Clone)]
+/// How long a break request stays open for the rest of the table to agree
+const PAUSE_VOTE_WINDOW: Duration = Duration::from_secs(30);
+/// Reorders arriving closer together than this are coalesced, keeping only the latest
+const REORDER_COALESCE: Duration = Duration::from_millis(250);
+/// Reorders and sorts each player may send between two turn changes
+const MAX_ARRANGEMENTS_PER_TURN: u32 = 40;
+
+#[derive(Debug)]
 pub enum RoomEvent {
-    PlayerJoined(String, mpsc::Sender<ServerMessage>), // Pass sender to the room
-    PlayerLeft(String),
-    PlayerAction(String, ClientMessage),
+    /// The player, a per-connection id, the connection's channel and language, and how it
+    /// attaches if the player already has the seat open on another device
+    PlayerJoined(
+        String,
+        String,
+        mpsc::Sender<ServerMessage>,
+        Locale,
+        AttachMode,
+    ),
+    /// The player and the per-connection id they joined with
+    PlayerLeft(String, String),
+    /// The acting seat, the action, the client's correlation id for it, if any, and the
+    /// connection it came from (`None` for bots)
+    PlayerAction(String, ClientMessage, Option<String>, Option<String>),
+    /// A read-only viewer with no hand of their own, keyed by a per-connection id
+    SpectatorJoined(String, mpsc::Sender<ServerMessage>),
+    SpectatorLeft(String),
+    /// An admin watching the unsanitized table and action log, keyed by a per-connection id
+    ObserverJoined(String, mpsc::Sender<ServerMessage>),
+    ObserverLeft(String),
+    /// Whether the user holds a seat at this table
+    HasPlayer(String, oneshot::Sender<bool>),
+    /// Whether the players currently let anyone spectate
+    AllowsSpectators(oneshot::Sender<bool>),
+    /// Stops the room if its game is over and nobody is connected; replies whether it did
+    CloseIfFinished(oneshot::Sender<bool>),
+    /// Where the game stands, if the user holds a seat and it is still being played
+    DescribeSeat(String, oneshot::Sender<Option<SeatSummary>>),
+    /// A player's connection got faster or slower at answering pings
+    ConnectionQuality(String, ConnectionQuality),
+    /// A player's latest average round trip, for the grace their turns get
+    RoundTrip(String, Duration),
+    /// The server went into maintenance, changed its notice or (`None`) came out of it
+    Maintenance(Option<MaintenanceNotice>),
+    /// Maintenance is about to take the server down: closes the room, replying with where
+    /// the game stands, or `None` if it was already over
+    Suspend(oneshot::Sender<Option<GameSnapshot>>),
+}
+
+/// A game in progress, as seen by one of its players.
+#[derive(Debug, Clone)]
+pub struct SeatSummary {
+    pub players: Vec<String>,
+    /// Seats a hot-seat host plays; empty for regular seats
+    pub hot_seats: Vec<String>,
+    pub regions: HashMap<String, String>,
+    pub round_index: usize,
+    pub round_name: String,
+    /// `None` between rounds
+    pub current_turn: Option<String>,
 }
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+
+/// Server-wide dependencies shared by every room.
+#[derive(Clone)]
+pub struct RoomServices {
+    pub db: SqlitePool,
+    pub audit: AuditLog,
+    pub word_filter: Arc<WordFilter>,
+    pub turn_timer: TurnTimerConfig,
+    /// Time allowed to ready up between rounds; zero waits indefinitely.
+    pub ready_countdown: Duration,
+    pub rules: RuleSet,
+    pub notifier: Notifier,
+    /// Idle time on a turn before the player gets a push about it; zero never pushes.
+    pub push_idle_turn: Duration,
+    pub seasons: SeasonSettings,
+    /// How long a room may go without any human action before it is abandoned; zero never.
+    pub idle_ttl: Duration,
+    /// How long the game waits for a dropped player before bots take their turns; zero
+    /// never pauses.
+    pub disconnect_grace: Duration,
+    /// Length of a break the players agreed to; zero turns breaks off.
+    pub break_length: Duration,
+    /// Announce each dealt card with a `CardDealt` before the new hands arrive.
+    pub stream_deals: bool,
+    /// Where the time spent handling each player action is recorded
+    pub action_latency: ActionLatency,
+    /// The game browser's listing of public tables
+    pub directory: RoomDirectory,
+    /// Encrypts the shuffle seeds stored with each finished game
+    pub seed_vault: SeedVault,
+}
 
 pub struct Room {
     pub id: String,
     pub game_state: GameState,
     pub players: Vec<String>,
-    pub player_channels: HashMap<String, mpsc::Sender<ServerMessage>>,
+    /// Each connected player's devices, keyed by player (the host, for hot-seat tables)
+    pub player_channels: HashMap<String, Devices>,
+    /// Language each connected player asked for; errors are localized per recipient
+    pub player_locales: HashMap<String, Locale>,
     // Channel to receive events from player WebSocket connections
     pub receiver: mpsc::Receiver<RoomEvent>,
     pub sender: mpsc::Sender<RoomEvent>,
+    pub services: RoomServices,
+    pub turn_timer: TurnTimer,
+    /// When waiting players get readied automatically, while between rounds
+    pub ready_deadline: Option<Instant>,
+    /// Set for tutorial rooms: the scripted deal, tutor moves and learner prompts
+    pub tutorial: Option<Tutorial>,
+    /// Read-only viewers joined through a spectate link
+    pub spectators: Spectators,
+    /// Players at the table may share a spectate link; off while anyone objects
+    pub spectators_allowed: bool,
+    /// Players who turned spectating off for the table
+    spectator_objections: HashSet<String>,
+    /// Queue a public table was formed in; `None` keeps the room out of the game browser
+    pub queue: Option<&'static str>,
+    /// Admins attached through the observation socket
+    pub observers: HashMap<String, mpsc::Sender<ServerMessage>>,
+    /// Deals, moves and scores so far, stored with the result
+    transcript: Transcript,
+    /// What each round dealt from the game's secret key was shuffled with, sealed and
+    /// stored with the result
+    round_seeds: Vec<RoundSeed>,
+    /// Pass-and-play seats driven by one connection, registered under the first seat's id.
+    /// Empty for regular rooms.
+    pub hot_seats: Vec<String>,
+    /// Who the turn was last handed to, so each hand-off is announced once
+    pub turn_holder: Option<String>,
+    /// When the turn holder gets a push if they still haven't acted
+    pub idle_push_at: Option<Instant>,
+    /// Region each human declared when joining the lobby, where they declared one
+    pub regions: HashMap<String, String>,
+    /// Last time a human sent an action, for abandoning rooms everyone walked away from
+    pub last_human_action: Instant,
+    /// Set once the game is called off; the run loop then ends
+    pub abandoned: bool,
+    /// Resumed from a hand-built position rather than dealt; never rated
+    pub staged: bool,
+    /// Instant play against bots: the result is recorded but never rated
+    pub casual: bool,
+    /// Bots explain each move to the players with a `BotExplanation`
+    pub teaching: bool,
+    /// Connections that dropped at some point, so their return reads as a reconnect
+    pub dropped_connections: HashSet<String>,
+    /// Dropped connections the game is paused for, with when their grace runs out
+    pub awaiting_reconnect: HashMap<String, Instant>,
+    /// Dropped connections whose grace ran out; bots play for them until they return
+    pub substituted: HashSet<String>,
+    /// Break being voted on: the connections that agreed, and when the vote lapses
+    pub pause_vote: Option<(HashSet<String>, Instant)>,
+    /// When the agreed break ends
+    pub break_until: Option<Instant>,
+    /// Rules being voted on before the first deal; nothing is dealt until it closes
+    pub rule_vote: Option<RuleVote>,
+    /// Latest order each seat asked for, applied once its drag-and-drop settles
+    pub pending_reorders: HashMap<String, (Vec<Card>, Instant)>,
+    /// Reorders and sorts per seat since the turn last changed hands
+    pub arrangements_this_turn: HashMap<String, u32>,
+    /// Looks of the players who have joined; everyone else shows the defaults
+    pub cosmetics: HashMap<String, Cosmetics>,
+    /// Avatar URLs of the players who have joined and uploaded one
+    pub avatar_urls: HashMap<String, String>,
+    /// Titles the players who have joined picked to show
+    pub titles: HashMap<String, PlayerTitle>,
+    /// Latest measured quality of each connected player's connection
+    pub connection_quality: HashMap<String, ConnectionQuality>,
+    /// The server's maintenance notice, repeated to players who reconnect while it lasts
+    pub maintenance: Option<MaintenanceNotice>,
+    /// Correlation id of the client action being handled, echoed on the errors it causes
+    pub correlation_id: Option<String>,
+    /// The position a human's turn began in, until the turn is reviewed
+    turn_start: Option<(String, GameState)>,
+    /// Each human's turns against the oracle's, for the review sent with `GameOver`
+    play_reviews: HashMap<String, PlayReview>,
+    /// Turn of the current round each bot dropped its hand on, for the bot telemetry
+    bajada_turns: HashMap<String, u32>,
+    /// Seed and inputs of the game, checked against a replay once it is over; `None` for
+    /// tables not dealt from a seed
+    replay: Option<ReplayLog>,
 }
 
 impl Room {
@@ -27,18 +237,156 @@ impl Room {
         players: Vec<String>,
         receiver: mpsc::Receiver<RoomEvent>,
         sender: mpsc::Sender<RoomEvent>,
+        services: RoomServices,
     ) -> Self {
-        let mut game_state = GameState::new(players.clone());
+        let mut game_state = GameState::with_rules(players.clone(), services.rules.clone());
         game_state.start_round();
+        let replay = ReplayLog::start(&game_state);
+        let turn_timer = TurnTimer::new(services.turn_timer.clone(), &players);
 
         Self {
             id,
             game_state,
             players,
             player_channels: HashMap::new(),
+            player_locales: HashMap::new(),
             receiver,
             sender,
+            services,
+            turn_timer,
+            ready_deadline: None,
+            tutorial: None,
+            spectators: Spectators::default(),
+            spectators_allowed: true,
+            spectator_objections: HashSet::new(),
+            queue: None,
+            observers: HashMap::new(),
+            transcript: Transcript::default(),
+            round_seeds: Vec::new(),
+            hot_seats: Vec::new(),
+            turn_holder: None,
+            idle_push_at: None,
+            regions: HashMap::new(),
+            last_human_action: Instant::now(),
+            abandoned: false,
+            staged: false,
+            casual: false,
+            teaching: false,
+            dropped_connections: HashSet::new(),
+            awaiting_reconnect: HashMap::new(),
+            substituted: HashSet::new(),
+            pause_vote: None,
+            break_until: None,
+            rule_vote: None,
+            pending_reorders: HashMap::new(),
+            arrangements_this_turn: HashMap::new(),
+            cosmetics: HashMap::new(),
+            avatar_urls: HashMap::new(),
+            titles: HashMap::new(),
+            connection_quality: HashMap::new(),
+            maintenance: None,
+            correlation_id: None,
+            turn_start: None,
+            play_reviews: HashMap::new(),
+            bajada_turns: HashMap::new(),
+            replay,
+        }
+    }
+
+    /// A room picking up play from `game_state`, e.g. one built from a `GameSnapshot`.
+    pub fn new_staged(
+        id: String,
+        game_state: GameState,
+        receiver: mpsc::Receiver<RoomEvent>,
+        sender: mpsc::Sender<RoomEvent>,
+        services: RoomServices,
+    ) -> Self {
+        let players = game_state.players.iter().map(|p| p.id.clone()).collect();
+        let mut room = Self::new(id, players, receiver, sender, services);
+        room.game_state = game_state;
+        room.staged = true;
+        room.replay = None;
+        room
+    }
+
+    /// A pass-and-play room where one connection, joined as `seats[0]`, plays every seat.
+    pub fn new_hot_seat(
+        id: String,
+        seats: Vec<String>,
+        receiver: mpsc::Receiver<RoomEvent>,
+        sender: mpsc::Sender<RoomEvent>,
+        services: RoomServices,
+    ) -> Self {
+        let mut room = Self::new(id, seats.clone(), receiver, sender, services);
+        room.hot_seats = seats;
+        room
+    }
+
+    /// Holds off the first deal while the humans at the table vote on the house rules and
+    /// turn length, which stay `preset`'s and the server's unless the vote changes them.
+    pub fn open_rule_vote(&mut self, preset: &'static QueuePreset) {
+        let rules = preset.rules(&self.services.rules);
+        self.game_state = GameState::with_rules(self.players.clone(), rules);
+        self.replay = None;
+        self.queue = Some(preset.id);
+        let default = Ballot {
+            preset: preset.id,
+            turn_seconds: self.services.turn_timer.turn.as_secs(),
+        };
+        let voters = self.humans().collect();
+        self.rule_vote = Some(RuleVote::new(voters, default, Instant::now()));
+    }
+
+    /// The connection that speaks for `user_id`: the hot-seat host for pass-and-play seats.
+    fn connection_of<'a>(&'a self, user_id: &'a str) -> &'a str {
+        if self.hot_seats.iter().any(|seat| seat == user_id) {
+            &self.hot_seats[0]
+        } else {
+            user_id
+        }
+    }
+
+    /// The seat a hot-seat device is showing: whoever holds the turn, or between rounds
+    /// the first seat still to ready up. `None` for regular connections.
+    fn active_hot_seat<'a>(&'a self, connection_id: &'a str) -> Option<&'a str> {
+        if self.hot_seats.first().map(String::as_str) != Some(connection_id) {
+            return None;
         }
+        let state = &self.game_state;
+        let current = state
+            .players
+            .get(state.current_turn)
+            .filter(|_| !state.is_waiting_for_next_round);
+        let waiting = state
+            .players
+            .iter()
+            .find(|p| state.is_waiting_for_next_round && !p.is_ready_for_next_round);
+        current
+            .or(waiting)
+            .map(|p| p.id.as_str())
+            .filter(|id| self.hot_seats.iter().any(|seat| seat == id))
+            .or(Some(connection_id))
+    }
+
+    /// A one-on-one lesson between `learner` and the tutor bot, following the embedded script.
+    /// Turns are untimed so the learner can read the prompts.
+    pub fn new_tutorial(
+        id: String,
+        learner: String,
+        receiver: mpsc::Receiver<RoomEvent>,
+        sender: mpsc::Sender<RoomEvent>,
+        mut services: RoomServices,
+    ) -> Self {
+        let script = TutorialScript::embedded();
+        let deck = Deck::stacked(&script.deck).expect("embedded tutorial deck is valid");
+        services.turn_timer.turn = Duration::ZERO;
+
+        let players = vec![learner, TUTOR_BOT_ID.to_string()];
+        let mut room = Self::new(id, players, receiver, sender, services);
+        room.game_state.start_round_with_deck(deck);
+        room.tutorial = Some(Tutorial::new(script));
+        room.replay = None;
+        room
     }
 
     pub async fn run(mut self) {
@@ -46,231 +394,2770 @@ impl Room {
 
         let mut bot_action_pending = false;
 
+        // With the rules still being voted on, nothing has been dealt yet
+        if self.rule_vote.is_none() {
+            self.sync_round().await;
+            self.sync_turn_timer();
+        }
+
         // Trigger bot turn if the first player happens to be a bot
         self.check_bot_turn(&mut bot_action_pending);
 
-        while let Some(event) = self.receiver.recv().await {
-            match event {
-                RoomEvent::PlayerJoined(user_id, sender) => {
-                    println!("Player {} joined room {}", user_id, self.id);
-                    self.player_channels.insert(user_id, sender);
-                    self.broadcast_state().await;
-                }
-                RoomEvent::PlayerLeft(user_id) => {
-                    println!("Player {} left room {}", user_id, self.id);
-                    self.player_channels.remove(&user_id);
-                    // For MVP maybe just end game or pause
-                }
-                RoomEvent::PlayerAction(user_id, action) => {
-                    if user_id.starts_with("bot_") {
-                        bot_action_pending = false;
+        loop {
+            let wakeup = [
+                self.turn_timer.next_wakeup(),
+                self.ready_deadline.filter(|_| !self.is_paused()),
+                self.awaiting_reconnect.values().min().copied(),
+                self.break_until,
+                self.pause_vote.as_ref().map(|(_, deadline)| *deadline),
+                self.rule_vote.as_ref().map(|vote| vote.deadline),
+                self.pending_reorders.values().map(|(_, at)| *at).min(),
+                self.idle_push_at,
+                self.abandon_deadline(),
+            ]
+            .into_iter()
+            .flatten()
+            .min();
+
+            tokio::select! {
+                event = self.receiver.recv() => {
+                    let Some(event) = event else { break };
+                    if let RoomEvent::CloseIfFinished(reply) = event {
+                        let finished = self.is_finished();
+                        let _ = reply.send(finished);
+                        if finished {
+                            break;
+                        }
+                        continue;
                     }
-                    let round_result = self.handle_action(user_id, action).await;
-                    if let Some(result) = round_result {
-                        self.broadcast_round_ended(&result).await;
+                    let action = match &event {
+                        RoomEvent::PlayerAction(_, action, _, _) => Some(action.kind()),
+                        _ => None,
+                    };
+                    let received = Instant::now();
+                    self.handle_event(event, &mut bot_action_pending).await;
+                    if let Some(action) = action {
+                        let room_size = self.players.len();
+                        self.services.action_latency.record(action, room_size, received.elapsed());
                     }
-                    self.broadcast_state().await;
                 }
+                _ = sleep_until(wakeup) => {
+                    self.on_timer_tick().await;
+                }
+            }
+            if self.abandoned {
+                break;
+            }
+            if self.spectators.take_shed() {
+                self.report_listing();
+                self.announce_spectators().await;
             }
 
+            self.send_tutorial_hint().await;
+            self.play_for_substituted().await;
             // Check if it's a bot's turn to play
             self.check_bot_turn(&mut bot_action_pending);
         }
 
+        self.services.directory.remove(&self.id);
         println!("Room {} loop ended", self.id);
     }
 
-    fn check_bot_turn(&self, bot_action_pending: &mut bool) {
-        if *bot_action_pending {
-            return;
-        }
-
-        let current_player_index = self.game_state.current_turn;
-        if let Some(user_id) = self.players.get(current_player_index)
-            && user_id.starts_with("bot_")
-        {
-            *bot_action_pending = true;
-
-            let diff = if user_id.contains("hard") {
-                crate::engine::bot::BotDifficulty::Hard
-            } else if user_id.contains("medium") {
-                crate::engine::bot::BotDifficulty::Medium
-            } else {
-                crate::engine::bot::BotDifficulty::Easy
-            };
-
-            let sender = self.sender.clone();
-            let uid = user_id.clone();
-            let gs = self.game_state.clone();
-
-            tokio::spawn(async move {
-                // Slight human-like delay
-                tokio::time::sleep(tokio::time::Duration::from_millis(1500)).await;
-                if let Some(action) = crate::engine::bot::play_bot_turn(&gs, &uid, diff) {
-                    let _ = sender.send(RoomEvent::PlayerAction(uid, action)).await;
+    async fn handle_event(&mut self, event: RoomEvent, bot_action_pending: &mut bool) {
+        match event {
+            RoomEvent::PlayerJoined(user_id, device_id, sender, locale, mode) => {
+                println!("Player {} joined room {}", user_id, self.id);
+                self.player_locales.insert(user_id.clone(), locale);
+                if let Some(devices) = self.player_channels.get_mut(&user_id) {
+                    // Another device of a player already at the table
+                    let handed_over = devices.attach(device_id.clone(), sender, mode);
+                    let in_control = devices.controls(&device_id);
+                    self.send_seat_control(&user_id, &device_id, in_control)
+                        .await;
+                    if let Some(previous) = handed_over {
+                        self.send_seat_control(&user_id, &previous, false).await;
+                    }
+                    self.send_state_to_user(&user_id).await;
+                    self.send_rule_vote_to_user(&user_id).await;
+                    self.send_maintenance_to_user(&user_id).await;
+                    return;
                 }
-            });
-        }
-    }
-
-    async fn handle_action(
-        &mut self,
-        user_id: String,
-        action: ClientMessage,
-    ) -> Option<crate::engine::game::RoundEndResult> {
-        // Enforce turn:
-        let current_player_index = self.game_state.current_turn;
-        if self.players.get(current_player_index) != Some(&user_id) {
-            self.send_error(&user_id, "Not your turn").await;
-            return None;
-        }
-
-        match action {
-            ClientMessage::DrawFromDeck => {
-                if let Err(e) = self.game_state.draw_from_deck() {
-                    self.send_error(&user_id, e).await;
+                self.player_channels
+                    .insert(user_id.clone(), Devices::new(device_id, sender));
+                // Picked up afresh on every join, so changes show after a reconnect
+                let cosmetics = cosmetics::load(&self.services.db, &user_id).await;
+                self.cosmetics.insert(user_id.clone(), cosmetics);
+                match repo::get_avatar_version(&self.services.db, &user_id).await {
+                    Some(version) => {
+                        let url = avatars::avatar_url(&user_id, version);
+                        self.avatar_urls.insert(user_id.clone(), url)
+                    }
+                    None => self.avatar_urls.remove(&user_id),
+                };
+                match titles::shown(&self.services.db, &user_id).await {
+                    Some(title) => self.titles.insert(user_id.clone(), title),
+                    None => self.titles.remove(&user_id),
+                };
+                let status = if self.dropped_connections.contains(&user_id) {
+                    ConnectionStatus::Reconnected
+                } else {
+                    ConnectionStatus::Connected
+                };
+                self.broadcast_connection(&user_id, status).await;
+                self.substituted.remove(&user_id);
+                if self.awaiting_reconnect.remove(&user_id).is_some() {
+                    self.sync_pause().await;
                 }
-                None
+                self.broadcast_state().await;
+                self.send_rule_vote_to_user(&user_id).await;
+                self.send_maintenance_to_user(&user_id).await;
             }
-            ClientMessage::DrawFromDiscard => {
-                if let Err(e) = self.game_state.draw_from_discard() {
-                    self.send_error(&user_id, e).await;
+            RoomEvent::PlayerLeft(user_id, device_id) => {
+                if let Some(devices) = self.player_channels.get_mut(&user_id) {
+                    let took_over = devices.detach(&device_id);
+                    if !devices.is_empty() {
+                        if let Some(next) = took_over {
+                            self.send_seat_control(&user_id, &next, true).await;
+                        }
+                        return;
+                    }
                 }
-                None
+                println!("Player {} left room {}", user_id, self.id);
+                self.player_channels.remove(&user_id);
+                self.player_locales.remove(&user_id);
+                self.connection_quality.remove(&user_id);
+                self.dropped_connections.insert(user_id.clone());
+                self.broadcast_connection(&user_id, ConnectionStatus::Disconnected)
+                    .await;
+                self.pause_for(&user_id).await;
             }
-            ClientMessage::Discard { payload } => {
-                match self.game_state.discard(payload.card_index) {
-                    Ok(round_result) => round_result,
-                    Err(e) => {
-                        self.send_error(&user_id, e).await;
-                        None
-                    }
+            RoomEvent::SpectatorJoined(spectator_id, sender) => {
+                if !self.spectators_allowed {
+                    // Dropping the sender ends the spectator's stream
+                    let _ = sender.send(ServerMessage::SpectatingDisallowed).await;
+                    return;
                 }
+                println!("Spectator {} joined room {}", spectator_id, self.id);
+                let state = self.build_state_message(None);
+                self.spectators.join(spectator_id, sender, state);
+                self.report_listing();
+                self.announce_spectators().await;
             }
-            ClientMessage::DropHand { payload } => {
-                if let Err(e) = self.game_state.drop_hand(&user_id, payload.combinations) {
-                    self.send_error(&user_id, e).await;
+            RoomEvent::SpectatorLeft(spectator_id) => {
+                if self.spectators.remove(&spectator_id) {
+                    self.report_listing();
+                    self.announce_spectators().await;
                 }
-                None
             }
-            ClientMessage::ShedCard { payload } => {
-                match self.game_state.shed_card(
-                    &user_id,
-                    payload.hand_card_index,
-                    &payload.target_player_id,
-                    payload.target_combo_idx,
-                ) {
-                    Ok(round_result) => round_result,
-                    Err(e) => {
-                        self.send_error(&user_id, e).await;
-                        None
-                    }
+            RoomEvent::ObserverJoined(observer_id, sender) => {
+                println!("Observer {} attached to room {}", observer_id, self.id);
+                let _ = sender.send(self.observed_state()).await;
+                self.observers.insert(observer_id, sender);
+            }
+            RoomEvent::ObserverLeft(observer_id) => {
+                self.observers.remove(&observer_id);
+            }
+            RoomEvent::HasPlayer(user_id, reply) => {
+                let _ = reply.send(self.players.contains(&user_id));
+            }
+            RoomEvent::AllowsSpectators(reply) => {
+                let _ = reply.send(self.spectators_allowed);
+            }
+            RoomEvent::DescribeSeat(user_id, reply) => {
+                let _ = reply.send(self.describe_seat(&user_id));
+            }
+            RoomEvent::ConnectionQuality(user_id, quality) => {
+                if self.player_channels.contains_key(&user_id)
+                    && self.connection_quality.insert(user_id, quality) != Some(quality)
+                {
+                    self.broadcast_state().await;
                 }
             }
-            ClientMessage::ReorderHand { payload } => {
-                if let Err(e) = self.game_state.reorder_hand(&user_id, payload.hand) {
-                    println!(
-                        "[Room {}] Rejected reorder from {}: {}",
-                        self.id, user_id, e
-                    );
-                    self.send_error(&user_id, e).await;
-                    // Forcefully resync the offending client with the source of truth
-                    self.send_state_to_user(&user_id).await;
+            RoomEvent::RoundTrip(user_id, rtt) => {
+                if self.players.contains(&user_id) {
+                    self.turn_timer.set_round_trip(&user_id, rtt);
                 }
-                None
             }
-            ClientMessage::ReadyForNextRound => {
-                if let Err(e) = self.game_state.mark_player_ready(&user_id) {
-                    self.send_error(&user_id, e).await;
+            RoomEvent::Maintenance(notice) => {
+                self.maintenance = notice.clone();
+                let msg = ServerMessage::ServerMaintenance { notice };
+                self.broadcast(&msg).await;
+            }
+            RoomEvent::Suspend(reply) => self.suspend(reply).await,
+            // Answered by the run loop, which owns shutting the room down
+            RoomEvent::CloseIfFinished(reply) => {
+                let _ = reply.send(false);
+            }
+            RoomEvent::PlayerAction(user_id, action, correlation_id, device_id) => {
+                // Tags any error the action causes, until it has been handled
+                self.correlation_id = correlation_id;
+                if let Some(device_id) = device_id
+                    && !self.device_controls(&user_id, &device_id)
+                {
+                    self.send_device_error(&user_id, &device_id, "mirroring_device")
+                        .await;
+                    self.correlation_id = None;
+                    return;
+                }
+                if user_id.starts_with("bot_") {
+                    *bot_action_pending = false;
+                } else {
+                    self.last_human_action = Instant::now();
+                }
+                if action.arranges_hand() {
+                    self.arrange_hand(user_id, action).await;
+                    self.correlation_id = None;
+                    return;
+                }
+                // Moves index into the hand as the player last arranged it
+                self.apply_pending_reorder(&user_id).await;
+
+                let is_out_of_turn = action.is_out_of_turn();
+                if self.turn_holder.as_ref() == Some(&user_id) {
+                    self.idle_push_at = self.idle_push_deadline(&user_id);
+                }
+                self.process_action(user_id, action).await;
+                self.correlation_id = None;
+                if !is_out_of_turn {
+                    self.sync_turn_timer();
+                    self.broadcast_state().await;
                 }
-                None
             }
         }
     }
 
-    async fn send_error(&self, user_id: &str, msg: &str) {
-        if let Some(sender) = self.player_channels.get(user_id) {
-            let _ = sender
-                .send(ServerMessage::Error {
-                    message: msg.to_string(),
-                })
-                .await;
+    /// Handles a reorder or sort, which only the arranging player needs to hear about.
+    /// Bursts of reorders (a card dragged across the hand) collapse into the last one.
+    async fn arrange_hand(&mut self, user_id: String, action: ClientMessage) {
+        let count = self
+            .arrangements_this_turn
+            .entry(user_id.clone())
+            .or_default();
+        *count += 1;
+        if *count > MAX_ARRANGEMENTS_PER_TURN {
+            self.pending_reorders.remove(&user_id);
+            let e = "too_many_reorders";
+            self.send_error(&user_id, e).await;
+            self.send_state_to_user(&user_id).await;
+            return;
+        }
+
+        match action {
+            ClientMessage::ReorderHand { payload } => {
+                let due = Instant::now() + REORDER_COALESCE;
+                self.pending_reorders.insert(user_id, (payload.hand, due));
+            }
+            action => {
+                // A sort starts from whatever order was asked for last
+                self.apply_pending_reorder(&user_id).await;
+                self.process_action(user_id.clone(), action).await;
+                self.send_state_to_user(&user_id).await;
+            }
         }
     }
 
-    async fn send_state_to_user(&self, user_id: &str) {
-        if let Some((_, msg)) = self.build_state_message_for_user(user_id)
-            && let Some(sender) = self.player_channels.get(user_id)
+    /// Opens the transcript's entry for a round that was just dealt, streaming the deal to
+    /// the table first when the room is set to.
+    async fn sync_round(&mut self) {
+        let rounds = self.transcript.rounds.len();
+        self.transcript.sync_round(&self.game_state);
+        if self.transcript.rounds.len() == rounds {
+            return;
+        }
+        if let Some(seed) = self.game_state.round_seed {
+            self.round_seeds.push(RoundSeed {
+                round_index: self.game_state.round_index,
+                seed,
+            });
+        }
+        if self.services.stream_deals {
+            self.stream_deal().await;
+        }
+    }
+
+    /// One `CardDealt` per card, round the table the way a dealer hands them out.
+    async fn stream_deal(&self) {
+        let Some(round) = self.transcript.rounds.last() else {
+            return;
+        };
+        let hand_size = round.deal.iter().map(|hand| hand.cards.len()).max();
+        for dealt in 0..hand_size.unwrap_or(0) {
+            for hand in round.deal.iter().filter(|hand| hand.cards.len() > dealt) {
+                let msg = ServerMessage::CardDealt {
+                    to: hand.player_id.clone(),
+                    count: 1,
+                };
+                self.broadcast(&msg).await;
+            }
+        }
+    }
+
+    /// Applies the order `user_id` is still waiting on, if any.
+    async fn apply_pending_reorder(&mut self, user_id: &str) {
+        let Some((hand, _)) = self.pending_reorders.remove(user_id) else {
+            return;
+        };
+        let action = ClientMessage::ReorderHand {
+            payload: ReorderHandPayload { hand },
+        };
+        if self.process_action(user_id.to_string(), action).await {
+            self.send_state_to_user(user_id).await;
+        }
+    }
+
+    /// Applies an action, records it in the audit log and announces a finished round.
+    /// Returns whether the action was accepted.
+    async fn process_action(&mut self, user_id: String, action: ClientMessage) -> bool {
+        let audited_action = action.clone();
+        // Explained from the position the bot chose its move in, sent once it is accepted
+        let explanation = (self.teaching && user_id.starts_with("bot_"))
+            .then(|| {
+                let difficulty = bot_identity::difficulty_of(&user_id);
+                let action = action.as_action()?;
+                bot::explain_action(&self.game_state, &user_id, difficulty, &action)
+            })
+            .flatten();
+        let starts_turn = !user_id.starts_with("bot_")
+            && matches!(
+                action,
+                ClientMessage::DrawFromDeck
+                    | ClientMessage::DrawFromDiscard
+                    | ClientMessage::PassTurn
+            );
+        let turn_start = starts_turn.then(|| self.game_state.clone());
+        let outcome = self.handle_action(user_id.clone(), action).await;
+        if outcome.is_ok()
+            && let Some(input) = replay_input(&user_id, &audited_action)
+        {
+            self.record_replay(input);
+        }
+        if outcome.is_ok()
+            && user_id.starts_with("bot_")
+            && matches!(audited_action, ClientMessage::DropHand { .. })
+            && let Some(bot) = self.game_state.players.iter().find(|p| p.id == user_id)
+        {
+            self.bajada_turns
+                .insert(user_id.clone(), bot.turns_played + 1);
+        }
+        if let Ok(round_end) = &outcome {
+            if let Some(start) = turn_start {
+                self.turn_start = Some((user_id.clone(), start));
+            }
+            self.review_turn(&user_id, round_end.as_ref());
+        }
+        if outcome.is_ok()
+            && let Some(text) = explanation
         {
-            let _ = sender.send(msg).await;
+            let msg = ServerMessage::BotExplanation {
+                player_id: user_id.clone(),
+                action: tutorial::action_type(&audited_action).unwrap_or_default(),
+                text,
+            };
+            for devices in self.player_channels.values() {
+                devices.send(msg.clone()).await;
+            }
+        }
+        self.services.audit.record_action(
+            &self.id,
+            &user_id,
+            &audited_action,
+            outcome.as_ref().err().copied(),
+        );
+        if !self.observers.is_empty() {
+            let msg = ServerMessage::ObservedAction {
+                user_id: user_id.clone(),
+                action: audited_action.clone(),
+                error: outcome.as_ref().err().map(|e| e.to_string()),
+                created_at_ms: SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap()
+                    .as_millis() as i64,
+            };
+            for sender in self.observers.values() {
+                let _ = sender.send(msg.clone()).await;
+            }
+        }
+        if outcome.is_ok()
+            && !user_id.starts_with("bot_")
+            && let Some(tutorial) = self.tutorial.as_mut()
+        {
+            tutorial.on_learner_action(&audited_action);
+        }
+        if outcome.is_ok() && audited_action.is_move() {
+            self.transcript.record_move(&self.game_state);
+        }
+        match outcome {
+            Ok(Some(result)) => {
+                self.finish_round(result).await;
+                true
+            }
+            Ok(None) => {
+                // Readying up may have dealt the next round
+                self.sync_round().await;
+                true
+            }
+            Err(_) => false,
         }
     }
 
-    fn build_state_message_for_user(
-        &self,
-        target_user_id: &str,
-    ) -> Option<(String, ServerMessage)> {
-        let sanitized_players: Vec<SanitizedPlayerState> = self
-            .game_state
-            .players
-            .iter()
-            .map(SanitizedPlayerState::from_player_state)
-            .collect();
+    async fn finish_round(&mut self, result: crate::engine::game::RoundEndResult) {
+        self.transcript.end_round(&result);
+        self.record_bot_round(&result);
+        self.broadcast_round_ended(&result).await;
+        if result.is_game_over {
+            self.notify_game_finished(&result);
+            self.verify_replay();
+            let final_totals: Vec<(String, u32)> = result
+                .player_scores
+                .iter()
+                .map(|(id, _, total)| (id.clone(), *total))
+                .collect();
+            let rounds_played = result.finished_round_index as i64 + 1;
+            let podium = Podium::new(&final_totals, &self.transcript, result.tie_break.clone());
+            let recorded = self.record_result("completed", true, rounds_played, final_totals);
+            let audience: Vec<_> = self.audience().cloned().collect();
+            let spectators = self.spectators.senders();
+            let play_review: Vec<PlayReview> = self
+                .players
+                .iter()
+                .filter_map(|id| self.play_reviews.get(id).cloned())
+                .collect();
+            tokio::spawn(async move {
+                let rating_changes = recorded.await.unwrap_or_default();
+                let msg = ServerMessage::GameOver {
+                    podium,
+                    rating_changes,
+                    play_review,
+                };
+                for sender in audience {
+                    let _ = sender.send(msg.clone()).await;
+                }
+                for sender in spectators {
+                    let _ = sender.try_send(msg.clone());
+                }
+            });
+        }
+        if self.game_state.is_waiting_for_next_round {
+            self.start_ready_countdown().await;
+        }
+    }
 
-        let top_discard = self.game_state.discard_pile.last().cloned();
+    /// Runs the clock for whoever holds the turn, or stops it between rounds.
+    fn sync_turn_timer(&mut self) {
+        let now = Instant::now();
+        let in_play = !self.game_state.is_game_over && !self.game_state.is_waiting_for_next_round;
+        match self.players.get(self.game_state.current_turn) {
+            Some(player_id) if in_play => self.turn_timer.start_turn(player_id, now),
+            _ => self.turn_timer.stop(now),
+        }
+        if !self.game_state.is_waiting_for_next_round {
+            self.ready_deadline = None;
+        }
 
-        let my_hand = self
-            .game_state
+        let holder = self
             .players
-            .iter()
-            .find(|p| p.id == target_user_id)
-            .map(|p| p.hand.clone())
-            .unwrap_or_default();
+            .get(self.game_state.current_turn)
+            .filter(|_| in_play)
+            .cloned();
+        if holder != self.turn_holder {
+            self.arrangements_this_turn.clear();
+            self.turn_holder = holder.clone();
+            self.idle_push_at = holder.as_deref().and_then(|h| self.idle_push_deadline(h));
+            if let Some(holder) = holder {
+                let event = WebhookEvent::YourTurn {
+                    room_id: self.id.clone(),
+                };
+                self.notify_if_away(&holder, event);
+            }
+        }
+    }
 
-        let msg = ServerMessage::GameStateUpdate {
-            my_hand,
-            players: sanitized_players,
-            current_round_index: self.game_state.round_index,
-            current_round_rules: self.game_state.current_round.description().to_string(),
-            current_turn_index: self.game_state.current_turn,
-            discard_pile_top: top_discard,
-            is_game_over: self.game_state.is_game_over,
-            is_waiting_for_next_round: self.game_state.is_waiting_for_next_round,
-            required_trios: self.game_state.current_round.get_requirements().0,
-            required_escalas: self.game_state.current_round.get_requirements().1,
-            last_action: self.game_state.last_action.clone(),
-        };
+    fn idle_push_deadline(&self, user_id: &str) -> Option<Instant> {
+        let idle = self.services.push_idle_turn;
+        (!idle.is_zero() && !user_id.starts_with("bot_")).then(|| Instant::now() + idle)
+    }
 
-        Some((target_user_id.to_string(), msg))
+    /// Sends `event` to a human player's webhook, unless they are connected and already see it.
+    fn notify_if_away(&self, user_id: &str, event: WebhookEvent) {
+        if user_id.starts_with("bot_")
+            || self
+                .player_channels
+                .contains_key(self.connection_of(user_id))
+        {
+            return;
+        }
+        self.services.notifier.notify(user_id, event);
     }
 
-    async fn broadcast_round_ended(&self, result: &crate::engine::game::RoundEndResult) {
-        let msg = ServerMessage::RoundEnded {
-            round_index: result.finished_round_index,
-            round_name: result.finished_round_name.clone(),
-            winner_id: result.winner_id.clone(),
-            player_scores: result
-                .player_scores
-                .iter()
-                .map(|(id, rp, tp)| PlayerScore {
-                    id: id.clone(),
-                    round_points: *rp,
-                    total_points: *tp,
-                })
-                .collect(),
-            next_round_index: result.next_round_index,
-            next_round_name: result.next_round_name.clone(),
-            is_game_over: result.is_game_over,
+    fn describe_seat(&self, user_id: &str) -> Option<SeatSummary> {
+        let state = &self.game_state;
+        if state.is_game_over || !self.players.iter().any(|id| id == user_id) {
+            return None;
+        }
+        let hot_seats = if self.hot_seats.first().map(String::as_str) == Some(user_id) {
+            self.hot_seats.clone()
+        } else {
+            Vec::new()
         };
+        Some(SeatSummary {
+            players: self.players.clone(),
+            hot_seats,
+            regions: self.regions.clone(),
+            round_index: state.round_index,
+            round_name: state.current_round.description().to_string(),
+            current_turn: self
+                .players
+                .get(state.current_turn)
+                .filter(|_| !state.is_waiting_for_next_round)
+                .cloned(),
+        })
+    }
 
-        for sender in self.player_channels.values() {
-            let _ = sender.send(msg.clone()).await;
+    /// A finished game with no players or spectators left to show it to.
+    fn is_finished(&self) -> bool {
+        self.game_state.is_game_over
+            && self.player_channels.is_empty()
+            && self.spectators.is_empty()
+            && self.observers.is_empty()
+    }
+
+    /// Regular matchmade games count towards the season; tutorials, hot-seat tables,
+    /// staged positions and instant play don't.
+    fn is_ranked(&self) -> bool {
+        self.tutorial.is_none() && self.hot_seats.is_empty() && !self.staged && !self.casual
+    }
+
+    /// Stores how each bot did in the round, if it played against humans in a real game.
+    fn record_bot_round(&mut self, result: &crate::engine::game::RoundEndResult) {
+        let bajada_turns = std::mem::take(&mut self.bajada_turns);
+        let against_humans = self.players.iter().any(|id| !id.starts_with("bot_"));
+        if !against_humans || self.tutorial.is_some() || self.staged {
+            return;
+        }
+        let finished_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as i64;
+        let stats: Vec<BotRoundStat> = result
+            .player_scores
+            .iter()
+            .filter(|(id, _, _)| id.starts_with("bot_"))
+            .map(|(id, round_points, _)| BotRoundStat {
+                room_id: self.id.clone(),
+                bot_id: id.clone(),
+                difficulty: bot_identity::difficulty_of(id).as_str().to_string(),
+                round_index: result.finished_round_index as i64,
+                went_out: result.winner_id == *id,
+                leftover_points: *round_points as i64,
+                bajada_turn: bajada_turns.get(id).map(|turn| *turn as i64),
+                finished_at,
+            })
+            .collect();
+        if stats.is_empty() {
+            return;
         }
+
+        let pool = self.services.db.clone();
+        let room_id = self.id.clone();
+        tokio::spawn(async move {
+            if let Err(e) = repo::insert_bot_round_stats(&pool, &stats).await {
+                println!("[Room {}] Failed to store bot stats: {}", room_id, e);
+            }
+        });
     }
 
-    async fn broadcast_state(&self) {
-        for user_id in self.player_channels.keys() {
-            self.send_state_to_user(user_id).await;
+    fn record_replay(&mut self, input: Input) {
+        if let Some(log) = self.replay.as_mut() {
+            log.record(input, &self.game_state);
         }
     }
+
+    /// Plays the finished game again from its seed in the background. A replay that ends up
+    /// somewhere else is stored for the admins: either the engine isn't deterministic or
+    /// the log was tampered with.
+    fn verify_replay(&mut self) {
+        let Some(log) = self.replay.take() else {
+            return;
+        };
+        let pool = self.services.db.clone();
+        let room_id = self.id.clone();
+        tokio::spawn(async move {
+            let Ok(Err(mismatch)) = tokio::task::spawn_blocking({
+                let log = log.clone();
+                move || replay::verify(&log)
+            })
+            .await
+            else {
+                return;
+            };
+            println!("[Room {}] Replay check failed: {}", room_id, mismatch);
+            let created_at = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_secs() as i64;
+            let replay = serde_json::to_string(&log).unwrap_or_default();
+            let reason = mismatch.to_string();
+            if let Err(e) =
+                repo::insert_replay_flag(&pool, &room_id, &reason, &replay, created_at).await
+            {
+                println!("[Room {}] Failed to store replay flag: {}", room_id, e);
+            }
+        });
+    }
+
+    /// Once a human's turn is over, compares what they were left holding with the best line
+    /// the oracle finds from where the turn began.
+    fn review_turn(
+        &mut self,
+        user_id: &str,
+        round_end: Option<&crate::engine::game::RoundEndResult>,
+    ) {
+        let turn_over = round_end.is_some()
+            || self
+                .players
+                .get(self.game_state.current_turn)
+                .map(String::as_str)
+                != Some(user_id);
+        if !turn_over || self.turn_start.as_ref().is_none_or(|(id, _)| id != user_id) {
+            return;
+        }
+        let Some((_, start)) = self.turn_start.take() else {
+            return;
+        };
+        let rules = &self.game_state.rules;
+        let actual = match round_end {
+            Some(result) => result
+                .final_hands
+                .iter()
+                .find(|hand| hand.player_id == user_id)
+                .map_or(0, |hand| rules.hand_points(&hand.cards)),
+            None => self
+                .game_state
+                .players
+                .iter()
+                .find(|p| p.id == user_id)
+                .map_or(0, |p| rules.hand_points(&p.hand)),
+        };
+        self.play_reviews
+            .entry(user_id.to_string())
+            .or_insert_with(|| PlayReview {
+                player_id: user_id.to_string(),
+                ..PlayReview::default()
+            })
+            .record_turn(&start, actual);
+    }
+
+    /// The rounds' shuffle seeds, sealed for the game record. `None` when no round was dealt
+    /// from a secret key.
+    fn sealed_seeds(&self) -> Option<ShuffleSeeds> {
+        if self.round_seeds.is_empty() {
+            return None;
+        }
+        let json = serde_json::to_vec(&self.round_seeds).ok()?;
+        Some(ShuffleSeeds {
+            room_id: self.id.clone(),
+            deck_jokers: self.game_state.rules.deck_jokers as i64,
+            sealed: self.services.seed_vault.seal(&json),
+        })
+    }
+
+    /// Persists the final totals and, for a ranked game whose result counts, rates it.
+    /// Stores the result in the background; the task yields the rating changes, if ranked.
+    fn record_result(
+        &self,
+        outcome: &str,
+        counted: bool,
+        rounds_played: i64,
+        final_totals: Vec<(String, u32)>,
+    ) -> JoinHandle<Vec<RatingChange>> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as i64;
+        let ranked = counted && self.is_ranked();
+        let result = GameResult {
+            room_id: self.id.clone(),
+            outcome: outcome.to_string(),
+            counted,
+            ranked,
+            rounds_played,
+            finished_at: now,
+        };
+        let players: Vec<GameResultPlayer> = final_totals
+            .iter()
+            .enumerate()
+            .map(|(seat, (id, total))| GameResultPlayer {
+                room_id: self.id.clone(),
+                user_id: id.clone(),
+                seat: seat as i64,
+                total: *total as i64,
+            })
+            .collect();
+
+        let transcript = serde_json::to_string(&self.transcript).unwrap_or_default();
+        let seeds = self.sealed_seeds();
+        // Only games played out from the deal earn titles
+        let titled = (outcome == "completed" && !self.staged && self.tutorial.is_none())
+            .then(|| self.transcript.clone());
+        // Finished practice games move the player along the adaptive ladder
+        let practiced = (self.casual && outcome == "completed")
+            .then(|| {
+                self.players
+                    .iter()
+                    .find(|id| !id.starts_with("bot_"))
+                    .cloned()
+            })
+            .flatten();
+
+        let pool = self.services.db.clone();
+        let settings = self.services.seasons;
+        let room_id = self.id.clone();
+        tokio::spawn(async move {
+            if let Err(e) = repo::insert_game_result(&pool, &result, &players, &transcript).await {
+                println!("[Room {}] Failed to store the result: {}", room_id, e);
+            }
+            if let Some(seeds) = seeds
+                && let Err(e) = repo::insert_shuffle_seeds(&pool, &seeds).await
+            {
+                println!(
+                    "[Room {}] Failed to store the shuffle seeds: {}",
+                    room_id, e
+                );
+            }
+            if let Some(user_id) = practiced
+                && let Err(e) = adaptive::record(&pool, &user_id, &final_totals).await
+            {
+                println!("[Room {}] Failed to update the bot level: {}", room_id, e);
+            }
+            if let Some(transcript) = titled
+                && let Err(e) = titles::award_for_game(&pool, &transcript, &final_totals, now).await
+            {
+                println!("[Room {}] Failed to award titles: {}", room_id, e);
+            }
+            if !ranked {
+                return Vec::new();
+            }
+            season::record_game(&pool, &final_totals, now, settings)
+                .await
+                .unwrap_or_else(|e| {
+                    println!("[Room {}] Failed to record ratings: {}", room_id, e);
+                    Vec::new()
+                })
+        })
+    }
+
+    /// When the room is abandoned unless a human acts first.
+    fn abandon_deadline(&self) -> Option<Instant> {
+        let ttl = self.services.idle_ttl;
+        (!ttl.is_zero() && !self.game_state.is_game_over).then(|| self.last_human_action + ttl)
+    }
+
+    /// Calls the game off, scoring it per the table's abandonment policy, and tells whoever
+    /// is still connected before the room closes.
+    async fn abandon(&mut self) {
+        println!(
+            "[Room {}] No human action for too long, abandoning",
+            self.id
+        );
+        let rounds_played = self.game_state.round_index as i64;
+        let final_totals = self.game_state.abandon();
+        let counted = self.game_state.rules.abandonment.counts_result();
+        self.turn_timer.stop(Instant::now());
+        self.ready_deadline = None;
+        self.idle_push_at = None;
+
+        let msg = ServerMessage::GameAbandoned {
+            player_scores: final_totals
+                .iter()
+                .map(|(id, total)| PlayerScore {
+                    id: id.clone(),
+                    round_points: 0,
+                    total_points: *total,
+                })
+                .collect(),
+            counted,
+        };
+        self.broadcast(&msg).await;
+
+        // Players still at the table when it was called off were left by those who weren't
+        let (present, gone): (Vec<String>, Vec<String>) = self
+            .humans()
+            .partition(|id| self.player_channels.contains_key(id));
+        if !gone.is_empty() {
+            self.grant_requeue_priority(present, &gone, "opponent_left");
+        }
+
+        self.record_result("abandoned", counted, rounds_played, final_totals);
+        self.abandoned = true;
+    }
+
+    /// Saves the game for after maintenance and closes the room. Nothing is recorded; the
+    /// game is neither finished nor abandoned.
+    async fn suspend(&mut self, reply: oneshot::Sender<Option<GameSnapshot>>) {
+        // Nothing worth saving before the deal
+        if self.game_state.is_game_over || self.rule_vote.is_some() {
+            let _ = reply.send(None);
+            return;
+        }
+        println!("[Room {}] Saving the game for maintenance", self.id);
+        let _ = reply.send(Some(GameSnapshot::from_state(&self.game_state)));
+        self.turn_timer.stop(Instant::now());
+        self.ready_deadline = None;
+        self.idle_push_at = None;
+
+        let msg = ServerMessage::GameSuspended {
+            notice: self.maintenance.clone(),
+        };
+        self.broadcast(&msg).await;
+        self.grant_requeue_priority(self.humans().collect(), &[], "server");
+        self.abandoned = true;
+    }
+
+    /// Human seats; none at hot-seat tables, which don't go through matchmaking.
+    fn humans(&self) -> impl Iterator<Item = String> + '_ {
+        self.players
+            .iter()
+            .filter(|id| self.hot_seats.is_empty() && !id.starts_with("bot_"))
+            .cloned()
+    }
+
+    /// Puts `affected` first in line the next time they search for a game, kept apart from
+    /// `abandoners` if they ask to be.
+    fn grant_requeue_priority(&self, affected: Vec<String>, abandoners: &[String], reason: &str) {
+        if affected.is_empty() {
+            return;
+        }
+        let granted_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as i64;
+        let abandoners = serde_json::to_string(abandoners).unwrap_or_default();
+        let priorities: Vec<RequeuePriority> = affected
+            .into_iter()
+            .map(|user_id| RequeuePriority {
+                user_id,
+                abandoners: abandoners.clone(),
+                reason: reason.to_string(),
+                granted_at,
+            })
+            .collect();
+        let pool = self.services.db.clone();
+        let room_id = self.id.clone();
+        tokio::spawn(async move {
+            for priority in &priorities {
+                if let Err(e) = repo::grant_requeue_priority(&pool, priority).await {
+                    println!("[Room {}] Failed to grant requeue priority: {}", room_id, e);
+                }
+            }
+        });
+    }
+
+    async fn send_maintenance_to_user(&self, user_id: &str) {
+        if let Some(notice) = &self.maintenance
+            && let Some(devices) = self.player_channels.get(user_id)
+        {
+            let msg = ServerMessage::ServerMaintenance {
+                notice: Some(notice.clone()),
+            };
+            devices.send(msg).await;
+        }
+    }
+
+    fn notify_game_finished(&self, result: &crate::engine::game::RoundEndResult) {
+        let best = result
+            .player_scores
+            .iter()
+            .map(|(_, _, total)| *total)
+            .min();
+        let broken_tie = result.tie_break.as_ref().and_then(|t| t.winner_id.clone());
+        let event = WebhookEvent::GameFinished {
+            room_id: self.id.clone(),
+            winner_ids: match broken_tie {
+                Some(winner_id) => vec![winner_id],
+                None => result
+                    .player_scores
+                    .iter()
+                    .filter(|(_, _, total)| Some(*total) == best)
+                    .map(|(id, _, _)| id.clone())
+                    .collect(),
+            },
+            player_scores: result
+                .player_scores
+                .iter()
+                .map(|(id, rp, tp)| PlayerScore {
+                    id: id.clone(),
+                    round_points: *rp,
+                    total_points: *tp,
+                })
+                .collect(),
+        };
+        for player_id in &self.players {
+            self.notify_if_away(player_id, event.clone());
+            if !player_id.starts_with("bot_") {
+                let push = PushEvent::GameOver {
+                    room_id: self.id.clone(),
+                };
+                self.services.notifier.push(player_id, push);
+            }
+        }
+    }
+
+    async fn start_ready_countdown(&mut self) {
+        let countdown = self.services.ready_countdown;
+        if countdown.is_zero() {
+            return;
+        }
+        self.ready_deadline = Some(Instant::now() + countdown);
+
+        let deadline_epoch_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as u64
+            + countdown.as_millis() as u64;
+        let msg = ServerMessage::NextRoundCountdown {
+            deadline_epoch_ms,
+            seconds: countdown.as_secs(),
+        };
+        self.broadcast(&msg).await;
+    }
+
+    async fn on_timer_tick(&mut self) {
+        if self
+            .abandon_deadline()
+            .is_some_and(|deadline| Instant::now() >= deadline)
+        {
+            self.abandon().await;
+            return;
+        }
+
+        if self
+            .rule_vote
+            .as_ref()
+            .is_some_and(|vote| Instant::now() >= vote.deadline)
+        {
+            self.close_rule_vote().await;
+        }
+
+        let now = Instant::now();
+        let out_of_grace: Vec<String> = self
+            .awaiting_reconnect
+            .iter()
+            .filter(|(_, deadline)| now >= **deadline)
+            .map(|(id, _)| id.clone())
+            .collect();
+        if !out_of_grace.is_empty() {
+            for user_id in out_of_grace {
+                println!(
+                    "[Room {}] {} did not reconnect in time, bots take over",
+                    self.id, user_id
+                );
+                self.awaiting_reconnect.remove(&user_id);
+                self.substituted.insert(user_id);
+            }
+            self.sync_pause().await;
+        }
+        if self.break_until.is_some_and(|deadline| now >= deadline) {
+            self.break_until = None;
+            self.sync_pause().await;
+        }
+        if self
+            .pause_vote
+            .as_ref()
+            .is_some_and(|(_, deadline)| now >= *deadline)
+        {
+            self.pause_vote = None;
+        }
+
+        let settled: Vec<String> = self
+            .pending_reorders
+            .iter()
+            .filter(|(_, (_, due))| now >= *due)
+            .map(|(id, _)| id.clone())
+            .collect();
+        for user_id in settled {
+            self.apply_pending_reorder(&user_id).await;
+        }
+
+        if self
+            .idle_push_at
+            .is_some_and(|deadline| Instant::now() >= deadline)
+        {
+            self.idle_push_at = None;
+            if let Some(holder) = &self.turn_holder {
+                let event = PushEvent::YourTurn {
+                    room_id: self.id.clone(),
+                };
+                self.services.notifier.push(holder, event);
+            }
+        }
+
+        if !self.is_paused()
+            && self
+                .ready_deadline
+                .is_some_and(|deadline| Instant::now() >= deadline)
+        {
+            self.ready_deadline = None;
+            if self.game_state.force_ready_all().is_ok() {
+                self.record_replay(Input::ForceReady);
+                self.sync_round().await;
+                println!(
+                    "[Room {}] Ready countdown elapsed, starting next round",
+                    self.id
+                );
+                self.sync_turn_timer();
+                self.broadcast_state().await;
+            }
+        }
+
+        for event in self.turn_timer.poll(Instant::now()) {
+            match event {
+                TimerEvent::Warning {
+                    player_id,
+                    remaining,
+                } => {
+                    let msg = ServerMessage::TurnWarning {
+                        player_id,
+                        remaining_ms: remaining.as_millis() as u64,
+                    };
+                    self.broadcast(&msg).await;
+                }
+                TimerEvent::Expired { player_id } => match self.game_state.rules.timeout {
+                    TimeoutPolicy::AutoPlay => self.auto_play_turn(&player_id).await,
+                    TimeoutPolicy::PenaltyDraw => self.skip_timed_out_turn(&player_id).await,
+                },
+            }
+        }
+    }
+
+    fn is_paused(&self) -> bool {
+        !self.awaiting_reconnect.is_empty() || self.break_until.is_some()
+    }
+
+    /// Freezes the game while `user_id`, who just dropped, gets a chance to come back.
+    async fn pause_for(&mut self, user_id: &str) {
+        let grace = self.services.disconnect_grace;
+        // The tutor waits for its learner anyway
+        if grace.is_zero()
+            || self.tutorial.is_some()
+            || self.game_state.is_game_over
+            || self.awaiting_reconnect.contains_key(user_id)
+        {
+            return;
+        }
+        self.awaiting_reconnect
+            .insert(user_id.to_string(), Instant::now() + grace);
+        self.sync_pause().await;
+
+        let msg = ServerMessage::GamePaused {
+            player_id: user_id.to_string(),
+            resume_deadline_epoch_ms: epoch_ms() + grace.as_millis() as u64,
+        };
+        self.broadcast(&msg).await;
+    }
+
+    /// Freezes or restarts the clock and the engine as reasons to pause come and go. Play
+    /// restarts once nobody is left to wait for and no break is running.
+    async fn sync_pause(&mut self) {
+        let paused = self.is_paused();
+        if paused == self.game_state.is_paused {
+            return;
+        }
+        self.game_state.is_paused = paused;
+        let now = Instant::now();
+        if paused {
+            self.turn_timer.pause(now);
+            return;
+        }
+        self.turn_timer.resume(now);
+
+        let mut substituted: Vec<String> = self.substituted.iter().cloned().collect();
+        substituted.sort();
+        let msg = ServerMessage::GameResumed { substituted };
+        self.broadcast(&msg).await;
+        self.broadcast_state().await;
+    }
+
+    /// Keeps the table moving for players who dropped and didn't come back in time: plays
+    /// their turns and readies them up between rounds.
+    async fn play_for_substituted(&mut self) {
+        if self.is_paused()
+            || self.rule_vote.is_some()
+            || self.substituted.is_empty()
+            || self.game_state.is_game_over
+        {
+            return;
+        }
+
+        if self.game_state.is_waiting_for_next_round {
+            let absent: Vec<String> = self
+                .game_state
+                .players
+                .iter()
+                .filter(|p| !p.is_ready_for_next_round)
+                .filter(|p| self.substituted.contains(self.connection_of(&p.id)))
+                .map(|p| p.id.clone())
+                .collect();
+            if absent.is_empty() {
+                return;
+            }
+            for player_id in absent {
+                if self.game_state.mark_player_ready(&player_id).is_ok() {
+                    self.record_replay(Input::Ready { player_id });
+                }
+            }
+            self.sync_round().await;
+            self.sync_turn_timer();
+            self.broadcast_state().await;
+            return;
+        }
+
+        let holder = self
+            .players
+            .get(self.game_state.current_turn)
+            .filter(|id| self.substituted.contains(self.connection_of(id)))
+            .cloned();
+        if let Some(holder) = holder {
+            self.auto_play_turn(&holder).await;
+        }
+    }
+
+    /// Plays out a timed-out turn with the easy bot so the table keeps moving.
+    async fn auto_play_turn(&mut self, user_id: &str) {
+        println!(
+            "[Room {}] {} ran out of time, auto-playing",
+            self.id, user_id
+        );
+
+        while !self.game_state.is_game_over
+            && !self.game_state.is_waiting_for_next_round
+            && self
+                .players
+                .get(self.game_state.current_turn)
+                .map(String::as_str)
+                == Some(user_id)
+        {
+            let Some(action) = crate::engine::bot::play_bot_turn(
+                &self.game_state,
+                user_id,
+                crate::engine::bot::BotDifficulty::Easy,
+            ) else {
+                break;
+            };
+            if !self
+                .process_action(user_id.to_string(), action.into())
+                .await
+            {
+                break;
+            }
+        }
+
+        self.sync_turn_timer();
+        self.broadcast_state().await;
+    }
+
+    /// Makes `user_id` take a penalty card and lose the turn they ran out of time on.
+    async fn skip_timed_out_turn(&mut self, user_id: &str) {
+        println!(
+            "[Room {}] {} ran out of time, drawing a penalty card",
+            self.id, user_id
+        );
+
+        match self.game_state.skip_timed_out_turn() {
+            Ok(result) => {
+                self.record_replay(Input::TimedOut);
+                self.transcript.record_move(&self.game_state);
+                if let Some(result) = result {
+                    self.finish_round(result).await;
+                }
+            }
+            Err(e) => println!("[Room {}] Could not skip the turn: {}", self.id, e),
+        }
+
+        self.sync_turn_timer();
+        self.broadcast_state().await;
+    }
+
+    /// Prompts the tutorial learner with the current step, once it is their move.
+    async fn send_tutorial_hint(&mut self) {
+        let Some(learner) = self.players.first() else {
+            return;
+        };
+        let Some(devices) = self.player_channels.get(learner) else {
+            return;
+        };
+        let locale = self.locale_of(learner);
+        let Some(hint) = self
+            .tutorial
+            .as_mut()
+            .and_then(|tutorial| tutorial.pending_hint(locale))
+        else {
+            return;
+        };
+        devices
+            .send(ServerMessage::TutorialHint {
+                step: hint.step,
+                text: hint.text,
+                expect: hint.expect,
+            })
+            .await;
+    }
+
+    fn check_bot_turn(&mut self, bot_action_pending: &mut bool) {
+        if *bot_action_pending || self.is_paused() || self.rule_vote.is_some() {
+            return;
+        }
+
+        let current_player_index = self.game_state.current_turn;
+        if let Some(user_id) = self.players.get(current_player_index)
+            && user_id.starts_with("bot_")
+        {
+            *bot_action_pending = true;
+
+            let diff = bot_identity::difficulty_of(user_id);
+
+            let sender = self.sender.clone();
+            let uid = user_id.clone();
+            let gs = self.game_state.clone();
+            let scripted = self
+                .tutorial
+                .as_mut()
+                .and_then(|tutorial| tutorial.next_bot_action());
+
+            tokio::spawn(async move {
+                // Slight human-like delay
+                tokio::time::sleep(tokio::time::Duration::from_millis(1500)).await;
+                let action = scripted
+                    .or_else(|| crate::engine::bot::play_bot_turn(&gs, &uid, diff).map(Into::into));
+                if let Some(action) = action {
+                    let _ = sender
+                        .send(RoomEvent::PlayerAction(uid, action, None, None))
+                        .await;
+                }
+            });
+        }
+    }
+
+    async fn handle_action(
+        &mut self,
+        user_id: String,
+        action: ClientMessage,
+    ) -> Result<Option<crate::engine::game::RoundEndResult>, &'static str> {
+        if self.rule_vote.is_some() && !action.is_out_of_turn() {
+            let e = "rule_vote_running";
+            self.send_error(&user_id, e).await;
+            return Err(e);
+        }
+        // Enforce turn (chat, reports and readying up are allowed at any time):
+        let current_player_index = self.game_state.current_turn;
+        if action.requires_turn() && self.players.get(current_player_index) != Some(&user_id) {
+            self.send_error(&user_id, "not_your_turn").await;
+            return Err("not_your_turn");
+        }
+
+        match action {
+            ClientMessage::DrawFromDeck => {
+                if let Err(e) = self.game_state.draw_from_deck() {
+                    self.send_error(&user_id, e).await;
+                    return Err(e);
+                }
+                Ok(None)
+            }
+            ClientMessage::DrawFromDiscard => {
+                if let Err(e) = self.game_state.draw_from_discard() {
+                    self.send_error(&user_id, e).await;
+                    return Err(e);
+                }
+                Ok(None)
+            }
+            ClientMessage::Discard { payload } => {
+                let result = self.game_state.discard(payload.card_index);
+                if let Err(e) = result {
+                    self.send_error(&user_id, e).await;
+                }
+                result
+            }
+            ClientMessage::PassTurn => {
+                let result = self.game_state.pass_turn();
+                if let Err(e) = result {
+                    self.send_error(&user_id, e).await;
+                }
+                result
+            }
+            ClientMessage::DropHand { payload } => {
+                if let Err(e) = self.game_state.drop_hand(&user_id, payload.combinations) {
+                    self.send_error(&user_id, e).await;
+                    return Err(e);
+                }
+                let combinations = self
+                    .game_state
+                    .players
+                    .iter()
+                    .find(|p| p.id == user_id)
+                    .map(|p| p.dropped_combinations.clone())
+                    .unwrap_or_default();
+                let msg = ServerMessage::PlayerDroppedHand {
+                    player_id: user_id.clone(),
+                    combinations,
+                };
+                self.broadcast(&msg).await;
+                Ok(None)
+            }
+            ClientMessage::ShedCard { payload } => {
+                let result = self.game_state.shed_card(
+                    &user_id,
+                    payload.hand_card_index,
+                    &payload.target_player_id,
+                    payload.target_combo_idx,
+                    payload.position,
+                );
+                if let Err(e) = result {
+                    self.send_error(&user_id, e).await;
+                    return result;
+                }
+                let card = self.game_state.last_action.as_ref().and_then(|a| a.card);
+                let combination = self
+                    .game_state
+                    .players
+                    .iter()
+                    .find(|p| p.id == payload.target_player_id)
+                    .and_then(|p| p.dropped_combinations.get(payload.target_combo_idx))
+                    .cloned();
+                if let (Some(card), Some(combination)) = (card, combination) {
+                    let msg = ServerMessage::CardShed {
+                        player_id: user_id.clone(),
+                        card,
+                        target_player_id: payload.target_player_id,
+                        target_combo_idx: payload.target_combo_idx,
+                        combination,
+                    };
+                    self.broadcast(&msg).await;
+                }
+                result
+            }
+            ClientMessage::ReorderHand { payload } => {
+                if let Err(e) = self.game_state.reorder_hand(&user_id, payload.hand) {
+                    println!(
+                        "[Room {}] Rejected reorder from {}: {}",
+                        self.id, user_id, e
+                    );
+                    self.send_error(&user_id, e).await;
+                    // Forcefully resync the offending client with the source of truth
+                    self.send_state_to_user(&user_id).await;
+                    return Err(e);
+                }
+                Ok(None)
+            }
+            ClientMessage::SortHand { payload } => {
+                if let Err(e) = self.game_state.sort_hand(&user_id, payload.by) {
+                    self.send_error(&user_id, e).await;
+                    return Err(e);
+                }
+                Ok(None)
+            }
+            ClientMessage::ReadyForNextRound => {
+                if let Err(e) = self.game_state.mark_player_ready(&user_id) {
+                    self.send_error(&user_id, e).await;
+                    return Err(e);
+                }
+                Ok(None)
+            }
+            ClientMessage::Chat { payload } => {
+                self.broadcast_chat(&user_id, &payload.text).await;
+                Ok(None)
+            }
+            ClientMessage::ReportPlayer { payload } => {
+                if let Err(e) =
+                    self.file_report(&user_id, payload.reported_player_id, payload.reason)
+                {
+                    self.send_error(&user_id, e).await;
+                    return Err(e);
+                }
+                Ok(None)
+            }
+            ClientMessage::RequestPause => {
+                if let Err(e) = self.vote_for_break(&user_id).await {
+                    self.send_error(&user_id, e).await;
+                    return Err(e);
+                }
+                Ok(None)
+            }
+            ClientMessage::DeclinePause => {
+                if self.pause_vote.take().is_none() {
+                    let e = "no_pause_requested";
+                    self.send_error(&user_id, e).await;
+                    return Err(e);
+                }
+                let msg = ServerMessage::PauseDeclined { player_id: user_id };
+                self.broadcast(&msg).await;
+                Ok(None)
+            }
+            ClientMessage::ResumeGame => {
+                if self.break_until.take().is_none() {
+                    let e = "not_on_break";
+                    self.send_error(&user_id, e).await;
+                    return Err(e);
+                }
+                self.sync_pause().await;
+                Ok(None)
+            }
+            ClientMessage::RequestHint { payload } => {
+                if let Err(e) = self.send_hint(&user_id, payload.level).await {
+                    self.send_error(&user_id, e).await;
+                    return Err(e);
+                }
+                Ok(None)
+            }
+            ClientMessage::SetLocale { payload } => {
+                let connection_id = self.connection_of(&user_id).to_string();
+                self.player_locales
+                    .insert(connection_id, Locale::parse(&payload.locale));
+                // Round names follow the new language
+                self.send_state_to_user(&user_id).await;
+                Ok(None)
+            }
+            // Answered by the connection itself, never forwarded
+            ClientMessage::Pong { .. } => Ok(None),
+            ClientMessage::AllowSpectators { payload } => {
+                let voter = self.connection_of(&user_id).to_string();
+                self.set_spectating(&voter, payload.allowed).await;
+                Ok(None)
+            }
+            ClientMessage::VoteRules { payload } => {
+                if let Err(e) = self.vote_on_rules(&user_id, payload).await {
+                    self.send_error(&user_id, e).await;
+                    return Err(e);
+                }
+                Ok(None)
+            }
+            ClientMessage::GetScores => {
+                if let Some(devices) = self.player_channels.get(self.connection_of(&user_id)) {
+                    let msg = ServerMessage::Scoreboard {
+                        seats: self.players.clone(),
+                        rounds: self.transcript.scoreboard(&self.players),
+                    };
+                    devices.send(msg).await;
+                }
+                Ok(None)
+            }
+        }
+    }
+
+    /// Records whether `user_id` lets the table be spectated. Any one objection turns
+    /// spectating off and sends away whoever is watching.
+    async fn set_spectating(&mut self, user_id: &str, allowed: bool) {
+        if allowed {
+            self.spectator_objections.remove(user_id);
+        } else {
+            self.spectator_objections.insert(user_id.to_string());
+        }
+        let now_allowed = self.spectator_objections.is_empty();
+        if now_allowed == self.spectators_allowed {
+            return;
+        }
+        self.spectators_allowed = now_allowed;
+        if !now_allowed {
+            for sender in self.spectators.drain() {
+                let _ = sender.try_send(ServerMessage::SpectatingDisallowed);
+            }
+        }
+        self.report_listing();
+        self.announce_spectators().await;
+        self.broadcast_state().await;
+    }
+
+    /// Lets the players know who can see the table: how many are watching and whether
+    /// anyone may.
+    async fn announce_spectators(&self) {
+        let msg = ServerMessage::SpectatorsChanged {
+            count: self.spectators.len(),
+            allowed: self.spectators_allowed,
+        };
+        for devices in self.player_channels.values() {
+            devices.send(msg.clone()).await;
+        }
+    }
+
+    /// Records `user_id`'s pick of rules, dealing as soon as every voter has made one.
+    async fn vote_on_rules(
+        &mut self,
+        user_id: &str,
+        payload: VoteRulesPayload,
+    ) -> Result<(), &'static str> {
+        let vote = self.rule_vote.as_mut().ok_or("no_rule_vote")?;
+        let ballot = vote.cast(user_id, &payload.preset, payload.turn_seconds)?;
+        let msg = ServerMessage::RuleVoteCast {
+            player_id: user_id.to_string(),
+            preset: ballot.preset.to_string(),
+            turn_seconds: ballot.turn_seconds,
+            waiting_on: vote.waiting_on(),
+        };
+        let complete = vote.is_complete();
+        self.broadcast(&msg).await;
+        if complete {
+            self.close_rule_vote().await;
+        }
+        Ok(())
+    }
+
+    /// Deals the first round under the rules the vote settled on.
+    async fn close_rule_vote(&mut self) {
+        let Some(vote) = self.rule_vote.take() else {
+            return;
+        };
+        let outcome = vote.outcome();
+        let preset = queues::find(outcome.preset).expect("the ballot only lists known presets");
+        println!(
+            "[Room {}] Rules voted: {} with {}s turns",
+            self.id, preset.id, outcome.turn_seconds
+        );
+
+        let paused = self.game_state.is_paused;
+        self.game_state =
+            GameState::with_rules(self.players.clone(), preset.rules(&self.services.rules));
+        self.game_state.is_paused = paused;
+        self.game_state.start_round();
+        self.replay = ReplayLog::start(&self.game_state);
+        self.queue = Some(preset.id);
+        self.services.turn_timer.turn = Duration::from_secs(outcome.turn_seconds);
+        self.turn_timer
+            .set_turn_length(self.services.turn_timer.turn);
+
+        let msg = ServerMessage::RulesChosen {
+            preset: preset.id.to_string(),
+            turn_seconds: outcome.turn_seconds,
+        };
+        self.broadcast(&msg).await;
+        self.sync_round().await;
+        self.sync_turn_timer();
+        if paused {
+            self.turn_timer.pause(Instant::now());
+        }
+        self.broadcast_state().await;
+    }
+
+    /// Shows a player who joins mid-vote the ballot and where the vote stands.
+    async fn send_rule_vote_to_user(&self, user_id: &str) {
+        let (Some(vote), Some(devices)) = (&self.rule_vote, self.player_channels.get(user_id))
+        else {
+            return;
+        };
+        let remaining = vote.deadline.saturating_duration_since(Instant::now());
+        let presets = vote
+            .presets()
+            .iter()
+            .map(|preset| RulePresetOption {
+                id: preset.id.to_string(),
+                name: preset.name.to_string(),
+                description: preset.description.to_string(),
+            })
+            .collect();
+        let default = vote.default();
+        devices
+            .send(ServerMessage::RuleVoteOpened {
+                presets,
+                turn_seconds: vote.turn_seconds(),
+                default_preset: default.preset.to_string(),
+                default_turn_seconds: default.turn_seconds,
+                waiting_on: vote.waiting_on(),
+                vote_deadline_epoch_ms: epoch_ms() + remaining.as_millis() as u64,
+            })
+            .await;
+    }
+
+    /// Counts `user_id` in favour of a break, which starts once every connected human at
+    /// the table has agreed.
+    async fn vote_for_break(&mut self, user_id: &str) -> Result<(), &'static str> {
+        let length = self.services.break_length;
+        if length.is_zero() {
+            return Err("breaks_disabled");
+        }
+        if self.is_paused() {
+            return Err("already_paused");
+        }
+
+        let now = Instant::now();
+        let voter = self.connection_of(user_id).to_string();
+        let (agreed, deadline) = self
+            .pause_vote
+            .get_or_insert_with(|| (HashSet::new(), now + PAUSE_VOTE_WINDOW));
+        agreed.insert(voter);
+        let agreed = agreed.clone();
+        let deadline = *deadline;
+
+        let mut waiting_on: Vec<String> = self
+            .player_channels
+            .keys()
+            .filter(|id| self.players.contains(*id) && !agreed.contains(*id))
+            .cloned()
+            .collect();
+        if waiting_on.is_empty() {
+            self.pause_vote = None;
+            self.break_until = Some(now + length);
+            self.sync_pause().await;
+            let msg = ServerMessage::BreakStarted {
+                resume_deadline_epoch_ms: epoch_ms() + length.as_millis() as u64,
+            };
+            self.broadcast(&msg).await;
+            return Ok(());
+        }
+
+        waiting_on.sort();
+        let mut agreed: Vec<String> = agreed.into_iter().collect();
+        agreed.sort();
+        let msg = ServerMessage::PauseRequested {
+            requested_by: user_id.to_string(),
+            agreed,
+            waiting_on,
+            vote_deadline_epoch_ms: epoch_ms()
+                + deadline.saturating_duration_since(now).as_millis() as u64,
+        };
+        self.broadcast(&msg).await;
+        Ok(())
+    }
+
+    /// Persists a report in the background; the room's audit trail is the evidence.
+    fn file_report(
+        &self,
+        reporter_id: &str,
+        reported_id: String,
+        reason: String,
+    ) -> Result<(), &'static str> {
+        if reported_id == reporter_id {
+            return Err("report_self");
+        }
+        if !self.players.contains(&reported_id) {
+            return Err("report_target_not_in_room");
+        }
+
+        let report = Report {
+            id: uuid::Uuid::new_v4().to_string(),
+            reporter_id: reporter_id.to_string(),
+            reported_id: reported_id.clone(),
+            room_id: self.id.clone(),
+            reason,
+            status: "open".to_string(),
+            resolution: None,
+            created_at: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_secs() as i64,
+        };
+
+        let db = self.services.db.clone();
+        let reporter_channel = self
+            .player_channels
+            .get(self.connection_of(reporter_id))
+            .cloned();
+        tokio::spawn(async move {
+            if let Err(e) = crate::db::repo::insert_report(&db, &report).await {
+                println!("[Moderation] Failed to store report {}: {}", report.id, e);
+                return;
+            }
+            if let Some(devices) = reporter_channel {
+                devices
+                    .send(ServerMessage::ReportReceived {
+                        reported_player_id: reported_id,
+                    })
+                    .await;
+            }
+        });
+
+        Ok(())
+    }
+
+    /// Sends the message to everyone, censored with the words blocked in their language.
+    async fn broadcast_chat(&self, user_id: &str, text: &str) {
+        let chat = |locale: Locale| ServerMessage::Chat {
+            player_id: user_id.to_string(),
+            text: self.services.word_filter.censor(text, locale.language()),
+        };
+        for (connection_id, devices) in &self.player_channels {
+            devices.send(chat(self.locale_of(connection_id))).await;
+        }
+        let msg = chat(Locale::default());
+        for sender in self.observers.values() {
+            let _ = sender.send(msg.clone()).await;
+        }
+        self.spectators.send(&msg);
+    }
+
+    /// Answers a hint request from `user_id`, up to the level the table's rules allow.
+    async fn send_hint(&self, user_id: &str, level: u8) -> Result<(), &'static str> {
+        if !(1..=hints::MAX_HINT_LEVEL).contains(&level) {
+            return Err("invalid_hint_level");
+        }
+        if level > self.game_state.rules.max_hint_level {
+            return Err("hint_level_not_allowed");
+        }
+        let player = self
+            .game_state
+            .players
+            .iter()
+            .find(|p| p.id == user_id)
+            .ok_or("player_not_found")?;
+        if player.has_dropped_hand {
+            return Err("already_dropped");
+        }
+
+        let hint = hints::hint(
+            &player.hand,
+            &self.game_state.current_round,
+            &self.game_state.rules,
+            level,
+        );
+        let suggestion = (level >= hints::MAX_HINT_LEVEL)
+            .then(|| analysis::evaluate_actions(&self.game_state, user_id))
+            .and_then(|scores| scores.into_iter().next())
+            .map(|best| best.action.into());
+        if let Some(devices) = self.player_channels.get(self.connection_of(user_id)) {
+            devices
+                .send(ServerMessage::Hint {
+                    level: hint.level,
+                    bajada_possible: hint.bajada_possible,
+                    meld_cards: hint.meld_cards,
+                    combinations: hint.combinations,
+                    suggestion,
+                })
+                .await;
+        }
+        Ok(())
+    }
+
+    async fn send_error(&self, user_id: &str, code: &str) {
+        let connection_id = self.connection_of(user_id);
+        if let Some(devices) = self.player_channels.get(connection_id) {
+            let localized = i18n::localize_error(code, self.locale_of(connection_id));
+            if let Some(correlation_id) = &self.correlation_id {
+                println!(
+                    "[Room {}] {} got error {:?} (correlation {})",
+                    self.id, user_id, code, correlation_id
+                );
+            }
+            devices
+                .send(ServerMessage::Error {
+                    message: localized.text,
+                    code: Some(localized.key.to_string()),
+                    correlation_id: self.correlation_id.clone(),
+                })
+                .await;
+        }
+    }
+
+    async fn send_state_to_user(&self, user_id: &str) {
+        let connection_id = self.connection_of(user_id);
+        if let Some(devices) = self.player_channels.get(connection_id) {
+            devices
+                .send(self.build_state_message(Some(connection_id)))
+                .await;
+        }
+    }
+
+    /// Whether `device_id` is the one of `user_id`'s devices allowed to act.
+    fn device_controls(&self, user_id: &str, device_id: &str) -> bool {
+        self.player_channels
+            .get(self.connection_of(user_id))
+            .is_some_and(|devices| devices.controls(device_id))
+    }
+
+    /// Tells one of `user_id`'s devices whether it now controls the seat.
+    async fn send_seat_control(&self, user_id: &str, device_id: &str, in_control: bool) {
+        let sender = self
+            .player_channels
+            .get(user_id)
+            .and_then(|devices| devices.sender_of(device_id));
+        if let Some(sender) = sender {
+            let _ = sender.send(ServerMessage::SeatControl { in_control }).await;
+        }
+    }
+
+    /// An error for one device only, e.g. a mirror that tried to act.
+    async fn send_device_error(&self, user_id: &str, device_id: &str, code: &str) {
+        let connection_id = self.connection_of(user_id);
+        let sender = self
+            .player_channels
+            .get(connection_id)
+            .and_then(|devices| devices.sender_of(device_id));
+        if let Some(sender) = sender {
+            let localized = i18n::localize_error(code, self.locale_of(connection_id));
+            let _ = sender
+                .send(ServerMessage::Error {
+                    message: localized.text,
+                    code: Some(localized.key.to_string()),
+                    correlation_id: self.correlation_id.clone(),
+                })
+                .await;
+        }
+    }
+
+    /// Everyone receiving public table messages but spectators, who are never waited on.
+    fn audience(&self) -> impl Iterator<Item = &mpsc::Sender<ServerMessage>> {
+        self.player_channels
+            .values()
+            .flat_map(Devices::senders)
+            .chain(self.observers.values())
+    }
+
+    /// Sends `msg` to everyone receiving public table messages.
+    async fn broadcast(&self, msg: &ServerMessage) {
+        for sender in self.audience() {
+            let _ = sender.send(msg.clone()).await;
+        }
+        self.spectators.send(msg);
+    }
+
+    /// Everything about the table, for admin observers.
+    fn observed_state(&self) -> ServerMessage {
+        let state = &self.game_state;
+        ServerMessage::ObservedState {
+            players: state.players.clone(),
+            round_index: state.round_index,
+            current_turn_index: state.current_turn,
+            deck: state.deck.cards().to_vec(),
+            discard_pile: state.discard_pile.clone(),
+            deck_seed: state.deck_seed,
+            is_game_over: state.is_game_over,
+            is_waiting_for_next_round: state.is_waiting_for_next_round,
+        }
+    }
+
+    /// The table as seen by `viewer`'s connection; spectators (`None`) get no hand.
+    fn build_state_message(&self, viewer: Option<&str>) -> ServerMessage {
+        let mut bots_seen = 0;
+        let sanitized_players: Vec<SanitizedPlayerState> = self
+            .game_state
+            .players
+            .iter()
+            .map(|p| {
+                let cosmetics = self.cosmetics.get(&p.id).cloned().unwrap_or_default();
+                let bot = p.id.starts_with("bot_").then(|| {
+                    bots_seen += 1;
+                    bot_identity::identity(&self.id, &p.id, bots_seen - 1)
+                });
+                SanitizedPlayerState::from_player_state(
+                    p,
+                    self.connection_status(&p.id),
+                    self.connection_quality
+                        .get(self.connection_of(&p.id))
+                        .copied(),
+                    cosmetics,
+                    self.avatar_urls.get(&p.id).cloned(),
+                    self.titles.get(&p.id).cloned(),
+                    bot,
+                )
+            })
+            .collect();
+
+        let top_discard = self.game_state.discard_pile.last().cloned();
+
+        let active_seat = viewer.and_then(|id| self.active_hot_seat(id));
+        let seat = active_seat.or(viewer);
+        let my_hand = self
+            .game_state
+            .players
+            .iter()
+            .find(|p| Some(p.id.as_str()) == seat)
+            .map(|p| p.hand.clone())
+            .unwrap_or_default();
+        let locale = viewer.map(|id| self.locale_of(id)).unwrap_or_default();
+        let view = |action: &LastAction| ActionView {
+            label: i18n::action_label(&action.action_type, locale),
+            action: action.clone(),
+        };
+
+        ServerMessage::GameStateUpdate {
+            my_hand,
+            players: sanitized_players,
+            current_round_index: self.game_state.round_index,
+            current_round_rules: i18n::translate(
+                self.game_state.current_round.description(),
+                locale,
+            ),
+            current_turn_index: self.game_state.current_turn,
+            turn_order: self.game_state.turn_order(),
+            next_player_id: self.game_state.next_player_id().map(str::to_string),
+            discard_pile_top: top_discard,
+            discard_pile_recent: self.game_state.visible_discards().to_vec(),
+            is_game_over: self.game_state.is_game_over,
+            is_waiting_for_next_round: self.game_state.is_waiting_for_next_round,
+            required_trios: self.game_state.current_round.get_requirements().0,
+            required_escalas: self.game_state.current_round.get_requirements().1,
+            last_action: self.game_state.last_action.as_ref().map(view),
+            recent_actions: self.game_state.recent_actions.iter().map(view).collect(),
+            turn_deadline_epoch_ms: self.turn_timer.deadline_epoch_ms(),
+            time_bank_remaining: self
+                .turn_timer
+                .time_bank_remaining(Instant::now())
+                .map(|bank| bank.as_millis() as u64),
+            active_seat_id: active_seat.map(str::to_string),
+            cross_region: lobby::mixes_regions(&self.regions),
+            spectators: self.spectators.len(),
+            spectators_allowed: self.spectators_allowed,
+            paused_for: {
+                let mut ids: Vec<String> = self.awaiting_reconnect.keys().cloned().collect();
+                ids.sort();
+                ids
+            },
+        }
+    }
+
+    fn connection_status(&self, player_id: &str) -> ConnectionStatus {
+        let connection = self.connection_of(player_id);
+        if !player_id.starts_with("bot_") && !self.player_channels.contains_key(connection) {
+            ConnectionStatus::Disconnected
+        } else if self.dropped_connections.contains(connection) {
+            ConnectionStatus::Reconnected
+        } else {
+            ConnectionStatus::Connected
+        }
+    }
+
+    /// Tells everyone else at the table that `user_id` came or went.
+    async fn broadcast_connection(&self, user_id: &str, status: ConnectionStatus) {
+        let msg = ServerMessage::PlayerConnectionChanged {
+            player_id: user_id.to_string(),
+            status,
+        };
+        let others = self
+            .player_channels
+            .iter()
+            .filter(|(id, _)| *id != user_id)
+            .flat_map(|(_, devices)| devices.senders())
+            .chain(self.observers.values());
+        for sender in others {
+            let _ = sender.send(msg.clone()).await;
+        }
+        self.spectators.send(&msg);
+    }
+
+    /// Language a connection asked for; spectators and observers get the default.
+    fn locale_of(&self, connection_id: &str) -> Locale {
+        self.player_locales
+            .get(connection_id)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Sends each player the results in their own language.
+    async fn broadcast_round_ended(&self, result: &crate::engine::game::RoundEndResult) {
+        for (connection_id, devices) in &self.player_channels {
+            let msg = self.round_ended_message(result, self.locale_of(connection_id));
+            devices.send(msg).await;
+        }
+        let msg = self.round_ended_message(result, Locale::default());
+        for sender in self.observers.values() {
+            let _ = sender.send(msg.clone()).await;
+        }
+        self.spectators.send(&msg);
+    }
+
+    fn round_ended_message(
+        &self,
+        result: &crate::engine::game::RoundEndResult,
+        locale: Locale,
+    ) -> ServerMessage {
+        ServerMessage::RoundEnded {
+            round_index: result.finished_round_index,
+            round_name: i18n::translate(&result.finished_round_name, locale),
+            winner_id: result.winner_id.clone(),
+            player_scores: result
+                .player_scores
+                .iter()
+                .map(|(id, rp, tp)| PlayerScore {
+                    id: id.clone(),
+                    round_points: *rp,
+                    total_points: *tp,
+                })
+                .collect(),
+            next_round_index: result.next_round_index,
+            next_round_name: i18n::translate(&result.next_round_name, locale),
+            is_game_over: result.is_game_over,
+            is_stalemate: result.is_stalemate,
+            player_stats: result.player_stats.clone(),
+            final_hands: result.final_hands.clone(),
+            winning_play: result.winning_play,
+            round_duration_ms: result.round_duration_ms,
+            game_duration_ms: result.game_duration_ms,
+            highlights: self
+                .transcript
+                .rounds
+                .last()
+                .map(|round| analysis::round_highlights(round, &self.game_state.rules))
+                .unwrap_or_default(),
+        }
+    }
+
+    /// What the game browser shows of the room; `None` for private tables.
+    fn listing(&self) -> Option<RoomListing> {
+        let state = &self.game_state;
+        Some(RoomListing {
+            room_id: self.id.clone(),
+            players: self.players.clone(),
+            round_index: state.round_index,
+            round_name: state.current_round.description().to_string(),
+            queue: self.queue?.to_string(),
+            spectators: self.spectators.len(),
+            spectators_allowed: self.spectators_allowed,
+            is_game_over: state.is_game_over,
+            listed_at_ms: 0,
+        })
+    }
+
+    fn report_listing(&self) {
+        if let Some(listing) = self.listing() {
+            self.services.directory.publish(listing);
+        }
+    }
+
+    async fn broadcast_state(&self) {
+        self.report_listing();
+        for user_id in self.player_channels.keys() {
+            self.send_state_to_user(user_id).await;
+        }
+        if !self.spectators.is_empty() {
+            self.spectators.send(&self.build_state_message(None));
+        }
+        if !self.observers.is_empty() {
+            let observed = self.observed_state();
+            for sender in self.observers.values() {
+                let _ = sender.send(observed.clone()).await;
+            }
+        }
+    }
+}
+
+/// Sleeps until `deadline`, or forever when there is nothing to wait for.
+fn epoch_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64
+}
+
+async fn sleep_until(deadline: Option<Instant>) {
+    match deadline {
+        Some(deadline) => tokio::time::sleep_until(deadline.into()).await,
+        None => std::future::pending().await,
+    }
+}
+
+/// The engine input behind an accepted action; `None` for messages that leave the table as is.
+fn replay_input(player_id: &str, action: &ClientMessage) -> Option<Input> {
+    let player_id = player_id.to_string();
+    match action {
+        ClientMessage::ReorderHand { payload } => Some(Input::ReorderHand {
+            player_id,
+            hand: payload.hand.clone(),
+        }),
+        ClientMessage::SortHand { payload } => Some(Input::SortHand {
+            player_id,
+            by: payload.by,
+        }),
+        ClientMessage::ReadyForNextRound => Some(Input::Ready { player_id }),
+        _ => action
+            .as_action()
+            .map(|action| Input::Move { player_id, action }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::events::{
+        AllowSpectatorsPayload, ChatPayload, DiscardPayload, DropHandPayload, ReportPlayerPayload,
+        SetLocalePayload, ShedCardPayload,
+    };
+    use crate::config::Config;
+    use crate::notify::http::ClientSettings;
+    use crate::notify::notifier::NotifierSettings;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    async fn services() -> RoomServices {
+        let config = Config::default();
+        let db = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let settings = NotifierSettings {
+            http: ClientSettings {
+                tls: None,
+                allow_private_addresses: false,
+                timeout: Duration::from_secs(1),
+            },
+            vapid: None,
+        };
+        RoomServices {
+            audit: AuditLog::spawn(db.clone()),
+            word_filter: Arc::new(WordFilter::with_defaults()),
+            turn_timer: TurnTimerConfig::from_config(&config),
+            ready_countdown: Duration::ZERO,
+            rules: config.rules.clone(),
+            notifier: Notifier::spawn(db.clone(), settings),
+            push_idle_turn: Duration::ZERO,
+            seasons: config.season_settings(),
+            idle_ttl: Duration::ZERO,
+            disconnect_grace: Duration::ZERO,
+            break_length: Duration::ZERO,
+            stream_deals: false,
+            action_latency: ActionLatency::default(),
+            directory: RoomDirectory::default(),
+            seed_vault: SeedVault::ephemeral(),
+            db,
+        }
+    }
+
+    type Inbox = mpsc::Receiver<ServerMessage>;
+
+    /// A room with every player connected from one device, and what each is sent.
+    async fn seated(players: &[&str]) -> (Room, HashMap<String, Inbox>) {
+        seated_with(players, services().await).await
+    }
+
+    async fn seated_with(
+        players: &[&str],
+        services: RoomServices,
+    ) -> (Room, HashMap<String, Inbox>) {
+        let (sender, receiver) = mpsc::channel(100);
+        let ids = players.iter().map(|player| player.to_string()).collect();
+        let mut room = Room::new("room".to_string(), ids, receiver, sender, services);
+        let mut inboxes = HashMap::new();
+        for player in players {
+            let (tx, rx) = mpsc::channel(1024);
+            let device = format!("{}-phone", player);
+            let joined = RoomEvent::PlayerJoined(
+                player.to_string(),
+                device,
+                tx,
+                Locale::default(),
+                AttachMode::Control,
+            );
+            room.handle(joined).await;
+            inboxes.insert(player.to_string(), rx);
+        }
+        (room, inboxes)
+    }
+
+    impl Room {
+        async fn handle(&mut self, event: RoomEvent) {
+            self.handle_event(event, &mut false).await;
+        }
+
+        async fn act(&mut self, user_id: &str, action: ClientMessage) {
+            let event = RoomEvent::PlayerAction(user_id.to_string(), action, None, None);
+            self.handle(event).await;
+        }
+    }
+
+    /// Everything sent so far and not yet read.
+    fn drain(inbox: &mut Inbox) -> Vec<ServerMessage> {
+        std::iter::from_fn(|| inbox.try_recv().ok()).collect()
+    }
+
+    fn allow_spectators(allowed: bool) -> ClientMessage {
+        ClientMessage::AllowSpectators {
+            payload: AllowSpectatorsPayload { allowed },
+        }
+    }
+
+    #[tokio::test]
+    async fn one_objection_turns_spectating_off_until_the_last_is_withdrawn() {
+        let (mut room, mut inboxes) = seated(&["ana", "beto"]).await;
+        let (watcher, mut watching) = mpsc::channel(16);
+        room.handle(RoomEvent::SpectatorJoined("fan".to_string(), watcher))
+            .await;
+        assert!(matches!(
+            drain(&mut watching).as_slice(),
+            [ServerMessage::GameStateUpdate { .. }]
+        ));
+
+        room.act("ana", allow_spectators(false)).await;
+        assert!(!room.spectators_allowed);
+        assert!(room.spectators.is_empty());
+        assert!(matches!(
+            drain(&mut watching).as_slice(),
+            [ServerMessage::SpectatingDisallowed]
+        ));
+        assert!(watching.recv().await.is_none(), "The stream ends");
+        let told = drain(inboxes.get_mut("beto").unwrap());
+        assert!(told.iter().any(|msg| matches!(
+            msg,
+            ServerMessage::SpectatorsChanged {
+                count: 0,
+                allowed: false
+            }
+        )));
+
+        let (late, mut turned_away) = mpsc::channel(16);
+        room.handle(RoomEvent::SpectatorJoined("late".to_string(), late))
+            .await;
+        assert!(matches!(
+            drain(&mut turned_away).as_slice(),
+            [ServerMessage::SpectatingDisallowed]
+        ));
+        assert!(room.spectators.is_empty());
+
+        room.act("beto", allow_spectators(false)).await;
+        room.act("ana", allow_spectators(true)).await;
+        assert!(!room.spectators_allowed, "beto still objects");
+        room.act("beto", allow_spectators(true)).await;
+        assert!(room.spectators_allowed);
+        let told = drain(inboxes.get_mut("ana").unwrap());
+        assert!(told.iter().any(|msg| matches!(
+            msg,
+            ServerMessage::SpectatorsChanged {
+                count: 0,
+                allowed: true
+            }
+        )));
+
+        let (back, mut watching) = mpsc::channel(16);
+        room.handle(RoomEvent::SpectatorJoined("fan".to_string(), back))
+            .await;
+        assert_eq!(room.spectators.len(), 1);
+        assert!(matches!(
+            drain(&mut watching).as_slice(),
+            [ServerMessage::GameStateUpdate { .. }]
+        ));
+    }
+
+    /// The hand and acting seat the state message for `viewer` shows.
+    fn shown_to(room: &Room, viewer: &str) -> (Vec<Card>, Option<String>) {
+        match room.build_state_message(Some(viewer)) {
+            ServerMessage::GameStateUpdate {
+                my_hand,
+                active_seat_id,
+                ..
+            } => (my_hand, active_seat_id),
+            other => panic!("Expected a state update, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn a_hot_seat_device_shows_the_turn_then_whoever_has_not_readied() {
+        let (sender, receiver) = mpsc::channel(100);
+        let seats = vec!["ana".to_string(), "ana#2".to_string()];
+        let mut room = Room::new_hot_seat(
+            "room".to_string(),
+            seats,
+            receiver,
+            sender,
+            services().await,
+        );
+        let (tx, mut inbox) = mpsc::channel(1024);
+        let joined = RoomEvent::PlayerJoined(
+            "ana".to_string(),
+            "ana-tablet".to_string(),
+            tx,
+            Locale::default(),
+            AttachMode::Control,
+        );
+        room.handle(joined).await;
+
+        for _ in 0..2 {
+            let turn = room.game_state.players[room.game_state.current_turn].clone();
+            assert_eq!(shown_to(&room, "ana"), (turn.hand, Some(turn.id.clone())));
+            room.act(&turn.id, ClientMessage::DrawFromDeck).await;
+            let discard = DiscardPayload { card_index: 0 };
+            room.act(&turn.id, ClientMessage::Discard { payload: discard })
+                .await;
+        }
+        let next = &room.game_state.players[room.game_state.current_turn];
+        let last_sent = drain(&mut inbox)
+            .into_iter()
+            .rev()
+            .find_map(|msg| match msg {
+                ServerMessage::GameStateUpdate { active_seat_id, .. } => Some(active_seat_id),
+                _ => None,
+            });
+        assert_eq!(last_sent, Some(Some(next.id.clone())));
+
+        room.game_state.is_waiting_for_next_round = true;
+        assert_eq!(shown_to(&room, "ana").1.as_deref(), Some("ana"));
+        room.act("ana", ClientMessage::ReadyForNextRound).await;
+        let (hand, seat) = shown_to(&room, "ana");
+        assert_eq!(seat.as_deref(), Some("ana#2"));
+        assert_eq!(hand, room.game_state.players[1].hand);
+
+        // Regular connections name no acting seat
+        let (other, _) = seated(&["beto", "carla"]).await;
+        assert_eq!(shown_to(&other, "beto").1, None);
+    }
+
+    fn reorder(hand: &[Card]) -> ClientMessage {
+        ClientMessage::ReorderHand {
+            payload: ReorderHandPayload {
+                hand: hand.to_vec(),
+            },
+        }
+    }
+
+    #[tokio::test]
+    async fn a_burst_of_reorders_is_applied_once_and_only_to_its_player() {
+        let (mut room, mut inboxes) = seated(&["ana", "beto"]).await;
+        for inbox in inboxes.values_mut() {
+            drain(inbox);
+        }
+        let mut hand = room.game_state.players[0].hand.clone();
+        for _ in 0..5 {
+            hand.rotate_left(1);
+            room.act("ana", reorder(&hand)).await;
+        }
+        assert_ne!(room.game_state.players[0].hand, hand, "Still coalescing");
+        assert!(drain(inboxes.get_mut("ana").unwrap()).is_empty());
+
+        tokio::time::sleep(REORDER_COALESCE).await;
+        room.on_timer_tick().await;
+        assert_eq!(room.game_state.players[0].hand, hand);
+        assert!(room.pending_reorders.is_empty());
+        let sent = drain(inboxes.get_mut("ana").unwrap());
+        assert_eq!(sent.len(), 1);
+        assert!(
+            matches!(&sent[0], ServerMessage::GameStateUpdate { my_hand, .. } if *my_hand == hand)
+        );
+        assert!(drain(inboxes.get_mut("beto").unwrap()).is_empty());
+    }
+
+    #[tokio::test]
+    async fn reorders_past_the_cap_are_refused_until_the_turn_changes() {
+        let (mut room, mut inboxes) = seated(&["ana", "beto"]).await;
+        for inbox in inboxes.values_mut() {
+            drain(inbox);
+        }
+        let dealt = room.game_state.players[0].hand.clone();
+        let mut hand = dealt.clone();
+        for _ in 0..MAX_ARRANGEMENTS_PER_TURN {
+            hand.rotate_left(1);
+            room.act("ana", reorder(&hand)).await;
+        }
+        hand.rotate_left(1);
+        room.act("ana", reorder(&hand)).await;
+
+        assert!(
+            room.pending_reorders.is_empty(),
+            "The pending order is dropped"
+        );
+        let sent = drain(inboxes.get_mut("ana").unwrap());
+        assert!(matches!(
+            sent.as_slice(),
+            [
+                ServerMessage::Error { code: Some(code), .. },
+                ServerMessage::GameStateUpdate { my_hand, .. },
+            ] if code == "too_many_reorders" && *my_hand == dealt
+        ));
+        assert!(drain(inboxes.get_mut("beto").unwrap()).is_empty());
+        assert_eq!(room.game_state.players[0].hand, dealt);
+
+        let turn = room.game_state.players[room.game_state.current_turn]
+            .id
+            .clone();
+        room.act(&turn, ClientMessage::DrawFromDeck).await;
+        let discard = DiscardPayload { card_index: 0 };
+        room.act(&turn, ClientMessage::Discard { payload: discard })
+            .await;
+        let hand = room.game_state.players[0].hand.clone();
+        room.act("ana", reorder(&hand)).await;
+        assert!(room.pending_reorders.contains_key("ana"));
+    }
+
+    #[tokio::test]
+    async fn chat_is_censored_in_each_readers_language() {
+        let (mut room, mut inboxes) = seated(&["ana", "beto"]).await;
+        let english = SetLocalePayload {
+            locale: "en".to_string(),
+        };
+        room.act("beto", ClientMessage::SetLocale { payload: english })
+            .await;
+        for inbox in inboxes.values_mut() {
+            drain(inbox);
+        }
+
+        let text = "qué weon, shit".to_string();
+        room.act(
+            "ana",
+            ClientMessage::Chat {
+                payload: ChatPayload { text },
+            },
+        )
+        .await;
+        let chat = |inbox: &mut Inbox| {
+            drain(inbox).into_iter().find_map(|msg| match msg {
+                ServerMessage::Chat { text, .. } => Some(text),
+                _ => None,
+            })
+        };
+        let ana = chat(inboxes.get_mut("ana").unwrap());
+        assert_eq!(ana.as_deref(), Some("qué ****, shit"));
+        let beto = chat(inboxes.get_mut("beto").unwrap());
+        assert_eq!(beto.as_deref(), Some("qué weon, ****"));
+    }
+
+    #[tokio::test]
+    async fn a_report_is_stored_and_acknowledged_to_its_reporter() {
+        let (mut room, mut inboxes) = seated(&["ana", "beto"]).await;
+        crate::db::repo::create_moderation_tables(&room.services.db)
+            .await
+            .unwrap();
+        for inbox in inboxes.values_mut() {
+            drain(inbox);
+        }
+        let report = |player: &str| ClientMessage::ReportPlayer {
+            payload: ReportPlayerPayload {
+                reported_player_id: player.to_string(),
+                reason: "insults".to_string(),
+            },
+        };
+        let ana = inboxes.get_mut("ana").unwrap();
+
+        room.act("ana", report("ana")).await;
+        room.act("ana", report("carla")).await;
+        let codes: Vec<_> = drain(ana)
+            .into_iter()
+            .filter_map(|msg| match msg {
+                ServerMessage::Error { code, .. } => code,
+                _ => None,
+            })
+            .collect();
+        assert_eq!(codes, ["report_self", "report_target_not_in_room"]);
+
+        room.act("ana", report("beto")).await;
+        let received = tokio::time::timeout(Duration::from_secs(5), ana.recv())
+            .await
+            .expect("The report should be acknowledged");
+        assert!(matches!(
+            received,
+            Some(ServerMessage::ReportReceived { reported_player_id }) if reported_player_id == "beto"
+        ));
+        let stored = crate::db::repo::list_reports(&room.services.db, Some("open")).await;
+        assert_eq!(stored.len(), 1);
+        assert_eq!(
+            (
+                stored[0].reporter_id.as_str(),
+                stored[0].reported_id.as_str()
+            ),
+            ("ana", "beto")
+        );
+        assert_eq!(
+            (stored[0].room_id.as_str(), stored[0].reason.as_str()),
+            ("room", "insults")
+        );
+        assert!(
+            drain(inboxes.get_mut("beto").unwrap()).is_empty(),
+            "beto isn't told"
+        );
+    }
+
+    /// The seats as `viewer`'s state update shows them.
+    fn players_shown_to(room: &Room, viewer: &str) -> Vec<SanitizedPlayerState> {
+        match room.build_state_message(Some(viewer)) {
+            ServerMessage::GameStateUpdate { players, .. } => players,
+            other => panic!("Expected a state update, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn the_table_hears_when_a_player_drops_out_and_comes_back() {
+        let (mut room, mut inboxes) = seated(&["ana", "beto"]).await;
+        for inbox in inboxes.values_mut() {
+            drain(inbox);
+        }
+        let changes = |inbox: &mut Inbox| -> Vec<(String, ConnectionStatus)> {
+            drain(inbox)
+                .into_iter()
+                .filter_map(|msg| match msg {
+                    ServerMessage::PlayerConnectionChanged { player_id, status } => {
+                        Some((player_id, status))
+                    }
+                    _ => None,
+                })
+                .collect()
+        };
+        let status_of = |room: &Room, player: &str| {
+            let players = players_shown_to(room, "ana");
+            players.iter().find(|p| p.id == player).unwrap().connection
+        };
+        assert_eq!(status_of(&room, "beto"), ConnectionStatus::Connected);
+
+        let left = RoomEvent::PlayerLeft("beto".to_string(), "beto-phone".to_string());
+        room.handle(left).await;
+        assert_eq!(
+            changes(inboxes.get_mut("ana").unwrap()),
+            [("beto".to_string(), ConnectionStatus::Disconnected)]
+        );
+        assert_eq!(status_of(&room, "beto"), ConnectionStatus::Disconnected);
+
+        let (tx, mut back) = mpsc::channel(1024);
+        let rejoined = RoomEvent::PlayerJoined(
+            "beto".to_string(),
+            "beto-laptop".to_string(),
+            tx,
+            Locale::default(),
+            AttachMode::Control,
+        );
+        room.handle(rejoined).await;
+        assert_eq!(
+            changes(inboxes.get_mut("ana").unwrap()),
+            [("beto".to_string(), ConnectionStatus::Reconnected)]
+        );
+        assert!(
+            changes(&mut back).is_empty(),
+            "Nobody is told of their own return"
+        );
+        assert_eq!(status_of(&room, "beto"), ConnectionStatus::Reconnected);
+        assert_eq!(status_of(&room, "ana"), ConnectionStatus::Connected);
+    }
+
+    #[tokio::test]
+    async fn everyone_sees_what_a_bajada_and_a_shed_put_on_the_table() {
+        use crate::engine::card::{Suit, Value};
+        let card = |suit, value| Card::Standard { suit, value };
+        let (mut room, mut inboxes) = seated(&["ana", "beto"]).await;
+        assert_eq!(room.game_state.current_round.get_requirements(), (2, 0));
+        let turn = room.game_state.current_turn;
+        let player = room.game_state.players[turn].id.clone();
+        let fives = vec![
+            card(Suit::Hearts, Value::Five),
+            card(Suit::Clubs, Value::Five),
+            card(Suit::Spades, Value::Five),
+        ];
+        let eights = vec![
+            card(Suit::Hearts, Value::Eight),
+            card(Suit::Clubs, Value::Eight),
+            card(Suit::Spades, Value::Eight),
+        ];
+        let filler = card(Suit::Diamonds, Value::King);
+        let state = &mut room.game_state.players[turn];
+        state.hand = [fives.clone(), eights.clone(), vec![filler, filler]].concat();
+        state.has_drawn_this_turn = true;
+        for inbox in inboxes.values_mut() {
+            drain(inbox);
+        }
+
+        let combinations = vec![fives.clone(), eights];
+        let payload = DropHandPayload {
+            combinations: combinations.clone(),
+        };
+        room.act(&player, ClientMessage::DropHand { payload }).await;
+        for inbox in inboxes.values_mut() {
+            let dropped: Vec<_> = drain(inbox)
+                .into_iter()
+                .filter_map(|msg| match msg {
+                    ServerMessage::PlayerDroppedHand {
+                        player_id,
+                        combinations,
+                    } => Some((player_id, combinations)),
+                    _ => None,
+                })
+                .collect();
+            assert_eq!(dropped, [(player.clone(), combinations.clone())]);
+        }
+
+        // A later turn, with the fourth five to add to the trio
+        let five = card(Suit::Diamonds, Value::Five);
+        let state = &mut room.game_state.players[turn];
+        state.dropped_hand_this_turn = false;
+        state.hand.push(five);
+        let payload = ShedCardPayload {
+            hand_card_index: 2,
+            target_player_id: player.clone(),
+            target_combo_idx: 0,
+            position: None,
+        };
+        room.act(&player, ClientMessage::ShedCard { payload }).await;
+        for inbox in inboxes.values_mut() {
+            let shed: Vec<_> = drain(inbox)
+                .into_iter()
+                .filter_map(|msg| match msg {
+                    ServerMessage::CardShed {
+                        player_id,
+                        card,
+                        target_player_id,
+                        target_combo_idx,
+                        combination,
+                    } => Some((
+                        player_id,
+                        card,
+                        target_player_id,
+                        target_combo_idx,
+                        combination,
+                    )),
+                    _ => None,
+                })
+                .collect();
+            let [(by, shed_card, onto, combo_idx, combination)] = shed.as_slice() else {
+                panic!("Expected one CardShed, got {:?}", shed);
+            };
+            assert_eq!((by, onto, *combo_idx), (&player, &player, 0));
+            assert_eq!(*shed_card, five);
+            assert_eq!(combination.len(), 4);
+            assert!(fives.iter().all(|c| combination.contains(c)) && combination.contains(&five));
+        }
+    }
+
+    #[tokio::test]
+    async fn the_scoreboard_keeps_running_totals_per_finished_round() {
+        let (mut room, mut inboxes) = seated(&["ana", "beto"]).await;
+        room.sync_round().await;
+        let totals = |room: &Room| -> Vec<u32> {
+            let points = |id: &String| {
+                let players = &room.game_state.players;
+                players.iter().find(|p| &p.id == id).unwrap().points
+            };
+            room.players.iter().map(points).collect()
+        };
+
+        let result = room.game_state.end_round();
+        room.finish_round(result).await;
+        let after_first = totals(&room);
+        room.game_state.force_ready_all().unwrap();
+        room.sync_round().await;
+        let result = room.game_state.end_round();
+        room.finish_round(result).await;
+        let after_second = totals(&room);
+        assert_ne!(after_first, after_second, "The second round scored points");
+
+        let beto = inboxes.get_mut("beto").unwrap();
+        drain(beto);
+        room.act("beto", ClientMessage::GetScores).await;
+        let scoreboard = drain(beto).into_iter().find_map(|msg| match msg {
+            ServerMessage::Scoreboard { seats, rounds } => Some((seats, rounds)),
+            _ => None,
+        });
+        let (seats, rounds) = scoreboard.expect("beto should get the scoreboard");
+        assert_eq!(seats, room.players);
+        let rows: Vec<_> = rounds
+            .iter()
+            .map(|row| (row.round_index, row.totals.clone()))
+            .collect();
+        assert_eq!(rows, [(0, after_first), (1, after_second)]);
+        assert!(
+            drain(inboxes.get_mut("ana").unwrap())
+                .iter()
+                .all(|msg| !matches!(msg, ServerMessage::Scoreboard { .. })),
+            "Only whoever asked gets it"
+        );
+    }
+
+    #[tokio::test]
+    async fn each_seat_shows_the_cosmetics_its_player_picked() {
+        let services = services().await;
+        repo::create_cosmetics_table(&services.db).await.unwrap();
+        let picked = Cosmetics {
+            card_back: "copihue".to_string(),
+            table_theme: "noche".to_string(),
+        };
+        repo::upsert_cosmetics(&services.db, "beto", &picked)
+            .await
+            .unwrap();
+        let (room, _inboxes) = seated_with(&["ana", "beto"], services).await;
+
+        let players = players_shown_to(&room, "ana");
+        let cosmetics_of = |id: &str| &players.iter().find(|p| p.id == id).unwrap().cosmetics;
+        assert_eq!(cosmetics_of("beto"), &picked);
+        assert_eq!(cosmetics_of("ana"), &Cosmetics::default());
+    }
+
+    #[tokio::test]
+    async fn a_finished_round_records_how_the_bot_did() {
+        let services = services().await;
+        repo::create_bot_stats_table(&services.db).await.unwrap();
+        let (sender, receiver) = mpsc::channel(100);
+        let ids = vec!["ana".to_string(), "bot_hard".to_string()];
+        let mut room = Room::new("room".to_string(), ids, receiver, sender, services);
+        room.bajada_turns.insert("bot_hard".to_string(), 3);
+
+        let result = room.game_state.end_round();
+        let (_, leftover, _) = result
+            .player_scores
+            .iter()
+            .find(|(id, _, _)| id == "bot_hard")
+            .cloned()
+            .unwrap();
+        let went_out = result.winner_id == "bot_hard";
+        room.finish_round(result).await;
+
+        let mut performance = Vec::new();
+        for _ in 0..100 {
+            performance = repo::get_bot_performance(&room.services.db, 0).await;
+            if !performance.is_empty() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        let [hard] = performance.as_slice() else {
+            panic!("Expected one difficulty, got {:?}", performance);
+        };
+        assert_eq!((hard.difficulty.as_str(), hard.rounds), ("hard", 1));
+        assert_eq!(hard.win_rate, if went_out { 1.0 } else { 0.0 });
+        assert_eq!(hard.average_leftover_points, leftover as f64);
+        assert_eq!(hard.average_bajada_turn, Some(3.0));
+        assert!(room.bajada_turns.is_empty(), "Counted afresh each round");
+    }
+
+    #[tokio::test]
+    async fn the_deal_is_streamed_card_by_card_only_when_the_room_asks() {
+        let dealt_to = |inbox: &mut Inbox| -> Vec<String> {
+            drain(inbox)
+                .into_iter()
+                .filter_map(|msg| match msg {
+                    ServerMessage::CardDealt { to, count: 1 } => Some(to),
+                    _ => None,
+                })
+                .collect()
+        };
+
+        let (mut room, mut inboxes) = seated(&["ana", "beto"]).await;
+        room.sync_round().await;
+        assert!(dealt_to(inboxes.get_mut("ana").unwrap()).is_empty());
+
+        let services = RoomServices {
+            stream_deals: true,
+            ..services().await
+        };
+        let (mut room, mut inboxes) = seated_with(&["ana", "beto"], services).await;
+        room.sync_round().await;
+        let hand_size = room.game_state.players[0].hand.len();
+        assert!(hand_size > 0);
+        let round_the_table: Vec<String> = (0..hand_size)
+            .flat_map(|_| room.game_state.players.iter().map(|p| p.id.clone()))
+            .collect();
+        for inbox in inboxes.values_mut() {
+            assert_eq!(dealt_to(inbox), round_the_table);
+        }
+
+        room.sync_round().await;
+        assert!(
+            dealt_to(inboxes.get_mut("ana").unwrap()).is_empty(),
+            "Each round is dealt once"
+        );
+    }
+
+    #[tokio::test]
+    async fn a_spectator_who_stops_reading_never_holds_up_the_table() {
+        let (mut room, mut inboxes) = seated(&["ana", "beto"]).await;
+        let (stalled, _never_read) = mpsc::channel(2);
+        room.handle(RoomEvent::SpectatorJoined("fan".to_string(), stalled))
+            .await;
+        assert_eq!(room.spectators.len(), 1);
+
+        let turns = async {
+            for _ in 0..3 {
+                room.broadcast_state().await;
+            }
+        };
+        tokio::time::timeout(Duration::from_secs(1), turns)
+            .await
+            .expect("Broadcasting shouldn't wait on the spectator");
+        assert!(
+            room.spectators.is_empty(),
+            "Let go once its queue filled up"
+        );
+        assert!(room.spectators.take_shed());
+        let ana = drain(inboxes.get_mut("ana").unwrap());
+        assert!(
+            ana.iter()
+                .filter(|msg| matches!(msg, ServerMessage::GameStateUpdate { .. }))
+                .count()
+                >= 3
+        );
+    }
 }
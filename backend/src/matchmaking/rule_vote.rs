@@ -0,0 +1,181 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use crate::matchmaking::queues::{self, QueuePreset};
+
+/// How long players have to vote on the rules before a casual game is dealt
+pub const VOTE_WINDOW: Duration = Duration::from_secs(30);
+
+/// Turn lengths on the ballot besides the server's own, in seconds
+const TURN_SECONDS: &[u64] = &[30, 60, 90];
+
+/// One player's choice, or the rules the vote settled on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Ballot {
+    /// Id of the queue preset whose rules apply
+    pub preset: &'static str,
+    /// Zero plays untimed
+    pub turn_seconds: u64,
+}
+
+/// The vote on house rules and turn length held before the first deal of a casual game.
+///
+/// Each question is settled on its own by the most votes. Ties, and questions nobody
+/// voted on, keep what the room was opened with.
+#[derive(Debug)]
+pub struct RuleVote {
+    voters: Vec<String>,
+    ballots: HashMap<String, Ballot>,
+    default: Ballot,
+    /// When the vote closes, whether or not everyone has voted
+    pub deadline: Instant,
+}
+
+impl RuleVote {
+    pub fn new(voters: Vec<String>, default: Ballot, now: Instant) -> Self {
+        Self {
+            voters,
+            ballots: HashMap::new(),
+            default,
+            deadline: now + VOTE_WINDOW,
+        }
+    }
+
+    /// The presets on the ballot.
+    pub fn presets(&self) -> &'static [QueuePreset] {
+        queues::PRESETS
+    }
+
+    /// The turn lengths on the ballot, shortest first.
+    pub fn turn_seconds(&self) -> Vec<u64> {
+        let mut options = TURN_SECONDS.to_vec();
+        options.push(self.default.turn_seconds);
+        options.sort_unstable();
+        options.dedup();
+        options
+    }
+
+    pub fn default(&self) -> Ballot {
+        self.default
+    }
+
+    /// Records `voter`'s choice, replacing any they made before.
+    pub fn cast(
+        &mut self,
+        voter: &str,
+        preset: &str,
+        turn_seconds: u64,
+    ) -> Result<Ballot, &'static str> {
+        if !self.voters.iter().any(|id| id == voter) {
+            return Err("not_a_rule_voter");
+        }
+        let preset = queues::find(preset).ok_or("unknown_rule_preset")?;
+        if !self.turn_seconds().contains(&turn_seconds) {
+            return Err("turn_length_not_on_ballot");
+        }
+        let ballot = Ballot {
+            preset: preset.id,
+            turn_seconds,
+        };
+        self.ballots.insert(voter.to_string(), ballot);
+        Ok(ballot)
+    }
+
+    /// Voters still to cast a ballot.
+    pub fn waiting_on(&self) -> Vec<String> {
+        self.voters
+            .iter()
+            .filter(|id| !self.ballots.contains_key(*id))
+            .cloned()
+            .collect()
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.waiting_on().is_empty()
+    }
+
+    /// The rules the room plays by as the votes stand.
+    pub fn outcome(&self) -> Ballot {
+        let preset = plurality(
+            self.ballots.values().map(|ballot| ballot.preset),
+            self.default.preset,
+        );
+        let turn_seconds = plurality(
+            self.ballots.values().map(|ballot| ballot.turn_seconds),
+            self.default.turn_seconds,
+        );
+        Ballot {
+            preset,
+            turn_seconds,
+        }
+    }
+}
+
+/// The most voted choice; `default` unless something beat it outright.
+fn plurality<T: Copy + Eq + std::hash::Hash>(votes: impl Iterator<Item = T>, default: T) -> T {
+    let mut counts: HashMap<T, usize> = HashMap::new();
+    for vote in votes {
+        *counts.entry(vote).or_default() += 1;
+    }
+    let most = counts.values().copied().max().unwrap_or(0);
+    let leaders: Vec<T> = counts
+        .into_iter()
+        .filter(|(_, count)| *count == most)
+        .map(|(choice, _)| choice)
+        .collect();
+    match leaders.as_slice() {
+        [only] => *only,
+        _ => default,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vote() -> RuleVote {
+        let default = Ballot {
+            preset: queues::DEFAULT_QUEUE,
+            turn_seconds: 45,
+        };
+        let voters = vec!["ana".into(), "beto".into(), "carla".into()];
+        RuleVote::new(voters, default, Instant::now())
+    }
+
+    #[test]
+    fn only_seated_voters_pick_from_the_ballot() {
+        let mut vote = vote();
+        assert_eq!(vote.turn_seconds(), vec![30, 45, 60, 90]);
+        assert!(vote.cast("intruso", "rapida", 30).is_err());
+        assert!(vote.cast("ana", "nope", 30).is_err());
+        assert!(vote.cast("ana", "rapida", 31).is_err());
+
+        vote.cast("ana", "rapida", 30).unwrap();
+        vote.cast("ana", "pozo_abierto", 30).unwrap();
+        assert_eq!(
+            vote.waiting_on(),
+            vec!["beto".to_string(), "carla".to_string()]
+        );
+        vote.cast("beto", "rapida", 90).unwrap();
+        vote.cast("carla", "rapida", 60).unwrap();
+        assert!(vote.is_complete());
+
+        let outcome = vote.outcome();
+        assert_eq!(outcome.preset, "rapida");
+        assert_eq!(
+            outcome.turn_seconds, 45,
+            "A three-way tie keeps the default"
+        );
+    }
+
+    #[test]
+    fn nobody_voting_keeps_the_room_as_opened() {
+        let mut vote = vote();
+        assert_eq!(vote.outcome(), vote.default());
+
+        vote.cast("ana", "pozo_abierto", 90).unwrap();
+        let outcome = vote.outcome();
+        assert_eq!(outcome.preset, "pozo_abierto");
+        assert_eq!(outcome.turn_seconds, 90);
+    }
+}
@@ -0,0 +1,76 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::api::server::AppState;
+use crate::db::repo;
+use crate::matchmaking::lobby::{BOT_IDS, TABLE_SIZE};
+use crate::scheduler::runner::JobOutcome;
+use crate::tournament::director;
+
+/// Seats a scheduled game can be set up with.
+pub const SEAT_RANGE: std::ops::RangeInclusive<usize> = 2..=TABLE_SIZE;
+
+/// The table for a scheduled game: RSVPs in the order they came in, then bots up to
+/// `max_players`.
+pub fn seat_list(rsvps: &[String], max_players: usize) -> Vec<String> {
+    let mut players: Vec<String> = rsvps.iter().take(max_players).cloned().collect();
+    let bots = max_players.saturating_sub(players.len());
+    players.extend(BOT_IDS.iter().take(bots).map(|id| id.to_string()));
+    players
+}
+
+/// Starts every scheduled game whose time has come. Games nobody is coming to are called
+/// off; games the server has no room for yet are retried on the next pass.
+pub async fn start_due_games(state: &AppState) -> JobOutcome {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64;
+    let due = repo::list_due_scheduled_games(&state.db, now).await;
+
+    let (mut started, mut cancelled) = (0, 0);
+    for game in &due {
+        let rsvps: Vec<String> = repo::get_scheduled_game_rsvps(&state.db, game.id)
+            .await
+            .into_iter()
+            .map(|r| r.user_id)
+            .collect();
+        if rsvps.is_empty() {
+            repo::update_scheduled_game_status(&state.db, game.id, "cancelled", None)
+                .await
+                .map_err(|e| e.to_string())?;
+            cancelled += 1;
+            continue;
+        }
+
+        let players = seat_list(&rsvps, game.max_players as usize);
+        let Some(room_id) = director::open_match(state, &players).await else {
+            break;
+        };
+        repo::update_scheduled_game_status(&state.db, game.id, "started", Some(&room_id))
+            .await
+            .map_err(|e| e.to_string())?;
+        started += 1;
+    }
+    Ok(format!(
+        "started {}, cancelled {} of {} due games",
+        started,
+        cancelled,
+        due.len()
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bots_fill_the_seats_nobody_claimed() {
+        let rsvps = ["ana".to_string(), "beto".to_string()];
+        assert_eq!(
+            seat_list(&rsvps, 4),
+            ["ana", "beto", "bot_easy", "bot_medium"]
+        );
+        assert_eq!(seat_list(&rsvps, 2), ["ana", "beto"]);
+        assert_eq!(seat_list(&rsvps[..1], 2), ["ana", "bot_easy"]);
+    }
+}
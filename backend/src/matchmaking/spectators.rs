@@ -0,0 +1,106 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tokio::sync::mpsc;
+
+use crate::api::events::ServerMessage;
+
+/// Anonymous watchers of a table, keyed by a per-connection id. Sending to them never
+/// waits: one whose queue is full has stopped reading and is let go, so a stalled share
+/// link can't hold up the game for the players.
+#[derive(Debug, Default)]
+pub struct Spectators {
+    channels: Mutex<HashMap<String, mpsc::Sender<ServerMessage>>>,
+    /// Someone was let go since the room last asked
+    shed: AtomicBool,
+}
+
+impl Spectators {
+    /// Adds a spectator after sending them `first`; one who can't take it isn't added.
+    pub fn join(&mut self, id: String, sender: mpsc::Sender<ServerMessage>, first: ServerMessage) {
+        if sender.try_send(first).is_ok() {
+            self.channels().insert(id, sender);
+        }
+    }
+
+    pub fn remove(&mut self, id: &str) -> bool {
+        self.channels().remove(id).is_some()
+    }
+
+    pub fn len(&self) -> usize {
+        self.channels().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Removes everyone, handing back their channels.
+    pub fn drain(&mut self) -> Vec<mpsc::Sender<ServerMessage>> {
+        self.channels().drain().map(|(_, sender)| sender).collect()
+    }
+
+    /// Everyone's channel, for messages sent once the room has moved on; use `try_send`.
+    pub fn senders(&self) -> Vec<mpsc::Sender<ServerMessage>> {
+        self.channels().values().cloned().collect()
+    }
+
+    /// Queues `msg` for everyone, letting go of whoever has no room left for it.
+    pub fn send(&self, msg: &ServerMessage) {
+        let mut channels = self.channels();
+        let before = channels.len();
+        channels.retain(|_, sender| sender.try_send(msg.clone()).is_ok());
+        if channels.len() < before {
+            self.shed.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// Whether anyone was let go since the last call, so the count can be announced.
+    pub fn take_shed(&self) -> bool {
+        self.shed.swap(false, Ordering::Relaxed)
+    }
+
+    fn channels(&self) -> std::sync::MutexGuard<'_, HashMap<String, mpsc::Sender<ServerMessage>>> {
+        // Nothing panics while holding the lock, so it can't be poisoned
+        self.channels.lock().unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_spectator_who_stops_reading_is_let_go() {
+        let mut spectators = Spectators::default();
+        let (reader, mut reading) = mpsc::channel(4);
+        let (stalled, _stalled) = mpsc::channel(1);
+        spectators.join(
+            "reader".to_string(),
+            reader,
+            ServerMessage::SpectatingDisallowed,
+        );
+        spectators.join(
+            "stalled".to_string(),
+            stalled,
+            ServerMessage::SpectatingDisallowed,
+        );
+        assert_eq!(spectators.len(), 2);
+        assert!(!spectators.take_shed());
+
+        spectators.send(&ServerMessage::SpectatingDisallowed);
+        assert_eq!(spectators.len(), 1, "The full queue is dropped");
+        assert!(spectators.take_shed());
+        assert!(!spectators.take_shed(), "Reported once");
+        assert!(reading.try_recv().is_ok() && reading.try_recv().is_ok());
+
+        let (full, _full) = mpsc::channel(1);
+        full.try_send(ServerMessage::SpectatingDisallowed).unwrap();
+        spectators.join(
+            "late".to_string(),
+            full,
+            ServerMessage::SpectatingDisallowed,
+        );
+        assert_eq!(spectators.len(), 1, "Can't take the first update");
+    }
+}
@@ -0,0 +1,403 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::config::Config;
+
+/// Round trips of lag a player's grace period covers: the turn reaching them and their
+/// move coming back
+const GRACE_ROUND_TRIPS: u32 = 2;
+
+/// Turn clock settings shared by every room.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TurnTimerConfig {
+    /// Base time per turn; zero disables the timer.
+    pub turn: Duration,
+    /// Per-player reserve spent once a turn overruns `turn`.
+    pub time_bank: Duration,
+    /// Remaining-time marks (before the hard deadline) that trigger a warning.
+    pub warnings: Vec<Duration>,
+    /// Most a slow connection's grace period may add after the deadline; zero grants none.
+    pub max_latency_grace: Duration,
+}
+
+impl TurnTimerConfig {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            turn: Duration::from_secs(config.turn_seconds),
+            time_bank: Duration::from_secs(config.time_bank_seconds),
+            warnings: config
+                .turn_warning_seconds
+                .iter()
+                .map(|secs| Duration::from_secs(*secs))
+                .collect(),
+            max_latency_grace: Duration::from_millis(config.turn_latency_grace_max_ms),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.turn.is_zero()
+    }
+}
+
+/// Something the room must react to when the clock advances.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TimerEvent {
+    Warning {
+        player_id: String,
+        remaining: Duration,
+    },
+    /// Base time and time bank are both spent.
+    Expired { player_id: String },
+}
+
+struct ActiveTurn {
+    player_id: String,
+    started_at: Instant,
+    /// Deadline shown to the table: base time plus whatever was left in the player's bank.
+    deadline: Instant,
+    deadline_epoch_ms: u64,
+    /// How long past `deadline` the turn actually expires, for the player's lag
+    grace: Duration,
+    /// Warnings still to fire, largest remaining-time first.
+    pending_warnings: Vec<Duration>,
+    /// Set while the clock is frozen
+    paused_at: Option<Instant>,
+}
+
+/// Tracks the current turn's deadline and each player's time bank.
+///
+/// Purely time arithmetic: the room decides when turns start and stop and what
+/// happens on expiry. `now` is passed in so the logic is testable.
+pub struct TurnTimer {
+    config: TurnTimerConfig,
+    banks: HashMap<String, Duration>,
+    /// Latest measured round trip of each player's connection
+    round_trips: HashMap<String, Duration>,
+    active: Option<ActiveTurn>,
+}
+
+impl TurnTimer {
+    pub fn new(config: TurnTimerConfig, players: &[String]) -> Self {
+        let banks = players
+            .iter()
+            .map(|id| (id.clone(), config.time_bank))
+            .collect();
+
+        Self {
+            config,
+            banks,
+            round_trips: HashMap::new(),
+            active: None,
+        }
+    }
+
+    /// Notes how long `player_id`'s connection takes to answer; their turns from the next
+    /// one on expire that much later, up to the configured cap.
+    pub fn set_round_trip(&mut self, player_id: &str, rtt: Duration) {
+        self.round_trips.insert(player_id.to_string(), rtt);
+    }
+
+    /// Changes the base time of turns started from now on; zero stops timing them.
+    pub fn set_turn_length(&mut self, turn: Duration) {
+        self.config.turn = turn;
+    }
+
+    fn grace_of(&self, player_id: &str) -> Duration {
+        self.round_trips
+            .get(player_id)
+            .map_or(Duration::ZERO, |rtt| *rtt * GRACE_ROUND_TRIPS)
+            .min(self.config.max_latency_grace)
+    }
+
+    /// Starts `player_id`'s clock unless it is already running.
+    pub fn start_turn(&mut self, player_id: &str, now: Instant) {
+        if !self.config.is_enabled() {
+            return;
+        }
+        if self
+            .active
+            .as_ref()
+            .is_some_and(|turn| turn.player_id == player_id)
+        {
+            return;
+        }
+        self.stop(now);
+
+        let bank = self.bank_of(player_id);
+        let allowed = self.config.turn + bank;
+        let mut pending_warnings: Vec<Duration> = self
+            .config
+            .warnings
+            .iter()
+            .copied()
+            .filter(|mark| *mark < allowed)
+            .collect();
+        pending_warnings.sort_unstable_by(|a, b| b.cmp(a));
+        pending_warnings.dedup();
+
+        self.active = Some(ActiveTurn {
+            player_id: player_id.to_string(),
+            started_at: now,
+            deadline: now + allowed,
+            deadline_epoch_ms: epoch_ms() + allowed.as_millis() as u64,
+            grace: self.grace_of(player_id),
+            pending_warnings,
+            paused_at: None,
+        });
+    }
+
+    /// Freezes the running turn's clock until [`TurnTimer::resume`].
+    pub fn pause(&mut self, now: Instant) {
+        if let Some(turn) = self.active.as_mut() {
+            turn.paused_at.get_or_insert(now);
+        }
+    }
+
+    /// Restarts a frozen clock, pushing the deadline back by the time spent paused.
+    pub fn resume(&mut self, now: Instant) {
+        let Some(turn) = self.active.as_mut() else {
+            return;
+        };
+        if let Some(paused_at) = turn.paused_at.take() {
+            let paused = now.saturating_duration_since(paused_at);
+            turn.started_at += paused;
+            turn.deadline += paused;
+            turn.deadline_epoch_ms += paused.as_millis() as u64;
+        }
+    }
+
+    /// Ends the running turn, charging any overtime to the player's bank.
+    pub fn stop(&mut self, now: Instant) {
+        if let Some(turn) = self.active.take() {
+            let remaining = self.bank_remaining(&turn, now);
+            self.banks.insert(turn.player_id, remaining);
+        }
+    }
+
+    /// Epoch milliseconds at which the current turn expires; `None` while paused.
+    pub fn deadline_epoch_ms(&self) -> Option<u64> {
+        self.active
+            .as_ref()
+            .filter(|turn| turn.paused_at.is_none())
+            .map(|turn| turn.deadline_epoch_ms)
+    }
+
+    /// The current player's bank as of `now`, already net of overtime in this turn.
+    pub fn time_bank_remaining(&self, now: Instant) -> Option<Duration> {
+        self.active
+            .as_ref()
+            .map(|turn| self.bank_remaining(turn, now))
+    }
+
+    /// When the room next needs to call [`TurnTimer::poll`].
+    pub fn next_wakeup(&self) -> Option<Instant> {
+        let turn = self
+            .active
+            .as_ref()
+            .filter(|turn| turn.paused_at.is_none())?;
+        match turn.pending_warnings.first() {
+            Some(mark) => Some(turn.deadline - *mark),
+            None => Some(turn.deadline + turn.grace),
+        }
+    }
+
+    /// Emits warnings whose mark has passed and, once the deadline and the player's grace
+    /// period are over, expires the turn.
+    pub fn poll(&mut self, now: Instant) -> Vec<TimerEvent> {
+        let mut events = Vec::new();
+        let Some(turn) = self.active.as_mut().filter(|turn| turn.paused_at.is_none()) else {
+            return events;
+        };
+
+        while let Some(mark) = turn.pending_warnings.first().copied() {
+            if now + mark < turn.deadline {
+                break;
+            }
+            turn.pending_warnings.remove(0);
+            events.push(TimerEvent::Warning {
+                player_id: turn.player_id.clone(),
+                remaining: turn.deadline.saturating_duration_since(now),
+            });
+        }
+
+        if now >= turn.deadline + turn.grace {
+            let player_id = turn.player_id.clone();
+            self.active = None;
+            self.banks.insert(player_id.clone(), Duration::ZERO);
+            events.push(TimerEvent::Expired { player_id });
+        }
+
+        events
+    }
+
+    fn bank_of(&self, player_id: &str) -> Duration {
+        self.banks
+            .get(player_id)
+            .copied()
+            .unwrap_or(self.config.time_bank)
+    }
+
+    fn bank_remaining(&self, turn: &ActiveTurn, now: Instant) -> Duration {
+        // A frozen clock stopped counting when it was paused
+        let now = turn.paused_at.unwrap_or(now);
+        let elapsed = now.saturating_duration_since(turn.started_at);
+        let overtime = elapsed.saturating_sub(self.config.turn);
+        self.bank_of(&turn.player_id).saturating_sub(overtime)
+    }
+}
+
+fn epoch_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn secs(s: u64) -> Duration {
+        Duration::from_secs(s)
+    }
+
+    fn timer() -> TurnTimer {
+        let config = TurnTimerConfig {
+            turn: secs(30),
+            time_bank: secs(20),
+            warnings: vec![secs(5), secs(10)],
+            max_latency_grace: secs(2),
+        };
+        TurnTimer::new(config, &["p1".to_string(), "p2".to_string()])
+    }
+
+    #[test]
+    fn deadline_includes_time_bank() {
+        let mut timer = timer();
+        let t0 = Instant::now();
+        timer.start_turn("p1", t0);
+
+        // First wakeup is the 10s warning before the 50s deadline
+        assert_eq!(timer.next_wakeup(), Some(t0 + secs(40)));
+        assert_eq!(timer.time_bank_remaining(t0), Some(secs(20)));
+        assert!(timer.deadline_epoch_ms().is_some());
+    }
+
+    #[test]
+    fn warnings_fire_in_order_then_expiry() {
+        let mut timer = timer();
+        let t0 = Instant::now();
+        timer.start_turn("p1", t0);
+
+        assert!(timer.poll(t0 + secs(39)).is_empty());
+        assert_eq!(
+            timer.poll(t0 + secs(40)),
+            vec![TimerEvent::Warning {
+                player_id: "p1".to_string(),
+                remaining: secs(10)
+            }]
+        );
+        assert_eq!(timer.next_wakeup(), Some(t0 + secs(45)));
+        assert_eq!(timer.poll(t0 + secs(45)).len(), 1);
+        assert_eq!(
+            timer.poll(t0 + secs(50)),
+            vec![TimerEvent::Expired {
+                player_id: "p1".to_string()
+            }]
+        );
+        assert_eq!(timer.next_wakeup(), None);
+    }
+
+    #[test]
+    fn overtime_is_charged_to_the_bank() {
+        let mut timer = timer();
+        let t0 = Instant::now();
+        timer.start_turn("p1", t0);
+        timer.start_turn("p2", t0 + secs(38)); // 8s over
+
+        timer.start_turn("p1", t0 + secs(40));
+        assert_eq!(timer.time_bank_remaining(t0 + secs(40)), Some(secs(12)));
+    }
+
+    #[test]
+    fn restarting_the_same_player_keeps_the_clock() {
+        let mut timer = timer();
+        let t0 = Instant::now();
+        timer.start_turn("p1", t0);
+        let wakeup = timer.next_wakeup();
+        timer.start_turn("p1", t0 + secs(5));
+        assert_eq!(timer.next_wakeup(), wakeup);
+    }
+
+    #[test]
+    fn expiry_empties_the_bank() {
+        let mut timer = timer();
+        let t0 = Instant::now();
+        timer.start_turn("p1", t0);
+        timer.poll(t0 + secs(50));
+
+        timer.start_turn("p1", t0 + secs(60));
+        assert_eq!(
+            timer.time_bank_remaining(t0 + secs(60)),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(timer.next_wakeup(), Some(t0 + secs(80)));
+    }
+
+    #[test]
+    fn slow_connections_get_a_capped_grace_period() {
+        let mut timer = timer();
+        let t0 = Instant::now();
+        timer.set_round_trip("p1", Duration::from_millis(400));
+        timer.set_round_trip("p2", secs(3));
+
+        timer.start_turn("p1", t0);
+        timer.poll(t0 + secs(45));
+        assert_eq!(
+            timer.next_wakeup(),
+            Some(t0 + Duration::from_millis(50_800))
+        );
+        assert!(timer.poll(t0 + secs(50)).is_empty());
+        assert_eq!(
+            timer.poll(t0 + Duration::from_millis(50_800)),
+            vec![TimerEvent::Expired {
+                player_id: "p1".to_string()
+            }]
+        );
+
+        timer.start_turn("p2", t0 + secs(60));
+        assert_eq!(timer.poll(t0 + secs(111)).len(), 2);
+        assert_eq!(timer.next_wakeup(), Some(t0 + secs(112)));
+    }
+
+    #[test]
+    fn paused_time_does_not_count() {
+        let mut timer = timer();
+        let t0 = Instant::now();
+        timer.start_turn("p1", t0);
+
+        timer.pause(t0 + secs(35));
+        assert_eq!(timer.next_wakeup(), None);
+        assert!(timer.poll(t0 + secs(100)).is_empty());
+        assert_eq!(timer.time_bank_remaining(t0 + secs(100)), Some(secs(15)));
+
+        timer.resume(t0 + secs(100));
+        // 65 seconds paused moves the 10s warning from 40s to 105s
+        assert_eq!(timer.next_wakeup(), Some(t0 + secs(105)));
+        assert_eq!(timer.time_bank_remaining(t0 + secs(100)), Some(secs(15)));
+    }
+
+    #[test]
+    fn disabled_timer_never_runs() {
+        let config = TurnTimerConfig {
+            turn: Duration::ZERO,
+            time_bank: secs(20),
+            warnings: vec![secs(5)],
+            max_latency_grace: secs(2),
+        };
+        let mut timer = TurnTimer::new(config, &["p1".to_string()]);
+        timer.start_turn("p1", Instant::now());
+        assert_eq!(timer.next_wakeup(), None);
+        assert_eq!(timer.deadline_epoch_ms(), None);
+    }
+}
@@ -0,0 +1,222 @@
+use std::collections::VecDeque;
+
+use serde::Deserialize;
+
+use crate::api::events::ClientMessage;
+use crate::api::i18n::Locale;
+use crate::engine::card::Card;
+
+/// Seat id of the bot that plays the learner's opponent.
+pub const TUTOR_BOT_ID: &str = "bot_tutor";
+
+const EMBEDDED_SCRIPT: &str = include_str!("tutorial.json");
+
+/// A scripted lesson: a fixed deal plus the tutor's moves and the prompts shown to the learner.
+#[derive(Debug, Clone, Deserialize)]
+pub struct TutorialScript {
+    /// Cards in the order they leave the deck: the learner's hand, the tutor's hand,
+    /// the first discard, then the draws. The rest of the deck is shuffled underneath.
+    pub deck: Vec<Card>,
+    pub steps: Vec<TutorialStep>,
+}
+
+impl TutorialScript {
+    /// The lesson shipped with the server.
+    pub fn embedded() -> Self {
+        serde_json::from_str(EMBEDDED_SCRIPT).expect("embedded tutorial script is valid")
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TutorialStep {
+    /// Prompt the learner, then wait for an accepted action of type `expect`
+    /// (the `ClientMessage` type tag). Without `expect` the step ends once shown.
+    Hint {
+        text: HintText,
+        #[serde(default)]
+        expect: Option<String>,
+    },
+    /// The tutor bot plays exactly this action on its turn.
+    Bot { action: ClientMessage },
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct HintText {
+    pub es: String,
+    pub en: String,
+}
+
+impl HintText {
+    pub fn in_locale(&self, locale: Locale) -> &str {
+        match locale {
+            Locale::EsCl => &self.es,
+            Locale::En => &self.en,
+        }
+    }
+}
+
+/// A prompt ready to send to the learner.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PendingHint {
+    pub step: usize,
+    pub text: String,
+    pub expect: Option<String>,
+}
+
+/// Walks a room through a [`TutorialScript`]. Once the script runs out the tutor falls back
+/// to the regular bot and the game carries on normally.
+pub struct Tutorial {
+    steps: VecDeque<TutorialStep>,
+    /// Script index of the step at the front of `steps`
+    step: usize,
+    hint_sent: bool,
+}
+
+impl Tutorial {
+    pub fn new(script: TutorialScript) -> Self {
+        Self {
+            steps: script.steps.into(),
+            step: 0,
+            hint_sent: false,
+        }
+    }
+
+    /// The tutor's scripted move, if the script is waiting on one.
+    pub fn next_bot_action(&mut self) -> Option<ClientMessage> {
+        match self.steps.front() {
+            Some(TutorialStep::Bot { action }) => {
+                let action = action.clone();
+                self.advance();
+                Some(action)
+            }
+            _ => None,
+        }
+    }
+
+    /// The current prompt, returned once per step.
+    pub fn pending_hint(&mut self, locale: Locale) -> Option<PendingHint> {
+        if self.hint_sent {
+            return None;
+        }
+        let Some(TutorialStep::Hint { text, expect }) = self.steps.front() else {
+            return None;
+        };
+        let hint = PendingHint {
+            step: self.step,
+            text: text.in_locale(locale).to_string(),
+            expect: expect.clone(),
+        };
+        if hint.expect.is_none() {
+            self.advance();
+        } else {
+            self.hint_sent = true;
+        }
+        Some(hint)
+    }
+
+    /// Records an action the learner got accepted; moves on if it was the one asked for.
+    pub fn on_learner_action(&mut self, action: &ClientMessage) {
+        let Some(TutorialStep::Hint {
+            expect: Some(expect),
+            ..
+        }) = self.steps.front()
+        else {
+            return;
+        };
+        if action_type(action).as_deref() == Some(expect.as_str()) {
+            self.advance();
+        }
+    }
+
+    fn advance(&mut self) {
+        self.steps.pop_front();
+        self.step += 1;
+        self.hint_sent = false;
+    }
+}
+
+/// The `type` tag a message carries on the wire.
+pub fn action_type(action: &ClientMessage) -> Option<String> {
+    serde_json::to_value(action)
+        .ok()?
+        .get("type")?
+        .as_str()
+        .map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::events::DiscardPayload;
+    use crate::engine::combo_finder::find_best_bajada;
+    use crate::engine::deck::Deck;
+    use crate::engine::game::GameState;
+    use crate::engine::rules::MIN_ESCALA_LEN;
+
+    #[test]
+    fn hints_wait_for_the_expected_action() {
+        let mut tutorial = Tutorial::new(TutorialScript::embedded());
+
+        let hint = tutorial.pending_hint(Locale::En).unwrap();
+        assert_eq!(hint.step, 0);
+        assert_eq!(hint.expect.as_deref(), Some("DrawFromDiscard"));
+        assert_eq!(tutorial.pending_hint(Locale::En), None, "Sent only once");
+
+        tutorial.on_learner_action(&ClientMessage::DrawFromDeck);
+        assert_eq!(tutorial.step, 0, "Not the action asked for");
+        tutorial.on_learner_action(&ClientMessage::DrawFromDiscard);
+        assert_eq!(tutorial.pending_hint(Locale::EsCl).unwrap().step, 1);
+    }
+
+    #[test]
+    fn embedded_script_plays_through() {
+        let script = TutorialScript::embedded();
+        let mut game = GameState::new(vec!["learner".to_string(), TUTOR_BOT_ID.to_string()]);
+        game.start_round_with_deck(Deck::stacked(&script.deck).unwrap());
+        let mut tutorial = Tutorial::new(script);
+
+        // The learner follows each prompt; the tutor's moves come from the script
+        game.draw_from_discard().unwrap();
+        tutorial.on_learner_action(&ClientMessage::DrawFromDiscard);
+
+        let hand = game.players[0].hand.clone();
+        let melds =
+            find_best_bajada(&hand, 2, 0, MIN_ESCALA_LEN, true, &game.rules).expect("Two tríos");
+        let combos = melds
+            .iter()
+            .map(|m| m.card_indices.iter().map(|&i| hand[i]).collect())
+            .collect();
+        game.drop_hand("learner", combos).unwrap();
+        game.discard(0).unwrap();
+        for action in [
+            ClientMessage::DropHand {
+                payload: crate::api::events::DropHandPayload {
+                    combinations: vec![],
+                },
+            },
+            ClientMessage::Discard {
+                payload: DiscardPayload { card_index: 0 },
+            },
+        ] {
+            tutorial.on_learner_action(&action);
+        }
+
+        while let Some(action) = tutorial.next_bot_action() {
+            match action {
+                ClientMessage::DrawFromDeck => game.draw_from_deck().unwrap(),
+                ClientMessage::Discard { payload } => {
+                    game.discard(payload.card_index).unwrap();
+                }
+                other => panic!("Unexpected tutor action {:?}", other),
+            }
+        }
+        assert_eq!(game.current_turn, 0);
+
+        game.draw_from_deck().unwrap();
+        let drawn = game.players[0].hand.len() - 1;
+        game.shed_card("learner", drawn, "learner", 0, None)
+            .or_else(|_| game.shed_card("learner", drawn, "learner", 1, None))
+            .expect("The drawn 7♦ extends the trío of Sevens");
+    }
+}
@@ -0,0 +1,486 @@
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use crate::api::events::ClientMessage;
+use crate::db::models::CollusionFlag;
+use crate::db::repo;
+use crate::engine::transcript::Transcript;
+
+/// Days of finished games and logged actions each scan looks back over.
+pub const SCAN_WINDOW_DAYS: i64 = 30;
+
+/// Sheds a player must have laid on opponents' melds before their targets are judged
+const MIN_SHEDS: usize = 10;
+/// Share of those sheds landing on one opponent that looks like feeding them
+const SHED_SHARE: f64 = 0.8;
+/// Ranked games a pair must have shared before their wins are judged
+const MIN_SHARED_GAMES: usize = 6;
+/// Share of the shared games one of the pair won
+const PAIR_WIN_SHARE: f64 = 0.8;
+/// Share of consecutive pair wins where the winner switched
+const ALTERNATION_SHARE: f64 = 0.8;
+/// Discards a player must have had the chance to pick up, from the suspect and from
+/// everyone else, before the two rates are compared
+const MIN_OFFERS: usize = 20;
+/// Standard deviations above their usual pickup rate that look like being fed
+const DISCARD_Z: f64 = 3.0;
+
+/// The pattern a pair of accounts was flagged for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FlagKind {
+    /// `user_a` lays nearly all their sheds on `user_b`'s melds
+    ShedFeeding,
+    /// The two keep winning the games they play together, taking turns
+    WinTrading,
+    /// `user_b` picks up `user_a`'s discards far more often than anyone else's
+    DiscardFeeding,
+}
+
+impl FlagKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FlagKind::ShedFeeding => "shed_feeding",
+            FlagKind::WinTrading => "win_trading",
+            FlagKind::DiscardFeeding => "discard_feeding",
+        }
+    }
+}
+
+/// A finished game as the detector sees it.
+#[derive(Debug, Clone)]
+pub struct FinishedGame {
+    pub room_id: String,
+    pub ranked: bool,
+    pub finished_at: i64,
+    /// Final totals, bots included
+    pub totals: Vec<(String, i64)>,
+    /// `None` when no transcript was kept
+    pub transcript: Option<Transcript>,
+}
+
+/// A card `from` laid on `to`'s meld, from the audit log.
+#[derive(Debug, Clone)]
+pub struct Shed {
+    pub room_id: String,
+    pub from: String,
+    pub to: String,
+}
+
+/// A pair of accounts worth a moderator's look. For the one-way patterns `user_a` is the
+/// one giving and `user_b` the one helped; for win trading the two are in id order.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Suspicion {
+    pub user_a: String,
+    pub user_b: String,
+    pub kind: FlagKind,
+    /// How far past the threshold: a share for sheds and win trading, a z-score for discards
+    pub score: f64,
+    /// Games the evidence comes from
+    pub games: usize,
+    pub detail: String,
+}
+
+/// Bots and hot-seat guests can't collude.
+fn is_account(id: &str) -> bool {
+    !id.starts_with("bot_") && !id.contains('#')
+}
+
+/// Every suspicious pair in `games` and `sheds`, strongest evidence first within each kind.
+pub fn detect(games: &[FinishedGame], sheds: &[Shed]) -> Vec<Suspicion> {
+    let mut flags = shed_feeding(sheds);
+    flags.extend(win_trading(games));
+    flags.extend(discard_feeding(games));
+    flags
+}
+
+fn shed_feeding(sheds: &[Shed]) -> Vec<Suspicion> {
+    // shedder -> target -> rooms, one entry per shed
+    let mut by_shedder: BTreeMap<&str, BTreeMap<&str, Vec<&str>>> = BTreeMap::new();
+    for shed in sheds {
+        if shed.from == shed.to || !is_account(&shed.from) {
+            continue;
+        }
+        by_shedder
+            .entry(&shed.from)
+            .or_default()
+            .entry(&shed.to)
+            .or_default()
+            .push(&shed.room_id);
+    }
+
+    let mut flags = Vec::new();
+    for (from, targets) in by_shedder {
+        let total: usize = targets.values().map(Vec::len).sum();
+        if total < MIN_SHEDS {
+            continue;
+        }
+        for (to, rooms) in targets {
+            let share = rooms.len() as f64 / total as f64;
+            if !is_account(to) || share < SHED_SHARE {
+                continue;
+            }
+            let games = rooms.iter().collect::<HashSet<_>>().len();
+            flags.push(Suspicion {
+                user_a: from.to_string(),
+                user_b: to.to_string(),
+                kind: FlagKind::ShedFeeding,
+                score: share,
+                games,
+                detail: format!(
+                    "{} of {} sheds onto opponents went to {}",
+                    rooms.len(),
+                    total,
+                    to
+                ),
+            });
+        }
+    }
+    sort(&mut flags);
+    flags
+}
+
+/// The single lowest total; `None` on a tie for first.
+fn winner(totals: &[(String, i64)]) -> Option<&str> {
+    let best = totals.iter().map(|(_, total)| *total).min()?;
+    let mut leaders = totals.iter().filter(|(_, total)| *total == best);
+    let (id, _) = leaders.next()?;
+    leaders.next().is_none().then_some(id.as_str())
+}
+
+fn win_trading(games: &[FinishedGame]) -> Vec<Suspicion> {
+    let mut ranked: Vec<&FinishedGame> = games.iter().filter(|g| g.ranked).collect();
+    ranked.sort_by_key(|g| g.finished_at);
+
+    // pair -> winner of each game they shared, oldest first
+    let mut pairs: BTreeMap<(&str, &str), Vec<Option<&str>>> = BTreeMap::new();
+    for game in ranked {
+        let mut accounts: Vec<&str> = game
+            .totals
+            .iter()
+            .map(|(id, _)| id.as_str())
+            .filter(|id| is_account(id))
+            .collect();
+        accounts.sort_unstable();
+        accounts.dedup();
+        let winner = winner(&game.totals);
+        for (i, a) in accounts.iter().enumerate() {
+            for b in &accounts[i + 1..] {
+                let pair_won = winner.filter(|w| w == a || w == b);
+                pairs.entry((a, b)).or_default().push(pair_won);
+            }
+        }
+    }
+
+    let mut flags = Vec::new();
+    for ((a, b), results) in pairs {
+        if results.len() < MIN_SHARED_GAMES {
+            continue;
+        }
+        let wins: Vec<&str> = results.iter().flatten().copied().collect();
+        let win_share = wins.len() as f64 / results.len() as f64;
+        let switches = wins.windows(2).filter(|w| w[0] != w[1]).count();
+        let Some(transitions) = wins.len().checked_sub(1).filter(|n| *n > 0) else {
+            continue;
+        };
+        let alternation = switches as f64 / transitions as f64;
+        if win_share < PAIR_WIN_SHARE || alternation < ALTERNATION_SHARE {
+            continue;
+        }
+        let a_wins = wins.iter().filter(|w| **w == a).count();
+        flags.push(Suspicion {
+            user_a: a.to_string(),
+            user_b: b.to_string(),
+            kind: FlagKind::WinTrading,
+            score: alternation,
+            games: results.len(),
+            detail: format!(
+                "won {} of {} ranked games together ({} by {}, {} by {}), switching winner {} of {} times",
+                wins.len(),
+                results.len(),
+                a_wins,
+                a,
+                wins.len() - a_wins,
+                b,
+                switches,
+                transitions
+            ),
+        });
+    }
+    sort(&mut flags);
+    flags
+}
+
+/// How often a player took the discards offered to them.
+#[derive(Default)]
+struct Pickups<'a> {
+    offered: usize,
+    taken: usize,
+    rooms: HashSet<&'a str>,
+}
+
+fn discard_feeding(games: &[FinishedGame]) -> Vec<Suspicion> {
+    // (discarder, next player) -> what the next player did with the discards
+    let mut offers: BTreeMap<(&str, &str), Pickups> = BTreeMap::new();
+    let mut totals: HashMap<&str, (usize, usize)> = HashMap::new();
+    for game in games {
+        let Some(transcript) = &game.transcript else {
+            continue;
+        };
+        for round in &transcript.rounds {
+            for pair in round.moves.windows(2) {
+                let (discard, draw) = (&pair[0], &pair[1]);
+                if discard.action != "discarded"
+                    || draw.player_id == discard.player_id
+                    || !draw.action.starts_with("drew_from_")
+                {
+                    continue;
+                }
+                let taken = draw.action == "drew_from_pozo";
+                let pickups = offers
+                    .entry((&discard.player_id, &draw.player_id))
+                    .or_default();
+                pickups.offered += 1;
+                pickups.taken += taken as usize;
+                pickups.rooms.insert(&game.room_id);
+                let total = totals.entry(&draw.player_id).or_default();
+                total.0 += 1;
+                total.1 += taken as usize;
+            }
+        }
+    }
+
+    let mut flags = Vec::new();
+    for ((from, to), pickups) in offers {
+        if !is_account(from) || !is_account(to) || pickups.offered < MIN_OFFERS {
+            continue;
+        }
+        let (offered, taken) = totals[to];
+        let (other_offered, other_taken) = (offered - pickups.offered, taken - pickups.taken);
+        if other_offered < MIN_OFFERS {
+            continue;
+        }
+        // Smoothed so a player who never picks up anyone else's discards isn't a zero
+        let usual = (other_taken as f64 + 1.0) / (other_offered as f64 + 2.0);
+        let n = pickups.offered as f64;
+        let z = (pickups.taken as f64 - n * usual) / (n * usual * (1.0 - usual)).sqrt();
+        if z < DISCARD_Z {
+            continue;
+        }
+        flags.push(Suspicion {
+            user_a: from.to_string(),
+            user_b: to.to_string(),
+            kind: FlagKind::DiscardFeeding,
+            score: z,
+            games: pickups.rooms.len(),
+            detail: format!(
+                "{} took {} of {} discards from {} against {} of {} from everyone else",
+                to, pickups.taken, pickups.offered, from, other_taken, other_offered
+            ),
+        });
+    }
+    sort(&mut flags);
+    flags
+}
+
+fn sort(flags: &mut [Suspicion]) {
+    flags.sort_by(|a, b| b.score.total_cmp(&a.score));
+}
+
+/// Runs the detector over the last [`SCAN_WINDOW_DAYS`] and files what it finds for review.
+/// Returns how many pairs were flagged.
+pub async fn scan(pool: &SqlitePool, now: i64) -> Result<usize, sqlx::Error> {
+    let since = now - SCAN_WINDOW_DAYS * 24 * 60 * 60;
+
+    let mut transcripts: HashMap<String, Transcript> =
+        repo::get_room_transcripts_since(pool, since)
+            .await
+            .into_iter()
+            .filter_map(|(room_id, json)| Some((room_id, serde_json::from_str(&json).ok()?)))
+            .collect();
+    let mut totals: HashMap<String, Vec<(String, i64)>> = HashMap::new();
+    for player in repo::get_result_players_since(pool, since).await {
+        totals
+            .entry(player.room_id)
+            .or_default()
+            .push((player.user_id, player.total));
+    }
+    let games: Vec<FinishedGame> = repo::get_counted_results_since(pool, since)
+        .await
+        .into_iter()
+        .map(|result| FinishedGame {
+            totals: totals.remove(&result.room_id).unwrap_or_default(),
+            transcript: transcripts.remove(&result.room_id),
+            room_id: result.room_id,
+            ranked: result.ranked,
+            finished_at: result.finished_at,
+        })
+        .collect();
+
+    let sheds: Vec<Shed> = repo::get_accepted_actions_since(pool, "ShedCard", since * 1000)
+        .await
+        .into_iter()
+        .filter_map(|entry| match serde_json::from_str(&entry.action) {
+            Ok(ClientMessage::ShedCard { payload }) => Some(Shed {
+                room_id: entry.room_id,
+                from: entry.user_id,
+                to: payload.target_player_id,
+            }),
+            _ => None,
+        })
+        .collect();
+
+    let flags = detect(&games, &sheds);
+    for flag in &flags {
+        let flag = CollusionFlag {
+            id: 0,
+            user_a: flag.user_a.clone(),
+            user_b: flag.user_b.clone(),
+            kind: flag.kind.as_str().to_string(),
+            score: flag.score,
+            games: flag.games as i64,
+            detail: flag.detail.clone(),
+            status: "open".to_string(),
+            resolution: None,
+            created_at: now,
+            updated_at: now,
+        };
+        repo::upsert_collusion_flag(pool, &flag).await?;
+    }
+    Ok(flags.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::transcript::{Move, RoundTranscript};
+
+    fn shed(n: usize, from: &str, to: &str) -> Shed {
+        Shed {
+            room_id: format!("room{}", n % 4),
+            from: from.to_string(),
+            to: to.to_string(),
+        }
+    }
+
+    fn game(n: i64, totals: &[(&str, i64)], moves: Vec<Move>) -> FinishedGame {
+        FinishedGame {
+            room_id: format!("room{}", n),
+            ranked: true,
+            finished_at: n,
+            totals: totals.iter().map(|(id, t)| (id.to_string(), *t)).collect(),
+            transcript: Some(Transcript {
+                rounds: vec![RoundTranscript {
+                    round_index: 0,
+                    round_name: String::new(),
+                    deck_seed: None,
+                    deal: Vec::new(),
+                    first_discard: None,
+                    moves,
+                    scores: Vec::new(),
+                    winner_id: None,
+                }],
+            }),
+        }
+    }
+
+    fn play(player: &str, action: &str) -> Move {
+        Move {
+            player_id: player.to_string(),
+            action: action.to_string(),
+            card: None,
+            combinations: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn sheds_piling_onto_one_opponent_are_flagged() {
+        let mut sheds: Vec<Shed> = (0..9).map(|n| shed(n, "ana", "beto")).collect();
+        sheds.push(shed(9, "ana", "carla"));
+        sheds.extend((0..10).map(|n| shed(n, "dani", ["ana", "beto"][n % 2])));
+        sheds.extend((0..12).map(|n| shed(n, "bot_1", "beto")));
+
+        let flags = detect(&[], &sheds);
+        assert_eq!(flags.len(), 1);
+        assert_eq!(
+            (flags[0].user_a.as_str(), flags[0].user_b.as_str()),
+            ("ana", "beto")
+        );
+        assert_eq!(flags[0].kind, FlagKind::ShedFeeding);
+        assert!((flags[0].score - 0.9).abs() < 1e-9);
+        assert_eq!(flags[0].games, 4);
+
+        assert!(detect(&[], &sheds[..9]).is_empty());
+    }
+
+    #[test]
+    fn taking_turns_at_winning_is_flagged() {
+        let traded: Vec<FinishedGame> = (0..8)
+            .map(|n| {
+                let (ana, beto) = if n % 2 == 0 { (10, 50) } else { (50, 10) };
+                game(
+                    n,
+                    &[("ana", ana), ("beto", beto), ("carla", 90)],
+                    Vec::new(),
+                )
+            })
+            .collect();
+        let flags = detect(&traded, &[]);
+        assert_eq!(flags.len(), 1);
+        assert_eq!(flags[0].kind, FlagKind::WinTrading);
+        assert_eq!(
+            (flags[0].user_a.as_str(), flags[0].user_b.as_str()),
+            ("ana", "beto")
+        );
+        assert_eq!(flags[0].games, 8);
+
+        // One strong player winning every game is not trading
+        let dominant: Vec<FinishedGame> = (0..8)
+            .map(|n| game(n, &[("ana", 10), ("beto", 50), ("carla", 90)], Vec::new()))
+            .collect();
+        assert!(detect(&dominant, &[]).is_empty());
+    }
+
+    /// `turns` rounds of each seat in `order` discarding for the next, who picks up the
+    /// first `taken[i]` discards of seat `i`.
+    fn cycle(order: &[&str], taken: &[usize], turns: usize) -> Vec<Move> {
+        let mut moves = Vec::new();
+        for turn in 0..turns {
+            for (i, player) in order.iter().enumerate() {
+                let next = order[(i + 1) % order.len()];
+                moves.push(play(player, "discarded"));
+                let draw = if turn < taken[i] {
+                    "drew_from_pozo"
+                } else {
+                    "drew_from_deck"
+                };
+                moves.push(play(next, draw));
+            }
+        }
+        moves
+    }
+
+    #[test]
+    fn picking_up_one_players_discards_far_more_than_others_is_flagged() {
+        let totals = [("ana", 50), ("beto", 40), ("carla", 30)];
+        let games = [
+            game(
+                0,
+                &totals,
+                cycle(&["ana", "beto", "carla"], &[25, 6, 5], 30),
+            ),
+            game(1, &totals, cycle(&["carla", "beto", "ana"], &[5, 6, 5], 30)),
+        ];
+
+        let flags = detect(&games, &[]);
+        assert_eq!(flags.len(), 1);
+        assert_eq!(flags[0].kind, FlagKind::DiscardFeeding);
+        assert_eq!(
+            (flags[0].user_a.as_str(), flags[0].user_b.as_str()),
+            ("ana", "beto")
+        );
+        assert_eq!(flags[0].games, 1);
+        assert!(flags[0].score > DISCARD_Z);
+    }
+}
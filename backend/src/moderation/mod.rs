@@ -0,0 +1,2 @@
+pub mod collusion;
+pub mod word_filter;
@@ -0,0 +1,248 @@
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+/// Built-in lists used when no filter file is configured.
+const DEFAULT_BLOCKED_ES: &[&str] = &[
+    "conchetumare",
+    "conchetumadre",
+    "ctm",
+    "culiao",
+    "culiado",
+    "maricon",
+    "mierda",
+    "puta",
+    "weon",
+    "hueon",
+];
+const DEFAULT_BLOCKED_EN: &[&str] = &["asshole", "bitch", "cunt", "fuck", "shit"];
+
+/// On-disk format of `CARIOCA_WORD_FILTER_FILE`.
+#[derive(Debug, Default, Deserialize)]
+struct WordFilterFile {
+    /// Blocked words keyed by locale, e.g. `{ "es": [...], "en": [...] }`
+    #[serde(default)]
+    blocked: HashMap<String, Vec<String>>,
+    /// Words that must never be flagged even if they contain a blocked word
+    #[serde(default)]
+    allowed: Vec<String>,
+}
+
+/// Locale-aware profanity filter applied to usernames and chat.
+///
+/// Only whole words count, so "Computadora" is no match for "puta". Matching is done on a
+/// normalized form (lowercase, accents stripped, common leetspeak undone) so "Wé0n" and
+/// "weon" are treated alike.
+#[derive(Debug, Clone, Default)]
+pub struct WordFilter {
+    blocked: HashMap<String, HashSet<String>>,
+    allowed: HashSet<String>,
+}
+
+impl WordFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The built-in es/en lists.
+    pub fn with_defaults() -> Self {
+        let mut filter = Self::new();
+        filter.block_words("es", DEFAULT_BLOCKED_ES.iter().copied());
+        filter.block_words("en", DEFAULT_BLOCKED_EN.iter().copied());
+        filter
+    }
+
+    /// Loads lists from a JSON file, replacing the defaults for every locale it mentions.
+    pub fn from_file(path: &Path) -> Result<Self, String> {
+        let raw = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        let file: WordFilterFile = serde_json::from_str(&raw)
+            .map_err(|e| format!("Invalid word filter file {}: {}", path.display(), e))?;
+
+        let mut filter = Self::with_defaults();
+        for (locale, words) in file.blocked {
+            filter.blocked.remove(&locale);
+            filter.block_words(&locale, words.iter().map(String::as_str));
+        }
+        filter.allow_words(file.allowed.iter().map(String::as_str));
+        Ok(filter)
+    }
+
+    pub fn block_words<'a>(&mut self, locale: &str, words: impl IntoIterator<Item = &'a str>) {
+        let entry = self.blocked.entry(locale.to_string()).or_default();
+        entry.extend(words.into_iter().map(normalize).filter(|w| !w.is_empty()));
+    }
+
+    pub fn allow_words<'a>(&mut self, words: impl IntoIterator<Item = &'a str>) {
+        self.allowed.extend(words.into_iter().map(normalize));
+    }
+
+    /// Everyone sees a username whatever their language, so every locale's list applies.
+    /// Words within a name are told apart by separators and capitals ("El_Weon",
+    /// "WeonMaster"); trailing digits and gamer-tag padding ("weon99", "xXweonXx") are
+    /// looked past.
+    pub fn is_clean_username(&self, username: &str) -> bool {
+        if self.allowed.contains(&normalize(username)) {
+            return true;
+        }
+        !username_words(username).iter().any(|word| {
+            let normalized = normalize(word);
+            let candidates = [
+                normalize(word.trim_end_matches(|c: char| c.is_ascii_digit())),
+                normalized.trim_matches('x').to_string(),
+                normalized,
+            ];
+            candidates
+                .iter()
+                .any(|candidate| self.blocked.values().any(|list| list.contains(candidate)))
+        })
+    }
+
+    /// Replaces every word blocked in `locale` (e.g. "es") with asterisks, leaving the rest
+    /// untouched.
+    pub fn censor(&self, text: &str, locale: &str) -> String {
+        let mut out = String::with_capacity(text.len());
+        let mut word = String::new();
+
+        for ch in text.chars() {
+            if ch.is_alphanumeric() || LEET.iter().any(|(l, _)| *l == ch) {
+                word.push(ch);
+            } else {
+                self.flush_word(&mut word, locale, &mut out);
+                out.push(ch);
+            }
+        }
+        self.flush_word(&mut word, locale, &mut out);
+        out
+    }
+
+    pub fn is_clean_text(&self, text: &str, locale: &str) -> bool {
+        self.censor(text, locale) == text
+    }
+
+    fn flush_word(&self, word: &mut String, locale: &str, out: &mut String) {
+        if word.is_empty() {
+            return;
+        }
+        let normalized = normalize(word);
+        let blocked = self
+            .blocked
+            .get(locale)
+            .is_some_and(|list| list.contains(&normalized));
+        if blocked && !self.allowed.contains(&normalized) {
+            out.extend(std::iter::repeat_n('*', word.chars().count()));
+        } else {
+            out.push_str(word);
+        }
+        word.clear();
+    }
+}
+
+/// Splits a username into words at anything but letters, digits and leetspeak symbols, and
+/// where a capital follows a lowercase letter.
+fn username_words(username: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut previous_lowercase = false;
+    for ch in username.chars() {
+        let part_of_word = ch.is_alphanumeric() || LEET.iter().any(|(l, _)| *l == ch);
+        if !part_of_word || (ch.is_uppercase() && previous_lowercase) {
+            words.extend((!word.is_empty()).then(|| std::mem::take(&mut word)));
+        }
+        if part_of_word {
+            word.push(ch);
+        }
+        previous_lowercase = ch.is_lowercase();
+    }
+    words.extend((!word.is_empty()).then_some(word));
+    words
+}
+
+/// Leetspeak substitutions undone before matching.
+const LEET: &[(char, char)] = &[
+    ('0', 'o'),
+    ('1', 'i'),
+    ('3', 'e'),
+    ('4', 'a'),
+    ('5', 's'),
+    ('7', 't'),
+    ('@', 'a'),
+    ('$', 's'),
+];
+
+fn normalize(text: &str) -> String {
+    text.chars()
+        .flat_map(char::to_lowercase)
+        .map(|c| match c {
+            'á' | 'à' | 'ä' | 'â' => 'a',
+            'é' | 'è' | 'ë' | 'ê' => 'e',
+            'í' | 'ì' | 'ï' | 'î' => 'i',
+            'ó' | 'ò' | 'ö' | 'ô' => 'o',
+            'ú' | 'ù' | 'ü' | 'û' => 'u',
+            other => LEET
+                .iter()
+                .find(|(l, _)| *l == other)
+                .map_or(other, |(_, plain)| *plain),
+        })
+        .filter(|c| c.is_alphanumeric())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn censors_whole_words_in_the_readers_locale() {
+        let filter = WordFilter::with_defaults();
+        assert_eq!(
+            filter.censor("que weon más lento", "es"),
+            "que **** más lento"
+        );
+        assert_eq!(filter.censor("oh shit, a joker", "en"), "oh ****, a joker");
+        assert_eq!(filter.censor("buena jugada", "es"), "buena jugada");
+        assert_eq!(filter.censor("una disputa", "es"), "una disputa");
+        assert_eq!(filter.censor("oh shit", "es"), "oh shit");
+    }
+
+    #[test]
+    fn normalizes_accents_case_and_leetspeak() {
+        let filter = WordFilter::with_defaults();
+        assert!(!filter.is_clean_text("WÉ0N", "es"));
+        assert!(!filter.is_clean_text("sh1t", "en"));
+    }
+
+    #[test]
+    fn usernames_match_whole_words() {
+        let filter = WordFilter::with_defaults();
+        for name in ["xXweonXx", "WeonMaster", "el_weon", "weon99", "CTM", "Sh1t"] {
+            assert!(!filter.is_clean_username(name), "{}", name);
+        }
+        for name in [
+            "carioquero",
+            "Computadora",
+            "Disputa",
+            "Shitake",
+            "Weonardo",
+        ] {
+            assert!(filter.is_clean_username(name), "{}", name);
+        }
+    }
+
+    #[test]
+    fn allowlist_overrides_blocked_words() {
+        let mut filter = WordFilter::with_defaults();
+        assert!(!filter.is_clean_username("Club_CTM"));
+        filter.allow_words(["club_ctm"]);
+        assert!(filter.is_clean_username("Club_CTM"));
+    }
+
+    #[test]
+    fn custom_locale_lists() {
+        let mut filter = WordFilter::new();
+        filter.block_words("pt", ["porra"]);
+        assert_eq!(filter.censor("porra!", "pt"), "*****!");
+        assert!(filter.is_clean_text("porra!", "es"));
+        assert!(filter.is_clean_text("weon", "pt"));
+    }
+}
@@ -0,0 +1,357 @@
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio_rustls::TlsConnector;
+use tokio_rustls::rustls::ClientConfig;
+use tokio_rustls::rustls::pki_types::ServerName;
+
+/// Longest outgoing URL a user may register.
+pub const MAX_URL_CHARS: usize = 512;
+/// Requests failing with a network error or a 5xx are retried this many times in total.
+const MAX_ATTEMPTS: u32 = 3;
+const FIRST_RETRY_DELAY: Duration = Duration::from_secs(2);
+/// Only the status line is read back, so a chatty endpoint can't hold the task.
+const MAX_RESPONSE_HEAD_BYTES: usize = 1024;
+
+/// How outgoing notification requests are made.
+#[derive(Clone)]
+pub struct ClientSettings {
+    /// Trust roots for `https://` targets; without them only `http://` ones are reached.
+    pub tls: Option<Arc<ClientConfig>>,
+    /// Lets requests reach loopback and private networks. Off outside development,
+    /// so a registered URL can't be used to probe the server's own network.
+    pub allow_private_addresses: bool,
+    /// Per attempt, covering connect, TLS handshake, request and response head.
+    pub timeout: Duration,
+}
+
+/// A parsed `http://` or `https://` URL.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpUrl {
+    pub tls: bool,
+    pub host: String,
+    pub port: u16,
+    /// Path and query, always starting with `/`
+    pub path: String,
+}
+
+impl HttpUrl {
+    pub fn parse(url: &str) -> Result<Self, &'static str> {
+        if url.chars().count() > MAX_URL_CHARS {
+            return Err("URL is too long");
+        }
+        let (tls, rest) = if let Some(rest) = url.strip_prefix("https://") {
+            (true, rest)
+        } else if let Some(rest) = url.strip_prefix("http://") {
+            (false, rest)
+        } else {
+            return Err("URL must start with http:// or https://");
+        };
+        if rest.chars().any(|c| c.is_whitespace() || c.is_control()) {
+            return Err("URL contains whitespace");
+        }
+
+        let authority_end = rest.find(['/', '?', '#']).unwrap_or(rest.len());
+        let (authority, path) = rest.split_at(authority_end);
+        if authority.contains('@') {
+            return Err("URL must not contain credentials");
+        }
+        let path = path.split('#').next().unwrap_or_default();
+        let path = match path {
+            "" => "/".to_string(),
+            p if p.starts_with('?') => format!("/{}", p),
+            p => p.to_string(),
+        };
+
+        let default_port = if tls { 443 } else { 80 };
+        let (host, port) = match authority.strip_prefix('[') {
+            // IPv6 literal: [::1]:8080
+            Some(bracketed) => {
+                let (host, after) = bracketed.split_once(']').ok_or("URL has an invalid host")?;
+                match after.strip_prefix(':') {
+                    Some(port) => (host, port.parse().map_err(|_| "Invalid URL port")?),
+                    None if after.is_empty() => (host, default_port),
+                    None => return Err("URL has an invalid host"),
+                }
+            }
+            None => match authority.rsplit_once(':') {
+                Some((host, port)) => (host, port.parse().map_err(|_| "Invalid URL port")?),
+                None => (authority, default_port),
+            },
+        };
+        if host.is_empty() {
+            return Err("URL has no host");
+        }
+
+        Ok(Self {
+            tls,
+            host: host.to_ascii_lowercase(),
+            port,
+            path,
+        })
+    }
+
+    /// `scheme://host[:port]`, omitting the scheme's default port.
+    pub fn origin(&self) -> String {
+        let scheme = if self.tls { "https" } else { "http" };
+        let host = if self.host.contains(':') {
+            format!("[{}]", self.host)
+        } else {
+            self.host.clone()
+        };
+        match (self.tls, self.port) {
+            (true, 443) | (false, 80) => format!("{}://{}", scheme, host),
+            (_, port) => format!("{}://{}:{}", scheme, host, port),
+        }
+    }
+}
+
+/// POSTs `body` to `url` with `headers`, retrying network errors and server errors with
+/// backoff. Returns the final response status; `Err` only when no response came back.
+pub async fn post(
+    url: &HttpUrl,
+    headers: &[(&str, String)],
+    body: &str,
+    settings: &ClientSettings,
+) -> Result<u16, String> {
+    let addr = tokio::net::lookup_host((url.host.as_str(), url.port))
+        .await
+        .map_err(|e| format!("cannot resolve {}: {}", url.host, e))?
+        .next()
+        .ok_or_else(|| format!("cannot resolve {}", url.host))?;
+    if !settings.allow_private_addresses && !is_public(&addr) {
+        return Err(format!("{} resolves to a private address", url.host));
+    }
+
+    let mut request = format!(
+        "POST {} HTTP/1.1\r\nHost: {}\r\nUser-Agent: carioca-notifier\r\n\
+         Content-Length: {}\r\nConnection: close\r\n",
+        url.path,
+        url.host,
+        body.len()
+    );
+    for (name, value) in headers {
+        request.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    request.push_str("\r\n");
+    request.push_str(body);
+
+    let mut delay = FIRST_RETRY_DELAY;
+    let mut attempt = 1;
+    loop {
+        let outcome = tokio::time::timeout(
+            settings.timeout,
+            send_once(url, addr, request.as_bytes(), settings),
+        )
+        .await
+        .unwrap_or_else(|_| Err("timed out".to_string()));
+        match outcome {
+            Ok(status) if status < 500 || attempt >= MAX_ATTEMPTS => return Ok(status),
+            Err(e) if attempt >= MAX_ATTEMPTS => return Err(e),
+            _ => {}
+        }
+        tokio::time::sleep(delay).await;
+        delay *= 2;
+        attempt += 1;
+    }
+}
+
+/// Sends one request to `addr`, resolved from `url`, and returns the response status code.
+async fn send_once(
+    url: &HttpUrl,
+    addr: SocketAddr,
+    request: &[u8],
+    settings: &ClientSettings,
+) -> Result<u16, String> {
+    let stream = TcpStream::connect(addr)
+        .await
+        .map_err(|e| format!("cannot connect to {}: {}", addr, e))?;
+    if !url.tls {
+        return exchange(stream, request).await;
+    }
+
+    let tls = settings
+        .tls
+        .clone()
+        .ok_or("no CA bundle is configured for https:// targets")?;
+    let server_name = ServerName::try_from(url.host.clone())
+        .map_err(|_| format!("invalid TLS server name {}", url.host))?;
+    let stream = TlsConnector::from(tls)
+        .connect(server_name, stream)
+        .await
+        .map_err(|e| format!("TLS handshake with {} failed: {}", url.host, e))?;
+    exchange(stream, request).await
+}
+
+async fn exchange<S: AsyncRead + AsyncWrite + Unpin>(
+    mut stream: S,
+    request: &[u8],
+) -> Result<u16, String> {
+    stream
+        .write_all(request)
+        .await
+        .map_err(|e| format!("write failed: {}", e))?;
+
+    let mut head = Vec::new();
+    let mut buf = [0u8; 256];
+    while !head.windows(2).any(|w| w == b"\r\n") && head.len() < MAX_RESPONSE_HEAD_BYTES {
+        let read = stream
+            .read(&mut buf)
+            .await
+            .map_err(|e| format!("read failed: {}", e))?;
+        if read == 0 {
+            break;
+        }
+        head.extend_from_slice(&buf[..read]);
+    }
+
+    // "HTTP/1.1 204 No Content"
+    String::from_utf8_lossy(&head)
+        .split_whitespace()
+        .nth(1)
+        .and_then(|code| code.parse().ok())
+        .ok_or_else(|| "malformed response".to_string())
+}
+
+/// Whether `addr` is reachable on the public internet.
+fn is_public(addr: &SocketAddr) -> bool {
+    match addr.ip() {
+        IpAddr::V4(ip) => {
+            let [a, b, ..] = ip.octets();
+            !(ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || ip.is_documentation()
+                // Carrier-grade NAT, 100.64.0.0/10
+                || (a == 100 && (64..128).contains(&b)))
+        }
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(v4) => is_public(&SocketAddr::new(IpAddr::V4(v4), addr.port())),
+            None => {
+                let first = ip.segments()[0];
+                !(ip.is_loopback()
+                    || ip.is_unspecified()
+                    // Unique local fc00::/7 and link-local fe80::/10
+                    || (first & 0xfe00) == 0xfc00
+                    || (first & 0xffc0) == 0xfe80)
+            }
+        },
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    #[test]
+    fn parses_urls() {
+        assert_eq!(
+            HttpUrl::parse("https://Hooks.example.com/carioca?key=1#frag"),
+            Ok(HttpUrl {
+                tls: true,
+                host: "hooks.example.com".to_string(),
+                port: 443,
+                path: "/carioca?key=1".to_string(),
+            })
+        );
+        let local = HttpUrl::parse("http://[::1]:8080").unwrap();
+        assert_eq!((local.host.as_str(), local.port), ("::1", 8080));
+        assert_eq!(local.path, "/");
+        assert_eq!(local.origin(), "http://[::1]:8080");
+
+        assert!(HttpUrl::parse("ftp://example.com").is_err());
+        assert!(HttpUrl::parse("https://user:pw@example.com/").is_err());
+        assert!(HttpUrl::parse("http://example.com:http/").is_err());
+        assert!(HttpUrl::parse("https:///path").is_err());
+    }
+
+    #[test]
+    fn private_addresses_are_not_public() {
+        for addr in [
+            "127.0.0.1:80",
+            "10.1.2.3:80",
+            "100.100.0.1:80",
+            "[::1]:80",
+            "[fd00::1]:80",
+        ] {
+            assert!(!is_public(&addr.parse().unwrap()), "{}", addr);
+        }
+        assert!(is_public(&"93.184.216.34:443".parse().unwrap()));
+    }
+
+    pub(crate) fn local_settings() -> ClientSettings {
+        ClientSettings {
+            tls: None,
+            allow_private_addresses: true,
+            timeout: Duration::from_secs(5),
+        }
+    }
+
+    /// Accepts one request on a local port, answers it with `status` and hands back the
+    /// raw request. Returns the server's base URL.
+    pub(crate) async fn one_shot_server(status: u16) -> (String, tokio::task::JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0u8; 1024];
+            let complete = |request: &[u8]| {
+                let text = String::from_utf8_lossy(request);
+                let Some((head, body)) = text.split_once("\r\n\r\n") else {
+                    return false;
+                };
+                let length = head
+                    .lines()
+                    .find_map(|line| line.strip_prefix("Content-Length: "))
+                    .and_then(|len| len.parse().ok())
+                    .unwrap_or(0);
+                body.len() >= length
+            };
+            while !complete(&request) {
+                let read = stream.read(&mut buf).await.unwrap();
+                request.extend_from_slice(&buf[..read]);
+            }
+            let response = format!("HTTP/1.1 {} Whatever\r\n\r\n", status);
+            stream.write_all(response.as_bytes()).await.unwrap();
+            String::from_utf8(request).unwrap()
+        });
+        (url, server)
+    }
+
+    #[tokio::test]
+    async fn posts_with_headers() {
+        let body = r#"{"ok":true}"#;
+        let (base, server) = one_shot_server(204).await;
+        let url = HttpUrl::parse(&format!("{}/hook", base)).unwrap();
+
+        let headers = [("X-Test", "yes".to_string())];
+        let status = post(&url, &headers, body, &local_settings()).await;
+        assert_eq!(status, Ok(204));
+
+        let request = server.await.unwrap();
+        assert!(
+            request.starts_with("POST /hook HTTP/1.1\r\n"),
+            "{}",
+            request
+        );
+        assert!(request.contains("X-Test: yes\r\n"));
+        assert!(request.ends_with(body));
+    }
+
+    #[tokio::test]
+    async fn refuses_private_targets_by_default() {
+        let url = HttpUrl::parse("http://127.0.0.1:9/hook").unwrap();
+        let settings = ClientSettings {
+            allow_private_addresses: false,
+            ..local_settings()
+        };
+        let err = post(&url, &[], "{}", &settings).await.unwrap_err();
+        assert!(err.contains("private address"), "got: {}", err);
+    }
+}
@@ -0,0 +1,4 @@
+pub mod http;
+pub mod notifier;
+pub mod push;
+pub mod webhook;
@@ -0,0 +1,116 @@
+use sqlx::SqlitePool;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+
+use crate::db::repo;
+use crate::notify::http::ClientSettings;
+use crate::notify::push::{self, PendingPushes, PushEvent, PushOutcome, VapidKeys};
+use crate::notify::webhook::{self, WebhookEvent};
+
+enum Outgoing {
+    Webhook(String, WebhookEvent),
+    Push(String, PushEvent),
+}
+
+/// Server-wide notification settings.
+pub struct NotifierSettings {
+    pub http: ClientSettings,
+    /// Web push is disabled without an application key pair
+    pub vapid: Option<VapidKeys>,
+}
+
+/// Cheap, cloneable handle rooms use to reach players outside the game socket, through
+/// their registered webhook and web-push subscriptions.
+///
+/// Lookups and deliveries happen on background tasks so the room actor never waits on
+/// SQLite or a slow endpoint.
+#[derive(Clone)]
+pub struct Notifier {
+    sender: mpsc::UnboundedSender<Outgoing>,
+    pending: PendingPushes,
+    vapid_public_key: Option<Arc<str>>,
+}
+
+impl Notifier {
+    /// Spawns the dispatcher task and returns a handle feeding it.
+    pub fn spawn(pool: SqlitePool, settings: NotifierSettings) -> Self {
+        let (sender, mut receiver) = mpsc::unbounded_channel::<Outgoing>();
+        let pending = PendingPushes::default();
+        let vapid_public_key = settings
+            .vapid
+            .as_ref()
+            .map(|keys| Arc::from(keys.public_key.as_str()));
+        let http = settings.http;
+        let vapid = settings.vapid.map(Arc::new);
+
+        let inbox = pending.clone();
+        tokio::spawn(async move {
+            while let Some(outgoing) = receiver.recv().await {
+                match outgoing {
+                    Outgoing::Webhook(user_id, event) => {
+                        let Some(webhook) = repo::get_webhook(&pool, &user_id).await else {
+                            continue;
+                        };
+                        let http = http.clone();
+                        tokio::spawn(async move {
+                            if let Err(e) = webhook::deliver(&webhook, &event, &http).await {
+                                println!("[Webhook] Delivery to {} failed: {}", user_id, e);
+                            }
+                        });
+                    }
+                    Outgoing::Push(user_id, event) => {
+                        let Some(vapid) = vapid.clone() else {
+                            continue;
+                        };
+                        let endpoints = repo::list_push_endpoints(&pool, &user_id).await;
+                        if endpoints.is_empty() {
+                            continue;
+                        }
+                        inbox.add(&user_id, event);
+                        for endpoint in endpoints {
+                            let (pool, http, vapid) = (pool.clone(), http.clone(), vapid.clone());
+                            tokio::spawn(async move {
+                                match push::send(&endpoint, &vapid, &http).await {
+                                    Ok(PushOutcome::Delivered) => {}
+                                    Ok(PushOutcome::Expired) => {
+                                        let _ =
+                                            repo::delete_push_subscription(&pool, &endpoint).await;
+                                    }
+                                    Err(e) => println!("[Push] Delivery failed: {}", e),
+                                }
+                            });
+                        }
+                    }
+                }
+            }
+        });
+
+        Self {
+            sender,
+            pending,
+            vapid_public_key,
+        }
+    }
+
+    /// Queues a webhook call for `user_id`; dropped silently if they have none.
+    pub fn notify(&self, user_id: &str, event: WebhookEvent) {
+        let _ = self
+            .sender
+            .send(Outgoing::Webhook(user_id.to_string(), event));
+    }
+
+    /// Queues a push to every browser `user_id` subscribed; dropped if they have none.
+    pub fn push(&self, user_id: &str, event: PushEvent) {
+        let _ = self.sender.send(Outgoing::Push(user_id.to_string(), event));
+    }
+
+    /// Events pushed to `user_id` since their service worker last asked.
+    pub fn take_pending_pushes(&self, user_id: &str) -> Vec<PushEvent> {
+        self.pending.take(user_id)
+    }
+
+    /// Public application key browsers subscribe with; `None` when web push is off.
+    pub fn vapid_public_key(&self) -> Option<&str> {
+        self.vapid_public_key.as_deref()
+    }
+}
@@ -0,0 +1,88 @@
+use serde::Serialize;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::api::events::PlayerScore;
+use crate::db::models::Webhook;
+use crate::notify::http::{self, ClientSettings, HttpUrl};
+
+/// Something a player may want to hear about while the app is closed.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum WebhookEvent {
+    YourTurn {
+        room_id: String,
+    },
+    GameFinished {
+        room_id: String,
+        /// Lowest total after the last round; several on a tie
+        winner_ids: Vec<String>,
+        player_scores: Vec<PlayerScore>,
+    },
+}
+
+/// The JSON body POSTed to the callback URL.
+#[derive(Debug, Serialize)]
+struct WebhookPayload<'a> {
+    user_id: &'a str,
+    /// Unix seconds
+    sent_at: i64,
+    #[serde(flatten)]
+    event: &'a WebhookEvent,
+}
+
+/// POSTs `event` to the user's callback URL.
+pub async fn deliver(
+    webhook: &Webhook,
+    event: &WebhookEvent,
+    settings: &ClientSettings,
+) -> Result<(), String> {
+    let url = HttpUrl::parse(&webhook.url).map_err(str::to_string)?;
+    let payload = WebhookPayload {
+        user_id: &webhook.user_id,
+        sent_at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as i64,
+        event,
+    };
+    let body = serde_json::to_string(&payload).map_err(|e| e.to_string())?;
+    let headers = [
+        ("Content-Type", "application/json".to_string()),
+        ("X-Carioca-Secret", webhook.secret.clone()),
+    ];
+
+    match http::post(&url, &headers, &body, settings).await? {
+        200..=299 => Ok(()),
+        status => Err(format!("endpoint answered {}", status)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notify::http::tests::{local_settings, one_shot_server};
+
+    #[tokio::test]
+    async fn posts_the_event_with_the_secret() {
+        let event = WebhookEvent::YourTurn {
+            room_id: "room-1".to_string(),
+        };
+        let (base, server) = one_shot_server(204).await;
+        let webhook = Webhook {
+            user_id: "ana".to_string(),
+            url: format!("{}/hook", base),
+            secret: "s3cret".to_string(),
+            created_at: 0,
+        };
+
+        deliver(&webhook, &event, &local_settings()).await.unwrap();
+
+        let request = server.await.unwrap();
+        assert!(request.contains("X-Carioca-Secret: s3cret\r\n"));
+        let body: serde_json::Value =
+            serde_json::from_str(request.split("\r\n\r\n").nth(1).unwrap()).unwrap();
+        assert_eq!(body["event"], "your_turn");
+        assert_eq!(body["room_id"], "room-1");
+        assert_eq!(body["user_id"], "ana");
+    }
+}
@@ -0,0 +1,113 @@
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+
+use crate::db::models::SharedGame;
+use crate::db::repo;
+
+/// Games listed individually in a record; older ones only count towards the totals.
+const RECENT_GAMES: usize = 5;
+
+/// A player's lifetime record against one opponent, over the counted games both finished.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HeadToHead {
+    pub opponent_id: String,
+    pub games: u32,
+    pub wins: u32,
+    pub losses: u32,
+    pub draws: u32,
+    /// Mean of the opponent's total minus the player's; positive when the player usually
+    /// finishes ahead
+    pub average_margin: f64,
+    /// Newest first
+    pub recent: Vec<SharedGame>,
+}
+
+impl HeadToHead {
+    /// `games` newest first, from the player's side.
+    pub fn from_games(opponent_id: &str, games: Vec<SharedGame>) -> Self {
+        let mut record = Self {
+            opponent_id: opponent_id.to_string(),
+            games: games.len() as u32,
+            wins: 0,
+            losses: 0,
+            draws: 0,
+            average_margin: 0.0,
+            recent: Vec::new(),
+        };
+        let mut margin = 0;
+        for game in &games {
+            margin += game.opponent_total - game.total;
+            match game.total.cmp(&game.opponent_total) {
+                std::cmp::Ordering::Less => record.wins += 1,
+                std::cmp::Ordering::Greater => record.losses += 1,
+                std::cmp::Ordering::Equal => record.draws += 1,
+            }
+        }
+        if !games.is_empty() {
+            record.average_margin = margin as f64 / games.len() as f64;
+        }
+        record.recent = games.into_iter().take(RECENT_GAMES).collect();
+        record
+    }
+}
+
+pub async fn between(pool: &SqlitePool, user_id: &str, opponent_id: &str) -> HeadToHead {
+    let games = repo::get_shared_games(pool, user_id, opponent_id).await;
+    HeadToHead::from_games(opponent_id, games)
+}
+
+/// The player's record against everyone else at the table they have met before. Bots and
+/// hot-seat guests have no record.
+pub async fn for_table(pool: &SqlitePool, user_id: &str, players: &[String]) -> Vec<HeadToHead> {
+    let mut records = Vec::new();
+    for opponent_id in players {
+        if opponent_id == user_id || opponent_id.starts_with("bot_") || opponent_id.contains('#') {
+            continue;
+        }
+        let record = between(pool, user_id, opponent_id).await;
+        if record.games > 0 {
+            records.push(record);
+        }
+    }
+    records
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn game(n: i64, total: i64, opponent_total: i64) -> SharedGame {
+        SharedGame {
+            room_id: format!("room{}", n),
+            finished_at: n,
+            total,
+            opponent_total,
+        }
+    }
+
+    #[test]
+    fn sums_up_the_rivalry() {
+        let games = (0..7)
+            .rev()
+            .map(|n| match n % 3 {
+                0 => game(n, 40, 100),
+                1 => game(n, 90, 60),
+                _ => game(n, 70, 70),
+            })
+            .collect();
+        let record = HeadToHead::from_games("beto", games);
+
+        assert_eq!((record.wins, record.losses, record.draws), (3, 2, 2));
+        assert_eq!(record.games, 7);
+        assert!((record.average_margin - 120.0 / 7.0).abs() < 1e-9);
+        let recent: Vec<i64> = record.recent.iter().map(|g| g.finished_at).collect();
+        assert_eq!(recent, [6, 5, 4, 3, 2]);
+    }
+
+    #[test]
+    fn strangers_have_no_record() {
+        let record = HeadToHead::from_games("beto", Vec::new());
+        assert_eq!(record.games, 0);
+        assert_eq!(record.average_margin, 0.0);
+    }
+}
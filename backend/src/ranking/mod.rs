@@ -0,0 +1,4 @@
+pub mod head_to_head;
+pub mod rating;
+pub mod season;
+pub mod titles;
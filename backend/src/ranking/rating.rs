@@ -0,0 +1,154 @@
+/// Where every player starts, and what soft resets pull ratings back towards.
+pub const BASE_RATING: i64 = 1000;
+/// Most a player can gain or lose in one game, spread across their opponents.
+const K_FACTOR: f64 = 32.0;
+/// Ranked games a new player's rating is provisional for, moving faster to find its level.
+pub const PLACEMENT_GAMES: i64 = 5;
+/// How much faster a provisional rating moves.
+const PLACEMENT_K_MULTIPLIER: f64 = 2.0;
+/// Time off ranked play before a rating starts to decay.
+pub const DECAY_GRACE_SECS: i64 = 14 * 24 * 60 * 60;
+/// Share of a rating's distance above [`BASE_RATING`] lost per full week idle past the grace.
+const DECAY_PER_WEEK: f64 = 0.05;
+const WEEK_SECS: i64 = 7 * 24 * 60 * 60;
+
+/// One seat of a rated game.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RatedSeat {
+    /// Rating going into the game
+    pub rating: i64,
+    /// Final points; lower places higher
+    pub total: u32,
+    /// Still in placement: the rating moves [`PLACEMENT_K_MULTIPLIER`] times as far
+    pub provisional: bool,
+}
+
+/// Whether a player with `ranked_games` behind them is still in placement.
+pub fn is_provisional(ranked_games: i64) -> bool {
+    ranked_games < PLACEMENT_GAMES
+}
+
+/// Fixed ratings for bots: they anchor the scale but are never rated themselves.
+pub fn bot_rating(bot_id: &str) -> i64 {
+    if bot_id.contains("hard") {
+        1300
+    } else if bot_id.contains("medium") {
+        1100
+    } else {
+        900
+    }
+}
+
+/// New ratings after a finished game, in the order given.
+///
+/// `players` holds each player's rating before the game and their final total; a lower
+/// total places higher. Each player is scored against every opponent as a one-on-one
+/// Elo match (win, draw or loss), with the K-factor shared out across the opponents.
+pub fn updated_ratings(players: &[(i64, u32)]) -> Vec<i64> {
+    let seats: Vec<RatedSeat> = players
+        .iter()
+        .map(|&(rating, total)| RatedSeat {
+            rating,
+            total,
+            provisional: false,
+        })
+        .collect();
+    updated_seat_ratings(&seats)
+}
+
+/// [`updated_ratings`], with provisional ratings moving further.
+pub fn updated_seat_ratings(seats: &[RatedSeat]) -> Vec<i64> {
+    let opponents = seats.len().saturating_sub(1).max(1) as f64;
+    seats
+        .iter()
+        .enumerate()
+        .map(|(i, seat)| {
+            let (rating, total) = (seat.rating, seat.total);
+            let delta: f64 = seats
+                .iter()
+                .enumerate()
+                .filter(|&(j, _)| j != i)
+                .map(|(_, other)| {
+                    let (other_rating, other_total) = (other.rating, other.total);
+                    let score = match total.cmp(&other_total) {
+                        std::cmp::Ordering::Less => 1.0,
+                        std::cmp::Ordering::Equal => 0.5,
+                        std::cmp::Ordering::Greater => 0.0,
+                    };
+                    let expected = 1.0 / (1.0 + 10f64.powf((other_rating - rating) as f64 / 400.0));
+                    score - expected
+                })
+                .sum();
+            let k = match seat.provisional {
+                true => K_FACTOR * PLACEMENT_K_MULTIPLIER,
+                false => K_FACTOR,
+            };
+            rating + (k / opponents * delta).round() as i64
+        })
+        .collect()
+}
+
+/// A rating after `idle_secs` without ranked play. Past [`DECAY_GRACE_SECS`], every full
+/// week takes [`DECAY_PER_WEEK`] of what is left above [`BASE_RATING`]; ratings at or below
+/// the base never decay.
+pub fn decayed(rating: i64, idle_secs: i64) -> i64 {
+    let weeks = (idle_secs - DECAY_GRACE_SECS).max(0) / WEEK_SECS;
+    if rating <= BASE_RATING || weeks == 0 {
+        return rating;
+    }
+    let keep = (1.0 - DECAY_PER_WEEK).powi(weeks.min(i32::MAX as i64) as i32);
+    BASE_RATING + ((rating - BASE_RATING) as f64 * keep).round() as i64
+}
+
+/// A rating carried into a new season: `keep` of the distance from [`BASE_RATING`] survives.
+pub fn soft_reset(rating: i64, keep: f64) -> i64 {
+    BASE_RATING + ((rating - BASE_RATING) as f64 * keep).round() as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn winner_gains_what_losers_lose_between_equals() {
+        let after = updated_ratings(&[(1000, 10), (1000, 40), (1000, 80)]);
+        assert_eq!(after, vec![1016, 1000, 984]);
+    }
+
+    #[test]
+    fn upsets_move_ratings_further() {
+        let expected_win = updated_ratings(&[(1300, 5), (1000, 50)]);
+        let upset = updated_ratings(&[(1300, 50), (1000, 5)]);
+        assert!(upset[1] - 1000 > expected_win[0] - 1300);
+    }
+
+    #[test]
+    fn provisional_ratings_move_twice_as_far() {
+        let seat = |total, provisional| RatedSeat {
+            rating: 1000,
+            total,
+            provisional,
+        };
+        let after = updated_seat_ratings(&[seat(10, true), seat(40, false)]);
+        assert_eq!(after, vec![1032, 984]);
+        assert!(is_provisional(PLACEMENT_GAMES - 1));
+        assert!(!is_provisional(PLACEMENT_GAMES));
+    }
+
+    #[test]
+    fn idle_ratings_decay_towards_base_after_the_grace() {
+        assert_eq!(decayed(1400, DECAY_GRACE_SECS), 1400);
+        assert_eq!(decayed(1400, DECAY_GRACE_SECS + WEEK_SECS - 1), 1400);
+        assert_eq!(decayed(1400, DECAY_GRACE_SECS + WEEK_SECS), 1380);
+        assert_eq!(decayed(1400, DECAY_GRACE_SECS + 2 * WEEK_SECS), 1361);
+        assert_eq!(decayed(900, DECAY_GRACE_SECS + 52 * WEEK_SECS), 900);
+        assert_eq!(decayed(1400, i64::MAX), BASE_RATING);
+    }
+
+    #[test]
+    fn soft_reset_halves_the_distance_to_base() {
+        assert_eq!(soft_reset(1400, 0.5), 1200);
+        assert_eq!(soft_reset(800, 0.5), 900);
+        assert_eq!(soft_reset(1234, 0.0), BASE_RATING);
+    }
+}
@@ -0,0 +1,287 @@
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+
+use crate::db::models::{LeaderboardEntry, RatingActivity, Season, SeasonRating};
+use crate::db::repo;
+use crate::ranking::rating::{self, BASE_RATING, RatedSeat};
+
+/// How seasons are laid out and how much rating survives from one to the next.
+#[derive(Debug, Clone, Copy)]
+pub struct SeasonSettings {
+    pub length_secs: i64,
+    /// Share of a rating's distance from the base that carries into the next season
+    pub carryover: f64,
+}
+
+/// The open season, starting the first one at `now` if there has never been one.
+pub async fn ensure_open_season(
+    pool: &SqlitePool,
+    now: i64,
+    settings: SeasonSettings,
+) -> Result<Season, sqlx::Error> {
+    match repo::get_open_season(pool).await {
+        Some(season) => Ok(season),
+        None => repo::insert_season(pool, now, now + settings.length_secs).await,
+    }
+}
+
+/// Closes every season whose end date has passed: archives its standings and opens the
+/// next one with soft-reset ratings. Returns the seasons it closed, oldest first.
+pub async fn roll_over(
+    pool: &SqlitePool,
+    now: i64,
+    settings: SeasonSettings,
+) -> Result<Vec<Season>, sqlx::Error> {
+    let mut closed = Vec::new();
+    let mut season = ensure_open_season(pool, now, settings).await?;
+
+    // Catches up one season at a time if the server was down across several end dates
+    while season.ends_at <= now {
+        let carried: Vec<SeasonRating> = repo::get_season_ratings(pool, season.id)
+            .await
+            .into_iter()
+            .map(|r| SeasonRating {
+                user_id: r.user_id,
+                rating: rating::soft_reset(r.rating, settings.carryover),
+                games: 0,
+            })
+            .collect();
+        let next_ends_at = season.ends_at + settings.length_secs;
+        let next = repo::roll_over_season(pool, &season, now, next_ends_at, &carried).await?;
+        println!(
+            "[Seasons] Closed season {}, season {} runs until {}",
+            season.id, next.id, next.ends_at
+        );
+        closed.push(Season {
+            closed_at: Some(now),
+            ..season
+        });
+        season = next;
+    }
+
+    Ok(closed)
+}
+
+/// How a game moved one player's season rating.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RatingChange {
+    pub user_id: String,
+    /// As stored before the game, ahead of any decay
+    pub before: i64,
+    pub after: i64,
+    /// Still in placement after this game
+    #[serde(default)]
+    pub provisional: bool,
+}
+
+/// How much ranked play a player has had, for placement; players rated before it was
+/// tracked count the games of their current season.
+pub async fn activity(pool: &SqlitePool, user_id: &str, now: i64) -> RatingActivity {
+    if let Some(activity) = repo::get_rating_activity(pool, user_id).await {
+        return activity;
+    }
+    let season_games = match repo::get_open_season(pool).await {
+        Some(season) => repo::get_season_rating(pool, season.id, user_id)
+            .await
+            .map_or(0, |r| r.games),
+        None => 0,
+    };
+    RatingActivity {
+        user_id: user_id.to_string(),
+        ranked_games: season_games,
+        last_ranked_at: now,
+    }
+}
+
+/// Rates a finished ranked game into the open season. `final_totals` lists every seat with
+/// its final points; bots count as fixed-rating opponents and are not stored. Ratings left
+/// idle past the grace decay first, and players still in placement move further. Returns
+/// the human players' changes in seat order.
+pub async fn record_game(
+    pool: &SqlitePool,
+    final_totals: &[(String, u32)],
+    now: i64,
+    settings: SeasonSettings,
+) -> Result<Vec<RatingChange>, sqlx::Error> {
+    if final_totals.iter().all(|(id, _)| id.starts_with("bot_")) {
+        return Ok(Vec::new());
+    }
+    let season = ensure_open_season(pool, now, settings).await?;
+
+    let mut before = Vec::with_capacity(final_totals.len());
+    for (id, total) in final_totals {
+        if id.starts_with("bot_") {
+            let seat = RatedSeat {
+                rating: rating::bot_rating(id),
+                total: *total,
+                provisional: false,
+            };
+            before.push((None, seat));
+            continue;
+        }
+        let current = repo::get_season_rating(pool, season.id, id)
+            .await
+            .unwrap_or(SeasonRating {
+                user_id: id.clone(),
+                rating: BASE_RATING,
+                games: 0,
+            });
+        let activity = activity(pool, id, now).await;
+        let seat = RatedSeat {
+            rating: rating::decayed(current.rating, now - activity.last_ranked_at),
+            total: *total,
+            provisional: rating::is_provisional(activity.ranked_games),
+        };
+        before.push((Some((current, activity)), seat));
+    }
+
+    let seats: Vec<RatedSeat> = before.iter().map(|(_, seat)| *seat).collect();
+    let after = rating::updated_seat_ratings(&seats);
+    let mut changes = Vec::new();
+    for ((player, _), new_rating) in before.into_iter().zip(after) {
+        if let Some((current, activity)) = player {
+            let activity = RatingActivity {
+                ranked_games: activity.ranked_games + 1,
+                last_ranked_at: now,
+                ..activity
+            };
+            changes.push(RatingChange {
+                user_id: current.user_id.clone(),
+                before: current.rating,
+                after: new_rating,
+                provisional: rating::is_provisional(activity.ranked_games),
+            });
+            let updated = SeasonRating {
+                rating: new_rating,
+                games: current.games + 1,
+                ..current
+            };
+            repo::upsert_season_rating(pool, season.id, &updated).await?;
+            repo::upsert_rating_activity(pool, &activity).await?;
+        }
+    }
+
+    Ok(changes)
+}
+
+/// Live ratings for the open season, frozen standings for a closed one.
+pub async fn leaderboard(pool: &SqlitePool, season: &Season, limit: i64) -> Vec<LeaderboardEntry> {
+    if season.closed_at.is_some() {
+        repo::archived_leaderboard(pool, season.id, limit).await
+    } else {
+        repo::live_leaderboard(pool, season.id, limit).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::models::User;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    const DAY: i64 = 24 * 60 * 60;
+    const SETTINGS: SeasonSettings = SeasonSettings {
+        length_secs: 30 * DAY,
+        carryover: 0.5,
+    };
+
+    async fn test_pool() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        repo::create_user_table(&pool).await.unwrap();
+        repo::create_season_tables(&pool).await.unwrap();
+        for name in ["ana", "beto"] {
+            let user = User {
+                id: name.to_string(),
+                username: name.to_string(),
+                password_hash: String::new(),
+                created_at: 0,
+                role: "player".to_string(),
+            };
+            repo::insert_user(&pool, &user).await.unwrap();
+        }
+        pool
+    }
+
+    #[tokio::test]
+    async fn games_update_the_open_season() {
+        let pool = test_pool().await;
+        let game = [
+            ("ana".to_string(), 20),
+            ("beto".to_string(), 90),
+            ("bot_hard".to_string(), 60),
+        ];
+        let changes = record_game(&pool, &game, 0, SETTINGS).await.unwrap();
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[0].before, BASE_RATING);
+        assert!(changes[0].after > changes[0].before);
+
+        let season = repo::get_open_season(&pool).await.unwrap();
+        let board = leaderboard(&pool, &season, 10).await;
+        let names: Vec<&str> = board.iter().map(|e| e.username.as_str()).collect();
+        assert_eq!(names, ["ana", "beto"], "Bots are not rated");
+        assert!(board[0].rating > BASE_RATING);
+        assert!(board[1].rating < BASE_RATING);
+        assert_eq!(board[0].games, 1);
+    }
+
+    #[tokio::test]
+    async fn placement_ends_and_idle_ratings_decay() {
+        let pool = test_pool().await;
+        let game = [("ana".to_string(), 0), ("beto".to_string(), 100)];
+        let mut changes = Vec::new();
+        for _ in 0..rating::PLACEMENT_GAMES {
+            changes = record_game(&pool, &game, 0, SETTINGS).await.unwrap();
+        }
+        assert!(
+            !changes[0].provisional,
+            "Placement is over after the last one"
+        );
+        let placed = changes[0].after;
+
+        let first = record_game(&pool, &game, DAY, SETTINGS).await.unwrap();
+        assert!(first[0].after - first[0].before < placed - changes[0].before);
+
+        let idle = DAY + rating::DECAY_GRACE_SECS + 7 * DAY;
+        let back = record_game(&pool, &game, idle, SETTINGS).await.unwrap();
+        let decayed = rating::decayed(back[0].before, idle - DAY);
+        assert!(decayed < back[0].before);
+        assert!(back[0].after < back[0].before + (first[0].after - first[0].before));
+        let activity = repo::get_rating_activity(&pool, "ana").await.unwrap();
+        assert_eq!(activity.ranked_games, rating::PLACEMENT_GAMES + 2);
+        assert_eq!(activity.last_ranked_at, idle);
+    }
+
+    #[tokio::test]
+    async fn rollover_archives_and_soft_resets() {
+        let pool = test_pool().await;
+        let game = [("ana".to_string(), 0), ("beto".to_string(), 100)];
+        record_game(&pool, &game, 0, SETTINGS).await.unwrap();
+        let first = repo::get_open_season(&pool).await.unwrap();
+        let final_rating = leaderboard(&pool, &first, 1).await[0].rating;
+
+        assert!(roll_over(&pool, DAY, SETTINGS).await.unwrap().is_empty());
+        let closed = roll_over(&pool, 31 * DAY, SETTINGS).await.unwrap();
+        assert_eq!(closed.len(), 1);
+
+        let first = repo::get_season(&pool, first.id).await.unwrap();
+        let archived = leaderboard(&pool, &first, 10).await;
+        assert_eq!(archived[0].username, "ana");
+        assert_eq!(archived[0].rating, final_rating);
+
+        let second = repo::get_open_season(&pool).await.unwrap();
+        assert_eq!(second.starts_at, first.ends_at);
+        let carried = repo::get_season_rating(&pool, second.id, "ana")
+            .await
+            .unwrap();
+        assert_eq!(carried.rating, rating::soft_reset(final_rating, 0.5));
+        assert_eq!(carried.games, 0);
+        assert!(
+            leaderboard(&pool, &second, 10).await.is_empty(),
+            "Nobody has played this season yet"
+        );
+    }
+}
@@ -0,0 +1,223 @@
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+
+use crate::db::models::LeaderboardEntry;
+use crate::db::repo;
+use crate::engine::game::RoundType;
+use crate::engine::transcript::Transcript;
+
+/// Something a player earned and may show next to their name.
+#[derive(Debug, Serialize)]
+pub struct Title {
+    pub id: &'static str,
+    pub name: &'static str,
+    pub description: &'static str,
+}
+
+pub const TITLES: &[Title] = &[
+    Title {
+        id: "rey_escala_real",
+        name: "Rey de la Escala Real",
+        description: "Won the Escala Real round",
+    },
+    Title {
+        id: "mano_limpia",
+        name: "Mano Limpia",
+        description: "Finished a whole game without a single point",
+    },
+    Title {
+        id: "ganador",
+        name: "Ganador de Mesa",
+        description: "Finished a whole game with the fewest points at the table",
+    },
+    Title {
+        id: "campeon_temporada",
+        name: "Campeón de Temporada",
+        description: "Finished a ranked season in first place",
+    },
+    Title {
+        id: "podio_temporada",
+        name: "Podio de Temporada",
+        description: "Finished a ranked season in the top three",
+    },
+];
+
+/// Places at the end of a season that earn `podio_temporada`
+const PODIUM_PLACES: i64 = 3;
+
+/// A title as shown at the table and on the match-found screen.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlayerTitle {
+    pub id: String,
+    pub name: String,
+}
+
+impl From<&Title> for PlayerTitle {
+    fn from(title: &Title) -> Self {
+        Self {
+            id: title.id.to_string(),
+            name: title.name.to_string(),
+        }
+    }
+}
+
+pub fn find(id: &str) -> Option<&'static Title> {
+    TITLES.iter().find(|title| title.id == id)
+}
+
+/// Titles a completed game earned, as `(user_id, title_id)`. Bots and hot-seat guests
+/// earn nothing.
+pub fn earned_in_game(
+    transcript: &Transcript,
+    final_totals: &[(String, u32)],
+) -> Vec<(String, &'static str)> {
+    let mut earned = Vec::new();
+    let escala_real = RoundType::EscalaReal.description();
+    for round in transcript
+        .rounds
+        .iter()
+        .filter(|r| r.round_name == escala_real)
+    {
+        if let Some(winner) = &round.winner_id {
+            earned.push((winner.clone(), "rey_escala_real"));
+        }
+    }
+
+    let fewest = final_totals.iter().map(|(_, total)| *total).min();
+    for (id, total) in final_totals {
+        if *total == 0 {
+            earned.push((id.clone(), "mano_limpia"));
+        }
+        if final_totals.len() > 1 && Some(*total) == fewest {
+            earned.push((id.clone(), "ganador"));
+        }
+    }
+
+    earned.retain(|(id, _)| is_human(id));
+    earned.sort_unstable();
+    earned.dedup();
+    earned
+}
+
+/// Titles a closed season's final standings earned.
+pub fn earned_in_season(standings: &[LeaderboardEntry]) -> Vec<(String, &'static str)> {
+    let mut earned = Vec::new();
+    for entry in standings {
+        if entry.rank == 1 {
+            earned.push((entry.user_id.clone(), "campeon_temporada"));
+        }
+        if entry.rank <= PODIUM_PLACES {
+            earned.push((entry.user_id.clone(), "podio_temporada"));
+        }
+    }
+    earned
+}
+
+fn is_human(user_id: &str) -> bool {
+    !user_id.starts_with("bot_") && !user_id.contains('#')
+}
+
+/// Stores the titles a completed game earned; returns how many were new.
+pub async fn award_for_game(
+    pool: &SqlitePool,
+    transcript: &Transcript,
+    final_totals: &[(String, u32)],
+    now: i64,
+) -> Result<usize, sqlx::Error> {
+    award(pool, earned_in_game(transcript, final_totals), now).await
+}
+
+/// Stores the titles a closed season's standings earned; returns how many were new.
+pub async fn award_for_season(
+    pool: &SqlitePool,
+    season_id: i64,
+    now: i64,
+) -> Result<usize, sqlx::Error> {
+    let standings = repo::archived_leaderboard(pool, season_id, PODIUM_PLACES).await;
+    award(pool, earned_in_season(&standings), now).await
+}
+
+async fn award(
+    pool: &SqlitePool,
+    earned: Vec<(String, &'static str)>,
+    now: i64,
+) -> Result<usize, sqlx::Error> {
+    let mut new = 0;
+    for (user_id, title_id) in earned {
+        if repo::award_title(pool, &user_id, title_id, now).await? {
+            new += 1;
+        }
+    }
+    Ok(new)
+}
+
+/// The title a player picked to show, if any.
+pub async fn shown(pool: &SqlitePool, user_id: &str) -> Option<PlayerTitle> {
+    let title_id = repo::get_selected_title(pool, user_id).await?;
+    find(&title_id).map(PlayerTitle::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::transcript::RoundTranscript;
+
+    fn round(round_name: &str, winner: &str) -> RoundTranscript {
+        RoundTranscript {
+            round_index: 0,
+            round_name: round_name.to_string(),
+            deck_seed: None,
+            deal: Vec::new(),
+            first_discard: None,
+            moves: Vec::new(),
+            scores: Vec::new(),
+            winner_id: Some(winner.to_string()),
+        }
+    }
+
+    #[test]
+    fn games_earn_titles_for_humans_only() {
+        let transcript = Transcript {
+            rounds: vec![
+                round(RoundType::TwoTrios.description(), "beto"),
+                round(RoundType::EscalaReal.description(), "ana"),
+            ],
+        };
+        let totals = [
+            ("ana".to_string(), 0),
+            ("bot_hard".to_string(), 0),
+            ("beto".to_string(), 40),
+            ("host#2".to_string(), 0),
+        ];
+        let earned = earned_in_game(&transcript, &totals);
+        assert_eq!(
+            earned,
+            [
+                ("ana".to_string(), "ganador"),
+                ("ana".to_string(), "mano_limpia"),
+                ("ana".to_string(), "rey_escala_real"),
+            ]
+        );
+        assert!(earned.iter().all(|(_, id)| find(id).is_some()));
+    }
+
+    #[test]
+    fn season_podium_earns_titles() {
+        let entry = |rank: i64, user_id: &str| LeaderboardEntry {
+            rank,
+            user_id: user_id.to_string(),
+            username: user_id.to_string(),
+            rating: 1500,
+            games: 10,
+        };
+        let standings = [entry(1, "ana"), entry(3, "beto"), entry(4, "carla")];
+        assert_eq!(
+            earned_in_season(&standings),
+            [
+                ("ana".to_string(), "campeon_temporada"),
+                ("ana".to_string(), "podio_temporada"),
+                ("beto".to_string(), "podio_temporada"),
+            ]
+        );
+    }
+}
@@ -0,0 +1,146 @@
+use sqlx::SqlitePool;
+use std::collections::HashMap;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::{Mutex, mpsc, oneshot};
+
+use crate::db::repo;
+use crate::engine::transcript::Transcript;
+use crate::matchmaking::room::RoomEvent;
+use crate::moderation::collusion;
+use crate::ranking::season::{self, SeasonSettings};
+use crate::ranking::titles;
+use crate::scheduler::runner::JobOutcome;
+use crate::stats::aggregate::{Aggregator, WINDOW_DAYS};
+
+/// How long a room gets to answer before it is treated as stuck and left alone.
+const ROOM_REPLY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Shuts down finished games nobody is watching any more and forgets rooms whose actor
+/// has already stopped.
+pub async fn close_finished_rooms(
+    active_rooms: &Mutex<HashMap<String, mpsc::Sender<RoomEvent>>>,
+) -> JobOutcome {
+    let rooms: Vec<(String, mpsc::Sender<RoomEvent>)> = active_rooms
+        .lock()
+        .await
+        .iter()
+        .map(|(id, tx)| (id.clone(), tx.clone()))
+        .collect();
+
+    let mut closed = Vec::new();
+    for (room_id, tx) in rooms {
+        let (reply_tx, reply_rx) = oneshot::channel();
+        let finished = if tx.send(RoomEvent::CloseIfFinished(reply_tx)).await.is_err() {
+            true
+        } else {
+            // A dropped reply means the room stopped while we asked
+            tokio::time::timeout(ROOM_REPLY_TIMEOUT, reply_rx)
+                .await
+                .map(|reply| reply.unwrap_or(true))
+                .unwrap_or(false)
+        };
+        if finished {
+            closed.push(room_id);
+        }
+    }
+
+    let mut active_rooms = active_rooms.lock().await;
+    for room_id in &closed {
+        active_rooms.remove(room_id);
+    }
+    Ok(format!(
+        "closed {} rooms, {} still open",
+        closed.len(),
+        active_rooms.len()
+    ))
+}
+
+/// Closes the ranked season once its end date has passed, awarding its podium their titles.
+pub async fn roll_over_seasons(pool: &SqlitePool, settings: SeasonSettings) -> JobOutcome {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64;
+    let closed = season::roll_over(pool, now, settings)
+        .await
+        .map_err(|e| e.to_string())?;
+    for season in &closed {
+        titles::award_for_season(pool, season.id, now)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(match closed.last() {
+        Some(season) => format!("closed season {}", season.id),
+        None => "season still open".to_string(),
+    })
+}
+
+/// Recomputes the global statistics over the last [`WINDOW_DAYS`] of finished games.
+pub async fn aggregate_global_stats(pool: &SqlitePool) -> JobOutcome {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64;
+    let games = repo::get_transcripts_since(pool, now - WINDOW_DAYS * 24 * 60 * 60).await;
+
+    let mut aggregator = Aggregator::default();
+    for (finished_at, json) in &games {
+        if let Ok(transcript) = serde_json::from_str::<Transcript>(json) {
+            aggregator.add_game(*finished_at, &transcript);
+        }
+    }
+    let stats = aggregator.finish(now);
+    let json = serde_json::to_string(&stats).map_err(|e| e.to_string())?;
+    repo::save_global_stats(pool, now, &json)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(format!("aggregated {} games", stats.games))
+}
+
+/// Flags account pairs whose recent games look like collusion for moderators to review.
+pub async fn detect_collusion(pool: &SqlitePool) -> JobOutcome {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64;
+    let flagged = collusion::scan(pool, now)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(format!("{} suspicious pairs", flagged))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn forgets_finished_and_stopped_rooms() {
+        let active_rooms = Mutex::new(HashMap::new());
+
+        let (finished_tx, mut finished_rx) = mpsc::channel(4);
+        let (playing_tx, mut playing_rx) = mpsc::channel(4);
+        let (stopped_tx, stopped_rx) = mpsc::channel(4);
+        drop(stopped_rx);
+        tokio::spawn(async move {
+            while let Some(RoomEvent::CloseIfFinished(reply)) = finished_rx.recv().await {
+                let _ = reply.send(true);
+            }
+        });
+        tokio::spawn(async move {
+            while let Some(RoomEvent::CloseIfFinished(reply)) = playing_rx.recv().await {
+                let _ = reply.send(false);
+            }
+        });
+        {
+            let mut rooms = active_rooms.lock().await;
+            rooms.insert("finished".to_string(), finished_tx);
+            rooms.insert("playing".to_string(), playing_tx);
+            rooms.insert("stopped".to_string(), stopped_tx);
+        }
+
+        let summary = close_finished_rooms(&active_rooms).await.unwrap();
+        assert_eq!(summary, "closed 2 rooms, 1 still open");
+        let rooms = active_rooms.lock().await;
+        assert!(rooms.contains_key("playing"));
+    }
+}
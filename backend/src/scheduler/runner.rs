@@ -0,0 +1,153 @@
+use serde::Serialize;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio::time::MissedTickBehavior;
+
+/// What a job run reports: a short summary on success, the reason on failure.
+pub type JobOutcome = Result<String, String>;
+
+/// The last known state of a recurring job, as shown in the admin API.
+#[derive(Debug, Clone, Serialize)]
+pub struct JobStatus {
+    pub name: &'static str,
+    pub interval_secs: u64,
+    pub running: bool,
+    pub runs: u64,
+    pub failures: u64,
+    pub last_started_at: Option<i64>,
+    pub last_duration_ms: Option<u64>,
+    /// Summary of the last successful run
+    pub last_result: Option<String>,
+    /// Cleared by the next successful run
+    pub last_error: Option<String>,
+}
+
+/// Runs maintenance jobs on fixed intervals inside the server process.
+///
+/// Each job gets its own task, so a slow job never delays the others, and each run is
+/// spawned separately so a panic is recorded as a failure instead of stopping the job.
+#[derive(Clone, Default)]
+pub struct Scheduler {
+    jobs: Arc<Mutex<Vec<JobStatus>>>,
+}
+
+impl Scheduler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Runs `job` every `interval`, starting one interval from now. Runs never overlap;
+    /// if one overruns, the next starts a full interval after it finishes.
+    pub fn every<F, Fut>(&self, name: &'static str, interval: Duration, job: F)
+    where
+        F: Fn() -> Fut + Send + 'static,
+        Fut: Future<Output = JobOutcome> + Send + 'static,
+    {
+        let index = {
+            let mut jobs = self.jobs.lock().unwrap();
+            jobs.push(JobStatus {
+                name,
+                interval_secs: interval.as_secs(),
+                running: false,
+                runs: 0,
+                failures: 0,
+                last_started_at: None,
+                last_duration_ms: None,
+                last_result: None,
+                last_error: None,
+            });
+            jobs.len() - 1
+        };
+
+        let jobs = self.jobs.clone();
+        tokio::spawn(async move {
+            let start = tokio::time::Instant::now() + interval;
+            let mut ticks = tokio::time::interval_at(start, interval);
+            ticks.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            loop {
+                ticks.tick().await;
+                let started = Instant::now();
+                Self::update(&jobs, index, |status| {
+                    status.running = true;
+                    status.last_started_at = Some(now_secs());
+                });
+
+                let outcome = tokio::spawn(job())
+                    .await
+                    .unwrap_or_else(|e| Err(format!("job panicked: {}", e)));
+
+                if let Err(e) = &outcome {
+                    println!("[Scheduler] {} failed: {}", name, e);
+                }
+                Self::update(&jobs, index, |status| {
+                    status.running = false;
+                    status.runs += 1;
+                    status.last_duration_ms = Some(started.elapsed().as_millis() as u64);
+                    match outcome {
+                        Ok(summary) => {
+                            status.last_result = Some(summary);
+                            status.last_error = None;
+                        }
+                        Err(e) => {
+                            status.failures += 1;
+                            status.last_error = Some(e);
+                        }
+                    }
+                });
+            }
+        });
+    }
+
+    /// Every registered job, in registration order.
+    pub fn status(&self) -> Vec<JobStatus> {
+        self.jobs.lock().unwrap().clone()
+    }
+
+    fn update(jobs: &Mutex<Vec<JobStatus>>, index: usize, change: impl FnOnce(&mut JobStatus)) {
+        change(&mut jobs.lock().unwrap()[index]);
+    }
+}
+
+fn now_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    #[tokio::test]
+    async fn records_runs_and_failures() {
+        let scheduler = Scheduler::new();
+        let calls = Arc::new(AtomicU32::new(0));
+        let counter = calls.clone();
+        scheduler.every("flaky", Duration::from_millis(10), move || {
+            let call = counter.fetch_add(1, Ordering::SeqCst);
+            async move {
+                match call {
+                    0 => Err("database locked".to_string()),
+                    1 => panic!("boom"),
+                    _ => Ok(format!("call {}", call)),
+                }
+            }
+        });
+
+        let status = &scheduler.status()[0];
+        assert_eq!(status.name, "flaky");
+        assert_eq!(status.runs, 0, "Nothing runs before the first interval");
+
+        while calls.load(Ordering::SeqCst) < 4 {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        let status = &scheduler.status()[0];
+        assert!(status.runs >= 3);
+        assert_eq!(status.failures, 2, "A panic counts as a failure");
+        assert!(status.last_result.as_deref().unwrap().starts_with("call "));
+        assert_eq!(status.last_error, None);
+    }
+}
@@ -0,0 +1,242 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+
+use crate::engine::transcript::Transcript;
+
+/// How far back the aggregation looks.
+pub const WINDOW_DAYS: i64 = 30;
+const DAY_SECS: i64 = 24 * 60 * 60;
+
+/// Server-wide numbers for players and for tuning presets and bots. Nothing in here can be
+/// traced back to a player.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GlobalStats {
+    /// Unix seconds
+    pub computed_at: i64,
+    pub window_days: i64,
+    pub games: u64,
+    /// Days with at least one finished game, oldest first
+    pub games_per_day: Vec<DayCount>,
+    /// Turns taken per finished round, over every seat
+    pub average_round_turns: Option<f64>,
+    /// In play order
+    pub round_types: Vec<RoundTypeStats>,
+    pub jokers: JokerStats,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DayCount {
+    /// Unix seconds at 00:00 UTC
+    pub day_start: i64,
+    pub games: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RoundTypeStats {
+    pub round_index: usize,
+    pub round_name: String,
+    pub rounds: u64,
+    /// Share of seats that got their hand down before the round ended
+    pub bajada_rate: f64,
+    pub average_turns: f64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct JokerStats {
+    pub bajadas: u64,
+    /// Bajadas with at least one joker in them
+    pub bajadas_with_jokers: u64,
+    /// Jokers put down in bajadas or shed onto the table
+    pub jokers_played: u64,
+    pub jokers_per_game: f64,
+}
+
+#[derive(Default)]
+struct RoundTotals {
+    name: String,
+    rounds: u64,
+    seats: u64,
+    bajadas: u64,
+    turns: u64,
+}
+
+/// Folds finished games into [`GlobalStats`].
+#[derive(Default)]
+pub struct Aggregator {
+    games: u64,
+    per_day: BTreeMap<i64, u64>,
+    round_types: BTreeMap<usize, RoundTotals>,
+    jokers: JokerStats,
+}
+
+impl Aggregator {
+    pub fn add_game(&mut self, finished_at: i64, transcript: &Transcript) {
+        self.games += 1;
+        *self
+            .per_day
+            .entry(finished_at - finished_at.rem_euclid(DAY_SECS))
+            .or_default() += 1;
+
+        for round in transcript.rounds.iter().filter(|r| !r.scores.is_empty()) {
+            let mut dropped = HashSet::new();
+            let mut turns = 0;
+            for play in &round.moves {
+                match play.action.as_str() {
+                    "bajó" => {
+                        dropped.insert(play.player_id.as_str());
+                        let jokers = play.combinations.iter().flatten().filter(|c| c.is_joker());
+                        let jokers = jokers.count() as u64;
+                        self.jokers.bajadas += 1;
+                        self.jokers.bajadas_with_jokers += u64::from(jokers > 0);
+                        self.jokers.jokers_played += jokers;
+                    }
+                    "shed" if play.card.is_some_and(|c| c.is_joker()) => {
+                        self.jokers.jokers_played += 1;
+                    }
+                    // Whatever ends a turn
+                    "discarded" | "passed" | "penalty_draw" | "skipped" => turns += 1,
+                    _ => {}
+                }
+            }
+
+            let totals = self.round_types.entry(round.round_index).or_default();
+            totals.name.clone_from(&round.round_name);
+            totals.rounds += 1;
+            totals.seats += round.scores.len() as u64;
+            totals.bajadas += dropped.len() as u64;
+            totals.turns += turns;
+        }
+    }
+
+    pub fn finish(self, computed_at: i64) -> GlobalStats {
+        let rounds: u64 = self.round_types.values().map(|t| t.rounds).sum();
+        let turns: u64 = self.round_types.values().map(|t| t.turns).sum();
+        let ratio = |a: u64, b: u64| if b == 0 { 0.0 } else { a as f64 / b as f64 };
+
+        GlobalStats {
+            computed_at,
+            window_days: WINDOW_DAYS,
+            games: self.games,
+            games_per_day: self
+                .per_day
+                .into_iter()
+                .map(|(day_start, games)| DayCount { day_start, games })
+                .collect(),
+            average_round_turns: (rounds > 0).then(|| ratio(turns, rounds)),
+            round_types: self
+                .round_types
+                .into_iter()
+                .map(|(round_index, t)| RoundTypeStats {
+                    round_index,
+                    round_name: t.name,
+                    rounds: t.rounds,
+                    bajada_rate: ratio(t.bajadas, t.seats),
+                    average_turns: ratio(t.turns, t.rounds),
+                })
+                .collect(),
+            jokers: JokerStats {
+                jokers_per_game: ratio(self.jokers.jokers_played, self.games),
+                ..self.jokers
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::card::{Card, Suit, Value};
+    use crate::engine::transcript::{Move, RoundScore, RoundTranscript};
+
+    fn play(player_id: &str, action: &str) -> Move {
+        Move {
+            player_id: player_id.to_string(),
+            action: action.to_string(),
+            card: None,
+            combinations: Vec::new(),
+        }
+    }
+
+    fn round(round_index: usize, moves: Vec<Move>, finished: bool) -> RoundTranscript {
+        let score = |id: &str| RoundScore {
+            player_id: id.to_string(),
+            round_points: 0,
+            total_points: 0,
+        };
+        RoundTranscript {
+            round_index,
+            round_name: format!("Round {}", round_index + 1),
+            deck_seed: None,
+            deal: Vec::new(),
+            first_discard: None,
+            moves,
+            scores: if finished {
+                vec![score("ana"), score("beto")]
+            } else {
+                Vec::new()
+            },
+            winner_id: None,
+        }
+    }
+
+    #[test]
+    fn aggregates_days_rounds_and_jokers() {
+        let trio = vec![
+            Card::Joker,
+            Card::Standard {
+                suit: Suit::Hearts,
+                value: Value::Five,
+            },
+            Card::Standard {
+                suit: Suit::Clubs,
+                value: Value::Five,
+            },
+        ];
+        let bajada = Move {
+            combinations: vec![trio.clone(), trio],
+            ..play("ana", "bajó")
+        };
+        let joker_shed = Move {
+            card: Some(Card::Joker),
+            ..play("ana", "shed")
+        };
+        let first = Transcript {
+            rounds: vec![
+                round(
+                    0,
+                    vec![
+                        play("ana", "discarded"),
+                        play("beto", "discarded"),
+                        bajada,
+                        joker_shed,
+                        play("ana", "discarded"),
+                    ],
+                    true,
+                ),
+                round(1, vec![play("ana", "discarded")], false),
+            ],
+        };
+        let second = Transcript {
+            rounds: vec![round(0, vec![play("ana", "passed")], true)],
+        };
+
+        let mut aggregator = Aggregator::default();
+        aggregator.add_game(DAY_SECS + 10, &first);
+        aggregator.add_game(DAY_SECS * 3 + 5, &second);
+        let stats = aggregator.finish(DAY_SECS * 4);
+
+        let days: Vec<(i64, u64)> = stats
+            .games_per_day
+            .iter()
+            .map(|d| (d.day_start, d.games))
+            .collect();
+        assert_eq!(days, [(DAY_SECS, 1), (DAY_SECS * 3, 1)]);
+        assert_eq!(stats.round_types.len(), 1, "Unfinished rounds are left out");
+        assert_eq!(stats.round_types[0].rounds, 2);
+        assert_eq!(stats.round_types[0].bajada_rate, 0.25);
+        assert_eq!(stats.average_round_turns, Some(2.0));
+        assert_eq!(stats.jokers.bajadas_with_jokers, 1);
+        assert_eq!(stats.jokers.jokers_played, 3);
+        assert_eq!(stats.jokers.jokers_per_game, 1.5);
+    }
+}
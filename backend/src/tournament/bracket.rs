@@ -0,0 +1,173 @@
+use serde::{Deserialize, Serialize};
+
+/// A single-elimination bracket of heads-up games. Round 0 pairs the seeds so the best
+/// meet as late as possible; when the field isn't a power of two, the top seeds get byes.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Bracket {
+    /// First round first; each round has half the matches of the one before
+    pub rounds: Vec<Vec<BracketMatch>>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BracketMatch {
+    /// `None` for a bye, or while the feeding match is undecided
+    pub players: [Option<String>; 2],
+    pub winner: Option<String>,
+    /// Room the match is played in, once opened
+    pub room_id: Option<String>,
+}
+
+impl BracketMatch {
+    /// Both players are known and the match hasn't been decided.
+    pub fn is_playable(&self) -> bool {
+        self.winner.is_none() && self.players.iter().all(Option::is_some)
+    }
+}
+
+impl Bracket {
+    /// Builds the bracket for `players` in seed order, best first.
+    pub fn seeded(players: &[String]) -> Result<Self, &'static str> {
+        if players.len() < 2 {
+            return Err("A tournament needs at least two players");
+        }
+
+        let size = players.len().next_power_of_two();
+        let mut order = vec![1];
+        while order.len() < size {
+            let sum = order.len() * 2 + 1;
+            order = order.iter().flat_map(|&seed| [seed, sum - seed]).collect();
+        }
+
+        let seat = |seed: usize| players.get(seed - 1).cloned();
+        let first: Vec<BracketMatch> = order
+            .chunks(2)
+            .map(|pair| BracketMatch {
+                players: [seat(pair[0]), seat(pair[1])],
+                ..BracketMatch::default()
+            })
+            .collect();
+        let mut rounds = vec![first];
+        while rounds.last().is_some_and(|round| round.len() > 1) {
+            let matches = rounds.last().map_or(0, Vec::len) / 2;
+            rounds.push(vec![BracketMatch::default(); matches]);
+        }
+
+        let mut bracket = Self { rounds };
+        for index in 0..bracket.rounds[0].len() {
+            let [a, b] = bracket.rounds[0][index].players.clone();
+            if let (Some(player), None) | (None, Some(player)) = (a, b) {
+                bracket.advance(0, index, player);
+            }
+        }
+        Ok(bracket)
+    }
+
+    /// Records who won a match and moves them on to the next round.
+    pub fn record_winner(
+        &mut self,
+        round: usize,
+        index: usize,
+        winner: &str,
+    ) -> Result<(), &'static str> {
+        let played = self
+            .rounds
+            .get(round)
+            .and_then(|matches| matches.get(index))
+            .ok_or("No such match")?;
+        if !played.is_playable() {
+            return Err("That match is not being played");
+        }
+        if !played.players.iter().flatten().any(|p| p == winner) {
+            return Err("The winner must be one of the match's players");
+        }
+        self.advance(round, index, winner.to_string());
+        Ok(())
+    }
+
+    /// Every match ready to be played, as `(round, index)`.
+    pub fn playable(&self) -> Vec<(usize, usize)> {
+        self.rounds
+            .iter()
+            .enumerate()
+            .flat_map(|(round, matches)| {
+                matches
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, m)| m.is_playable())
+                    .map(move |(index, _)| (round, index))
+            })
+            .collect()
+    }
+
+    pub fn champion(&self) -> Option<&str> {
+        self.rounds.last()?.first()?.winner.as_deref()
+    }
+
+    fn advance(&mut self, round: usize, index: usize, winner: String) {
+        self.rounds[round][index].winner = Some(winner.clone());
+        if let Some(next) = self.rounds.get_mut(round + 1) {
+            next[index / 2].players[index % 2] = Some(winner);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn players(n: usize) -> Vec<String> {
+        (1..=n).map(|seed| format!("p{}", seed)).collect()
+    }
+
+    fn pairing(m: &BracketMatch) -> [Option<&str>; 2] {
+        [m.players[0].as_deref(), m.players[1].as_deref()]
+    }
+
+    #[test]
+    fn seeds_keep_the_favourites_apart() {
+        let bracket = Bracket::seeded(&players(8)).unwrap();
+        let first: Vec<_> = bracket.rounds[0].iter().map(pairing).collect();
+        assert_eq!(
+            first,
+            [
+                [Some("p1"), Some("p8")],
+                [Some("p4"), Some("p5")],
+                [Some("p2"), Some("p7")],
+                [Some("p3"), Some("p6")],
+            ]
+        );
+        assert_eq!(bracket.rounds.len(), 3);
+        assert_eq!(bracket.playable().len(), 4);
+    }
+
+    #[test]
+    fn top_seeds_get_byes() {
+        let bracket = Bracket::seeded(&players(5)).unwrap();
+        assert_eq!(bracket.rounds[0][0].winner.as_deref(), Some("p1"));
+        assert_eq!(bracket.rounds[1][0].players[0].as_deref(), Some("p1"));
+        assert_eq!(
+            bracket.playable(),
+            [(0, 1), (1, 1)],
+            "p4 plays p5 while p2 and p3 already meet in the semifinal"
+        );
+        assert!(Bracket::seeded(&players(1)).is_err());
+    }
+
+    #[test]
+    fn winners_advance_to_the_final() {
+        let mut bracket = Bracket::seeded(&players(4)).unwrap();
+        assert!(bracket.record_winner(0, 0, "p2").is_err(), "p2 isn't in it");
+        bracket.record_winner(0, 0, "p4").unwrap();
+        bracket.record_winner(0, 1, "p2").unwrap();
+        assert!(
+            bracket.record_winner(0, 1, "p3").is_err(),
+            "Already decided"
+        );
+
+        assert_eq!(pairing(&bracket.rounds[1][0]), [Some("p4"), Some("p2")]);
+        assert_eq!(bracket.champion(), None);
+        bracket.record_winner(1, 0, "p2").unwrap();
+        assert_eq!(bracket.champion(), Some("p2"));
+        assert!(bracket.playable().is_empty());
+    }
+}
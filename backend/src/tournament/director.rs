@@ -0,0 +1,162 @@
+use std::cmp::Reverse;
+
+use crate::api::admission::Admission;
+use crate::api::server::AppState;
+use crate::db::models::Tournament;
+use crate::db::repo;
+use crate::matchmaking::room::Room;
+use crate::notify::push::PushEvent;
+use crate::ranking::rating::BASE_RATING;
+use crate::scheduler::runner::JobOutcome;
+use crate::tournament::bracket::Bracket;
+
+/// Seeds the registered players by their rating this season, builds the bracket and opens
+/// the first round's rooms.
+pub async fn start(state: &AppState, tournament: &Tournament) -> Result<Bracket, String> {
+    let season = repo::get_open_season(&state.db).await;
+    let mut seeds = Vec::new();
+    for player in repo::get_tournament_players(&state.db, tournament.id).await {
+        let rating = match &season {
+            Some(season) => repo::get_season_rating(&state.db, season.id, &player.user_id)
+                .await
+                .map_or(BASE_RATING, |r| r.rating),
+            None => BASE_RATING,
+        };
+        seeds.push((player.user_id, rating));
+    }
+    // Stable, so equal ratings keep registration order
+    seeds.sort_by_key(|(_, rating)| Reverse(*rating));
+    let seeds: Vec<String> = seeds.into_iter().map(|(id, _)| id).collect();
+
+    let mut bracket = Bracket::seeded(&seeds)?;
+    open_rooms(state, &mut bracket).await;
+    save(state, tournament.id, &bracket).await?;
+    Ok(bracket)
+}
+
+/// Collects the results of finished matches, moves their winners on and opens rooms for
+/// the matches that became playable.
+pub async fn advance(state: &AppState) -> JobOutcome {
+    let running = repo::list_tournaments_by_status(&state.db, "running").await;
+    let mut changed = 0;
+    for tournament in &running {
+        let Some(mut bracket) = tournament
+            .bracket
+            .as_deref()
+            .and_then(|json| serde_json::from_str::<Bracket>(json).ok())
+        else {
+            println!("[Tournament {}] Unreadable bracket", tournament.id);
+            continue;
+        };
+        let before = bracket.clone();
+
+        for (round, index) in bracket.playable() {
+            let Some(room_id) = bracket.rounds[round][index].room_id.clone() else {
+                continue;
+            };
+            if repo::get_game_result(&state.db, &room_id).await.is_some() {
+                // Lowest total wins; a tie goes to the higher seed
+                let totals = repo::get_game_result_players(&state.db, &room_id).await;
+                let slots = &bracket.rounds[round][index].players;
+                let winner = slots
+                    .iter()
+                    .flatten()
+                    .min_by_key(|id| {
+                        totals
+                            .iter()
+                            .find(|p| &p.user_id == *id)
+                            .map_or(i64::MAX, |p| p.total)
+                    })
+                    .cloned();
+                if let Some(winner) = winner {
+                    bracket.record_winner(round, index, &winner)?;
+                }
+            } else if !state.active_rooms.lock().await.contains_key(&room_id) {
+                // The room went away without a result, e.g. across a restart: replay it
+                bracket.rounds[round][index].room_id = None;
+            }
+        }
+        open_rooms(state, &mut bracket).await;
+
+        if bracket != before {
+            save(state, tournament.id, &bracket).await?;
+            changed += 1;
+        }
+    }
+    Ok(format!(
+        "{} of {} running tournaments changed",
+        changed,
+        running.len()
+    ))
+}
+
+/// Opens a room for every playable match that doesn't have one yet. A full server leaves
+/// the rest for the next pass.
+async fn open_rooms(state: &AppState, bracket: &mut Bracket) {
+    for (round, index) in bracket.playable() {
+        let pairing = &mut bracket.rounds[round][index];
+        if pairing.room_id.is_some() {
+            continue;
+        }
+        let players: Vec<String> = pairing.players.iter().flatten().cloned().collect();
+        let Some(room_id) = open_match(state, &players).await else {
+            return;
+        };
+        pairing.room_id = Some(room_id);
+    }
+}
+
+/// Opens a room for a scheduled game between `players` and lets them know, unless the
+/// server is in maintenance or out of room capacity.
+pub async fn open_match(state: &AppState, players: &[String]) -> Option<String> {
+    if state.maintenance.is_active() {
+        return None;
+    }
+    let Admission::Admitted(permit) = state.capacity.rooms.enter() else {
+        return None;
+    };
+
+    let room_id = uuid::Uuid::new_v4().to_string();
+    let (tx, rx) = tokio::sync::mpsc::channel(100);
+    let room = Room::new(
+        room_id.clone(),
+        players.to_vec(),
+        rx,
+        tx.clone(),
+        state.room_services(),
+    );
+    tokio::spawn(async move {
+        room.run().await;
+        drop(permit);
+    });
+    state.active_rooms.lock().await.insert(room_id.clone(), tx);
+
+    for player in players.iter().filter(|id| !id.starts_with("bot_")) {
+        let event = PushEvent::MatchFound {
+            room_id: room_id.clone(),
+        };
+        state.notifier.push(player, event);
+    }
+    Some(room_id)
+}
+
+/// Stores the bracket, finishing the tournament once it has a champion, and lets every
+/// participant know.
+async fn save(state: &AppState, tournament_id: i64, bracket: &Bracket) -> Result<(), String> {
+    let status = match bracket.champion() {
+        Some(_) => "finished",
+        None => "running",
+    };
+    let json = serde_json::to_string(bracket).map_err(|e| e.to_string())?;
+    repo::update_tournament(&state.db, tournament_id, status, &json)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    for player in repo::get_tournament_players(&state.db, tournament_id).await {
+        state.notifier.push(
+            &player.user_id,
+            PushEvent::TournamentUpdate { tournament_id },
+        );
+    }
+    Ok(())
+}
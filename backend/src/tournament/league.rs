@@ -0,0 +1,146 @@
+use serde::Serialize;
+
+/// Points for a win and a draw in the league table; a loss earns nothing.
+const WIN_POINTS: u32 = 2;
+const DRAW_POINTS: u32 = 1;
+
+/// Heads-up pairings so that every player meets every other exactly once, split into
+/// matchdays in which nobody plays twice (the circle method). Indexes into the player list;
+/// with an odd count, one player sits out each matchday.
+pub fn round_robin(players: usize) -> Vec<Vec<(usize, usize)>> {
+    // An odd field gets a phantom seat; whoever draws it rests
+    let seats = players + players % 2;
+    let mut circle: Vec<usize> = (0..seats).collect();
+    let mut matchdays = Vec::new();
+    for _ in 1..seats {
+        let pairings = (0..seats / 2)
+            .map(|i| (circle[i], circle[seats - 1 - i]))
+            .filter(|&(a, b)| a < players && b < players)
+            .collect();
+        matchdays.push(pairings);
+        // The first seat stays put while the rest rotate
+        circle[1..].rotate_right(1);
+    }
+    matchdays
+}
+
+/// How a played fixture ended: each side's final total, lowest wins.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FixtureResult {
+    pub players: [String; 2],
+    pub totals: [i64; 2],
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LeagueStanding {
+    /// 1-based; players level on points and difference share a place
+    pub place: usize,
+    pub user_id: String,
+    pub played: u32,
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32,
+    pub points: u32,
+    /// Opponents' totals minus the player's own, over every game played
+    pub difference: i64,
+}
+
+/// The league table: most points first, then the best difference, then `players` order.
+pub fn standings(players: &[String], results: &[FixtureResult]) -> Vec<LeagueStanding> {
+    let mut table: Vec<LeagueStanding> = players
+        .iter()
+        .map(|user_id| LeagueStanding {
+            place: 0,
+            user_id: user_id.clone(),
+            played: 0,
+            wins: 0,
+            draws: 0,
+            losses: 0,
+            points: 0,
+            difference: 0,
+        })
+        .collect();
+
+    for result in results {
+        for side in 0..2 {
+            let Some(row) = table
+                .iter_mut()
+                .find(|row| row.user_id == result.players[side])
+            else {
+                continue;
+            };
+            let (own, theirs) = (result.totals[side], result.totals[1 - side]);
+            row.played += 1;
+            row.difference += theirs - own;
+            if own < theirs {
+                row.wins += 1;
+                row.points += WIN_POINTS;
+            } else if own == theirs {
+                row.draws += 1;
+                row.points += DRAW_POINTS;
+            } else {
+                row.losses += 1;
+            }
+        }
+    }
+
+    table.sort_by_key(|row| std::cmp::Reverse((row.points, row.difference)));
+    for i in 0..table.len() {
+        let level = |row: &LeagueStanding| (row.points, row.difference);
+        table[i].place = match i.checked_sub(1) {
+            Some(prev) if level(&table[prev]) == level(&table[i]) => table[prev].place,
+            _ => i + 1,
+        };
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn everyone_meets_everyone_once() {
+        for players in 2..=7 {
+            let matchdays = round_robin(players);
+            let mut met = HashSet::new();
+            for pairings in &matchdays {
+                let mut busy = HashSet::new();
+                for &(a, b) in pairings {
+                    assert!(busy.insert(a) && busy.insert(b), "Nobody plays twice a day");
+                    assert!(met.insert((a.min(b), a.max(b))), "No pairing repeats");
+                }
+            }
+            assert_eq!(met.len(), players * (players - 1) / 2);
+            assert_eq!(matchdays.len(), players - 1 + players % 2);
+        }
+    }
+
+    #[test]
+    fn ranks_by_points_then_difference() {
+        let ids: Vec<String> = ["ana", "beto", "carla"].map(String::from).to_vec();
+        let game = |a: &str, b: &str, totals| FixtureResult {
+            players: [a.to_string(), b.to_string()],
+            totals,
+        };
+        let table = standings(
+            &ids,
+            &[
+                game("ana", "beto", [40, 100]),
+                game("beto", "carla", [30, 90]),
+                game("carla", "ana", [50, 50]),
+            ],
+        );
+
+        let rows: Vec<(usize, &str, u32, i64)> = table
+            .iter()
+            .map(|r| (r.place, r.user_id.as_str(), r.points, r.difference))
+            .collect();
+        assert_eq!(
+            rows,
+            [(1, "ana", 3, 60), (2, "beto", 2, 0), (3, "carla", 1, -60)]
+        );
+        assert_eq!((table[0].wins, table[0].draws, table[0].played), (1, 1, 2));
+    }
+}